@@ -0,0 +1,450 @@
+//! Analyzes each file of a directory separately, then merges (averages) the results.
+
+use crate::{
+    analyze_file, brute_force, directory_file_params,
+    fail_under::check_fail_under,
+    file_filter::FileFilter,
+    file_sampling,
+    file_weights::{self, FileWeights},
+    find_directory_files_filtered, is_stdin_schema, load_schema, progress_bar,
+    reference_file::{analyzed_reference_index, find_reference_file},
+    reference_sizes::DirectoryReferenceSizes,
+    result_files::{
+        write_cross_file_results_to_file, write_field_tree_json, write_individual_results_to_files,
+        write_merged_results_to_file, write_sqlite,
+    },
+    validate_ground_truth, validate_region_percentages,
+    zstd_dictionary::train_zstd_dictionary,
+    AnalyzeFileParams, DirectoryCommand,
+};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use std::{
+    io::stdout,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+use struct_compression_analyzer::{
+    analyzer::CompressionOptions,
+    csv,
+    plot::{generate_plots, PlotOptions},
+    results::{
+        analysis_results::AnalysisResults,
+        cross_file_results::{CrossFileResults, CrossFileSample},
+        field_tree::FieldTree,
+        merge_strategy::MergeStrategy,
+        merged_analysis_results::MergedAnalysisResults,
+        reference_comparison::ReferenceComparison,
+        run_history::{RunHistory, RunSummary},
+        saved_results::SavedResults,
+    },
+    schema::Schema,
+    utils::metrics_cache::GroupMetricsCache,
+};
+
+/// Analyzes all files of the directory, then prints and writes the merged results.
+pub fn run(dir_cmd: &DirectoryCommand) -> anyhow::Result<()> {
+    let schema = load_schema(&dir_cmd.schema)?;
+    validate_region_percentages(
+        dir_cmd.offset.is_some(),
+        dir_cmd.length.is_some(),
+        dir_cmd.offset_pct,
+        dir_cmd.length_pct,
+    )?;
+    if !(dir_cmd.plot_scale.is_finite() && dir_cmd.plot_scale > 0.0) {
+        anyhow::bail!("Plot scale must be greater than 0");
+    }
+    if dir_cmd
+        .fail_under
+        .is_some_and(|ratio| !(ratio.is_finite() && ratio > 0.0))
+    {
+        anyhow::bail!("`--fail-under` must be greater than 0");
+    }
+    if dir_cmd.plot_only || dir_cmd.csv_only {
+        anyhow::bail!("`--plot-only` and `--csv-only` require `--from-results`");
+    }
+    // Checked before analyzing, rather than failing once done.
+    if dir_cmd.sqlite.is_some() && !cfg!(feature = "sqlite") {
+        anyhow::bail!("`--sqlite` requires building with the `sqlite` feature");
+    }
+    if dir_cmd.history_length == 0 {
+        anyhow::bail!("History length must be greater than 0");
+    }
+    validate_ground_truth(dir_cmd.ground_truth, dir_cmd.compressor)?;
+    let run_label = dir_cmd
+        .run_label
+        .clone()
+        .unwrap_or_else(|| dir_cmd.path.display().to_string());
+    let file_filter = FileFilter::new(&dir_cmd.include, &dir_cmd.exclude)?;
+    let mut files = find_directory_files_filtered(&dir_cmd.path, &file_filter)?;
+    let total_files = files.len();
+    let subset_note = select_file_subset(&mut files, dir_cmd)?;
+    let reference_file = dir_cmd
+        .reference_file
+        .as_deref()
+        .map(|reference| find_reference_file(&files, reference))
+        .transpose()?;
+    if dir_cmd.weights.is_some() && dir_cmd.merge_strategy != MergeStrategy::Unweighted {
+        anyhow::bail!("`--weights` and `--merge-strategy` can't be used together");
+    }
+    let file_weights = dir_cmd
+        .weights
+        .as_deref()
+        .map(|path| FileWeights::load(path, &dir_cmd.path))
+        .transpose()?;
+    let reference_sizes = dir_cmd
+        .reference_sizes
+        .as_deref()
+        .map(|dir| DirectoryReferenceSizes::new(dir, &dir_cmd.path));
+    match &subset_note {
+        Some(note) => println!(
+            "Analyzing directory: {} ({} of {} files, {})",
+            dir_cmd.path.display(),
+            files.len(),
+            total_files,
+            note
+        ),
+        None => println!(
+            "Analyzing directory: {} ({} files)",
+            dir_cmd.path.display(),
+            files.len()
+        ),
+    }
+
+    // Trained up front, so every file is compressed with the same dictionary.
+    let zstd_dictionary = if dir_cmd.train_zstd_dict {
+        train_zstd_dictionary(&schema, dir_cmd, &files)
+    } else {
+        None
+    };
+    let metrics_cache = dir_cmd
+        .cache_dir
+        .as_deref()
+        .map(GroupMetricsCache::load)
+        .transpose()?
+        .map(Arc::new);
+
+    // Setup progress bar
+    let pb = progress_bar(
+        files.len() as u64,
+        "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files",
+        dir_cmd.quiet,
+    );
+
+    // Counter for completed files
+    let completed_files = AtomicUsize::new(0);
+
+    // Process every file with rayon, collecting individual results
+    let analyze_start_time = Instant::now();
+    let cross_file_max_files = if dir_cmd.cross_file {
+        dir_cmd.cross_file_max_files.unwrap_or(usize::MAX)
+    } else {
+        0
+    };
+    let (mut individual_results, per_file): (Vec<AnalysisResults>, Vec<_>) = files
+        .par_iter()
+        .enumerate()
+        // 1 item at once per thread. Our items are big generally, and take time to process
+        // so 'max work stealing' is preferred.
+        .with_max_len(1)
+        .map(|(index, path)| {
+            let result = analyze_file(&AnalyzeFileParams {
+                retain_cross_file_sample: index < cross_file_max_files,
+                zstd_dictionary: zstd_dictionary.clone(),
+                metrics_cache: metrics_cache.clone(),
+                ..directory_file_params(&schema, dir_cmd, path)
+            })
+            .and_then(|(mut results, sample)| {
+                if let Some(reference_sizes) = &reference_sizes {
+                    reference_sizes.apply(path, &mut results)?;
+                }
+                Ok((results, sample))
+            });
+
+            // Update progress bar
+            let completed = completed_files.fetch_add(1, Ordering::SeqCst) + 1;
+            pb.set_position(completed as u64);
+
+            let weight = file_weights
+                .as_ref()
+                .map_or(file_weights::DEFAULT_FILE_WEIGHT, |w| w.get(path));
+            result.map(|(results, sample)| (results, (path.clone(), (sample, weight))))
+        })
+        .filter_map(|result| match result {
+            Ok(results) => Some(results),
+            Err(e) => {
+                eprintln!("Error processing {}: {}", dir_cmd.path.display(), e);
+                None
+            }
+        })
+        .unzip();
+    // Files which failed to analyze were dropped; keep the paths aligned with the results.
+    let (files, per_file): (Vec<PathBuf>, Vec<_>) = per_file.into_iter().unzip();
+    let reference_index = reference_file
+        .as_deref()
+        .map(|reference| analyzed_reference_index(&files, reference))
+        .transpose()?;
+    let (cross_file_samples, individual_weights): (Vec<Option<CrossFileSample>>, Vec<f64>) =
+        per_file.into_iter().unzip();
+
+    // Finish progress bar
+    pb.finish_with_message(format!(
+        "Analysis completed in {:.2}s",
+        analyze_start_time.elapsed().as_secs_f64()
+    ));
+
+    // Measure redundancy across files before the samples are dropped.
+    let cross_file_results = if dir_cmd.cross_file {
+        let cross_file_start_time = Instant::now();
+        let samples: Vec<CrossFileSample> =
+            cross_file_samples.into_iter().map_while(|s| s).collect();
+        let options = CompressionOptions::default()
+            .with_zstd_compression_level(dir_cmd.zstd_compression_level)
+            .with_zstd_strategy(dir_cmd.zstd_strategy);
+        let results = CrossFileResults::from_samples(&samples, &individual_results, &options);
+        println!(
+            "{}ms... Cross-file analysis complete.",
+            cross_file_start_time.elapsed().as_millis()
+        );
+        Some(results)
+    } else {
+        drop(cross_file_samples);
+        None
+    };
+
+    if let (Some(cache), Some(cache_dir)) = (&metrics_cache, &dir_cmd.cache_dir) {
+        cache.save(cache_dir)?;
+        println!(
+            "Group metrics cache: {} hits, {} misses ({} entries)",
+            cache.hits(),
+            cache.misses(),
+            cache.len()
+        );
+    }
+
+    if dir_cmd.brute_force {
+        brute_force::run(dir_cmd, &mut individual_results)?;
+    }
+
+    // Merge all results
+    println!(
+        "{}ms... Merging {} files.",
+        analyze_start_time.elapsed().as_millis(),
+        individual_results.len()
+    );
+    let merge_start_time = Instant::now();
+    let merged_results = if file_weights.is_some() {
+        MergedAnalysisResults::from_results_weighted(&individual_results, &individual_weights)?
+    } else {
+        MergedAnalysisResults::from_results_with_strategy(
+            &individual_results,
+            dir_cmd.merge_strategy,
+        )?
+    }
+    .with_file_names(
+        files
+            .iter()
+            .map(|f| {
+                f.strip_prefix(&dir_cmd.path)
+                    .unwrap_or(f)
+                    .display()
+                    .to_string()
+            })
+            .collect(),
+    )
+    .with_ground_truth(dir_cmd.ground_truth);
+    if dir_cmd.strict {
+        merged_results.ensure_matching_fields()?;
+    }
+    println!(
+        "{}ms... Aggregated (Merged) Analysis Results:",
+        merge_start_time.elapsed().as_millis()
+    );
+    if let Some(note) = &subset_note {
+        println!(
+            "Note: Results are for a subset of {} of {} files ({}).",
+            files.len(),
+            total_files,
+            note
+        );
+    }
+
+    match reference_index {
+        Some(reference_index) => ReferenceComparison::new(
+            &schema,
+            &merged_results.original_results,
+            &merged_results.file_names,
+            reference_index,
+        )
+        .print(&mut stdout(), dir_cmd.show_extra_stats)?,
+        None => merged_results.print(
+            &mut stdout(),
+            &schema,
+            dir_cmd.format.unwrap_or_default(),
+            !dir_cmd.show_extra_stats,
+            dir_cmd.color,
+        )?,
+    }
+
+    if let Some(cross_file_results) = &cross_file_results {
+        println!();
+        cross_file_results.print(&mut stdout(), &schema)?;
+    }
+
+    if let Some(history_path) = &dir_cmd.history {
+        let mut history = RunHistory::load(history_path)?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let summary = RunSummary::new(&merged_results, &schema, run_label.clone(), timestamp);
+        println!();
+        match history.previous() {
+            Some(previous) => summary
+                .diff(previous)
+                .print(&mut stdout(), dir_cmd.show_extra_stats)?,
+            None => println!("No previous run in history, this run is the new baseline."),
+        }
+        history.push(summary, dir_cmd.history_length);
+        history.save(history_path)?;
+    }
+
+    // Print individual files
+    if dir_cmd.all_files {
+        println!("Individual Files:");
+        for x in 0..individual_results.len() {
+            println!("- {}", files[x].display());
+            individual_results[x].print(
+                &mut stdout(),
+                &schema,
+                dir_cmd.format.unwrap_or_default(),
+                !dir_cmd.show_extra_stats,
+            )?;
+            println!();
+        }
+    }
+
+    // Write reports, output, etc.
+    if let Some(output_dir) = &dir_cmd.output {
+        std::fs::create_dir_all(output_dir)?;
+
+        // Write analysis results to files
+        write_merged_results_to_file(
+            &merged_results,
+            output_dir,
+            &schema,
+            dir_cmd.format.unwrap_or_default(),
+            false,
+            &dir_cmd.output_prefix,
+        )?;
+        write_individual_results_to_files(
+            &individual_results,
+            output_dir,
+            &schema,
+            dir_cmd.format.unwrap_or_default(),
+            false,
+            &files,
+            &dir_cmd.output_prefix,
+        )?;
+        if let Some(cross_file_results) = &cross_file_results {
+            write_cross_file_results_to_file(
+                cross_file_results,
+                output_dir,
+                &schema,
+                &dir_cmd.output_prefix,
+            )?;
+        }
+
+        // Write CSV reports
+        csv::write_all_csvs(
+            &merged_results.original_results,
+            &merged_results,
+            &schema,
+            output_dir,
+            &files,
+            &dir_cmd.output_prefix,
+        )?;
+        generate_plots(
+            &merged_results.original_results,
+            output_dir,
+            &dir_cmd.output_prefix,
+            &plot_options(dir_cmd),
+        )
+        .unwrap();
+        println!("Generated reports in: {}", output_dir.display());
+    }
+
+    if let Some(save_path) = &dir_cmd.save_results {
+        let saved = SavedResults::new(
+            files.clone(),
+            merged_results.file_names.clone(),
+            file_weights
+                .is_some()
+                .then(|| individual_weights.clone())
+                .or_else(|| dir_cmd.merge_strategy.weights(&individual_results)),
+            merged_results.original_results.clone(),
+        );
+        // A schema read from stdin can't be read again, so it must be passed to
+        // `report-from-analysis` instead. Includes are resolved, as the included files
+        // can't be found relative to the saved results.
+        let saved = match is_stdin_schema(&dir_cmd.schema) {
+            true => saved,
+            false => saved.with_schema(Schema::load_yaml_from_file(&dir_cmd.schema)?),
+        };
+        saved.save(save_path)?;
+        println!("Saved results to: {}", save_path.display());
+    }
+
+    if let Some(database_path) = &dir_cmd.sqlite {
+        write_sqlite(&merged_results, &schema, &run_label, database_path)?;
+    }
+
+    if let Some(tree_path) = &dir_cmd.field_tree_json {
+        write_field_tree_json(
+            &FieldTree::new(&schema, &merged_results.per_field),
+            tree_path,
+        )?;
+    }
+
+    if let Some(fail_under) = dir_cmd.fail_under {
+        check_fail_under(&merged_results, fail_under)?;
+    }
+
+    Ok(())
+}
+
+/// The options of the plots generated by a directory analysis.
+fn plot_options(dir_cmd: &DirectoryCommand) -> PlotOptions {
+    PlotOptions::default()
+        .with_scale(dir_cmd.plot_scale)
+        .with_format(dir_cmd.plot_format)
+        .with_y_range(dir_cmd.plot_y_range)
+}
+
+/// Narrows down the files to analyze according to `--max-files` or `--sample-files`.
+///
+/// Returns a description of how the subset was picked, if only a subset of the files is analyzed.
+fn select_file_subset(
+    files: &mut Vec<PathBuf>,
+    dir_cmd: &DirectoryCommand,
+) -> anyhow::Result<Option<String>> {
+    match (dir_cmd.max_files, dir_cmd.sample_files) {
+        (Some(_), Some(_)) => {
+            anyhow::bail!("--max-files and --sample-files can't be used together")
+        }
+        (Some(0), _) | (_, Some(0)) => {
+            anyhow::bail!("The number of files to analyze must be greater than 0")
+        }
+        (Some(max_files), None) => Ok(
+            file_sampling::take_first_files(files, max_files).then(|| "first files".to_string())
+        ),
+        (None, Some(sample_files)) => {
+            let seed = dir_cmd.seed.unwrap_or_else(file_sampling::default_seed);
+            Ok(
+                file_sampling::sample_random_files(files, sample_files, seed)
+                    .then(|| format!("random sample, --seed {}", seed)),
+            )
+        }
+        (None, None) => Ok(None),
+    }
+}
@@ -0,0 +1,100 @@
+//! Analyzes multiple files as one logical stream, as if they were concatenated.
+//!
+//! Unlike `analyze-directory`, which analyzes each file separately and merges (averages)
+//! the results, all records of all files are fed into a single [`SchemaAnalyzer`].
+
+use crate::{
+    file_data::{add_file_data, bytes_per_element, compression_options, read_file_region},
+    find_directory_files_recursive, load_schema, AnalyzeFileParams, FilesCommand,
+};
+use std::{io::stdout, path::PathBuf};
+use struct_compression_analyzer::{
+    analyzer::{SchemaAnalyzer, DEFAULT_MAX_DISTINCT_VALUES, DEFAULT_MAX_VALUE_TRANSITIONS},
+    results::PrintFormat,
+};
+
+/// Analyzes all files of the command as one stream, then prints the results.
+pub fn run(cmd: &FilesCommand) -> anyhow::Result<()> {
+    let schema = load_schema(&cmd.schema)?;
+    let files = expand_paths(&cmd.paths)?;
+    let format = cmd.format.unwrap_or_default();
+
+    let params_for = |path| AnalyzeFileParams {
+        schema: &schema,
+        path,
+        bytes_per_element: bytes_per_element(&schema),
+        offset: None,
+        length: None,
+        offset_pct: None,
+        length_pct: None,
+        zstd_compression_level: cmd.zstd_compression_level,
+        zstd_calibration_level: None,
+        zstd_strategy: cmd.zstd_strategy,
+        compressor: cmd.compressor,
+        retain_cross_file_sample: false,
+        include_estimate_breakdown: cmd.show_estimate_terms,
+        track_value_transitions: false,
+        max_value_transitions: DEFAULT_MAX_VALUE_TRANSITIONS,
+        max_distinct_values: DEFAULT_MAX_DISTINCT_VALUES,
+        strict: cmd.strict,
+        byte_transpose: cmd.byte_transpose,
+        entropy_coded_size: cmd.entropy_coded_size,
+        order1_entropy: cmd.show_extra_stats,
+        zstd_timing: cmd.show_extra_stats,
+        run_stats: cmd.show_extra_stats,
+        entropy_window: cmd.entropy_window,
+        zstd_dictionary: None,
+        metrics_cache: None,
+    };
+
+    let mut analyzer = SchemaAnalyzer::new(&schema, compression_options(&params_for(&files[0])));
+    let mut total_bytes = 0;
+    for path in &files {
+        // The conditional offsets of the schema are evaluated for each file.
+        let params = params_for(path);
+        let data = read_file_region(&params)?;
+        add_file_data(&mut analyzer, &data, &params)?;
+        total_bytes += data.len();
+    }
+
+    let results = analyzer.generate_results()?;
+    if !matches!(format, PrintFormat::Json) {
+        println!(
+            "Analysis Results ({} files as one stream, {} bytes):",
+            files.len(),
+            total_bytes
+        );
+    }
+    results.print(&mut stdout(), &schema, format, !cmd.show_extra_stats)?;
+    Ok(())
+}
+
+/// Expands the paths of the command into the files to analyze, in order.
+/// Directories are replaced by the files within them, and patterns by the files they match,
+/// both in sorted order.
+fn expand_paths(paths: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        let pattern = path.to_string_lossy();
+        if pattern.contains(['*', '?', '[']) {
+            let mut matches = glob::glob(&pattern)?.collect::<Result<Vec<_>, _>>()?;
+            matches.retain(|path| path.is_file());
+            if matches.is_empty() {
+                anyhow::bail!("Pattern '{}' matches no files", pattern);
+            }
+            matches.sort();
+            files.extend(matches);
+        } else if path.is_dir() {
+            let mut dir_files = find_directory_files_recursive(path)?;
+            dir_files.sort();
+            files.extend(dir_files);
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    if files.is_empty() {
+        anyhow::bail!("No files to analyze");
+    }
+    Ok(files)
+}
@@ -0,0 +1,106 @@
+//! Brute forces the estimator coefficients of the comparisons of a directory's results.
+
+use crate::{progress_bar, DirectoryCommand};
+use std::{
+    fs::File,
+    io::stdout,
+    path::Path,
+    time::{Duration, Instant},
+};
+use struct_compression_analyzer::{
+    brute_force::{
+        brute_force_custom::CustomComparisonOptimizationResult,
+        brute_force_split::SplitComparisonOptimizationResult, optimize_and_apply_coefficients,
+        print_all_optimization_results, progress::ComparisonKind, BruteForceConfig,
+    },
+    csv,
+    results::analysis_results::AnalysisResults,
+};
+
+/// Finds the LZ match and entropy multipliers which best predict the sizes of the comparisons,
+/// applies them to the `individual_results`, then prints them and writes them to the output.
+pub fn run(
+    dir_cmd: &DirectoryCommand,
+    individual_results: &mut [AnalysisResults],
+) -> anyhow::Result<()> {
+    println!("\nRunning LZ parameter optimization on merged results...");
+    let brute_force_start_time = Instant::now();
+    let pb = progress_bar(
+        100,
+        "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}% {msg}",
+        dir_cmd.quiet,
+    );
+    let status = pb.clone();
+    let config = BruteForceConfig {
+        max_duration: dir_cmd
+            .brute_force_max_seconds
+            .map(Duration::try_from_secs_f64)
+            .transpose()?,
+        error_metric: dir_cmd.brute_force_error_metric,
+        strategy: dir_cmd.brute_force_strategy,
+        use_reference_size: dir_cmd.brute_force_reference,
+        ..BruteForceConfig::default()
+    }
+    .with_progress(move |progress| {
+        let kind = match progress.kind {
+            ComparisonKind::Split => "split",
+            ComparisonKind::Custom => "custom",
+        };
+        // Errors of f32::MAX and above are the misprediction penalty.
+        let best_error = if progress.best_error >= f32::MAX as f64 {
+            "penalized".to_string()
+        } else {
+            format!("{:.4}", progress.best_error)
+        };
+        status.set_position((progress.fraction_done * 100.0) as u64);
+        status.set_message(format!(
+            "{} {}/{} '{}', {} (best error: {})",
+            kind,
+            progress.comparison_index + 1,
+            progress.comparison_count,
+            progress.comparison_name,
+            progress.group_name,
+            best_error
+        ));
+    });
+    let (split_results, custom_results) =
+        optimize_and_apply_coefficients(individual_results, Some(&config));
+    pb.finish_and_clear();
+    println!(
+        "{}ms... Brute force optimization complete.",
+        brute_force_start_time.elapsed().as_millis()
+    );
+
+    print_all_optimization_results(&mut stdout(), &split_results, &custom_results)?;
+
+    // Save optimization results to file if output directory is specified
+    if let Some(output_dir) = &dir_cmd.output {
+        std::fs::create_dir_all(output_dir)?;
+        write_optimization_results_to_file(
+            &split_results,
+            &custom_results,
+            output_dir,
+            &dir_cmd.output_prefix,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Write optimization results to a text file and a CSV file
+fn write_optimization_results_to_file(
+    split_results: &[(String, SplitComparisonOptimizationResult)],
+    custom_results: &[(String, CustomComparisonOptimizationResult)],
+    output_dir: &Path,
+    prefix: &str,
+) -> std::io::Result<()> {
+    let output_path = output_dir.join(format!("{prefix}brute-force-results.txt"));
+    let mut file = File::create(output_path)?;
+    print_all_optimization_results(&mut file, split_results, custom_results)?;
+    csv::write_optimization_csv(
+        split_results,
+        custom_results,
+        &output_dir.join(format!("{prefix}brute-force-results.csv")),
+    )?;
+    Ok(())
+}
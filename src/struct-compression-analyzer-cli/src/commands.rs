@@ -0,0 +1,315 @@
+//! The command line arguments of each command.
+
+use crate::DirectoryCommand;
+use argh::FromArgs;
+use std::path::PathBuf;
+use struct_compression_analyzer::{
+    comparison::GroundTruth,
+    plot::{PlotFormat, YAxisRange},
+    results::{color::ColorMode, PrintFormat},
+    utils::{analyze_utils::ZstdStrategy, compressor::Compressor},
+};
+
+#[derive(Debug, FromArgs)]
+/// CLI for analyzing struct compression
+pub struct Args {
+    #[argh(subcommand)]
+    /// the command to execute.
+    pub command: Command,
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand)]
+#[allow(clippy::large_enum_variant)]
+pub enum Command {
+    File(FileCommand),
+    Directory(DirectoryCommand),
+    Scaffold(ScaffoldCommand),
+    EstimatorReport(EstimatorReportCommand),
+    Files(FilesCommand),
+    ValidateSchema(ValidateSchemaCommand),
+    ReportFromAnalysis(ReportFromAnalysisCommand),
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "validate-schema")]
+/// Check a schema for mistakes (e.g. comparisons referencing misspelled fields) without analyzing any files
+pub struct ValidateSchemaCommand {
+    #[argh(positional)]
+    /// path to the schema file, or `-` to read it from stdin (after `--`)
+    pub schema: PathBuf,
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "report-from-analysis")]
+/// Regenerate the CSV and plot reports from results saved with `analyze-directory --save-results`
+pub struct ReportFromAnalysisCommand {
+    #[argh(positional)]
+    /// path to the saved results
+    pub results: PathBuf,
+
+    #[argh(positional)]
+    /// directory to write the reports to
+    pub output: PathBuf,
+
+    /// path to the schema file, or `-` to read it from stdin; by default, the schema saved
+    /// with the results is used
+    #[argh(option, long = "schema")]
+    pub schema: Option<PathBuf>,
+
+    /// prefix for the names of all files and directories written to the output directory
+    #[argh(option, long = "output-prefix", default = "String::new()")]
+    pub output_prefix: String,
+
+    /// only regenerate the plots
+    #[argh(switch, long = "plot-only")]
+    pub plot_only: bool,
+
+    /// only regenerate the CSVs
+    #[argh(switch, long = "csv-only")]
+    pub csv_only: bool,
+
+    /// scale of the generated plots (default: 1.0)
+    #[argh(option, long = "plot-scale", default = "1.0")]
+    pub plot_scale: f64,
+
+    /// image format of the generated plots: png or svg (default: png)
+    #[argh(option, long = "plot-format", default = "PlotFormat::Png")]
+    pub plot_format: PlotFormat,
+
+    /// range of compression ratios on the y axis of the plots: 'auto' or 'MIN:MAX'
+    /// (default: 0.6:1.2)
+    #[argh(option, long = "plot-y-range", default = "YAxisRange::default()")]
+    pub plot_y_range: YAxisRange,
+
+    /// compressor the size estimator is checked against: 'zstd', 'brotli' or 'deflate'
+    /// (default: 'zstd')
+    #[argh(option, long = "ground-truth", default = "GroundTruth::Zstd")]
+    pub ground_truth: GroundTruth,
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "scaffold")]
+/// Generate a starter schema from a sample file
+pub struct ScaffoldCommand {
+    #[argh(positional)]
+    /// path to a sample file containing the records
+    pub path: PathBuf,
+
+    /// size of a single record (struct) in bytes
+    #[argh(option, short = 'r', long = "record-size")]
+    pub record_size: u64,
+
+    /// file to write the schema to. If not specified, the schema is printed to stdout.
+    #[argh(option, short = 'o')]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "estimator-report")]
+/// Grade how well the size estimator predicts the compressed sizes of a directory of files
+pub struct EstimatorReportCommand {
+    #[argh(positional)]
+    /// path to the schema file, or `-` to read it from stdin (after `--`)
+    pub schema: PathBuf,
+
+    #[argh(positional)]
+    /// path to the directory to analyze
+    pub path: PathBuf,
+
+    /// also write the report card as JSON to this path
+    #[argh(option, long = "json")]
+    pub json: Option<PathBuf>,
+
+    /// zstd compression level (default: 16)
+    #[argh(option, short = 'z', default = "16")]
+    pub zstd_compression_level: i32,
+
+    /// zstd strategy, overriding the one implied by the level (default: 'default')
+    #[argh(option, long = "zstd-strategy", default = "ZstdStrategy::Default")]
+    pub zstd_strategy: ZstdStrategy,
+
+    /// compressor measuring the actual sizes besides zstd: 'zstd', 'brotli',
+    /// 'brotli:QUALITY:WINDOW', 'deflate' or 'deflate:LEVEL' (default: 'zstd')
+    #[argh(option, long = "compressor", default = "Compressor::Zstd")]
+    pub compressor: Compressor,
+
+    /// compressor the size estimator is checked against: 'zstd', 'brotli' or 'deflate', which
+    /// require the matching `--compressor` (default: 'zstd')
+    #[argh(option, long = "ground-truth", default = "GroundTruth::Zstd")]
+    pub ground_truth: GroundTruth,
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "analyze-files")]
+/// Analyze multiple files as one logical stream, as if they were concatenated
+pub struct FilesCommand {
+    #[argh(positional)]
+    /// path to the schema file, or `-` to read it from stdin (after `--`)
+    pub schema: PathBuf,
+
+    #[argh(positional)]
+    /// files to analyze, in order. Directories include all files within them, and patterns
+    /// with '*', '?' or '[' are expanded (e.g. 'textures/*.bin'), both in sorted order.
+    pub paths: Vec<PathBuf>,
+
+    /// output format ('detailed', 'concise', 'json')
+    #[argh(option, short = 'f')]
+    pub format: Option<PrintFormat>,
+
+    /// show extra stats, including the order-1 (conditional) entropy of each field and group,
+    /// the runs of identical values of each field, and the time taken to compress each group
+    /// with zstd, which take longer to calculate
+    #[argh(switch, long = "show-extra-stats")]
+    pub show_extra_stats: bool,
+
+    /// show the intermediate terms (LZ savings, entropy) of the default size estimator per group
+    #[argh(switch, long = "show-estimate-terms")]
+    pub show_estimate_terms: bool,
+
+    /// treat schema problems which are otherwise printed as warnings as errors,
+    /// e.g. a split comparison group which resolves to no fields
+    #[argh(switch, long = "strict")]
+    pub strict: bool,
+
+    /// add a built-in custom comparison of the records with their bytes transposed
+    /// (all 1st bytes, then all 2nd bytes, etc.)
+    #[argh(switch, long = "byte-transpose")]
+    pub byte_transpose: bool,
+
+    /// also code each field with an order-0 range coder and report the size, next to the
+    /// theoretical minimum and zstd size (slower)
+    #[argh(switch, long = "entropy-coded-size")]
+    pub entropy_coded_size: bool,
+
+    /// also record the entropy of each field per this many records, to see how it drifts
+    /// across a file (available in the CSVs and JSON output)
+    #[argh(option, long = "entropy-window")]
+    pub entropy_window: Option<usize>,
+
+    /// zstd compression level (default: 16)
+    #[argh(option, short = 'z', default = "16")]
+    pub zstd_compression_level: i32,
+
+    /// zstd strategy, overriding the one implied by the level: 'default', 'fast', 'dfast',
+    /// 'greedy', 'lazy', 'lazy2', 'btlazy2', 'btopt', 'btultra', 'btultra2' (default: 'default')
+    #[argh(option, long = "zstd-strategy", default = "ZstdStrategy::Default")]
+    pub zstd_strategy: ZstdStrategy,
+
+    /// compressor measuring the actual sizes besides zstd: 'zstd', 'brotli',
+    /// 'brotli:QUALITY:WINDOW', 'deflate' or 'deflate:LEVEL' (default: 'zstd')
+    #[argh(option, long = "compressor", default = "Compressor::Zstd")]
+    pub compressor: Compressor,
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "analyze-file")]
+/// Analyze a single file
+pub struct FileCommand {
+    #[argh(positional)]
+    /// path to the schema file, or `-` to read it from stdin (after `--`)
+    pub schema: PathBuf,
+
+    #[argh(positional)]
+    /// path to the file to analyze
+    pub path: PathBuf,
+
+    /// offset to start analyzing from. Can be repeated to analyze multiple regions of the file,
+    /// producing one result per region.
+    #[argh(option, short = 'o')]
+    pub offset: Vec<u64>,
+
+    /// length of the data to analyze. If not specified, the entire rest of the file is analyzed.
+    /// When analyzing multiple regions, specify once per `--offset`, in the same order.
+    #[argh(option, short = 'l')]
+    pub length: Vec<u64>,
+
+    /// offset to start analyzing from, as a percentage of the file size.
+    /// Rounded down to a whole number of structs (elements). Can't be used with `--offset`.
+    #[argh(option, long = "offset-pct")]
+    pub offset_pct: Option<f64>,
+
+    /// length of the data to analyze, as a percentage of the file size.
+    /// Rounded down to a whole number of structs (elements). Can't be used with `--length`.
+    #[argh(option, long = "length-pct")]
+    pub length_pct: Option<f64>,
+
+    /// analyze the region of every `conditional_offsets` entry of the schema which matches the
+    /// file, rather than only the first, producing one result per region.
+    /// Can't be used with `--offset`, `--length` or their percentages.
+    #[argh(switch, long = "all-offsets")]
+    pub all_offsets: bool,
+
+    /// also print the merged results of all regions
+    #[argh(switch, short = 'm')]
+    pub merge_regions: bool,
+
+    /// write the per-field metrics as JSON to this path, nested following the schema's groups.
+    /// With multiple regions, the metrics of all regions are merged.
+    #[argh(option, long = "field-tree-json")]
+    pub field_tree_json: Option<PathBuf>,
+
+    /// YAML (or JSON) file of the sizes a real codec achieves on fields, groups and comparison
+    /// groups, which the zstd and estimated sizes are then compared against.
+    /// Applied to every region.
+    #[argh(option, long = "reference-sizes")]
+    pub reference_sizes: Option<PathBuf>,
+
+    /// output format ('detailed', 'concise', 'json')
+    #[argh(option, short = 'f')]
+    pub format: Option<PrintFormat>,
+
+    /// show extra stats, including the order-1 (conditional) entropy of each field and group,
+    /// the runs of identical values of each field, and the time taken to compress each group
+    /// with zstd, which take longer to calculate
+    #[argh(switch, long = "show-extra-stats")]
+    pub show_extra_stats: bool,
+
+    /// color the zstd ratios of the merged comparisons printed to the terminal, green if the
+    /// group is smaller and red if larger: 'auto' (if stdout is a terminal), 'always' or 'never'
+    /// (default: 'auto'). Files are never colored.
+    #[argh(option, long = "color", default = "ColorMode::Auto")]
+    pub color: ColorMode,
+
+    /// show the intermediate terms (LZ savings, entropy) of the default size estimator per group
+    #[argh(switch, long = "show-estimate-terms")]
+    pub show_estimate_terms: bool,
+
+    /// treat schema problems which are otherwise printed as warnings as errors,
+    /// e.g. a split comparison group which resolves to no fields
+    #[argh(switch, long = "strict")]
+    pub strict: bool,
+
+    /// add a built-in custom comparison of the records with their bytes transposed
+    /// (all 1st bytes, then all 2nd bytes, etc.)
+    #[argh(switch, long = "byte-transpose")]
+    pub byte_transpose: bool,
+
+    /// also code each field with an order-0 range coder and report the size, next to the
+    /// theoretical minimum and zstd size (slower)
+    #[argh(switch, long = "entropy-coded-size")]
+    pub entropy_coded_size: bool,
+
+    /// also record the entropy of each field per this many records, to see how it drifts
+    /// across a file (available in the CSVs and JSON output)
+    #[argh(option, long = "entropy-window")]
+    pub entropy_window: Option<usize>,
+
+    /// zstd compression level (default: 3)
+    #[argh(option, short = 'z', default = "3")]
+    pub zstd_compression_level: i32,
+
+    /// zstd strategy, overriding the one implied by the level: 'default', 'fast', 'dfast',
+    /// 'greedy', 'lazy', 'lazy2', 'btlazy2', 'btopt', 'btultra', 'btultra2' (default: 'default')
+    #[argh(option, long = "zstd-strategy", default = "ZstdStrategy::Default")]
+    pub zstd_strategy: ZstdStrategy,
+
+    /// compressor measuring the actual sizes besides zstd: 'zstd', 'brotli',
+    /// 'brotli:QUALITY:WINDOW', 'deflate' or 'deflate:LEVEL' (default: 'zstd')
+    #[argh(option, long = "compressor", default = "Compressor::Zstd")]
+    pub compressor: Compressor,
+
+    /// number of threads to analyze with; 0 uses all cores (default: 0)
+    #[argh(option, long = "threads", default = "0")]
+    pub threads: usize,
+}
@@ -0,0 +1,314 @@
+//! The command line arguments of `analyze-directory`.
+
+use argh::FromArgs;
+use std::path::PathBuf;
+use struct_compression_analyzer::{
+    analyzer::{DEFAULT_MAX_DISTINCT_VALUES, DEFAULT_MAX_VALUE_TRANSITIONS},
+    brute_force::{ErrorMetric, OptimizationStrategy},
+    comparison::GroundTruth,
+    plot::{PlotFormat, YAxisRange},
+    results::{
+        color::ColorMode, merge_strategy::MergeStrategy, run_history::DEFAULT_HISTORY_LENGTH,
+        PrintFormat,
+    },
+    utils::{analyze_utils::ZstdStrategy, compressor::Compressor},
+};
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "analyze-directory")]
+/// Analyze all files in a directory
+pub struct DirectoryCommand {
+    #[argh(positional)]
+    /// path to the schema file, or `-` to read it from stdin (after `--`)
+    pub schema: PathBuf,
+
+    #[argh(positional)]
+    /// path to the directory containing files to analyze
+    pub path: PathBuf,
+
+    /// offset to start analyzing from
+    #[argh(option, short = 'o')]
+    pub offset: Option<u64>,
+
+    /// length of the data to analyze. If not specified, the entire rest of the file is analyzed.
+    #[argh(option, short = 'l')]
+    pub length: Option<u64>,
+
+    /// offset to start analyzing from, as a percentage of each file's size.
+    /// Rounded down to a whole number of structs (elements). Can't be used with `--offset`.
+    #[argh(option, long = "offset-pct")]
+    pub offset_pct: Option<f64>,
+
+    /// length of the data to analyze, as a percentage of each file's size.
+    /// Rounded down to a whole number of structs (elements). Can't be used with `--length`.
+    #[argh(option, long = "length-pct")]
+    pub length_pct: Option<f64>,
+
+    /// output format ('detailed', 'concise', 'json')
+    #[argh(option, short = 'f')]
+    pub format: Option<PrintFormat>,
+
+    /// print info for all files
+    #[argh(switch, short = 'a')]
+    pub all_files: bool,
+
+    /// output directory for CSV and plot reports
+    #[argh(option)]
+    pub output: Option<PathBuf>,
+
+    /// prefix for the names of all files and directories written to the output directory
+    #[argh(option, long = "output-prefix", default = "String::new()")]
+    pub output_prefix: String,
+
+    /// save the analysis results of each file to this path, so the reports can be
+    /// regenerated later with `--from-results` without analyzing the files again
+    #[argh(option, long = "save-results")]
+    pub save_results: Option<PathBuf>,
+
+    /// regenerate the CSV and plot reports in `--output` from results saved with
+    /// `--save-results`, instead of analyzing the files in the directory
+    #[argh(option, long = "from-results")]
+    pub from_results: Option<PathBuf>,
+
+    /// with `--from-results`, only regenerate the plots
+    #[argh(switch, long = "plot-only")]
+    pub plot_only: bool,
+
+    /// with `--from-results`, only regenerate the CSVs
+    #[argh(switch, long = "csv-only")]
+    pub csv_only: bool,
+
+    /// write the merged per-field metrics as JSON to this path, nested following the schema's groups
+    #[argh(option, long = "field-tree-json")]
+    pub field_tree_json: Option<PathBuf>,
+
+    /// write the merged results to the SQLite database at this path (created if needed),
+    /// to query results across runs. Requires the `sqlite` feature.
+    #[argh(option, long = "sqlite")]
+    pub sqlite: Option<PathBuf>,
+
+    /// label of this run in the SQLite database and the run history; rows with the same label
+    /// are replaced (default: the analyzed directory)
+    #[argh(option, long = "run-label")]
+    pub run_label: Option<String>,
+
+    /// compare the merged results against the previous run stored in this history file,
+    /// then append this run to it. The file is created if it doesn't exist.
+    #[argh(option, long = "history")]
+    pub history: Option<PathBuf>,
+
+    /// maximum number of runs kept in the `--history` file; the oldest runs are
+    /// dropped first (default: 10)
+    #[argh(option, long = "history-length", default = "DEFAULT_HISTORY_LENGTH")]
+    pub history_length: usize,
+
+    /// scale of the generated plots; multiplies the image size, fonts and lines,
+    /// e.g. 2.0 for high DPI output (default: 1.0)
+    #[argh(option, long = "plot-scale", default = "1.0")]
+    pub plot_scale: f64,
+
+    /// image format of the generated plots: png or svg (default: png)
+    #[argh(option, long = "plot-format", default = "PlotFormat::Png")]
+    pub plot_format: PlotFormat,
+
+    /// range of compression ratios on the y axis of the plots: 'auto' to fit each plot
+    /// to its data, or 'MIN:MAX' (default: 0.6:1.2)
+    #[argh(option, long = "plot-y-range", default = "YAxisRange::default()")]
+    pub plot_y_range: YAxisRange,
+
+    /// show extra stats, including the order-1 (conditional) entropy of each field and group,
+    /// the runs of identical values of each field, and the time taken to compress each group
+    /// with zstd, which take longer to calculate
+    #[argh(switch, long = "show-extra-stats")]
+    pub show_extra_stats: bool,
+
+    /// color the zstd ratios of the merged comparisons printed to the terminal, green if the
+    /// group is smaller and red if larger: 'auto' (if stdout is a terminal), 'always' or 'never'
+    /// (default: 'auto'). Files are never colored.
+    #[argh(option, long = "color", default = "ColorMode::Auto")]
+    pub color: ColorMode,
+
+    /// show the intermediate terms (LZ savings, entropy) of the default size estimator per group
+    #[argh(switch, long = "show-estimate-terms")]
+    pub show_estimate_terms: bool,
+
+    /// treat schema problems which are otherwise printed as warnings as errors,
+    /// e.g. a split comparison group which resolves to no fields, or fields which
+    /// are missing from some files
+    #[argh(switch, long = "strict")]
+    pub strict: bool,
+
+    /// exit with an error (for gating CI) unless a split or custom comparison has a merged
+    /// zstd ratio to its baseline below this, e.g. 0.95 for at least 5% smaller. Comparisons
+    /// which don't are listed. Custom comparisons use their best group.
+    #[argh(option, long = "fail-under")]
+    pub fail_under: Option<f64>,
+
+    /// add a built-in custom comparison of the records with their bytes transposed
+    /// (all 1st bytes, then all 2nd bytes, etc.)
+    #[argh(switch, long = "byte-transpose")]
+    pub byte_transpose: bool,
+
+    /// also code each field with an order-0 range coder and report the size, next to the
+    /// theoretical minimum and zstd size (slower)
+    #[argh(switch, long = "entropy-coded-size")]
+    pub entropy_coded_size: bool,
+
+    /// also record the entropy of each field per this many records, to see how it drifts
+    /// across a file (available in the CSVs and JSON output)
+    #[argh(option, long = "entropy-window")]
+    pub entropy_window: Option<usize>,
+
+    /// zstd compression level (default: 16)
+    #[argh(option, short = 'z', default = "16")]
+    pub zstd_compression_level: i32,
+
+    /// zstd strategy, overriding the one implied by the level: 'default', 'fast', 'dfast',
+    /// 'greedy', 'lazy', 'lazy2', 'btlazy2', 'btopt', 'btultra', 'btultra2' (default: 'default')
+    #[argh(option, long = "zstd-strategy", default = "ZstdStrategy::Default")]
+    pub zstd_strategy: ZstdStrategy,
+
+    /// zstd compression level of the sizes the brute forcer fits the estimates to, if it
+    /// should differ from the level of the reported sizes (default: same as `-z`)
+    #[argh(option, long = "zstd-calibration-level")]
+    pub zstd_calibration_level: Option<i32>,
+
+    /// compressor measuring the actual sizes besides zstd: 'zstd', 'brotli',
+    /// 'brotli:QUALITY:WINDOW', 'deflate' or 'deflate:LEVEL' (default: 'zstd')
+    #[argh(option, long = "compressor", default = "Compressor::Zstd")]
+    pub compressor: Compressor,
+
+    /// compressor the size estimator is checked against when computing the agreement and
+    /// false positive percentages of the merged results: 'zstd', 'brotli' or 'deflate', which
+    /// require the matching `--compressor` (default: 'zstd')
+    #[argh(option, long = "ground-truth", default = "GroundTruth::Zstd")]
+    pub ground_truth: GroundTruth,
+
+    /// enable brute forcing of LZ match and entropy multiplier parameters
+    #[argh(switch, long = "brute-force-lz-params")]
+    pub brute_force: bool,
+
+    /// maximum time in seconds to brute force the parameters of each group; once exceeded,
+    /// the best parameters found so far are used (default: unlimited)
+    #[argh(option, long = "brute-force-max-seconds")]
+    pub brute_force_max_seconds: Option<f64>,
+
+    /// when brute forcing, fit the estimates to the reference sizes (see `--reference-sizes`)
+    /// instead of the zstd sizes, for groups which have one
+    #[argh(switch, long = "brute-force-reference")]
+    pub brute_force_reference: bool,
+
+    /// how the error of each file is scored when brute forcing: 'absolute', 'relative',
+    /// 'squaredrelative' or 'logratio' (default: 'absolute')
+    #[argh(
+        option,
+        long = "brute-force-error-metric",
+        default = "ErrorMetric::Absolute"
+    )]
+    pub brute_force_error_metric: ErrorMetric,
+
+    /// how the parameters are searched when brute forcing: 'exhaustive' (every pair on the grid)
+    /// or 'coordinatedescent' (much faster, may miss the best pair) (default: 'exhaustive')
+    #[argh(
+        option,
+        long = "brute-force-strategy",
+        default = "OptimizationStrategy::Exhaustive"
+    )]
+    pub brute_force_strategy: OptimizationStrategy,
+
+    /// measure LZ matches and zstd size on the concatenation of all files, to find redundancy
+    /// shared between files. This keeps the data of all files in memory.
+    #[argh(switch, long = "cross-file")]
+    pub cross_file: bool,
+
+    /// maximum number of files to concatenate for cross-file analysis (default: all)
+    #[argh(option, long = "cross-file-max-files")]
+    pub cross_file_max_files: Option<usize>,
+
+    /// train a zstd dictionary on samples of the groups of (up to 64 of) the files, then also
+    /// report the zstd size of each group compressed with it. Skipped if there are too few samples.
+    #[argh(switch, long = "train-zstd-dict")]
+    pub train_zstd_dict: bool,
+
+    /// directory of a cache of the metrics of the groups of each file, which is reused by later
+    /// runs to skip compressing unchanged groups, e.g. when tuning the estimator
+    #[argh(option, long = "cache-dir")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// CSV file of `path,weight` rows; the merged results use weighted averages,
+    /// with unlisted files having a weight of 1.0
+    #[argh(option, long = "weights")]
+    pub weights: Option<PathBuf>,
+
+    /// directory mirroring the analyzed one with the sizes a real codec achieves on each file,
+    /// which the zstd and estimated sizes are then compared against; those of 'a/b.bin' are read
+    /// from 'a/b.bin.yaml' in it, in the format of `analyze-file --reference-sizes`
+    #[argh(option, long = "reference-sizes")]
+    pub reference_sizes: Option<PathBuf>,
+
+    /// how the merged results weight each file: 'unweighted' (every file counts equally) or
+    /// 'weightedbyoriginalsize' (by size in bytes). Can't be used with `--weights`
+    /// (default: 'unweighted')
+    #[argh(option, long = "merge-strategy", default = "MergeStrategy::Unweighted")]
+    pub merge_strategy: MergeStrategy,
+
+    /// print every file's results as differences from this file (one of the analyzed files,
+    /// e.g. a known-good reference build) instead of printing the merged results
+    #[argh(option, long = "reference-file")]
+    pub reference_file: Option<PathBuf>,
+
+    /// only analyze files matching this glob pattern; matched against the file name, or the path
+    /// relative to the directory if it contains a `/`, e.g. '*.dds' or 'textures/**'.
+    /// Can be repeated to include files matching any of the patterns.
+    #[argh(option, long = "include")]
+    pub include: Vec<String>,
+
+    /// skip files matching this glob pattern, even if they match an `--include` pattern;
+    /// matched like `--include`. Can be repeated.
+    #[argh(option, long = "exclude")]
+    pub exclude: Vec<String>,
+
+    /// analyze only the first N files (in path order), for a quick preview of large directories
+    #[argh(option, long = "max-files")]
+    pub max_files: Option<usize>,
+
+    /// analyze only a random selection of N files, for a quick preview of large directories
+    #[argh(option, long = "sample-files")]
+    pub sample_files: Option<usize>,
+
+    /// seed used to pick the files for `--sample-files` (default: random)
+    #[argh(option, long = "seed")]
+    pub seed: Option<u64>,
+
+    /// count transitions between consecutive values of each field (up to 16 bits),
+    /// written to the CSV reports; for designing context models
+    #[argh(switch, long = "value-transitions")]
+    pub value_transitions: bool,
+
+    /// maximum number of distinct value transitions tracked per field; fields with more
+    /// are not reported (default: 65536)
+    #[argh(
+        option,
+        long = "max-value-transitions",
+        default = "DEFAULT_MAX_VALUE_TRANSITIONS"
+    )]
+    pub max_value_transitions: usize,
+
+    /// maximum number of distinct values counted per field; values first seen after
+    /// reaching it are not counted, and the value stats are marked as truncated (default: 65536)
+    #[argh(
+        option,
+        long = "max-distinct-values",
+        default = "DEFAULT_MAX_DISTINCT_VALUES"
+    )]
+    pub max_distinct_values: usize,
+
+    /// number of threads to analyze and brute force with; 0 uses all cores (default: 0)
+    #[argh(option, long = "threads", default = "0")]
+    pub threads: usize,
+
+    /// hide the progress bars of the analysis and brute forcing. They are also hidden
+    /// when stdout isn't a terminal, e.g. when redirected to a file.
+    #[argh(switch, short = 'q', long = "quiet")]
+    pub quiet: bool,
+}
@@ -0,0 +1,78 @@
+//! Grades the accuracy of the size estimator over a directory of files.
+
+use crate::{
+    analyze_file, file_data::bytes_per_element, find_directory_files_recursive, load_schema,
+    validate_ground_truth, AnalyzeFileParams, EstimatorReportCommand,
+};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use std::{fs::File, io::stdout};
+use struct_compression_analyzer::{
+    analyzer::{DEFAULT_MAX_DISTINCT_VALUES, DEFAULT_MAX_VALUE_TRANSITIONS},
+    results::{
+        analysis_results::AnalysisResults, estimator_report::EstimatorReportCard,
+        merged_analysis_results::MergedAnalysisResults,
+    },
+};
+
+/// Analyzes every file in the directory, then prints the report card of the merged results
+/// and optionally writes it as JSON.
+pub fn run(cmd: &EstimatorReportCommand) -> anyhow::Result<()> {
+    validate_ground_truth(cmd.ground_truth, cmd.compressor)?;
+    let schema = load_schema(&cmd.schema)?;
+    let files = find_directory_files_recursive(&cmd.path)?;
+    println!(
+        "Analyzing directory: {} ({} files)",
+        cmd.path.display(),
+        files.len()
+    );
+
+    let results: Vec<AnalysisResults> = files
+        .par_iter()
+        .filter_map(|path| {
+            let result = analyze_file(&AnalyzeFileParams {
+                schema: &schema,
+                path,
+                bytes_per_element: bytes_per_element(&schema),
+                offset: None,
+                length: None,
+                offset_pct: None,
+                length_pct: None,
+                zstd_compression_level: cmd.zstd_compression_level,
+                zstd_calibration_level: None,
+                zstd_strategy: cmd.zstd_strategy,
+                compressor: cmd.compressor,
+                retain_cross_file_sample: false,
+                include_estimate_breakdown: false,
+                track_value_transitions: false,
+                max_value_transitions: DEFAULT_MAX_VALUE_TRANSITIONS,
+                max_distinct_values: DEFAULT_MAX_DISTINCT_VALUES,
+                strict: false,
+                byte_transpose: false,
+                entropy_coded_size: false,
+                order1_entropy: false,
+                zstd_timing: false,
+                run_stats: false,
+                entropy_window: None,
+                zstd_dictionary: None,
+                metrics_cache: None,
+            });
+            match result {
+                Ok((results, _)) => Some(results),
+                Err(e) => {
+                    eprintln!("Error processing {}: {}", path.display(), e);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    let merged = MergedAnalysisResults::from_results(&results)?.with_ground_truth(cmd.ground_truth);
+    let card = EstimatorReportCard::new(&merged);
+    card.print(&mut stdout())?;
+
+    if let Some(json_path) = &cmd.json {
+        serde_json::to_writer_pretty(File::create(json_path)?, &card)?;
+        println!("Wrote report card to: {}", json_path.display());
+    }
+    Ok(())
+}
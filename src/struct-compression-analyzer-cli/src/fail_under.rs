@@ -0,0 +1,52 @@
+//! Fails a directory run for `--fail-under` unless a comparison compresses well enough.
+
+use struct_compression_analyzer::results::merged_analysis_results::MergedAnalysisResults;
+
+/// Fails unless a comparison of the merged results has a zstd ratio below `fail_under`,
+/// for `--fail-under`. Prints the comparisons which don't.
+pub fn check_fail_under(
+    merged_results: &MergedAnalysisResults,
+    fail_under: f64,
+) -> anyhow::Result<()> {
+    check_ratios(&merged_results.comparison_zstd_ratios(), fail_under)
+}
+
+/// Fails unless one of the `(name, ratio)` pairs has a ratio below `fail_under`.
+/// Ratios which aren't finite (e.g. NaN) can't be below anything, so they fail too.
+fn check_ratios(ratios: &[(&str, f64)], fail_under: f64) -> anyhow::Result<()> {
+    let failed: Vec<_> = ratios
+        .iter()
+        .filter(|(_, ratio)| !(ratio.is_finite() && *ratio < fail_under))
+        .collect();
+    if !failed.is_empty() {
+        println!();
+        println!("Comparisons with a zstd ratio of {fail_under} or more, or no valid ratio:");
+        for (name, ratio) in &failed {
+            println!("- {name}: {ratio:.4}");
+        }
+    }
+
+    if failed.len() == ratios.len() {
+        anyhow::bail!("No comparison has a zstd ratio below {fail_under}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_if_any_ratio_is_below_the_threshold() {
+        assert!(check_ratios(&[("a", 1.1), ("b", 0.9)], 1.0).is_ok());
+        assert!(check_ratios(&[("a", 1.0), ("b", 1.1)], 1.0).is_err());
+        assert!(check_ratios(&[], 1.0).is_err());
+    }
+
+    #[test]
+    fn non_finite_ratios_fail() {
+        assert!(check_ratios(&[("a", f64::NAN)], 1.0).is_err());
+        assert!(check_ratios(&[("a", f64::NEG_INFINITY)], 1.0).is_err());
+        assert!(check_ratios(&[("a", f64::NAN), ("b", 0.9)], 1.0).is_ok());
+    }
+}
@@ -0,0 +1,211 @@
+//! Reading the region of a file to analyze, and feeding its records to the analyzer.
+
+use crate::AnalyzeFileParams;
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+use struct_compression_analyzer::{
+    analyzer::{CompressionOptions, SchemaAnalyzer},
+    offset_evaluator::try_evaluate_file_offsets,
+    schema::Schema,
+    utils::metrics_cache::CachedRegion,
+};
+
+/// Creates an analyzer and feeds it the region of the file to analyze.
+pub fn ingest_file<'a>(params: &AnalyzeFileParams<'a>) -> anyhow::Result<SchemaAnalyzer<'a>> {
+    let mut file = File::open(params.path)?;
+    let (offset, length) = resolve_file_region(params, &mut file)?;
+    let mut options = compression_options(params);
+    if let Some(cache) = &params.metrics_cache {
+        let region = CachedRegion::new(cache.clone(), params.schema, params.path, offset, length)?;
+        options = options.with_metrics_cache(region);
+    }
+
+    let mut analyzer = SchemaAnalyzer::new(params.schema, options);
+    file.seek(SeekFrom::Start(offset))?;
+    if params.schema.continuous {
+        // Records may straddle entry boundaries, so the bitstream is analyzed in one go.
+        let data = read_region(&mut file, length)?;
+        add_file_data(&mut analyzer, &data, params)?;
+    } else {
+        // Entries are read as they are analyzed, rather than buffering the whole region first.
+        if params.schema.element_size.is_some() {
+            analyzer.add_sized_entries_from_reader(file, length)?;
+        } else {
+            let count = entry_count(params.path, length, params.bytes_per_element)?;
+            analyzer.add_entries_from_reader(file, params.bytes_per_element as usize, count)?;
+        }
+    }
+
+    Ok(analyzer)
+}
+
+/// Reads the region of the file to analyze, see [`resolve_file_region`].
+pub fn read_file_region(params: &AnalyzeFileParams) -> anyhow::Result<Box<[u8]>> {
+    let mut file = File::open(params.path)?;
+    let (offset, length) = resolve_file_region(params, &mut file)?;
+    file.seek(SeekFrom::Start(offset))?;
+    read_region(&mut file, length)
+}
+
+/// Reads `length` bytes from the current position of the file.
+fn read_region(file: &mut File, length: u64) -> anyhow::Result<Box<[u8]>> {
+    let mut data = unsafe { Box::new_uninit_slice(length as usize).assume_init() };
+    file.read_exact(&mut data)?;
+    Ok(data)
+}
+
+/// The offset and length of the region of the file to analyze. Unless given, the offset
+/// is evaluated from the conditional offsets of the schema.
+fn resolve_file_region(params: &AnalyzeFileParams, file: &mut File) -> anyhow::Result<(u64, u64)> {
+    let file_len = file.metadata()?.len();
+
+    // The first matching conditional offset may also specify the length of the region.
+    let mut schema_length = None;
+    let offset = match (params.offset, params.offset_pct) {
+        (Some(offset), _) => offset,
+        (None, Some(pct)) => percentage_of_file(file_len, pct, params.bytes_per_element),
+        (None, None) => {
+            match try_evaluate_file_offsets(&params.schema.conditional_offsets, file)?.first() {
+                Some(region) => {
+                    schema_length = region.length;
+                    region.offset
+                }
+                None => 0,
+            }
+        }
+    };
+    if offset > file_len {
+        anyhow::bail!(
+            "Offset {:#X} is past the end of {} ({} bytes)",
+            offset,
+            params.path.display(),
+            file_len
+        );
+    }
+
+    // Read up to length in AnalyzeFileParams at file offset
+    let length = match (params.length, params.length_pct) {
+        (Some(l), _) => l,
+        (None, Some(pct)) => percentage_of_file(file_len, pct, params.bytes_per_element),
+        (None, None) => schema_length.unwrap_or(file_len - offset),
+    };
+    if offset.saturating_add(length) > file_len {
+        anyhow::bail!(
+            "Region at offset {:#X} with length {} exceeds the size of {} ({} bytes)",
+            offset,
+            length,
+            params.path.display(),
+            file_len
+        );
+    }
+    Ok((offset, length))
+}
+
+/// The compression options of the analysis of a file.
+pub fn compression_options(params: &AnalyzeFileParams) -> CompressionOptions {
+    let options = CompressionOptions::default()
+        .with_zstd_compression_level(params.zstd_compression_level)
+        .with_zstd_calibration_level(params.zstd_calibration_level)
+        .with_zstd_strategy(params.zstd_strategy)
+        .with_compressor(params.compressor)
+        .with_estimate_breakdown(params.include_estimate_breakdown)
+        .with_value_transitions(params.track_value_transitions)
+        .with_max_value_transitions(params.max_value_transitions)
+        .with_max_distinct_values(params.max_distinct_values)
+        .with_strict(params.strict)
+        .with_byte_transpose_comparison(params.byte_transpose)
+        .with_entropy_coded_size(params.entropy_coded_size)
+        .with_order1_entropy(params.order1_entropy)
+        .with_zstd_timing(params.zstd_timing)
+        .with_run_stats(params.run_stats)
+        .with_entropy_window(params.entropy_window);
+    match &params.zstd_dictionary {
+        Some(dictionary) => options.with_zstd_dictionary(dictionary.clone()),
+        None => options,
+    }
+}
+
+/// Size of each element (struct) of the schema in bytes, which regions are rounded down to.
+/// Elements of varying size (see [`Schema::element_size`]) can't be rounded to, so this is 1.
+pub fn bytes_per_element(schema: &Schema) -> u64 {
+    match schema.element_size {
+        Some(_) => 1,
+        None => (schema.root.bits / 8) as u64,
+    }
+}
+
+/// Number of fixed size entries in a region of `length` bytes of the file at `path`.
+///
+/// # Errors
+/// If the region ends with a partial entry, or the entries are smaller than a byte.
+fn entry_count(path: &Path, length: u64, bytes_per_element: u64) -> anyhow::Result<u64> {
+    if bytes_per_element == 0 {
+        anyhow::bail!(
+            "{}: the schema's root is smaller than a byte, so there are no entries to analyze",
+            path.display()
+        );
+    }
+    if !length.is_multiple_of(bytes_per_element) {
+        anyhow::bail!(
+            "{}: {} bytes to analyze is not a multiple of the record size ({} bytes)",
+            path.display(),
+            length,
+            bytes_per_element
+        );
+    }
+
+    Ok(length / bytes_per_element)
+}
+
+/// Adds the records (structs) read from a file to the analyzer.
+pub fn add_file_data(
+    analyzer: &mut SchemaAnalyzer,
+    data: &[u8],
+    params: &AnalyzeFileParams,
+) -> anyhow::Result<()> {
+    if params.schema.continuous {
+        // Records are packed back-to-back, and may not be byte aligned.
+        analyzer.add_bitstream(data)?;
+    } else if params.schema.element_size.is_some() {
+        // The size of each entry is read from its header.
+        analyzer.add_sized_entries(data)?;
+    } else {
+        entry_count(params.path, data.len() as u64, params.bytes_per_element)?;
+        for entry in data.chunks_exact(params.bytes_per_element as usize) {
+            analyzer.add_entry(entry)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts a percentage of the file size to bytes, rounded down to a whole number of
+/// elements (structs), such that the analyzed region always contains complete structs.
+fn percentage_of_file(file_len: u64, pct: f64, bytes_per_element: u64) -> u64 {
+    let bytes = (file_len as f64 * pct / 100.0) as u64;
+    if bytes_per_element == 0 {
+        return bytes;
+    }
+
+    bytes - (bytes % bytes_per_element)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_count_rejects_partial_and_empty_entries() {
+        let path = Path::new("data.bin");
+        assert_eq!(entry_count(path, 64, 8).unwrap(), 8);
+        assert_eq!(entry_count(path, 0, 8).unwrap(), 0);
+
+        let partial = entry_count(path, 65, 8).unwrap_err().to_string();
+        assert!(partial.contains("not a multiple of the record size (8 bytes)"));
+        let empty = entry_count(path, 64, 0).unwrap_err().to_string();
+        assert!(empty.contains("smaller than a byte"));
+    }
+}
@@ -0,0 +1,114 @@
+//! Filtering of the files found in a directory by glob patterns, e.g. to skip readmes or
+//! thumbnails stored next to the data files.
+
+use anyhow::Context;
+use glob::{MatchOptions, Pattern};
+use std::path::Path;
+
+/// `*` and `?` don't match across directories; `**` does.
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Include and exclude glob patterns for the files of a directory.
+///
+/// A pattern containing a `/` is matched against the path relative to the directory,
+/// e.g. `textures/**/*.dds`; any other pattern is matched against the file name alone,
+/// e.g. `*.md`.
+#[derive(Default)]
+pub struct FileFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl FileFilter {
+    /// Creates a filter keeping files which match any of the `include` patterns
+    /// (or all files if there are none) and none of the `exclude` patterns.
+    /// Exclude wins when a file matches both.
+    pub fn new(include: &[String], exclude: &[String]) -> anyhow::Result<Self> {
+        Ok(Self {
+            include: parse_patterns(include)?,
+            exclude: parse_patterns(exclude)?,
+        })
+    }
+
+    /// Returns `true` if the file at `relative_path` (relative to the directory) is kept.
+    pub fn matches(&self, relative_path: &Path) -> bool {
+        let matches_any = |patterns: &[Pattern]| {
+            patterns
+                .iter()
+                .any(|pattern| pattern_matches(pattern, relative_path))
+        };
+
+        (self.include.is_empty() || matches_any(&self.include)) && !matches_any(&self.exclude)
+    }
+}
+
+fn parse_patterns(patterns: &[String]) -> anyhow::Result<Vec<Pattern>> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern).with_context(|| format!("Invalid file pattern '{pattern}'"))
+        })
+        .collect()
+}
+
+fn pattern_matches(pattern: &Pattern, relative_path: &Path) -> bool {
+    if pattern.as_str().contains('/') {
+        pattern.matches_path_with(relative_path, MATCH_OPTIONS)
+    } else {
+        relative_path
+            .file_name()
+            .is_some_and(|name| pattern.matches_path_with(Path::new(name), MATCH_OPTIONS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_filter(include: &[&str], exclude: &[&str]) -> FileFilter {
+        let patterns =
+            |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        FileFilter::new(&patterns(include), &patterns(exclude)).unwrap()
+    }
+
+    #[test]
+    fn exclude_wins_over_include() {
+        let filter = file_filter(&["*.bin"], &["skip*"]);
+        assert!(filter.matches(Path::new("data.bin")));
+        assert!(!filter.matches(Path::new("skip.bin")));
+        assert!(!filter.matches(Path::new("readme.md")));
+
+        // No include patterns keeps everything not excluded.
+        let filter = file_filter(&[], &["*.md"]);
+        assert!(filter.matches(Path::new("data.bin")));
+        assert!(!filter.matches(Path::new("docs/readme.md")));
+    }
+
+    #[test]
+    fn matches_file_names_or_relative_paths() {
+        // Without a '/', only the file name is matched, in any directory.
+        let by_name = file_filter(&["*.dds"], &[]);
+        assert!(by_name.matches(Path::new("a.dds")));
+        assert!(by_name.matches(Path::new("textures/ui/a.dds")));
+
+        // With a '/', the path relative to the directory is matched;
+        // '*' doesn't cross directories, '**' does.
+        let by_path = file_filter(&["textures/*.dds"], &[]);
+        assert!(by_path.matches(Path::new("textures/a.dds")));
+        assert!(!by_path.matches(Path::new("textures/ui/a.dds")));
+        assert!(!by_path.matches(Path::new("a.dds")));
+
+        let recursive = file_filter(&["textures/**/*.dds"], &[]);
+        assert!(recursive.matches(Path::new("textures/ui/a.dds")));
+        assert!(!recursive.matches(Path::new("models/a.dds")));
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(FileFilter::new(&["[".to_string()], &[]).is_err());
+    }
+}
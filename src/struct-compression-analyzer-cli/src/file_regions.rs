@@ -0,0 +1,152 @@
+//! The regions of a file analyzed by `analyze-file`, which produces one result per region.
+
+use crate::FileCommand;
+use std::{
+    fs::File,
+    io::{stdout, Write},
+};
+use struct_compression_analyzer::{
+    offset_evaluator::try_evaluate_file_offsets,
+    results::{
+        analysis_results::AnalysisResults, json::write_json,
+        merged_analysis_results::MergedAnalysisResults, PrintFormat,
+    },
+    schema::Schema,
+};
+
+/// A region of a file: its offset and length, if given.
+/// Without either, the schema's conditional offsets or the rest of the file are used.
+pub type FileRegion = (Option<u64>, Option<u64>);
+
+/// Pairs up the `--offset` and `--length` arguments into the regions to analyze.
+/// With no offsets, the whole file (or the offset specified by the schema) is one region.
+pub fn file_regions(offsets: &[u64], lengths: &[u64]) -> anyhow::Result<Vec<FileRegion>> {
+    if offsets.is_empty() {
+        return match lengths {
+            [] => Ok(vec![(None, None)]),
+            [length] => Ok(vec![(None, Some(*length))]),
+            _ => anyhow::bail!("Multiple `--length`s require an `--offset` for each"),
+        };
+    }
+
+    if lengths.is_empty() {
+        return Ok(offsets.iter().map(|o| (Some(*o), None)).collect());
+    }
+
+    if lengths.len() != offsets.len() {
+        anyhow::bail!(
+            "Got {} `--offset`s but {} `--length`s. Specify a length for every offset, or none.",
+            offsets.len(),
+            lengths.len()
+        );
+    }
+
+    Ok(offsets
+        .iter()
+        .zip(lengths)
+        .map(|(o, l)| (Some(*o), Some(*l)))
+        .collect())
+}
+
+/// The regions of all conditional offsets of the schema which match the file, for `--all-offsets`.
+pub fn conditional_regions(
+    schema: &Schema,
+    file_cmd: &FileCommand,
+) -> anyhow::Result<Vec<FileRegion>> {
+    if !file_cmd.offset.is_empty()
+        || !file_cmd.length.is_empty()
+        || file_cmd.offset_pct.is_some()
+        || file_cmd.length_pct.is_some()
+    {
+        anyhow::bail!(
+            "`--all-offsets` can't be used with `--offset`, `--length` or their percentages"
+        );
+    }
+
+    let mut file = File::open(&file_cmd.path)?;
+    let regions = try_evaluate_file_offsets(&schema.conditional_offsets, &mut file)?;
+    if regions.is_empty() {
+        anyhow::bail!(
+            "No conditional offset of the schema matches {}",
+            file_cmd.path.display()
+        );
+    }
+    Ok(regions
+        .into_iter()
+        .map(|region| (Some(region.offset), region.length))
+        .collect())
+}
+
+/// Prints the results of each region, followed by their merged results with `--merge-regions`.
+/// A single region is printed without a region header.
+pub fn print_region_results(
+    schema: &Schema,
+    file_cmd: &FileCommand,
+    regions: &[FileRegion],
+    region_results: &[AnalysisResults],
+) -> anyhow::Result<()> {
+    let format = file_cmd.format.unwrap_or_default();
+    if let PrintFormat::Json = format {
+        // A single JSON document, with the results of all regions (or their merge).
+        let mut out = stdout();
+        match region_results {
+            [analysis_result] => write_json(analysis_result, &mut out)?,
+            all if file_cmd.merge_regions => {
+                write_json(&MergedAnalysisResults::from_results(all)?, &mut out)?
+            }
+            all => write_json(all, &mut out)?,
+        }
+        writeln!(out)?;
+    } else if let [analysis_result] = region_results {
+        println!("Analysis Results:");
+        analysis_result.print(&mut stdout(), schema, format, !file_cmd.show_extra_stats)?;
+    } else {
+        for (x, ((offset, length), analysis_result)) in
+            regions.iter().zip(region_results).enumerate()
+        {
+            println!(
+                "Analysis Results (Region {}: offset {:#X}, length {}):",
+                x,
+                offset.unwrap_or(0),
+                length.map_or("rest of file".to_string(), |l| l.to_string())
+            );
+            analysis_result.print(&mut stdout(), schema, format, !file_cmd.show_extra_stats)?;
+            println!();
+        }
+    }
+
+    if file_cmd.merge_regions && region_results.len() > 1 && !matches!(format, PrintFormat::Json) {
+        let merged_results = MergedAnalysisResults::from_results(region_results)?;
+        merged_results.print(
+            &mut stdout(),
+            schema,
+            format,
+            !file_cmd.show_extra_stats,
+            file_cmd.color,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_offsets_with_lengths() {
+        assert_eq!(file_regions(&[], &[]).unwrap(), [(None, None)]);
+        assert_eq!(file_regions(&[], &[16]).unwrap(), [(None, Some(16))]);
+        assert_eq!(
+            file_regions(&[0, 64], &[]).unwrap(),
+            [(Some(0), None), (Some(64), None)]
+        );
+        assert_eq!(
+            file_regions(&[0, 64], &[32, 16]).unwrap(),
+            [(Some(0), Some(32)), (Some(64), Some(16))]
+        );
+
+        assert!(file_regions(&[], &[16, 32]).is_err());
+        assert!(file_regions(&[0, 64], &[32]).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! Selection of a subset of the files in a directory, for quick previews of large directories.
+
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Keeps only the first `max_files` files, in path order.
+///
+/// Returns `true` if any files were removed.
+pub fn take_first_files(files: &mut Vec<PathBuf>, max_files: usize) -> bool {
+    if files.len() <= max_files {
+        return false;
+    }
+
+    // Directory walk order depends on the file system; sort for repeatable subsets.
+    files.sort();
+    files.truncate(max_files);
+    true
+}
+
+/// Keeps a random selection of `sample_files` files, chosen using `seed`.
+///
+/// The same seed always selects the same files from the same directory.
+/// Returns `true` if any files were removed.
+pub fn sample_random_files(files: &mut Vec<PathBuf>, sample_files: usize, seed: u64) -> bool {
+    if files.len() <= sample_files {
+        return false;
+    }
+
+    files.sort();
+
+    // Partial Fisher-Yates shuffle; the first `sample_files` entries become the sample.
+    let mut rng = SplitMix64(seed);
+    for x in 0..sample_files {
+        let remaining = (files.len() - x) as u64;
+        let swap_with = x + (rng.next() % remaining) as usize;
+        files.swap(x, swap_with);
+    }
+
+    files.truncate(sample_files);
+    files.sort();
+    true
+}
+
+/// Creates a seed for [`sample_random_files`] when the user didn't provide one.
+pub fn default_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+/// Small, fast PRNG; more than good enough for picking files.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(count: usize) -> Vec<PathBuf> {
+        // Reverse order, so sorting is observable.
+        (0..count)
+            .rev()
+            .map(|x| PathBuf::from(format!("{x:03}.bin")))
+            .collect()
+    }
+
+    #[test]
+    fn takes_first_files_in_path_order() {
+        let mut first = files(10);
+        assert!(take_first_files(&mut first, 3));
+        assert_eq!(first, files(3).into_iter().rev().collect::<Vec<_>>());
+
+        let mut all = files(3);
+        assert!(!take_first_files(&mut all, 3));
+        assert_eq!(all, files(3));
+    }
+
+    #[test]
+    fn samples_sorted_subset_repeatably() {
+        let mut sample = files(100);
+        assert!(sample_random_files(&mut sample, 10, 42));
+        assert_eq!(sample.len(), 10);
+        assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(sample.iter().all(|file| files(100).contains(file)));
+
+        let mut same_seed = files(100);
+        sample_random_files(&mut same_seed, 10, 42);
+        assert_eq!(same_seed, sample);
+
+        let mut other_seed = files(100);
+        sample_random_files(&mut other_seed, 10, 43);
+        assert_ne!(other_seed, sample);
+
+        let mut all = files(10);
+        assert!(!sample_random_files(&mut all, 10, 42));
+        assert_eq!(all, files(10));
+    }
+}
@@ -0,0 +1,114 @@
+//! Loading of per-file weights used when merging the results of a directory.
+
+use anyhow::{bail, Context};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Weight assigned to files which are not listed in the weights file.
+pub const DEFAULT_FILE_WEIGHT: f64 = 1.0;
+
+/// Maps file paths to the weight they contribute to the merged results.
+pub struct FileWeights {
+    /// The directory being analyzed; listed paths are relative to this.
+    base_dir: PathBuf,
+    weights: HashMap<PathBuf, f64>,
+}
+
+impl FileWeights {
+    /// Loads weights from a CSV file with `path,weight` rows.
+    ///
+    /// Empty lines and lines starting with `#` are ignored, as is a header row.
+    /// Paths may be relative to `base_dir` (the analyzed directory), or as found when walking it.
+    ///
+    /// # Arguments
+    /// * `csv_path` - Path to the CSV file.
+    /// * `base_dir` - The directory being analyzed.
+    pub fn load(csv_path: &Path, base_dir: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(csv_path)
+            .with_context(|| format!("Failed to read weights file {}", csv_path.display()))?;
+
+        let mut weights = HashMap::new();
+        for (line_idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // Split on the last comma, such that paths with commas still work.
+            let Some((path, weight)) = line.rsplit_once(',') else {
+                bail!(
+                    "Invalid weights file {} at line {}: expected `path,weight`",
+                    csv_path.display(),
+                    line_idx + 1
+                );
+            };
+
+            let weight = match weight.trim().parse::<f64>() {
+                Ok(weight) if weight.is_finite() && weight >= 0.0 => weight,
+                // Header row
+                Err(_) if weights.is_empty() => continue,
+                _ => bail!(
+                    "Invalid weight '{}' in {} at line {}: expected a non-negative number",
+                    weight.trim(),
+                    csv_path.display(),
+                    line_idx + 1
+                ),
+            };
+
+            weights.insert(PathBuf::from(path.trim()), weight);
+        }
+
+        Ok(Self {
+            base_dir: base_dir.to_path_buf(),
+            weights,
+        })
+    }
+
+    /// Returns the weight of a file found in the analyzed directory.
+    /// Unlisted files default to [`DEFAULT_FILE_WEIGHT`].
+    pub fn get(&self, path: &Path) -> f64 {
+        let relative = path.strip_prefix(&self.base_dir).unwrap_or(path);
+        self.weights
+            .get(relative)
+            .or_else(|| self.weights.get(path))
+            .copied()
+            .unwrap_or(DEFAULT_FILE_WEIGHT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Loads weights from a temporary file with the given contents.
+    fn load(contents: &str) -> anyhow::Result<FileWeights> {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), contents).unwrap();
+        FileWeights::load(file.path(), Path::new("dir"))
+    }
+
+    #[test]
+    fn parses_weights() {
+        let weights =
+            load("path,weight\n# comment\n\na.bin, 2.5\ndir/b.bin,0\nc,d.bin,3\n").unwrap();
+
+        // Relative to the directory, or as found when walking it.
+        assert_eq!(weights.get(Path::new("dir/a.bin")), 2.5);
+        assert_eq!(weights.get(Path::new("dir/b.bin")), 0.0);
+        assert_eq!(weights.get(Path::new("dir/c,d.bin")), 3.0);
+        assert_eq!(weights.get(Path::new("dir/e.bin")), DEFAULT_FILE_WEIGHT);
+    }
+
+    #[test]
+    fn rejects_invalid_weights() {
+        assert!(load("a.bin 2\n").is_err());
+        assert!(load("a.bin,-1\n").is_err());
+        assert!(load("a.bin,inf\n").is_err());
+        // Only the first row may be a header.
+        assert!(load("a.bin,1\npath,weight\n").is_err());
+        assert!(load("").is_ok());
+        assert!(FileWeights::load(Path::new("does/not/exist.csv"), Path::new("dir")).is_err());
+    }
+}
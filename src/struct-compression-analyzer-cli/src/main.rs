@@ -1,130 +1,59 @@
 #![doc = include_str!(concat!("../", env!("CARGO_PKG_README")))]
 
-use argh::FromArgs;
-use indicatif::{ProgressBar, ProgressStyle};
+use commands::{
+    Args, Command, EstimatorReportCommand, FileCommand, FilesCommand, ReportFromAnalysisCommand,
+};
+use directory_command::DirectoryCommand;
+use file_data::{bytes_per_element, ingest_file};
+use file_filter::FileFilter;
+use file_regions::{conditional_regions, file_regions, print_region_results};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use mimalloc::MiMalloc;
-use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use result_files::write_field_tree_json;
+use scaffold::generate_scaffold_schema;
 use std::{
-    fs::File,
-    io::{stdout, Read, Seek, SeekFrom},
+    io::{self, stdout, IsTerminal},
     path::{Path, PathBuf},
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::Arc,
     time::Instant,
 };
 use struct_compression_analyzer::{
-    analyzer::{CompressionOptions, SchemaAnalyzer},
-    brute_force::{
-        brute_force_custom::CustomComparisonOptimizationResult,
-        brute_force_split::SplitComparisonOptimizationResult, optimize_and_apply_coefficients,
-        print_all_optimization_results,
-    },
-    csv,
-    offset_evaluator::try_evaluate_file_offset,
-    plot::generate_plots,
+    analyzer::{DEFAULT_MAX_DISTINCT_VALUES, DEFAULT_MAX_VALUE_TRANSITIONS},
+    comparison::GroundTruth,
     results::{
-        analysis_results::AnalysisResults, merged_analysis_results::MergedAnalysisResults,
-        PrintFormat,
+        analysis_results::AnalysisResults, cross_file_results::CrossFileSample,
+        field_tree::FieldTree, merged_analysis_results::MergedAnalysisResults,
+        reference_sizes::ReferenceSizes,
     },
     schema::Schema,
+    utils::{
+        analyze_utils::ZstdStrategy, compressor::Compressor, metrics_cache::GroupMetricsCache,
+    },
 };
 use walkdir::WalkDir;
 
+mod analyze_directory;
+mod analyze_files;
+mod brute_force;
+mod commands;
+mod directory_command;
+mod estimator_report;
+mod fail_under;
+mod file_data;
+mod file_filter;
+mod file_regions;
+mod file_sampling;
+mod file_weights;
+mod reference_file;
+mod reference_sizes;
+mod report_from_analysis;
+mod result_files;
+mod scaffold;
+mod zstd_dictionary;
+
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
-#[derive(Debug, FromArgs)]
-/// CLI for analyzing struct compression
-struct Args {
-    #[argh(subcommand)]
-    /// the command to execute.
-    command: Command,
-}
-
-#[derive(Debug, FromArgs)]
-#[argh(subcommand)]
-enum Command {
-    File(FileCommand),
-    Directory(DirectoryCommand),
-}
-
-#[derive(Debug, FromArgs)]
-#[argh(subcommand, name = "analyze-file")]
-/// Analyze a single file
-struct FileCommand {
-    #[argh(positional)]
-    /// path to the schema file
-    schema: PathBuf,
-
-    #[argh(positional)]
-    /// path to the file to analyze
-    path: PathBuf,
-
-    /// offset to start analyzing from
-    #[argh(option, short = 'o')]
-    offset: Option<u64>,
-
-    /// length of the data to analyze. If not specified, the entire rest of the file is analyzed.
-    #[argh(option, short = 'l')]
-    length: Option<u64>,
-
-    /// output format ('detailed', 'concise')
-    #[argh(option, short = 'f')]
-    format: Option<PrintFormat>,
-
-    /// show extra stats
-    #[argh(switch, long = "show-extra-stats")]
-    show_extra_stats: bool,
-
-    /// zstd compression level (default: 3)
-    #[argh(option, short = 'z', default = "3")]
-    zstd_compression_level: i32,
-}
-
-#[derive(Debug, FromArgs)]
-#[argh(subcommand, name = "analyze-directory")]
-/// Analyze all files in a directory
-struct DirectoryCommand {
-    #[argh(positional)]
-    /// path to the schema file
-    schema: PathBuf,
-
-    #[argh(positional)]
-    /// path to the directory containing files to analyze
-    path: PathBuf,
-
-    /// offset to start analyzing from
-    #[argh(option, short = 'o')]
-    offset: Option<u64>,
-
-    /// length of the data to analyze. If not specified, the entire rest of the file is analyzed.
-    #[argh(option, short = 'l')]
-    length: Option<u64>,
-
-    /// output format ('detailed', 'concise')
-    #[argh(option, short = 'f')]
-    format: Option<PrintFormat>,
-
-    /// print info for all files
-    #[argh(switch, short = 'a')]
-    all_files: bool,
-
-    /// output directory for CSV and plot reports
-    #[argh(option)]
-    output: Option<PathBuf>,
-
-    /// show extra stats
-    #[argh(switch, long = "show-extra-stats")]
-    show_extra_stats: bool,
-
-    /// zstd compression level (default: 16)
-    #[argh(option, short = 'z', default = "16")]
-    zstd_compression_level: i32,
-
-    /// enable brute forcing of LZ match and entropy multiplier parameters
-    #[argh(switch, long = "brute-force-lz-params")]
-    brute_force: bool,
-}
-
 /// Parameters to function used to analyze a single file.
 struct AnalyzeFileParams<'a> {
     /// The schema to use for analysis
@@ -138,182 +67,185 @@ struct AnalyzeFileParams<'a> {
     offset: Option<u64>,
     /// The length of the data to analyze. If not specified, the entire rest of the file is analyzed.
     length: Option<u64>,
+    /// The offset to start analyzing from, as a percentage of the file size.
+    /// Takes precedence over the schema's offset; exclusive with [`Self::offset`].
+    offset_pct: Option<f64>,
+    /// The length of the data to analyze, as a percentage of the file size.
+    /// Exclusive with [`Self::length`].
+    length_pct: Option<f64>,
     /// The zstd compression level.
     zstd_compression_level: i32,
+    /// The zstd compression level of the sizes the estimator is calibrated against, if different.
+    zstd_calibration_level: Option<i32>,
+    /// The zstd strategy.
+    zstd_strategy: ZstdStrategy,
+    /// The compressor measuring the actual sizes besides zstd.
+    compressor: Compressor,
+    /// Whether to retain the bytes of the file for cross-file analysis.
+    retain_cross_file_sample: bool,
+    /// Whether to record the intermediate terms of the size estimator.
+    include_estimate_breakdown: bool,
+    /// Whether to count the transitions between consecutive values of each field.
+    track_value_transitions: bool,
+    /// Maximum number of distinct value transitions tracked per field.
+    max_value_transitions: usize,
+    /// Maximum number of distinct values counted per field.
+    max_distinct_values: usize,
+    /// Whether to treat schema problems which are otherwise warnings as errors.
+    strict: bool,
+    /// Whether to add the built-in byte transpose comparison.
+    byte_transpose: bool,
+    /// Whether to measure the order-0 range coded size of each field.
+    entropy_coded_size: bool,
+    /// Whether to calculate the order-1 entropy of each field and group.
+    order1_entropy: bool,
+    /// Whether to measure the time taken to compress each group with zstd.
+    zstd_timing: bool,
+    /// Whether to calculate the run-length statistics of each field.
+    run_stats: bool,
+    /// Number of records per window of the windowed entropy of each field, if recorded.
+    entropy_window: Option<usize>,
+    /// The zstd dictionary shared by all files, if one was trained.
+    zstd_dictionary: Option<Arc<[u8]>>,
+    /// The cache of the metrics of the groups of all files, if one is used.
+    metrics_cache: Option<Arc<GroupMetricsCache>>,
 }
 
 fn main() -> anyhow::Result<()> {
     let args: Args = argh::from_env();
+    let threads = match &args.command {
+        Command::File(file_cmd) => file_cmd.threads,
+        Command::Directory(dir_cmd) => dir_cmd.threads,
+        _ => 0,
+    };
 
+    // 0 keeps rayon's global pool, which uses all cores.
+    if threads == 0 {
+        return run(args.command);
+    }
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()?
+        .install(|| run(args.command))
+}
+
+/// Runs a command, on the threads of the current rayon pool.
+fn run(command: Command) -> anyhow::Result<()> {
     let start_time = Instant::now();
-    match args.command {
+    match command {
         Command::File(file_cmd) => {
             let schema = load_schema(&file_cmd.schema)?;
-            let analysis_result = analyze_file(&AnalyzeFileParams {
-                schema: &schema,
-                path: &file_cmd.path,
-                bytes_per_element: (schema.root.bits / 8) as u64,
-                offset: file_cmd.offset,
-                length: file_cmd.length,
-                zstd_compression_level: file_cmd.zstd_compression_level,
-            })?;
-            println!("Analysis Results:");
-            analysis_result.print(
-                &mut stdout(),
-                &schema,
-                file_cmd.format.unwrap_or(PrintFormat::default()),
-                !file_cmd.show_extra_stats,
+            let regions = if file_cmd.all_offsets {
+                conditional_regions(&schema, &file_cmd)?
+            } else {
+                file_regions(&file_cmd.offset, &file_cmd.length)?
+            };
+            validate_region_percentages(
+                !file_cmd.offset.is_empty(),
+                !file_cmd.length.is_empty(),
+                file_cmd.offset_pct,
+                file_cmd.length_pct,
             )?;
-        }
-        Command::Directory(dir_cmd) => {
-            let schema = load_schema(&dir_cmd.schema)?;
-            let files = find_directory_files_recursive(&dir_cmd.path)?;
-            println!(
-                "Analyzing directory: {} ({} files)",
-                dir_cmd.path.display(),
-                files.len()
-            );
-
-            // Setup progress bar
-            let pb = ProgressBar::new(files.len() as u64);
-            pb.set_style(
-                ProgressStyle::default_bar()
-                    .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} files")
-                    .unwrap()
-                    .progress_chars("#>-"),
-            );
-
-            // Counter for completed files
-            let completed_files = AtomicUsize::new(0);
-
-            // Process every file with rayon, collecting individual results
-            let analyze_start_time = Instant::now();
-            let mut individual_results: Vec<AnalysisResults> = files
-                .par_iter()
-                // 1 item at once per thread. Our items are big generally, and take time to process
-                // so 'max work stealing' is preferred.
-                .with_max_len(1)
-                .map(|path| {
-                    let result = analyze_file(&AnalyzeFileParams {
-                        schema: &schema,
-                        path,
-                        bytes_per_element: (schema.root.bits / 8) as u64,
-                        offset: dir_cmd.offset,
-                        length: dir_cmd.length,
-                        zstd_compression_level: dir_cmd.zstd_compression_level,
-                    });
-
-                    // Update progress bar
-                    let completed = completed_files.fetch_add(1, Ordering::SeqCst) + 1;
-                    pb.set_position(completed as u64);
-
-                    result
-                })
-                .filter_map(|result| match result {
-                    Ok(results) => Some(results),
-                    Err(e) => {
-                        eprintln!("Error processing {}: {}", dir_cmd.path.display(), e);
-                        None
-                    }
-                })
-                .collect();
-
-            // Finish progress bar
-            pb.finish_with_message(format!(
-                "Analysis completed in {:.2}s",
-                analyze_start_time.elapsed().as_secs_f64()
-            ));
-
-            // Run brute force optimization on merged results if enabled
-            if dir_cmd.brute_force {
-                println!("\nRunning LZ parameter optimization on merged results...");
-                let brute_force_start_time = Instant::now();
-                let (split_results, custom_results) =
-                    optimize_and_apply_coefficients(&mut individual_results, None);
-                println!(
-                    "{}ms... Brute force optimization complete.",
-                    brute_force_start_time.elapsed().as_millis()
-                );
-
-                print_all_optimization_results(&mut stdout(), &split_results, &custom_results)?;
-
-                // Save optimization results to file if output directory is specified
-                if let Some(output_dir) = &dir_cmd.output {
-                    std::fs::create_dir_all(output_dir)?;
-                    write_optimization_results_to_file(
-                        &split_results,
-                        &custom_results,
-                        output_dir,
-                    )?;
+            let reference_sizes = file_cmd
+                .reference_sizes
+                .as_deref()
+                .map(ReferenceSizes::load)
+                .transpose()?;
+            let mut region_results = Vec::with_capacity(regions.len());
+            for (offset, length) in &regions {
+                let (mut analysis_result, _) = analyze_file(&AnalyzeFileParams {
+                    schema: &schema,
+                    path: &file_cmd.path,
+                    bytes_per_element: bytes_per_element(&schema),
+                    offset: *offset,
+                    length: *length,
+                    offset_pct: file_cmd.offset_pct,
+                    length_pct: file_cmd.length_pct,
+                    zstd_compression_level: file_cmd.zstd_compression_level,
+                    zstd_calibration_level: None,
+                    zstd_strategy: file_cmd.zstd_strategy,
+                    compressor: file_cmd.compressor,
+                    retain_cross_file_sample: false,
+                    include_estimate_breakdown: file_cmd.show_estimate_terms,
+                    track_value_transitions: false,
+                    max_value_transitions: DEFAULT_MAX_VALUE_TRANSITIONS,
+                    max_distinct_values: DEFAULT_MAX_DISTINCT_VALUES,
+                    strict: file_cmd.strict,
+                    byte_transpose: file_cmd.byte_transpose,
+                    entropy_coded_size: file_cmd.entropy_coded_size,
+                    order1_entropy: file_cmd.show_extra_stats,
+                    zstd_timing: file_cmd.show_extra_stats,
+                    run_stats: file_cmd.show_extra_stats,
+                    entropy_window: file_cmd.entropy_window,
+                    zstd_dictionary: None,
+                    metrics_cache: None,
+                })?;
+                if let Some(reference_sizes) = &reference_sizes {
+                    reference_sizes.apply(&mut analysis_result)?;
                 }
+                region_results.push(analysis_result);
             }
 
-            // Merge all results
-            println!(
-                "{}ms... Merging {} files.",
-                analyze_start_time.elapsed().as_millis(),
-                individual_results.len()
-            );
-            let merge_start_time = Instant::now();
-            let merged_results = MergedAnalysisResults::from_results(&individual_results)?;
-            println!(
-                "{}ms... Aggregated (Merged) Analysis Results:",
-                merge_start_time.elapsed().as_millis()
-            );
+            print_region_results(&schema, &file_cmd, &regions, &region_results)?;
 
-            merged_results.print(
-                &mut stdout(),
-                &schema,
-                dir_cmd.format.unwrap_or(PrintFormat::default()),
-                !dir_cmd.show_extra_stats,
-            )?;
+            if let Some(tree_path) = &file_cmd.field_tree_json {
+                let merged_results;
+                let per_field = match region_results.as_slice() {
+                    [analysis_result] => &analysis_result.per_field,
+                    _ => {
+                        merged_results = MergedAnalysisResults::from_results(&region_results)?;
+                        &merged_results.per_field
+                    }
+                };
+                write_field_tree_json(&FieldTree::new(&schema, per_field), tree_path)?;
+            }
+        }
+        Command::Directory(dir_cmd) => {
+            if let Some(results_path) = &dir_cmd.from_results {
+                let report_cmd = ReportFromAnalysisCommand::from_directory(&dir_cmd, results_path)?;
+                return report_from_analysis::run(&report_cmd);
+            }
+            analyze_directory::run(&dir_cmd)?
+        }
+        Command::Scaffold(scaffold_cmd) => {
+            if scaffold_cmd.record_size == 0 {
+                anyhow::bail!("Record size must be greater than 0");
+            }
 
-            // Print individual files
-            if dir_cmd.all_files {
-                println!("Individual Files:");
-                for x in 0..individual_results.len() {
-                    println!("- {}", files[x].display());
-                    individual_results[x].print(
-                        &mut stdout(),
-                        &schema,
-                        dir_cmd.format.unwrap_or(PrintFormat::default()),
-                        !dir_cmd.show_extra_stats,
-                    )?;
-                    println!();
+            let sample_len = std::fs::metadata(&scaffold_cmd.path)?.len();
+            let name = scaffold_cmd
+                .path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("New Schema");
+            let yaml = generate_scaffold_schema(name, scaffold_cmd.record_size, sample_len);
+
+            // Sanity check, the scaffold should always be a valid schema.
+            Schema::from_yaml(&yaml)?;
+            match &scaffold_cmd.output {
+                Some(output) => {
+                    std::fs::write(output, yaml)?;
+                    println!("Wrote schema to: {}", output.display());
                 }
+                None => print!("{}", yaml),
             }
 
-            // Write reports, output, etc.
-            if let Some(output_dir) = &dir_cmd.output {
-                std::fs::create_dir_all(output_dir)?;
-
-                // Write analysis results to files
-                write_merged_results_to_file(
-                    &merged_results,
-                    output_dir,
-                    &schema,
-                    dir_cmd.format.unwrap_or(PrintFormat::default()),
-                    false,
-                )?;
-                write_individual_results_to_files(
-                    &individual_results,
-                    output_dir,
-                    &schema,
-                    dir_cmd.format.unwrap_or(PrintFormat::default()),
-                    false,
-                    &files,
-                )?;
-
-                // Write CSV reports
-                csv::write_all_csvs(
-                    &merged_results.original_results,
-                    &merged_results,
-                    output_dir,
-                    &files,
-                )?;
-                generate_plots(&merged_results.original_results, output_dir).unwrap();
-                println!("Generated reports in: {}", output_dir.display());
+            // Nothing was analyzed, so don't print timings.
+            return Ok(());
+        }
+        Command::EstimatorReport(report_cmd) => estimator_report::run(&report_cmd)?,
+        Command::Files(files_cmd) => analyze_files::run(&files_cmd)?,
+        // Nothing is analyzed, so don't print timings.
+        Command::ReportFromAnalysis(report_cmd) => return report_from_analysis::run(&report_cmd),
+        Command::ValidateSchema(validate_cmd) => {
+            let schema = load_schema(&validate_cmd.schema)?;
+            if let Err(errors) = schema.validate() {
+                for error in &errors {
+                    eprintln!("{}", error);
+                }
+                anyhow::bail!("Found {} problem(s) in the schema", errors.len());
             }
+            println!("Schema is valid: {}", validate_cmd.schema.display());
         }
     }
 
@@ -326,55 +258,151 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn analyze_file(params: &AnalyzeFileParams) -> anyhow::Result<AnalysisResults> {
-    // Read the file contents
-    let mut file = File::open(params.path)?;
+fn analyze_file(
+    params: &AnalyzeFileParams,
+) -> anyhow::Result<(AnalysisResults, Option<CrossFileSample>)> {
+    let mut analyzer = ingest_file(params)?;
 
-    let offset = if params.offset.is_none() {
-        try_evaluate_file_offset(&params.schema.conditional_offsets, &mut file)?.unwrap_or(0)
-    } else {
-        params.offset.unwrap_or(0)
-    };
-
-    // Read up to length in AnalyzeFileParams at file offset
-    let length = match params.length {
-        Some(l) => l,
-        None => file.metadata()?.len() - offset,
-    };
-    file.seek(SeekFrom::Start(offset))?;
+    // Output the analysis results here
+    let results = analyzer.generate_results()?;
+    let sample = params
+        .retain_cross_file_sample
+        .then(|| CrossFileSample::from_analyzer(&mut analyzer));
+    Ok((results, sample))
+}
 
-    let mut data = unsafe { Box::new_uninit_slice(length as usize).assume_init() };
-    file.read_exact(&mut data)?;
+/// The parameters of the analysis of a file of a directory, without cross-file samples
+/// a zstd dictionary or a size cache.
+fn directory_file_params<'a>(
+    schema: &'a Schema,
+    dir_cmd: &DirectoryCommand,
+    path: &'a PathBuf,
+) -> AnalyzeFileParams<'a> {
+    AnalyzeFileParams {
+        schema,
+        path,
+        bytes_per_element: bytes_per_element(schema),
+        offset: dir_cmd.offset,
+        length: dir_cmd.length,
+        offset_pct: dir_cmd.offset_pct,
+        length_pct: dir_cmd.length_pct,
+        zstd_compression_level: dir_cmd.zstd_compression_level,
+        zstd_calibration_level: dir_cmd.zstd_calibration_level,
+        zstd_strategy: dir_cmd.zstd_strategy,
+        compressor: dir_cmd.compressor,
+        retain_cross_file_sample: false,
+        include_estimate_breakdown: dir_cmd.show_estimate_terms,
+        track_value_transitions: dir_cmd.value_transitions,
+        max_value_transitions: dir_cmd.max_value_transitions,
+        max_distinct_values: dir_cmd.max_distinct_values,
+        strict: dir_cmd.strict,
+        byte_transpose: dir_cmd.byte_transpose,
+        entropy_coded_size: dir_cmd.entropy_coded_size,
+        order1_entropy: dir_cmd.show_extra_stats,
+        zstd_timing: dir_cmd.show_extra_stats,
+        run_stats: dir_cmd.show_extra_stats,
+        entropy_window: dir_cmd.entropy_window,
+        zstd_dictionary: None,
+        metrics_cache: None,
+    }
+}
 
-    // Analyze the file with SchemaAnalyzer
-    let mut analyzer = SchemaAnalyzer::new(
-        params.schema,
-        CompressionOptions::default().with_zstd_compression_level(params.zstd_compression_level),
+/// Creates a progress bar of `len` steps drawn with `template`. It is hidden if `quiet`,
+/// or if stdout isn't a terminal, so that redirected output isn't cluttered by it.
+fn progress_bar(len: u64, template: &str, quiet: bool) -> ProgressBar {
+    let pb = ProgressBar::new(len);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template(template)
+            .unwrap()
+            .progress_chars("#>-"),
     );
-    let mut bytes_left = length;
+    if quiet || !stdout().is_terminal() {
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    pb
+}
 
-    while bytes_left > 0 {
-        let start_offset = length - bytes_left;
-        let slice =
-            &data[start_offset as usize..start_offset as usize + params.bytes_per_element as usize];
-        analyzer.add_entry(slice)?;
-        bytes_left -= params.bytes_per_element;
+/// Ensures the `--offset-pct` and `--length-pct` arguments are valid percentages,
+/// and aren't combined with their absolute (byte) counterparts.
+fn validate_region_percentages(
+    has_offset: bool,
+    has_length: bool,
+    offset_pct: Option<f64>,
+    length_pct: Option<f64>,
+) -> anyhow::Result<()> {
+    if has_offset && offset_pct.is_some() {
+        anyhow::bail!("`--offset` and `--offset-pct` can't be used together");
+    }
+    if has_length && length_pct.is_some() {
+        anyhow::bail!("`--length` and `--length-pct` can't be used together");
     }
 
-    // Output the analysis results here
-    Ok(analyzer.generate_results()?)
+    for pct in [offset_pct, length_pct].into_iter().flatten() {
+        if !(0.0..=100.0).contains(&pct) {
+            anyhow::bail!("Percentage {} must be between 0 and 100", pct);
+        }
+    }
+
+    Ok(())
+}
+
+/// Ensures the `--ground-truth` sizes are measured by the selected `--compressor`;
+/// otherwise the Brotli or Deflate ground truth would silently fall back to zstd sizes.
+fn validate_ground_truth(ground_truth: GroundTruth, compressor: Compressor) -> anyhow::Result<()> {
+    if ground_truth == GroundTruth::Brotli && !matches!(compressor, Compressor::Brotli { .. }) {
+        anyhow::bail!("`--ground-truth brotli` requires `--compressor brotli`");
+    }
+    if ground_truth == GroundTruth::Deflate && !matches!(compressor, Compressor::Deflate { .. }) {
+        anyhow::bail!("`--ground-truth deflate` requires `--compressor deflate`");
+    }
+
+    Ok(())
+}
+
+/// Ensures the `--ground-truth` sizes were measured when the saved results were analyzed,
+/// the counterpart of [`validate_ground_truth`] for results loaded from disk.
+fn validate_saved_ground_truth(
+    ground_truth: GroundTruth,
+    results: &[AnalysisResults],
+) -> anyhow::Result<()> {
+    if !results
+        .iter()
+        .all(|result| result.measures_ground_truth(ground_truth))
+    {
+        let name = ground_truth.name().to_lowercase();
+        anyhow::bail!(
+            "`--ground-truth {name}` requires results analyzed with `--compressor {name}`"
+        );
+    }
+
+    Ok(())
 }
 
 fn load_schema(schema_path: &Path) -> anyhow::Result<Schema> {
+    if is_stdin_schema(schema_path) {
+        return Ok(Schema::from_reader(io::stdin().lock())?);
+    }
     Ok(Schema::load_from_file(schema_path)?)
 }
 
+/// Whether the schema path argument is `-`, i.e. the schema is read from stdin.
+fn is_stdin_schema(schema_path: &Path) -> bool {
+    schema_path == Path::new("-")
+}
+
 fn find_directory_files_recursive(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    find_directory_files_filtered(path, &FileFilter::default())
+}
+
+/// Finds the files under `path` which are kept by `filter`.
+fn find_directory_files_filtered(path: &Path, filter: &FileFilter) -> anyhow::Result<Vec<PathBuf>> {
     let mut files = Vec::new();
     for entry in WalkDir::new(path)
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file())
+        .filter(|e| filter.matches(e.path().strip_prefix(path).unwrap_or(e.path())))
     {
         let metadata = std::fs::metadata(entry.path())?;
         files.push((entry.path().to_path_buf(), metadata.len()));
@@ -382,58 +410,3 @@ fn find_directory_files_recursive(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
 
     Ok(files.into_iter().map(|(path, _)| path).collect())
 }
-
-/// Write merged analysis results to a file
-fn write_merged_results_to_file(
-    merged_results: &MergedAnalysisResults,
-    output_dir: &Path,
-    schema: &Schema,
-    format: PrintFormat,
-    skip_misc_stats: bool,
-) -> std::io::Result<()> {
-    let output_path = output_dir.join("overall-result.txt");
-    let mut file = File::create(output_path)?;
-    merged_results.print(&mut file, schema, format, skip_misc_stats)?;
-    Ok(())
-}
-
-/// Write individual analysis results to files in a subdirectory
-fn write_individual_results_to_files(
-    individual_results: &[AnalysisResults],
-    output_dir: &Path,
-    schema: &Schema,
-    format: PrintFormat,
-    skip_misc_stats: bool,
-    files: &[PathBuf],
-) -> std::io::Result<()> {
-    // Create analysis_results directory
-    let results_dir = output_dir.join("analysis_results");
-    std::fs::create_dir_all(&results_dir)?;
-
-    // Write each file's results
-    for (x, result) in individual_results.iter().enumerate() {
-        let file_name = files[x]
-            .file_name()
-            .and_then(|os_str| os_str.to_str())
-            .map(|s| format!("{}.txt", s))
-            .unwrap_or_else(|| format!("result_{}.txt", x));
-
-        let output_path = results_dir.join(file_name);
-        let mut file = File::create(output_path)?;
-        result.print(&mut file, schema, format, skip_misc_stats)?;
-    }
-
-    Ok(())
-}
-
-/// Write optimization results to a file
-fn write_optimization_results_to_file(
-    split_results: &[(String, SplitComparisonOptimizationResult)],
-    custom_results: &[(String, CustomComparisonOptimizationResult)],
-    output_dir: &Path,
-) -> std::io::Result<()> {
-    let output_path = output_dir.join("brute-force-results.txt");
-    let mut file = File::create(output_path)?;
-    print_all_optimization_results(&mut file, split_results, custom_results)?;
-    Ok(())
-}
@@ -0,0 +1,59 @@
+//! Resolution of the `--reference-file` among the files of an analyzed directory.
+
+use std::path::{Path, PathBuf};
+
+/// Finds the `--reference-file` among the files to analyze, returning its path as listed.
+pub fn find_reference_file(files: &[PathBuf], reference: &Path) -> anyhow::Result<PathBuf> {
+    let canonical = reference.canonicalize().map_err(|e| {
+        anyhow::anyhow!("Reference file '{}' not found: {}", reference.display(), e)
+    })?;
+    files
+        .iter()
+        .find(|file| file.canonicalize().is_ok_and(|file| file == canonical))
+        .cloned()
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Reference file '{}' is not one of the analyzed files",
+                reference.display()
+            )
+        })
+}
+
+/// Index of the reference file (as returned by [`find_reference_file`]) among the files
+/// which analyzed successfully, i.e. in the results being compared.
+pub fn analyzed_reference_index(
+    analyzed_files: &[PathBuf],
+    reference: &Path,
+) -> anyhow::Result<usize> {
+    analyzed_files
+        .iter()
+        .position(|file| file == reference)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Reference file '{}' failed to analyze, so nothing can be compared against it",
+                reference.display()
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexes_reference_among_analyzed_files() {
+        // 'a.bin' failed to analyze, so the reference is the first analyzed file.
+        let analyzed = [PathBuf::from("dir/b.bin"), PathBuf::from("dir/c.bin")];
+        assert_eq!(
+            analyzed_reference_index(&analyzed, Path::new("dir/b.bin")).unwrap(),
+            0
+        );
+        assert_eq!(
+            analyzed_reference_index(&analyzed, Path::new("dir/c.bin")).unwrap(),
+            1
+        );
+
+        // The reference itself failed.
+        assert!(analyzed_reference_index(&analyzed, Path::new("dir/a.bin")).is_err());
+    }
+}
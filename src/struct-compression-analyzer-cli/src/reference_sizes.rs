@@ -0,0 +1,50 @@
+//! Loading of the reference sizes of the files of an analyzed directory.
+
+use anyhow::Context;
+use std::path::{Path, PathBuf};
+use struct_compression_analyzer::results::{
+    analysis_results::AnalysisResults, reference_sizes::ReferenceSizes,
+};
+
+/// Extension of the reference sizes file of each analyzed file.
+const REFERENCE_SIZES_EXTENSION: &str = "yaml";
+
+/// A directory mirroring the analyzed one, holding the reference sizes of each file.
+///
+/// The reference sizes of `<analyzed dir>/textures/a.dds` are read from
+/// `<reference dir>/textures/a.dds.yaml`; files without one have no reference sizes.
+pub struct DirectoryReferenceSizes {
+    /// The directory being analyzed; paths of the reference sizes are relative to this.
+    base_dir: PathBuf,
+    /// The directory holding the reference sizes.
+    reference_dir: PathBuf,
+}
+
+impl DirectoryReferenceSizes {
+    /// # Arguments
+    /// * `reference_dir` - The directory holding the reference sizes.
+    /// * `base_dir` - The directory being analyzed.
+    pub fn new(reference_dir: &Path, base_dir: &Path) -> Self {
+        Self {
+            base_dir: base_dir.to_path_buf(),
+            reference_dir: reference_dir.to_path_buf(),
+        }
+    }
+
+    /// Sets the reference sizes of a file found in the analyzed directory on its results,
+    /// if it has any.
+    pub fn apply(&self, path: &Path, results: &mut AnalysisResults) -> anyhow::Result<()> {
+        let relative = path.strip_prefix(&self.base_dir).unwrap_or(path);
+        let mut sizes_path = self.reference_dir.join(relative).into_os_string();
+        sizes_path.push(".");
+        sizes_path.push(REFERENCE_SIZES_EXTENSION);
+        let sizes_path = PathBuf::from(sizes_path);
+        if !sizes_path.is_file() {
+            return Ok(());
+        }
+
+        ReferenceSizes::load(&sizes_path)
+            .and_then(|sizes| sizes.apply(results))
+            .with_context(|| format!("Failed to apply reference sizes {}", sizes_path.display()))
+    }
+}
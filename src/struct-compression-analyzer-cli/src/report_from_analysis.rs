@@ -0,0 +1,114 @@
+//! Regenerates the reports of a directory from its saved results, without the analyzed files.
+//! Used by both the `report-from-analysis` command and the directory's `--from-results`.
+
+use crate::{
+    load_schema, validate_saved_ground_truth, DirectoryCommand, ReportFromAnalysisCommand,
+};
+use std::path::Path;
+use struct_compression_analyzer::{
+    csv,
+    plot::{generate_plots, PlotOptions},
+    results::{merged_analysis_results::MergedAnalysisResults, saved_results::SavedResults},
+    schema::Schema,
+};
+
+impl ReportFromAnalysisCommand {
+    /// The equivalent of a directory's `--from-results`, reusing its output, schema and plot options.
+    pub fn from_directory(dir_cmd: &DirectoryCommand, results: &Path) -> anyhow::Result<Self> {
+        let Some(output) = &dir_cmd.output else {
+            anyhow::bail!("`--from-results` requires an `--output` directory");
+        };
+
+        Ok(Self {
+            results: results.to_path_buf(),
+            output: output.clone(),
+            schema: Some(dir_cmd.schema.clone()),
+            output_prefix: dir_cmd.output_prefix.clone(),
+            plot_only: dir_cmd.plot_only,
+            csv_only: dir_cmd.csv_only,
+            plot_scale: dir_cmd.plot_scale,
+            plot_format: dir_cmd.plot_format,
+            plot_y_range: dir_cmd.plot_y_range,
+            ground_truth: dir_cmd.ground_truth,
+        })
+    }
+}
+
+/// Loads the saved results and schema, then writes the CSVs and/or plots to the output directory.
+pub fn run(cmd: &ReportFromAnalysisCommand) -> anyhow::Result<()> {
+    if cmd.plot_only && cmd.csv_only {
+        anyhow::bail!("`--plot-only` and `--csv-only` can't be used together");
+    }
+    if !(cmd.plot_scale.is_finite() && cmd.plot_scale > 0.0) {
+        anyhow::bail!("Plot scale must be greater than 0");
+    }
+
+    let saved = SavedResults::load(&cmd.results)?;
+    validate_saved_ground_truth(cmd.ground_truth, &saved.results)?;
+    let schema = match &cmd.schema {
+        Some(schema_path) => load_schema(schema_path)?,
+        None => saved.load_schema()?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "{} doesn't include the schema, pass it with `--schema`",
+                cmd.results.display()
+            )
+        })?,
+    };
+    let merged_results = saved
+        .to_merged_results()?
+        .with_ground_truth(cmd.ground_truth);
+    println!(
+        "Loaded results of {} files from: {}",
+        saved.results.len(),
+        cmd.results.display()
+    );
+
+    let plot_options = PlotOptions::default()
+        .with_scale(cmd.plot_scale)
+        .with_format(cmd.plot_format)
+        .with_y_range(cmd.plot_y_range);
+    write_saved_reports(
+        &saved,
+        &merged_results,
+        &schema,
+        &cmd.output,
+        &cmd.output_prefix,
+        !cmd.plot_only,
+        (!cmd.csv_only).then_some(plot_options),
+    )
+}
+
+/// Writes the CSVs (if `write_csvs`) and plots (if there are `plot_options`) of saved results.
+fn write_saved_reports(
+    saved: &SavedResults,
+    merged_results: &MergedAnalysisResults,
+    schema: &Schema,
+    output_dir: &Path,
+    prefix: &str,
+    write_csvs: bool,
+    plot_options: Option<PlotOptions>,
+) -> anyhow::Result<()> {
+    std::fs::create_dir_all(output_dir)?;
+    if write_csvs {
+        csv::write_all_csvs(
+            &merged_results.original_results,
+            merged_results,
+            schema,
+            output_dir,
+            &saved.file_paths,
+            prefix,
+        )?;
+    }
+    if let Some(plot_options) = plot_options {
+        generate_plots(
+            &merged_results.original_results,
+            output_dir,
+            prefix,
+            &plot_options,
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to generate plots: {e}"))?;
+    }
+
+    println!("Generated reports in: {}", output_dir.display());
+    Ok(())
+}
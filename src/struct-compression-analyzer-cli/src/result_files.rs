@@ -0,0 +1,118 @@
+//! Writers of the results of a directory, to its output directory and other files.
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+use struct_compression_analyzer::{
+    results::{
+        analysis_results::AnalysisResults, color::ColorMode, cross_file_results::CrossFileResults,
+        field_tree::FieldTree, merged_analysis_results::MergedAnalysisResults, PrintFormat,
+    },
+    schema::Schema,
+};
+
+/// Write the merged results of a run to an SQLite database, for `--sqlite`.
+#[cfg(feature = "sqlite")]
+pub fn write_sqlite(
+    merged_results: &MergedAnalysisResults,
+    schema: &Schema,
+    run_label: &str,
+    database_path: &Path,
+) -> anyhow::Result<()> {
+    struct_compression_analyzer::sqlite::write_sqlite(
+        merged_results,
+        schema,
+        run_label,
+        database_path,
+    )?;
+    println!(
+        "Wrote results to SQLite database: {}",
+        database_path.display()
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub fn write_sqlite(
+    _merged_results: &MergedAnalysisResults,
+    _schema: &Schema,
+    _run_label: &str,
+    _database_path: &Path,
+) -> anyhow::Result<()> {
+    anyhow::bail!("`--sqlite` requires building with the `sqlite` feature")
+}
+
+/// Write the per-field metrics as JSON, nested following the schema's groups
+pub fn write_field_tree_json(tree: &FieldTree, path: &Path) -> anyhow::Result<()> {
+    serde_json::to_writer_pretty(File::create(path)?, tree)?;
+    println!("Wrote field tree to: {}", path.display());
+    Ok(())
+}
+
+/// Write merged analysis results to a file
+pub fn write_merged_results_to_file(
+    merged_results: &MergedAnalysisResults,
+    output_dir: &Path,
+    schema: &Schema,
+    format: PrintFormat,
+    skip_misc_stats: bool,
+    prefix: &str,
+) -> std::io::Result<()> {
+    let extension = results_extension(format);
+    let output_path = output_dir.join(format!("{prefix}overall-result.{extension}"));
+    let mut file = File::create(output_path)?;
+    merged_results.print(&mut file, schema, format, skip_misc_stats, ColorMode::Never)?;
+    Ok(())
+}
+
+/// Extension of the files the results are written to in the given format.
+fn results_extension(format: PrintFormat) -> &'static str {
+    match format {
+        PrintFormat::Json => "json",
+        PrintFormat::Detailed | PrintFormat::Concise => "txt",
+    }
+}
+
+/// Write individual analysis results to files in a subdirectory
+pub fn write_individual_results_to_files(
+    individual_results: &[AnalysisResults],
+    output_dir: &Path,
+    schema: &Schema,
+    format: PrintFormat,
+    skip_misc_stats: bool,
+    files: &[PathBuf],
+    prefix: &str,
+) -> std::io::Result<()> {
+    // Create analysis_results directory
+    let results_dir = output_dir.join(format!("{prefix}analysis_results"));
+    std::fs::create_dir_all(&results_dir)?;
+
+    // Write each file's results
+    for (x, result) in individual_results.iter().enumerate() {
+        let file_name = files[x]
+            .file_name()
+            .and_then(|os_str| os_str.to_str())
+            .map(|s| format!("{}.{}", s, results_extension(format)))
+            .unwrap_or_else(|| format!("result_{}.{}", x, results_extension(format)));
+
+        let output_path = results_dir.join(file_name);
+        let mut file = File::create(output_path)?;
+        result.print(&mut file, schema, format, skip_misc_stats)?;
+    }
+
+    Ok(())
+}
+
+/// Write cross-file redundancy results to a file
+pub fn write_cross_file_results_to_file(
+    cross_file_results: &CrossFileResults,
+    output_dir: &Path,
+    schema: &Schema,
+    prefix: &str,
+) -> std::io::Result<()> {
+    let output_path = output_dir.join(format!("{prefix}cross-file-results.txt"));
+    let mut file = File::create(output_path)?;
+    cross_file_results.print(&mut file, schema)?;
+    Ok(())
+}
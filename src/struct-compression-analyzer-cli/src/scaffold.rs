@@ -0,0 +1,133 @@
+//! Generates a starter schema from a sample file, to make authoring the first schema easier.
+
+use std::fmt::Write;
+
+/// Generates a skeleton YAML schema for records of `record_size` bytes.
+///
+/// The schema has a single catch-all field spanning the whole record, and commented
+/// examples of split and custom comparisons which can be filled in once the record
+/// has been broken down into fields.
+///
+/// # Arguments
+/// * `name` - Name of the schema, usually derived from the sample file.
+/// * `record_size` - Size of a single record (struct) in bytes.
+/// * `sample_len` - Size of the sample file in bytes.
+pub fn generate_scaffold_schema(name: &str, record_size: u64, sample_len: u64) -> String {
+    let bits = record_size * 8;
+    let record_count = sample_len / record_size;
+    let trailing_bytes = sample_len % record_size;
+
+    let mut yaml = String::new();
+    // Writing to a String can't fail.
+    let _ = writeln!(yaml, "version: '1.0'");
+    let _ = writeln!(yaml, "metadata:");
+    let _ = writeln!(yaml, "  name: {}", yaml_string(name));
+    let _ = writeln!(yaml, "  description: |");
+    let _ = writeln!(
+        yaml,
+        "    Starter schema for {} byte records. Generated from a sample of {} bytes ({} records).",
+        record_size, sample_len, record_count
+    );
+    if trailing_bytes != 0 {
+        let _ = writeln!(
+            yaml,
+            "    Sample size is not a multiple of the record size ({} trailing bytes); \
+             the data may have a header, see `conditional_offsets`.",
+            trailing_bytes
+        );
+    }
+
+    let _ = write!(
+        yaml,
+        r#"
+# Uncomment to skip a file header, when the data matches a magic value.
+# conditional_offsets:
+#   - offset: 0x80  # data starts after a 128 byte header
+#     conditions:
+#       - byte_offset: 0x00
+#         bit_offset: 0
+#         bits: 32
+#         value: 0x44445320 # file magic
+
+analysis:
+  # Compare a group against its split components (e.g. interleaved vs separated layout).
+  split_groups: []
+  #  - name: split_data
+  #    group_1: [data]             # Base group to compare against.
+  #    group_2: [field_a, field_b] # Derived group to compare with.
+  #    description: Compare the interleaved record against its fields laid out one after another.
+
+  # Compare custom arrangements of fields against a baseline.
+  compare_groups: []
+  #  - name: reorder_fields
+  #    description: Place all 'field_b' values before all 'field_a' values.
+  #    baseline:
+  #      - {{ type: array, field: field_a }}
+  #      - {{ type: array, field: field_b }}
+  #    comparisons:
+  #      b_then_a:
+  #        - {{ type: array, field: field_b }}
+  #        - {{ type: array, field: field_a }}
+
+root:
+  type: group
+  fields:
+    # Catch-all field covering the whole record. Break it down into individual fields,
+    # e.g. `field_a: 16` and `field_b: {bits_minus_16}`; the sizes must add up to {bits} bits.
+    data:
+      type: field
+      bits: {bits}
+      description: Entire {record_size} byte record
+"#,
+        bits_minus_16 = bits.saturating_sub(16),
+    );
+
+    // Counting values of large fields is slow and not meaningful.
+    if bits > 24 {
+        let _ = writeln!(yaml, "      skip_frequency_analysis: true");
+    }
+
+    yaml
+}
+
+/// Quotes a string for use as a YAML scalar.
+fn yaml_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use struct_compression_analyzer::schema::{Field, FieldDefinition, Schema};
+
+    /// Parses a scaffold and returns its schema and catch-all field.
+    fn parse_scaffold(record_size: u64, sample_len: u64) -> (Schema, Field) {
+        let yaml = generate_scaffold_schema("it's a test", record_size, sample_len);
+        let mut schema = Schema::from_yaml(&yaml).unwrap();
+        let Some(FieldDefinition::Field(field)) = schema.root.fields.shift_remove("data") else {
+            panic!("scaffold should have a single 'data' field");
+        };
+        (schema, field)
+    }
+
+    #[test]
+    fn generates_valid_schema() {
+        let (schema, field) = parse_scaffold(8, 800);
+        assert_eq!(schema.metadata.name, "it's a test");
+        assert!(schema.metadata.description.contains("(100 records)"));
+        assert!(!schema.metadata.description.contains("trailing bytes"));
+        assert_eq!(field.bits, 64);
+    }
+
+    #[test]
+    fn notes_trailing_bytes() {
+        let (schema, _) = parse_scaffold(8, 803);
+        assert!(schema.metadata.description.contains("(3 trailing bytes)"));
+    }
+
+    #[test]
+    fn skips_frequency_analysis_of_large_records() {
+        assert!(!parse_scaffold(3, 300).1.skip_frequency_analysis);
+        assert!(parse_scaffold(4, 400).1.skip_frequency_analysis);
+    }
+}
@@ -0,0 +1,56 @@
+//! Training of the zstd dictionary shared by all files of a directory, for `--train-zstd-dict`.
+
+use crate::{directory_file_params, file_data::ingest_file, DirectoryCommand};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use std::{path::PathBuf, sync::Arc, time::Instant};
+use struct_compression_analyzer::{
+    schema::Schema,
+    utils::zstd_dictionary::{ZstdDictionarySamples, DEFAULT_ZSTD_DICTIONARY_SIZE},
+};
+
+/// Maximum number of files sampled to train a zstd dictionary with `--train-zstd-dict`.
+const MAX_ZSTD_DICTIONARY_FILES: usize = 64;
+
+/// Trains a zstd dictionary on the groups of up to [`MAX_ZSTD_DICTIONARY_FILES`] files,
+/// spread evenly over the directory. Prints a note and returns [`None`] if it can't be trained,
+/// e.g. due to too few samples.
+pub fn train_zstd_dictionary(
+    schema: &Schema,
+    dir_cmd: &DirectoryCommand,
+    files: &[PathBuf],
+) -> Option<Arc<[u8]>> {
+    let train_start_time = Instant::now();
+    let step = files.len().div_ceil(MAX_ZSTD_DICTIONARY_FILES).max(1);
+    let sampled_files: Vec<&PathBuf> = files.iter().step_by(step).collect();
+    let samples = sampled_files
+        .par_iter()
+        .with_max_len(1)
+        .filter_map(|path| {
+            // Files which fail to analyze are reported by the analysis itself.
+            let mut analyzer = ingest_file(&directory_file_params(schema, dir_cmd, path)).ok()?;
+            let mut samples = ZstdDictionarySamples::new();
+            samples.add_analyzer(&mut analyzer);
+            Some(samples)
+        })
+        .reduce(ZstdDictionarySamples::new, |mut a, b| {
+            a.extend(b);
+            a
+        });
+
+    match samples.train(DEFAULT_ZSTD_DICTIONARY_SIZE) {
+        Ok(dictionary) => {
+            println!(
+                "{}ms... Trained a {} byte zstd dictionary from {} samples of {} files.",
+                train_start_time.elapsed().as_millis(),
+                dictionary.len(),
+                samples.len(),
+                sampled_files.len()
+            );
+            Some(Arc::from(dictionary))
+        }
+        Err(e) => {
+            eprintln!("Note: Skipping zstd dictionary: {}", e);
+            None
+        }
+    }
+}
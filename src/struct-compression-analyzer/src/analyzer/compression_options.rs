@@ -0,0 +1,405 @@
+//! Options configuring how the analyzed data is compressed and its size estimated.
+//! See [`CompressionOptions`].
+
+use crate::utils::analyze_utils::{
+    calculate_file_entropy_order1, get_zstd_compressed_size_with_strategy, size_estimate,
+    size_estimate_breakdown, SizeEstimateBreakdown, ZstdStrategy,
+};
+use crate::utils::compressor::{
+    get_brotli_compressed_size, get_deflate_compressed_size, Compressor,
+};
+use crate::utils::metrics_cache::CachedRegion;
+use crate::utils::size_estimate_expression::SizeEstimateExpression;
+use crate::utils::zstd_dictionary::get_zstd_dict_compressed_size;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Struct to encapsulate parameters for size estimation functions.
+/// Functions accept this struct return an estimated size in bytes.
+#[derive(Debug, Clone, Copy)]
+pub struct SizeEstimationParameters<'a> {
+    /// The name of the caller.
+    /// This is the name of a `split_comparison` or a `custom_comparison`.
+    /// This lets you use different functions for different callers, if using the API.
+    pub name: &'a str,
+    /// The length of the raw bytes of the data.
+    pub data_len: usize,
+    /// The raw bytes of the data.
+    /// Only available when making the comparison initial time, not post process.
+    pub data: Option<&'a [u8]>,
+    /// The number of LZ matches found in the data.
+    pub num_lz_matches: usize,
+    /// The estimated entropy of the data.
+    pub entropy: f64,
+    /// LZ Match Multiplier (user provided)
+    pub lz_match_multiplier: f64,
+    /// Entropy Multiplier (user provided)
+    pub entropy_multiplier: f64,
+}
+
+/// Function pointer type for size estimation functions.
+///
+/// Takes the uncompressed data and [`SizeEstimationParameters`] and returns the estimated size in bytes.
+pub type SizeEstimatorFn = fn(SizeEstimationParameters) -> usize;
+
+/// Options to configure the behavior of compression when analysing schemas.
+#[derive(Debug, Clone)]
+pub struct CompressionOptions {
+    /// The zstd compression level to use.
+    /// Usually '7' is good enough to represent the data well at runtime,
+    /// but we default to higher for accuracy when analyzing.
+    pub zstd_compression_level: i32,
+    /// The zstd compression level of the sizes the estimator is calibrated against
+    /// ([`GroupComparisonMetrics::zstd_calibration_size`]), if it differs from
+    /// [`Self::zstd_compression_level`], which is used for the reported sizes.
+    /// `None` (the default) to calibrate against the reported sizes.
+    ///
+    /// [`GroupComparisonMetrics::zstd_calibration_size`]: crate::comparison::GroupComparisonMetrics::zstd_calibration_size
+    pub zstd_calibration_level: Option<i32>,
+    /// The zstd match finding strategy to use.
+    /// Defaults to the strategy implied by [`Self::zstd_compression_level`].
+    pub zstd_strategy: ZstdStrategy,
+    /// Function pointer to use for size estimation.
+    /// The function takes [`SizeEstimationParameters`] and returns the estimated size in bytes.
+    pub size_estimator_fn: SizeEstimatorFn,
+    /// User provided formula to use for size estimation, overrides [`Self::size_estimator_fn`].
+    /// If not set, this is taken from [`AnalysisConfig::size_estimate`] of the schema.
+    ///
+    /// [`AnalysisConfig::size_estimate`]: crate::schema::AnalysisConfig::size_estimate
+    pub size_estimate_expression: Option<Arc<SizeEstimateExpression>>,
+    /// LZ Match Multiplier (user provided)
+    pub lz_match_multiplier: f64,
+    /// Entropy Multiplier (user provided)
+    pub entropy_multiplier: f64,
+    /// Whether to record the intermediate terms of the default estimator
+    /// ([`SizeEstimateBreakdown`]) for each group. Intended for debugging the estimator.
+    pub include_estimate_breakdown: bool,
+    /// Whether to count the transitions between consecutive values of each field
+    /// (previous value → current value). Intended for designing context models.
+    /// Only fields whose values are counted (up to 16 bits) are tracked.
+    pub track_value_transitions: bool,
+    /// Maximum number of distinct transitions tracked per field; fields exceeding this
+    /// stop being tracked. The number of transitions grows with the square of the number
+    /// of unique values, so this bounds memory usage.
+    pub max_value_transitions: usize,
+    /// Maximum number of distinct values counted per field. Once reached, new values are no
+    /// longer added to the value counts (values already seen are still counted), and the field
+    /// is marked as overflowed ([`FieldMetrics::value_counts_overflowed`]). This bounds memory usage.
+    ///
+    /// [`FieldMetrics::value_counts_overflowed`]: crate::results::FieldMetrics::value_counts_overflowed
+    pub max_distinct_values: usize,
+    /// Whether to treat problems in the schema which are otherwise reported as warnings
+    /// as errors, e.g. a split comparison group which resolves to no fields.
+    pub strict: bool,
+    /// Whether to add the built-in [`BYTE_TRANSPOSE_COMPARISON_NAME`] custom comparison,
+    /// which compares the records against the same records with their bytes transposed.
+    ///
+    /// [`BYTE_TRANSPOSE_COMPARISON_NAME`]: crate::comparison::compare_groups::BYTE_TRANSPOSE_COMPARISON_NAME
+    pub byte_transpose_comparison: bool,
+    /// Whether to measure the size of each field coded with an order-0 range coder
+    /// ([`FieldMetrics::entropy_coded_size`]). This encodes every field once more.
+    ///
+    /// [`FieldMetrics::entropy_coded_size`]: crate::results::FieldMetrics::entropy_coded_size
+    pub measure_entropy_coded_size: bool,
+    /// Whether to calculate the order-1 (conditional) entropy of each field and group
+    /// ([`FieldMetrics::entropy_order1`]). This counts every byte once more.
+    ///
+    /// [`FieldMetrics::entropy_order1`]: crate::results::FieldMetrics::entropy_order1
+    pub measure_order1_entropy: bool,
+    /// Whether to calculate the run-length statistics of each field ([`FieldMetrics::run_stats`]).
+    /// This reads every value once more.
+    ///
+    /// [`FieldMetrics::run_stats`]: crate::results::FieldMetrics::run_stats
+    pub measure_run_stats: bool,
+    /// Whether to measure the time taken to compress each group with zstd
+    /// ([`GroupComparisonMetrics::zstd_compress_ns`]). Timed groups are always compressed,
+    /// bypassing [`Self::metrics_cache`], and the times are noisy; meant for comparing layouts.
+    ///
+    /// [`GroupComparisonMetrics::zstd_compress_ns`]: crate::comparison::GroupComparisonMetrics::zstd_compress_ns
+    pub measure_zstd_time: bool,
+    /// Number of entries per window of [`FieldMetrics::windowed_entropy`], i.e. records the
+    /// entropy of each field per this many records, to see how it drifts across the file.
+    /// `None` (the default) to not record it.
+    ///
+    /// [`FieldMetrics::windowed_entropy`]: crate::results::FieldMetrics::windowed_entropy
+    pub entropy_window: Option<usize>,
+    /// The compressor measuring the actual size of each group, besides zstd.
+    /// With [`Compressor::Brotli`], groups also get a [`GroupComparisonMetrics::brotli_size`];
+    /// with [`Compressor::Deflate`], a [`GroupComparisonMetrics::deflate_size`].
+    ///
+    /// [`GroupComparisonMetrics::brotli_size`]: crate::comparison::GroupComparisonMetrics::brotli_size
+    /// [`GroupComparisonMetrics::deflate_size`]: crate::comparison::GroupComparisonMetrics::deflate_size
+    pub compressor: Compressor,
+    /// A zstd dictionary shared by all files, see [`zstd_dictionary`].
+    /// When set, groups also get a [`GroupComparisonMetrics::zstd_dict_size`].
+    ///
+    /// [`zstd_dictionary`]: crate::utils::zstd_dictionary
+    /// [`GroupComparisonMetrics::zstd_dict_size`]: crate::comparison::GroupComparisonMetrics::zstd_dict_size
+    pub zstd_dictionary: Option<Arc<[u8]>>,
+    /// The analyzed region of a file, whose group metrics are looked up in a cache
+    /// before compressing, see [`metrics_cache`].
+    ///
+    /// [`metrics_cache`]: crate::utils::metrics_cache
+    pub metrics_cache: Option<CachedRegion>,
+}
+
+/// Default for [`CompressionOptions::max_value_transitions`].
+pub const DEFAULT_MAX_VALUE_TRANSITIONS: usize = 1 << 16;
+
+/// Default for [`CompressionOptions::max_distinct_values`].
+/// Values are only counted for fields of up to 16 bits, so by default no field overflows.
+pub const DEFAULT_MAX_DISTINCT_VALUES: usize = 1 << 16;
+
+impl Default for CompressionOptions {
+    fn default() -> Self {
+        Self {
+            zstd_compression_level: 16,
+            zstd_calibration_level: None,
+            zstd_strategy: ZstdStrategy::Default,
+            size_estimator_fn: size_estimate,
+            size_estimate_expression: None,
+            lz_match_multiplier: 0.0,
+            entropy_multiplier: 0.0,
+            include_estimate_breakdown: false,
+            track_value_transitions: false,
+            max_value_transitions: DEFAULT_MAX_VALUE_TRANSITIONS,
+            max_distinct_values: DEFAULT_MAX_DISTINCT_VALUES,
+            strict: false,
+            byte_transpose_comparison: false,
+            measure_entropy_coded_size: false,
+            measure_order1_entropy: false,
+            measure_run_stats: false,
+            measure_zstd_time: false,
+            entropy_window: None,
+            compressor: Compressor::Zstd,
+            zstd_dictionary: None,
+            metrics_cache: None,
+        }
+    }
+}
+
+impl CompressionOptions {
+    /// Sets the zstd compression level.
+    /// Usually '7' is good enough to represent the data well at runtime,
+    /// but we default to higher for accuracy when analyzing.
+    pub fn with_zstd_compression_level(mut self, level: i32) -> Self {
+        self.zstd_compression_level = level;
+        self
+    }
+
+    /// Sets the zstd strategy, overriding the strategy implied by the compression level.
+    pub fn with_zstd_strategy(mut self, strategy: ZstdStrategy) -> Self {
+        self.zstd_strategy = strategy;
+        self
+    }
+
+    /// Sets the zstd compression level of the sizes the estimator is calibrated against,
+    /// see [`Self::zstd_calibration_level`].
+    pub fn with_zstd_calibration_level(mut self, level: Option<i32>) -> Self {
+        self.zstd_calibration_level = level;
+        self
+    }
+
+    /// Determines the actual size of the data when compressed with the configured
+    /// zstd level and strategy.
+    pub fn zstd_compressed_size(&self, data: &[u8]) -> u64 {
+        self.zstd_compressed_size_at_level(data, self.zstd_compression_level)
+    }
+
+    /// Determines the size of the data when compressed with zstd at
+    /// [`Self::zstd_calibration_level`], or [`None`] if that is not set
+    /// or equal to [`Self::zstd_compression_level`].
+    pub fn zstd_calibration_size(&self, data: &[u8]) -> Option<u64> {
+        self.zstd_calibration_level
+            .filter(|&level| level != self.zstd_compression_level)
+            .map(|level| self.zstd_compressed_size_at_level(data, level))
+    }
+
+    fn zstd_compressed_size_at_level(&self, data: &[u8], level: i32) -> u64 {
+        get_zstd_compressed_size_with_strategy(data, level, self.zstd_strategy)
+    }
+
+    /// Determines the size of the data when compressed with zstd, like
+    /// [`Self::zstd_compressed_size`], and the time taken to compress it in nanoseconds,
+    /// or 0 if [`Self::measure_zstd_time`] is not set.
+    pub fn zstd_compressed_size_timed(&self, data: &[u8]) -> (u64, u64) {
+        if !self.measure_zstd_time {
+            return (self.zstd_compressed_size(data), 0);
+        }
+
+        let start = Instant::now();
+        let size = get_zstd_compressed_size_with_strategy(
+            data,
+            self.zstd_compression_level,
+            self.zstd_strategy,
+        );
+        // At least 1ns, as 0 means the time wasn't measured.
+        (size, (start.elapsed().as_nanos() as u64).max(1))
+    }
+
+    /// Sets whether to measure the time taken to compress each group with zstd.
+    pub fn with_zstd_timing(mut self, enabled: bool) -> Self {
+        self.measure_zstd_time = enabled;
+        self
+    }
+
+    /// Sets the compressor measuring the actual size of each group, besides zstd.
+    pub fn with_compressor(mut self, compressor: Compressor) -> Self {
+        self.compressor = compressor;
+        self
+    }
+
+    /// Sets the zstd dictionary shared by all files, see [`Self::zstd_dictionary`].
+    pub fn with_zstd_dictionary(mut self, dictionary: impl Into<Arc<[u8]>>) -> Self {
+        self.zstd_dictionary = Some(dictionary.into());
+        self
+    }
+
+    /// Determines the size of the data when compressed with the configured zstd level,
+    /// strategy and [`Self::zstd_dictionary`], or [`None`] if there is no dictionary.
+    pub fn zstd_dict_compressed_size(&self, data: &[u8]) -> Option<u64> {
+        self.zstd_dictionary.as_deref().map(|dictionary| {
+            get_zstd_dict_compressed_size(
+                data,
+                dictionary,
+                self.zstd_compression_level,
+                self.zstd_strategy,
+            )
+        })
+    }
+
+    /// Determines the size of the data when compressed with Brotli,
+    /// or [`None`] if [`Self::compressor`] is not [`Compressor::Brotli`].
+    pub fn brotli_compressed_size(&self, data: &[u8]) -> Option<u64> {
+        match self.compressor {
+            Compressor::Brotli { quality, window } => {
+                Some(get_brotli_compressed_size(data, quality, window))
+            }
+            Compressor::Zstd | Compressor::Deflate { .. } => None,
+        }
+    }
+
+    /// Determines the size of the data when compressed with Deflate,
+    /// or [`None`] if [`Self::compressor`] is not [`Compressor::Deflate`].
+    pub fn deflate_compressed_size(&self, data: &[u8]) -> Option<u64> {
+        match self.compressor {
+            Compressor::Deflate { level } => Some(get_deflate_compressed_size(data, level)),
+            Compressor::Zstd | Compressor::Brotli { .. } => None,
+        }
+    }
+
+    /// Sets the analyzed region of a file, whose group metrics are cached,
+    /// see [`Self::metrics_cache`].
+    pub fn with_metrics_cache(mut self, region: CachedRegion) -> Self {
+        self.metrics_cache = Some(region);
+        self
+    }
+
+    /// Sets the size estimator function.
+    /// The function takes in the `uncompressed data` and [`SizeEstimationParameters`]
+    /// and returns the estimated size of the compressed data in bytes.
+    pub fn with_size_estimator_fn(mut self, estimator_fn: SizeEstimatorFn) -> Self {
+        self.size_estimator_fn = estimator_fn;
+        self
+    }
+
+    /// Sets the formula used for size estimation.
+    /// When set, this takes precedence over the size estimator function.
+    pub fn with_size_estimate_expression(mut self, expression: SizeEstimateExpression) -> Self {
+        self.size_estimate_expression = Some(Arc::new(expression));
+        self
+    }
+
+    /// Sets whether to record the intermediate terms of the default estimator for each group.
+    pub fn with_estimate_breakdown(mut self, include: bool) -> Self {
+        self.include_estimate_breakdown = include;
+        self
+    }
+
+    /// Sets whether to count the transitions between consecutive values of each field.
+    pub fn with_value_transitions(mut self, track: bool) -> Self {
+        self.track_value_transitions = track;
+        self
+    }
+
+    /// Sets the maximum number of distinct value transitions tracked per field.
+    pub fn with_max_value_transitions(mut self, max: usize) -> Self {
+        self.max_value_transitions = max;
+        self
+    }
+
+    /// Sets the maximum number of distinct values counted per field.
+    pub fn with_max_distinct_values(mut self, max: usize) -> Self {
+        self.max_distinct_values = max;
+        self
+    }
+
+    /// Sets whether to treat schema problems which are otherwise reported as warnings as errors.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets whether to add the built-in byte transpose comparison to the custom comparisons.
+    pub fn with_byte_transpose_comparison(mut self, enabled: bool) -> Self {
+        self.byte_transpose_comparison = enabled;
+        self
+    }
+
+    /// Sets whether to measure the order-0 range coded size of each field.
+    pub fn with_entropy_coded_size(mut self, enabled: bool) -> Self {
+        self.measure_entropy_coded_size = enabled;
+        self
+    }
+
+    /// Sets whether to calculate the order-1 (conditional) entropy of each field and group.
+    pub fn with_order1_entropy(mut self, enabled: bool) -> Self {
+        self.measure_order1_entropy = enabled;
+        self
+    }
+
+    /// Sets whether to calculate the run-length statistics of each field.
+    pub fn with_run_stats(mut self, enabled: bool) -> Self {
+        self.measure_run_stats = enabled;
+        self
+    }
+
+    /// Calculates the order-1 entropy of the data,
+    /// or [`None`] if [`Self::measure_order1_entropy`] is not set.
+    pub fn order1_entropy(&self, data: &[u8]) -> Option<f64> {
+        self.measure_order1_entropy
+            .then(|| calculate_file_entropy_order1(data))
+    }
+
+    /// Sets the number of entries per window of the windowed entropy of each field,
+    /// or `None` to not record it.
+    pub fn with_entropy_window(mut self, entries: Option<usize>) -> Self {
+        self.entropy_window = entries;
+        self
+    }
+
+    /// Returns the intermediate terms of the estimate, if requested via
+    /// [`Self::include_estimate_breakdown`].
+    ///
+    /// Only available when using the default estimator ([`size_estimate`]);
+    /// custom functions and formulas don't expose their terms.
+    pub fn estimate_size_breakdown(
+        &self,
+        params: SizeEstimationParameters,
+    ) -> Option<SizeEstimateBreakdown> {
+        let is_default_estimator = self.size_estimate_expression.is_none()
+            && std::ptr::fn_addr_eq(self.size_estimator_fn, size_estimate as SizeEstimatorFn);
+
+        (self.include_estimate_breakdown && is_default_estimator)
+            .then(|| size_estimate_breakdown(params))
+    }
+
+    /// Estimates the compressed size in bytes, using the user provided formula if
+    /// available, otherwise the size estimator function.
+    pub fn estimate_size(&self, params: SizeEstimationParameters) -> usize {
+        match &self.size_estimate_expression {
+            Some(expression) => expression.estimate_size(&params),
+            None => (self.size_estimator_fn)(params),
+        }
+    }
+}
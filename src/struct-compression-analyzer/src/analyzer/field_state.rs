@@ -0,0 +1,294 @@
+//! The intermediate state of each field and group while analyzing, see [`AnalyzerFieldState`].
+
+use super::{AnalysisError, CompressionOptions};
+use crate::schema::{BitOrder, Endian, FieldDefinition, Group, ValueInterpretation};
+use crate::utils::analyze_utils::{create_bit_writer, reverse_bits, BitWriterContainer};
+use crate::utils::constants::CHILD_MARKER;
+use ahash::{AHashMap, HashMapExt};
+use bitstream_io::{BitRead, BitReader, BitWrite, Endianness};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::io::Cursor;
+
+/// Intermediate statistics for a single field or group of fields
+pub struct AnalyzerFieldState {
+    /// Name of the field or group
+    pub name: String,
+    /// Name of the full path to the field or group
+    pub full_path: String,
+    /// The depth of the field in the group/field chain.
+    pub depth: usize,
+    /// Total number of observed values
+    pub count: u64,
+    /// Length of the field or group in bits.
+    pub lenbits: u32,
+    /// Bitstream writer for accumulating data belonging to this field or group.
+    /// The writer uses the endian inherited from the schema root.
+    pub writer: BitWriterContainer,
+    /// Bit-level statistics. Index of tuple is bit offset.
+    pub bit_counts: Vec<BitStats>,
+    /// The order of the bits within the field
+    pub bit_order: BitOrder,
+    /// The byte order of the value of the field. Values of little endian fields are stored
+    /// with their bytes swapped, i.e. most significant byte first.
+    /// Groups are always [`Endian::Big`], i.e. stored as-is.
+    pub byte_order: Endian,
+    /// How values of the field are rendered in value stats
+    pub interpretation: ValueInterpretation,
+    /// Whether the field is a reserved region, which should be constant.
+    pub reserved: bool,
+    /// Per-field override of [`CompressionOptions::lz_match_multiplier`].
+    pub lz_match_multiplier: Option<f64>,
+    /// Per-field override of [`CompressionOptions::entropy_multiplier`].
+    pub entropy_multiplier: Option<f64>,
+    /// Count of occurrences for each observed value
+    pub value_counts: FxHashMap<u64, u64>,
+    /// Whether values were left out of [`Self::value_counts`], because the field had more than
+    /// [`CompressionOptions::max_distinct_values`] distinct values.
+    pub value_counts_overflowed: bool,
+    /// (previous value, current value) → occurrence count.
+    /// `None` if not tracked, or if the field exceeded
+    /// [`CompressionOptions::max_value_transitions`].
+    pub value_transitions: Option<FxHashMap<(u64, u64), u64>>,
+    /// The last observed value, used for counting [`Self::value_transitions`].
+    pub previous_value: Option<u64>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BitStats {
+    /// Count of zero values observed at this bit position
+    pub zeros: u64,
+    /// Count of one values observed at this bit position
+    pub ones: u64,
+}
+
+/// Minimum share of the more common value for a bit to be considered near-constant.
+/// See [`BitStats::is_near_constant`].
+pub const NEAR_CONSTANT_BIT_THRESHOLD: f64 = 0.99;
+
+impl BitStats {
+    /// Binary entropy of the bit in bits, i.e. `-p·log2(p) - q·log2(q)`.
+    /// This is 0 for a bit which never changes (or was never observed), and 1 for a fully random bit.
+    pub fn entropy(&self) -> f64 {
+        let total = self.zeros + self.ones;
+        if total == 0 {
+            return 0.0;
+        }
+
+        [self.zeros, self.ones]
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| {
+                let p = count as f64 / total as f64;
+                -p * p.log2()
+            })
+            .sum()
+    }
+
+    /// Whether one value makes up more than [`NEAR_CONSTANT_BIT_THRESHOLD`] of the observations.
+    /// Such bits carry (almost) no information.
+    pub fn is_near_constant(&self) -> bool {
+        let total = self.zeros + self.ones;
+        total > 0 && self.zeros.max(self.ones) as f64 / total as f64 > NEAR_CONSTANT_BIT_THRESHOLD
+    }
+}
+
+pub(super) fn process_field_or_group<TEndian: Endianness>(
+    reader: &mut BitReader<Cursor<&[u8]>, TEndian>,
+    mut bit_count: u32,
+    field_stats: &mut AnalyzerFieldState,
+    skip_frequency_analysis: bool,
+    compression_options: &CompressionOptions,
+) -> Result<(), AnalysisError> {
+    let writer = &mut field_stats.writer;
+    // We don't support value counting for structs >8 bytes.
+    let can_bit_stats = bit_count <= 64;
+    let skip_count_values = bit_count > 16 || skip_frequency_analysis;
+
+    // Only whole bytes can be swapped, and only values read at once.
+    let byte_order = match bit_count {
+        bits if bits <= 64 && bits.is_multiple_of(8) => field_stats.byte_order,
+        _ => Endian::Big,
+    };
+
+    field_stats.count += 1;
+    while bit_count > 0 {
+        // Read max possible number of bits at once.
+        let max_bits = bit_count.min(64);
+        let bits = byte_order.read_value(reader.read_var::<u64>(max_bits)?, max_bits);
+
+        // Update the value counts
+        if !skip_count_values {
+            let value = if field_stats.bit_order == BitOrder::Lsb {
+                reverse_bits(max_bits, bits)
+            } else {
+                bits
+            };
+            count_value(
+                &mut field_stats.value_counts,
+                &mut field_stats.value_counts_overflowed,
+                value,
+                compression_options.max_distinct_values,
+            );
+            count_value_transition(
+                &mut field_stats.value_transitions,
+                &mut field_stats.previous_value,
+                value,
+                compression_options.max_value_transitions,
+            );
+        }
+
+        // Write the values to the output
+        match writer {
+            BitWriterContainer::Msb(w) => w.write_var(max_bits, bits)?,
+            BitWriterContainer::Lsb(w) => w.write_var(max_bits, bits)?,
+        }
+
+        // Update stats for individual bits.
+        if can_bit_stats {
+            for i in 0..max_bits {
+                let idx = i as usize;
+                let bit_value = (bits >> (max_bits - 1 - i)) & 1;
+                if bit_value == 0 {
+                    field_stats.bit_counts[idx].zeros += 1;
+                } else {
+                    field_stats.bit_counts[idx].ones += 1;
+                }
+            }
+        }
+
+        bit_count -= max_bits;
+    }
+
+    // Flush any remaining bits to ensure all data is written
+    match writer {
+        BitWriterContainer::Msb(w) => w.flush()?,
+        BitWriterContainer::Lsb(w) => w.flush()?,
+    }
+
+    Ok(())
+}
+
+/// Counts an occurrence of `value`, unless it's a new value and there are already
+/// `max_distinct_values` distinct values, in which case `overflowed` is set instead.
+#[inline]
+fn count_value(
+    value_counts: &mut FxHashMap<u64, u64>,
+    overflowed: &mut bool,
+    value: u64,
+    max_distinct_values: usize,
+) {
+    let distinct_values = value_counts.len();
+    match value_counts.entry(value) {
+        Entry::Occupied(mut entry) => *entry.get_mut() += 1,
+        Entry::Vacant(entry) if distinct_values < max_distinct_values => {
+            entry.insert(1);
+        }
+        Entry::Vacant(_) => *overflowed = true,
+    }
+}
+
+/// Records the transition from the previous value of a field to `value`, if tracked.
+fn count_value_transition(
+    value_transitions: &mut Option<FxHashMap<(u64, u64), u64>>,
+    previous_value: &mut Option<u64>,
+    value: u64,
+    max_value_transitions: usize,
+) {
+    let previous = previous_value.replace(value);
+    let (Some(transitions), Some(previous)) = (value_transitions.as_mut(), previous) else {
+        return;
+    };
+
+    *transitions.entry((previous, value)).or_insert(0) += 1;
+    if transitions.len() > max_value_transitions {
+        // Too many unique values to be useful; stop tracking to bound memory usage.
+        *value_transitions = None;
+    }
+}
+
+pub(super) fn build_field_stats<'a>(
+    group: &'a Group,
+    parent_path: &'a str,
+    depth: usize,
+    file_bit_order: BitOrder,
+) -> AHashMap<String, AnalyzerFieldState> {
+    let mut stats = AHashMap::new();
+
+    for (name, field) in &group.fields {
+        let path = if parent_path.is_empty() {
+            name.clone()
+        } else {
+            format!("{}{CHILD_MARKER}{}", parent_path, name)
+        };
+
+        match field {
+            FieldDefinition::Field(field) => {
+                let writer = create_bit_writer(file_bit_order);
+                stats.insert(
+                    name.clone(),
+                    AnalyzerFieldState {
+                        full_path: path,
+                        depth,
+                        lenbits: field.bits,
+                        count: 0,
+                        writer,
+                        bit_counts: vec![BitStats::default(); clamp_bits(field.bits as usize)],
+                        name: name.clone(),
+                        bit_order: field.bit_order.get_with_default_resolve(),
+                        byte_order: field.byte_order.unwrap_or_default(),
+                        interpretation: field.interpretation,
+                        reserved: field.reserved,
+                        lz_match_multiplier: field.lz_match_multiplier,
+                        entropy_multiplier: field.entropy_multiplier,
+                        value_counts: FxHashMap::new(),
+                        value_counts_overflowed: false,
+                        value_transitions: None,
+                        previous_value: None,
+                    },
+                );
+            }
+            FieldDefinition::Group(group) => {
+                let writer = create_bit_writer(file_bit_order);
+
+                // Add stats entry for the group itself
+                stats.insert(
+                    name.clone(),
+                    AnalyzerFieldState {
+                        full_path: path.clone(),
+                        depth,
+                        lenbits: group.bits,
+                        count: 0,
+                        writer,
+                        bit_counts: vec![BitStats::default(); clamp_bits(group.bits as usize)],
+                        name: name.clone(),
+                        bit_order: group.bit_order.get_with_default_resolve(),
+                        byte_order: Endian::Big,
+                        interpretation: ValueInterpretation::default(),
+                        reserved: false,
+                        lz_match_multiplier: None,
+                        entropy_multiplier: None,
+                        value_counts: FxHashMap::new(),
+                        value_counts_overflowed: false,
+                        value_transitions: None,
+                        previous_value: None,
+                    },
+                );
+
+                // Process nested fields
+                stats.extend(build_field_stats(group, &path, depth + 1, file_bit_order));
+            }
+        }
+    }
+
+    stats
+}
+
+fn clamp_bits(bits: usize) -> usize {
+    if bits > 64 {
+        0
+    } else {
+        bits
+    }
+}
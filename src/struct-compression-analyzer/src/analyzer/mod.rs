@@ -0,0 +1,1184 @@
+//! Analyzes binary data structures against schema definitions.
+//!
+//! This module implements analysis of binary data structures according to a defined schema.
+//! It handles both field and group analysis, maintaining statistics about the data including:
+//!
+//! - Bit-level statistics for compression analysis
+//! - Value frequency distributions
+//! - Conditional processing based on schema rules
+//!
+//! # Core Types
+//!
+//! - [`SchemaAnalyzer`]: Main analyzer that processes binary data.
+//! - [`CompressionOptions`]: Configuration options for the analyzer.
+//!
+//! # Internal Types
+//!
+//! - [`AnalyzerFieldState`]: Per-field statistics and analysis state (working state)
+//! - [`BitStats`]: Bit-level statistics for individual field positions (part of results)
+//!
+//! # Example
+//!
+//! ```rust no_run
+//! use struct_compression_analyzer::{schema::Schema, analyzer::{SchemaAnalyzer, CompressionOptions}};
+//! use anyhow::Result;
+//! use std::fs::read_to_string;
+//! use std::path::Path;
+//!
+//! async fn schema_analyzer_example() -> Result<()> {
+//!     // Load schema from disk.
+//!     let schema = Schema::load_from_file(Path::new("schema.yaml"))?;
+//!
+//!     // Create analysis options
+//!     let options = CompressionOptions::default();
+//!
+//!     // Create the analyzer from the schema, creating the initial state.
+//!     let mut analyzer = SchemaAnalyzer::new(&schema, options);
+//!
+//!     // Process multiple entries
+//!     // From binary file, or wherever they may come from.
+//!     analyzer.add_entry(&[0x01, 0x02, 0x03])?;
+//!     analyzer.add_entry(&[0x04, 0x05, 0x06])?;
+//!
+//!     // Generate final analysis
+//!     let results = analyzer.generate_results()?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! # Statistics and Metrics
+//!
+//! The analyzer tracks several key metrics:
+//!
+//! - Bit-level statistics (zero/one counts per bit position)
+//! - Value frequency distributions
+//! - Field-level bit order and alignment
+//! - Conditional processing outcomes
+
+use super::schema::{Group, Schema};
+use crate::comparison::split_comparison::{normalize_group_length, SplitComparisonBytes};
+use crate::results::analysis_results::AnalysisResults;
+use crate::results::analysis_results::{compute_analysis_results, split_group_bytes};
+use crate::results::ComputeAnalysisResultsError;
+use crate::schema::{Condition, FieldDefinition};
+use crate::utils::analyze_utils::{create_bit_reader, BitReaderContainer};
+use ahash::AHashMap;
+use bitstream_io::{BitRead, BitReader, Endianness};
+use rustc_hash::FxHashMap;
+use std::io::{Cursor, Read, SeekFrom};
+use std::sync::Arc;
+use thiserror::Error;
+
+pub use compression_options::{
+    CompressionOptions, SizeEstimationParameters, SizeEstimatorFn, DEFAULT_MAX_DISTINCT_VALUES,
+    DEFAULT_MAX_VALUE_TRANSITIONS,
+};
+use field_state::{build_field_stats, process_field_or_group};
+pub use field_state::{AnalyzerFieldState, BitStats, NEAR_CONSTANT_BIT_THRESHOLD};
+
+mod compression_options;
+mod field_state;
+mod sized_entries;
+
+/// Analyzes binary structures against a schema definition
+///
+/// Maintains state between data ingestion and final analysis:
+/// - Parsed schema structure
+/// - Accumulated raw data entries
+/// - Intermediate analysis state
+pub struct SchemaAnalyzer<'a> {
+    /// Schema definition tree
+    pub schema: &'a Schema,
+    /// Raw data as fed into the analyzer.
+    pub entries: Vec<u8>,
+    /// Intermediate analysis state (field name → statistics)
+    /// This supports both 'groups' and fields.
+    pub field_states: AHashMap<String, AnalyzerFieldState>,
+    /// Configuration options for analysis.
+    pub compression_options: CompressionOptions,
+}
+
+/// Errors that can occur during schema analysis.
+#[derive(Debug, Error)]
+pub enum AnalysisError {
+    #[error("I/O error in add_entry reader during analysis. This is indicative of a bug in schema parsing or sanitization; and should normally not happen. Details: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error(
+        "Field '{0}' not found in Analyzer. This is indicative of a bug and should not happen."
+    )]
+    FieldNotFound(String),
+
+    #[error("Invalid entry length: expected {expected}, got {found}")]
+    InvalidEntryLength { expected: usize, found: usize },
+
+    #[error(
+        "Entry {index} is truncated: expected {expected} bytes, but the reader ended after {found}"
+    )]
+    TruncatedEntry {
+        index: u64,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("Entry {index} has an element size selector value of {value}, which has no size in the schema's `element_size`")]
+    UnknownElementSize { index: u64, value: u64 },
+}
+
+/// Number of bytes read at once by [`SchemaAnalyzer::add_entries_from_reader`],
+/// rounded down to whole entries.
+const READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+impl<'a> SchemaAnalyzer<'a> {
+    /// Creates a new analyzer bound to a specific schema
+    ///
+    /// # Example
+    /// ```rust
+    /// # use struct_compression_analyzer::{schema::Schema, analyzer::{SchemaAnalyzer, CompressionOptions}};
+    /// # let schema = Schema::from_yaml("version: '1.0'\nroot: { type: group, fields: {} }").unwrap();
+    /// let options = CompressionOptions::default();
+    /// let analyzer = SchemaAnalyzer::new(&schema, options);
+    /// ```
+    pub fn new(schema: &'a Schema, mut options: CompressionOptions) -> Self {
+        if options.size_estimate_expression.is_none() {
+            options.size_estimate_expression = schema.analysis.size_estimate.clone().map(Arc::new);
+        }
+
+        let mut field_states = build_field_stats(&schema.root, "", 0, schema.bit_order);
+        if options.track_value_transitions {
+            for state in field_states.values_mut() {
+                state.value_transitions = Some(FxHashMap::default());
+            }
+        }
+
+        Self {
+            schema,
+            entries: Vec::new(),
+            field_states,
+            compression_options: options,
+        }
+    }
+
+    /// Ingests a raw binary entry for analysis
+    ///
+    /// # Arguments
+    /// * `entry` - Byte slice representing one instance of the schema structure
+    ///
+    /// # Notes
+    /// - Partial entries will be handled in future implementations
+    /// - This only reads up to the number of bits specified in the schema.
+    pub fn add_entry(&mut self, entry: &[u8]) -> Result<(), AnalysisError> {
+        self.entries.extend_from_slice(entry);
+
+        // Throw error if the entry length is less than the schema.
+        // Entries of varying size instead only hold the fields of their own variant.
+        if self.schema.element_size.is_none() && entry.len() * 8 < self.schema.root.bits as usize {
+            return Err(AnalysisError::InvalidEntryLength {
+                expected: self.schema.root.bits as usize,
+                found: self.entries.len() * 8,
+            });
+        }
+
+        let reader = create_bit_reader(entry, self.schema.bit_order);
+        match reader {
+            BitReaderContainer::Msb(mut bit_reader) => {
+                self.process_group(&self.schema.root, &mut bit_reader)
+            }
+            BitReaderContainer::Lsb(mut bit_reader) => {
+                self.process_group(&self.schema.root, &mut bit_reader)
+            }
+        }
+    }
+
+    /// Ingests `count` consecutive entries read from `reader`, without buffering all of them
+    /// up front. The results are identical to calling [`Self::add_entry`] for each entry.
+    ///
+    /// # Arguments
+    /// * `reader` - Source of the entries, positioned at the first entry.
+    /// * `bytes_per_element` - Size of each entry in bytes.
+    /// * `count` - Number of entries to read.
+    ///
+    /// # Errors
+    /// [`AnalysisError::TruncatedEntry`] if the reader ends before `count` entries were read.
+    pub fn add_entries_from_reader<R: Read>(
+        &mut self,
+        mut reader: R,
+        bytes_per_element: usize,
+        count: u64,
+    ) -> Result<(), AnalysisError> {
+        if bytes_per_element == 0 {
+            return Ok(());
+        }
+
+        let entries_per_chunk = (READ_CHUNK_SIZE / bytes_per_element).max(1) as u64;
+        let mut chunk = vec![0u8; entries_per_chunk.min(count) as usize * bytes_per_element];
+        let mut index = 0;
+        while index < count {
+            let entries = entries_per_chunk.min(count - index) as usize;
+            let chunk = &mut chunk[..entries * bytes_per_element];
+            let read = read_up_to(&mut reader, chunk)?;
+            if read < chunk.len() {
+                let complete = read / bytes_per_element;
+                return Err(AnalysisError::TruncatedEntry {
+                    index: index + complete as u64,
+                    expected: bytes_per_element,
+                    found: read % bytes_per_element,
+                });
+            }
+
+            for entry in chunk.chunks_exact(bytes_per_element) {
+                self.add_entry(entry)?;
+            }
+            index += entries as u64;
+        }
+        Ok(())
+    }
+
+    /// Ingests a continuous bitstream of records for analysis.
+    ///
+    /// Unlike [`Self::add_entry`], records are not re-aligned to a byte boundary;
+    /// each record starts at the bit where the previous one ended. This is used for
+    /// formats where fields may straddle the boundary between records.
+    /// See [`Schema::continuous`].
+    ///
+    /// # Arguments
+    /// * `data` - The bitstream, containing any number of back-to-back records.
+    ///
+    /// # Notes
+    /// - Trailing bits which don't form a complete record are ignored.
+    pub fn add_bitstream(&mut self, data: &[u8]) -> Result<(), AnalysisError> {
+        self.entries.extend_from_slice(data);
+
+        let reader = create_bit_reader(data, self.schema.bit_order);
+        match reader {
+            BitReaderContainer::Msb(mut bit_reader) => {
+                self.process_bitstream(data.len(), &mut bit_reader)
+            }
+            BitReaderContainer::Lsb(mut bit_reader) => {
+                self.process_bitstream(data.len(), &mut bit_reader)
+            }
+        }
+    }
+
+    fn process_bitstream<TEndian: Endianness>(
+        &mut self,
+        data_len: usize,
+        reader: &mut BitReader<Cursor<&[u8]>, TEndian>,
+    ) -> Result<(), AnalysisError> {
+        let record_bits = self.schema.root.bits as u64;
+        if record_bits == 0 {
+            return Ok(());
+        }
+
+        let record_count = (data_len as u64 * 8) / record_bits;
+        for record in 0..record_count {
+            // Skipped fields/groups don't advance the reader, so always seek to the next
+            // record explicitly, rather than relying on the reader position.
+            reader.seek_bits(SeekFrom::Start(record * record_bits))?;
+            self.process_group(&self.schema.root, reader)?;
+        }
+
+        Ok(())
+    }
+
+    fn process_group<TEndian: Endianness>(
+        &mut self,
+        group: &Group,
+        reader: &mut BitReader<Cursor<&[u8]>, TEndian>,
+    ) -> Result<(), AnalysisError> {
+        // Check self, if the group should be skipped.
+        // Note; this code is here because the 'root' of schema is a group.
+        if should_skip(reader, &group.skip_if_not, &group.skip_if)? {
+            return Ok(());
+        }
+
+        for (name, field_def) in &group.fields {
+            match field_def {
+                FieldDefinition::Field(field) => {
+                    // Check if the child field can be skipped.
+                    if should_skip(reader, &field.skip_if_not, &field.skip_if)? {
+                        continue;
+                    }
+
+                    let bits_left = field.bits;
+                    let field_stats = self
+                        .field_states
+                        .get_mut(name)
+                        .ok_or_else(|| AnalysisError::FieldNotFound(name.clone()))?;
+
+                    process_field_or_group(
+                        reader,
+                        bits_left,
+                        field_stats,
+                        field.skip_frequency_analysis,
+                        &self.compression_options,
+                    )?;
+                }
+                FieldDefinition::Group(child_group) => {
+                    // Checked before reading the group as a whole, as a skipped group
+                    // may extend past the end of the entry, e.g. another variant of it.
+                    if should_skip(reader, &child_group.skip_if_not, &child_group.skip_if)? {
+                        continue;
+                    }
+
+                    let bits_left = child_group.bits;
+                    let field_stats = self
+                        .field_states
+                        .get_mut(name)
+                        .ok_or_else(|| AnalysisError::FieldNotFound(name.clone()))?;
+
+                    // Note (processing field/group)
+                    let current_offset = reader.position_in_bits()?;
+                    process_field_or_group(
+                        reader,
+                        bits_left,
+                        field_stats,
+                        child_group.skip_frequency_analysis,
+                        &self.compression_options,
+                    )?;
+                    reader.seek_bits(SeekFrom::Start(current_offset))?;
+
+                    // Process nested fields
+                    self.process_group(child_group, reader)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates final analysis results
+    ///
+    /// # Returns
+    /// Computed metrics including:
+    /// - Entropy calculations
+    /// - Bit distribution statistics
+    /// - Value frequency analysis
+    pub fn generate_results(&mut self) -> Result<AnalysisResults, ComputeAnalysisResultsError> {
+        compute_analysis_results(self)
+    }
+
+    /// Regenerates the bytes of both groups of the split comparison with the given name,
+    /// i.e. the exact bytes that were compressed to compute its [`SplitComparisonResult`].
+    /// Intended for investigating surprising results.
+    ///
+    /// # Remarks
+    /// The group bytes are not kept around after the results are computed, as that would
+    /// double the memory usage of every analysis. Instead they are reassembled from the
+    /// per-field data held by the analyzer, which copies both groups; i.e. this allocates
+    /// roughly twice the size of the fields listed in the comparison, every call.
+    ///
+    /// # Returns
+    /// The bytes of both groups, or [`None`] if the schema has no split comparison with this name,
+    /// or it compares more than 2 groups ([`SplitComparison::groups`]).
+    ///
+    /// [`SplitComparison::groups`]: crate::schema::SplitComparison::groups
+    ///
+    /// [`SplitComparisonResult`]: crate::comparison::split_comparison::SplitComparisonResult
+    pub fn split_comparison_bytes(&mut self, name: &str) -> Option<SplitComparisonBytes> {
+        let comparison = self
+            .schema
+            .analysis
+            .split_groups
+            .iter()
+            .find(|comparison| comparison.name == name && !comparison.is_multi_group())?;
+
+        let group_1 = split_group_bytes(&mut self.field_states, &comparison.group_1);
+        let mut group_2 = split_group_bytes(&mut self.field_states, &comparison.group_2);
+        if comparison.normalize_length {
+            normalize_group_length(&mut group_2, group_1.len());
+        }
+        Some(SplitComparisonBytes { group_1, group_2 })
+    }
+}
+
+/// Checks if we should skip processing based on conditions, i.e. if any `skip_if_not`
+/// condition fails, or any `skip_if` condition matches.
+#[inline]
+fn should_skip<TEndian: Endianness>(
+    reader: &mut BitReader<Cursor<&[u8]>, TEndian>,
+    skip_if_not: &[Condition],
+    skip_if: &[Condition],
+) -> Result<bool, AnalysisError> {
+    // Fast return, since there usually are no conditions.
+    if skip_if_not.is_empty() && skip_if.is_empty() {
+        return Ok(false);
+    }
+
+    // (condition, whether a match skips)
+    let conditions = skip_if
+        .iter()
+        .map(|condition| (condition, true))
+        .chain(skip_if_not.iter().map(|condition| (condition, false)));
+
+    let original_pos_bits = reader.position_in_bits()?;
+    let mut skip = false;
+    for (condition, skip_on_match) in conditions {
+        let offset = (condition.byte_offset * 8) + condition.bit_offset as u64;
+        let target_pos = original_pos_bits.wrapping_add(offset);
+
+        reader.seek_bits(SeekFrom::Start(target_pos))?;
+        // Conditions past the end of the entry never match, like those of conditional offsets.
+        // e.g. after a smaller variant of an entry, see `Schema::element_size`.
+        let matches = reader
+            .read_var::<u64>(condition.bits as u32)
+            .is_ok_and(|value| condition.matches(value));
+        if matches == skip_on_match {
+            skip = true;
+            break;
+        }
+    }
+
+    reader.seek_bits(SeekFrom::Start(original_pos_bits))?;
+    Ok(skip)
+}
+
+/// Reads into `buf` until it is full or the reader ends, returning the number of bytes read.
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(read)
+}
+
+/// Creates an analyzer with each of `entries` added; shared by the tests of other modules.
+#[cfg(test)]
+pub(crate) fn analyzer_with_entries(
+    schema: &Schema,
+    options: CompressionOptions,
+    entries: impl IntoIterator<Item = impl AsRef<[u8]>>,
+) -> SchemaAnalyzer<'_> {
+    let mut analyzer = SchemaAnalyzer::new(schema, options);
+    for entry in entries {
+        analyzer.add_entry(entry.as_ref()).unwrap();
+    }
+    analyzer
+}
+
+/// Analyzes `entries`, see [`analyzer_with_entries`].
+#[cfg(test)]
+pub(crate) fn analyze_entries(
+    schema: &Schema,
+    options: CompressionOptions,
+    entries: impl IntoIterator<Item = impl AsRef<[u8]>>,
+) -> AnalysisResults {
+    analyzer_with_entries(schema, options, entries)
+        .generate_results()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comparison::GroupComparisonMetrics,
+        results::{json::write_json, PrintFormat},
+        schema::{BitOrder, Schema},
+        utils::analyze_utils::{get_writer_buffer, get_zstd_compressed_size, BitWriterContainer},
+        utils::size_estimate_expression::SizeEstimateExpression,
+    };
+    use bitstream_io::BitWrite;
+
+    fn create_test_schema() -> Schema {
+        let yaml = r###"
+version: '1.0'
+root:
+  type: group
+  fields:
+    id:
+      type: field
+      bits: 32
+      description: "ID field"
+    nested:
+      type: group
+      bit_order: lsb
+      fields:
+        value:
+          type: field
+          bits: 8
+          description: "Nested value"
+        "###;
+
+        Schema::from_yaml(yaml).expect("Failed to parse test schema")
+    }
+
+    #[test]
+    fn reading_entries_matches_adding_them() {
+        let schema = create_test_schema();
+        let data: Vec<u8> = (0..5000u32).map(|x| (x * 7 % 251) as u8).collect();
+        let json = |analyzer: &mut SchemaAnalyzer| {
+            let mut json = Vec::new();
+            write_json(&analyzer.generate_results().unwrap(), &mut json).unwrap();
+            json
+        };
+
+        let mut buffered = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        for entry in data.chunks_exact(5) {
+            buffered.add_entry(entry).unwrap();
+        }
+
+        // Reads of odd sizes, spanning entries.
+        let mut streamed = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        let reader = std::io::BufReader::with_capacity(7, &data[..]);
+        streamed.add_entries_from_reader(reader, 5, 1000).unwrap();
+        assert_eq!(streamed.entries, buffered.entries);
+        assert_eq!(json(&mut streamed), json(&mut buffered));
+
+        let mut truncated = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        let error = truncated
+            .add_entries_from_reader(&data[..4998], 5, 1000)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            AnalysisError::TruncatedEntry {
+                index: 999,
+                expected: 5,
+                found: 3
+            }
+        ));
+    }
+
+    #[test]
+    fn test_analyzer_initialization() {
+        let schema = create_test_schema();
+        let options = CompressionOptions::default();
+        let analyzer = SchemaAnalyzer::new(&schema, options);
+
+        // Should collect stats for all fields and groups
+        assert_eq!(
+            analyzer.field_states.len(),
+            3,
+            "Should have stats for root group + 2 fields"
+        );
+    }
+
+    #[test]
+    fn test_big_endian_bitorder() -> Result<(), AnalysisError> {
+        let yaml = r###"
+version: '1.0'
+root:
+  type: group
+  fields:
+    flags:
+      type: field
+      bits: 2
+      bit_order: msb
+"###;
+        let schema = Schema::from_yaml(yaml).expect("Failed to parse test schema");
+        let options = CompressionOptions::default();
+        let mut analyzer = SchemaAnalyzer::new(&schema, options);
+
+        // Add 4 entries (2 bits each) to make exactly 1 byte (8 bits)
+        // Values: 0b11, 0b00, 0b10, 0b01 → combined as 0b11001001 (0xC9)
+        analyzer.add_entry(&[0b11000000])?; // 0b11 in first 2 bits
+        analyzer.add_entry(&[0b00000000])?; // 0b00
+        analyzer.add_entry(&[0b10000000])?; // 0b10
+        analyzer.add_entry(&[0b01000000])?; // 0b01
+
+        // Assert general writing.
+        {
+            let flags_field = analyzer
+                .field_states
+                .get_mut("flags")
+                .ok_or(AnalysisError::FieldNotFound("flags".to_string()))?;
+            assert_eq!(flags_field.count, 4, "Should process 4 entries");
+            assert_eq!(
+                flags_field.bit_counts.len(),
+                2,
+                "Should track 2 bits per field"
+            );
+
+            // Check writer accumulated correct bits
+            let writer = match &mut flags_field.writer {
+                BitWriterContainer::Msb(value) => value,
+                _ => panic!("Expected MSB variant"),
+            };
+            writer.byte_align()?;
+            writer.flush()?;
+            let inner_writer = writer.writer().unwrap();
+            let data = inner_writer.get_ref();
+            assert_eq!(data[0], 0xC9_u8, "Combined bits should form 0xC9");
+
+            // Check value counts
+            let expected_counts =
+                FxHashMap::from_iter([(0b11, 1), (0b00, 1), (0b10, 1), (0b01, 1)]);
+            assert_eq!(
+                flags_field.value_counts, expected_counts,
+                "Value counts should match"
+            );
+
+            // Check bit counts (each bit position should have 2 zeros and 2 ones)
+            for (x, stats) in flags_field.bit_counts.iter().enumerate() {
+                assert_eq!(
+                    stats.zeros, 2,
+                    "Bit {} should have 2 zeros (actual: {})",
+                    x, stats.zeros
+                );
+                assert_eq!(
+                    stats.ones, 2,
+                    "Bit {} should have 2 ones (actual: {})",
+                    x, stats.ones
+                );
+            }
+        }
+
+        // Add another entry, to specifically test big endian
+        analyzer.add_entry(&[0b01000000])?; // 0b01
+        let flags_field = analyzer
+            .field_states
+            .get_mut("flags")
+            .ok_or(AnalysisError::FieldNotFound("flags".to_string()))?;
+        let expected_counts = FxHashMap::from_iter([(0b11, 1), (0b00, 1), (0b10, 1), (0b01, 2)]);
+        assert_eq!(
+            flags_field.value_counts, expected_counts,
+            "Value counts should match"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_little_endian_bitorder() {
+        let yaml = r###"
+version: '1.0'
+root:
+  type: group
+  fields:
+    flags:
+      type: field
+      bits: 2
+      bit_order: lsb
+"###;
+        let schema = Schema::from_yaml(yaml).expect("Failed to parse test schema");
+        let options = CompressionOptions::default();
+        let mut analyzer = SchemaAnalyzer::new(&schema, options);
+
+        // Add 4 entries (2 bits each) to make exactly 1 byte (8 bits)
+        // This is a repeat of the logic from the big endian test.
+        analyzer.add_entry(&[0b11000000]).unwrap(); // 0b11 in first 2 bits
+        analyzer.add_entry(&[0b00000000]).unwrap(); // 0b00
+        analyzer.add_entry(&[0b10000000]).unwrap(); // 0b01 (due to endian flip)
+        analyzer.add_entry(&[0b01000000]).unwrap(); // 0b10 (due to endian flip)
+
+        // Asserts for general writing are in the equivalent big endian test.
+        // Add another entry, to specifically test little endian
+        analyzer.add_entry(&[0b10000000]).unwrap(); // 0b01 (due to endian flip)
+        let flags_field = analyzer.field_states.get_mut("flags").unwrap();
+        let expected_counts = FxHashMap::from_iter([(0b11, 1), (0b00, 1), (0b10, 1), (0b01, 2)]);
+        assert_eq!(
+            flags_field.value_counts, expected_counts,
+            "Value counts should match"
+        );
+    }
+
+    #[test]
+    fn test_field_stats_structure() {
+        let schema = create_test_schema();
+        let options = CompressionOptions::default();
+        let analyzer = SchemaAnalyzer::new(&schema, options);
+
+        // Verify field hierarchy and properties
+        let root_group = analyzer.field_states.get("id").unwrap();
+        assert_eq!(root_group.name, "id");
+        assert_eq!(root_group.full_path, "id");
+        assert_eq!(root_group.depth, 0);
+        assert_eq!(root_group.count, 0);
+        assert_eq!(root_group.lenbits, 32);
+        assert_eq!(root_group.bit_counts.len(), root_group.lenbits as usize);
+        assert_eq!(root_group.bit_order, BitOrder::Msb);
+
+        let id_field = analyzer.field_states.get("nested").unwrap();
+        assert_eq!(id_field.full_path, "nested");
+        assert_eq!(id_field.name, "nested");
+        assert_eq!(id_field.depth, 0);
+        assert_eq!(id_field.count, 0);
+        assert_eq!(id_field.lenbits, 8);
+        assert_eq!(id_field.bit_counts.len(), id_field.lenbits as usize);
+        assert_eq!(id_field.bit_order, BitOrder::Lsb);
+
+        let nested_value = analyzer.field_states.get("value").unwrap();
+        assert_eq!(nested_value.full_path, "nested.value");
+        assert_eq!(nested_value.name, "value");
+        assert_eq!(nested_value.depth, 1);
+        assert_eq!(nested_value.count, 0);
+        assert_eq!(nested_value.lenbits, 8);
+        assert_eq!(nested_value.bit_counts.len(), nested_value.lenbits as usize);
+        assert_eq!(nested_value.bit_order, BitOrder::Lsb); // inherited from parent
+    }
+
+    #[test]
+    fn skips_group_based_on_conditions() {
+        let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  skip_if_not:
+    - byte_offset: 0
+      bit_offset: 0
+      bits: 8
+      value: 0x55
+  fields:
+    dummy: 8
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let options = CompressionOptions::default();
+        let mut analyzer = SchemaAnalyzer::new(&schema, options);
+
+        // Should process - matching magic
+        analyzer.add_entry(&[0x55]).unwrap();
+        assert_eq!(analyzer.field_states.get("dummy").unwrap().count, 1);
+
+        // Should skip - non-matching magic
+        analyzer.add_entry(&[0xAA]).unwrap();
+        assert_eq!(analyzer.field_states.get("dummy").unwrap().count, 1);
+
+        // Should process - matching magic
+        analyzer.add_entry(&[0x55]).unwrap();
+        assert_eq!(analyzer.field_states.get("dummy").unwrap().count, 2);
+    }
+
+    #[test]
+    fn skips_field_based_on_conditions() {
+        let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    header:
+      type: field
+      bits: 7
+      skip_if_not:
+        - byte_offset: 0
+          bit_offset: 0
+          bits: 1
+          value: 1
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let options = CompressionOptions::default();
+        let mut analyzer = SchemaAnalyzer::new(&schema, options);
+
+        // First bit 1 - processes
+        analyzer.add_entry(&[0b10000000]).unwrap();
+        assert_eq!(analyzer.field_states.get("header").unwrap().count, 1);
+
+        // First bit 0 - skips
+        analyzer.add_entry(&[0b00000000]).unwrap();
+        assert_eq!(analyzer.field_states.get("header").unwrap().count, 1);
+    }
+
+    #[test]
+    fn skips_field_if_condition_matches() {
+        let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    header:
+      type: field
+      bits: 8
+      skip_if:
+        - byte_offset: 0
+          bit_offset: 0
+          bits: 1
+          value: 1
+      skip_if_not:
+        - byte_offset: 0
+          bit_offset: 1
+          bits: 1
+          value: 1
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let options = CompressionOptions::default();
+        let mut analyzer = SchemaAnalyzer::new(&schema, options);
+
+        // First bit 0, second bit 1 - processes
+        analyzer.add_entry(&[0b01000000]).unwrap();
+        assert_eq!(analyzer.field_states.get("header").unwrap().count, 1);
+
+        // First bit 1 - skips, even though `skip_if_not` passes
+        analyzer.add_entry(&[0b11000000]).unwrap();
+        assert_eq!(analyzer.field_states.get("header").unwrap().count, 1);
+
+        // Second bit 0 - skips by `skip_if_not`
+        analyzer.add_entry(&[0b00000000]).unwrap();
+        assert_eq!(analyzer.field_states.get("header").unwrap().count, 1);
+    }
+
+    #[test]
+    fn reads_16_bit_fields_in_both_byte_orders() {
+        let analyze = |byte_order: &str| {
+            let yaml = format!(
+                "version: '1.0'\nbyte_order: {byte_order}\nroot: {{ type: group, fields: {{ value: 16, nibble: 4, pad: 4 }} }}\n"
+            );
+            let schema = Schema::from_yaml(&yaml).unwrap();
+            let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+            analyzer.add_entry(&[0x34, 0x12, 0xA0]).unwrap();
+            let values = |name: &str| -> Vec<u64> {
+                analyzer.field_states[name]
+                    .value_counts
+                    .keys()
+                    .copied()
+                    .collect()
+            };
+            let (value, nibble) = (values("value"), values("nibble"));
+            let bytes =
+                get_writer_buffer(&mut analyzer.field_states.get_mut("value").unwrap().writer)
+                    .to_vec();
+            (value, nibble, bytes)
+        };
+
+        // Big endian reads the bytes as-is.
+        assert_eq!(analyze("big"), (vec![0x3412], vec![0xA], vec![0x34, 0x12]));
+        // Little endian fields are assembled most significant byte first, so slices of them
+        // (e.g. in compare groups) read the high byte first; fields of under a byte are as-is.
+        assert_eq!(
+            analyze("little"),
+            (vec![0x1234], vec![0xA], vec![0x12, 0x34])
+        );
+    }
+
+    #[test]
+    fn bit_entropy_handles_edge_cases() {
+        let bit = |zeros, ones| BitStats { zeros, ones };
+        assert_eq!(bit(0, 0).entropy(), 0.0);
+        assert_eq!(bit(10, 0).entropy(), 0.0);
+        assert_eq!(bit(0, 10).entropy(), 0.0);
+        assert_eq!(bit(5, 5).entropy(), 1.0);
+        assert!((bit(1, 3).entropy() - 0.8113).abs() < 0.0001);
+
+        assert!(!bit(0, 0).is_near_constant());
+        assert!(bit(0, 10).is_near_constant());
+        assert!(bit(1000, 1).is_near_constant());
+        assert!(!bit(99, 1).is_near_constant());
+    }
+
+    #[test]
+    fn test_builder() {
+        let options = CompressionOptions::default().with_zstd_compression_level(7);
+        assert_eq!(options.zstd_compression_level, 7);
+
+        let options = CompressionOptions::default();
+        assert_eq!(options.zstd_compression_level, 16); // Check default value.
+    }
+
+    #[test]
+    fn calibration_size_only_differs_from_reported_level() {
+        let data: Vec<u8> = (0..4096u32).map(|x| (x * x % 251) as u8).collect();
+        let options = CompressionOptions::default().with_zstd_compression_level(1);
+        assert_eq!(options.zstd_calibration_size(&data), None);
+
+        let options = options.with_zstd_calibration_level(Some(1));
+        assert_eq!(options.zstd_calibration_size(&data), None);
+
+        let options = options.with_zstd_calibration_level(Some(19));
+        let metrics = GroupComparisonMetrics::from_bytes(&data, "", &options);
+        assert_eq!(metrics.zstd_size, get_zstd_compressed_size(&data, 1));
+        assert_eq!(
+            metrics.zstd_calibration_size,
+            Some(get_zstd_compressed_size(&data, 19))
+        );
+    }
+
+    #[test]
+    fn uses_size_estimate_expression_from_schema() {
+        let yaml = r#"
+version: '1.0'
+analysis:
+  size_estimate: "len / 2"
+root: { type: group, fields: { a: 8 } }
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        let params = SizeEstimationParameters {
+            name: "",
+            data_len: 100,
+            data: None,
+            num_lz_matches: 0,
+            entropy: 8.0,
+            lz_match_multiplier: 0.0,
+            entropy_multiplier: 1.0,
+        };
+        assert_eq!(analyzer.compression_options.estimate_size(params), 50);
+
+        // Explicitly provided expression takes precedence over the schema.
+        let options = CompressionOptions::default()
+            .with_size_estimate_expression(SizeEstimateExpression::parse("len").unwrap());
+        let analyzer = SchemaAnalyzer::new(&schema, options);
+        assert_eq!(analyzer.compression_options.estimate_size(params), 100);
+    }
+
+    #[test]
+    fn estimate_breakdown_only_recorded_when_requested() {
+        let params = SizeEstimationParameters {
+            name: "",
+            data_len: 100,
+            data: None,
+            num_lz_matches: 20,
+            entropy: 4.0,
+            lz_match_multiplier: 0.5,
+            entropy_multiplier: 1.0,
+        };
+
+        assert_eq!(
+            CompressionOptions::default().estimate_size_breakdown(params),
+            None
+        );
+
+        let options = CompressionOptions::default().with_estimate_breakdown(true);
+        let breakdown = options.estimate_size_breakdown(params).unwrap();
+        assert_eq!(breakdown.lz_savings, 10);
+        assert_eq!(breakdown.bytes_after_lz, 90);
+        assert_eq!(breakdown.entropy_bytes, 45);
+        assert_eq!(
+            breakdown.entropy_bytes as usize,
+            options.estimate_size(params)
+        );
+
+        // Custom estimators don't expose their terms.
+        let options = options.with_size_estimator_fn(|p| p.data_len);
+        assert_eq!(options.estimate_size_breakdown(params), None);
+    }
+
+    #[test]
+    fn continuous_bitstream_reads_fields_across_byte_boundaries() {
+        let yaml = r#"
+version: '1.0'
+continuous: true
+root: { type: group, fields: { value: 3 } }
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+
+        // 0..8 as back-to-back 3-bit values: 000 001 010 011 100 101 110 111
+        analyzer.add_bitstream(&[0x05, 0x39, 0x77]).unwrap();
+
+        let state = analyzer.field_states.get("value").unwrap();
+        assert_eq!(state.count, 8);
+        for x in 0..8 {
+            assert_eq!(state.value_counts.get(&x), Some(&1), "value {}", x);
+        }
+    }
+
+    #[test]
+    fn continuous_bitstream_ignores_incomplete_trailing_record() {
+        let yaml = r#"
+version: '1.0'
+continuous: true
+root: { type: group, fields: { value: 3 } }
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+
+        // 16 bits = 5 complete 3-bit records + 1 trailing bit.
+        analyzer.add_bitstream(&[0b1111_1111, 0b1111_1111]).unwrap();
+
+        let state = analyzer.field_states.get("value").unwrap();
+        assert_eq!(state.count, 5);
+        assert_eq!(state.value_counts.get(&0b111), Some(&5));
+        assert_eq!(analyzer.entries.len(), 2);
+    }
+
+    #[test]
+    fn counts_value_transitions_of_cyclic_field() {
+        let yaml = r#"
+version: '1.0'
+root: { type: group, fields: { state: 8 } }
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let options = CompressionOptions::default().with_value_transitions(true);
+        let mut analyzer = SchemaAnalyzer::new(&schema, options);
+
+        // Cycles 1 -> 2 -> 3 -> 1 -> ...
+        let sequence = [1u8, 2, 3];
+        for x in 0..30 {
+            analyzer.add_entry(&[sequence[x % 3]]).unwrap();
+        }
+
+        let results = analyzer.generate_results().unwrap();
+        let transitions = results.per_field["state"]
+            .value_transitions
+            .as_ref()
+            .unwrap();
+        assert_eq!(transitions.len(), 3);
+        assert_eq!(transitions.get(&(1, 2)), Some(&10));
+        assert_eq!(transitions.get(&(2, 3)), Some(&10));
+        assert_eq!(transitions.get(&(3, 1)), Some(&9));
+    }
+
+    #[test]
+    fn value_transitions_are_opt_in_and_capped() {
+        let yaml = r#"
+version: '1.0'
+root: { type: group, fields: { state: 8 } }
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let data: Vec<u8> = (0..=255u8).collect();
+
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        for entry in data.chunks(1) {
+            analyzer.add_entry(entry).unwrap();
+        }
+        let results = analyzer.generate_results().unwrap();
+        assert!(results.per_field["state"].value_transitions.is_none());
+
+        let options = CompressionOptions::default()
+            .with_value_transitions(true)
+            .with_max_value_transitions(16);
+        let mut analyzer = SchemaAnalyzer::new(&schema, options);
+        for entry in data.chunks(1) {
+            analyzer.add_entry(entry).unwrap();
+        }
+        let results = analyzer.generate_results().unwrap();
+        assert!(results.per_field["state"].value_transitions.is_none());
+    }
+
+    #[test]
+    fn value_counts_are_capped_and_flag_overflow() {
+        let yaml = r#"
+version: '1.0'
+root: { type: group, fields: { state: 8 } }
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let analyze = |options: CompressionOptions| {
+            let mut analyzer = SchemaAnalyzer::new(&schema, options);
+            // 0..16, then 0..16 again; the 2nd pass only repeats known values.
+            for x in (0..16u8).chain(0..16u8).chain(100..104u8) {
+                analyzer.add_entry(&[x]).unwrap();
+            }
+            analyzer.generate_results().unwrap()
+        };
+
+        let results = analyze(CompressionOptions::default());
+        let state = &results.per_field["state"];
+        assert!(!state.value_counts_overflowed);
+        assert_eq!(state.value_counts.len(), 20);
+
+        let results = analyze(CompressionOptions::default().with_max_distinct_values(16));
+        let state = &results.per_field["state"];
+        assert!(state.value_counts_overflowed);
+        assert_eq!(state.value_counts.len(), 16);
+        // Known values are still counted after the cap is reached.
+        assert_eq!(state.value_counts.get(&3), Some(&2));
+        assert_eq!(state.value_counts.get(&100), None);
+    }
+
+    #[test]
+    fn regenerates_split_comparison_bytes() {
+        let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    colors:
+      type: group
+      fields:
+        r: 8
+        g: 8
+analysis:
+  split_groups:
+    - name: colors
+      group_1: [colors]
+      group_2: [r, g]
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        for entry in [1u8, 2, 3, 4, 5, 6].chunks(2) {
+            analyzer.add_entry(entry).unwrap();
+        }
+        let results = analyzer.generate_results().unwrap();
+
+        let bytes = analyzer.split_comparison_bytes("colors").unwrap();
+        assert_eq!(bytes.group_1, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(bytes.group_2, vec![1, 3, 5, 2, 4, 6]);
+
+        // Same bytes as those the results were computed from.
+        let comparison = &results.split_comparisons[0];
+        assert_eq!(
+            comparison.group1_metrics.zstd_size,
+            CompressionOptions::default().zstd_compressed_size(&bytes.group_1)
+        );
+        assert_eq!(
+            comparison.group2_metrics.zstd_size,
+            CompressionOptions::default().zstd_compressed_size(&bytes.group_2)
+        );
+        assert!(analyzer.split_comparison_bytes("missing").is_none());
+    }
+
+    #[test]
+    fn byte_transpose_comparison_is_opt_in() {
+        let yaml = r#"
+version: '1.0'
+root: { type: group, fields: { a: 8, b: 8 } }
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let analyze = |options: CompressionOptions| {
+            let mut analyzer = SchemaAnalyzer::new(&schema, options);
+            for entry in [1u8, 2, 1, 3, 1, 4].chunks(2) {
+                analyzer.add_entry(entry).unwrap();
+            }
+            analyzer.generate_results().unwrap()
+        };
+
+        assert!(analyze(CompressionOptions::default())
+            .custom_comparisons
+            .is_empty());
+
+        let results = analyze(CompressionOptions::default().with_byte_transpose_comparison(true));
+        assert_eq!(results.custom_comparisons.len(), 1);
+        let comparison = &results.custom_comparisons[0];
+        assert_eq!(comparison.name, "byte_transpose");
+        assert_eq!(comparison.baseline_metrics.original_size, 6);
+        assert_eq!(comparison.group_metrics[0].original_size, 6);
+    }
+
+    #[test]
+    fn split_group_without_fields_warns_or_errors_under_strict() {
+        let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    value: 8
+    empty:
+      type: group
+      fields:
+        nested: { type: group, fields: {} }
+analysis:
+  split_groups:
+    - name: empty_split
+      group_1: [value]
+      group_2: [empty]
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let analyze = |options: CompressionOptions| {
+            let mut analyzer = SchemaAnalyzer::new(&schema, options);
+            for entry in [1u8, 2, 3, 4].chunks(1) {
+                analyzer.add_entry(entry).unwrap();
+            }
+            analyzer.generate_results()
+        };
+
+        let results = analyze(CompressionOptions::default()).unwrap();
+        assert_eq!(results.split_comparisons[0].empty_groups, vec!["group_2"]);
+
+        let mut output = Vec::new();
+        results
+            .print(&mut output, &schema, PrintFormat::Concise, true)
+            .unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("[WARNING!!] group_2 resolves to no fields!!"));
+
+        match analyze(CompressionOptions::default().with_strict(true)) {
+            Err(ComputeAnalysisResultsError::EmptySplitGroup { comparison, group }) => {
+                assert_eq!(comparison, "empty_split");
+                assert_eq!(group, "group_2");
+            }
+            _ => panic!("expected an EmptySplitGroup error"),
+        }
+    }
+}
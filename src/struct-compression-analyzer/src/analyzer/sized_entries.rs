@@ -0,0 +1,185 @@
+//! Ingestion of back-to-back entries of varying size, see [`Schema::element_size`].
+//!
+//! [`Schema::element_size`]: crate::schema::Schema::element_size
+
+use super::{read_up_to, AnalysisError, SchemaAnalyzer, READ_CHUNK_SIZE};
+use std::io::Read;
+
+impl SchemaAnalyzer<'_> {
+    /// Ingests back-to-back entries, whose sizes are selected by the [`Schema::element_size`]
+    /// of each entry. Without one, every entry is the size of the schema's root.
+    ///
+    /// # Arguments
+    /// * `data` - The entries, starting at the first entry.
+    ///
+    /// # Errors
+    /// [`AnalysisError::UnknownElementSize`] if an entry's selector has no size, or
+    /// [`AnalysisError::TruncatedEntry`] if `data` ends with an incomplete entry.
+    ///
+    /// [`Schema::element_size`]: crate::schema::Schema::element_size
+    pub fn add_sized_entries(&mut self, data: &[u8]) -> Result<(), AnalysisError> {
+        let mut index = 0;
+        let consumed = self.add_complete_entries(data, &mut index)?;
+        if consumed < data.len() {
+            return Err(self.truncated_entry(&data[consumed..], index));
+        }
+        Ok(())
+    }
+
+    /// Ingests `length` bytes of entries read from `reader`, without buffering all of them
+    /// up front. The results are identical to calling [`Self::add_sized_entries`] on the bytes.
+    ///
+    /// # Arguments
+    /// * `reader` - Source of the entries, positioned at the first entry.
+    /// * `length` - Number of bytes to read.
+    ///
+    /// # Errors
+    /// See [`Self::add_sized_entries`]. Entries cut short by the reader ending early are
+    /// also reported as [`AnalysisError::TruncatedEntry`].
+    pub fn add_sized_entries_from_reader<R: Read>(
+        &mut self,
+        mut reader: R,
+        length: u64,
+    ) -> Result<(), AnalysisError> {
+        // Large enough to always hold the largest entry.
+        let largest_entry = match &self.schema.element_size {
+            Some(element_size) => element_size.sizes.values().max().copied().unwrap_or(0),
+            None => self.schema.root.bits,
+        };
+        let mut buffer = vec![0u8; READ_CHUNK_SIZE.max(largest_entry as usize / 8)];
+        let mut remaining = length;
+        let mut filled = 0;
+        let mut index = 0;
+        loop {
+            let to_read = ((buffer.len() - filled) as u64).min(remaining) as usize;
+            let read = read_up_to(&mut reader, &mut buffer[filled..filled + to_read])?;
+            remaining -= read as u64;
+            filled += read;
+
+            let consumed = self.add_complete_entries(&buffer[..filled], &mut index)?;
+            if remaining == 0 || read < to_read {
+                if consumed < filled {
+                    return Err(self.truncated_entry(&buffer[consumed..filled], index));
+                }
+                return Ok(());
+            }
+
+            buffer.copy_within(consumed..filled, 0);
+            filled -= consumed;
+        }
+    }
+
+    /// Adds the complete entries at the start of `data`, counting them in `index`.
+    /// Returns the number of bytes consumed; the rest is an incomplete entry.
+    fn add_complete_entries(
+        &mut self,
+        data: &[u8],
+        index: &mut u64,
+    ) -> Result<usize, AnalysisError> {
+        let mut offset = 0;
+        while let Some(size) = self.entry_size(&data[offset..], *index)? {
+            if size == 0 || offset + size > data.len() {
+                break;
+            }
+
+            self.add_entry(&data[offset..offset + size])?;
+            offset += size;
+            *index += 1;
+        }
+        Ok(offset)
+    }
+
+    /// Size in bytes of the entry at the start of `data`, or [`None`] if `data`
+    /// is too short to read the selector of its [`Schema::element_size`].
+    ///
+    /// [`Schema::element_size`]: crate::schema::Schema::element_size
+    fn entry_size(&self, data: &[u8], index: u64) -> Result<Option<usize>, AnalysisError> {
+        let Some(element_size) = &self.schema.element_size else {
+            return Ok(Some(self.schema.root.bits as usize / 8));
+        };
+        let Some(value) = element_size.read_selector(data) else {
+            return Ok(None);
+        };
+        match element_size.sizes.get(&value) {
+            Some(&bits) => Ok(Some(bits as usize / 8)),
+            None => Err(AnalysisError::UnknownElementSize { index, value }),
+        }
+    }
+
+    /// The error for the incomplete entry `rest`, left at the end of the data.
+    fn truncated_entry(&self, rest: &[u8], index: u64) -> AnalysisError {
+        let expected = match self.entry_size(rest, index) {
+            Ok(Some(size)) => size,
+            // Not even the selector could be read.
+            _ => self
+                .schema
+                .element_size
+                .as_ref()
+                .map_or(0, |element_size| element_size.header_bytes()),
+        };
+        AnalysisError::TruncatedEntry {
+            index,
+            expected,
+            found: rest.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::CompressionOptions;
+    use crate::schema::Schema;
+    use std::io::Cursor;
+
+    #[test]
+    fn splits_entries_by_element_size() {
+        let yaml = r#"
+version: '1.0'
+element_size:
+  byte_offset: 0
+  bit_offset: 0
+  bits: 8
+  sizes: { 0: 16, 1: 24 }
+root:
+  type: group
+  fields:
+    short:
+      type: group
+      skip_if_not: [{ byte_offset: 0, bit_offset: 0, bits: 8, value: 0 }]
+      fields: { short_mode: 8, a: 8 }
+    long:
+      type: group
+      skip_if_not: [{ byte_offset: 0, bit_offset: 0, bits: 8, value: 1 }]
+      fields: { long_mode: 8, b: 16 }
+"#;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let data = [0, 5, 1, 0xAB, 0xCD, 0, 6];
+        let count = |analyzer: &SchemaAnalyzer, name: &str| analyzer.field_states[name].count;
+
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        analyzer.add_sized_entries(&data).unwrap();
+        assert_eq!((count(&analyzer, "a"), count(&analyzer, "b")), (2, 1));
+
+        let mut streamed = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        streamed
+            .add_sized_entries_from_reader(Cursor::new(&data), data.len() as u64)
+            .unwrap();
+        assert_eq!((count(&streamed, "a"), count(&streamed, "b")), (2, 1));
+        assert_eq!(streamed.entries, analyzer.entries);
+
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        assert!(matches!(
+            analyzer.add_sized_entries(&[0, 5, 1, 0xAB]),
+            Err(AnalysisError::TruncatedEntry {
+                index: 1,
+                expected: 3,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            analyzer.add_sized_entries(&[2, 0]),
+            Err(AnalysisError::UnknownElementSize { index: 0, value: 2 })
+        ));
+    }
+}
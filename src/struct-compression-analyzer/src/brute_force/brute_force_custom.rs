@@ -1,6 +1,7 @@
 use super::{
-    find_optimal_coefficients_for_metrics_parallel, BruteForceComparisonMetrics, BruteForceConfig,
-    OptimizationResult,
+    find_optimal_coefficients_for_metrics_parallel, format_min_error,
+    progress::{ComparisonKind, ProgressReporter},
+    timed_out_marker, BruteForceComparisonMetrics, BruteForceConfig, OptimizationResult,
 };
 use crate::results::analysis_results::AnalysisResults;
 
@@ -57,20 +58,45 @@ fn find_optimal_custom_result_coefficients_for_comparison(
     let first_result = &original_results[0].custom_comparisons[comparison_idx];
     let num_comparisons = first_result.group_metrics.len();
 
+    let comparison_count = original_results[0].custom_comparisons.len();
+    let progress = |group_name| {
+        ProgressReporter::new(
+            config,
+            ComparisonKind::Custom,
+            comparison_idx,
+            comparison_count,
+            &first_result.name,
+            group_name,
+        )
+    };
+
     // Extract baseline metrics and find optimal coefficients
-    let baseline_metrics = extract_baseline_metrics(comparison_idx, original_results);
-    let baseline_best = find_optimal_coefficients_for_metrics_parallel(&baseline_metrics, config);
+    let baseline_metrics =
+        extract_baseline_metrics(comparison_idx, original_results, config.use_reference_size);
+    let baseline_best = find_optimal_coefficients_for_metrics_parallel(
+        &baseline_metrics,
+        config,
+        progress("baseline").as_ref(),
+    );
 
     // Initialize comparison group optimization results
     let mut comparison_bests = Vec::with_capacity(num_comparisons);
 
     // Process each comparison group separately
     for group_idx in 0..num_comparisons {
-        let group_metrics =
-            extract_comparison_group_metrics(comparison_idx, group_idx, original_results);
+        let group_metrics = extract_comparison_group_metrics(
+            comparison_idx,
+            group_idx,
+            original_results,
+            config.use_reference_size,
+        );
 
         // Find optimal coefficients for this comparison group
-        let group_best = find_optimal_coefficients_for_metrics_parallel(&group_metrics, config);
+        let group_best = find_optimal_coefficients_for_metrics_parallel(
+            &group_metrics,
+            config,
+            progress(&first_result.group_names[group_idx]).as_ref(),
+        );
         comparison_bests.push(group_best);
     }
 
@@ -85,13 +111,15 @@ fn find_optimal_custom_result_coefficients_for_comparison(
 fn extract_baseline_metrics(
     comparison_idx: usize,
     original_results: &[AnalysisResults], // guaranteed non-empty
+    use_reference_size: bool,
 ) -> Box<[BruteForceComparisonMetrics]> {
     original_results
         .iter()
         .map(|result| {
-            result.custom_comparisons[comparison_idx]
-                .baseline_metrics
-                .into()
+            BruteForceComparisonMetrics::new(
+                &result.custom_comparisons[comparison_idx].baseline_metrics,
+                use_reference_size,
+            )
         })
         .collect()
 }
@@ -104,14 +132,21 @@ fn extract_baseline_metrics(
 /// * `comparison_idx` - The index of the custom comparison in the custom_comparisons array
 /// * `group_idx` - The index of the comparison group in the group_metrics array
 /// * `original_results` - The original results to extract metrics from
+/// * `use_reference_size` - Whether to fit to the reference sizes, see [`BruteForceConfig::use_reference_size`]
 fn extract_comparison_group_metrics(
     comparison_idx: usize,
     group_idx: usize,
     original_results: &[AnalysisResults], // guaranteed non-empty
+    use_reference_size: bool,
 ) -> Box<[BruteForceComparisonMetrics]> {
     original_results
         .iter()
-        .map(|result| result.custom_comparisons[comparison_idx].group_metrics[group_idx].into())
+        .map(|result| {
+            BruteForceComparisonMetrics::new(
+                &result.custom_comparisons[comparison_idx].group_metrics[group_idx],
+                use_reference_size,
+            )
+        })
         .collect()
 }
 
@@ -131,25 +166,35 @@ pub fn print_optimization_results<W: std::io::Write>(
     )?;
     writeln!(
         writer,
-        "Comparison Name | Group | LZ Multiplier | Entropy Multiplier |"
+        "Comparison Name | Group | LZ Multiplier | Entropy Multiplier | Min Error    |"
     )?;
     writeln!(
         writer,
-        "----------------|-------|---------------|--------------------|"
+        "----------------|-------|---------------|--------------------|--------------|"
     )?;
 
     for (name, result) in results {
         writeln!(
             writer,
-            "{:<16}|{:<7}|{:<15.3}|{:<20.3}|",
-            name, "BASE", result.baseline.lz_match_multiplier, result.baseline.entropy_multiplier
+            "{:<16}|{:<7}|{:<15.3}|{:<20.3}|{:<14}|{}",
+            name,
+            "BASE",
+            result.baseline.lz_match_multiplier,
+            result.baseline.entropy_multiplier,
+            format_min_error(&result.baseline),
+            timed_out_marker(&result.baseline)
         )?;
 
         for (i, comparison) in result.comparisons.iter().enumerate() {
             writeln!(
                 writer,
-                "{:<16}|{:<7}|{:<15.3}|{:<20.3}|",
-                "", i, comparison.lz_match_multiplier, comparison.entropy_multiplier
+                "{:<16}|{:<7}|{:<15.3}|{:<20.3}|{:<14}|{}",
+                "",
+                i,
+                comparison.lz_match_multiplier,
+                comparison.entropy_multiplier,
+                format_min_error(comparison),
+                timed_out_marker(comparison)
             )?;
         }
     }
@@ -163,7 +208,7 @@ mod tests {
 
     use super::*;
     use crate::{
-        brute_force::calculate_error_for_bruteforce_metrics,
+        brute_force::{calculate_error_for_bruteforce_metrics, ErrorMetric},
         comparison::{
             compare_groups::GroupComparisonResult, GroupComparisonMetrics, GroupDifference,
         },
@@ -188,8 +233,17 @@ mod tests {
             lz_matches: baseline_lz_matches,
             entropy: baseline_entropy,
             estimated_size: 0, // Not used in testing
+            has_estimate: false,
             zstd_size: baseline_zstd_size,
             original_size: baseline_original_size,
+            estimate_breakdown: None,
+            brotli_size: None,
+            deflate_size: None,
+            zstd_dict_size: None,
+            entropy_order1: None,
+            reference_size: None,
+            zstd_compress_ns: 0,
+            zstd_calibration_size: None,
         };
 
         let mut group_names = Vec::with_capacity(comparison_group_count);
@@ -203,8 +257,17 @@ mod tests {
                 lz_matches: comparison_lz_matches,
                 entropy: comparison_entropy,
                 estimated_size: 0, // Not used in testing
+                has_estimate: false,
                 zstd_size: comparison_zstd_size,
                 original_size: comparison_original_size,
+                estimate_breakdown: None,
+                brotli_size: None,
+                deflate_size: None,
+                zstd_dict_size: None,
+                entropy_order1: None,
+                reference_size: None,
+                zstd_compress_ns: 0,
+                zstd_calibration_size: None,
             };
 
             group_metrics.push(metrics);
@@ -314,13 +377,18 @@ mod tests {
         assert!(comparisons[1].entropy_multiplier <= config.max_entropy_multiplier);
 
         // Calculate baseline error using the optimal parameters
-        let baseline_metrics = extract_baseline_metrics(0, &original_results);
+        let baseline_metrics = extract_baseline_metrics(0, &original_results, false);
         let baseline_error = calculate_error_for_bruteforce_metrics(
             &baseline_metrics,
             optimal_results[0].1.baseline.lz_match_multiplier,
             optimal_results[0].1.baseline.entropy_multiplier,
+            ErrorMetric::Absolute,
         );
 
+        // The reported error is the error of the optimal parameters
+        assert_eq!(optimal_results[0].1.baseline.min_error, baseline_error);
+        assert!(!optimal_results[0].1.baseline.is_penalized());
+
         // Assert the error is below a reasonable threshold
         assert!(
             baseline_error < 5.0,
@@ -330,11 +398,12 @@ mod tests {
 
         // Check errors for each comparison group
         for i in 0..2 {
-            let group_metrics = extract_comparison_group_metrics(0, i, &original_results);
+            let group_metrics = extract_comparison_group_metrics(0, i, &original_results, false);
             let group_error = calculate_error_for_bruteforce_metrics(
                 &group_metrics,
                 optimal_results[0].1.comparisons[i].lz_match_multiplier,
                 optimal_results[0].1.comparisons[i].entropy_multiplier,
+                ErrorMetric::Absolute,
             );
 
             assert!(
@@ -1,6 +1,7 @@
 use super::{
-    find_optimal_coefficients_for_metrics_parallel, BruteForceComparisonMetrics, BruteForceConfig,
-    OptimizationResult,
+    find_optimal_coefficients_for_metrics_parallel, format_min_error,
+    progress::{ComparisonKind, ProgressReporter},
+    timed_out_marker, BruteForceComparisonMetrics, BruteForceConfig, OptimizationResult,
 };
 use crate::results::analysis_results::AnalysisResults;
 
@@ -50,13 +51,41 @@ fn find_optimal_split_result_coefficients_for_comparison(
     config: &BruteForceConfig,
     original_results: &[AnalysisResults], // guaranteed non-empty
 ) -> SplitComparisonOptimizationResult {
+    let comparisons = original_results
+        .first()
+        .map(|result| result.split_comparisons.as_slice())
+        .unwrap_or_default();
+    let progress = |group_name| {
+        ProgressReporter::new(
+            config,
+            ComparisonKind::Split,
+            comparison_idx,
+            comparisons.len(),
+            comparisons
+                .get(comparison_idx)
+                .map(|comparison| comparison.name.as_str())
+                .unwrap_or_default(),
+            group_name,
+        )
+    };
+
     // Find optimal coefficients for group 1
-    let group1_metrics = extract_group1_metrics(comparison_idx, original_results);
-    let group1_best = find_optimal_coefficients_for_metrics_parallel(&group1_metrics, config);
+    let group1_metrics =
+        extract_group1_metrics(comparison_idx, original_results, config.use_reference_size);
+    let group1_best = find_optimal_coefficients_for_metrics_parallel(
+        &group1_metrics,
+        config,
+        progress("group 1").as_ref(),
+    );
 
     // Find optimal coefficients for group 2
-    let group2_metrics = extract_group2_metrics(comparison_idx, original_results);
-    let group2_best = find_optimal_coefficients_for_metrics_parallel(&group2_metrics, config);
+    let group2_metrics =
+        extract_group2_metrics(comparison_idx, original_results, config.use_reference_size);
+    let group2_best = find_optimal_coefficients_for_metrics_parallel(
+        &group2_metrics,
+        config,
+        progress("group 2").as_ref(),
+    );
 
     SplitComparisonOptimizationResult {
         group_1: group1_best,
@@ -69,13 +98,15 @@ fn find_optimal_split_result_coefficients_for_comparison(
 fn extract_group1_metrics(
     comparison_idx: usize,
     original_results: &[AnalysisResults], // guaranteed non-empty
+    use_reference_size: bool,
 ) -> Box<[BruteForceComparisonMetrics]> {
     original_results
         .iter()
         .map(|result| {
-            result.split_comparisons[comparison_idx]
-                .group1_metrics
-                .into()
+            BruteForceComparisonMetrics::new(
+                &result.split_comparisons[comparison_idx].group1_metrics,
+                use_reference_size,
+            )
         })
         .collect()
 }
@@ -85,13 +116,15 @@ fn extract_group1_metrics(
 fn extract_group2_metrics(
     comparison_idx: usize,
     original_results: &[AnalysisResults], // guaranteed non-empty
+    use_reference_size: bool,
 ) -> Box<[BruteForceComparisonMetrics]> {
     original_results
         .iter()
         .map(|result| {
-            result.split_comparisons[comparison_idx]
-                .group2_metrics
-                .into()
+            BruteForceComparisonMetrics::new(
+                &result.split_comparisons[comparison_idx].group2_metrics,
+                use_reference_size,
+            )
         })
         .collect()
 }
@@ -112,23 +145,33 @@ pub fn print_optimization_results<W: std::io::Write>(
     )?;
     writeln!(
         writer,
-        "Comparison Name               | Group | LZ Multiplier | Entropy Multiplier |"
+        "Comparison Name               | Group | LZ Multiplier | Entropy Multiplier | Min Error    |"
     )?;
     writeln!(
         writer,
-        "------------------------------|-------|---------------|--------------------|"
+        "------------------------------|-------|---------------|--------------------|--------------|"
     )?;
 
     for (name, result) in results {
         writeln!(
             writer,
-            "{:<30}|{:<7}|{:<15.4}|{:<20.4}|",
-            name, "G1", result.group_1.lz_match_multiplier, result.group_1.entropy_multiplier
+            "{:<30}|{:<7}|{:<15.4}|{:<20.4}|{:<14}|{}",
+            name,
+            "G1",
+            result.group_1.lz_match_multiplier,
+            result.group_1.entropy_multiplier,
+            format_min_error(&result.group_1),
+            timed_out_marker(&result.group_1)
         )?;
         writeln!(
             writer,
-            "{:<30}|{:<7}|{:<15.4}|{:<20.4}|",
-            "", "G2", result.group_2.lz_match_multiplier, result.group_2.entropy_multiplier
+            "{:<30}|{:<7}|{:<15.4}|{:<20.4}|{:<14}|{}",
+            "",
+            "G2",
+            result.group_2.lz_match_multiplier,
+            result.group_2.entropy_multiplier,
+            format_min_error(&result.group_2),
+            timed_out_marker(&result.group_2)
         )?;
     }
 
@@ -138,13 +181,16 @@ pub fn print_optimization_results<W: std::io::Write>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::brute_force::progress::ProgressCallback;
     use crate::{
-        brute_force::calculate_error_for_bruteforce_metrics,
+        brute_force::{calculate_error_for_bruteforce_metrics, ErrorMetric},
         comparison::{
             split_comparison::SplitComparisonResult, GroupComparisonMetrics, GroupDifference,
         },
         results::analysis_results::AnalysisResults,
     };
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
 
     /// Creates a simple mock AnalysisResults instance for testing
     #[allow(clippy::too_many_arguments)]
@@ -162,16 +208,34 @@ mod tests {
             lz_matches: group1_lz_matches,
             entropy: group1_entropy,
             estimated_size: 0, // Not used in optimization
+            has_estimate: false,
             zstd_size: group1_zstd_size,
             original_size: group1_original_size,
+            estimate_breakdown: None,
+            brotli_size: None,
+            deflate_size: None,
+            zstd_dict_size: None,
+            entropy_order1: None,
+            reference_size: None,
+            zstd_compress_ns: 0,
+            zstd_calibration_size: None,
         };
 
         let group2_metrics = GroupComparisonMetrics {
             lz_matches: group2_lz_matches,
             entropy: group2_entropy,
             estimated_size: 0, // Not used in optimization
+            has_estimate: false,
             zstd_size: group2_zstd_size,
             original_size: group2_original_size,
+            estimate_breakdown: None,
+            brotli_size: None,
+            deflate_size: None,
+            zstd_dict_size: None,
+            entropy_order1: None,
+            reference_size: None,
+            zstd_compress_ns: 0,
+            zstd_calibration_size: None,
         };
 
         let difference = GroupDifference::from_metrics(&group1_metrics, &group2_metrics);
@@ -184,6 +248,10 @@ mod tests {
             difference,
             baseline_comparison_metrics: vec![],
             split_comparison_metrics: vec![],
+            residual_entropy: None,
+            empty_groups: Vec::new(),
+            size_mismatch_bytes: 0,
+            truncated_data_bytes: 0,
         };
 
         AnalysisResults {
@@ -228,23 +296,54 @@ mod tests {
         assert!(result.group_2.entropy_multiplier <= config.max_entropy_multiplier);
 
         // Assert the error is below 5 (known correct assumption)
-        let group1_metrics = extract_group1_metrics(0, &original_results);
+        let group1_metrics = extract_group1_metrics(0, &original_results, false);
         let group1_error = calculate_error_for_bruteforce_metrics(
             &group1_metrics,
             result.group_1.lz_match_multiplier,
             result.group_1.entropy_multiplier,
+            ErrorMetric::Absolute,
         );
         assert!(group1_error < 5.0);
 
-        let group2_metrics = extract_group2_metrics(0, &original_results);
+        let group2_metrics = extract_group2_metrics(0, &original_results, false);
         let group2_error = calculate_error_for_bruteforce_metrics(
             &group2_metrics,
             result.group_2.lz_match_multiplier,
             result.group_2.entropy_multiplier,
+            ErrorMetric::Absolute,
         );
         assert!(group2_error < 5.0);
     }
 
+    #[test]
+    fn stops_search_when_max_duration_is_exceeded() {
+        let original_results = vec![create_mock_analysis_results(
+            100, 1.0, 110, 1000, // Group 1
+            200, 1.5, 220, 1000, // Group 2
+        )];
+
+        let config = BruteForceConfig {
+            max_duration: Some(Duration::ZERO),
+            ..BruteForceConfig::default()
+        };
+        let result =
+            find_optimal_split_result_coefficients_for_comparison(0, &config, &original_results);
+        assert!(result.group_1.timed_out);
+        assert!(result.group_2.timed_out);
+
+        // A search over a small grid completes within the budget.
+        let config = BruteForceConfig {
+            lz_step_size: 0.01,
+            entropy_step_size: 0.01,
+            max_duration: Some(Duration::from_secs(60)),
+            ..BruteForceConfig::default()
+        };
+        let result =
+            find_optimal_split_result_coefficients_for_comparison(0, &config, &original_results);
+        assert!(!result.group_1.timed_out);
+        assert!(!result.group_2.timed_out);
+    }
+
     #[test]
     fn handles_empty_split_results() {
         // Test the function with an empty results array
@@ -266,4 +365,41 @@ mod tests {
             config.min_entropy_multiplier
         );
     }
+
+    #[test]
+    fn reports_progress_of_each_group() {
+        let mut results = vec![create_mock_analysis_results(
+            100, 1.0, 110, 1000, // Group 1
+            200, 1.5, 220, 1000, // Group 2
+        )];
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let config = BruteForceConfig {
+            lz_step_size: 0.01,
+            entropy_step_size: 0.01,
+            progress: Some(ProgressCallback::new(move |progress| {
+                sink.lock().unwrap().push((
+                    progress.group_name.to_string(),
+                    progress.fraction_done,
+                    progress.best_error,
+                ));
+            })),
+            ..BruteForceConfig::default()
+        };
+        let optimized = find_optimal_split_result_coefficients(&mut results, Some(&config));
+
+        let reports = reports.lock().unwrap();
+        for (group, result) in [
+            ("group 1", optimized[0].1.group_1),
+            ("group 2", optimized[0].1.group_2),
+        ] {
+            let group_reports: Vec<_> = reports.iter().filter(|r| r.0 == group).collect();
+            // Roughly one report per percent, and a last one once done.
+            assert!(group_reports.len() >= 50, "{} reports", group_reports.len());
+            let last = group_reports.last().unwrap();
+            assert_eq!(last.1, 1.0);
+            assert_eq!(last.2, result.min_error);
+            assert!(group_reports.windows(2).all(|w| w[0].2 >= w[1].2));
+        }
+    }
 }
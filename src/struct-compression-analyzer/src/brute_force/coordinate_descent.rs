@@ -0,0 +1,226 @@
+//! A faster alternative to the exhaustive grid search of the brute force optimizer.
+//!
+//! The grid search tests every pair of coefficients, which gets slow with fine step sizes.
+//! `find_optimal_coefficients_gradient` instead runs a coordinate descent (pattern search):
+//! it starts from the midpoint of the configured ranges, moves along one coefficient at a time
+//! while that lowers the error, and halves the step once no move helps. It stops once the steps
+//! are smaller than the configured step sizes, i.e. at the resolution of the grid.
+//!
+//! The search has no randomness, so the same metrics and config always give the same result.
+//! Like any local search, it may settle in a local minimum the exhaustive search would avoid.
+
+use super::{
+    calculate_error_for_bruteforce_metrics, BruteForceComparisonMetrics, BruteForceConfig,
+    OptimizationResult, DURATION_CHECK_INTERVAL,
+};
+use derive_more::FromStr;
+use std::time::Instant;
+
+/// How the coefficients are searched, see [`BruteForceConfig::strategy`].
+///
+/// Parsed case-insensitively from the variant name, e.g. `coordinatedescent`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromStr)]
+pub enum OptimizationStrategy {
+    /// Test every pair of coefficients on the grid, in parallel. Finds the global minimum.
+    #[default]
+    Exhaustive,
+    /// Coordinate descent from the midpoint of the ranges, see the [module docs](self).
+    /// Much faster, but may settle in a local minimum.
+    CoordinateDescent,
+}
+
+/// Finds the optimal coefficients (lz_match_multiplier and entropy_multiplier) for a given
+/// set of metrics by running a coordinate descent, respecting the ranges of the config.
+///
+/// # Arguments
+///
+/// * `metrics` - The metrics to find optimal coefficients for
+/// * `config` - Configuration for the optimization process. The step sizes are the
+///   resolution the search stops at.
+///
+/// # Returns
+///
+/// The optimal [`OptimizationResult`] containing the best coefficients found,
+/// and the error for them ([`OptimizationResult::min_error`]).
+/// If [`BruteForceConfig::max_duration`] is exceeded, this is the best result found so far.
+pub(crate) fn find_optimal_coefficients_gradient(
+    metrics: &[BruteForceComparisonMetrics],
+    config: &BruteForceConfig,
+) -> OptimizationResult {
+    let error_at = |lz_multiplier: f64, entropy_multiplier: f64| {
+        calculate_error_for_bruteforce_metrics(
+            metrics,
+            lz_multiplier,
+            entropy_multiplier,
+            config.error_metric,
+        )
+    };
+    let clamp_lz = |value: f64| value.clamp(config.min_lz_multiplier, config.max_lz_multiplier);
+    let clamp_entropy =
+        |value: f64| value.clamp(config.min_entropy_multiplier, config.max_entropy_multiplier);
+
+    let lz_range = config.max_lz_multiplier - config.min_lz_multiplier;
+    let entropy_range = config.max_entropy_multiplier - config.min_entropy_multiplier;
+    let mut best = OptimizationResult {
+        lz_match_multiplier: config.min_lz_multiplier + lz_range / 2.0,
+        entropy_multiplier: config.min_entropy_multiplier + entropy_range / 2.0,
+        ..Default::default()
+    };
+    best.min_error = error_at(best.lz_match_multiplier, best.entropy_multiplier);
+
+    let deadline = config
+        .max_duration
+        .map(|duration| Instant::now() + duration);
+    let mut iterations_until_check = DURATION_CHECK_INTERVAL;
+
+    // A step size of 0 would never be reached by halving.
+    let min_lz_step = config.lz_step_size.max(f64::MIN_POSITIVE);
+    let min_entropy_step = config.entropy_step_size.max(f64::MIN_POSITIVE);
+    let mut lz_step = lz_range / 4.0;
+    let mut entropy_step = entropy_range / 4.0;
+    while lz_step >= min_lz_step || entropy_step >= min_entropy_step {
+        iterations_until_check -= 1;
+        if iterations_until_check == 0 {
+            iterations_until_check = DURATION_CHECK_INTERVAL;
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                best.timed_out = true;
+                break;
+            }
+        }
+
+        let (lz, entropy) = (best.lz_match_multiplier, best.entropy_multiplier);
+        let candidates = [
+            (clamp_lz(lz - lz_step), entropy),
+            (clamp_lz(lz + lz_step), entropy),
+            (lz, clamp_entropy(entropy - entropy_step)),
+            (lz, clamp_entropy(entropy + entropy_step)),
+        ];
+
+        let mut improved = false;
+        for (lz_multiplier, entropy_multiplier) in candidates {
+            let error = error_at(lz_multiplier, entropy_multiplier);
+            if error < best.min_error {
+                best.lz_match_multiplier = lz_multiplier;
+                best.entropy_multiplier = entropy_multiplier;
+                best.min_error = error;
+                improved = true;
+            }
+        }
+
+        if !improved {
+            lz_step /= 2.0;
+            entropy_step /= 2.0;
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::SizeEstimationParameters;
+    use crate::brute_force::find_optimal_coefficients_for_metrics_parallel;
+    use crate::utils::analyze_utils::size_estimate;
+
+    /// Metrics of files whose zstd sizes are exactly what the estimator predicts
+    /// with the given coefficients.
+    fn metrics_fitting(
+        lz_multiplier: f64,
+        entropy_multiplier: f64,
+    ) -> Vec<BruteForceComparisonMetrics> {
+        [
+            (100, 5.0, 2000),
+            (400, 3.5, 4000),
+            (50, 7.0, 1000),
+            (900, 2.0, 8000),
+        ]
+        .into_iter()
+        .map(
+            |(lz_matches, entropy, original_size)| BruteForceComparisonMetrics {
+                lz_matches,
+                entropy,
+                zstd_size: size_estimate(SizeEstimationParameters {
+                    name: "",
+                    data_len: original_size as usize,
+                    data: None,
+                    num_lz_matches: lz_matches as usize,
+                    entropy,
+                    lz_match_multiplier: lz_multiplier,
+                    entropy_multiplier,
+                }) as u64,
+                original_size,
+            },
+        )
+        .collect()
+    }
+
+    #[test]
+    fn converges_to_within_a_grid_step_of_exhaustive_search() {
+        let metrics = metrics_fitting(0.62, 1.21);
+        let config = BruteForceConfig {
+            lz_step_size: 0.001,
+            entropy_step_size: 0.01,
+            ..BruteForceConfig::default()
+        };
+
+        let exhaustive = find_optimal_coefficients_for_metrics_parallel(&metrics, &config, None);
+        let descent = find_optimal_coefficients_gradient(&metrics, &config);
+
+        // Error of the exhaustive answer moved by one grid step, in any direction.
+        let error_at = |lz, entropy| {
+            calculate_error_for_bruteforce_metrics(&metrics, lz, entropy, config.error_metric)
+        };
+        let one_step_error = [
+            (config.lz_step_size, 0.0),
+            (-config.lz_step_size, 0.0),
+            (0.0, config.entropy_step_size),
+            (0.0, -config.entropy_step_size),
+        ]
+        .iter()
+        .map(|(lz, entropy)| {
+            error_at(
+                exhaustive.lz_match_multiplier + lz,
+                exhaustive.entropy_multiplier + entropy,
+            )
+        })
+        .fold(0.0, f64::max);
+
+        assert!(!descent.is_penalized());
+        assert!(
+            descent.min_error <= one_step_error,
+            "descent error {} exceeds one grid step from exhaustive ({} at {:?})",
+            descent.min_error,
+            exhaustive.min_error,
+            (
+                exhaustive.lz_match_multiplier,
+                exhaustive.entropy_multiplier
+            )
+        );
+    }
+
+    #[test]
+    fn is_deterministic_and_respects_bounds() {
+        let metrics = metrics_fitting(0.9, 1.7);
+        let config = BruteForceConfig {
+            max_lz_multiplier: 0.5,
+            max_entropy_multiplier: 1.5,
+            strategy: OptimizationStrategy::CoordinateDescent,
+            ..BruteForceConfig::default()
+        };
+
+        let first = find_optimal_coefficients_gradient(&metrics, &config);
+        let second = find_optimal_coefficients_gradient(&metrics, &config);
+        assert_eq!(first.lz_match_multiplier, second.lz_match_multiplier);
+        assert_eq!(first.entropy_multiplier, second.entropy_multiplier);
+        assert_eq!(first.min_error, second.min_error);
+
+        // The true coefficients are out of range, so the search ends at the bounds.
+        assert!(first.lz_match_multiplier <= config.max_lz_multiplier);
+        assert!(first.entropy_multiplier <= config.max_entropy_multiplier);
+
+        // Dispatched by the strategy of the config.
+        let dispatched = find_optimal_coefficients_for_metrics_parallel(&metrics, &config, None);
+        assert_eq!(dispatched.min_error, first.min_error);
+    }
+}
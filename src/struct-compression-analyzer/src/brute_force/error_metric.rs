@@ -0,0 +1,70 @@
+//! How the difference between an estimated and an actual (zstd) size is scored
+//! while brute forcing coefficients.
+//!
+//! The errors of all files are summed, so with [`ErrorMetric::Absolute`] large files dominate
+//! the total and small files barely affect the chosen coefficients. The relative metrics
+//! weigh every file equally, regardless of its size.
+
+use derive_more::FromStr;
+
+/// The error of a single estimate, summed over all files to score a set of coefficients.
+///
+/// Parsed case-insensitively from the variant name, e.g. `relative` or `logratio`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromStr)]
+pub enum ErrorMetric {
+    /// `|estimated - zstd|`, in bytes.
+    #[default]
+    Absolute,
+    /// `|estimated - zstd| / zstd`.
+    Relative,
+    /// `((estimated - zstd) / zstd)²`. Like [`Self::Relative`], but punishes outliers harder.
+    SquaredRelative,
+    /// `|ln(estimated / zstd)|`. Symmetric: over and under estimating by the same factor
+    /// give the same error.
+    LogRatio,
+}
+
+impl ErrorMetric {
+    /// Calculates the error of an estimated size against the actual zstd size.
+    /// Sizes of 0 are treated as 1 in the relative metrics, to avoid dividing by 0.
+    #[inline(always)]
+    pub fn error(self, estimated_size: f64, zstd_size: f64) -> f64 {
+        match self {
+            ErrorMetric::Absolute => (estimated_size - zstd_size).abs(),
+            ErrorMetric::Relative => (estimated_size - zstd_size).abs() / zstd_size.max(1.0),
+            ErrorMetric::SquaredRelative => {
+                let relative = (estimated_size - zstd_size) / zstd_size.max(1.0);
+                relative * relative
+            }
+            ErrorMetric::LogRatio => (estimated_size.max(1.0) / zstd_size.max(1.0)).ln().abs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_metrics_ignore_scale() {
+        for metric in [
+            ErrorMetric::Relative,
+            ErrorMetric::SquaredRelative,
+            ErrorMetric::LogRatio,
+        ] {
+            let small = metric.error(110.0, 100.0);
+            let large = metric.error(110_000.0, 100_000.0);
+            assert!((small - large).abs() < 1e-9, "{metric:?}");
+        }
+        assert_eq!(ErrorMetric::Absolute.error(110.0, 100.0), 10.0);
+        assert_eq!(ErrorMetric::Absolute.error(110_000.0, 100_000.0), 10_000.0);
+    }
+
+    #[test]
+    fn log_ratio_is_symmetric() {
+        let over = ErrorMetric::LogRatio.error(200.0, 100.0);
+        let under = ErrorMetric::LogRatio.error(50.0, 100.0);
+        assert!((over - under).abs() < 1e-9);
+        assert_eq!("squaredrelative".parse(), Ok(ErrorMetric::SquaredRelative));
+    }
+}
@@ -0,0 +1,245 @@
+//! The grid search for the coefficients, and the reduced metrics it runs on.
+
+use super::coordinate_descent::find_optimal_coefficients_gradient;
+use super::progress::ProgressReporter;
+use super::{
+    calculate_error, BruteForceConfig, ErrorMetric, OptimizationResult, OptimizationStrategy,
+    DURATION_CHECK_INTERVAL,
+};
+use crate::comparison::GroupComparisonMetrics;
+use rayon::prelude::*;
+use std::time::Instant;
+
+/// Optimized, reduced form of [`GroupComparisonMetrics`],
+/// meant for storing only the fields used during brute forcing.
+#[derive(Clone, Default, Debug, PartialEq, Copy)]
+pub(crate) struct BruteForceComparisonMetrics {
+    /// Number of total LZ matches
+    pub lz_matches: u64,
+    /// Amount of entropy in the input data set
+    pub entropy: f64,
+    /// Size the estimate is fitted to: the size compressed by zstd at the calibration level,
+    /// or the reference size with [`BruteForceConfig::use_reference_size`].
+    pub zstd_size: u64,
+    /// Size of the original data.
+    pub original_size: u64,
+}
+
+impl BruteForceComparisonMetrics {
+    /// Reduces [`GroupComparisonMetrics`] to the fields used during brute forcing.
+    ///
+    /// The zstd size at the calibration level (if any) replaces the reported zstd size,
+    /// and with `use_reference_size`, the reference size (if any) replaces both.
+    pub(crate) fn new(value: &GroupComparisonMetrics, use_reference_size: bool) -> Self {
+        let zstd_size = match value.reference_size {
+            Some(reference_size) if use_reference_size => reference_size,
+            _ => value.zstd_calibration_size.unwrap_or(value.zstd_size),
+        };
+        BruteForceComparisonMetrics {
+            lz_matches: value.lz_matches,
+            entropy: value.entropy,
+            zstd_size,
+            original_size: value.original_size,
+        }
+    }
+}
+
+/// Finds the optimal coefficients (lz_match_multiplier and entropy_multiplier) for a given
+/// set of metrics by running a brute force optimization. This runs in parallel on all threads
+/// of the current rayon pool, splitting the LZ range into one chunk per thread.
+///
+/// With [`OptimizationStrategy::CoordinateDescent`], this runs
+/// [`find_optimal_coefficients_gradient`] on the current thread instead.
+///
+/// # Arguments
+///
+/// * `metrics` - The metrics to find optimal coefficients for
+/// * `config` - Configuration for the optimization process
+/// * `progress` - Where to report the progress of the search to, if requested
+///
+/// # Returns
+///
+/// The optimal [`OptimizationResult`] containing the best coefficients
+pub(crate) fn find_optimal_coefficients_for_metrics_parallel(
+    metrics: &[BruteForceComparisonMetrics],
+    config: &BruteForceConfig,
+    progress: Option<&ProgressReporter>,
+) -> OptimizationResult {
+    if config.strategy == OptimizationStrategy::CoordinateDescent {
+        let best_result = find_optimal_coefficients_gradient(metrics, config);
+        if let Some(progress) = progress {
+            progress.finish(best_result.min_error);
+        }
+        return best_result;
+    }
+
+    // Determine how to split the lz range
+    let num_chunks = rayon::current_num_threads();
+    let lz_range = config.max_lz_multiplier - config.min_lz_multiplier;
+    let chunk_size = lz_range / num_chunks as f64;
+
+    // Create chunks for parallel processing
+    let mut chunks = Vec::with_capacity(num_chunks);
+    for x in 0..num_chunks {
+        let start = config.min_lz_multiplier + (x as f64 * chunk_size);
+        let end = if x == num_chunks - 1 {
+            config.max_lz_multiplier
+        } else {
+            config.min_lz_multiplier + ((x + 1) as f64 * chunk_size)
+        };
+
+        chunks.push((start, end));
+    }
+
+    // Process chunks in parallel
+    let results: Vec<_> = chunks
+        .par_iter()
+        .map(|(start, end)| {
+            find_optimal_coefficients_for_metrics(
+                metrics,
+                &BruteForceConfig {
+                    min_lz_multiplier: *start,
+                    max_lz_multiplier: *end,
+                    min_entropy_multiplier: config.min_entropy_multiplier,
+                    max_entropy_multiplier: config.max_entropy_multiplier,
+                    entropy_step_size: config.entropy_step_size,
+                    lz_step_size: config.lz_step_size,
+                    max_duration: config.max_duration,
+                    error_metric: config.error_metric,
+                    strategy: config.strategy,
+                    progress: None,
+                    use_reference_size: config.use_reference_size,
+                },
+                progress,
+            )
+        })
+        .collect();
+
+    // Find the overall best result using a simple for loop
+    let mut best_result = OptimizationResult {
+        min_error: f64::MAX,
+        ..Default::default()
+    };
+    let mut timed_out = false;
+    for result in results {
+        timed_out |= result.timed_out;
+        if result.min_error < best_result.min_error {
+            best_result = result;
+        }
+    }
+
+    best_result.timed_out = timed_out;
+    if let Some(progress) = progress {
+        progress.finish(best_result.min_error);
+    }
+    best_result
+}
+
+/// Finds the optimal coefficients (lz_match_multiplier and entropy_multiplier) for a given
+/// set of metrics by running a brute force optimization.
+///
+/// # Arguments
+///
+/// * `metrics` - The metrics to find optimal coefficients for
+/// * `config` - Configuration for the optimization process
+/// * `progress` - Where to report each searched LZ multiplier to, if requested
+///
+/// # Returns
+///
+/// The optimal [`OptimizationResult`] containing the best coefficients,
+/// and the minimum error found for them ([`OptimizationResult::min_error`]).
+/// If [`BruteForceConfig::max_duration`] is exceeded, this is the best result found so far.
+pub(crate) fn find_optimal_coefficients_for_metrics(
+    metrics: &[BruteForceComparisonMetrics],
+    config: &BruteForceConfig,
+    progress: Option<&ProgressReporter>,
+) -> OptimizationResult {
+    let mut best_result = OptimizationResult {
+        min_error: f64::MAX,
+        ..Default::default()
+    };
+    let deadline = config
+        .max_duration
+        .map(|duration| Instant::now() + duration);
+    let mut iterations_until_check = DURATION_CHECK_INTERVAL;
+
+    let mut lz_multiplier = config.min_lz_multiplier;
+    'search: while lz_multiplier <= config.max_lz_multiplier {
+        let mut entropy_multiplier = config.min_entropy_multiplier;
+        while entropy_multiplier <= config.max_entropy_multiplier {
+            // Checking the time is slow compared to an iteration, so only do it periodically.
+            iterations_until_check -= 1;
+            if iterations_until_check == 0 {
+                iterations_until_check = DURATION_CHECK_INTERVAL;
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    best_result.timed_out = true;
+                    break 'search;
+                }
+            }
+
+            // Calculate the error with the given coefficients
+            let error = calculate_error_for_bruteforce_metrics(
+                metrics,
+                lz_multiplier,
+                entropy_multiplier,
+                config.error_metric,
+            );
+
+            // Update if better than current best
+            if error < best_result.min_error {
+                best_result = OptimizationResult {
+                    lz_match_multiplier: lz_multiplier,
+                    entropy_multiplier,
+                    timed_out: false,
+                    min_error: error,
+                };
+            }
+
+            entropy_multiplier += config.entropy_step_size;
+        }
+
+        if let Some(progress) = progress {
+            progress.row_done(best_result.min_error);
+        }
+        lz_multiplier += config.lz_step_size;
+    }
+
+    best_result
+}
+
+/// Calculates the error for a given set of metrics with specified coefficients.
+/// This returns the sum of all errors for all results in the metrics slice.
+///
+/// # Arguments
+///
+/// * `metrics` - The metrics to calculate the error for
+/// * `lz_match_multiplier` - The LZ match multiplier to test
+/// * `entropy_multiplier` - The entropy multiplier to test
+/// * `error_metric` - How the error of each result is scored
+///
+/// # Returns
+///
+/// The sum of all errors for the given metrics with the specified coefficients
+#[inline(always)]
+pub(crate) fn calculate_error_for_bruteforce_metrics(
+    metrics: &[BruteForceComparisonMetrics],
+    lz_match_multiplier: f64,
+    entropy_multiplier: f64,
+    error_metric: ErrorMetric,
+) -> f64 {
+    let mut total_error = 0.0f64;
+
+    for result in metrics {
+        total_error += calculate_error(
+            result.lz_matches,
+            result.entropy,
+            result.zstd_size,
+            result.original_size,
+            lz_match_multiplier,
+            entropy_multiplier,
+            error_metric,
+        );
+    }
+
+    total_error
+}
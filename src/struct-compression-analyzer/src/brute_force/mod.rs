@@ -30,17 +30,28 @@
 
 pub mod brute_force_custom;
 pub mod brute_force_split;
+pub mod coordinate_descent;
+pub mod error_metric;
+mod grid_search;
+pub mod progress;
 use crate::analyzer::SizeEstimationParameters;
 use crate::comparison::{GroupComparisonMetrics, GroupDifference};
 use crate::results::analysis_results::AnalysisResults;
-use crate::utils::analyze_utils::size_estimate;
+use crate::utils::analyze_utils::{size_estimate, size_estimate_breakdown};
 use brute_force_custom::{
     find_optimal_custom_result_coefficients, CustomComparisonOptimizationResult,
 };
 use brute_force_split::{
     find_optimal_split_result_coefficients, SplitComparisonOptimizationResult,
 };
-use rayon::prelude::*;
+pub use coordinate_descent::OptimizationStrategy;
+pub use error_metric::ErrorMetric;
+pub(crate) use grid_search::{
+    calculate_error_for_bruteforce_metrics, find_optimal_coefficients_for_metrics_parallel,
+    BruteForceComparisonMetrics,
+};
+use progress::ProgressCallback;
+use std::time::Duration;
 
 /// Configuration for the brute force optimization process.
 #[derive(Debug, Clone)]
@@ -57,8 +68,28 @@ pub struct BruteForceConfig {
     pub max_entropy_multiplier: f64,
     /// Step size for entropy multiplier
     pub entropy_step_size: f64,
+    /// Maximum time to spend searching the coefficients of a single group.
+    /// Once exceeded, the search stops and returns the best coefficients found so far.
+    /// [`None`] searches the whole range.
+    pub max_duration: Option<Duration>,
+    /// How the error of each file is scored; the coefficients minimizing the sum are chosen.
+    pub error_metric: ErrorMetric,
+    /// How the coefficients are searched. The exhaustive search tests every pair on the grid
+    /// formed by the ranges and step sizes; the others use the step sizes as their resolution.
+    pub strategy: OptimizationStrategy,
+    /// Called as the search of each group advances, see [`progress`].
+    /// [`None`] tracks no progress.
+    pub progress: Option<ProgressCallback>,
+    /// Fits the estimates to the [`reference sizes`] of the groups instead of their zstd sizes,
+    /// where a group has one. Groups without a reference size still use their zstd size.
+    ///
+    /// [`reference sizes`]: crate::comparison::GroupComparisonMetrics::reference_size
+    pub use_reference_size: bool,
 }
 
+/// Number of tested coefficient pairs between checks of [`BruteForceConfig::max_duration`].
+const DURATION_CHECK_INTERVAL: u32 = 1024;
+
 impl Default for BruteForceConfig {
     fn default() -> Self {
         Self {
@@ -68,10 +99,26 @@ impl Default for BruteForceConfig {
             min_entropy_multiplier: 1.0,
             max_entropy_multiplier: 1.75,
             entropy_step_size: 0.001,
+            max_duration: None,
+            error_metric: ErrorMetric::Absolute,
+            strategy: OptimizationStrategy::Exhaustive,
+            progress: None,
+            use_reference_size: false,
         }
     }
 }
 
+impl BruteForceConfig {
+    /// Sets the function called with the progress of the search, see [`progress`].
+    pub fn with_progress(
+        mut self,
+        callback: impl Fn(&progress::OptimizationProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(ProgressCallback::new(callback));
+        self
+    }
+}
+
 /// Result of a brute force optimization.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct OptimizationResult {
@@ -79,6 +126,43 @@ pub struct OptimizationResult {
     pub lz_match_multiplier: f64,
     /// Optimized entropy multiplier
     pub entropy_multiplier: f64,
+    /// Whether the search was stopped early after exceeding [`BruteForceConfig::max_duration`].
+    /// If so, these are the best coefficients found before stopping.
+    pub timed_out: bool,
+    /// The error of these coefficients: the sum of the errors ([`BruteForceConfig::error_metric`])
+    /// between the estimated and zstd sizes of all files. A file whose estimate is on the wrong side of
+    /// its original size (i.e. predicts growth where zstd shrinks, or vice versa) adds
+    /// [`f32::MAX`], see [`OptimizationResult::is_penalized`].
+    pub min_error: f64,
+}
+
+impl OptimizationResult {
+    /// Whether the best coefficients still mispredict whether at least one file shrinks,
+    /// in which case [`Self::min_error`] is dominated by the penalty and not a size difference.
+    pub fn is_penalized(&self) -> bool {
+        self.min_error >= f32::MAX as f64
+    }
+}
+
+/// Suffix for a printed [`OptimizationResult`], noting whether the search timed out.
+pub(crate) fn timed_out_marker(result: &OptimizationResult) -> &'static str {
+    if result.timed_out {
+        " (timed out, best so far)"
+    } else {
+        ""
+    }
+}
+
+/// Formats the [`OptimizationResult::min_error`] of a result for printing.
+pub(crate) fn format_min_error(result: &OptimizationResult) -> String {
+    if result.is_penalized() {
+        "penalized".to_string()
+    } else if result.min_error.fract() == 0.0 {
+        format!("{:.0}", result.min_error)
+    } else {
+        // Relative error metrics
+        format!("{:.4}", result.min_error)
+    }
 }
 
 /// Calculates the error for a given set of LZ match and entropy multipliers.
@@ -91,10 +175,11 @@ pub struct OptimizationResult {
 /// * `original_size` - The original size of the input
 /// * `lz_match_multiplier` - The current LZ match multiplier
 /// * `entropy_multiplier` - The current entropy multiplier
+/// * `error_metric` - How the difference between the estimated and actual size is scored
 ///
 /// # Returns
 ///
-/// The error for the tested parameters (difference between estimated and actual size).
+/// The error for the tested parameters, per `error_metric`.
 #[inline(always)]
 pub(crate) fn calculate_error(
     // Compression Estimator Params
@@ -106,6 +191,7 @@ pub(crate) fn calculate_error(
     // Coefficients to Test
     lz_match_multiplier: f64,
     entropy_multiplier: f64,
+    error_metric: ErrorMetric,
 ) -> f64 {
     // Calculate estimated size with current coefficients
     let estimated_size = size_estimate(SizeEstimationParameters {
@@ -119,7 +205,7 @@ pub(crate) fn calculate_error(
     });
 
     // Calculate error (difference between estimated and actual size)
-    let error = ((estimated_size as f64) - (zstd_size as f64)).abs();
+    let error = error_metric.error(estimated_size as f64, zstd_size as f64);
 
     // If the ratios are on the opposite side of 1.0
     // (i.e.) estimate thinks its worse, when its better, impose a 'killing'
@@ -322,7 +408,7 @@ fn update_group_metrics(
     entropy_multiplier: f64,
 ) {
     // Recalculate estimated size with the optimized parameters
-    let estimated_size = size_estimate(SizeEstimationParameters {
+    let params = SizeEstimationParameters {
         name: "",
         data_len: metrics.original_size as usize,
         data: None,
@@ -330,10 +416,15 @@ fn update_group_metrics(
         entropy: metrics.entropy,
         lz_match_multiplier,
         entropy_multiplier,
-    });
+    };
+    let estimated_size = size_estimate(params);
 
-    // Update the estimated size
+    // Update the estimated size, and its terms if they were requested.
     metrics.estimated_size = estimated_size as u64;
+    metrics.has_estimate = true;
+    if metrics.estimate_breakdown.is_some() {
+        metrics.estimate_breakdown = Some(size_estimate_breakdown(params));
+    }
 }
 
 /// Updates a [`GroupDifference`] struct with recalculated values.
@@ -363,175 +454,6 @@ pub fn print_all_optimization_results<W: std::io::Write>(
     Ok(())
 }
 
-/// Optimized, reduced form of [`GroupComparisonMetrics`],
-/// meant for storing only the fields used during brute forcing.
-#[derive(Clone, Default, Debug, PartialEq, Copy)]
-pub(crate) struct BruteForceComparisonMetrics {
-    /// Number of total LZ matches
-    pub lz_matches: u64,
-    /// Amount of entropy in the input data set
-    pub entropy: f64,
-    /// Size compressed by zstd.
-    pub zstd_size: u64,
-    /// Size of the original data.
-    pub original_size: u64,
-}
-
-impl From<GroupComparisonMetrics> for BruteForceComparisonMetrics {
-    fn from(value: GroupComparisonMetrics) -> Self {
-        BruteForceComparisonMetrics {
-            lz_matches: value.lz_matches,
-            entropy: value.entropy,
-            zstd_size: value.zstd_size,
-            original_size: value.original_size,
-        }
-    }
-}
-
-/// Finds the optimal coefficients (lz_match_multiplier and entropy_multiplier) for a given
-/// set of metrics by running a brute force optimization. This runs in parallel on all threads.
-///
-/// # Arguments
-///
-/// * `metrics` - The metrics to find optimal coefficients for
-/// * `config` - Configuration for the optimization process
-///
-/// # Returns
-///
-/// The optimal [`OptimizationResult`] containing the best coefficients
-pub(crate) fn find_optimal_coefficients_for_metrics_parallel(
-    metrics: &[BruteForceComparisonMetrics],
-    config: &BruteForceConfig,
-) -> OptimizationResult {
-    // Determine how to split the lz range
-    let num_chunks = rayon::current_num_threads();
-    let lz_range = config.max_lz_multiplier - config.min_lz_multiplier;
-    let chunk_size = lz_range / num_chunks as f64;
-
-    // Create chunks for parallel processing
-    let mut chunks = Vec::with_capacity(num_chunks);
-    for x in 0..num_chunks {
-        let start = config.min_lz_multiplier + (x as f64 * chunk_size);
-        let end = if x == num_chunks - 1 {
-            config.max_lz_multiplier
-        } else {
-            config.min_lz_multiplier + ((x + 1) as f64 * chunk_size)
-        };
-
-        chunks.push((start, end));
-    }
-
-    // Process chunks in parallel
-    let results: Vec<_> = chunks
-        .par_iter()
-        .map(|(start, end)| {
-            find_optimal_coefficients_for_metrics(
-                metrics,
-                &BruteForceConfig {
-                    min_lz_multiplier: *start,
-                    max_lz_multiplier: *end,
-                    min_entropy_multiplier: config.min_entropy_multiplier,
-                    max_entropy_multiplier: config.max_entropy_multiplier,
-                    entropy_step_size: config.entropy_step_size,
-                    lz_step_size: config.lz_step_size,
-                },
-            )
-        })
-        .collect();
-
-    // Find the overall best result using a simple for loop
-    let mut best_result = OptimizationResult::default();
-    let mut min_error = f64::MAX;
-    for (result, error) in results {
-        if error < min_error {
-            min_error = error;
-            best_result = result;
-        }
-    }
-
-    best_result
-}
-
-/// Finds the optimal coefficients (lz_match_multiplier and entropy_multiplier) for a given
-/// set of metrics by running a brute force optimization.
-///
-/// # Arguments
-///
-/// * `metrics` - The metrics to find optimal coefficients for
-/// * `config` - Configuration for the optimization process
-///
-/// # Returns
-///
-/// The optimal [`OptimizationResult`] containing the best coefficients,
-/// and the minimum error found for this best result.
-pub(crate) fn find_optimal_coefficients_for_metrics(
-    metrics: &[BruteForceComparisonMetrics],
-    config: &BruteForceConfig,
-) -> (OptimizationResult, f64) {
-    let mut best_result = OptimizationResult::default();
-    let mut min_error = f64::MAX;
-
-    let mut lz_multiplier = config.min_lz_multiplier;
-    while lz_multiplier <= config.max_lz_multiplier {
-        let mut entropy_multiplier = config.min_entropy_multiplier;
-        while entropy_multiplier <= config.max_entropy_multiplier {
-            // Calculate the error with the given coefficients
-            let error =
-                calculate_error_for_bruteforce_metrics(metrics, lz_multiplier, entropy_multiplier);
-
-            // Update if better than current best
-            if error < min_error {
-                best_result = OptimizationResult {
-                    lz_match_multiplier: lz_multiplier,
-                    entropy_multiplier,
-                };
-
-                min_error = error;
-            }
-
-            entropy_multiplier += config.entropy_step_size;
-        }
-
-        lz_multiplier += config.lz_step_size;
-    }
-
-    (best_result, min_error)
-}
-
-/// Calculates the error for a given set of metrics with specified coefficients.
-/// This returns the sum of all errors for all results in the metrics slice.
-///
-/// # Arguments
-///
-/// * `metrics` - The metrics to calculate the error for
-/// * `lz_match_multiplier` - The LZ match multiplier to test
-/// * `entropy_multiplier` - The entropy multiplier to test
-///
-/// # Returns
-///
-/// The sum of all errors for the given metrics with the specified coefficients
-#[inline(always)]
-pub(crate) fn calculate_error_for_bruteforce_metrics(
-    metrics: &[BruteForceComparisonMetrics],
-    lz_match_multiplier: f64,
-    entropy_multiplier: f64,
-) -> f64 {
-    let mut total_error = 0.0f64;
-
-    for result in metrics {
-        total_error += calculate_error(
-            result.lz_matches,
-            result.entropy,
-            result.zstd_size,
-            result.original_size,
-            lz_match_multiplier,
-            entropy_multiplier,
-        );
-    }
-
-    total_error
-}
-
 /// These tests are crap, they weren't written by a human, after all.
 #[cfg(test)]
 mod tests {
@@ -588,16 +510,34 @@ mod tests {
             lz_matches: GROUP1_LZ_MATCHES,
             entropy: GROUP1_ENTROPY,
             estimated_size: GROUP1_ESTIMATED_SIZE,
+            has_estimate: true,
             zstd_size: GROUP1_ZSTD_SIZE,
             original_size: GROUP1_ORIGINAL_SIZE,
+            estimate_breakdown: None,
+            brotli_size: None,
+            deflate_size: None,
+            zstd_dict_size: None,
+            entropy_order1: None,
+            reference_size: None,
+            zstd_compress_ns: 0,
+            zstd_calibration_size: None,
         };
 
         let group2_metrics = GroupComparisonMetrics {
             lz_matches: GROUP2_LZ_MATCHES,
             entropy: GROUP2_ENTROPY,
             estimated_size: GROUP2_ESTIMATED_SIZE,
+            has_estimate: true,
             zstd_size: GROUP2_ZSTD_SIZE,
             original_size: GROUP2_ORIGINAL_SIZE,
+            estimate_breakdown: None,
+            brotli_size: None,
+            deflate_size: None,
+            zstd_dict_size: None,
+            entropy_order1: None,
+            reference_size: None,
+            zstd_compress_ns: 0,
+            zstd_calibration_size: None,
         };
 
         let difference = GroupDifference {
@@ -613,16 +553,34 @@ mod tests {
             lz_matches: GROUP1_LZ_MATCHES,
             entropy: GROUP1_ENTROPY,
             estimated_size: GROUP1_ESTIMATED_SIZE,
+            has_estimate: true,
             zstd_size: GROUP1_ZSTD_SIZE,
             original_size: GROUP1_ORIGINAL_SIZE,
+            estimate_breakdown: None,
+            brotli_size: None,
+            deflate_size: None,
+            zstd_dict_size: None,
+            entropy_order1: None,
+            reference_size: None,
+            zstd_compress_ns: 0,
+            zstd_calibration_size: None,
         };
 
         let group_metrics = vec![GroupComparisonMetrics {
             lz_matches: GROUP2_LZ_MATCHES,
             entropy: GROUP2_ENTROPY,
             estimated_size: GROUP2_ESTIMATED_SIZE,
+            has_estimate: true,
             zstd_size: GROUP2_ZSTD_SIZE,
             original_size: GROUP2_ORIGINAL_SIZE,
+            estimate_breakdown: None,
+            brotli_size: None,
+            deflate_size: None,
+            zstd_dict_size: None,
+            entropy_order1: None,
+            reference_size: None,
+            zstd_compress_ns: 0,
+            zstd_calibration_size: None,
         }];
 
         let group_difference = GroupDifference {
@@ -653,6 +611,10 @@ mod tests {
                 difference,
                 baseline_comparison_metrics: Vec::new(),
                 split_comparison_metrics: Vec::new(),
+                residual_entropy: None,
+                empty_groups: Vec::new(),
+                size_mismatch_bytes: 0,
+                truncated_data_bytes: 0,
             }],
             custom_comparisons: vec![GroupComparisonResult {
                 name: TEST_NAME_CUSTOM.to_string(),
@@ -675,6 +637,11 @@ mod tests {
             min_entropy_multiplier: TEST_MIN_ENTROPY,
             max_entropy_multiplier: TEST_MAX_ENTROPY,
             entropy_step_size: TEST_ENTROPY_STEP,
+            max_duration: None,
+            error_metric: ErrorMetric::Absolute,
+            strategy: OptimizationStrategy::Exhaustive,
+            progress: None,
+            use_reference_size: false,
         };
 
         // Create mock result
@@ -750,8 +717,17 @@ mod tests {
             lz_matches: GROUP1_LZ_MATCHES,
             entropy: GROUP1_ENTROPY,
             estimated_size: GROUP1_ESTIMATED_SIZE,
+            has_estimate: true,
             zstd_size: GROUP1_ZSTD_SIZE,
             original_size: GROUP1_ORIGINAL_SIZE,
+            estimate_breakdown: None,
+            brotli_size: None,
+            deflate_size: None,
+            zstd_dict_size: None,
+            entropy_order1: None,
+            reference_size: None,
+            zstd_compress_ns: 0,
+            zstd_calibration_size: None,
         };
 
         let original_estimated_size = metrics.estimated_size;
@@ -769,6 +745,56 @@ mod tests {
         assert_eq!(metrics.original_size, GROUP1_ORIGINAL_SIZE);
     }
 
+    #[test]
+    fn fits_to_reference_size_when_requested() {
+        let metrics = GroupComparisonMetrics {
+            zstd_size: GROUP1_ZSTD_SIZE,
+            reference_size: Some(GROUP1_ZSTD_SIZE / 2),
+            ..GroupComparisonMetrics::default()
+        };
+        assert_eq!(
+            BruteForceComparisonMetrics::new(&metrics, false).zstd_size,
+            GROUP1_ZSTD_SIZE
+        );
+        assert_eq!(
+            BruteForceComparisonMetrics::new(&metrics, true).zstd_size,
+            GROUP1_ZSTD_SIZE / 2
+        );
+
+        // Groups without a reference size keep their zstd size.
+        let metrics = GroupComparisonMetrics {
+            reference_size: None,
+            ..metrics
+        };
+        assert_eq!(
+            BruteForceComparisonMetrics::new(&metrics, true).zstd_size,
+            GROUP1_ZSTD_SIZE
+        );
+    }
+
+    #[test]
+    fn fits_to_calibration_size_when_present() {
+        let metrics = GroupComparisonMetrics {
+            zstd_size: GROUP1_ZSTD_SIZE,
+            zstd_calibration_size: Some(GROUP1_ZSTD_SIZE + 10),
+            ..GroupComparisonMetrics::default()
+        };
+        assert_eq!(
+            BruteForceComparisonMetrics::new(&metrics, false).zstd_size,
+            GROUP1_ZSTD_SIZE + 10
+        );
+
+        // The reference size takes precedence when requested.
+        let metrics = GroupComparisonMetrics {
+            reference_size: Some(GROUP1_ZSTD_SIZE / 2),
+            ..metrics
+        };
+        assert_eq!(
+            BruteForceComparisonMetrics::new(&metrics, true).zstd_size,
+            GROUP1_ZSTD_SIZE / 2
+        );
+    }
+
     #[test]
     fn can_calculate_group_difference() {
         // Create test groups using constants
@@ -776,16 +802,34 @@ mod tests {
             lz_matches: GROUP1_LZ_MATCHES,
             entropy: GROUP1_ENTROPY,
             estimated_size: GROUP1_ESTIMATED_SIZE,
+            has_estimate: true,
             zstd_size: GROUP1_ZSTD_SIZE,
             original_size: GROUP1_ORIGINAL_SIZE,
+            estimate_breakdown: None,
+            brotli_size: None,
+            deflate_size: None,
+            zstd_dict_size: None,
+            entropy_order1: None,
+            reference_size: None,
+            zstd_compress_ns: 0,
+            zstd_calibration_size: None,
         };
 
         let group2_metrics = GroupComparisonMetrics {
             lz_matches: GROUP2_LZ_MATCHES,
             entropy: GROUP2_ENTROPY,
             estimated_size: GROUP2_ESTIMATED_SIZE,
+            has_estimate: true,
             zstd_size: GROUP2_ZSTD_SIZE,
             original_size: GROUP2_ORIGINAL_SIZE,
+            estimate_breakdown: None,
+            brotli_size: None,
+            deflate_size: None,
+            zstd_dict_size: None,
+            entropy_order1: None,
+            reference_size: None,
+            zstd_compress_ns: 0,
+            zstd_calibration_size: None,
         };
 
         let mut difference = GroupDifference {
@@ -0,0 +1,137 @@
+//! Progress reporting for the brute force optimization, which can take a long time.
+//!
+//! Set [`BruteForceConfig::progress`] to a [`ProgressCallback`] to receive an
+//! [`OptimizationProgress`] as the search of each group advances. The callback is
+//! invoked from the worker threads, each time the search of a group completes another percent,
+//! so it must be cheap and thread safe. Without a callback, no progress is tracked.
+
+use super::BruteForceConfig;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// The kind of comparison whose coefficients are being searched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonKind {
+    /// A split comparison, see [`SplitComparisonResult`].
+    ///
+    /// [`SplitComparisonResult`]: crate::comparison::split_comparison::SplitComparisonResult
+    Split,
+    /// A custom comparison, see [`GroupComparisonResult`].
+    ///
+    /// [`GroupComparisonResult`]: crate::comparison::compare_groups::GroupComparisonResult
+    Custom,
+}
+
+/// How far the search of the coefficients of a group has come.
+#[derive(Debug, Clone, Copy)]
+pub struct OptimizationProgress<'a> {
+    /// Kind of the comparison being processed.
+    pub kind: ComparisonKind,
+    /// Index of the comparison being processed, among comparisons of the same kind.
+    pub comparison_index: usize,
+    /// Number of comparisons of the same kind.
+    pub comparison_count: usize,
+    /// Name of the comparison being processed.
+    pub comparison_name: &'a str,
+    /// Group of the comparison being processed, e.g. `group 1` or `baseline`.
+    pub group_name: &'a str,
+    /// Share of the search of this group which is done, between 0 and 1.
+    pub fraction_done: f64,
+    /// The lowest error found so far for this group, see [`OptimizationResult::min_error`].
+    ///
+    /// [`OptimizationResult::min_error`]: super::OptimizationResult::min_error
+    pub best_error: f64,
+}
+
+/// A function receiving the [`OptimizationProgress`] of a brute force optimization.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(&OptimizationProgress) + Send + Sync>);
+
+impl ProgressCallback {
+    /// Wraps a function to be called with the progress of the optimization.
+    pub fn new(callback: impl Fn(&OptimizationProgress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// Tracks the progress of the search of a single group, shared by its worker threads.
+pub(crate) struct ProgressReporter<'a> {
+    callback: &'a ProgressCallback,
+    kind: ComparisonKind,
+    comparison_index: usize,
+    comparison_count: usize,
+    comparison_name: &'a str,
+    group_name: &'a str,
+    /// Number of LZ multiplier values searched, over all threads.
+    total_rows: u64,
+    completed_rows: AtomicU64,
+    /// Bits of the lowest error so far. Non-negative floats order the same as their bits.
+    best_error_bits: AtomicU64,
+}
+
+impl<'a> ProgressReporter<'a> {
+    /// Creates a reporter for a group, or [`None`] if the config has no [`BruteForceConfig::progress`].
+    pub(crate) fn new(
+        config: &'a BruteForceConfig,
+        kind: ComparisonKind,
+        comparison_index: usize,
+        comparison_count: usize,
+        comparison_name: &'a str,
+        group_name: &'a str,
+    ) -> Option<Self> {
+        let callback = config.progress.as_ref()?;
+        let lz_range = config.max_lz_multiplier - config.min_lz_multiplier;
+        let total_rows = (lz_range / config.lz_step_size)
+            .floor()
+            .clamp(0.0, u32::MAX as f64) as u64
+            + 1;
+        Some(Self {
+            callback,
+            kind,
+            comparison_index,
+            comparison_count,
+            comparison_name,
+            group_name,
+            total_rows,
+            completed_rows: AtomicU64::new(0),
+            best_error_bits: AtomicU64::new(f64::MAX.to_bits()),
+        })
+    }
+
+    /// Records that a thread searched all entropy multipliers for another LZ multiplier,
+    /// reporting the progress if it completes another percent.
+    pub(crate) fn row_done(&self, best_error: f64) {
+        self.best_error_bits
+            .fetch_min(best_error.to_bits(), Ordering::Relaxed);
+        let done = self.completed_rows.fetch_add(1, Ordering::Relaxed) + 1;
+        if done * 100 / self.total_rows != (done - 1) * 100 / self.total_rows {
+            self.report((done as f64 / self.total_rows as f64).min(1.0));
+        }
+    }
+
+    /// Reports the search of the group as complete.
+    pub(crate) fn finish(&self, best_error: f64) {
+        self.best_error_bits
+            .fetch_min(best_error.to_bits(), Ordering::Relaxed);
+        self.report(1.0);
+    }
+
+    fn report(&self, fraction_done: f64) {
+        (self.callback.0)(&OptimizationProgress {
+            kind: self.kind,
+            comparison_index: self.comparison_index,
+            comparison_count: self.comparison_count,
+            comparison_name: self.comparison_name,
+            group_name: self.group_name,
+            fraction_done,
+            best_error: f64::from_bits(self.best_error_bits.load(Ordering::Relaxed)),
+        });
+    }
+}
@@ -0,0 +1,352 @@
+//! Automatic search for the best way to split a field into separately grouped parts.
+//!
+//! For each [`AutoSplit`] in the schema, the field is split at every combination of up to
+//! [`AutoSplit::max_splits`] split points, placed at multiples of [`AutoSplit::granularity`]
+//! bits. Each layout groups every part of the field separately, i.e. it is the equivalent of
+//! a custom comparison with one `array` component per part:
+//!
+//! ```yaml
+//! # 32-bit 'color' field, split at bits 8 and 16
+//! split_8_8_16:
+//!   - { type: array, field: color, offset: 0, bits: 8 }
+//!   - { type: array, field: color, offset: 8, bits: 8 }
+//!   - { type: array, field: color, offset: 16, bits: 16 }
+//! ```
+//!
+//! The layouts are compared against the field as-is (a single `array` of the field),
+//! and are stored as a regular [`GroupComparisonResult`] named after
+//! [`auto_split_comparison_name`], so they are merged, printed and exported like any
+//! other custom comparison. The layouts are ranked by zstd size when printing, see
+//! [`rank_layouts`] and [`suggested_schema_snippet`].
+//!
+//! To avoid a combinatorial explosion, a single field may produce at most
+//! [`MAX_AUTO_SPLIT_LAYOUTS`] layouts.
+//!
+//! [`AutoSplit`]: crate::schema::AutoSplit
+//! [`AutoSplit::max_splits`]: crate::schema::AutoSplit::max_splits
+//! [`AutoSplit::granularity`]: crate::schema::AutoSplit::granularity
+
+use super::{
+    compare_groups::{process_single_comparison, GroupComparisonError, GroupComparisonResult},
+    GroupComparisonMetrics,
+};
+use crate::{
+    analyzer::{AnalyzerFieldState, CompressionOptions},
+    schema::{CustomComparison, GroupComponent, GroupComponentArray, Schema},
+};
+use ahash::AHashMap;
+use indexmap::IndexMap;
+use std::fmt::Write;
+
+/// Maximum number of layouts a single [`AutoSplit`](crate::schema::AutoSplit) may produce.
+pub const MAX_AUTO_SPLIT_LAYOUTS: usize = 256;
+
+/// Name of the comparison holding the layouts of the
+/// [`AutoSplit`](crate::schema::AutoSplit) of a given field.
+pub fn auto_split_comparison_name(field: &str) -> String {
+    format!("auto_split_{field}")
+}
+
+/// Name of the comparison group of a layout, e.g. `split_8_8_16`.
+///
+/// # Arguments
+/// * `widths` - The widths of the parts of the layout, in bits.
+pub fn layout_group_name(widths: &[u32]) -> String {
+    let widths: Vec<String> = widths.iter().map(|width| width.to_string()).collect();
+    format!("split_{}", widths.join("_"))
+}
+
+/// Enumerates all layouts of a field, as the widths of their parts (in bits).
+///
+/// Layouts with fewer split points come first; layouts with the same number of split points
+/// are ordered by their split points.
+///
+/// # Arguments
+/// * `field_bits` - The number of bits in the field.
+/// * `granularity` - Split points are placed at multiples of this many bits.
+/// * `max_splits` - Maximum number of split points in a single layout.
+///
+/// # Returns
+/// The layouts, or an error describing why the field can't be split.
+pub fn split_layouts(
+    field_bits: u32,
+    granularity: u32,
+    max_splits: u32,
+) -> Result<Vec<Vec<u32>>, String> {
+    if granularity == 0 || max_splits == 0 {
+        return Err("granularity and max_splits must be at least 1".to_string());
+    }
+
+    let points: Vec<u32> = (1..)
+        .map(|x| x * granularity)
+        .take_while(|point| *point < field_bits)
+        .collect();
+    if points.is_empty() {
+        return Err(format!(
+            "a {field_bits} bit field can't be split at multiples of {granularity} bits"
+        ));
+    }
+
+    let mut num_layouts = 0usize;
+    let mut combinations = 1usize; // C(n, k), for k = 1..=max_splits
+    for k in 1..=(max_splits as usize).min(points.len()) {
+        combinations = combinations * (points.len() - k + 1) / k;
+        num_layouts += combinations;
+        if num_layouts > MAX_AUTO_SPLIT_LAYOUTS {
+            return Err(format!(
+                "more than {MAX_AUTO_SPLIT_LAYOUTS} layouts; increase the granularity or reduce max_splits"
+            ));
+        }
+    }
+
+    let mut layouts = Vec::with_capacity(num_layouts);
+    for num_splits in 1..=(max_splits as usize).min(points.len()) {
+        push_combinations(&points, num_splits, 0, &mut Vec::new(), &mut |splits| {
+            let mut widths = Vec::with_capacity(splits.len() + 1);
+            let mut start = 0;
+            for &split in splits.iter().chain(std::iter::once(&field_bits)) {
+                widths.push(split - start);
+                start = split;
+            }
+            layouts.push(widths);
+        });
+    }
+
+    Ok(layouts)
+}
+
+/// Calls `f` with every combination of `count` items of `points`, from index `start` onwards.
+fn push_combinations(
+    points: &[u32],
+    count: usize,
+    start: usize,
+    current: &mut Vec<u32>,
+    f: &mut impl FnMut(&[u32]),
+) {
+    if current.len() == count {
+        f(current);
+        return;
+    }
+
+    for x in start..points.len() {
+        current.push(points[x]);
+        push_combinations(points, count, x + 1, current, f);
+        current.pop();
+    }
+}
+
+/// Creates the group components of a layout.
+fn layout_components(field: &str, widths: &[u32]) -> Vec<GroupComponent> {
+    let mut offset = 0;
+    widths
+        .iter()
+        .map(|&bits| {
+            let component = GroupComponent::Array(GroupComponentArray {
+                field: field.to_string(),
+                offset,
+                bits,
+                ..Default::default()
+            });
+            offset += bits;
+            component
+        })
+        .collect()
+}
+
+/// Analyzes all [`AutoSplit`](crate::schema::AutoSplit) searches defined in the [`Schema`].
+/// This is an internal API.
+///
+/// # Arguments
+///
+/// * `schema` - Reference to loaded schema definition
+/// * `field_stats` - Mutable reference to field statistics map
+/// * `compression_options` - Compression options, zstd compression level, etc.
+///
+/// # Returns
+///
+/// A [`GroupComparisonResult`] for each auto split, in schema order.
+pub(crate) fn analyze_auto_splits(
+    schema: &Schema,
+    field_stats: &mut AHashMap<String, AnalyzerFieldState>,
+    compression_options: CompressionOptions,
+) -> Result<Vec<GroupComparisonResult>, GroupComparisonError> {
+    schema
+        .analysis
+        .auto_split
+        .iter()
+        .map(|auto_split| {
+            let name = auto_split_comparison_name(&auto_split.field);
+            let field_bits = field_stats
+                .get(&auto_split.field)
+                .map(|field| field.lenbits)
+                .ok_or_else(|| {
+                    GroupComparisonError::InvalidConfiguration(format!(
+                        "Auto split of '{}': field not found.",
+                        auto_split.field
+                    ))
+                })?;
+            let layouts = split_layouts(field_bits, auto_split.granularity, auto_split.max_splits)
+                .map_err(|e| {
+                    GroupComparisonError::InvalidConfiguration(format!(
+                        "Auto split of '{}': {}.",
+                        auto_split.field, e
+                    ))
+                })?;
+
+            let comparison = CustomComparison {
+                description: format!(
+                    "Splits of '{}' at multiples of {} bits, with up to {} split points",
+                    auto_split.field, auto_split.granularity, auto_split.max_splits
+                ),
+                baseline: layout_components(&auto_split.field, &[field_bits]),
+                comparisons: layouts
+                    .iter()
+                    .map(|widths| {
+                        (
+                            layout_group_name(widths),
+                            layout_components(&auto_split.field, widths),
+                        )
+                    })
+                    .collect::<IndexMap<_, _>>(),
+                name,
+            };
+            process_single_comparison(&comparison, field_stats, compression_options.clone())
+        })
+        .collect()
+}
+
+/// Ranks the layouts of an auto split comparison by their zstd size, smallest first.
+///
+/// # Returns
+/// The indices of the layouts (into `group_metrics`), ordered by zstd size.
+/// Layouts with the same size keep their original order.
+pub fn rank_layouts(group_metrics: &[GroupComparisonMetrics]) -> Vec<usize> {
+    let mut ranked: Vec<usize> = (0..group_metrics.len()).collect();
+    ranked.sort_by_key(|&x| group_metrics[x].zstd_size);
+    ranked
+}
+
+/// Creates a `compare_groups` schema snippet comparing the field as-is against a layout,
+/// so a suggested layout can be kept in the schema.
+///
+/// # Arguments
+/// * `field` - Name of the split field.
+/// * `widths` - The widths of the parts of the layout, in bits.
+pub fn suggested_schema_snippet(field: &str, widths: &[u32]) -> String {
+    let mut snippet = String::new();
+    let _ = writeln!(snippet, "compare_groups:");
+    let _ = writeln!(snippet, "  - name: split_{field}");
+    let _ = writeln!(
+        snippet,
+        "    description: Group each part of '{field}' separately."
+    );
+    let _ = writeln!(snippet, "    baseline:");
+    let _ = writeln!(snippet, "      - {{ type: array, field: {field} }}");
+    let _ = writeln!(snippet, "    comparisons:");
+    let _ = writeln!(snippet, "      {}:", layout_group_name(widths));
+    let mut offset = 0;
+    for bits in widths {
+        let _ = writeln!(
+            snippet,
+            "        - {{ type: array, field: {field}, offset: {offset}, bits: {bits} }}"
+        );
+        offset += bits;
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::SchemaAnalyzer;
+
+    #[test]
+    fn enumerates_layouts_with_fewest_splits_first() {
+        let layouts = split_layouts(32, 8, 2).unwrap();
+        assert_eq!(
+            layouts,
+            vec![
+                vec![8, 24],
+                vec![16, 16],
+                vec![24, 8],
+                vec![8, 8, 16],
+                vec![8, 16, 8],
+                vec![16, 8, 8],
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unsplittable_or_oversized_searches() {
+        assert!(split_layouts(8, 8, 2).is_err());
+        assert!(split_layouts(32, 0, 2).is_err());
+        assert!(split_layouts(32, 8, 0).is_err());
+        // 63 split points, C(63, 1) + C(63, 2) = 2016 layouts
+        assert!(split_layouts(64, 1, 2).is_err());
+        // max_splits is capped by the number of split points
+        assert_eq!(split_layouts(16, 4, 100).unwrap().len(), 7);
+    }
+
+    #[test]
+    fn snippet_can_be_parsed_as_a_comparison() {
+        let snippet = suggested_schema_snippet("color", &[8, 8, 16]);
+        let yaml = format!(
+            "version: '1.0'\nroot:\n  type: group\n  fields:\n    color: 32\nanalysis:\n{}",
+            indent(&snippet)
+        );
+        let schema = Schema::from_yaml(&yaml).unwrap();
+        let comparison = &schema.analysis.compare_groups[0];
+        assert_eq!(comparison.name, "split_color");
+        let components = &comparison.comparisons["split_8_8_16"];
+        assert_eq!(components.len(), 3);
+        let GroupComponent::Array(last) = &components[2] else {
+            panic!("expected an array component");
+        };
+        assert_eq!((last.offset, last.bits), (16, 16));
+    }
+
+    fn indent(text: &str) -> String {
+        text.lines().map(|line| format!("  {line}\n")).collect()
+    }
+
+    #[test]
+    fn finds_split_of_field_with_constant_upper_bytes() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    value: 32
+analysis:
+  auto_split:
+    - field: value
+      max_splits: 1
+"#,
+        )
+        .unwrap();
+
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        let mut state = 0x12345678u32;
+        for _ in 0..4096 {
+            // Random low bytes, constant upper bytes.
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let value = (state & 0xFFFF) | 0x4242_0000;
+            analyzer.add_entry(&value.to_be_bytes()).unwrap();
+        }
+        let results = analyzer.generate_results().unwrap();
+
+        let comparison = &results.custom_comparisons[0];
+        assert_eq!(comparison.name, auto_split_comparison_name("value"));
+        assert_eq!(
+            comparison.group_names,
+            vec!["split_8_24", "split_16_16", "split_24_8"]
+        );
+
+        let best = rank_layouts(&comparison.group_metrics)[0];
+        assert_eq!(comparison.group_names[best], "split_16_16");
+        assert!(comparison.group_metrics[best].zstd_size < comparison.baseline_metrics.zstd_size);
+    }
+}
@@ -0,0 +1,142 @@
+use super::{GenerateBytesError, GenerateBytesResult};
+use crate::utils::{
+    analyze_utils::{bit_writer_to_reader, BitReaderContainer},
+    bitstream_ext::BitReaderExt,
+};
+use crate::{analyzer::AnalyzerFieldState, schema::GroupComponentByteSplit};
+use ahash::AHashMap;
+use bitstream_io::{BitWrite, BitWriter, Endianness};
+use std::io::{self, SeekFrom};
+
+/// Processes a [`GroupComponentByteSplit`], writing each byte position of the values of its
+/// field as a contiguous plane to a provided [`BitWriter`].
+///
+/// # Arguments
+/// * `field_stats` - A mutable reference to a map of field stats.
+/// * `writer` - The bit writer to write the planes to.
+/// * `split` - Contains info about the planes to write.
+pub(crate) fn write_byte_split<TWrite: io::Write, TEndian: Endianness>(
+    field_stats: &mut AHashMap<String, AnalyzerFieldState>,
+    writer: &mut BitWriter<TWrite, TEndian>,
+    split: &GroupComponentByteSplit,
+) -> GenerateBytesResult<()> {
+    let field = field_stats
+        .get_mut(&split.field)
+        .ok_or_else(|| GenerateBytesError::FieldNotFound(split.field.clone()))?;
+
+    let bits = field.lenbits;
+    if bits > 64 {
+        return Err(GenerateBytesError::InvalidComponentType(format!(
+            "byte_split of '{}' requires a field of up to 64 bits, but it has {bits}",
+            split.field
+        )));
+    }
+
+    let num_bytes = bits.div_ceil(8);
+    let planes: Vec<u32> = if split.planes.is_empty() {
+        (0..num_bytes).collect()
+    } else {
+        split.planes.clone()
+    };
+    if let Some(plane) = planes.iter().find(|&&plane| plane >= num_bytes) {
+        return Err(GenerateBytesError::InvalidComponentType(format!(
+            "byte_split of '{}' has no byte {plane}, as it only has {num_bytes} bytes",
+            split.field
+        )));
+    }
+
+    let mut reader = bit_writer_to_reader(&mut field.writer);
+    let remaining = match &mut reader {
+        BitReaderContainer::Msb(reader) => reader.remaining_bits(),
+        BitReaderContainer::Lsb(reader) => reader.remaining_bits(),
+    }
+    .map_err(|e| GenerateBytesError::SeekError {
+        source: e,
+        operation: "checking remaining bits".into(),
+    })?;
+
+    // Each plane is a pass over all (whole) values of the field.
+    let num_values = remaining / bits as u64;
+    for plane in planes {
+        reader
+            .seek_bits(SeekFrom::Start(0))
+            .map_err(|e| GenerateBytesError::SeekError {
+                source: e,
+                operation: format!("seeking to the start of byte plane {plane}"),
+            })?;
+
+        let shift = 8 * (num_bytes - 1 - plane);
+        for _ in 0..num_values {
+            let value = reader
+                .read(bits)
+                .map_err(|e| GenerateBytesError::ReadError {
+                    source: e,
+                    context: format!("reading {bits}-bit value to split"),
+                })?;
+
+            writer
+                .write_var::<u64>(8, (value >> shift) & 0xFF)
+                .map_err(|e| GenerateBytesError::WriteError {
+                    source: e,
+                    context: format!("writing byte plane {plane}"),
+                })?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comparison::compare_groups::test_helpers::{
+        create_mock_field_states, TEST_FIELD_NAME,
+    };
+    use crate::schema::BitOrder;
+    use bitstream_io::BigEndian;
+    use std::io::Cursor;
+
+    fn byte_split(data: &[u8], bits: u32, planes: Vec<u32>) -> GenerateBytesResult<Vec<u8>> {
+        let mut field_stats =
+            create_mock_field_states(TEST_FIELD_NAME, data, bits, BitOrder::Msb, BitOrder::Msb);
+        let mut output = Vec::new();
+        let mut writer = BitWriter::endian(Cursor::new(&mut output), BigEndian);
+        let component = GroupComponentByteSplit {
+            field: TEST_FIELD_NAME.to_string(),
+            planes,
+        };
+        write_byte_split(&mut field_stats, &mut writer, &component)?;
+        writer.byte_align().unwrap();
+        Ok(output)
+    }
+
+    #[test]
+    fn separates_planes_of_16_bit_values() {
+        // 0x1234, 0x5678, 0x9ABC
+        let data = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC];
+        assert_eq!(
+            byte_split(&data, 16, Vec::new()).unwrap(),
+            [0x12, 0x56, 0x9A, 0x34, 0x78, 0xBC]
+        );
+        // Only the low bytes.
+        assert_eq!(byte_split(&data, 16, vec![1]).unwrap(), [0x34, 0x78, 0xBC]);
+    }
+
+    #[test]
+    fn first_plane_of_partial_bytes_is_partial() {
+        // 12-bit values 0xABC, 0xDEF
+        let data = [0xAB, 0xCD, 0xEF];
+        assert_eq!(
+            byte_split(&data, 12, Vec::new()).unwrap(),
+            [0x0A, 0x0D, 0xBC, 0xEF]
+        );
+    }
+
+    #[test]
+    fn rejects_planes_past_the_end_of_the_field() {
+        assert!(matches!(
+            byte_split(&[0x12, 0x34], 16, vec![2]),
+            Err(GenerateBytesError::InvalidComponentType(_))
+        ));
+    }
+}
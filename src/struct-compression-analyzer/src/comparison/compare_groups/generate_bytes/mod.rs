@@ -13,15 +13,19 @@
 //!
 //! Two primary internal functions handle byte generation:
 //!
-//! - `generate_group_bytes`: Creates a [`Vec<u8>`] from group components
+//! - `with_group_bytes`: Generates the bytes of group components into a reused buffer,
+//!   and passes them to a callback
 //! - `generate_output_for_compare_groups_entry`: Writes directly to a provided bitstream
 //!
 //! # Component Types
 //!
-//! The module handles two primary component types:
+//! The module handles five primary component types:
 //!
 //! - Arrays: Sequential field values with optional bit slicing
 //! - Structs: Grouped fields with padding and alignment
+//! - RLE: Run-length encoded bytes of other components
+//! - Transforms: Field values transformed relative to the previous value, e.g. delta coded
+//! - Byte splits: Field values split into planes of their bytes
 //!
 //! # Error Handling
 //!
@@ -40,20 +44,44 @@
 //!
 //! - `write_array`: Array component processing
 //! - `write_struct`: Struct component processing
+//! - `transpose`: Byte transpose of fixed size records
+//! - `rle`: Run-length encoding of a byte stream
+//! - `transform`: Transform component processing
+//! - `byte_split`: Byte split component processing
 //!
 //! [`GenerateBytesError`]: crate::comparison::compare_groups::generate_bytes::GenerateBytesError
 use thiserror::Error;
+mod byte_split;
+mod rle;
+mod transform;
+mod transpose;
 mod write_array;
 mod write_struct;
 
+pub(crate) use transpose::transpose_records;
+
 pub(crate) type GenerateBytesResult<T> = std::result::Result<T, GenerateBytesError>;
+use crate::comparison::compare_groups::generate_bytes::byte_split::write_byte_split;
+use crate::comparison::compare_groups::generate_bytes::rle::rle_runs;
+use crate::comparison::compare_groups::generate_bytes::transform::write_transform;
 use crate::comparison::compare_groups::generate_bytes::write_array::write_array;
 use crate::comparison::compare_groups::generate_bytes::write_struct::write_struct;
 use crate::{analyzer::AnalyzerFieldState, schema::GroupComponent};
 use ahash::AHashMap;
 use bitstream_io::{BigEndian, BitWrite, BitWriter, Endianness};
+use std::cell::RefCell;
 use std::io::Cursor;
 
+/// Maximum number of buffers kept per thread by [`with_group_bytes`].
+/// Calls are not nested in practice; this only bounds the memory kept if they ever are.
+const MAX_POOLED_BUFFERS: usize = 2;
+
+thread_local! {
+    /// Buffers reused by [`with_group_bytes`]. Every group of every comparison of every file
+    /// is generated, so allocating a new buffer each time adds up in directory mode.
+    static GROUP_BYTES_BUFFERS: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
 /// Errors that can occur while generating bytes from a schema for analysis
 #[derive(Error, Debug)]
 pub enum GenerateBytesError {
@@ -87,7 +115,7 @@ pub enum GenerateBytesError {
         operation: String,
     },
 
-    #[error("Nested structure contains unsupported component type. Nested arrays and structs are not allowed within structs.")]
+    #[error("Nested structure contains unsupported component type. Nested arrays, structs, rle, transforms and byte splits are not allowed within structs.")]
     UnsupportedNestedComponent,
 }
 
@@ -98,8 +126,8 @@ pub enum GenerateBytesError {
 /// - `writer`: Bitstream writer implementing `std::io::Write`
 /// - `components`: Slice of group components to process
 ///
-/// # Panics
-/// - If encountering any component type other than Array or Struct
+/// # Errors
+/// - If encountering any component type other than Array, Struct, RLE, Transform or ByteSplit
 pub(crate) fn generate_output_for_compare_groups_entry<
     TWrite: std::io::Write,
     TEndian: Endianness,
@@ -112,28 +140,71 @@ pub(crate) fn generate_output_for_compare_groups_entry<
         match component {
             GroupComponent::Array(array) => write_array(field_stats, writer, array)?,
             GroupComponent::Struct(struct_) => write_struct(field_stats, writer, struct_)?,
-            _ => {
-                return Err(GenerateBytesError::InvalidComponentType(
-                    "Only arrays and structs are allowed at top level".into(),
-                ))
+            GroupComponent::Rle(rle) => with_group_bytes(&rle.components, field_stats, |bytes| {
+                rle_runs(bytes).try_for_each(|run| writer.write_bytes(&run))
+            })?
+            .map_err(|e| GenerateBytesError::WriteError {
+                source: e,
+                context: "writing run-length encoded bytes".into(),
+            })?,
+            GroupComponent::Transform(transform) => {
+                write_transform(field_stats, writer, transform)?
             }
+            GroupComponent::ByteSplit(split) => write_byte_split(field_stats, writer, split)?,
+            _ => return Err(GenerateBytesError::InvalidComponentType(
+                "Only arrays, structs, rle, transforms and byte splits are allowed at top level"
+                    .into(),
+            )),
         }
     }
     Ok(())
 }
 
-pub(crate) fn generate_group_bytes(
+/// Generates the bytes of group components, and passes them to `f`.
+///
+/// The bytes are written into a thread-local buffer which is reused by subsequent calls,
+/// so the bytes are only borrowed for the duration of `f`; copy them if they need to outlive it.
+///
+/// # Parameters
+/// - `components`: Slice of group components to process
+/// - `field_stats`: Mutable reference to field statistics map
+/// - `f`: Callback which receives the generated bytes
+///
+/// # Returns
+/// The result of `f`
+pub(crate) fn with_group_bytes<R>(
     components: &[GroupComponent],
     field_stats: &mut AHashMap<String, AnalyzerFieldState>,
-) -> GenerateBytesResult<Vec<u8>> {
-    let mut output = Vec::new();
-    let mut writer = BitWriter::endian(Cursor::new(&mut output), BigEndian);
+    f: impl FnOnce(&[u8]) -> R,
+) -> GenerateBytesResult<R> {
+    let mut buffer = GROUP_BYTES_BUFFERS
+        .with_borrow_mut(|buffers| buffers.pop())
+        .unwrap_or_default();
+    buffer.clear();
+
+    let result = write_group_bytes(components, field_stats, &mut buffer).map(|()| f(&buffer));
+    GROUP_BYTES_BUFFERS.with_borrow_mut(|buffers| {
+        if buffers.len() < MAX_POOLED_BUFFERS {
+            buffers.push(buffer);
+        }
+    });
+    result
+}
+
+/// Writes the bytes of group components to the end of `output`.
+fn write_group_bytes(
+    components: &[GroupComponent],
+    field_stats: &mut AHashMap<String, AnalyzerFieldState>,
+    output: &mut Vec<u8>,
+) -> GenerateBytesResult<()> {
+    let mut cursor = Cursor::new(output);
+    cursor.set_position(cursor.get_ref().len() as u64);
+    let mut writer = BitWriter::endian(cursor, BigEndian);
 
     generate_output_for_compare_groups_entry(field_stats, &mut writer, components)?;
     writer
         .byte_align()
-        .map_err(GenerateBytesError::ByteAlignmentFailed)?;
-    Ok(output)
+        .map_err(GenerateBytesError::ByteAlignmentFailed)
 }
 
 #[cfg(test)]
@@ -203,6 +274,34 @@ mod generate_output_tests {
         assert_eq!(input_data, output.as_slice());
     }
 
+    #[test]
+    fn with_group_bytes_reuses_buffer() {
+        let input_data = [0x12, 0x34, 0x56, 0x78];
+        let mut field_stats = create_mock_field_states(
+            TEST_FIELD_NAME,
+            &input_data,
+            8,
+            BitOrder::Msb,
+            BitOrder::Msb,
+        );
+        let components = vec![GroupComponent::Array(GroupComponentArray {
+            field: TEST_FIELD_NAME.to_string(),
+            ..Default::default()
+        })];
+
+        let first = with_group_bytes(&components, &mut field_stats, |bytes| {
+            assert_eq!(bytes, input_data);
+            bytes.as_ptr()
+        })
+        .unwrap();
+        let second = with_group_bytes(&components, &mut field_stats, |bytes| {
+            assert_eq!(bytes, input_data);
+            bytes.as_ptr()
+        })
+        .unwrap();
+        assert_eq!(first, second);
+    }
+
     #[test]
     fn can_write_multiple_components() {
         let input_data = [0b0010_0001, 0b1000_0100];
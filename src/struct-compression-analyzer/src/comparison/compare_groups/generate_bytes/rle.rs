@@ -0,0 +1,49 @@
+/// Splits a byte stream into runs of identical bytes, as `(length, value)` pairs.
+///
+/// This is the encoding used by the `rle` group component:
+/// each run is emitted as 2 bytes, the run length (1-255) followed by the repeated byte.
+/// Runs longer than 255 bytes are split into multiple runs.
+///
+/// i.e. `AA AA AA BB` is encoded as `03 AA 01 BB`.
+///
+/// This is intentionally simple; it estimates whether run-length encoding a field would
+/// help, not how well a specific RLE scheme would do. Streams without runs double in size.
+///
+/// # Arguments
+/// * `bytes` - The bytes to encode.
+pub(crate) fn rle_runs(bytes: &[u8]) -> impl Iterator<Item = [u8; 2]> + '_ {
+    let mut remaining = bytes;
+    core::iter::from_fn(move || {
+        let (&value, _) = remaining.split_first()?;
+        let length = remaining
+            .iter()
+            .take(u8::MAX as usize)
+            .take_while(|&&byte| byte == value)
+            .count();
+        remaining = &remaining[length..];
+        Some([length as u8, value])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(bytes: &[u8]) -> Vec<u8> {
+        rle_runs(bytes).flatten().collect()
+    }
+
+    #[test]
+    fn encodes_runs_as_length_value_pairs() {
+        assert_eq!(
+            encode(&[0xAA, 0xAA, 0xAA, 0xBB, 0xAA]),
+            vec![3, 0xAA, 1, 0xBB, 1, 0xAA]
+        );
+        assert!(encode(&[]).is_empty());
+    }
+
+    #[test]
+    fn splits_runs_longer_than_255_bytes() {
+        assert_eq!(encode(&[7; 300]), vec![255, 7, 45, 7]);
+    }
+}
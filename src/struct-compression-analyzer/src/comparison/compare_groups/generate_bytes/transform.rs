@@ -0,0 +1,118 @@
+use super::{GenerateBytesError, GenerateBytesResult};
+use crate::utils::{
+    analyze_utils::{bit_writer_to_reader, BitReaderContainer},
+    bitstream_ext::BitReaderExt,
+};
+use crate::{analyzer::AnalyzerFieldState, schema::GroupComponentTransform};
+use ahash::AHashMap;
+use bitstream_io::{BitWrite, BitWriter, Endianness};
+use std::io;
+
+/// Processes a [`GroupComponentTransform`], writing the transformed values of its field
+/// to a provided [`BitWriter`].
+///
+/// Each value is transformed relative to the previous value of the field
+/// (see [`TransformOp::apply`]), and written with the same number of bits as the field.
+///
+/// # Arguments
+/// * `field_stats` - A mutable reference to a map of field stats.
+/// * `writer` - The bit writer to write the transformed values to.
+/// * `transform` - Contains info about the transform to apply.
+///
+/// [`TransformOp::apply`]: crate::schema::TransformOp::apply
+pub(crate) fn write_transform<TWrite: io::Write, TEndian: Endianness>(
+    field_stats: &mut AHashMap<String, AnalyzerFieldState>,
+    writer: &mut BitWriter<TWrite, TEndian>,
+    transform: &GroupComponentTransform,
+) -> GenerateBytesResult<()> {
+    let field = field_stats
+        .get_mut(&transform.field)
+        .ok_or_else(|| GenerateBytesError::FieldNotFound(transform.field.clone()))?;
+
+    let bits = field.lenbits;
+    let mut reader = bit_writer_to_reader(&mut field.writer);
+    let remaining = match &mut reader {
+        BitReaderContainer::Msb(reader) => reader.remaining_bits(),
+        BitReaderContainer::Lsb(reader) => reader.remaining_bits(),
+    }
+    .map_err(|e| GenerateBytesError::SeekError {
+        source: e,
+        operation: "checking remaining bits".into(),
+    })?;
+
+    // Only whole values are transformed; a trailing partial value is ignored.
+    let mut prev = 0;
+    for _ in 0..remaining / bits as u64 {
+        let value = reader
+            .read(bits)
+            .map_err(|e| GenerateBytesError::ReadError {
+                source: e,
+                context: format!("reading {bits}-bit value to transform"),
+            })?;
+
+        writer
+            .write_var::<u64>(bits, transform.op.apply(value, prev, bits))
+            .map_err(|e| GenerateBytesError::WriteError {
+                source: e,
+                context: format!("writing {bits}-bit transformed value"),
+            })?;
+        prev = value;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comparison::compare_groups::test_helpers::{
+        create_mock_field_states, TEST_FIELD_NAME,
+    };
+    use crate::schema::{BitOrder, TransformOp};
+    use bitstream_io::BigEndian;
+    use std::io::Cursor;
+
+    fn transform(data: &[u8], bits: u32, op: TransformOp) -> Vec<u8> {
+        let mut field_stats =
+            create_mock_field_states(TEST_FIELD_NAME, data, bits, BitOrder::Msb, BitOrder::Msb);
+        let mut output = Vec::new();
+        let mut writer = BitWriter::endian(Cursor::new(&mut output), BigEndian);
+        let component = GroupComponentTransform {
+            field: TEST_FIELD_NAME.to_string(),
+            op,
+        };
+        write_transform(&mut field_stats, &mut writer, &component).unwrap();
+        writer.byte_align().unwrap();
+        output
+    }
+
+    #[test]
+    fn delta_codes_values() {
+        // 10, 12, 11, 11 -> 10, 2, -1 (wrapped), 0
+        assert_eq!(
+            transform(&[10, 12, 11, 11], 8, TransformOp::Delta),
+            [10, 2, 255, 0]
+        );
+        // Wraps within the width of the field: 4-bit values 1, 0 -> 1, -1.
+        assert_eq!(transform(&[0x10], 4, TransformOp::Delta), [0x1F]);
+    }
+
+    #[test]
+    fn xors_with_previous_values() {
+        assert_eq!(
+            transform(&[0b1100, 0b1010, 0b1010], 8, TransformOp::XorPrev),
+            [0b1100, 0b0110, 0]
+        );
+    }
+
+    #[test]
+    fn zigzag_maps_small_magnitudes_to_small_values() {
+        // 0, -1, 1, -2, 2 as 8-bit two's complement.
+        assert_eq!(
+            transform(&[0, 255, 1, 254, 2], 8, TransformOp::ZigZag),
+            [0, 1, 2, 3, 4]
+        );
+        // 4-bit -8 and 7 are the extremes.
+        assert_eq!(transform(&[0x87], 4, TransformOp::ZigZag), [0xFE]);
+    }
+}
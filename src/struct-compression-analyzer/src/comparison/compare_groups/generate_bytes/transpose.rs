@@ -0,0 +1,64 @@
+/// Transposes a stream of fixed size records, treating it as a matrix where each row is a
+/// record and each column is a byte within the record.
+///
+/// i.e. given records `A0 A1 A2`, `B0 B1 B2`, the output is `A0 B0 A1 B1 A2 B2`.
+///
+/// This groups the Nth byte of every record together, which often improves compression
+/// when bytes at the same offset are correlated between records (a structure of arrays).
+///
+/// Any trailing bytes which don't form a full record are appended to the end unchanged.
+///
+/// # Arguments
+/// * `bytes` - The record bytes to transpose.
+/// * `record_size` - The size of each record in bytes. A size of 0 returns the input as is.
+pub(crate) fn transpose_records(bytes: &[u8], record_size: usize) -> Vec<u8> {
+    if record_size == 0 {
+        return bytes.to_vec();
+    }
+
+    let num_records = bytes.len() / record_size;
+    let full_len = num_records * record_size;
+    let mut output = Vec::with_capacity(bytes.len());
+    for column in 0..record_size {
+        output.extend(
+            bytes[..full_len]
+                .iter()
+                .skip(column)
+                .step_by(record_size)
+                .copied(),
+        );
+    }
+
+    output.extend_from_slice(&bytes[full_len..]);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transposes_bytes_within_records() {
+        let input = [0xA0, 0xA1, 0xA2, 0xB0, 0xB1, 0xB2];
+        assert_eq!(
+            transpose_records(&input, 3),
+            vec![0xA0, 0xB0, 0xA1, 0xB1, 0xA2, 0xB2]
+        );
+    }
+
+    #[test]
+    fn keeps_trailing_partial_record() {
+        let input = [0xA0, 0xA1, 0xB0, 0xB1, 0xC0];
+        assert_eq!(
+            transpose_records(&input, 2),
+            vec![0xA0, 0xB0, 0xA1, 0xB1, 0xC0]
+        );
+    }
+
+    #[test]
+    fn single_byte_records_are_unchanged() {
+        let input = [1, 2, 3, 4];
+        assert_eq!(transpose_records(&input, 1), input.to_vec());
+        assert_eq!(transpose_records(&input, 0), input.to_vec());
+    }
+}
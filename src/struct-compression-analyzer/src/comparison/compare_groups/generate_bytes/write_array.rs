@@ -3,7 +3,10 @@ use crate::utils::{
     analyze_utils::{get_writer_buffer, BitWriterContainer},
     bitstream_ext::BitReaderExt,
 };
-use crate::{analyzer::AnalyzerFieldState, schema::GroupComponentArray};
+use crate::{
+    analyzer::AnalyzerFieldState,
+    schema::{ArrayEdge, GroupComponentArray},
+};
 use ahash::AHashMap;
 use bitstream_io::{BigEndian, BitRead, BitReader, BitWrite, BitWriter, Endianness, LittleEndian};
 use std::io::{self, Cursor, SeekFrom};
@@ -29,20 +32,90 @@ pub(crate) fn write_array<TWrite: io::Write, TEndian: Endianness>(
     let bits: u32 = array.get_bits(field);
     let offset = array.offset;
     let field_len = field.lenbits;
+    let lsb_first = matches!(field.writer, BitWriterContainer::Lsb(_));
+    let excluded = excluded_bits_mask(array, bits, lsb_first)?;
     match &field.writer {
         BitWriterContainer::Msb(_) => {
             let bytes = get_writer_buffer(&mut field.writer);
             let mut reader = BitReader::endian(Cursor::new(bytes), BigEndian);
-            write_array_inner(&mut reader, bits, offset, field_len, writer)
+            write_array_inner(
+                &mut reader,
+                bits,
+                offset,
+                field_len,
+                excluded,
+                array,
+                writer,
+            )
         }
         BitWriterContainer::Lsb(_) => {
             let bytes = get_writer_buffer(&mut field.writer);
             let mut reader = BitReader::endian(Cursor::new(bytes), LittleEndian);
-            write_array_inner(&mut reader, bits, offset, field_len, writer)
+            write_array_inner(
+                &mut reader,
+                bits,
+                offset,
+                field_len,
+                excluded,
+                array,
+                writer,
+            )
         }
     }
 }
 
+/// Mask of the bits of each value read for an array which are left out, per its
+/// [`exclude_bits`]. Bit 0 is the least significant bit of the value.
+///
+/// # Arguments
+/// * `array` - The array whose excluded bits to get.
+/// * `bits` - The number of bits read per element.
+/// * `lsb_first` - Whether values are read least significant bit first.
+///
+/// [`exclude_bits`]: GroupComponentArray::exclude_bits
+fn excluded_bits_mask(
+    array: &GroupComponentArray,
+    bits: u32,
+    lsb_first: bool,
+) -> GenerateBytesResult<u64> {
+    let mut mask = 0u64;
+    for &bit in &array.exclude_bits {
+        let Some(position) = bit.checked_sub(array.offset).filter(|&pos| pos < bits) else {
+            return Err(GenerateBytesError::InvalidComponentType(format!(
+                "array of '{}' excludes bit {bit}, which isn't among the bits read ({} to {})",
+                array.field,
+                array.offset,
+                array.offset + bits - 1
+            )));
+        };
+        mask |= 1
+            << if lsb_first {
+                position
+            } else {
+                bits - 1 - position
+            };
+    }
+
+    if mask.count_ones() == bits {
+        return Err(GenerateBytesError::InvalidComponentType(format!(
+            "array of '{}' excludes all of the bits read",
+            array.field
+        )));
+    }
+    Ok(mask)
+}
+
+/// Removes the bits set in `excluded` from a `bits`-bit value, shifting the more significant
+/// bits down to fill the gaps.
+fn remove_bits(value: u64, bits: u32, excluded: u64) -> u64 {
+    (0..bits)
+        .filter(|bit| excluded & (1 << bit) == 0)
+        .enumerate()
+        .fold(0, |compacted, (kept, bit)| {
+            compacted | ((value >> bit) & 1) << kept
+        })
+}
+
 /// Processes an array component by reading bits from a field's stored data
 /// and writing them to the output writer according to array configuration.
 ///
@@ -58,37 +131,32 @@ fn write_array_inner<
     bits: u32,
     offset: u32,
     field_len: u32,
+    excluded: u64,
+    array: &GroupComponentArray,
     writer: &mut BitWriter<TWrite, TEndian>,
 ) -> GenerateBytesResult<()> {
-    // Loop until we run out of bits in the source field data
-    loop {
-        // Calculate ending position before reading to maintain alignment
-        let ending_pos = reader
-            .position_in_bits()
-            .map_err(|e| GenerateBytesError::SeekError {
-                source: e,
-                operation: "getting array position".into(),
-            })?
-            + field_len as u64;
+    let written_bits = bits - excluded.count_ones();
+    // Only whole elements are read; a trailing partial element is ignored.
+    let num_elements = reader
+        .remaining_bits()
+        .map_err(|e| GenerateBytesError::SeekError {
+            source: e,
+            operation: "checking remaining bits".into(),
+        })?
+        / field_len as u64;
 
-        // Check remaining bits before attempting read
-        let remaining = reader
-            .remaining_bits()
-            .map_err(|e| GenerateBytesError::SeekError {
-                source: e,
-                operation: "checking remaining bits".into(),
-            })?;
-
-        if remaining < field_len as u64 {
-            return Ok(());
-        }
+    for index in 0..num_elements {
+        let Some(source) = source_element(index, num_elements, array) else {
+            continue;
+        };
 
-        // Seek to the array element offset
+        // Seek to the slice of the source element
+        let position = source * field_len as u64 + offset as u64;
         reader
-            .seek_bits(SeekFrom::Current(offset as i64))
+            .seek_bits(SeekFrom::Start(position))
             .map_err(|e| GenerateBytesError::SeekError {
                 source: e,
-                operation: format!("seeking to array offset {}", offset),
+                operation: format!("seeking to array element {source} offset {offset}"),
             })?;
 
         // Read the actual value from the source bitstream
@@ -100,21 +168,35 @@ fn write_array_inner<
             })?;
 
         // Write the value to the output stream
-        writer
-            .write_var::<u64>(bits, value)
-            .map_err(|e| GenerateBytesError::WriteError {
+        let value = match excluded {
+            0 => value,
+            _ => remove_bits(value, bits, excluded),
+        };
+        writer.write_var::<u64>(written_bits, value).map_err(|e| {
+            GenerateBytesError::WriteError {
                 source: e,
-                context: format!("writing {bits}-bit array element"),
-            })?;
-
-        // Return to calculated end position for next iteration
-        reader.seek_bits(SeekFrom::Start(ending_pos)).map_err(|e| {
-            GenerateBytesError::SeekError {
-                source: e,
-                operation: format!("seeking to array end position {}", ending_pos),
+                context: format!("writing {written_bits}-bit array element"),
             }
         })?;
     }
+
+    Ok(())
+}
+
+/// Index of the element read for the element at `index`, after applying the array's
+/// [`element_offset`], or [`None`] if it's out of bounds and skipped.
+///
+/// [`element_offset`]: GroupComponentArray::element_offset
+fn source_element(index: u64, num_elements: u64, array: &GroupComponentArray) -> Option<u64> {
+    let source = index as i64 + array.element_offset as i64;
+    if (0..num_elements as i64).contains(&source) {
+        return Some(source as u64);
+    }
+
+    match array.edge {
+        ArrayEdge::Skip => None,
+        ArrayEdge::Clamp => Some(source.clamp(0, num_elements as i64 - 1) as u64),
+    }
 }
 
 #[cfg(test)]
@@ -133,6 +215,9 @@ mod tests {
             field: TEST_FIELD_NAME.to_string(),
             offset,
             bits,
+            element_offset: 0,
+            edge: ArrayEdge::Skip,
+            exclude_bits: Vec::new(),
             lz_match_multiplier: default_lz_match_multiplier(),
             entropy_multiplier: default_entropy_multiplier(),
         }
@@ -230,4 +315,103 @@ mod tests {
         // Read back written data
         assert_eq!(expected_output, output.as_slice());
     }
+
+    #[test]
+    fn can_read_neighbouring_elements() {
+        // 4-bit elements 1, 2, 3, 4, LSB first.
+        let input_data = [0b0010_0001, 0b0100_0011];
+        let read = |element_offset: i32, edge: ArrayEdge| {
+            let mut field_stats = create_mock_field_states(
+                TEST_FIELD_NAME,
+                &input_data,
+                4,
+                BitOrder::Lsb,
+                BitOrder::Lsb,
+            );
+            let mut output = Vec::new();
+            let mut writer = BitWriter::endian(Cursor::new(&mut output), LittleEndian);
+            let array = GroupComponentArray {
+                element_offset,
+                edge,
+                ..test_array_group_component(0, 4)
+            };
+            write_array(&mut field_stats, &mut writer, &array).unwrap();
+            writer.byte_align().unwrap();
+            output
+        };
+
+        // Elements 2, 3, 4; the last has no next element.
+        assert_eq!(read(1, ArrayEdge::Skip), [0b0011_0010, 0b0100]);
+        // Elements 2, 3, 4, 4.
+        assert_eq!(read(1, ArrayEdge::Clamp), [0b0011_0010, 0b0100_0100]);
+        // Elements 1, 1, 2, 3.
+        assert_eq!(read(-1, ArrayEdge::Clamp), [0b0001_0001, 0b0011_0010]);
+        // Far past either end, every element is skipped.
+        assert!(read(-8, ArrayEdge::Skip).is_empty());
+    }
+
+    #[test]
+    fn excluded_bits_are_removed_and_the_rest_compacted() {
+        // 4-bit elements 0b1010, 0b0110, 0b1111, 0b0001 when read MSB first.
+        let input_data = [0b1010_0110, 0b1111_0001];
+        let read = |exclude_bits: Vec<u32>, bit_order: BitOrder| {
+            let mut field_stats =
+                create_mock_field_states(TEST_FIELD_NAME, &input_data, 4, bit_order, bit_order);
+            let mut output = Vec::new();
+            let mut writer = BitWriter::endian(Cursor::new(&mut output), BigEndian);
+            let array = GroupComponentArray {
+                exclude_bits,
+                ..test_array_group_component(0, 0)
+            };
+            let result = write_array(&mut field_stats, &mut writer, &array);
+            writer.byte_align().unwrap();
+            result.map(|()| output)
+        };
+
+        // Without bit 1, each element is 3 bits: 110, 010, 111, 001; 12 bits instead of 16.
+        assert_eq!(
+            read(vec![1], BitOrder::Msb).unwrap(),
+            [0b1100_1011, 0b1001_0000]
+        );
+        // Without the first and last bits: 01, 11, 11, 00; the stream fits in a single byte.
+        assert_eq!(read(vec![0, 3], BitOrder::Msb).unwrap(), [0b0111_1100]);
+
+        // Read LSB first, the elements are 0b0110, 0b1010, 0b0001, 0b1111, and bit 1 is the
+        // second bit read of each: 010, 100, 001, 111.
+        assert_eq!(
+            read(vec![1], BitOrder::Lsb).unwrap(),
+            [0b0101_0000, 0b1111_0000]
+        );
+
+        // Outside the bits read, or all of them.
+        assert!(matches!(
+            read(vec![4], BitOrder::Msb),
+            Err(GenerateBytesError::InvalidComponentType(_))
+        ));
+        assert!(matches!(
+            read(vec![0, 1, 2, 3], BitOrder::Msb),
+            Err(GenerateBytesError::InvalidComponentType(_))
+        ));
+    }
+
+    #[test]
+    fn excluded_bits_are_offsets_in_the_field() {
+        // Reads bits 1-3 of the MSB first elements 0b1010, 0b0110 (010, 110), without bit 2.
+        let mut field_stats = create_mock_field_states(
+            TEST_FIELD_NAME,
+            &[0b1010_0110],
+            4,
+            BitOrder::Msb,
+            BitOrder::Msb,
+        );
+        let mut output = Vec::new();
+        let mut writer = BitWriter::endian(Cursor::new(&mut output), BigEndian);
+        let array = GroupComponentArray {
+            exclude_bits: vec![2],
+            ..test_array_group_component(1, 3)
+        };
+        write_array(&mut field_stats, &mut writer, &array).unwrap();
+        writer.byte_align().unwrap();
+        assert_eq!(output, [0b0010_0000]);
+    }
 }
@@ -1,7 +1,7 @@
 use super::{GenerateBytesError, GenerateBytesResult};
 use crate::{
     analyzer::AnalyzerFieldState,
-    schema::{GroupComponent, GroupComponentStruct},
+    schema::{GroupComponent, GroupComponentPadding, GroupComponentStruct},
     utils::analyze_utils::{bit_writer_to_reader, BitReaderContainer},
 };
 use ahash::AHashMap;
@@ -31,7 +31,11 @@ pub(crate) fn write_struct<TWrite: io::Write, TEndian: Endianness>(
     // Initialize readers for all fields used in the struct
     for field in &mut strct.fields {
         let field_name = match field {
-            GroupComponent::Array(_) | GroupComponent::Struct(_) => {
+            GroupComponent::Array(_)
+            | GroupComponent::Struct(_)
+            | GroupComponent::Rle(_)
+            | GroupComponent::Transform(_)
+            | GroupComponent::ByteSplit(_) => {
                 return Err(GenerateBytesError::UnsupportedNestedComponent)
             }
             GroupComponent::Field(field) => Some(field.field.clone()),
@@ -62,16 +66,18 @@ pub(crate) fn write_struct<TWrite: io::Write, TEndian: Endianness>(
 
         for field in &strct.fields {
             match field {
-                GroupComponent::Array(_) | GroupComponent::Struct(_) => {
+                GroupComponent::Array(_)
+                | GroupComponent::Struct(_)
+                | GroupComponent::Rle(_)
+                | GroupComponent::Transform(_)
+                | GroupComponent::ByteSplit(_) => {
                     return Err(GenerateBytesError::UnsupportedNestedComponent)
                 }
                 GroupComponent::Padding(padding) => {
-                    writer
-                        .write_var(padding.bits as u32, padding.value)
-                        .map_err(|e| GenerateBytesError::WriteError {
-                            source: e,
-                            context: "writing padding bits".into(),
-                        })?;
+                    write_padding(writer, padding).map_err(|e| GenerateBytesError::WriteError {
+                        source: e,
+                        context: "writing padding bits".into(),
+                    })?;
                 }
                 GroupComponent::Field(field) => {
                     let reader = field_readers
@@ -140,6 +146,29 @@ pub(crate) fn write_struct<TWrite: io::Write, TEndian: Endianness>(
     }
 }
 
+/// Writes the bits of a [`GroupComponentPadding`], i.e. its [`GroupComponentPadding::value`],
+/// or its [`GroupComponentPadding::pattern`] repeated (and truncated) to fill the bits.
+fn write_padding<TWrite: io::Write, TEndian: Endianness>(
+    writer: &mut BitWriter<TWrite, TEndian>,
+    padding: &GroupComponentPadding,
+) -> io::Result<()> {
+    if padding.pattern.is_empty() {
+        return writer.write_var(padding.bits as u32, padding.value);
+    }
+
+    let mut remaining = padding.bits as u32;
+    for &byte in padding.pattern.iter().cycle() {
+        if remaining == 0 {
+            break;
+        }
+
+        let bits = remaining.min(8);
+        writer.write_var(bits, byte & (u8::MAX >> (8 - bits)))?;
+        remaining -= bits;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -272,6 +301,7 @@ mod tests {
                 fields: vec![GroupComponent::Padding(GroupComponentPadding {
                     bits: 4,
                     value: 0b1010,
+                    pattern: Vec::new(),
                 })],
                 lz_match_multiplier: default_lz_match_multiplier(),
                 entropy_multiplier: default_entropy_multiplier(),
@@ -296,6 +326,7 @@ mod tests {
                 fields: vec![GroupComponent::Padding(GroupComponentPadding {
                     bits: 4,
                     value: 0b1010,
+                    pattern: Vec::new(),
                 })],
                 lz_match_multiplier: default_lz_match_multiplier(),
                 entropy_multiplier: default_entropy_multiplier(),
@@ -306,4 +337,35 @@ mod tests {
         writer.flush().unwrap();
         assert_eq!(output, [0b1010_0000]);
     }
+
+    #[test]
+    fn padding_pattern_repeats_and_truncates() {
+        let write_padding_bits = |bits: u8| {
+            let mut field_states =
+                create_mock_field_states(TEST_FIELD_NAME, &[], 0, BitOrder::Msb, BitOrder::Msb);
+            let mut output = Vec::new();
+            let mut writer = BitWriter::endian(Cursor::new(&mut output), BigEndian);
+            write_struct(
+                &mut field_states,
+                &mut writer,
+                &GroupComponentStruct {
+                    fields: vec![GroupComponent::Padding(GroupComponentPadding {
+                        bits,
+                        value: 0xFF, // ignored in favour of the pattern
+                        pattern: vec![0xAB, 0x0C],
+                    })],
+                    lz_match_multiplier: default_lz_match_multiplier(),
+                    entropy_multiplier: default_entropy_multiplier(),
+                },
+            )
+            .unwrap();
+            writer.byte_align().unwrap();
+            writer.flush().unwrap();
+            output
+        };
+
+        assert_eq!(write_padding_bits(16), [0xAB, 0x0C]);
+        assert_eq!(write_padding_bits(28), [0xAB, 0x0C, 0xAB, 0xC0]);
+        assert_eq!(write_padding_bits(4), [0xB0]);
+    }
 }
@@ -63,11 +63,12 @@ pub(crate) mod test_helpers;
 
 use super::{GroupComparisonMetrics, GroupDifference};
 use crate::analyzer::CompressionOptions;
-use crate::comparison::compare_groups::generate_bytes::generate_group_bytes;
+use crate::comparison::compare_groups::generate_bytes::{transpose_records, with_group_bytes};
 use crate::schema::Schema;
 use crate::{analyzer::AnalyzerFieldState, schema::CustomComparison};
 use ahash::AHashMap;
 use generate_bytes::GenerateBytesError;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// Describes an error that occurred while computing a group comparison.
@@ -84,7 +85,7 @@ pub enum GroupComparisonError {
 }
 
 /// Contains the result of comparing custom field groupings defined in the schema.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GroupComparisonResult {
     /// The name of the group comparison. (Copied from schema)
     pub name: String,
@@ -127,36 +128,69 @@ impl GroupComparisonResult {
 
         // Calculate baseline metrics
         let baseline_name = format!("{}-baseline", name);
-        let baseline_metrics =
-            GroupComparisonMetrics::from_bytes(baseline_bytes, &baseline_name, compression_options);
+        let baseline_metrics = GroupComparisonMetrics::from_bytes(
+            baseline_bytes,
+            &baseline_name,
+            &compression_options,
+        );
 
         // Process comparison groups
-        let mut group_metrics = Vec::with_capacity(comparison_byte_slices.len());
-        let mut differences = Vec::with_capacity(comparison_byte_slices.len());
-        let mut names = Vec::with_capacity(comparison_byte_slices.len());
-        for group_name in group_names {
-            names.push(group_name.clone());
-        }
+        let group_metrics = comparison_byte_slices
+            .iter()
+            .zip(group_names.iter())
+            .map(|(comparison, group_name)| {
+                let comparison_name = format!("{}-{}", name, group_name);
+                GroupComparisonMetrics::from_bytes(
+                    comparison.as_ref(),
+                    &comparison_name,
+                    &compression_options,
+                )
+            })
+            .collect();
 
-        for (comparison, group_name) in comparison_byte_slices.iter().zip(group_names.iter()) {
-            let comparison_name = format!("{}-{}", name, group_name);
-            let metrics = GroupComparisonMetrics::from_bytes(
-                comparison.as_ref(),
-                &comparison_name,
-                compression_options,
-            );
-            differences.push(GroupDifference::from_metrics(&baseline_metrics, &metrics));
-            group_metrics.push(metrics);
-        }
+        Ok(Self::from_group_metrics(
+            name,
+            description,
+            baseline_metrics,
+            group_names.to_vec(),
+            group_metrics,
+        ))
+    }
 
-        Ok(Self {
+    /// Creates comparison results from precomputed group metrics,
+    /// computing the differences against the baseline.
+    pub(crate) fn from_group_metrics(
+        name: String,
+        description: String,
+        baseline_metrics: GroupComparisonMetrics,
+        group_names: Vec<String>,
+        group_metrics: Vec<GroupComparisonMetrics>,
+    ) -> Self {
+        let differences = group_metrics
+            .iter()
+            .map(|metrics| GroupDifference::from_metrics(&baseline_metrics, metrics))
+            .collect();
+
+        Self {
             name,
             description,
             baseline_metrics,
-            group_names: names,
+            group_names,
             group_metrics,
             differences,
-        })
+        }
+    }
+
+    /// Zstd bytes saved by using the smallest group instead of the baseline.
+    /// This is 0 if no group beats the baseline.
+    pub fn zstd_bytes_saved(&self) -> u64 {
+        let smallest = self
+            .group_metrics
+            .iter()
+            .map(|metrics| metrics.zstd_size)
+            .min()
+            .unwrap_or(self.baseline_metrics.zstd_size);
+        self.baseline_metrics.zstd_size.saturating_sub(smallest)
     }
 }
 
@@ -177,46 +211,55 @@ pub(crate) fn process_single_comparison(
     field_stats: &mut AHashMap<String, AnalyzerFieldState>,
     compression_options: CompressionOptions,
 ) -> Result<GroupComparisonResult, GroupComparisonError> {
-    // Generate baseline bytes with error context
-    let baseline_bytes = generate_group_bytes(&comparison.baseline, field_stats).map_err(|e| {
+    // The bytes of each group are only borrowed while computing its metrics,
+    // so all groups share the same reused buffer.
+    let baseline_name = format!("{}-baseline", comparison.name);
+    let baseline_metrics = with_group_bytes(&comparison.baseline, field_stats, |bytes| {
+        GroupComparisonMetrics::from_bytes_cached(
+            bytes,
+            &baseline_name,
+            &comparison.baseline,
+            &compression_options,
+        )
+    })
+    .map_err(|e| {
         GroupComparisonError::InvalidConfiguration(format!(
             "Comparison '{}' baseline error: {}. This is indicative of a configuration error.",
             comparison.name, e
         ))
     })?;
 
-    // Generate comparison group bytes in schema order
-    let mut comparison_bytes = Vec::new();
-    let mut group_names = Vec::new();
-
+    // Compute comparison group metrics in schema order
+    let mut group_names = Vec::with_capacity(comparison.comparisons.len());
+    let mut group_metrics = Vec::with_capacity(comparison.comparisons.len());
     for (group_name, components) in &comparison.comparisons {
-        let bytes = generate_group_bytes(components, field_stats).map_err(|e| {
+        let comparison_name = format!("{}-{}", comparison.name, group_name);
+        let metrics = with_group_bytes(components, field_stats, |bytes| {
+            GroupComparisonMetrics::from_bytes_cached(
+                bytes,
+                &comparison_name,
+                components,
+                &compression_options,
+            )
+        })
+        .map_err(|e| {
             GroupComparisonError::InvalidConfiguration(format!(
                 "Comparison '{}' group '{}' error: {}. This is indicative of a configuration error.",
                 comparison.name, group_name, e
             ))
         })?;
 
-        comparison_bytes.push(bytes);
         group_names.push(group_name.clone());
+        group_metrics.push(metrics);
     }
 
-    // Create custom compression options for this comparison using its multipliers
-    let custom_compression_options = CompressionOptions {
-        zstd_compression_level: compression_options.zstd_compression_level,
-        size_estimator_fn: compression_options.size_estimator_fn,
-        lz_match_multiplier: compression_options.lz_match_multiplier,
-        entropy_multiplier: compression_options.entropy_multiplier,
-    };
-
-    GroupComparisonResult::from_custom_comparison(
+    Ok(GroupComparisonResult::from_group_metrics(
         comparison.name.clone(),
         comparison.description.clone(),
-        &baseline_bytes,
-        &comparison_bytes,
-        &group_names,
-        custom_compression_options,
-    )
+        baseline_metrics,
+        group_names,
+        group_metrics,
+    ))
 }
 
 /// Analyzes all custom comparisons defined in the [`Schema`].
@@ -242,11 +285,70 @@ pub(crate) fn analyze_custom_comparisons(
         .iter()
         .map(|comparison| {
             // Use base compression options but pass comparison through for multipliers
-            process_single_comparison(comparison, field_stats, compression_options)
+            process_single_comparison(comparison, field_stats, compression_options.clone())
         })
         .collect()
 }
 
+/// Name of the built-in comparison added by [`CompressionOptions::byte_transpose_comparison`].
+pub const BYTE_TRANSPOSE_COMPARISON_NAME: &str = "byte_transpose";
+
+/// Name of the transposed group in the [`BYTE_TRANSPOSE_COMPARISON_NAME`] comparison.
+pub const BYTE_TRANSPOSE_GROUP_NAME: &str = "transposed";
+
+/// Analyzes the built-in byte transpose comparison.
+/// This is an internal API.
+///
+/// The baseline is the original record data, and the single comparison group is the same
+/// data with the bytes of the records transposed (rows = records, columns = byte within record),
+/// i.e. all 1st bytes, then all 2nd bytes, etc.
+///
+/// # Arguments
+///
+/// * `entries` - The raw bytes of all records.
+/// * `record_bits` - The size of a single record in bits.
+/// * `compression_options` - Compression options, zstd compression level, etc.
+///
+/// # Returns
+///
+/// The comparison result, or [`None`] if the records are not a whole number of bytes,
+/// or are a single byte, in which case there is nothing to transpose.
+pub(crate) fn analyze_byte_transpose_comparison(
+    entries: &[u8],
+    record_bits: u32,
+    compression_options: CompressionOptions,
+) -> Result<Option<GroupComparisonResult>, GroupComparisonError> {
+    if !record_bits.is_multiple_of(8) || record_bits <= 8 {
+        return Ok(None);
+    }
+
+    let record_size = (record_bits / 8) as usize;
+    let transposed = transpose_records(entries, record_size);
+
+    // Named like the groups of `from_custom_comparison`; the definitions only identify the
+    // groups in the metrics cache.
+    let name = BYTE_TRANSPOSE_COMPARISON_NAME;
+    let baseline_metrics = GroupComparisonMetrics::from_bytes_cached(
+        entries,
+        &format!("{name}-baseline"),
+        "entries",
+        &compression_options,
+    );
+    let transposed_metrics = GroupComparisonMetrics::from_bytes_cached(
+        &transposed,
+        &format!("{name}-{BYTE_TRANSPOSE_GROUP_NAME}"),
+        &(BYTE_TRANSPOSE_GROUP_NAME, record_size),
+        &compression_options,
+    );
+    Ok(Some(GroupComparisonResult::from_group_metrics(
+        name.to_string(),
+        format!("Records with their {record_size} bytes transposed (all 1st bytes, then all 2nd bytes, etc.)"),
+        baseline_metrics,
+        vec![BYTE_TRANSPOSE_GROUP_NAME.to_string()],
+        vec![transposed_metrics],
+    )))
+}
+
 #[cfg(test)]
 mod from_custom_comparison_tests {
     use super::*;
@@ -255,6 +357,7 @@ mod from_custom_comparison_tests {
     use crate::schema::BitOrder;
     use crate::schema::GroupComponent;
     use crate::schema::GroupComponentArray;
+    use crate::schema::GroupComponentRle;
     use indexmap::IndexMap;
 
     #[test]
@@ -382,6 +485,44 @@ mod from_custom_comparison_tests {
         assert_eq!(result.differences[1].entropy, 0.0);
     }
 
+    #[test]
+    fn rle_reduces_size_of_field_with_long_runs() {
+        // 8 runs of 128 identical bytes
+        let input_data: Vec<u8> = (0..1024).map(|x| (x / 128) as u8).collect();
+        let mut field_stats = create_mock_field_states(
+            TEST_FIELD_NAME,
+            &input_data,
+            8,
+            BitOrder::Msb,
+            BitOrder::Msb,
+        );
+
+        let array = GroupComponent::Array(GroupComponentArray {
+            field: TEST_FIELD_NAME.to_string(),
+            ..Default::default()
+        });
+        let comparison = CustomComparison {
+            name: "rle".to_string(),
+            description: String::new(),
+            baseline: vec![array.clone()],
+            comparisons: IndexMap::from([(
+                "rle".to_string(),
+                vec![GroupComponent::Rle(GroupComponentRle {
+                    components: vec![array],
+                })],
+            )]),
+        };
+
+        let result =
+            process_single_comparison(&comparison, &mut field_stats, CompressionOptions::default())
+                .unwrap();
+
+        assert_eq!(result.baseline_metrics.original_size, 1024);
+        // 8 (length, value) pairs
+        assert_eq!(result.group_metrics[0].original_size, 16);
+        assert!(result.differences[0].zstd_size < 0);
+    }
+
     #[test]
     fn invalid_configuration_error() {
         let invalid_comparison = CustomComparison {
@@ -431,3 +572,55 @@ mod from_custom_comparison_tests {
         ));
     }
 }
+
+#[cfg(test)]
+mod byte_transpose_tests {
+    use super::*;
+
+    /// Records of 4 bytes, where each byte (column) is correlated with the same byte of the
+    /// other records, but not with the other bytes of the same record.
+    fn column_correlated_records(num_records: u32) -> Vec<u8> {
+        let mut seed = 0x1234_5678u32;
+        let mut data = Vec::new();
+        for x in 0..num_records {
+            // xorshift, so values are not correlated with the rest of the record.
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            data.extend_from_slice(&[0x11, (x / 16) as u8, (seed >> 24) as u8, 0x22]);
+        }
+        data
+    }
+
+    #[test]
+    fn column_correlated_records_compress_better_transposed() {
+        let data = column_correlated_records(4096);
+        let result = analyze_byte_transpose_comparison(&data, 32, CompressionOptions::default())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(result.name, BYTE_TRANSPOSE_COMPARISON_NAME);
+        assert_eq!(result.group_names, vec![BYTE_TRANSPOSE_GROUP_NAME]);
+        assert_eq!(result.group_metrics[0].original_size, data.len() as u64);
+        assert!(
+            result.group_metrics[0].zstd_size < result.baseline_metrics.zstd_size,
+            "transposed: {}, baseline: {}",
+            result.group_metrics[0].zstd_size,
+            result.baseline_metrics.zstd_size
+        );
+    }
+
+    #[test]
+    fn skips_records_which_are_not_whole_bytes() {
+        let data = column_correlated_records(16);
+        let options = CompressionOptions::default();
+        assert!(
+            analyze_byte_transpose_comparison(&data, 12, options.clone())
+                .unwrap()
+                .is_none()
+        );
+        assert!(analyze_byte_transpose_comparison(&data, 8, options)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    analyzer::AnalyzerFieldState, schema::BitOrder,
+    analyzer::AnalyzerFieldState,
+    schema::{BitOrder, Endian, ValueInterpretation},
     utils::analyze_utils::create_bit_writer_with_owned_data,
 };
 use ahash::{AHashMap, HashMapExt};
@@ -35,9 +36,17 @@ pub(crate) fn create_mock_field_states(
             full_path: name.clone(),
             bit_counts: Vec::new(),
             bit_order: field_bit_order,
+            byte_order: Endian::Big,
             count: 0,
             depth: 0,
             value_counts: FxHashMap::new(),
+            value_transitions: None,
+            interpretation: ValueInterpretation::default(),
+            value_counts_overflowed: false,
+            reserved: false,
+            lz_match_multiplier: None,
+            entropy_multiplier: None,
+            previous_value: None,
             writer,
             lenbits: len_bits,
         },
@@ -4,13 +4,16 @@
 //!
 //! - [`split_comparison`]: Easy comparison of 'splitting' structs.
 //!     - e.g. interleaved (RGBRGBRGB) vs. separated fields (RRRGGGBB)
+//! - [`multi_split`]: Split comparisons of more than 2 groups
 //! - [`compare_groups`]: Comparison of more custom field transformations and analysis
 //! - [`stats`]: Additional statistics for comparing groups
+//! - [`auto_split`]: Automatic search for the best split of a field
 //!
 //! # Types
 //!
 //! - [`GroupComparisonMetrics`]: Collects compression metrics (LZ matches, entropy, sizes)
 //! - [`GroupDifference`]: Tracks metric differences between two field groups
+//! - [`GroundTruth`]: Selects the compressor the size estimator is checked against
 //!
 //! # Example
 //!
@@ -20,8 +23,8 @@
 //!
 //! fn calculate_example(baseline_data: &[u8], comparison_data: &[u8]) {
 //!     let options = CompressionOptions::default();
-//!     let baseline = GroupComparisonMetrics::from_bytes(&baseline_data, "name_a", options);
-//!     let comparison = GroupComparisonMetrics::from_bytes(&comparison_data, "name_b", options);
+//!     let baseline = GroupComparisonMetrics::from_bytes(&baseline_data, "name_a", &options);
+//!     let comparison = GroupComparisonMetrics::from_bytes(&comparison_data, "name_b", &options);
 //!
 //!     // Compare the difference
 //!     let difference = GroupDifference::from_metrics(&baseline, &comparison);
@@ -29,36 +32,88 @@
 //! ```
 //!
 //! [`split_comparison`]: self::split_comparison
+//! [`multi_split`]: self::multi_split
 //! [`compare_groups`]: self::compare_groups
 //! [`stats`]: self::stats
+//! [`auto_split`]: self::auto_split
 //! [`GroupComparisonMetrics`]: GroupComparisonMetrics
 //! [`GroupDifference`]: GroupDifference
+//! [`GroundTruth`]: GroundTruth
 
 use crate::{
     analyzer::{CompressionOptions, SizeEstimationParameters},
-    utils::analyze_utils::{calculate_file_entropy, get_zstd_compressed_size},
+    utils::analyze_utils::{calculate_file_entropy, SizeEstimateBreakdown},
 };
+use derive_more::FromStr;
 use lossless_transform_utils::match_estimator::estimate_num_lz_matches_fast;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
 
+pub mod auto_split;
 pub mod compare_groups;
+pub mod multi_split;
 pub mod split_comparison;
 pub mod stats;
 
 /// The statistics for a given group of fields.
 /// This can be a group created by the [`split_comparison`] module, the
 /// [`compare_groups`] module or any other piece of code that compares multiple sets of bytes.
-#[derive(Clone, Default, Debug, PartialEq, Copy)]
+#[derive(Clone, Default, Debug, PartialEq, Copy, Serialize, Deserialize)]
 pub struct GroupComparisonMetrics {
     /// Number of total LZ matches
     pub lz_matches: u64,
     /// Amount of entropy in the input data set
     pub entropy: f64,
     /// Size estimated by the size estimator function.
+    /// Only meaningful if [`Self::has_estimate`] is set; an estimate may legitimately be 0.
     pub estimated_size: u64,
+    /// Whether the size estimator was run for this group, i.e. whether
+    /// [`Self::estimated_size`] holds an estimate.
+    pub has_estimate: bool,
     /// Size compressed by zstd.
     pub zstd_size: u64,
     /// Size of the original data.
     pub original_size: u64,
+    /// Intermediate terms of the estimate.
+    /// Only set when requested via [`CompressionOptions::include_estimate_breakdown`].
+    pub estimate_breakdown: Option<SizeEstimateBreakdown>,
+    /// Size compressed by Brotli.
+    /// Only set when selected via [`CompressionOptions::compressor`].
+    #[serde(default)]
+    pub brotli_size: Option<u64>,
+    /// Size compressed by Deflate, without the gzip header and trailer
+    /// (see [`GZIP_OVERHEAD`]).
+    /// Only set when selected via [`CompressionOptions::compressor`].
+    ///
+    /// [`GZIP_OVERHEAD`]: crate::utils::compressor::GZIP_OVERHEAD
+    #[serde(default)]
+    pub deflate_size: Option<u64>,
+    /// Size compressed by zstd, with the dictionary shared by all files.
+    /// Only set when a dictionary is given via [`CompressionOptions::zstd_dictionary`].
+    #[serde(default)]
+    pub zstd_dict_size: Option<u64>,
+    /// Order-1 (conditional) entropy, see [`FieldMetrics::entropy_order1`].
+    /// Only set when requested via [`CompressionOptions::measure_order1_entropy`].
+    ///
+    /// [`FieldMetrics::entropy_order1`]: crate::results::FieldMetrics::entropy_order1
+    #[serde(default)]
+    pub entropy_order1: Option<f64>,
+    /// Size achieved by an external codec, see [`FieldMetrics::reference_size`].
+    /// Only set when supplied via [`ReferenceSizes`].
+    ///
+    /// [`FieldMetrics::reference_size`]: crate::results::FieldMetrics::reference_size
+    /// [`ReferenceSizes`]: crate::results::reference_sizes::ReferenceSizes
+    #[serde(default)]
+    pub reference_size: Option<u64>,
+    /// Time taken to compress the group with zstd, in nanoseconds.
+    /// Only measured when requested via [`CompressionOptions::measure_zstd_time`], else 0.
+    #[serde(default)]
+    pub zstd_compress_ns: u64,
+    /// Size compressed by zstd at [`CompressionOptions::zstd_calibration_level`],
+    /// which the brute forcer fits the estimator to instead of [`Self::zstd_size`].
+    /// Only set when that level differs from [`CompressionOptions::zstd_compression_level`].
+    #[serde(default)]
+    pub zstd_calibration_size: Option<u64>,
 }
 
 /// Represents the difference between 2 groups of fields.
@@ -66,7 +121,7 @@ pub struct GroupComparisonMetrics {
 ///
 /// This can be used for representing the difference between either splits, or any two arbitrary
 /// groups of analyzed bytes. Usually this is the difference between a result and a baseline.
-#[derive(PartialEq, Debug, Clone, Copy, Default)]
+#[derive(PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct GroupDifference {
     /// The difference in LZ matches.
     pub lz_matches: i64,
@@ -81,7 +136,66 @@ pub struct GroupDifference {
     pub original_size: i64,
 }
 
+/// The compressor whose sizes are treated as the 'actual' sizes when measuring how often the
+/// size estimator picks the better group (agreement, false positive and correct positive rates).
+///
+/// Parsed case-insensitively from the variant name, e.g. `zstd`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromStr, Serialize)]
+pub enum GroundTruth {
+    /// Sizes compressed by zstd, i.e. [`GroupComparisonMetrics::zstd_size`].
+    #[default]
+    Zstd,
+    /// Sizes compressed by Brotli, i.e. [`GroupComparisonMetrics::brotli_size`].
+    /// Requires the results to be analyzed with [`Compressor::Brotli`]; groups without a
+    /// Brotli size fall back to their zstd size.
+    ///
+    /// [`Compressor::Brotli`]: crate::utils::compressor::Compressor::Brotli
+    Brotli,
+    /// Sizes compressed by Deflate, i.e. [`GroupComparisonMetrics::deflate_size`].
+    /// Requires the results to be analyzed with [`Compressor::Deflate`]; groups without a
+    /// Deflate size fall back to their zstd size.
+    ///
+    /// [`Compressor::Deflate`]: crate::utils::compressor::Compressor::Deflate
+    Deflate,
+}
+
+impl GroundTruth {
+    /// Human readable name of the compressor, used as a label when printing.
+    pub fn name(self) -> &'static str {
+        match self {
+            GroundTruth::Zstd => "Zstd",
+            GroundTruth::Brotli => "Brotli",
+            GroundTruth::Deflate => "Deflate",
+        }
+    }
+}
+
 impl GroupComparisonMetrics {
+    /// The compressed size of the group, as measured by the given ground truth compressor.
+    pub fn actual_size(&self, ground_truth: GroundTruth) -> u64 {
+        match ground_truth {
+            GroundTruth::Zstd => self.zstd_size,
+            GroundTruth::Brotli => self.brotli_size.unwrap_or(self.zstd_size),
+            GroundTruth::Deflate => self.deflate_size.unwrap_or(self.zstd_size),
+        }
+    }
+
+    /// Whether the group was compressed by the given ground truth compressor, i.e.
+    /// [`Self::actual_size`] doesn't fall back to the zstd size.
+    pub fn is_measured_by(&self, ground_truth: GroundTruth) -> bool {
+        match ground_truth {
+            GroundTruth::Zstd => true,
+            GroundTruth::Brotli => self.brotli_size.is_some(),
+            GroundTruth::Deflate => self.deflate_size.is_some(),
+        }
+    }
+
+    /// Whether the estimator ran, but estimated a size of 0 for a group which is not empty.
+    /// This is usually a sign of a misconfigured estimator (e.g. multipliers) or formula.
+    pub fn has_zero_estimate(&self) -> bool {
+        self.has_estimate && self.estimated_size == 0 && self.original_size != 0
+    }
+
     /// Calculates group comparison metrics for a given byte slice.
     ///
     /// This function computes various metrics such as entropy, LZ matches, estimated size,
@@ -97,29 +211,105 @@ impl GroupComparisonMetrics {
     pub fn from_bytes(
         bytes: &[u8],
         group_name: &str,
-        compression_options: CompressionOptions,
+        compression_options: &CompressionOptions,
     ) -> Self {
-        let entropy = calculate_file_entropy(bytes);
-        let lz_matches = estimate_num_lz_matches_fast(bytes) as u64;
-        let estimated_size = (compression_options.size_estimator_fn)(SizeEstimationParameters {
-            name: group_name,
-            data: Some(bytes),
-            data_len: bytes.len(),
-            num_lz_matches: lz_matches as usize,
-            entropy,
-            lz_match_multiplier: compression_options.lz_match_multiplier,
-            entropy_multiplier: compression_options.entropy_multiplier,
-        }) as u64;
-        let zstd_size = get_zstd_compressed_size(bytes, compression_options.zstd_compression_level);
+        Self::measure(bytes, compression_options).with_estimate(
+            bytes,
+            group_name,
+            compression_options.lz_match_multiplier,
+            compression_options.entropy_multiplier,
+            compression_options,
+        )
+    }
 
+    /// Like [`Self::from_bytes`], but reuses the metrics cached in
+    /// [`CompressionOptions::metrics_cache`] before compressing, see [`metrics_cache`].
+    ///
+    /// # Arguments
+    /// * `bytes` - A slice of bytes representing the data to analyze.
+    /// * `group_name` - The name of the group being analyzed.
+    /// * `group_definition` - The definition of the group in the schema, e.g. its fields,
+    ///   which identifies it in the cache.
+    /// * `compression_options` - Compression options, zstd compression level, etc.
+    ///
+    /// [`metrics_cache`]: crate::utils::metrics_cache
+    pub fn from_bytes_cached(
+        bytes: &[u8],
+        group_name: &str,
+        group_definition: &(impl Debug + ?Sized),
+        compression_options: &CompressionOptions,
+    ) -> Self {
+        Self::measure_cached(bytes, group_definition, compression_options).with_estimate(
+            bytes,
+            group_name,
+            compression_options.lz_match_multiplier,
+            compression_options.entropy_multiplier,
+            compression_options,
+        )
+    }
+
+    /// The metrics of [`Self::from_bytes_cached`], without the estimated size.
+    /// Timed groups ([`CompressionOptions::measure_zstd_time`]) are always measured.
+    pub(crate) fn measure_cached(
+        bytes: &[u8],
+        group_definition: &(impl Debug + ?Sized),
+        compression_options: &CompressionOptions,
+    ) -> Self {
+        match &compression_options.metrics_cache {
+            Some(region) if !compression_options.measure_zstd_time => region.cache.get_or_compute(
+                region.key(group_definition, compression_options),
+                region.file_modified,
+                || Self::measure(bytes, compression_options),
+            ),
+            _ => Self::measure(bytes, compression_options),
+        }
+    }
+
+    /// Measures everything but the estimated size, i.e. what is worth caching.
+    fn measure(bytes: &[u8], compression_options: &CompressionOptions) -> Self {
+        let (zstd_size, zstd_compress_ns) = compression_options.zstd_compressed_size_timed(bytes);
         GroupComparisonMetrics {
-            lz_matches,
-            entropy,
-            estimated_size,
+            lz_matches: estimate_num_lz_matches_fast(bytes) as u64,
+            entropy: calculate_file_entropy(bytes),
+            estimated_size: 0,
+            has_estimate: false,
             zstd_size,
             original_size: bytes.len() as u64,
+            estimate_breakdown: None,
+            brotli_size: compression_options.brotli_compressed_size(bytes),
+            deflate_size: compression_options.deflate_compressed_size(bytes),
+            zstd_dict_size: compression_options.zstd_dict_compressed_size(bytes),
+            entropy_order1: compression_options.order1_entropy(bytes),
+            reference_size: None,
+            zstd_compress_ns,
+            zstd_calibration_size: compression_options.zstd_calibration_size(bytes),
         }
     }
+
+    /// Sets the estimated size of the group of `bytes`, from its measured entropy and
+    /// LZ matches, with the given multipliers.
+    pub(crate) fn with_estimate(
+        mut self,
+        bytes: &[u8],
+        group_name: &str,
+        lz_match_multiplier: f64,
+        entropy_multiplier: f64,
+        compression_options: &CompressionOptions,
+    ) -> Self {
+        let estimation_params = SizeEstimationParameters {
+            name: group_name,
+            data: Some(bytes),
+            data_len: bytes.len(),
+            num_lz_matches: self.lz_matches as usize,
+            entropy: self.entropy,
+            lz_match_multiplier,
+            entropy_multiplier,
+        };
+        self.estimated_size = compression_options.estimate_size(estimation_params) as u64;
+        self.has_estimate = true;
+        self.estimate_breakdown = compression_options.estimate_size_breakdown(estimation_params);
+        self
+    }
 }
 
 impl GroupDifference {
@@ -0,0 +1,156 @@
+//! Split comparisons of more than 2 groups, i.e. those with [`SplitComparison::groups`].
+//!
+//! Instead of authoring one split comparison per candidate layout, e.g. for the many possible
+//! channel layouts of a BC7 block, all layouts can be listed in a single comparison:
+//!
+//! ```yaml
+//! split_groups:
+//!   - name: channels
+//!     groups:
+//!       - [colors]                     # baseline, RGBRGBRGB
+//!       - [color_r, color_g, color_b]  # group_2, RRRGGGBBB
+//!       - [color_r, colors_gb]         # group_3, RRRGBGBGB
+//! ```
+//!
+//! The groups are put together exactly like [`SplitComparison::group_1`] and
+//! [`SplitComparison::group_2`] are, with the first group as baseline. As the result holds a
+//! baseline and any number of groups compared against it, it is a [`GroupComparisonResult`],
+//! reported (merged, printed, etc.) alongside the custom comparisons.
+
+use super::{compare_groups::GroupComparisonResult, GroupComparisonMetrics};
+use crate::{
+    analyzer::{AnalyzerFieldState, CompressionOptions},
+    results::{analysis_results::split_group_bytes, ComputeAnalysisResultsError},
+    schema::SplitComparison,
+};
+use ahash::AHashMap;
+
+/// Analyzes all split comparisons which list their groups in [`SplitComparison::groups`].
+/// This is an internal API.
+///
+/// # Arguments
+///
+/// * `comparisons` - The split comparisons of the schema; two-group comparisons are skipped.
+/// * `field_stats` - Mutable reference to field statistics map
+/// * `compression_options` - Compression options, zstd compression level, etc.
+///
+/// # Returns
+///
+/// A [`GroupComparisonResult`] per comparison, with groups named `group_2`, `group_3`, etc.
+/// Under [`CompressionOptions::strict`], an error if a group resolves to no fields.
+pub(crate) fn analyze_multi_split_comparisons(
+    comparisons: &[SplitComparison],
+    field_stats: &mut AHashMap<String, AnalyzerFieldState>,
+    compression_options: &CompressionOptions,
+) -> Result<Vec<GroupComparisonResult>, ComputeAnalysisResultsError> {
+    let mut results = Vec::new();
+    for comparison in comparisons.iter().filter(|c| c.is_multi_group()) {
+        let mut group_metrics = Vec::with_capacity(comparison.groups.len());
+        for (index, names) in comparison.groups.iter().enumerate() {
+            let group = format!("group_{}", index + 1);
+            let bytes = split_group_bytes(field_stats, names);
+            if bytes.is_empty() && compression_options.strict {
+                return Err(ComputeAnalysisResultsError::EmptySplitGroup {
+                    comparison: comparison.name.clone(),
+                    group,
+                });
+            }
+
+            let metrics_name = format!("{}-{}", comparison.name, index + 1);
+            group_metrics.push(GroupComparisonMetrics::from_bytes_cached(
+                &bytes,
+                &metrics_name,
+                names,
+                compression_options,
+            ));
+        }
+
+        let baseline_metrics = group_metrics.remove(0);
+        let group_names = (2..=comparison.groups.len())
+            .map(|number| format!("group_{number}"))
+            .collect();
+        results.push(GroupComparisonResult::from_group_metrics(
+            comparison.name.clone(),
+            comparison.description.clone(),
+            baseline_metrics,
+            group_names,
+            group_metrics,
+        ));
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::{CompressionOptions, SchemaAnalyzer};
+    use crate::schema::{Schema, SchemaError};
+
+    const SCHEMA: &str = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    colors:
+      type: group
+      fields:
+        r: 8
+        g: 8
+        b: 8
+analysis:
+  split_groups:
+"#;
+
+    #[test]
+    fn compares_each_group_against_the_first() {
+        let schema = Schema::from_yaml(&format!(
+            "{SCHEMA}    - {{ name: legacy, group_1: [colors], group_2: [r, g, b] }}\n    - {{ name: many, description: layouts, groups: [[colors], [r, g, b], [b, g, r]] }}\n"
+        ))
+        .unwrap();
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        for x in 0..32u8 {
+            analyzer.add_entry(&[x, x / 2, 7]).unwrap();
+        }
+        let results = analyzer.generate_results().unwrap();
+
+        // Two-group comparisons are still split comparisons.
+        assert_eq!(results.split_comparisons.len(), 1);
+        assert_eq!(results.split_comparisons[0].name, "legacy");
+
+        let many = &results.custom_comparisons[0];
+        assert_eq!(many.name, "many");
+        assert_eq!(many.description, "layouts");
+        assert_eq!(many.group_names, ["group_2", "group_3"]);
+        assert_eq!(
+            many.baseline_metrics.zstd_size,
+            results.split_comparisons[0].group1_metrics.zstd_size
+        );
+        assert_eq!(
+            many.group_metrics[0].zstd_size,
+            results.split_comparisons[0].group2_metrics.zstd_size
+        );
+        assert_eq!(many.group_metrics[1].original_size, 96);
+    }
+
+    #[test]
+    fn groups_are_validated() {
+        for (comparison, reason) in [
+            ("{ name: s, group_1: [r] }", "needs both"),
+            ("{ name: s, groups: [[r]] }", "at least 2"),
+            (
+                "{ name: s, group_1: [r], groups: [[r], [g]] }",
+                "can't have both",
+            ),
+            (
+                "{ name: s, groups: [[r], [g]], normalize_length: true }",
+                "normalize_length",
+            ),
+        ] {
+            let error = Schema::from_yaml(&format!("{SCHEMA}    - {comparison}\n")).unwrap_err();
+            assert!(
+                matches!(&error, SchemaError::InvalidSplitComparison { .. })
+                    && error.to_string().contains(reason),
+                "{comparison}: {error}"
+            );
+        }
+    }
+}
@@ -7,6 +7,7 @@
 //!
 //! - [`SplitComparisonResult`]: Results from comparing field arrangements
 //! - [`FieldComparisonMetrics`]: Field-level compression statistics
+//! - [`SplitComparisonBytes`]: The compared bytes, regenerated on demand for investigation
 //!
 //! # Example
 //!
@@ -22,6 +23,7 @@
 //! Each comparison tracks:
 //! - Entropy and LZ matches (data redundancy measures)
 //! - Sizes (original, estimated compression, actual zstd compression)
+//! - Entropy of the residual between both groups, when they have equal length ([`ResidualEntropy`])
 //!
 //! # Usage Notes
 //!
@@ -31,16 +33,17 @@
 //!
 //! [`SplitComparisonResult`]: crate::comparison::split_comparison::SplitComparisonResult
 //! [`FieldComparisonMetrics`]: crate::comparison::split_comparison::FieldComparisonMetrics
+//! [`ResidualEntropy`]: crate::comparison::split_comparison::ResidualEntropy
+//! [`SplitComparisonBytes`]: crate::comparison::split_comparison::SplitComparisonBytes
 //! [`make_split_comparison_result`]: crate::comparison::split_comparison::make_split_comparison_result
 
 use super::{GroupComparisonMetrics, GroupDifference};
 use crate::{
-    analyzer::{CompressionOptions, SizeEstimationParameters},
-    results::FieldMetrics,
-    schema::CompressionEstimationParams,
-    utils::analyze_utils::{calculate_file_entropy, get_zstd_compressed_size},
+    analyzer::CompressionOptions, results::FieldMetrics, schema::CompressionEstimationParams,
+    utils::analyze_utils::calculate_file_entropy,
 };
-use lossless_transform_utils::match_estimator::estimate_num_lz_matches_fast;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
 
 /// Calculates the compression statistics of two splits (of the same data) and
 /// returns them as a [`SplitComparisonResult`] object. This can also be used for
@@ -55,6 +58,9 @@ use lossless_transform_utils::match_estimator::estimate_num_lz_matches_fast;
 /// * `description` - A description of the group comparison.
 /// * `baseline_bytes` - The bytes of the baseline (original/reference) group.
 /// * `split_bytes` - The bytes of the second (comparison) group.
+/// * `group_1` - The definition of the baseline group, e.g. its fields, which identifies it in
+///   [`CompressionOptions::metrics_cache`]. See [`GroupComparisonMetrics::from_bytes_cached`].
+/// * `group_2` - The definition of the second (comparison) group.
 /// * `baseline_comparison_metrics` - The metrics for the individual fields in the baseline (original/reference) group.
 /// * `split_comparison_metrics` - The metrics for the individual fields in the second (comparison) group.
 /// * `compression_options` - Compression options, zstd compression level, etc.
@@ -69,6 +75,8 @@ pub fn make_split_comparison_result(
     description: String,
     baseline_bytes: &[u8],
     split_bytes: &[u8],
+    group_1: &(impl Debug + ?Sized),
+    group_2: &(impl Debug + ?Sized),
     baseline_comparison_metrics: Vec<FieldComparisonMetrics>,
     split_comparison_metrics: Vec<FieldComparisonMetrics>,
     compression_options: CompressionOptions,
@@ -80,51 +88,26 @@ pub fn make_split_comparison_result(
     let comp_est_2 = compression_estimation_group_2
         .unwrap_or(CompressionEstimationParams::new(&compression_options));
 
-    // Calculate entropy and LZ matches for both group sets.
-    let entropy1 = calculate_file_entropy(baseline_bytes);
-    let entropy2 = calculate_file_entropy(split_bytes);
-    let lz_matches1 = estimate_num_lz_matches_fast(baseline_bytes);
-    let lz_matches2 = estimate_num_lz_matches_fast(split_bytes);
     let name_1 = format!("{}-1", name);
     let name_2 = format!("{}-2", name);
-    let estimated_size_1 = (compression_options.size_estimator_fn)(SizeEstimationParameters {
-        name: &name_1,
-        data_len: baseline_bytes.len(),
-        data: Some(baseline_bytes),
-        num_lz_matches: lz_matches1,
-        entropy: entropy1,
-        lz_match_multiplier: comp_est_1.lz_match_multiplier,
-        entropy_multiplier: comp_est_1.entropy_multiplier,
-    });
-    let estimated_size_2 = (compression_options.size_estimator_fn)(SizeEstimationParameters {
-        name: &name_2,
-        data_len: split_bytes.len(),
-        data: Some(split_bytes),
-        num_lz_matches: lz_matches2,
-        entropy: entropy2,
-        lz_match_multiplier: comp_est_2.lz_match_multiplier,
-        entropy_multiplier: comp_est_2.entropy_multiplier,
-    });
-    let actual_size_1 =
-        get_zstd_compressed_size(baseline_bytes, compression_options.zstd_compression_level);
-    let actual_size_2 =
-        get_zstd_compressed_size(split_bytes, compression_options.zstd_compression_level);
-
-    let group1_metrics = GroupComparisonMetrics {
-        lz_matches: lz_matches1 as u64,
-        entropy: entropy1,
-        estimated_size: estimated_size_1 as u64,
-        zstd_size: actual_size_1,
-        original_size: baseline_bytes.len() as u64,
-    };
-
-    let group2_metrics = GroupComparisonMetrics {
-        lz_matches: lz_matches2 as u64,
-        entropy: entropy2,
-        estimated_size: estimated_size_2 as u64,
-        zstd_size: actual_size_2,
-        original_size: split_bytes.len() as u64,
-    };
+    let group1_metrics =
+        GroupComparisonMetrics::measure_cached(baseline_bytes, group_1, &compression_options)
+            .with_estimate(
+                baseline_bytes,
+                &name_1,
+                comp_est_1.lz_match_multiplier,
+                comp_est_1.entropy_multiplier,
+                &compression_options,
+            );
+    let group2_metrics =
+        GroupComparisonMetrics::measure_cached(split_bytes, group_2, &compression_options)
+            .with_estimate(
+                split_bytes,
+                &name_2,
+                comp_est_2.lz_match_multiplier,
+                comp_est_2.entropy_multiplier,
+                &compression_options,
+            );
 
     SplitComparisonResult {
         name,
@@ -134,11 +117,15 @@ pub fn make_split_comparison_result(
         group2_metrics,
         baseline_comparison_metrics,
         split_comparison_metrics,
+        residual_entropy: ResidualEntropy::from_bytes(baseline_bytes, split_bytes),
+        empty_groups: Vec::new(),
+        size_mismatch_bytes: baseline_bytes.len().abs_diff(split_bytes.len()) as u64,
+        truncated_data_bytes: 0,
     }
 }
 
 /// The result of comparing 2 arbitrary groups of fields based on the schema.
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct SplitComparisonResult {
     /// The name of the group comparison. (Copied from schema)
     pub name: String,
@@ -154,6 +141,84 @@ pub struct SplitComparisonResult {
     pub baseline_comparison_metrics: Vec<FieldComparisonMetrics>,
     /// The statistics for the individual fields of the split group.
     pub split_comparison_metrics: Vec<FieldComparisonMetrics>,
+    /// Entropy of the residual between both groups.
+    /// `None` if the groups differ in length.
+    pub residual_entropy: Option<ResidualEntropy>,
+    /// The groups (`group_1`, `group_2`) which resolve to no fields, e.g. because they only
+    /// list groups containing nested groups without any fields.
+    /// The metrics of a comparison with an empty group are meaningless.
+    pub empty_groups: Vec<String>,
+    /// Absolute difference between the sizes of both groups, in bytes.
+    /// Both groups hold the same fields, so this should be 0, up to a few bytes of padding
+    /// at the end of each field; more indicates the groups don't match.
+    #[serde(default)]
+    pub size_mismatch_bytes: u64,
+    /// Number of non-zero bytes discarded from the end of group 2 when truncating it to the length
+    /// of group 1 ([`SplitComparison::normalize_length`]). Zero bytes are assumed to be padding;
+    /// anything else was data, which is then missing from the comparison.
+    ///
+    /// [`SplitComparison::normalize_length`]: crate::schema::SplitComparison::normalize_length
+    #[serde(default)]
+    pub truncated_data_bytes: u64,
+}
+
+/// The bytes of both groups of a split comparison, as they were compressed.
+/// Regenerated on demand via [`SchemaAnalyzer::split_comparison_bytes`].
+///
+/// [`SchemaAnalyzer::split_comparison_bytes`]: crate::analyzer::SchemaAnalyzer::split_comparison_bytes
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SplitComparisonBytes {
+    /// The bytes of the first (baseline) group.
+    pub group_1: Vec<u8>,
+    /// The bytes of the second (comparison) group.
+    pub group_2: Vec<u8>,
+}
+
+/// Entropy of the byte-wise residual between group 2 and group 1.
+///
+/// This tells how much information group 2 holds which isn't already present at the same
+/// position in group 1. A residual entropy near 0 means the transform is largely redundant
+/// (the groups are mostly identical), while a residual entropy close to the entropy of the
+/// groups themselves means the two layouts share little byte-for-byte.
+#[derive(PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ResidualEntropy {
+    /// Entropy of `group2 ^ group1`, in bits per byte.
+    pub xor: f64,
+    /// Entropy of `group2 - group1` (wrapping), in bits per byte.
+    pub subtract: f64,
+}
+
+impl ResidualEntropy {
+    /// Computes the residual entropy of two groups.
+    /// Returns `None` if the groups have different lengths, or are empty.
+    ///
+    /// # Arguments
+    /// * `group1_bytes` - The bytes of the baseline group.
+    /// * `group2_bytes` - The bytes of the comparison group.
+    pub fn from_bytes(group1_bytes: &[u8], group2_bytes: &[u8]) -> Option<Self> {
+        if group1_bytes.len() != group2_bytes.len() || group1_bytes.is_empty() {
+            return None;
+        }
+
+        let mut residual = vec![0u8; group1_bytes.len()];
+        for (out, (a, b)) in residual
+            .iter_mut()
+            .zip(group1_bytes.iter().zip(group2_bytes))
+        {
+            *out = b ^ a;
+        }
+        let xor = calculate_file_entropy(&residual);
+
+        for (out, (a, b)) in residual
+            .iter_mut()
+            .zip(group1_bytes.iter().zip(group2_bytes))
+        {
+            *out = b.wrapping_sub(*a);
+        }
+        let subtract = calculate_file_entropy(&residual);
+
+        Some(Self { xor, subtract })
+    }
 }
 
 /// Helper functions around [`SplitComparisonResult`]
@@ -177,6 +242,14 @@ impl SplitComparisonResult {
     pub fn split_max_entropy_diff_ratio(&self) -> f64 {
         calculate_max_entropy_diff_ratio(&self.split_comparison_metrics)
     }
+
+    /// Zstd bytes saved by using the better of the two groups instead of group 1 (the baseline).
+    /// This is 0 if splitting doesn't help.
+    pub fn zstd_bytes_saved(&self) -> u64 {
+        self.group1_metrics
+            .zstd_size
+            .saturating_sub(self.group2_metrics.zstd_size)
+    }
 }
 
 /// Represents the statistics for the individual fields which were used
@@ -187,7 +260,7 @@ impl SplitComparisonResult {
 ///
 /// This is useful when dumping
 /// extra info about the fields.
-#[derive(PartialEq, Debug, Clone, Copy, Default)]
+#[derive(PartialEq, Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct FieldComparisonMetrics {
     /// LZ compression matches in the field
     pub lz_matches: u64,
@@ -205,6 +278,22 @@ impl From<FieldMetrics> for FieldComparisonMetrics {
     }
 }
 
+/// Pads `group` with zero bytes, or truncates it, to `len` bytes,
+/// for [`SplitComparison::normalize_length`].
+///
+/// # Returns
+///
+/// The number of non-zero (i.e. non-padding) bytes discarded by truncating.
+///
+/// [`SplitComparison::normalize_length`]: crate::schema::SplitComparison::normalize_length
+pub(crate) fn normalize_group_length(group: &mut Vec<u8>, len: usize) -> u64 {
+    let discarded = group
+        .get(len..)
+        .map_or(0, |tail| tail.iter().filter(|&&byte| byte != 0).count());
+    group.resize(len, 0);
+    discarded as u64
+}
+
 pub(crate) fn calculate_max_entropy_diff(results: &[FieldComparisonMetrics]) -> f64 {
     let entropy_values: Vec<f64> = results.iter().map(|m| m.entropy).collect();
     if entropy_values.len() < 2 {
@@ -241,3 +330,125 @@ pub(crate) fn calculate_max_entropy_diff_ratio(results: &[FieldComparisonMetrics
         max / min
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::SchemaAnalyzer;
+    use crate::schema::Schema;
+
+    #[test]
+    fn padding_is_reported_as_size_mismatch() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    ab:
+      type: group
+      fields:
+        a: 4
+        b: 4
+analysis:
+  split_groups:
+    - name: nibbles
+      group_1: [ab]
+      group_2: [a, b]
+"#,
+        )
+        .unwrap();
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        // 3 records; each 4 bit field holds 12 bits, padded to 2 bytes.
+        for x in 0..3u8 {
+            analyzer.add_entry(&[x]).unwrap();
+        }
+        let results = analyzer.generate_results().unwrap();
+
+        let comparison = &results.split_comparisons[0];
+        assert_eq!(comparison.group1_metrics.original_size, 3);
+        assert_eq!(comparison.group2_metrics.original_size, 4);
+        assert_eq!(comparison.size_mismatch_bytes, 1);
+    }
+
+    #[test]
+    fn normalizing_length_pads_or_truncates_group() {
+        let mut group = vec![1, 2];
+        assert_eq!(normalize_group_length(&mut group, 4), 0);
+        assert_eq!(group, [1, 2, 0, 0]);
+
+        // Trailing zeros are padding; anything else is data.
+        let mut group = vec![1, 2, 0, 3, 0];
+        assert_eq!(normalize_group_length(&mut group, 2), 1);
+        assert_eq!(group, [1, 2]);
+    }
+
+    #[test]
+    fn normalized_groups_have_no_size_mismatch() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    ab:
+      type: group
+      fields:
+        a: 4
+        b: 4
+analysis:
+  split_groups:
+    - name: nibbles
+      group_1: [ab]
+      group_2: [a, b]
+      normalize_length: true
+"#,
+        )
+        .unwrap();
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        // Group 2 is 1 byte longer due to padding, see `padding_is_reported_as_size_mismatch`.
+        for x in 1..4u8 {
+            analyzer.add_entry(&[x]).unwrap();
+        }
+        let results = analyzer.generate_results().unwrap();
+
+        let comparison = &results.split_comparisons[0];
+        assert_eq!(comparison.group2_metrics.original_size, 3);
+        assert_eq!(comparison.size_mismatch_bytes, 0);
+        // The last byte of 'b' holds its 3rd value, which is data.
+        assert_eq!(comparison.truncated_data_bytes, 1);
+        assert!(comparison.residual_entropy.is_some());
+        assert_eq!(
+            analyzer
+                .split_comparison_bytes("nibbles")
+                .unwrap()
+                .group_2
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn residual_entropy_is_zero_for_identical_groups() {
+        let data: Vec<u8> = (0..=255u8).collect();
+        let residual = ResidualEntropy::from_bytes(&data, &data).unwrap();
+        assert_eq!(residual.xor, 0.0);
+        assert_eq!(residual.subtract, 0.0);
+    }
+
+    #[test]
+    fn residual_entropy_captures_constant_delta() {
+        let group1: Vec<u8> = (0..=255u8).collect();
+        let group2: Vec<u8> = group1.iter().map(|x| x.wrapping_add(3)).collect();
+        let residual = ResidualEntropy::from_bytes(&group1, &group2).unwrap();
+        // Difference is always 3, but XOR varies with carries.
+        assert_eq!(residual.subtract, 0.0);
+        assert!(residual.xor > 0.0);
+    }
+
+    #[test]
+    fn residual_entropy_skipped_when_lengths_differ() {
+        assert_eq!(ResidualEntropy::from_bytes(&[1, 2, 3], &[1, 2]), None);
+        assert_eq!(ResidualEntropy::from_bytes(&[], &[]), None);
+    }
+}
@@ -0,0 +1,146 @@
+//! Correlation coefficients, e.g. of the estimated and zstd sizes of a group across files.
+
+use super::SizeCorrelation;
+use crate::comparison::GroupComparisonMetrics;
+
+/// Calculate the Pearson correlation coefficient of two equally long arrays of values.
+///
+/// # Returns
+///
+/// The correlation (`-1.0..=1.0`), or NaN if the arrays differ in length, have fewer than
+/// 2 values, or either array has no variation.
+pub fn calculate_pearson_correlation(xs: &[f64], ys: &[f64]) -> f64 {
+    if xs.len() != ys.len() || xs.len() < 2 {
+        return f64::NAN;
+    }
+
+    let mean_x = xs.iter().sum::<f64>() / xs.len() as f64;
+    let mean_y = ys.iter().sum::<f64>() / ys.len() as f64;
+    let (mut covariance, mut variance_x, mut variance_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(ys) {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance_x += (x - mean_x) * (x - mean_x);
+        variance_y += (y - mean_y) * (y - mean_y);
+    }
+
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return f64::NAN;
+    }
+    covariance / (variance_x * variance_y).sqrt()
+}
+
+/// Calculate Spearman's rank correlation of two equally long arrays of values,
+/// i.e. the Pearson correlation of their ranks. Tied values are given the mean of their ranks.
+///
+/// # Returns
+///
+/// The correlation (`-1.0..=1.0`), or NaN if undefined (see [`calculate_pearson_correlation`]).
+pub fn calculate_spearman_correlation(xs: &[f64], ys: &[f64]) -> f64 {
+    calculate_pearson_correlation(&ranks(xs), &ranks(ys))
+}
+
+/// The (1-based) ranks of values, with ties given the mean of their ranks.
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        // Ranks start..end (0-based) share their mean, 1-based.
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &x in &order[start..end] {
+            ranks[x] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// Calculate the correlation between the estimated and zstd sizes of a group across files.
+///
+/// # Arguments
+///
+/// * `metrics` - The metrics of the group in each file. Files without an estimate are skipped.
+///
+/// # Returns
+///
+/// Optional [`SizeCorrelation`], or [`None`] if no file has an estimate
+pub fn calculate_estimate_correlation<'a>(
+    metrics: impl IntoIterator<Item = &'a GroupComparisonMetrics>,
+) -> Option<SizeCorrelation> {
+    let (estimated, zstd): (Vec<f64>, Vec<f64>) = metrics
+        .into_iter()
+        .filter(|m| m.has_estimate)
+        .map(|m| (m.estimated_size as f64, m.zstd_size as f64))
+        .unzip();
+    if estimated.is_empty() {
+        return None;
+    }
+
+    Some(SizeCorrelation {
+        pearson: calculate_pearson_correlation(&estimated, &zstd),
+        spearman: calculate_spearman_correlation(&estimated, &zstd),
+        count: estimated.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comparison::stats::format_size_correlation;
+
+    #[test]
+    fn correlation_tracks_magnitude_and_order() {
+        let xs = [1.0, 2.0, 3.0, 4.0];
+        assert!(
+            (calculate_pearson_correlation(&xs, &[10.0, 20.0, 30.0, 40.0]) - 1.0).abs() < 1e-12
+        );
+        assert!((calculate_pearson_correlation(&xs, &[4.0, 3.0, 2.0, 1.0]) + 1.0).abs() < 1e-12);
+
+        // Monotonic but not linear: ranked perfectly, but the magnitudes don't track.
+        let ys = [1.0, 2.0, 3.0, 100.0];
+        assert!(calculate_pearson_correlation(&xs, &ys) < 0.9);
+        assert_eq!(calculate_spearman_correlation(&xs, &ys), 1.0);
+        assert_eq!(ranks(&[3.0, 1.0, 3.0]), vec![2.5, 1.0, 2.5]);
+
+        // Undefined for constant series and too few values.
+        assert!(calculate_pearson_correlation(&xs, &[5.0; 4]).is_nan());
+        assert!(calculate_spearman_correlation(&xs, &[5.0; 4]).is_nan());
+        assert!(calculate_pearson_correlation(&xs[..1], &[1.0]).is_nan());
+    }
+
+    #[test]
+    fn estimate_correlation_skips_groups_without_estimate() {
+        let metrics = |estimated_size, zstd_size, has_estimate| GroupComparisonMetrics {
+            estimated_size,
+            zstd_size,
+            has_estimate,
+            ..GroupComparisonMetrics::default()
+        };
+        let groups = [
+            metrics(100, 90, true),
+            metrics(200, 180, true),
+            metrics(0, 1000, false),
+        ];
+        let correlation = calculate_estimate_correlation(&groups).unwrap();
+        assert_eq!(correlation.count, 2);
+        assert!((correlation.pearson - 1.0).abs() < 1e-12);
+        assert_eq!(
+            format_size_correlation(&correlation),
+            "r=1.000, rho=1.000 (n=2)"
+        );
+
+        let constant = [metrics(100, 90, true), metrics(100, 95, true)];
+        let correlation = calculate_estimate_correlation(&constant).unwrap();
+        assert_eq!(
+            format_size_correlation(&correlation),
+            "r=NaN, rho=NaN (n=2)"
+        );
+        assert!(calculate_estimate_correlation(&groups[2..]).is_none());
+    }
+}
@@ -0,0 +1,244 @@
+//! Confidence intervals and effect sizes, widened with Student's t-distribution
+//! so they stay honest when only a few files were analyzed.
+
+use super::EffectSize;
+use core::f64::consts::PI;
+
+/// Two-sided 90% critical values of Student's t-distribution, for 1 to 30 degrees of freedom.
+const T_CRITICAL_90: [f64; 30] = [
+    6.314, 2.920, 2.353, 2.132, 2.015, 1.943, 1.895, 1.860, 1.833, 1.812, 1.796, 1.782, 1.771,
+    1.761, 1.753, 1.746, 1.740, 1.734, 1.729, 1.725, 1.721, 1.717, 1.714, 1.711, 1.708, 1.706,
+    1.703, 1.701, 1.699, 1.697,
+];
+
+/// Two-sided 95% critical values of Student's t-distribution, for 1 to 30 degrees of freedom.
+const T_CRITICAL_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+
+/// Two-sided 99% critical values of Student's t-distribution, for 1 to 30 degrees of freedom.
+const T_CRITICAL_99: [f64; 30] = [
+    63.657, 9.925, 5.841, 4.604, 4.032, 3.707, 3.499, 3.355, 3.250, 3.169, 3.106, 3.055, 3.012,
+    2.977, 2.947, 2.921, 2.898, 2.878, 2.861, 2.845, 2.831, 2.819, 2.807, 2.797, 2.787, 2.779,
+    2.771, 2.763, 2.756, 2.750,
+];
+
+/// Two-sided 99.9% critical values of Student's t-distribution, for 1 to 30 degrees of freedom.
+const T_CRITICAL_999: [f64; 30] = [
+    636.619, 31.599, 12.924, 8.610, 6.869, 5.959, 5.408, 5.041, 4.781, 4.587, 4.437, 4.318, 4.221,
+    4.140, 4.073, 4.015, 3.965, 3.922, 3.883, 3.850, 3.819, 3.792, 3.768, 3.745, 3.725, 3.707,
+    3.690, 3.674, 3.659, 3.646,
+];
+
+/// Two-sided critical value of Student's t-distribution for a `confidence` level (e.g. 0.95)
+/// and `df` (at least 1) degrees of freedom.
+///
+/// The 90%, 95%, 99% and 99.9% levels are looked up in the `T_CRITICAL_*` tables up to
+/// 30 degrees of freedom; everything else is approximated with [`t_quantile`].
+fn t_critical(confidence: f64, df: usize) -> f64 {
+    let table = match confidence {
+        0.90 => Some(&T_CRITICAL_90),
+        0.95 => Some(&T_CRITICAL_95),
+        0.99 => Some(&T_CRITICAL_99),
+        0.999 => Some(&T_CRITICAL_999),
+        _ => None,
+    };
+    if let Some(&t) = table.and_then(|table| table.get(df - 1)) {
+        return t;
+    }
+
+    t_quantile((1.0 + confidence) / 2.0, df)
+}
+
+/// Quantile `p` (0.5 to 1.0) of Student's t-distribution with `df` degrees of freedom.
+///
+/// 1 and 2 degrees of freedom have closed forms; past that, the Cornish-Fisher expansion
+/// around the normal quantile is used. Past 30 degrees of freedom it is within 1e-4 of the
+/// exact value, even for `p` = 0.99995. With fewer degrees of freedom it underestimates the
+/// far tails, e.g. 5.795 rather than 5.841 for `p` = 0.995 with 3 degrees of freedom,
+/// which is why [`t_critical`] prefers the exact tables.
+fn t_quantile(p: f64, df: usize) -> f64 {
+    match df {
+        1 => (PI * (p - 0.5)).tan(),
+        2 => (2.0 * p - 1.0) / (2.0 * p * (1.0 - p)).sqrt(),
+        _ => {
+            let z = normal_quantile(p);
+            let v = df as f64;
+            let (z2, z3) = (z * z, z * z * z);
+            let (z5, z7, z9) = (z3 * z2, z3 * z2 * z2, z3 * z3 * z3);
+            let g1 = (z3 + z) / 4.0;
+            let g2 = (5.0 * z5 + 16.0 * z3 + 3.0 * z) / 96.0;
+            let g3 = (3.0 * z7 + 19.0 * z5 + 17.0 * z3 - 15.0 * z) / 384.0;
+            let g4 = (79.0 * z9 + 776.0 * z7 + 1482.0 * z5 - 1920.0 * z3 - 945.0 * z) / 92160.0;
+            z + g1 / v + g2 / (v * v) + g3 / (v * v * v) + g4 / (v * v * v * v)
+        }
+    }
+}
+
+/// Quantile `p` (0.5 to 1.0) of the standard normal distribution.
+///
+/// Uses Acklam's rational approximation, which has a relative error below 1.2e-9.
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+
+    if p <= 1.0 - 0.02425 {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        let q = (-2.0 * (1.0 - p).ln()).sqrt();
+        -(((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    }
+}
+
+/// Calculate the confidence interval of the mean of an array of values.
+///
+/// The interval is widened with Student's t-distribution, so it stays honest
+/// when there are only a few values.
+///
+/// # Arguments
+///
+/// * `values` - Slice of values to analyze
+/// * `confidence` - Confidence level, between 0 and 1 (exclusive), e.g. 0.95
+///
+/// # Returns
+///
+/// The `(low, high)` bounds of the interval, or [`None`] if there are fewer than 2 values
+/// or the confidence level is out of range
+pub fn calculate_confidence_interval(values: &[f64], confidence: f64) -> Option<(f64, f64)> {
+    let count = values.len();
+    if count < 2 || !(confidence > 0.0 && confidence < 1.0) {
+        return None;
+    }
+
+    let mean = values.iter().sum::<f64>() / count as f64;
+    let variance = values.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (count - 1) as f64;
+    let margin = t_critical(confidence, count - 1) * variance.sqrt() / (count as f64).sqrt();
+    Some((mean - margin, mean + margin))
+}
+
+/// Calculate the effect size of a comparison group from its per-file ZSTD ratios.
+///
+/// # Arguments
+///
+/// * `ratios` - The ZSTD ratios (`group zstd size / baseline zstd size`) of each file
+///
+/// # Returns
+///
+/// An [`EffectSize`], or [`None`] if there are fewer than 2 files
+pub fn calculate_effect_size(ratios: &[f64]) -> Option<EffectSize> {
+    let count = ratios.len();
+    if count < 2 {
+        return None;
+    }
+
+    let differences: Vec<f64> = ratios.iter().map(|ratio| 1.0 - ratio).collect();
+    let mean = differences.iter().sum::<f64>() / count as f64;
+    let variance = differences
+        .iter()
+        .map(|x| (x - mean) * (x - mean))
+        .sum::<f64>()
+        / (count - 1) as f64;
+    let std_dev = variance.sqrt();
+    let (confidence_low, confidence_high) = calculate_confidence_interval(&differences, 0.95)?;
+    let cohens_d = if std_dev > 0.0 {
+        mean / std_dev
+    } else if mean == 0.0 {
+        0.0
+    } else {
+        f64::INFINITY.copysign(mean)
+    };
+
+    Some(EffectSize {
+        mean_difference: mean,
+        confidence_low,
+        confidence_high,
+        cohens_d,
+        count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effect_size_interval_matches_ratio_confidence_interval() {
+        let ratios = [0.8, 0.85, 0.9, 0.7];
+        let effect_size = calculate_effect_size(&ratios).unwrap();
+        let (low, high) = calculate_confidence_interval(&ratios, 0.95).unwrap();
+        let (ratio_low, ratio_high) = effect_size.ratio_confidence_interval();
+        assert!((ratio_low - low).abs() < 1e-12);
+        assert!((ratio_high - high).abs() < 1e-12);
+    }
+
+    #[test]
+    fn t_critical_values_approximate_the_table() {
+        for df in 1..=T_CRITICAL_95.len() {
+            let t = t_quantile(0.975, df);
+            assert!((t - T_CRITICAL_95[df - 1]).abs() < 0.01, "df={df}: {t}");
+        }
+        assert!((normal_quantile(0.975) - 1.959_964).abs() < 1e-6);
+        assert!((normal_quantile(0.995) - 2.575_829).abs() < 1e-6);
+        // 99%, 5 degrees of freedom.
+        assert!((t_critical(0.99, 5) - 4.032).abs() < 0.01);
+    }
+
+    #[test]
+    fn t_critical_uses_exact_tables_for_few_degrees_of_freedom() {
+        assert_eq!(t_critical(0.90, 3), 2.353);
+        assert_eq!(t_critical(0.99, 3), 5.841);
+        assert_eq!(t_critical(0.999, 3), 12.924);
+
+        // Past the tables, the approximation is close to the exact value.
+        assert!((t_critical(0.99, 40) - 2.704).abs() < 1e-3);
+        assert!((t_critical(0.999, 40) - 3.551).abs() < 1e-3);
+    }
+
+    #[test]
+    fn confidence_interval_widens_for_few_values() {
+        let (low, high) = calculate_confidence_interval(&[0.8, 0.9, 1.0], 0.95).unwrap();
+        // mean 0.9, standard error 0.1 / sqrt(3), t = 4.303
+        let margin = 4.303 * 0.1 / 3f64.sqrt();
+        assert!((low - (0.9 - margin)).abs() < 1e-12);
+        assert!((high - (0.9 + margin)).abs() < 1e-12);
+
+        let (low_90, high_90) = calculate_confidence_interval(&[0.8, 0.9, 1.0], 0.9).unwrap();
+        assert!(low_90 > low && high_90 < high);
+
+        assert!(calculate_confidence_interval(&[0.9], 0.95).is_none());
+        assert!(calculate_confidence_interval(&[0.8, 0.9], 1.0).is_none());
+    }
+}
@@ -0,0 +1,344 @@
+//! Statistical functions for analyzing compression metrics.
+//!
+//! This module provides functionality for calculating and analyzing statistical
+//! measures related to compression ratios and other metrics.
+//!
+//! # Types
+//!
+//! - [`Stats`]: Container for a complete set of statistical measures including
+//!   quartiles, mean, median, IQR, standard deviation, min/max, and sample count.
+//! - [`ZstdRatioExtremes`]: The files which benefited the most and least from a transform.
+//! - [`EffectSize`]: How reliable the difference between a group and its baseline is across files.
+//! - [`SizeCorrelation`]: How well the estimated sizes of a group track its zstd sizes across files.
+//!
+//! # Functions
+//!
+//! ## Core Statistics
+//!
+//! - [`calculate_stats`]: Calculate comprehensive statistics for an array of values
+//! - [`calculate_percentile`]: Helper function to calculate a specific percentile
+//! - [`format_stats`]: Format statistics as a human-readable string
+//! - [`calculate_effect_size`]: Calculate the effect size of a group from its per-file ratios
+//! - [`calculate_confidence_interval`]: Confidence interval of the mean of an array of values
+//! - [`format_effect_size`]: Format an effect size as a human-readable string
+//! - [`calculate_pearson_correlation`]: Pearson correlation coefficient of two arrays of values
+//! - [`calculate_spearman_correlation`]: Spearman's rank correlation of two arrays of values
+//! - [`calculate_estimate_correlation`]: Correlation between the estimated and zstd sizes of a group
+//! - [`format_size_correlation`]: Format a size correlation as a human-readable string
+//!
+//! ## ZSTD Compression Ratio Statistics
+//!
+//! - [`calculate_zstd_ratio_stats`]: Statistics for ZSTD ratios in split comparisons
+//! - [`calculate_custom_zstd_ratio_stats`]: Statistics for ZSTD ratios in custom comparisons
+//! - [`calculate_zstd_ratio_confidence`]: Confidence interval of the mean ZSTD ratio in split comparisons
+//! - [`find_zstd_ratio_extremes`]: Files with the best and worst ZSTD ratio in split comparisons
+//! - [`find_custom_zstd_ratio_extremes`]: Files with the best and worst ZSTD ratio in custom comparisons
+//! - [`calculate_zstd_bytes_saved`]: Total ZSTD bytes saved by a split comparison across all files
+//! - [`calculate_custom_zstd_bytes_saved`]: Total ZSTD bytes saved by a custom comparison across all files
+//! - [`calculate_zstd_effect_size`]: Effect size (Cohen's d) of the ZSTD ratios in split comparisons
+//! - [`calculate_custom_zstd_effect_size`]: Effect size (Cohen's d) of the ZSTD ratios in custom comparisons
+//!
+//! # Statistical Measures
+//!
+//! The module provides calculation of:
+//! - Interquartile Range (IQR)
+//! - Percentile ranges (Q1, median, Q3)
+//! - Minimum and maximum values
+//! - Mean (average)
+//! - Standard deviation
+//! - Sample count
+//! - Effect size (Cohen's d) and 95% confidence interval of the mean
+//! - Confidence interval of the mean, at any confidence level
+//! - Pearson and Spearman correlation coefficients
+
+use core::cmp::Ordering;
+use serde::Serialize;
+
+pub use correlation::{
+    calculate_estimate_correlation, calculate_pearson_correlation, calculate_spearman_correlation,
+};
+pub use inference::{calculate_confidence_interval, calculate_effect_size};
+pub use zstd_ratio::{
+    calculate_custom_zstd_bytes_saved, calculate_custom_zstd_effect_size,
+    calculate_custom_zstd_ratio_stats, calculate_zstd_bytes_saved, calculate_zstd_effect_size,
+    calculate_zstd_ratio_confidence, calculate_zstd_ratio_stats, find_custom_zstd_ratio_extremes,
+    find_zstd_ratio_extremes,
+};
+pub(crate) use zstd_ratio::{custom_zstd_ratios, split_zstd_ratios};
+
+mod correlation;
+mod inference;
+mod zstd_ratio;
+
+/// Statistics for a set of numeric values.
+#[derive(Debug, Clone, Copy)]
+pub struct Stats {
+    /// Minimum value
+    pub min: f64,
+    /// First quartile (25th percentile)
+    pub q1: f64,
+    /// Median (50th percentile)
+    pub median: f64,
+    /// Third quartile (75th percentile)
+    pub q3: f64,
+    /// Maximum value
+    pub max: f64,
+    /// Interquartile range (IQR = Q3 - Q1)
+    pub iqr: f64,
+    /// Mean (average) value
+    pub mean: f64,
+    /// Sample standard deviation; 0 for a single value
+    pub std_dev: f64,
+    /// Sample size
+    pub count: usize,
+}
+
+/// The files for which a comparison had the best and worst ZSTD ratio.
+///
+/// The ratio is `comparison zstd size / baseline zstd size`; so the lowest ratio
+/// is the file which benefited the most from the transform.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ZstdRatioExtremes {
+    /// Index of the file (in the analyzed results) with the lowest ratio
+    pub best_file_index: usize,
+    /// Lowest ratio
+    pub best_ratio: f64,
+    /// Index of the file (in the analyzed results) with the highest ratio
+    pub worst_file_index: usize,
+    /// Highest ratio
+    pub worst_ratio: f64,
+}
+
+/// The effect size of a comparison group against its baseline, computed from the
+/// per-file ZSTD ratios (`group zstd size / baseline zstd size`).
+///
+/// Each file is a paired sample: the difference of a file is how much smaller the group is
+/// relative to the baseline, i.e. `1 - ratio`. Cohen's d is then the paired form (d<sub>z</sub>):
+/// the mean difference divided by the standard deviation of the differences.
+/// A small but consistent improvement therefore has a large d, while a large improvement
+/// which varies a lot between files has a small d.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EffectSize {
+    /// Mean size reduction relative to the baseline (mean of `1 - ratio`).
+    /// Positive if the group compresses better than the baseline.
+    pub mean_difference: f64,
+    /// Lower bound of the 95% confidence interval of [`Self::mean_difference`].
+    pub confidence_low: f64,
+    /// Upper bound of the 95% confidence interval of [`Self::mean_difference`].
+    pub confidence_high: f64,
+    /// Cohen's d, positive if the group compresses better than the baseline.
+    /// Infinite if all files have the same non-zero difference.
+    pub cohens_d: f64,
+    /// Sample size (number of files).
+    pub count: usize,
+}
+
+impl EffectSize {
+    /// The 95% confidence interval of the mean ratio (`1 - difference`), as `(low, high)`.
+    pub fn ratio_confidence_interval(&self) -> (f64, f64) {
+        (1.0 - self.confidence_high, 1.0 - self.confidence_low)
+    }
+}
+
+/// The correlation between the estimated and zstd sizes of a group across files.
+///
+/// Unlike the agreement percentages, which only check whether the estimator picks the better
+/// group, this shows whether the estimates follow the magnitude of the actual sizes, i.e.
+/// whether the estimator is well calibrated. Both coefficients are NaN if undefined, which
+/// is the case with fewer than 2 files, or if either size is the same in every file.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SizeCorrelation {
+    /// Pearson correlation coefficient (`r`) of the sizes.
+    pub pearson: f64,
+    /// Spearman's rank correlation (`rho`) of the sizes.
+    pub spearman: f64,
+    /// Sample size (number of files with an estimate).
+    pub count: usize,
+}
+
+impl EffectSize {
+    /// Conventional description of the magnitude of [`Self::cohens_d`].
+    pub fn magnitude(&self) -> &'static str {
+        match self.cohens_d.abs() {
+            d if d < 0.2 => "negligible",
+            d if d < 0.5 => "small",
+            d if d < 0.8 => "medium",
+            _ => "large",
+        }
+    }
+}
+
+/// Calculate statistics for an array of values.
+///
+/// This function calculates various statistics including min, max, quartiles,
+/// interquartile range (IQR), mean and standard deviation.
+///
+/// # Arguments
+///
+/// * `values` - Slice of values to analyze
+///
+/// # Returns
+///
+/// A [`Stats`] struct containing the calculated statistics
+pub fn calculate_stats(values: &[f64]) -> Option<Stats> {
+    let count = values.len();
+    if count == 0 {
+        return None;
+    }
+
+    let mut sorted_values = values.to_vec();
+    sorted_values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let min = sorted_values[0];
+    let max = sorted_values[count - 1];
+
+    // Calculate mean
+    let sum: f64 = sorted_values.iter().sum();
+    let mean = sum / count as f64;
+
+    // Sample standard deviation (n - 1), a single value has no spread.
+    let std_dev = if count > 1 {
+        let variance = sorted_values
+            .iter()
+            .map(|x| (x - mean) * (x - mean))
+            .sum::<f64>()
+            / (count - 1) as f64;
+        variance.sqrt()
+    } else {
+        0.0
+    };
+
+    // Calculate median and quartiles
+    let median = calculate_percentile(&sorted_values, 0.5);
+    let q1 = calculate_percentile(&sorted_values, 0.25);
+    let q3 = calculate_percentile(&sorted_values, 0.75);
+    let iqr = q3 - q1;
+
+    Some(Stats {
+        min,
+        q1,
+        median,
+        q3,
+        max,
+        iqr,
+        mean,
+        std_dev,
+        count,
+    })
+}
+
+/// Calculate a specific percentile of values.
+///
+/// # Arguments
+///
+/// * `sorted_values` - Sorted slice of values
+/// * `percentile` - Percentile to calculate (0.0 to 1.0)
+///
+/// # Returns
+///
+/// The value at the specified percentile
+pub fn calculate_percentile(sorted_values: &[f64], percentile: f64) -> f64 {
+    let count = sorted_values.len();
+    if count == 0 {
+        return 0.0;
+    }
+
+    let index = percentile * (count - 1) as f64;
+    let lower_idx = index.floor() as usize;
+    let upper_idx = index.ceil() as usize;
+
+    if lower_idx == upper_idx {
+        sorted_values[lower_idx]
+    } else {
+        let weight = index - lower_idx as f64;
+        sorted_values[lower_idx] * (1.0 - weight) + sorted_values[upper_idx] * weight
+    }
+}
+
+/// Format statistics as a string.
+///
+/// # Arguments
+///
+/// * `stats` - The statistics to format
+///
+/// # Returns
+///
+/// A formatted string representation of the statistics
+pub fn format_stats(stats: &Stats) -> String {
+    format!(
+        "min: {:.3}, Q1: {:.3}, median: {:.3}, Q3: {:.3}, max: {:.3}, IQR: {:.3}, mean: {:.3}, stddev: {:.3} (n={})",
+        stats.min,
+        stats.q1,
+        stats.median,
+        stats.q3,
+        stats.max,
+        stats.iqr,
+        stats.mean,
+        stats.std_dev,
+        stats.count
+    )
+}
+
+/// Format an effect size as a string.
+///
+/// # Arguments
+///
+/// * `effect_size` - The effect size to format
+///
+/// # Returns
+///
+/// A formatted string representation of the effect size
+pub fn format_effect_size(effect_size: &EffectSize) -> String {
+    format!(
+        "mean saving: {:.2}%, Cohen's d: {:.2} ({}) (n={})",
+        effect_size.mean_difference * 100.0,
+        effect_size.cohens_d,
+        effect_size.magnitude(),
+        effect_size.count
+    )
+}
+
+/// Format a size correlation as a string.
+///
+/// # Arguments
+///
+/// * `correlation` - The correlation to format
+///
+/// # Returns
+///
+/// A formatted string representation of the correlation
+pub fn format_size_correlation(correlation: &SizeCorrelation) -> String {
+    format!(
+        "r={:.3}, rho={:.3} (n={})",
+        correlation.pearson, correlation.spearman, correlation.count
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_include_quartiles_and_standard_deviation() {
+        let stats = calculate_stats(&[4.0, 1.0, 3.0, 2.0, 5.0]).unwrap();
+        assert_eq!(
+            (stats.min, stats.q1, stats.median, stats.q3, stats.max),
+            (1.0, 2.0, 3.0, 4.0, 5.0)
+        );
+        assert_eq!(stats.iqr, 2.0);
+        assert_eq!(stats.mean, 3.0);
+        assert!((stats.std_dev - 2.5f64.sqrt()).abs() < 1e-12);
+        assert!(format_stats(&stats).contains("stddev: 1.581 (n=5)"));
+    }
+
+    #[test]
+    fn single_value_has_no_spread() {
+        let stats = calculate_stats(&[0.9]).unwrap();
+        assert_eq!(
+            (stats.min, stats.q1, stats.median, stats.q3, stats.max),
+            (0.9, 0.9, 0.9, 0.9, 0.9)
+        );
+        assert_eq!(stats.std_dev, 0.0);
+        assert!(calculate_stats(&[]).is_none());
+    }
+}
@@ -0,0 +1,266 @@
+//! Statistics of the ZSTD ratios of split and custom comparisons across files.
+
+use super::{calculate_confidence_interval, calculate_effect_size, calculate_stats};
+use super::{EffectSize, Stats, ZstdRatioExtremes};
+use crate::{plot::calc_ratio_f64, results::analysis_results::AnalysisResults};
+
+/// Calculate ZSTD ratio statistics between two groups in split comparison.
+///
+/// This function calculates the ZSTD compression ratio statistics between
+/// group1_metrics and group2_metrics using the results array.
+///
+/// # Arguments
+///
+/// * `results` - Slice of analysis results
+/// * `comparison_index` - Index of the comparison to analyze
+///
+/// # Returns
+///
+/// Optional [`Stats`] struct containing the ratio statistics, or [`None`] if there are no results
+pub fn calculate_zstd_ratio_stats(
+    results: &[AnalysisResults],
+    comparison_index: usize,
+) -> Option<Stats> {
+    let ratios: Vec<f64> = split_zstd_ratios(results, comparison_index)
+        .map(|(_, ratio)| ratio)
+        .collect();
+
+    calculate_stats(&ratios)
+}
+
+/// Calculate ZSTD ratio statistics between two groups in custom comparison.
+///
+/// This function calculates the ZSTD compression ratio statistics between
+/// a specific group in group_metrics and the baseline metrics.
+///
+/// # Arguments
+///
+/// * `results` - Slice of analysis results
+/// * `comparison_index` - Index of the custom comparison to analyze
+/// * `group_index` - Index of the group within group_metrics to compare with baseline
+///
+/// # Returns
+///
+/// Optional [`Stats`] struct containing the ratio statistics, or [`None`] if there are no results
+pub fn calculate_custom_zstd_ratio_stats(
+    results: &[AnalysisResults],
+    comparison_index: usize,
+    group_index: usize,
+) -> Option<Stats> {
+    let ratios: Vec<f64> = custom_zstd_ratios(results, comparison_index, group_index)
+        .map(|(_, ratio)| ratio)
+        .collect();
+
+    calculate_stats(&ratios)
+}
+
+/// Calculate the confidence interval of the mean ZSTD ratio between the two groups
+/// of a split comparison.
+///
+/// The ratio is `group 2 zstd size / group 1 zstd size`; so an interval entirely below 1
+/// means group 2 reliably compresses better, rather than by chance on the analyzed files.
+///
+/// # Arguments
+///
+/// * `results` - Slice of analysis results
+/// * `comparison_index` - Index of the comparison to analyze
+/// * `confidence` - Confidence level, between 0 and 1 (exclusive), e.g. 0.95
+///
+/// # Returns
+///
+/// The `(low, high)` bounds of the interval, or [`None`] if there are fewer than 2 results
+pub fn calculate_zstd_ratio_confidence(
+    results: &[AnalysisResults],
+    comparison_index: usize,
+    confidence: f64,
+) -> Option<(f64, f64)> {
+    let ratios: Vec<f64> = split_zstd_ratios(results, comparison_index)
+        .map(|(_, ratio)| ratio)
+        .collect();
+
+    calculate_confidence_interval(&ratios, confidence)
+}
+
+/// Calculate the effect size of group 2 against group 1 of a split comparison.
+///
+/// # Arguments
+///
+/// * `results` - Slice of analysis results
+/// * `comparison_index` - Index of the comparison to analyze
+///
+/// # Returns
+///
+/// Optional [`EffectSize`], or [`None`] if there are fewer than 2 results
+pub fn calculate_zstd_effect_size(
+    results: &[AnalysisResults],
+    comparison_index: usize,
+) -> Option<EffectSize> {
+    let ratios: Vec<f64> = split_zstd_ratios(results, comparison_index)
+        .map(|(_, ratio)| ratio)
+        .collect();
+
+    calculate_effect_size(&ratios)
+}
+
+/// Calculate the effect size of a group of a custom comparison against its baseline.
+///
+/// # Arguments
+///
+/// * `results` - Slice of analysis results
+/// * `comparison_index` - Index of the custom comparison to analyze
+/// * `group_index` - Index of the group within group_metrics to compare with baseline
+///
+/// # Returns
+///
+/// Optional [`EffectSize`], or [`None`] if there are fewer than 2 results
+pub fn calculate_custom_zstd_effect_size(
+    results: &[AnalysisResults],
+    comparison_index: usize,
+    group_index: usize,
+) -> Option<EffectSize> {
+    let ratios: Vec<f64> = custom_zstd_ratios(results, comparison_index, group_index)
+        .map(|(_, ratio)| ratio)
+        .collect();
+
+    calculate_effect_size(&ratios)
+}
+
+/// Find the files with the best and worst ZSTD ratio between the two groups of a split comparison.
+///
+/// # Arguments
+///
+/// * `results` - Slice of analysis results
+/// * `comparison_index` - Index of the comparison to analyze
+///
+/// # Returns
+///
+/// Optional [`ZstdRatioExtremes`], or [`None`] if there are no results
+pub fn find_zstd_ratio_extremes(
+    results: &[AnalysisResults],
+    comparison_index: usize,
+) -> Option<ZstdRatioExtremes> {
+    find_ratio_extremes(split_zstd_ratios(results, comparison_index))
+}
+
+/// Find the files with the best and worst ZSTD ratio between a group of a custom comparison
+/// and its baseline.
+///
+/// # Arguments
+///
+/// * `results` - Slice of analysis results
+/// * `comparison_index` - Index of the custom comparison to analyze
+/// * `group_index` - Index of the group within group_metrics to compare with baseline
+///
+/// # Returns
+///
+/// Optional [`ZstdRatioExtremes`], or [`None`] if there are no results
+pub fn find_custom_zstd_ratio_extremes(
+    results: &[AnalysisResults],
+    comparison_index: usize,
+    group_index: usize,
+) -> Option<ZstdRatioExtremes> {
+    find_ratio_extremes(custom_zstd_ratios(results, comparison_index, group_index))
+}
+
+/// Calculate the total ZSTD bytes saved by a split comparison across all files,
+/// when each file uses whichever of the two groups compresses better.
+///
+/// # Arguments
+///
+/// * `results` - Slice of analysis results
+/// * `comparison_index` - Index of the comparison to analyze
+pub fn calculate_zstd_bytes_saved(results: &[AnalysisResults], comparison_index: usize) -> u64 {
+    results
+        .iter()
+        .filter_map(|result| result.split_comparisons.get(comparison_index))
+        .map(|comparison| comparison.zstd_bytes_saved())
+        .sum()
+}
+
+/// Calculate the total ZSTD bytes saved by a custom comparison across all files,
+/// when each file uses whichever group (including the baseline) compresses best.
+///
+/// # Arguments
+///
+/// * `results` - Slice of analysis results
+/// * `comparison_index` - Index of the custom comparison to analyze
+pub fn calculate_custom_zstd_bytes_saved(
+    results: &[AnalysisResults],
+    comparison_index: usize,
+) -> u64 {
+    results
+        .iter()
+        .filter_map(|result| result.custom_comparisons.get(comparison_index))
+        .map(|comparison| comparison.zstd_bytes_saved())
+        .sum()
+}
+
+/// ZSTD ratios between group 2 and group 1 of a split comparison, as `(file index, ratio)`.
+pub(crate) fn split_zstd_ratios(
+    results: &[AnalysisResults],
+    comparison_index: usize,
+) -> impl Iterator<Item = (usize, f64)> + '_ {
+    results.iter().enumerate().filter_map(move |(x, result)| {
+        result
+            .split_comparisons
+            .get(comparison_index)
+            .map(|comparison| {
+                (
+                    x,
+                    calc_ratio_f64(
+                        comparison.group2_metrics.zstd_size,
+                        comparison.group1_metrics.zstd_size,
+                    ),
+                )
+            })
+    })
+}
+
+/// ZSTD ratios between a custom comparison group and its baseline, as `(file index, ratio)`.
+pub(crate) fn custom_zstd_ratios(
+    results: &[AnalysisResults],
+    comparison_index: usize,
+    group_index: usize,
+) -> impl Iterator<Item = (usize, f64)> + '_ {
+    results.iter().enumerate().filter_map(move |(x, result)| {
+        let comparison = result.custom_comparisons.get(comparison_index)?;
+        // Only include results where the group_index is valid
+        comparison
+            .group_metrics
+            .get(group_index)
+            .map(|group_metrics| {
+                (
+                    x,
+                    calc_ratio_f64(
+                        group_metrics.zstd_size,
+                        comparison.baseline_metrics.zstd_size,
+                    ),
+                )
+            })
+    })
+}
+
+fn find_ratio_extremes(
+    mut ratios: impl Iterator<Item = (usize, f64)>,
+) -> Option<ZstdRatioExtremes> {
+    let (first_index, first_ratio) = ratios.next()?;
+    let mut extremes = ZstdRatioExtremes {
+        best_file_index: first_index,
+        best_ratio: first_ratio,
+        worst_file_index: first_index,
+        worst_ratio: first_ratio,
+    };
+
+    for (x, ratio) in ratios {
+        if ratio < extremes.best_ratio {
+            extremes.best_file_index = x;
+            extremes.best_ratio = ratio;
+        }
+        if ratio > extremes.worst_ratio {
+            extremes.worst_file_index = x;
+            extremes.worst_ratio = ratio;
+        }
+    }
+
+    Some(extremes)
+}
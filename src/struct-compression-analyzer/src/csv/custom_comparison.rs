@@ -0,0 +1,312 @@
+//! The CSVs of the custom comparisons of each file.
+
+use super::{best_worst_record, calc_ratio, reference_ratio, sanitize_filename};
+use super::{BEST_WORST_FILES_CSV, BEST_WORST_HEADERS};
+use crate::comparison::stats::find_custom_zstd_ratio_extremes;
+use crate::results::analysis_results::AnalysisResults;
+use csv::Writer;
+use std::path::{Path, PathBuf};
+
+/// Writes CSV files comparing groups of fields within each file, for custom comparisons.
+///
+/// This function is analogous to `write_split_comparison_csv`, but handles
+/// `custom_comparisons` instead.  It includes multiple comparison groups.
+///
+/// # Arguments
+///
+/// * `results` - A slice of [`AnalysisResults`], one for each analyzed file.
+/// * `output_dir` - The directory where the CSV files will be written.
+/// * `file_paths` - A slice of `PathBuf`s representing the original file paths for each result.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
+pub fn write_custom_comparison_csv(
+    results: &[AnalysisResults],
+    output_dir: &Path,
+    file_paths: &[PathBuf],
+) -> std::io::Result<()> {
+    for (comp_idx, comparison) in results[0].custom_comparisons.iter().enumerate() {
+        let mut wtr = Writer::from_path(
+            output_dir.join(sanitize_filename(&comparison.name) + "_comparison.csv"),
+        )?;
+
+        // Dynamically build headers based on the number of comparison groups
+        let mut headers = vec![
+            "name".to_string(),
+            "file_name".to_string(),
+            "base_size".to_string(),
+        ];
+
+        // LZ stats
+        headers.push("base_lz".to_string());
+        for group_name in &comparison.group_names {
+            headers.push(format!("{}_lz", group_name));
+        }
+
+        // Estimated Size stats
+        headers.push("base_est".to_string());
+        for group_name in &comparison.group_names {
+            headers.push(format!("{}_est", group_name));
+        }
+
+        // Estimated Ratio stats
+        for group_name in &comparison.group_names {
+            headers.push(format!("{}_ratio_est", group_name));
+        }
+
+        // Estimated Diff stats
+        for group_name in &comparison.group_names {
+            headers.push(format!("{}_diff_est", group_name));
+        }
+
+        // Zstd Size stats
+        headers.push("base_zstd".to_string());
+        for group_name in &comparison.group_names {
+            headers.push(format!("{}_zstd", group_name));
+        }
+
+        // Zstd Ratio stats
+        for group_name in &comparison.group_names {
+            headers.push(format!("{}_ratio_zstd", group_name));
+        }
+
+        // Zstd Diff stats
+        for group_name in &comparison.group_names {
+            headers.push(format!("{}_diff_zstd", group_name));
+        }
+
+        // Brotli Size and Ratio stats, only measured when selected.
+        let has_brotli = comparison.baseline_metrics.brotli_size.is_some();
+        if has_brotli {
+            headers.push("base_brotli".to_string());
+            for group_name in &comparison.group_names {
+                headers.push(format!("{}_brotli", group_name));
+            }
+            for group_name in &comparison.group_names {
+                headers.push(format!("{}_ratio_brotli", group_name));
+            }
+        }
+
+        // Deflate Size and Ratio stats, only measured when selected.
+        let has_deflate = comparison.baseline_metrics.deflate_size.is_some();
+        if has_deflate {
+            headers.push("base_deflate".to_string());
+            for group_name in &comparison.group_names {
+                headers.push(format!("{}_deflate", group_name));
+            }
+            for group_name in &comparison.group_names {
+                headers.push(format!("{}_ratio_deflate", group_name));
+            }
+        }
+
+        // Zstd Size and Ratio stats with a dictionary, only measured when one is given.
+        let has_zstd_dict = comparison.baseline_metrics.zstd_dict_size.is_some();
+        if has_zstd_dict {
+            headers.push("base_zstd_dict".to_string());
+            for group_name in &comparison.group_names {
+                headers.push(format!("{}_zstd_dict", group_name));
+            }
+            for group_name in &comparison.group_names {
+                headers.push(format!("{}_ratio_zstd_dict", group_name));
+            }
+        }
+
+        // Reference sizes and the ratios to them, only known when supplied.
+        let has_reference = results.iter().any(|result| {
+            let comparison = &result.custom_comparisons[comp_idx];
+            comparison.baseline_metrics.reference_size.is_some()
+                || comparison
+                    .group_metrics
+                    .iter()
+                    .any(|m| m.reference_size.is_some())
+        });
+        // Compression times, only measured on request.
+        let has_zstd_time = results.iter().any(|result| {
+            let comparison = &result.custom_comparisons[comp_idx];
+            comparison.baseline_metrics.zstd_compress_ns != 0
+                || comparison
+                    .group_metrics
+                    .iter()
+                    .any(|m| m.zstd_compress_ns != 0)
+        });
+        if has_zstd_time {
+            headers.push("base_zstd_ns".to_string());
+            for group_name in &comparison.group_names {
+                headers.push(format!("{group_name}_zstd_ns"));
+            }
+        }
+
+        if has_reference {
+            for suffix in ["reference", "zstd_reference_ratio", "est_reference_ratio"] {
+                headers.push(format!("base_{suffix}"));
+                for group_name in &comparison.group_names {
+                    headers.push(format!("{group_name}_{suffix}"));
+                }
+            }
+        }
+
+        wtr.write_record(&headers)?;
+
+        for (file_idx, result) in results.iter().enumerate() {
+            // Get equivalent comparison for this result.
+            let comparison = &result.custom_comparisons[comp_idx];
+
+            // Write reference, baseline metrics.
+            let mut record = vec![
+                comparison.name.clone(),
+                file_paths[file_idx]
+                    .file_name()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap(),
+                comparison.baseline_metrics.original_size.to_string(),
+            ];
+
+            // Write LZ values
+            record.push(comparison.baseline_metrics.lz_matches.to_string());
+            for group_metrics in comparison.group_metrics.iter() {
+                record.push(group_metrics.lz_matches.to_string());
+            }
+
+            // Write Estimated Size values
+            record.push(comparison.baseline_metrics.estimated_size.to_string());
+            for group_metrics in comparison.group_metrics.iter() {
+                record.push(group_metrics.estimated_size.to_string());
+            }
+
+            // Write Estimated Ratio values
+            for group_metrics in comparison.group_metrics.iter() {
+                record.push(calc_ratio(
+                    group_metrics.estimated_size,
+                    comparison.baseline_metrics.estimated_size,
+                ));
+            }
+
+            // Write Estimated Diff values
+            for difference in &comparison.differences {
+                record.push(difference.estimated_size.to_string());
+            }
+
+            // Write Zstd Size values
+            record.push(comparison.baseline_metrics.zstd_size.to_string());
+            for group_metrics in comparison.group_metrics.iter() {
+                record.push(group_metrics.zstd_size.to_string());
+            }
+
+            // Write Zstd Ratio values
+            for group_metrics in comparison.group_metrics.iter() {
+                record.push(calc_ratio(
+                    group_metrics.zstd_size,
+                    comparison.baseline_metrics.zstd_size,
+                ));
+            }
+
+            // Write Zstd Diff values
+            for difference in &comparison.differences {
+                record.extend([difference.zstd_size.to_string()]);
+            }
+
+            // Write Brotli Size and Ratio values
+            if has_brotli {
+                let base_brotli = comparison.baseline_metrics.brotli_size.unwrap_or_default();
+                record.push(base_brotli.to_string());
+                for group_metrics in comparison.group_metrics.iter() {
+                    record.push(group_metrics.brotli_size.unwrap_or_default().to_string());
+                }
+                for group_metrics in comparison.group_metrics.iter() {
+                    record.push(calc_ratio(
+                        group_metrics.brotli_size.unwrap_or_default(),
+                        base_brotli,
+                    ));
+                }
+            }
+
+            // Write Deflate Size and Ratio values
+            if has_deflate {
+                let base_deflate = comparison.baseline_metrics.deflate_size.unwrap_or_default();
+                record.push(base_deflate.to_string());
+                for group_metrics in comparison.group_metrics.iter() {
+                    record.push(group_metrics.deflate_size.unwrap_or_default().to_string());
+                }
+                for group_metrics in comparison.group_metrics.iter() {
+                    record.push(calc_ratio(
+                        group_metrics.deflate_size.unwrap_or_default(),
+                        base_deflate,
+                    ));
+                }
+            }
+
+            // Write Zstd Size and Ratio values with a dictionary
+            if has_zstd_dict {
+                let base_dict = comparison
+                    .baseline_metrics
+                    .zstd_dict_size
+                    .unwrap_or_default();
+                record.push(base_dict.to_string());
+                for group_metrics in comparison.group_metrics.iter() {
+                    record.push(group_metrics.zstd_dict_size.unwrap_or_default().to_string());
+                }
+                for group_metrics in comparison.group_metrics.iter() {
+                    record.push(calc_ratio(
+                        group_metrics.zstd_dict_size.unwrap_or_default(),
+                        base_dict,
+                    ));
+                }
+            }
+
+            // Write compression times
+            if has_zstd_time {
+                record.push(comparison.baseline_metrics.zstd_compress_ns.to_string());
+                record.extend(
+                    comparison
+                        .group_metrics
+                        .iter()
+                        .map(|m| m.zstd_compress_ns.to_string()),
+                );
+            }
+
+            // Write reference sizes and the ratios to them
+            if has_reference {
+                let all_metrics: Vec<_> = std::iter::once(&comparison.baseline_metrics)
+                    .chain(&comparison.group_metrics)
+                    .collect();
+                record.extend(all_metrics.iter().map(|m| {
+                    m.reference_size
+                        .map(|size| size.to_string())
+                        .unwrap_or_default()
+                }));
+                record.extend(
+                    all_metrics
+                        .iter()
+                        .map(|m| reference_ratio(m.zstd_size, m.reference_size)),
+                );
+                record.extend(
+                    all_metrics
+                        .iter()
+                        .map(|m| reference_ratio(m.estimated_size, m.reference_size)),
+                );
+            }
+
+            wtr.write_record(&record)?;
+        }
+        wtr.flush()?;
+    }
+
+    // Summary of the outliers of each comparison group.
+    let mut wtr = Writer::from_path(output_dir.join(BEST_WORST_FILES_CSV))?;
+    let mut headers = vec!["name", "group"];
+    headers.extend(BEST_WORST_HEADERS);
+    wtr.write_record(&headers)?;
+    for (comp_idx, comparison) in results[0].custom_comparisons.iter().enumerate() {
+        for (group_idx, group_name) in comparison.group_names.iter().enumerate() {
+            if let Some(extremes) = find_custom_zstd_ratio_extremes(results, comp_idx, group_idx) {
+                let mut record = vec![comparison.name.clone(), group_name.clone()];
+                record.extend(best_worst_record(&extremes, file_paths));
+                wtr.write_record(&record)?;
+            }
+        }
+    }
+    wtr.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,274 @@
+//! The per-field CSVs of the values, value transitions, windowed entropy,
+//! run lengths and bits of each field.
+
+use super::{calc_ratio, ordered_field_paths, sanitize_filename};
+use crate::results::analysis_results::AnalysisResults;
+use crate::results::merged_analysis_results::MergedAnalysisResults;
+use crate::schema::Schema;
+use ahash::AHashMap;
+use csv::Writer;
+use std::path::{Path, PathBuf};
+
+/// Writes CSV files containing value statistics for each field.
+///
+/// This function generates a CSV file for each field, listing the unique values
+/// encountered in the merged data, along with their counts and ratios.
+///
+/// If the value counts of a field are truncated ([`FieldMetrics::value_counts_overflowed`]),
+/// only the counted values are listed, and the ratios are relative to them. This is noted
+/// in the `unique_values_truncated` column of the per-field CSVs.
+///
+/// # Arguments
+///
+/// * `results` - The merged `AnalysisResults` object.
+/// * `schema` - The schema used for the analysis; fields are written in schema order.
+/// * `output_dir` - The directory where the CSV files will be written.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
+///
+/// [`FieldMetrics::value_counts_overflowed`]: crate::results::FieldMetrics::value_counts_overflowed
+pub fn write_field_value_stats_csv(
+    results: &MergedAnalysisResults,
+    schema: &Schema,
+    output_dir: &Path,
+) -> std::io::Result<()> {
+    for field_path in ordered_field_paths(schema, &results.per_field) {
+        let mut wtr =
+            Writer::from_path(output_dir.join(sanitize_filename(field_path) + "_value_stats.csv"))?;
+        wtr.write_record(["value", "count", "ratio"])?;
+
+        // Write value counts for each result
+        if let Some(field) = results.per_field.get(field_path) {
+            // Get sorted value counts
+            let value_counts = field.sorted_value_counts();
+
+            // Calculate total count for ratio
+            let total_values: u64 = value_counts.iter().map(|(_, count)| **count).sum();
+
+            // Write sorted values with ratios
+            for (value, count) in value_counts {
+                wtr.write_record(&[
+                    field.format_value(*value),
+                    count.to_string(),
+                    calc_ratio(*count, total_values),
+                ])?;
+            }
+        }
+        wtr.flush()?;
+    }
+    Ok(())
+}
+
+/// Writes CSV files containing the value transitions of each field, for fields where
+/// they were tracked.
+///
+/// Each row is a transition from the previous value of the field to the current value,
+/// along with how often it occurred, and its ratio among all transitions from the same
+/// previous value (i.e. the probability of `current` given `previous`).
+///
+/// # Arguments
+///
+/// * `results` - The merged `AnalysisResults` object.
+/// * `schema` - The schema used for the analysis; fields are written in schema order.
+/// * `output_dir` - The directory where the CSV files will be written.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
+pub fn write_field_value_transitions_csv(
+    results: &MergedAnalysisResults,
+    schema: &Schema,
+    output_dir: &Path,
+) -> std::io::Result<()> {
+    for field_path in ordered_field_paths(schema, &results.per_field) {
+        let field = &results.per_field[field_path];
+        let Some(transitions) = &field.value_transitions else {
+            continue;
+        };
+
+        // Total number of transitions starting from each value.
+        let mut totals: AHashMap<u64, u64> = AHashMap::new();
+        for ((previous, _), count) in transitions {
+            *totals.entry(*previous).or_insert(0) += count;
+        }
+
+        let mut wtr = Writer::from_path(
+            output_dir.join(sanitize_filename(field_path) + "_value_transitions.csv"),
+        )?;
+        wtr.write_record(["previous", "current", "count", "ratio"])?;
+        for ((previous, current), count) in field.sorted_value_transitions() {
+            wtr.write_record(&[
+                field.format_value(*previous),
+                field.format_value(*current),
+                count.to_string(),
+                calc_ratio(*count, totals[previous]),
+            ])?;
+        }
+        wtr.flush()?;
+    }
+    Ok(())
+}
+
+/// Writes CSV files containing the windowed entropy of each field
+/// ([`FieldMetrics::windowed_entropy`]), for fields where it was recorded.
+///
+/// Each row is the entropy of one window of a file, with windows numbered from 0
+/// within each file.
+///
+/// # Arguments
+///
+/// * `results` - A slice of [`AnalysisResults`], one for each analyzed file.
+/// * `schema` - The schema used for the analysis; fields are written in schema order.
+/// * `output_dir` - The directory where the CSV files will be written.
+/// * `file_paths` - A slice of [`PathBuf`]s representing the original file paths for each result.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
+///
+/// [`FieldMetrics::windowed_entropy`]: crate::results::FieldMetrics::windowed_entropy
+pub fn write_field_windowed_entropy_csv(
+    results: &[AnalysisResults],
+    schema: &Schema,
+    output_dir: &Path,
+    file_paths: &[PathBuf],
+) -> std::io::Result<()> {
+    for field_path in ordered_field_paths(schema, &results[0].per_field) {
+        let fields = results
+            .iter()
+            .map(|result| result.per_field.get(field_path));
+        if fields
+            .clone()
+            .all(|field| field.is_none_or(|field| field.windowed_entropy.is_empty()))
+        {
+            continue;
+        }
+
+        let mut wtr = Writer::from_path(
+            output_dir.join(sanitize_filename(field_path) + "_windowed_entropy.csv"),
+        )?;
+        wtr.write_record(["file_name", "window", "entropy"])?;
+        for (field, file_path) in fields.zip(file_paths) {
+            let Some(field) = field else {
+                continue;
+            };
+
+            let file_name = file_path
+                .file_name()
+                .and_then(|os_str| os_str.to_str())
+                .unwrap_or_default();
+            for (window, entropy) in field.windowed_entropy.iter().enumerate() {
+                wtr.write_record(&[
+                    file_name.to_string(),
+                    window.to_string(),
+                    entropy.to_string(),
+                ])?;
+            }
+        }
+        wtr.flush()?;
+    }
+    Ok(())
+}
+
+/// Writes CSV files containing the run-length statistics of each field
+/// ([`FieldMetrics::run_stats`]), for fields where they were calculated.
+///
+/// Each row is the number of runs of identical consecutive values in one file,
+/// and the maximum and mean length of those runs.
+///
+/// # Arguments
+///
+/// * `results` - A slice of [`AnalysisResults`], one for each analyzed file.
+/// * `schema` - The schema used for the analysis; fields are written in schema order.
+/// * `output_dir` - The directory where the CSV files will be written.
+/// * `file_paths` - A slice of [`PathBuf`]s representing the original file paths for each result.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
+///
+/// [`FieldMetrics::run_stats`]: crate::results::FieldMetrics::run_stats
+pub fn write_field_run_stats_csv(
+    results: &[AnalysisResults],
+    schema: &Schema,
+    output_dir: &Path,
+    file_paths: &[PathBuf],
+) -> std::io::Result<()> {
+    for field_path in ordered_field_paths(schema, &results[0].per_field) {
+        let fields = results
+            .iter()
+            .map(|result| result.per_field.get(field_path));
+        if fields
+            .clone()
+            .all(|field| field.is_none_or(|field| field.run_stats.is_none()))
+        {
+            continue;
+        }
+
+        let mut wtr =
+            Writer::from_path(output_dir.join(sanitize_filename(field_path) + "_run_stats.csv"))?;
+        wtr.write_record(["file_name", "runs", "max_run_length", "mean_run_length"])?;
+        for (field, file_path) in fields.zip(file_paths) {
+            let Some(run_stats) = field.and_then(|field| field.run_stats.as_ref()) else {
+                continue;
+            };
+
+            let file_name = file_path
+                .file_name()
+                .and_then(|os_str| os_str.to_str())
+                .unwrap_or_default();
+            wtr.write_record(&[
+                file_name.to_string(),
+                run_stats.runs.to_string(),
+                run_stats.max_run_length.to_string(),
+                run_stats.mean_run_length.to_string(),
+            ])?;
+        }
+        wtr.flush()?;
+    }
+    Ok(())
+}
+
+/// Writes CSV files containing bit-level statistics for each field.
+///
+/// This function generates a CSV file for each field, showing the counts of 0s
+/// and 1s at each bit offset within the field, along with the ratio of 0s to
+/// the total number of bits at that offset.
+///
+/// # Arguments
+///
+/// * `results` - The merged `AnalysisResults` object.
+/// * `schema` - The schema used for the analysis; fields are written in schema order.
+/// * `output_dir` - The directory where the CSV files will be written.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
+pub fn write_field_bit_stats_csv(
+    results: &MergedAnalysisResults,
+    schema: &Schema,
+    output_dir: &Path,
+) -> std::io::Result<()> {
+    for field_path in ordered_field_paths(schema, &results.per_field) {
+        let mut wtr =
+            Writer::from_path(output_dir.join(sanitize_filename(field_path) + "_bit_stats.csv"))?;
+        wtr.write_record(["bit_offset", "zero_count", "one_count", "ratio", "entropy"])?;
+
+        // Write bit stats for each result
+        if let Some(field) = results.per_field.get(field_path) {
+            for (i, stats) in field.bit_counts.iter().enumerate() {
+                wtr.write_record(&[
+                    i.to_string(),
+                    stats.zeros.to_string(),
+                    stats.ones.to_string(),
+                    calc_ratio(stats.zeros, stats.zeros + stats.ones),
+                    stats.entropy().to_string(),
+                ])?;
+            }
+        }
+        wtr.flush()?;
+    }
+    Ok(())
+}
@@ -1,9 +1,27 @@
+use crate::comparison::stats::ZstdRatioExtremes;
 use crate::results::analysis_results::AnalysisResults;
 use crate::results::merged_analysis_results::MergedAnalysisResults;
+use crate::results::FieldMetrics;
+use crate::schema::Schema;
+use ahash::AHashMap;
 use csv::Writer;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+mod custom_comparison;
+mod field_values;
+mod optimization;
+mod split_comparison;
+mod summary;
+pub use custom_comparison::write_custom_comparison_csv;
+pub use field_values::{
+    write_field_bit_stats_csv, write_field_run_stats_csv, write_field_value_stats_csv,
+    write_field_value_transitions_csv, write_field_windowed_entropy_csv,
+};
+pub use optimization::write_optimization_csv;
+pub use split_comparison::write_split_comparison_csv;
+pub use summary::{write_summary_csv, SUMMARY_CSV};
+
 /// Writes all CSVs related to analysis results.
 ///
 /// This function orchestrates the writing of multiple CSV files:
@@ -12,13 +30,20 @@ use std::path::{Path, PathBuf};
 /// - Custom comparison statistics.
 /// - Per-field value statistics.
 /// - Per-field bit statistics.
+/// - Per-field value transitions, if tracked.
+/// - Per-field windowed entropy, if recorded.
+/// - Per-field run-length statistics, if calculated.
+/// - A summary with one row per file ([`SUMMARY_CSV`]), in the `summary` subdirectory.
 ///
 /// # Arguments
 ///
 /// * `results` - A slice of [`AnalysisResults`], one for each analyzed file.
 /// * `merged_results` -  An [`MergedAnalysisResults`] object representing the merged results of all files.
+/// * `schema` - The schema used for the analysis; fields are written in schema order.
 /// * `output_dir` - The directory where the CSV files will be written.
 /// * `file_paths` - A slice of [`PathBuf`]s representing the original file paths for each result.
+/// * `prefix` - Prefix for the names of the created subdirectories, allowing multiple runs to
+///   share the same `output_dir`. Use an empty string for no prefix.
 ///
 /// # Returns
 ///
@@ -26,27 +51,66 @@ use std::path::{Path, PathBuf};
 pub fn write_all_csvs(
     results: &[AnalysisResults],
     merged_results: &MergedAnalysisResults,
+    schema: &Schema,
     output_dir: &Path,
     file_paths: &[PathBuf],
+    prefix: &str,
 ) -> std::io::Result<()> {
     // Create subdirectories for each stat type
-    let field_stats_dir = output_dir.join("field_stats");
-    let split_comparison_dir = output_dir.join("split_comparison");
-    let custom_comparison_dir = output_dir.join("custom_comparison");
-    let value_stats_dir = output_dir.join("value_stats");
-    let bit_stats_dir = output_dir.join("bit_stats");
+    let field_stats_dir = output_dir.join(format!("{prefix}field_stats"));
+    let split_comparison_dir = output_dir.join(format!("{prefix}split_comparison"));
+    let custom_comparison_dir = output_dir.join(format!("{prefix}custom_comparison"));
+    let value_stats_dir = output_dir.join(format!("{prefix}value_stats"));
+    let bit_stats_dir = output_dir.join(format!("{prefix}bit_stats"));
+    let summary_dir = output_dir.join(format!("{prefix}summary"));
 
     fs::create_dir_all(&field_stats_dir)?;
     fs::create_dir_all(&split_comparison_dir)?;
     fs::create_dir_all(&custom_comparison_dir)?;
     fs::create_dir_all(&value_stats_dir)?;
     fs::create_dir_all(&bit_stats_dir)?;
+    fs::create_dir_all(&summary_dir)?;
 
-    write_field_csvs(results, &field_stats_dir, file_paths)?;
+    write_field_csvs(results, schema, &field_stats_dir, file_paths)?;
     write_split_comparison_csv(results, &split_comparison_dir, file_paths)?;
     write_custom_comparison_csv(results, &custom_comparison_dir, file_paths)?;
-    write_field_value_stats_csv(merged_results, &value_stats_dir)?;
-    write_field_bit_stats_csv(merged_results, &bit_stats_dir)?;
+    write_field_value_stats_csv(merged_results, schema, &value_stats_dir)?;
+    write_field_bit_stats_csv(merged_results, schema, &bit_stats_dir)?;
+    write_summary_csv(results, merged_results, &summary_dir, file_paths)?;
+
+    // Only tracked on request.
+    if merged_results
+        .per_field
+        .values()
+        .any(|field| field.value_transitions.is_some())
+    {
+        let transitions_dir = output_dir.join(format!("{prefix}value_transitions"));
+        fs::create_dir_all(&transitions_dir)?;
+        write_field_value_transitions_csv(merged_results, schema, &transitions_dir)?;
+    }
+
+    // Only recorded on request.
+    if results.iter().any(|result| {
+        result
+            .per_field
+            .values()
+            .any(|field| !field.windowed_entropy.is_empty())
+    }) {
+        let windowed_entropy_dir = output_dir.join(format!("{prefix}windowed_entropy"));
+        fs::create_dir_all(&windowed_entropy_dir)?;
+        write_field_windowed_entropy_csv(results, schema, &windowed_entropy_dir, file_paths)?;
+    }
+
+    // Only calculated on request.
+    if merged_results
+        .per_field
+        .values()
+        .any(|field| field.run_stats.is_some())
+    {
+        let run_stats_dir = output_dir.join(format!("{prefix}run_stats"));
+        fs::create_dir_all(&run_stats_dir)?;
+        write_field_run_stats_csv(results, schema, &run_stats_dir, file_paths)?;
+    }
     Ok(())
 }
 
@@ -54,10 +118,16 @@ pub fn write_all_csvs(
 ///
 /// Creates one CSV file per field. Each row in a field's CSV represents the
 /// field's metrics from one of the input files.
+/// The `entropy_coded_size` column is empty unless it was measured
+/// (see [`FieldMetrics::entropy_coded_size`]), and the `reference_size` columns are empty
+/// unless a reference size was supplied (see [`FieldMetrics::reference_size`]).
+/// The `bit_waste` column is empty unless the values of the field were counted
+/// (see [`FieldMetrics::bit_waste`]).
 ///
 /// # Arguments
 ///
 /// * `results` - A slice of [`AnalysisResults`], one for each analyzed file.
+/// * `schema` - The schema used for the analysis; fields are written in schema order.
 /// * `output_dir` - The directory where the CSV files will be written.
 /// * `file_paths` - A slice of [`PathBuf`]s representing the original file paths for each result.
 ///
@@ -66,6 +136,7 @@ pub fn write_all_csvs(
 /// * `std::io::Result<()>` - Ok if successful, otherwise an error.
 pub fn write_field_csvs(
     results: &[AnalysisResults],
+    schema: &Schema,
     output_dir: &Path,
     file_paths: &[PathBuf],
 ) -> std::io::Result<()> {
@@ -81,15 +152,21 @@ pub fn write_field_csvs(
         "zstd_size_pct",
         "original_size_pct",
         "zstd_ratio",
+        "theoretical_min_size",
+        "entropy_coded_size",
+        "reference_size",
+        "zstd_reference_ratio",
+        "estimated_reference_ratio",
         "lenbits",
         "unique_values",
+        "unique_values_truncated",
+        "bit_waste",
         "bit_order",
         "file_name",
     ];
 
     // Get field paths from first result (all results have same fields)
-    let field_paths = results[0].per_field.keys();
-    for field_path in field_paths {
+    for field_path in ordered_field_paths(schema, &results[0].per_field) {
         let mut wtr = Writer::from_path(output_dir.join(sanitize_filename(field_path) + ".csv"))?;
         wtr.write_record(CSV_HEADERS)?;
 
@@ -112,8 +189,24 @@ pub fn write_field_csvs(
                     calc_ratio(field.zstd_size, parent_stats.zstd_size),
                     calc_ratio(field.original_size, parent_stats.original_size),
                     calc_ratio(field.zstd_size, field.original_size),
+                    field.theoretical_min_size().to_string(),
+                    field
+                        .entropy_coded_size
+                        .map(|size| size.to_string())
+                        .unwrap_or_default(),
+                    field
+                        .reference_size
+                        .map(|size| size.to_string())
+                        .unwrap_or_default(),
+                    reference_ratio(field.zstd_size, field.reference_size),
+                    reference_ratio(field.estimated_size, field.reference_size),
                     field.lenbits.to_string(),
                     field.value_counts.len().to_string(),
+                    field.value_counts_overflowed.to_string(),
+                    field
+                        .bit_waste()
+                        .map(|bits| bits.to_string())
+                        .unwrap_or_default(),
                     format!("{:?}", field.bit_order),
                     file_path
                         .file_name()
@@ -129,353 +222,47 @@ pub fn write_field_csvs(
     Ok(())
 }
 
-/// Writes CSV files comparing groups of fields within each file, for split comparisons.
-///
-/// This function generates CSV files that compare two groups of fields
-/// (defined in the schema) within each analyzed file.  It reports on
-/// differences in size, LZ77 matches, estimated size, and Zstd compression.
-///
-/// # Arguments
-///
-/// * `results` - A slice of [`AnalysisResults`], one for each analyzed file.
-/// * `output_dir` - The directory where the CSV files will be written.
-/// * `file_paths` - A slice of `PathBuf`s representing the original file paths for each result.
-///
-/// # Returns
-///
-/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
-pub fn write_split_comparison_csv(
-    results: &[AnalysisResults],
-    output_dir: &Path,
-    file_paths: &[PathBuf],
-) -> std::io::Result<()> {
-    // Add group comparison CSVs
-    const GROUP_HEADERS: &[&str] = &[
-        "name",
-        "file_name",
-        "size",
-        "base lz",
-        "comp lz",
-        "base est",
-        "base zstd",
-        "comp est",
-        "comp zstd",
-        "ratio est",
-        "ratio zstd",
-        "diff est",
-        "diff zstd",
-        "base group lz",
-        "comp group lz",
-        "base group entropy",
-        "comp group entropy",
-        "max comp lz diff",
-        "max comp entropy diff",
-    ];
-
-    for (comp_idx, comparison) in results[0].split_comparisons.iter().enumerate() {
-        let mut wtr = Writer::from_path(
-            output_dir.join(sanitize_filename(&comparison.name) + "_comparison.csv"),
-        )?;
-        wtr.write_record(GROUP_HEADERS)?;
-
-        for (file_idx, result) in results.iter().enumerate() {
-            // Get equivalent comparison for this result.
-            let comparison = &result.split_comparisons[comp_idx];
-            let base_group_lz: Vec<_> = comparison
-                .baseline_comparison_metrics
-                .iter()
-                .map(|m| m.lz_matches.to_string())
-                .collect();
-            let comp_group_lz: Vec<_> = comparison
-                .split_comparison_metrics
-                .iter()
-                .map(|m| m.lz_matches.to_string())
-                .collect();
-            let comp_group_entropy: Vec<_> = comparison
-                .split_comparison_metrics
-                .iter()
-                .map(|m| format!("{:.2}", m.entropy))
-                .collect();
-            let base_group_entropy: Vec<_> = comparison
-                .baseline_comparison_metrics
-                .iter()
-                .map(|m| format!("{:.2}", m.entropy))
-                .collect();
-
-            let group2_lz_values: Vec<u64> = comparison
-                .split_comparison_metrics
-                .iter()
-                .map(|m| m.lz_matches)
-                .collect();
-
-            let max_intra_comp_lz_diff_ratio = if group2_lz_values.len() < 2 {
-                0.0
-            } else {
-                let max = *group2_lz_values.iter().max().unwrap() as f64;
-                let min = *group2_lz_values.iter().min().unwrap() as f64;
-                max / min
-            };
-
-            wtr.write_record(vec![
-                comparison.name.clone(), // name
-                file_paths[file_idx]
-                    .file_name()
-                    .map(|s| s.to_string_lossy().into_owned())
-                    .unwrap(), // file name
-                comparison.group1_metrics.original_size.to_string(), // size
-                comparison.group1_metrics.lz_matches.to_string(), // base lz
-                comparison.group2_metrics.lz_matches.to_string(), // comp lz
-                comparison.group1_metrics.estimated_size.to_string(), // base est
-                comparison.group1_metrics.zstd_size.to_string(), // base zstd
-                comparison.group2_metrics.estimated_size.to_string(), // comp est
-                comparison.group2_metrics.zstd_size.to_string(), // comp zstd
-                calc_ratio(
-                    comparison.group2_metrics.estimated_size,
-                    comparison.group1_metrics.estimated_size,
-                ), // ratio est
-                calc_ratio(
-                    comparison.group2_metrics.zstd_size,
-                    comparison.group1_metrics.zstd_size,
-                ), // ratio zstd
-                comparison.difference.estimated_size.to_string(), // diff est
-                comparison.difference.zstd_size.to_string(), // diff zstd
-                base_group_lz.join("|"),
-                comp_group_lz.join("|"),
-                base_group_entropy.join("|"),
-                comp_group_entropy.join("|"),
-                format!("{:.2}", max_intra_comp_lz_diff_ratio),
-                format!("{:.2}", comparison.split_max_entropy_diff()),
-            ])?;
-
-            wtr.flush()?;
-        }
-    }
-
-    Ok(())
-}
-
-/// Writes CSV files comparing groups of fields within each file, for custom comparisons.
-///
-/// This function is analogous to `write_split_comparison_csv`, but handles
-/// `custom_comparisons` instead.  It includes multiple comparison groups.
-///
-/// # Arguments
-///
-/// * `results` - A slice of [`AnalysisResults`], one for each analyzed file.
-/// * `output_dir` - The directory where the CSV files will be written.
-/// * `file_paths` - A slice of `PathBuf`s representing the original file paths for each result.
-///
-/// # Returns
-///
-/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
-pub fn write_custom_comparison_csv(
-    results: &[AnalysisResults],
-    output_dir: &Path,
-    file_paths: &[PathBuf],
-) -> std::io::Result<()> {
-    for (comp_idx, comparison) in results[0].custom_comparisons.iter().enumerate() {
-        let mut wtr = Writer::from_path(
-            output_dir.join(sanitize_filename(&comparison.name) + "_comparison.csv"),
-        )?;
-
-        // Dynamically build headers based on the number of comparison groups
-        let mut headers = vec![
-            "name".to_string(),
-            "file_name".to_string(),
-            "base_size".to_string(),
-        ];
-
-        // LZ stats
-        headers.push("base_lz".to_string());
-        for group_name in &comparison.group_names {
-            headers.push(format!("{}_lz", group_name));
-        }
-
-        // Estimated Size stats
-        headers.push("base_est".to_string());
-        for group_name in &comparison.group_names {
-            headers.push(format!("{}_est", group_name));
-        }
-
-        // Estimated Ratio stats
-        for group_name in &comparison.group_names {
-            headers.push(format!("{}_ratio_est", group_name));
-        }
-
-        // Estimated Diff stats
-        for group_name in &comparison.group_names {
-            headers.push(format!("{}_diff_est", group_name));
-        }
-
-        // Zstd Size stats
-        headers.push("base_zstd".to_string());
-        for group_name in &comparison.group_names {
-            headers.push(format!("{}_zstd", group_name));
-        }
-
-        // Zstd Ratio stats
-        for group_name in &comparison.group_names {
-            headers.push(format!("{}_ratio_zstd", group_name));
-        }
-
-        // Zstd Diff stats
-        for group_name in &comparison.group_names {
-            headers.push(format!("{}_diff_zstd", group_name));
-        }
-
-        wtr.write_record(&headers)?;
-
-        for (file_idx, result) in results.iter().enumerate() {
-            // Get equivalent comparison for this result.
-            let comparison = &result.custom_comparisons[comp_idx];
-
-            // Write reference, baseline metrics.
-            let mut record = vec![
-                comparison.name.clone(),
-                file_paths[file_idx]
-                    .file_name()
-                    .map(|s| s.to_string_lossy().into_owned())
-                    .unwrap(),
-                comparison.baseline_metrics.original_size.to_string(),
-            ];
-
-            // Write LZ values
-            record.push(comparison.baseline_metrics.lz_matches.to_string());
-            for group_metrics in comparison.group_metrics.iter() {
-                record.push(group_metrics.lz_matches.to_string());
-            }
-
-            // Write Estimated Size values
-            record.push(comparison.baseline_metrics.estimated_size.to_string());
-            for group_metrics in comparison.group_metrics.iter() {
-                record.push(group_metrics.estimated_size.to_string());
-            }
-
-            // Write Estimated Ratio values
-            for group_metrics in comparison.group_metrics.iter() {
-                record.push(calc_ratio(
-                    group_metrics.estimated_size,
-                    comparison.baseline_metrics.estimated_size,
-                ));
-            }
-
-            // Write Estimated Diff values
-            for difference in &comparison.differences {
-                record.push(difference.estimated_size.to_string());
-            }
-
-            // Write Zstd Size values
-            record.push(comparison.baseline_metrics.zstd_size.to_string());
-            for group_metrics in comparison.group_metrics.iter() {
-                record.push(group_metrics.zstd_size.to_string());
-            }
-
-            // Write Zstd Ratio values
-            for group_metrics in comparison.group_metrics.iter() {
-                record.push(calc_ratio(
-                    group_metrics.zstd_size,
-                    comparison.baseline_metrics.zstd_size,
-                ));
-            }
-
-            // Write Zstd Diff values
-            for difference in &comparison.differences {
-                record.extend([difference.zstd_size.to_string()]);
-            }
-
-            wtr.write_record(&record)?;
-        }
-        wtr.flush()?;
-    }
-
-    Ok(())
-}
-
-/// Writes CSV files containing value statistics for each field.
-///
-/// This function generates a CSV file for each field, listing the unique values
-/// encountered in the merged data, along with their counts and ratios.
-///
-/// # Arguments
-///
-/// * `results` - The merged `AnalysisResults` object.
-/// * `output_dir` - The directory where the CSV files will be written.
-///
-/// # Returns
-///
-/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
-pub fn write_field_value_stats_csv(
-    results: &MergedAnalysisResults,
-    output_dir: &Path,
-) -> std::io::Result<()> {
-    // Get field paths from first result
-    let field_paths = results.per_field.keys();
-    for field_path in field_paths {
-        let mut wtr =
-            Writer::from_path(output_dir.join(sanitize_filename(field_path) + "_value_stats.csv"))?;
-        wtr.write_record(["value", "count", "ratio"])?;
-
-        // Write value counts for each result
-        if let Some(field) = results.per_field.get(field_path) {
-            // Get sorted value counts
-            let value_counts = field.sorted_value_counts();
-
-            // Calculate total count for ratio
-            let total_values: u64 = value_counts.iter().map(|(_, count)| **count).sum();
-
-            // Write sorted values with ratios
-            for (value, count) in value_counts {
-                wtr.write_record(&[
-                    value.to_string(),
-                    count.to_string(),
-                    calc_ratio(*count, total_values),
-                ])?;
-            }
-        }
-        wtr.flush()?;
-    }
-    Ok(())
+/// Name of the CSV listing the files with the best and worst zstd ratio of each comparison.
+const BEST_WORST_FILES_CSV: &str = "best_worst_files.csv";
+
+const BEST_WORST_HEADERS: &[&str] = &[
+    "best file",
+    "best ratio zstd",
+    "worst file",
+    "worst ratio zstd",
+];
+
+/// Creates the `best file..worst ratio zstd` columns of a best/worst files CSV row.
+fn best_worst_record(extremes: &ZstdRatioExtremes, file_paths: &[PathBuf]) -> Vec<String> {
+    let file_name = |x: usize| {
+        file_paths
+            .get(x)
+            .and_then(|path| path.file_name())
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+
+    vec![
+        file_name(extremes.best_file_index),
+        format!("{:.3}", extremes.best_ratio),
+        file_name(extremes.worst_file_index),
+        format!("{:.3}", extremes.worst_ratio),
+    ]
 }
 
-/// Writes CSV files containing bit-level statistics for each field.
-///
-/// This function generates a CSV file for each field, showing the counts of 0s
-/// and 1s at each bit offset within the field, along with the ratio of 0s to
-/// the total number of bits at that offset.
-///
-/// # Arguments
-///
-/// * `results` - The merged `AnalysisResults` object.
-/// * `output_dir` - The directory where the CSV files will be written.
-///
-/// # Returns
-///
-/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
-pub fn write_field_bit_stats_csv(
-    results: &MergedAnalysisResults,
-    output_dir: &Path,
-) -> std::io::Result<()> {
-    // Get field paths from first result
-    let field_paths = results.per_field.keys();
-    for field_path in field_paths {
-        let mut wtr =
-            Writer::from_path(output_dir.join(sanitize_filename(field_path) + "_bit_stats.csv"))?;
-        wtr.write_record(["bit_offset", "zero_count", "one_count", "ratio"])?;
-
-        // Write bit stats for each result
-        if let Some(field) = results.per_field.get(field_path) {
-            for (i, stats) in field.bit_counts.iter().enumerate() {
-                wtr.write_record(&[
-                    i.to_string(),
-                    stats.zeros.to_string(),
-                    stats.ones.to_string(),
-                    calc_ratio(stats.zeros, stats.zeros + stats.ones),
-                ])?;
-            }
-        }
-        wtr.flush()?;
-    }
-    Ok(())
+/// Paths of the fields (and groups) present in `per_field`, in schema order.
+///
+/// `per_field` is a hash map, so iterating it directly would produce a different
+/// order on every run.
+pub(crate) fn ordered_field_paths<'a>(
+    schema: &Schema,
+    per_field: &'a AHashMap<String, FieldMetrics>,
+) -> Vec<&'a str> {
+    schema
+        .ordered_field_and_group_paths()
+        .iter()
+        .filter_map(|path| per_field.get_key_value(path).map(|(key, _)| key.as_str()))
+        .collect()
 }
 
 /// Calculates a ratio between two numbers, handling division by zero.
@@ -488,6 +275,11 @@ pub fn write_field_bit_stats_csv(
 /// # Returns
 ///
 /// A string representing the ratio, or "0.0" if the denominator is zero.
+///
+/// # Remarks
+///
+/// Like all numbers in the CSVs, this is formatted by Rust's formatter, which ignores the
+/// system locale; the decimal separator is always `.`.
 pub fn calc_ratio(child: u64, parent: u64) -> String {
     if parent == 0 {
         "0.0".into()
@@ -496,6 +288,13 @@ pub fn calc_ratio(child: u64, parent: u64) -> String {
     }
 }
 
+/// Ratio of `size` to a reference size, or an empty string if there is no reference size.
+fn reference_ratio(size: u64, reference_size: Option<u64>) -> String {
+    reference_size
+        .map(|reference_size| calc_ratio(size, reference_size))
+        .unwrap_or_default()
+}
+
 /// Sanitizes a string to be used as a filename by replacing non-alphanumeric characters with underscores.
 /// # Arguments
 ///
@@ -506,3 +305,162 @@ pub fn calc_ratio(child: u64, parent: u64) -> String {
 fn sanitize_filename(name: &str) -> String {
     name.replace(|c: char| !c.is_alphanumeric(), "_")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_entries, CompressionOptions};
+    use crate::utils::compressor::Compressor;
+
+    const SCHEMA: &str = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    header:
+      type: group
+      fields:
+        mode: 4
+        flags: 4
+    zeta: 8
+    alpha: 8
+    colors:
+      type: group
+      fields:
+        r: 5
+        g: 6
+        b: 5
+"#;
+
+    fn analyze(schema: &Schema) -> AnalysisResults {
+        let entries = (0..64u8).map(|x| [x, x.wrapping_mul(3), x ^ 0x5A, x, x.wrapping_add(7)]);
+        analyze_entries(schema, CompressionOptions::default(), entries)
+    }
+
+    /// Writes the CSVs of a fresh analysis, returning the names (sorted) and contents
+    /// of the per-field CSVs.
+    fn write_csvs(schema: &Schema, output_dir: &Path) -> Vec<(String, String)> {
+        let results = vec![analyze(schema)];
+        let merged = MergedAnalysisResults::from_results(&results).unwrap();
+        write_all_csvs(
+            &results,
+            &merged,
+            schema,
+            output_dir,
+            &[PathBuf::from("file.bin")],
+            "",
+        )
+        .unwrap();
+
+        let mut files = Vec::new();
+        for dir in ["field_stats", "value_stats", "bit_stats"] {
+            let mut entries: Vec<_> = fs::read_dir(output_dir.join(dir))
+                .unwrap()
+                .map(|e| e.unwrap().path())
+                .collect();
+            entries.sort();
+            for path in entries {
+                let name = format!("{}/{}", dir, path.file_name().unwrap().to_string_lossy());
+                files.push((name, fs::read_to_string(&path).unwrap()));
+            }
+        }
+        files
+    }
+
+    #[test]
+    fn field_paths_follow_schema_order() {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let expected = schema.ordered_field_and_group_paths();
+
+        // Each analysis has its own randomly seeded hash map.
+        for _ in 0..2 {
+            let results = analyze(&schema);
+            assert_eq!(ordered_field_paths(&schema, &results.per_field), expected);
+        }
+    }
+
+    #[test]
+    fn numbers_use_dot_separator_regardless_of_locale() {
+        // Locale which uses ',' as its decimal separator.
+        for var in ["LC_ALL", "LC_NUMERIC", "LANG"] {
+            std::env::set_var(var, "de_DE.UTF-8");
+        }
+
+        assert_eq!(calc_ratio(1, 4), "0.25");
+        assert_eq!(calc_ratio(3, 2), "1.5");
+        assert_eq!(calc_ratio(1, 0), "0.0");
+
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let files = write_csvs(&schema, output_dir.path());
+
+        // Every number written must parse back, i.e. no locale specific separators.
+        let mut numbers = 0;
+        for (name, contents) in files {
+            let mut reader = csv::Reader::from_reader(contents.as_bytes());
+            for record in reader.records() {
+                for value in record.unwrap().iter() {
+                    let looks_numeric = value.starts_with(|c: char| c.is_ascii_digit())
+                        && value
+                            .chars()
+                            .all(|c| c.is_ascii_digit() || ".,-e".contains(c));
+                    if looks_numeric {
+                        assert!(value.parse::<f64>().is_ok(), "{}: '{}'", name, value);
+                        numbers += 1;
+                    }
+                }
+            }
+        }
+        assert!(numbers > 0);
+    }
+
+    #[test]
+    fn size_mismatch_is_the_last_split_comparison_column() {
+        let yaml = SCHEMA.to_owned()
+            + r#"
+analysis:
+  split_groups:
+    - { name: swap, group_1: [zeta, alpha], group_2: [alpha, zeta] }
+"#;
+        let schema = Schema::from_yaml(&yaml).unwrap();
+        let entries = (0..64u8).map(|x| [x, x.wrapping_mul(3), x ^ 0x5A, x, x.wrapping_add(7)]);
+        let options =
+            CompressionOptions::default().with_compressor(Compressor::Deflate { level: 6 });
+        let results = vec![analyze_entries(&schema, options, entries)];
+        let output_dir = tempfile::tempdir().unwrap();
+        let paths = [PathBuf::from("file.bin")];
+        write_split_comparison_csv(&results, output_dir.path(), &paths).unwrap();
+        let contents = fs::read_to_string(output_dir.path().join("swap_comparison.csv")).unwrap();
+
+        let mut reader = csv::Reader::from_reader(contents.as_bytes());
+        let headers = reader.headers().unwrap().clone();
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!(headers.iter().next_back(), Some("size mismatch"));
+        assert!(headers.iter().any(|header| header == "ratio deflate"));
+        assert_eq!(record.len(), headers.len());
+        assert_eq!(
+            record.iter().next_back(),
+            Some(
+                results[0].split_comparisons[0]
+                    .size_mismatch_bytes
+                    .to_string()
+                    .as_str()
+            )
+        );
+    }
+
+    #[test]
+    fn csv_output_is_identical_across_runs() {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let base_dir = tempfile::tempdir().unwrap();
+        let first = write_csvs(&schema, &base_dir.path().join("first"));
+        let second = write_csvs(&schema, &base_dir.path().join("second"));
+
+        // One file per field or group, for each per-field stat type.
+        assert_eq!(
+            first.len(),
+            schema.ordered_field_and_group_paths().len() * 3
+        );
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::brute_force::{
+    brute_force_custom::CustomComparisonOptimizationResult,
+    brute_force_split::SplitComparisonOptimizationResult, OptimizationResult,
+};
+use csv::Writer;
+use std::path::Path;
+
+/// Writes the results of a brute force optimization to a CSV file, one row per group.
+///
+/// Columns:
+/// - `comparison_type`: `split` or `custom`.
+/// - `comparison_name`: Name of the comparison.
+/// - `group`: `group_1`/`group_2` for split comparisons; `baseline` or the index of the
+///   comparison group for custom comparisons.
+/// - `lz_match_multiplier`, `entropy_multiplier`: The optimized coefficients.
+/// - `min_error`: The error of the coefficients, see [`OptimizationResult::min_error`].
+/// - `penalized`: Whether the error includes a misprediction penalty,
+///   see [`OptimizationResult::is_penalized`].
+/// - `timed_out`: Whether the search stopped early, see [`OptimizationResult::timed_out`].
+///
+/// # Arguments
+///
+/// * `split_results` - Optimization results for split comparisons.
+/// * `custom_results` - Optimization results for custom comparisons.
+/// * `path` - The path of the CSV file to write.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
+pub fn write_optimization_csv(
+    split_results: &[(String, SplitComparisonOptimizationResult)],
+    custom_results: &[(String, CustomComparisonOptimizationResult)],
+    path: &Path,
+) -> std::io::Result<()> {
+    let mut wtr = Writer::from_path(path)?;
+    wtr.write_record([
+        "comparison_type",
+        "comparison_name",
+        "group",
+        "lz_match_multiplier",
+        "entropy_multiplier",
+        "min_error",
+        "penalized",
+        "timed_out",
+    ])?;
+
+    for (name, result) in split_results {
+        write_optimization_record(&mut wtr, "split", name, "group_1", &result.group_1)?;
+        write_optimization_record(&mut wtr, "split", name, "group_2", &result.group_2)?;
+    }
+
+    for (name, result) in custom_results {
+        write_optimization_record(&mut wtr, "custom", name, "baseline", &result.baseline)?;
+        for (x, comparison) in result.comparisons.iter().enumerate() {
+            write_optimization_record(&mut wtr, "custom", name, &x.to_string(), comparison)?;
+        }
+    }
+
+    wtr.flush()?;
+    Ok(())
+}
+
+fn write_optimization_record(
+    wtr: &mut Writer<std::fs::File>,
+    comparison_type: &str,
+    name: &str,
+    group: &str,
+    result: &OptimizationResult,
+) -> std::io::Result<()> {
+    wtr.write_record([
+        comparison_type,
+        name,
+        group,
+        &result.lz_match_multiplier.to_string(),
+        &result.entropy_multiplier.to_string(),
+        &result.min_error.to_string(),
+        &result.is_penalized().to_string(),
+        &result.timed_out.to_string(),
+    ])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_one_row_per_group() {
+        let result = |min_error| OptimizationResult {
+            lz_match_multiplier: 0.5,
+            entropy_multiplier: 1.25,
+            timed_out: false,
+            min_error,
+        };
+        let split = vec![(
+            "split".to_string(),
+            SplitComparisonOptimizationResult {
+                group_1: result(3.0),
+                group_2: result(f32::MAX as f64),
+            },
+        )];
+        let custom = vec![(
+            "custom".to_string(),
+            CustomComparisonOptimizationResult {
+                baseline: result(1.0),
+                comparisons: vec![result(2.0)].into_boxed_slice(),
+            },
+        )];
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("optimization.csv");
+        write_optimization_csv(&split, &custom, &path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1], "split,split,group_1,0.5,1.25,3,false,false");
+        assert!(lines[2].ends_with(",true,false"));
+        assert_eq!(lines[4], "custom,custom,0,0.5,1.25,2,false,false");
+    }
+}
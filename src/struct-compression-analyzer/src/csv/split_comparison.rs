@@ -0,0 +1,243 @@
+//! The CSVs of the split comparisons of each file.
+
+use super::{best_worst_record, calc_ratio, reference_ratio, sanitize_filename};
+use super::{BEST_WORST_FILES_CSV, BEST_WORST_HEADERS};
+use crate::comparison::stats::find_zstd_ratio_extremes;
+use crate::results::analysis_results::AnalysisResults;
+use csv::Writer;
+use std::path::{Path, PathBuf};
+
+/// Writes CSV files comparing groups of fields within each file, for split comparisons.
+///
+/// This function generates CSV files that compare two groups of fields
+/// (defined in the schema) within each analyzed file.  It reports on
+/// differences in size, LZ77 matches, estimated size, and Zstd compression.
+/// The last column, `size mismatch`, is the [`SplitComparisonResult::size_mismatch_bytes`]
+/// of each file.
+/// A `best_worst_files.csv` lists the files with the best and worst Zstd ratio of each comparison.
+///
+/// # Arguments
+///
+/// * `results` - A slice of [`AnalysisResults`], one for each analyzed file.
+/// * `output_dir` - The directory where the CSV files will be written.
+/// * `file_paths` - A slice of `PathBuf`s representing the original file paths for each result.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
+///
+/// [`SplitComparisonResult::size_mismatch_bytes`]: crate::comparison::split_comparison::SplitComparisonResult::size_mismatch_bytes
+pub fn write_split_comparison_csv(
+    results: &[AnalysisResults],
+    output_dir: &Path,
+    file_paths: &[PathBuf],
+) -> std::io::Result<()> {
+    // Add group comparison CSVs
+    const GROUP_HEADERS: &[&str] = &[
+        "name",
+        "file_name",
+        "size",
+        "base lz",
+        "comp lz",
+        "base est",
+        "base zstd",
+        "comp est",
+        "comp zstd",
+        "ratio est",
+        "ratio zstd",
+        "diff est",
+        "diff zstd",
+        "base group lz",
+        "comp group lz",
+        "base group entropy",
+        "comp group entropy",
+        "max comp lz diff",
+        "max comp entropy diff",
+    ];
+
+    for (comp_idx, comparison) in results[0].split_comparisons.iter().enumerate() {
+        let mut wtr = Writer::from_path(
+            output_dir.join(sanitize_filename(&comparison.name) + "_comparison.csv"),
+        )?;
+        // Brotli sizes are only measured when selected, see `CompressionOptions::compressor`.
+        let has_brotli = comparison.group1_metrics.brotli_size.is_some();
+        let mut headers = GROUP_HEADERS.to_vec();
+        if has_brotli {
+            headers.extend(["base brotli", "comp brotli", "ratio brotli"]);
+        }
+        let has_deflate = comparison.group1_metrics.deflate_size.is_some();
+        if has_deflate {
+            headers.extend(["base deflate", "comp deflate", "ratio deflate"]);
+        }
+        // Likewise for sizes with a zstd dictionary, see `CompressionOptions::zstd_dictionary`.
+        let has_zstd_dict = comparison.group1_metrics.zstd_dict_size.is_some();
+        if has_zstd_dict {
+            headers.extend(["base zstd dict", "comp zstd dict", "ratio zstd dict"]);
+        }
+        // Reference sizes are only known when supplied, and may be missing for some files.
+        let has_reference = results.iter().any(|result| {
+            let comparison = &result.split_comparisons[comp_idx];
+            comparison.group1_metrics.reference_size.is_some()
+                || comparison.group2_metrics.reference_size.is_some()
+        });
+        // Compression times are only measured on request.
+        let has_zstd_time = results.iter().any(|result| {
+            let comparison = &result.split_comparisons[comp_idx];
+            comparison.group1_metrics.zstd_compress_ns != 0
+                || comparison.group2_metrics.zstd_compress_ns != 0
+        });
+        if has_zstd_time {
+            headers.extend(["base zstd ns", "comp zstd ns"]);
+        }
+        if has_reference {
+            headers.extend([
+                "base reference",
+                "comp reference",
+                "base zstd/reference",
+                "comp zstd/reference",
+                "base est/reference",
+                "comp est/reference",
+            ]);
+        }
+        headers.push("size mismatch");
+        wtr.write_record(&headers)?;
+
+        for (file_idx, result) in results.iter().enumerate() {
+            // Get equivalent comparison for this result.
+            let comparison = &result.split_comparisons[comp_idx];
+            let base_group_lz: Vec<_> = comparison
+                .baseline_comparison_metrics
+                .iter()
+                .map(|m| m.lz_matches.to_string())
+                .collect();
+            let comp_group_lz: Vec<_> = comparison
+                .split_comparison_metrics
+                .iter()
+                .map(|m| m.lz_matches.to_string())
+                .collect();
+            let comp_group_entropy: Vec<_> = comparison
+                .split_comparison_metrics
+                .iter()
+                .map(|m| format!("{:.2}", m.entropy))
+                .collect();
+            let base_group_entropy: Vec<_> = comparison
+                .baseline_comparison_metrics
+                .iter()
+                .map(|m| format!("{:.2}", m.entropy))
+                .collect();
+
+            let group2_lz_values: Vec<u64> = comparison
+                .split_comparison_metrics
+                .iter()
+                .map(|m| m.lz_matches)
+                .collect();
+
+            let max_intra_comp_lz_diff_ratio = if group2_lz_values.len() < 2 {
+                0.0
+            } else {
+                let max = *group2_lz_values.iter().max().unwrap() as f64;
+                let min = *group2_lz_values.iter().min().unwrap() as f64;
+                max / min
+            };
+
+            let mut record = vec![
+                comparison.name.clone(), // name
+                file_paths[file_idx]
+                    .file_name()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap(), // file name
+                comparison.group1_metrics.original_size.to_string(), // size
+                comparison.group1_metrics.lz_matches.to_string(), // base lz
+                comparison.group2_metrics.lz_matches.to_string(), // comp lz
+                comparison.group1_metrics.estimated_size.to_string(), // base est
+                comparison.group1_metrics.zstd_size.to_string(), // base zstd
+                comparison.group2_metrics.estimated_size.to_string(), // comp est
+                comparison.group2_metrics.zstd_size.to_string(), // comp zstd
+                calc_ratio(
+                    comparison.group2_metrics.estimated_size,
+                    comparison.group1_metrics.estimated_size,
+                ), // ratio est
+                calc_ratio(
+                    comparison.group2_metrics.zstd_size,
+                    comparison.group1_metrics.zstd_size,
+                ), // ratio zstd
+                comparison.difference.estimated_size.to_string(), // diff est
+                comparison.difference.zstd_size.to_string(), // diff zstd
+                base_group_lz.join("|"),
+                comp_group_lz.join("|"),
+                base_group_entropy.join("|"),
+                comp_group_entropy.join("|"),
+                format!("{:.2}", max_intra_comp_lz_diff_ratio),
+                format!("{:.2}", comparison.split_max_entropy_diff()),
+            ];
+            if has_brotli {
+                let base_brotli = comparison.group1_metrics.brotli_size.unwrap_or_default();
+                let comp_brotli = comparison.group2_metrics.brotli_size.unwrap_or_default();
+                record.extend([
+                    base_brotli.to_string(),              // base brotli
+                    comp_brotli.to_string(),              // comp brotli
+                    calc_ratio(comp_brotli, base_brotli), // ratio brotli
+                ]);
+            }
+            if has_deflate {
+                let base_deflate = comparison.group1_metrics.deflate_size.unwrap_or_default();
+                let comp_deflate = comparison.group2_metrics.deflate_size.unwrap_or_default();
+                record.extend([
+                    base_deflate.to_string(),               // base deflate
+                    comp_deflate.to_string(),               // comp deflate
+                    calc_ratio(comp_deflate, base_deflate), // ratio deflate
+                ]);
+            }
+            if has_zstd_dict {
+                let base_dict = comparison.group1_metrics.zstd_dict_size.unwrap_or_default();
+                let comp_dict = comparison.group2_metrics.zstd_dict_size.unwrap_or_default();
+                record.extend([
+                    base_dict.to_string(),            // base zstd dict
+                    comp_dict.to_string(),            // comp zstd dict
+                    calc_ratio(comp_dict, base_dict), // ratio zstd dict
+                ]);
+            }
+            if has_zstd_time {
+                record.extend([
+                    comparison.group1_metrics.zstd_compress_ns.to_string(), // base zstd ns
+                    comparison.group2_metrics.zstd_compress_ns.to_string(), // comp zstd ns
+                ]);
+            }
+            if has_reference {
+                let (base, comp) = (&comparison.group1_metrics, &comparison.group2_metrics);
+                record.extend([
+                    base.reference_size
+                        .map(|s| s.to_string())
+                        .unwrap_or_default(),
+                    comp.reference_size
+                        .map(|s| s.to_string())
+                        .unwrap_or_default(),
+                    reference_ratio(base.zstd_size, base.reference_size),
+                    reference_ratio(comp.zstd_size, comp.reference_size),
+                    reference_ratio(base.estimated_size, base.reference_size),
+                    reference_ratio(comp.estimated_size, comp.reference_size),
+                ]);
+            }
+            record.push(comparison.size_mismatch_bytes.to_string()); // size mismatch
+            wtr.write_record(&record)?;
+
+            wtr.flush()?;
+        }
+    }
+
+    // Summary of the outliers of each comparison.
+    let mut wtr = Writer::from_path(output_dir.join(BEST_WORST_FILES_CSV))?;
+    let mut headers = vec!["name"];
+    headers.extend(BEST_WORST_HEADERS);
+    wtr.write_record(&headers)?;
+    for (comp_idx, comparison) in results[0].split_comparisons.iter().enumerate() {
+        if let Some(extremes) = find_zstd_ratio_extremes(results, comp_idx) {
+            let mut record = vec![comparison.name.clone()];
+            record.extend(best_worst_record(&extremes, file_paths));
+            wtr.write_record(&record)?;
+        }
+    }
+    wtr.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use super::calc_ratio;
+use crate::comparison::{compare_groups::GroupComparisonResult, GroupComparisonMetrics};
+use crate::results::analysis_results::AnalysisResults;
+use crate::results::merged_analysis_results::MergedAnalysisResults;
+use csv::Writer;
+use std::path::{Path, PathBuf};
+
+/// Name of the CSV written by [`write_summary_csv`].
+pub const SUMMARY_CSV: &str = "summary.csv";
+
+/// Writes a single wide CSV with one row per file, for quick pivot table analysis.
+///
+/// Columns:
+/// - `file_name`, `original_size`, `entropy`, `lz_matches`, `zstd_size`, `zstd_ratio`:
+///   The file level metrics of each file.
+/// - `{comparison}_ratio_est`, `{comparison}_ratio_zstd`: The estimated and zstd ratio of
+///   group 2 to group 1 of each split comparison.
+/// - `{comparison}_{group}_ratio_est`, `{comparison}_{group}_ratio_zstd`: The estimated and zstd
+///   ratio of each group to the baseline of each custom comparison.
+///
+/// The comparison columns follow the comparisons of the merged results, in schema order.
+/// Files without one of the comparisons have blanks in its columns.
+///
+/// # Arguments
+///
+/// * `results` - A slice of [`AnalysisResults`], one for each analyzed file.
+/// * `merged_results` - The merged results of all files, which determine the comparison columns.
+/// * `output_dir` - The directory where `summary.csv` will be written.
+/// * `file_paths` - A slice of [`PathBuf`]s representing the original file paths for each result.
+///
+/// # Returns
+///
+/// * `std::io::Result<()>` - Ok if successful, otherwise an error.
+pub fn write_summary_csv(
+    results: &[AnalysisResults],
+    merged_results: &MergedAnalysisResults,
+    output_dir: &Path,
+    file_paths: &[PathBuf],
+) -> std::io::Result<()> {
+    let mut headers = [
+        "file_name",
+        "original_size",
+        "entropy",
+        "lz_matches",
+        "zstd_size",
+        "zstd_ratio",
+    ]
+    .map(String::from)
+    .to_vec();
+    for comparison in &merged_results.split_comparisons {
+        headers.push(format!("{}_ratio_est", comparison.name));
+        headers.push(format!("{}_ratio_zstd", comparison.name));
+    }
+    for comparison in &merged_results.custom_comparisons {
+        for group_name in &comparison.group_names {
+            headers.push(format!("{}_{}_ratio_est", comparison.name, group_name));
+            headers.push(format!("{}_{}_ratio_zstd", comparison.name, group_name));
+        }
+    }
+
+    let mut wtr = Writer::from_path(output_dir.join(SUMMARY_CSV))?;
+    wtr.write_record(&headers)?;
+    let mut record = Vec::with_capacity(headers.len());
+    for (result, file_path) in results.iter().zip(file_paths) {
+        record.clear();
+        record.extend([
+            file_path
+                .file_name()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            result.original_size.to_string(),
+            result.file_entropy.to_string(),
+            result.file_lz_matches.to_string(),
+            result.zstd_file_size.to_string(),
+            calc_ratio(result.zstd_file_size, result.original_size),
+        ]);
+
+        for merged in &merged_results.split_comparisons {
+            let comparison = result
+                .split_comparisons
+                .iter()
+                .find(|comparison| comparison.name == merged.name);
+            push_ratios(
+                &mut record,
+                comparison.map(|c| (&c.group1_metrics, &c.group2_metrics)),
+            );
+        }
+
+        for merged in &merged_results.custom_comparisons {
+            let comparison = result
+                .custom_comparisons
+                .iter()
+                .find(|comparison| comparison.name == merged.name);
+            for group_name in &merged.group_names {
+                push_ratios(
+                    &mut record,
+                    comparison.and_then(|c| custom_group_metrics(c, group_name)),
+                );
+            }
+        }
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// The baseline metrics and the metrics of the named group of a custom comparison.
+fn custom_group_metrics<'a>(
+    comparison: &'a GroupComparisonResult,
+    group_name: &str,
+) -> Option<(&'a GroupComparisonMetrics, &'a GroupComparisonMetrics)> {
+    let index = comparison
+        .group_names
+        .iter()
+        .position(|name| name == group_name)?;
+    let metrics = comparison.group_metrics.get(index)?;
+    Some((&comparison.baseline_metrics, metrics))
+}
+
+/// Pushes the estimated and zstd ratio of `(base, comp)`, or blanks if there are no metrics.
+fn push_ratios(
+    record: &mut Vec<String>,
+    metrics: Option<(&GroupComparisonMetrics, &GroupComparisonMetrics)>,
+) {
+    match metrics {
+        Some((base, comp)) => record.extend([
+            calc_ratio(comp.estimated_size, base.estimated_size),
+            calc_ratio(comp.zstd_size, base.zstd_size),
+        ]),
+        None => record.extend([String::new(), String::new()]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_entries, CompressionOptions};
+    use crate::schema::Schema;
+    use std::fs;
+
+    const SCHEMA: &str = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    a: 8
+    b: 8
+analysis:
+  split_groups:
+    - { name: swap, group_1: [a, b], group_2: [b, a] }
+  compare_groups:
+    - name: order
+      baseline: [{ type: array, field: a }, { type: array, field: b }]
+      comparisons:
+        reversed: [{ type: array, field: b }, { type: array, field: a }]
+"#;
+
+    fn analyze(schema: &Schema, seed: u8) -> AnalysisResults {
+        let entries = (0..64u8).map(|x| [x.wrapping_mul(seed), x % 5]);
+        analyze_entries(schema, CompressionOptions::default(), entries)
+    }
+
+    #[test]
+    fn writes_one_row_per_file() {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let mut results = vec![analyze(&schema, 3), analyze(&schema, 7)];
+        let merged = MergedAnalysisResults::from_results(&results).unwrap();
+        // Defensive: a file missing a comparison gets blanks.
+        results[1].custom_comparisons.clear();
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let paths = [PathBuf::from("dir/first.bin"), PathBuf::from("second.bin")];
+        write_summary_csv(&results, &merged, output_dir.path(), &paths).unwrap();
+        let contents = fs::read_to_string(output_dir.path().join(SUMMARY_CSV)).unwrap();
+
+        let mut lines = contents.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "file_name,original_size,entropy,lz_matches,zstd_size,zstd_ratio,\
+             swap_ratio_est,swap_ratio_zstd,order_reversed_ratio_est,order_reversed_ratio_zstd"
+        );
+        let first: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(first[..2], ["first.bin", "128"]);
+        assert!(first[6..].iter().all(|ratio| ratio.parse::<f64>().is_ok()));
+        let second: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(second.len(), first.len());
+        assert_eq!(second[0], "second.bin");
+        assert!(!second[7].is_empty());
+        assert_eq!(second[8..], ["", ""]);
+        assert!(lines.next().is_none());
+    }
+}
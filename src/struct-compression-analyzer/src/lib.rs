@@ -8,4 +8,6 @@ pub mod offset_evaluator;
 pub mod plot;
 pub mod results;
 pub mod schema;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 pub mod utils;
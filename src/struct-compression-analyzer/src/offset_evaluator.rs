@@ -22,6 +22,12 @@
 //!
 //! - [`try_evaluate_file_offset()`]: Find offset in file
 //! - [`try_evaluate_offset()`]: Find offset in byte slice
+//! - [`try_evaluate_file_offsets()`]: Find all matching regions in file
+//! - [`try_evaluate_offsets()`]: Find all matching regions in byte slice
+//!
+//! The single offset functions return the first match, in schema order. Container formats
+//! embedding several blobs can list one [ConditionalOffset] per blob (with an optional
+//! `length`), and use the multiple offset functions to get the [`OffsetRegion`] of each.
 //!
 //! ## Example Usage
 //!
@@ -29,7 +35,7 @@
 //!
 //! ```rust
 //! use struct_compression_analyzer::offset_evaluator::try_evaluate_offset;
-//! use struct_compression_analyzer::schema::{BitOrder, Condition, ConditionalOffset};
+//! use struct_compression_analyzer::schema::{BitOrder, Condition, ConditionalOffset, Endian};
 //!
 //! let mut sample_data = vec![0u8; 0x80 + 4];
 //! // Set DDS magic
@@ -40,6 +46,7 @@
 //! // DDS with DX10 header (BC7, BC6H etc.)
 //! let conditions = vec![ConditionalOffset {
 //!     offset: 0x94, // Offset to jump to (DX10 block data)
+//!     length: None, // Until the end of the data
 //!     conditions: vec![
 //!         Condition {
 //!             byte_offset: 0, // File Magic
@@ -47,6 +54,7 @@
 //!             bits: 32,
 //!             value: 0x44445320, // DDS magic
 //!             bit_order: BitOrder::Msb,
+//!             endian: Endian::Big,
 //!         },
 //!         Condition {
 //!             byte_offset: 0x54,
@@ -54,6 +62,7 @@
 //!             bits: 32,
 //!             value: 0x44583130, // 'DX10' fourCC code
 //!             bit_order: BitOrder::Msb,
+//!             endian: Endian::Big,
 //!         },
 //!     ],
 //! }];
@@ -62,20 +71,46 @@
 //! assert_eq!(result, Some(0x94));
 //! ```
 
-use crate::{
-    schema::{BitOrder, Condition, ConditionalOffset},
-    utils::analyze_utils::reverse_bits,
-};
+use crate::schema::{Condition, ConditionalOffset};
 use bitstream_io::{BigEndian, BitRead, BitReader};
 use std::{
     fs::File,
     io::{self, Cursor, Read, Seek, SeekFrom},
 };
 
+/// A region of the data whose [ConditionalOffset] matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OffsetRegion {
+    /// Offset of the region, from the start of the data.
+    pub offset: u64,
+    /// Length of the region, or [`None`] if it extends to the end of the data.
+    pub length: Option<u64>,
+}
+
+/// Returns the offset of the first [ConditionalOffset] whose conditions match the file.
 pub fn try_evaluate_file_offset(
     conditional_offsets: &[ConditionalOffset],
     file: &mut File,
 ) -> io::Result<Option<u64>> {
+    let data = read_condition_bytes(conditional_offsets, file)?;
+    Ok(try_evaluate_offset(conditional_offsets, &data))
+}
+
+/// Returns the regions of all [ConditionalOffset]s whose conditions match the file,
+/// in schema order.
+pub fn try_evaluate_file_offsets(
+    conditional_offsets: &[ConditionalOffset],
+    file: &mut File,
+) -> io::Result<Vec<OffsetRegion>> {
+    let data = read_condition_bytes(conditional_offsets, file)?;
+    Ok(try_evaluate_offsets(conditional_offsets, &data))
+}
+
+/// Reads the start of the file, up to the last byte checked by any of the conditions.
+fn read_condition_bytes(
+    conditional_offsets: &[ConditionalOffset],
+    file: &mut File,
+) -> io::Result<Box<[u8]>> {
     // Calculate maximum needed read length from all conditions
     let max_read = conditional_offsets
         .iter()
@@ -88,17 +123,36 @@ pub fn try_evaluate_file_offset(
     file.seek(SeekFrom::Start(0))?;
     let mut data = unsafe { Box::new_uninit_slice(max_read as usize).assume_init() };
     file.read_exact(&mut data)?;
-
-    Ok(try_evaluate_offset(conditional_offsets, &data))
+    Ok(data)
 }
 
+/// Returns the offset of the first [ConditionalOffset] whose conditions match the data.
 pub fn try_evaluate_offset(conditional_offsets: &[ConditionalOffset], data: &[u8]) -> Option<u64> {
-    for offset_def in conditional_offsets {
-        if matches_all_conditions(offset_def, data) {
-            return Some(offset_def.offset);
-        }
-    }
-    None
+    matching_regions(conditional_offsets, data)
+        .next()
+        .map(|region| region.offset)
+}
+
+/// Returns the regions of all [ConditionalOffset]s whose conditions match the data,
+/// in schema order.
+pub fn try_evaluate_offsets(
+    conditional_offsets: &[ConditionalOffset],
+    data: &[u8],
+) -> Vec<OffsetRegion> {
+    matching_regions(conditional_offsets, data).collect()
+}
+
+fn matching_regions<'a>(
+    conditional_offsets: &'a [ConditionalOffset],
+    data: &'a [u8],
+) -> impl Iterator<Item = OffsetRegion> + 'a {
+    conditional_offsets
+        .iter()
+        .filter(|offset_def| matches_all_conditions(offset_def, data))
+        .map(|offset_def| OffsetRegion {
+            offset: offset_def.offset,
+            length: offset_def.length,
+        })
 }
 
 fn matches_all_conditions(offset_def: &ConditionalOffset, data: &[u8]) -> bool {
@@ -116,14 +170,8 @@ fn check_condition(condition: &Condition, data: &[u8]) -> bool {
         return false;
     }
 
-    let comp_value = match condition.bit_order {
-        BitOrder::Default => condition.value,
-        BitOrder::Msb => condition.value,
-        BitOrder::Lsb => reverse_bits(condition.bits as u32, condition.value),
-    };
-
     match reader.read_var::<u64>(condition.bits as u32) {
-        Ok(extracted) => extracted == comp_value,
+        Ok(extracted) => condition.matches(extracted),
         Err(_) => false,
     }
 }
@@ -131,11 +179,12 @@ fn check_condition(condition: &Condition, data: &[u8]) -> bool {
 #[cfg(test)]
 mod byte_tests {
     use super::*;
-    use crate::schema::{BitOrder, Condition, ConditionalOffset};
+    use crate::schema::{BitOrder, Condition, ConditionalOffset, Endian};
 
     fn create_bc7_conditions() -> Vec<ConditionalOffset> {
         vec![ConditionalOffset {
             offset: 0x94,
+            length: None,
             conditions: vec![
                 Condition {
                     byte_offset: 0x00,
@@ -143,6 +192,7 @@ mod byte_tests {
                     bits: 32,
                     value: 0x44445320,
                     bit_order: BitOrder::Msb,
+                    endian: Endian::Big,
                 },
                 Condition {
                     byte_offset: 0x54,
@@ -150,6 +200,7 @@ mod byte_tests {
                     bits: 32,
                     value: 0x44583130,
                     bit_order: BitOrder::Msb,
+                    endian: Endian::Big,
                 },
             ],
         }]
@@ -211,12 +262,46 @@ mod byte_tests {
         data[0x54..0x58].copy_from_slice(&[0x44, 0x58, 0x31, 0x30]);
         assert_eq!(try_evaluate_offset(&conditions, &data), Some(0x94));
     }
+
+    #[test]
+    fn evaluates_all_matching_regions() {
+        let yaml_data = r#"
+            - offset: 0x10
+              length: 0x20
+              conditions:
+                - { byte_offset: 0, bit_offset: 0, bits: 8, value: 0xAB }
+            - offset: 0x40
+              conditions:
+                - { byte_offset: 1, bit_offset: 0, bits: 8, value: 0xFF }
+            - offset: 0x30
+              conditions:
+                - { byte_offset: 1, bit_offset: 0, bits: 8, value: 0xCD }
+        "#;
+
+        let conditions: Vec<ConditionalOffset> = serde_yaml::from_str(yaml_data).unwrap();
+        let data = [0xAB, 0xCD];
+        assert_eq!(
+            try_evaluate_offsets(&conditions, &data),
+            [
+                OffsetRegion {
+                    offset: 0x10,
+                    length: Some(0x20)
+                },
+                OffsetRegion {
+                    offset: 0x30,
+                    length: None
+                },
+            ]
+        );
+        assert_eq!(try_evaluate_offset(&conditions, &data), Some(0x10));
+        assert!(try_evaluate_offsets(&conditions, &[0, 0]).is_empty());
+    }
 }
 
 #[cfg(test)]
 mod bit_tests {
     use super::*;
-    use crate::schema::{BitOrder, Condition, ConditionalOffset};
+    use crate::schema::{BitOrder, Condition, ConditionalOffset, Endian};
 
     // New bit-oriented tests will go here
 
@@ -224,6 +309,7 @@ mod bit_tests {
     fn validates_bitstream_header() {
         let conditions = [ConditionalOffset {
             offset: 0,
+            length: None,
             conditions: vec![
                 Condition {
                     byte_offset: 0,
@@ -231,6 +317,7 @@ mod bit_tests {
                     bits: 4,
                     value: 0b1110,
                     bit_order: BitOrder::Msb,
+                    endian: Endian::Big,
                 },
                 Condition {
                     byte_offset: 1,
@@ -238,6 +325,7 @@ mod bit_tests {
                     bits: 8,
                     value: 0xC0,
                     bit_order: BitOrder::Msb,
+                    endian: Endian::Big,
                 },
             ],
         }];
@@ -255,7 +343,7 @@ mod bit_tests {
 #[cfg(test)]
 mod endian_tests {
     use super::*;
-    use crate::schema::{BitOrder, Condition};
+    use crate::schema::{BitOrder, Condition, Endian};
 
     #[test]
     fn big_endian() {
@@ -266,6 +354,7 @@ mod endian_tests {
             bits: 4,
             value: 0b0011,
             bit_order: BitOrder::Msb,
+            endian: Endian::Big,
         };
         assert!(check_condition(&condition, &data));
     }
@@ -279,6 +368,44 @@ mod endian_tests {
             bits: 4,
             value: 0b1100,
             bit_order: BitOrder::Lsb,
+            endian: Endian::Big,
+        };
+        assert!(check_condition(&condition, &data));
+    }
+
+    #[test]
+    fn little_endian_bytes() {
+        // DDS magic, as stored in the file.
+        let data = [0x44, 0x44, 0x53, 0x20];
+        let little = Condition {
+            byte_offset: 0,
+            bit_offset: 0,
+            bits: 32,
+            value: 0x20534444, // The magic read as a little endian u32
+            bit_order: BitOrder::Msb,
+            endian: Endian::Little,
+        };
+        assert!(check_condition(&little, &data));
+
+        let big = Condition {
+            endian: Endian::Big,
+            ..little.clone()
+        };
+        assert!(!check_condition(&big, &data));
+        assert!(check_condition(
+            &Condition {
+                value: 0x44445320,
+                ..big
+            },
+            &data
+        ));
+
+        // Partial values swap only the bytes they span.
+        let condition = Condition {
+            byte_offset: 1,
+            bits: 16,
+            value: 0x5344,
+            ..little
         };
         assert!(check_condition(&condition, &data));
     }
@@ -0,0 +1,244 @@
+//! Box plots of the distribution of zstd ratios across files.
+//!
+//! The line plots draw one point per file, which gets unreadable with hundreds of files.
+//! [`generate_ratio_distribution_plot`] instead summarizes each comparison with a box and
+//! whiskers, using the [`Stats`] of [`calculate_zstd_ratio_stats`] and
+//! [`calculate_custom_zstd_ratio_stats`]:
+//!
+//! - The box spans the first to third quartile, with a line at the median.
+//! - The whiskers extend to the lowest and highest ratio within 1.5 IQR of the box (Tukey).
+//! - Ratios beyond the whiskers (outliers) are drawn as individual points.
+
+use super::render::{create_drawing_area, scaled};
+use super::{PlotFormat, PlotOptions};
+use crate::comparison::stats::{
+    calculate_custom_zstd_ratio_stats, calculate_zstd_ratio_stats, custom_zstd_ratios,
+    split_zstd_ratios, Stats,
+};
+use crate::results::analysis_results::AnalysisResults;
+use plotters::{coord::Shift, prelude::*};
+use std::path::Path;
+
+/// How far the whiskers may extend beyond the box, in multiples of the IQR.
+const WHISKER_IQR_MULTIPLIER: f64 = 1.5;
+
+/// Half of the width of a box, in units of the x axis (1 per box).
+const BOX_HALF_WIDTH: f64 = 0.3;
+
+/// The box and whiskers of one comparison (group).
+#[derive(Debug, Clone)]
+struct RatioBox {
+    /// Name of the comparison, and group for custom comparisons.
+    label: String,
+    /// Statistics of the zstd ratios.
+    stats: Stats,
+    /// Lowest ratio within [`WHISKER_IQR_MULTIPLIER`] IQR below the first quartile.
+    whisker_low: f64,
+    /// Highest ratio within [`WHISKER_IQR_MULTIPLIER`] IQR above the third quartile.
+    whisker_high: f64,
+    /// Ratios beyond the whiskers.
+    outliers: Vec<f64>,
+}
+
+impl RatioBox {
+    fn new(label: String, stats: Stats, ratios: impl Iterator<Item = f64>) -> Self {
+        let low_fence = stats.q1 - WHISKER_IQR_MULTIPLIER * stats.iqr;
+        let high_fence = stats.q3 + WHISKER_IQR_MULTIPLIER * stats.iqr;
+        let mut this = Self {
+            label,
+            stats,
+            whisker_low: stats.q1,
+            whisker_high: stats.q3,
+            outliers: Vec::new(),
+        };
+
+        for ratio in ratios {
+            if ratio < low_fence || ratio > high_fence {
+                this.outliers.push(ratio);
+            } else {
+                this.whisker_low = this.whisker_low.min(ratio);
+                this.whisker_high = this.whisker_high.max(ratio);
+            }
+        }
+        this
+    }
+}
+
+/// Generates a box plot of the zstd ratios of every comparison across all files.
+///
+/// There is one box per split comparison, then one per group of each custom comparison.
+/// The box spans the first to third quartile, with a line at the median; the whiskers
+/// extend to the lowest and highest ratio within 1.5 IQR of the box, and the ratios
+/// beyond them (outliers) are drawn as points.
+///
+/// # Arguments
+///
+/// * `results` - A slice of [`AnalysisResults`], one for each analyzed file.
+/// * `output_path` - The path where the plot file will be written.
+/// * `options` - The scale, image format and y axis range of the plot.
+///
+/// # Returns
+///
+/// * `Result<(), Box<dyn std::error::Error>>` - Ok if successful, otherwise a boxed [`std::error::Error`].
+pub fn generate_ratio_distribution_plot(
+    results: &[AnalysisResults],
+    output_path: &Path,
+    options: &PlotOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let boxes = make_ratio_boxes(results);
+    if boxes.is_empty() {
+        return Ok(()); // No data to plot
+    }
+
+    // Each box gets a constant amount of space, with room for its label.
+    let width = scaled((boxes.len() as f64 * 320.0).max(640.0), options.scale);
+    let size = (width, scaled(1440.0, options.scale));
+    match options.format {
+        PlotFormat::Png => draw_distribution_plot(
+            create_drawing_area(BitMapBackend::new(output_path, size))?,
+            &boxes,
+            options,
+        ),
+        PlotFormat::Svg => draw_distribution_plot(
+            create_drawing_area(SVGBackend::new(output_path, size))?,
+            &boxes,
+            options,
+        ),
+    }
+}
+
+/// The boxes of all split comparisons, then all groups of all custom comparisons.
+fn make_ratio_boxes(results: &[AnalysisResults]) -> Vec<RatioBox> {
+    let Some(first) = results.first() else {
+        return Vec::new();
+    };
+
+    let mut boxes = Vec::new();
+    for (x, comparison) in first.split_comparisons.iter().enumerate() {
+        if let Some(stats) = calculate_zstd_ratio_stats(results, x) {
+            let ratios = split_zstd_ratios(results, x).map(|(_, ratio)| ratio);
+            boxes.push(RatioBox::new(comparison.name.clone(), stats, ratios));
+        }
+    }
+
+    for (x, comparison) in first.custom_comparisons.iter().enumerate() {
+        for (y, group_name) in comparison.group_names.iter().enumerate() {
+            if let Some(stats) = calculate_custom_zstd_ratio_stats(results, x, y) {
+                let label = format!("{} ({})", comparison.name, group_name);
+                let ratios = custom_zstd_ratios(results, x, y).map(|(_, ratio)| ratio);
+                boxes.push(RatioBox::new(label, stats, ratios));
+            }
+        }
+    }
+    boxes
+}
+
+/// Draws the boxes to the given drawing area.
+fn draw_distribution_plot<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    boxes: &[RatioBox],
+    options: &PlotOptions,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let scale = options.scale;
+    let y_range = options.y_range.resolve(boxes.iter().flat_map(|ratio_box| {
+        [ratio_box.whisker_low, ratio_box.whisker_high]
+            .into_iter()
+            .chain(ratio_box.outliers.iter().copied())
+    }));
+
+    // Box `i` is centered on `x = i`.
+    let mut chart = ChartBuilder::on(&root)
+        .margin(scaled(5.0, scale))
+        .x_label_area_size(scaled(80.0, scale))
+        .y_label_area_size(scaled(80.0, scale))
+        .build_cartesian_2d(-0.5f64..boxes.len() as f64 - 0.5, y_range)?;
+
+    let font_size = 40.0 * scale;
+    let label = |x: &f64| {
+        let index = x.round();
+        match boxes.get(index as usize) {
+            Some(ratio_box) if (x - index).abs() < 1e-6 && index >= 0.0 => ratio_box.label.clone(),
+            _ => String::new(),
+        }
+    };
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .y_label_style(("sans-serif", font_size).into_font())
+        .x_labels(boxes.len())
+        .x_label_style(("sans-serif", font_size).into_font())
+        .x_label_formatter(&label)
+        .draw()?;
+
+    let stroke_width = scaled(3.0, scale);
+    let line_style = ShapeStyle::from(BLACK).stroke_width(stroke_width);
+    let box_style = ShapeStyle::from(RGBColor(150, 150, 255)).filled();
+    let outlier_style = ShapeStyle::from(RED).stroke_width(stroke_width);
+    for (x, ratio_box) in boxes.iter().enumerate() {
+        let x = x as f64;
+        let (left, right) = (x - BOX_HALF_WIDTH, x + BOX_HALF_WIDTH);
+        let stats = &ratio_box.stats;
+
+        chart.draw_series([
+            Rectangle::new([(left, stats.q3), (right, stats.q1)], box_style),
+            Rectangle::new([(left, stats.q3), (right, stats.q1)], line_style),
+        ])?;
+
+        let cap = BOX_HALF_WIDTH / 2.0;
+        chart.draw_series(
+            [
+                // Median
+                [(left, stats.median), (right, stats.median)],
+                // Whiskers, with caps
+                [(x, stats.q3), (x, ratio_box.whisker_high)],
+                [(x, stats.q1), (x, ratio_box.whisker_low)],
+                [
+                    (x - cap, ratio_box.whisker_high),
+                    (x + cap, ratio_box.whisker_high),
+                ],
+                [
+                    (x - cap, ratio_box.whisker_low),
+                    (x + cap, ratio_box.whisker_low),
+                ],
+            ]
+            .map(|points| PathElement::new(points, line_style)),
+        )?;
+
+        chart.draw_series(
+            ratio_box
+                .outliers
+                .iter()
+                .map(|&ratio| Circle::new((x, ratio), 7.5 * scale, outlier_style)),
+        )?;
+    }
+
+    root.present()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comparison::stats::calculate_stats;
+
+    #[test]
+    fn ratios_beyond_whiskers_are_outliers() {
+        let ratios = [0.90, 0.91, 0.92, 0.93, 0.94, 0.95, 0.96, 1.50, 0.20];
+        let stats = calculate_stats(&ratios).unwrap();
+        let ratio_box = RatioBox::new("split".into(), stats, ratios.into_iter());
+
+        assert_eq!(ratio_box.outliers, [1.50, 0.20]);
+        assert_eq!(ratio_box.whisker_low, 0.90);
+        assert_eq!(ratio_box.whisker_high, 0.96);
+
+        // Without outliers, the whiskers reach the extremes.
+        let ratios = [0.8, 0.9, 1.0];
+        let stats = calculate_stats(&ratios).unwrap();
+        let ratio_box = RatioBox::new("split".into(), stats, ratios.into_iter());
+        assert!(ratio_box.outliers.is_empty());
+        assert_eq!((ratio_box.whisker_low, ratio_box.whisker_high), (0.8, 1.0));
+    }
+}
@@ -0,0 +1,27 @@
+//! The image format plots are written in.
+
+use derive_more::FromStr;
+
+/// The image format of the plots, see [`generate_plots`].
+///
+/// Parsed case-insensitively from the variant name, e.g. `svg`.
+///
+/// [`generate_plots`]: super::generate_plots
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromStr)]
+pub enum PlotFormat {
+    /// Raster images, `.png`.
+    #[default]
+    Png,
+    /// Vector images, `.svg`. Suited for embedding in web pages, and diffing between runs.
+    Svg,
+}
+
+impl PlotFormat {
+    /// File extension of plots in this format, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            PlotFormat::Png => "png",
+            PlotFormat::Svg => "svg",
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Bar charts of how often each value of a field occurs.
+//!
+//! [`generate_value_histogram_plot`] draws one bar per observed value, using the
+//! [`FieldMetrics::value_counts`] of a field. Only fields of up to [`MAX_HISTOGRAM_BITS`]
+//! are plotted by [`generate_plots`], since wider fields would produce thousands of bars.
+//!
+//! [`generate_plots`]: super::generate_plots
+
+use super::render::{create_drawing_area, scaled};
+use super::{PlotFormat, PlotOptions};
+use crate::results::FieldMetrics;
+use plotters::{coord::Shift, prelude::*};
+use std::path::Path;
+
+/// Fields wider than this many bits have too many possible values for a readable histogram,
+/// and are skipped by [`generate_plots`].
+///
+/// [`generate_plots`]: super::generate_plots
+pub const MAX_HISTOGRAM_BITS: u32 = 12;
+
+/// Half of the width of a bar, in units of the x axis (1 per value).
+const BAR_HALF_WIDTH: f64 = 0.4;
+
+/// Generates a bar chart of the share of observations (in %) of each value of a field.
+///
+/// The x axis spans every value the field can hold, values which were never observed have
+/// no bar. Labels are rendered according to the [`FieldMetrics::interpretation`] of the field.
+/// Nothing is written if the field has no value counts, e.g. due to `skip_frequency_analysis`.
+///
+/// # Arguments
+///
+/// * `field_metrics` - The metrics of the field, usually merged over all files.
+/// * `output_path` - The path where the plot file will be written.
+/// * `options` - The scale and image format of the plot.
+///
+/// # Returns
+///
+/// * `Result<(), Box<dyn std::error::Error>>` - Ok if successful, otherwise a boxed [`std::error::Error`].
+pub fn generate_value_histogram_plot(
+    field_metrics: &FieldMetrics,
+    output_path: &Path,
+    options: &PlotOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if field_metrics.value_counts.is_empty() {
+        return Ok(()); // No data to plot
+    }
+
+    // Each possible value gets a constant amount of space, within reason.
+    let num_values = value_space(field_metrics);
+    let width = scaled(
+        (num_values as f64 * 48.0).clamp(640.0, 3840.0),
+        options.scale,
+    );
+    let size = (width, scaled(1440.0, options.scale));
+    match options.format {
+        PlotFormat::Png => draw_value_histogram(
+            create_drawing_area(BitMapBackend::new(output_path, size))?,
+            field_metrics,
+            options,
+        ),
+        PlotFormat::Svg => draw_value_histogram(
+            create_drawing_area(SVGBackend::new(output_path, size))?,
+            field_metrics,
+            options,
+        ),
+    }
+}
+
+/// Number of values the field can hold, i.e. the length of the x axis.
+fn value_space(field_metrics: &FieldMetrics) -> u64 {
+    let largest_value = field_metrics
+        .value_counts
+        .keys()
+        .max()
+        .copied()
+        .unwrap_or(0);
+    1u64.checked_shl(field_metrics.lenbits)
+        .unwrap_or(u64::MAX)
+        .max(largest_value.saturating_add(1))
+}
+
+/// Draws the bars of the field to the given drawing area.
+fn draw_value_histogram<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    field_metrics: &FieldMetrics,
+    options: &PlotOptions,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let scale = options.scale;
+    let total: u64 = field_metrics.value_counts.values().sum();
+    let bars: Vec<(f64, f64)> = field_metrics
+        .sorted_value_counts()
+        .into_iter()
+        .map(|(&value, &count)| (value as f64, count as f64 / total as f64 * 100.0))
+        .collect();
+    let max_share = bars.iter().map(|&(_, share)| share).fold(0.0, f64::max);
+
+    // Value `v` is centered on `x = v`.
+    let num_values = value_space(field_metrics);
+    let font_size = 40.0 * scale;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(&field_metrics.full_path, ("sans-serif", font_size))
+        .margin(scaled(5.0, scale))
+        .x_label_area_size(scaled(80.0, scale))
+        .y_label_area_size(scaled(80.0, scale))
+        .build_cartesian_2d(-0.5f64..num_values as f64 - 0.5, 0f64..max_share * 1.1)?;
+
+    let label = |x: &f64| {
+        let value = x.round();
+        if (x - value).abs() < 1e-6 && value >= 0.0 {
+            field_metrics.format_value(value as u64)
+        } else {
+            String::new()
+        }
+    };
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .y_desc("% of values")
+        .axis_desc_style(("sans-serif", font_size).into_font())
+        .y_label_style(("sans-serif", font_size).into_font())
+        .x_labels(num_values.min(32) as usize)
+        .x_label_style(("sans-serif", font_size).into_font())
+        .x_label_formatter(&label)
+        .draw()?;
+
+    let bar_style = ShapeStyle::from(RGBColor(150, 150, 255)).filled();
+    chart.draw_series(bars.iter().map(|&(x, share)| {
+        Rectangle::new(
+            [(x - BAR_HALF_WIDTH, share), (x + BAR_HALF_WIDTH, 0.0)],
+            bar_style,
+        )
+    }))?;
+
+    root.present()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_space_covers_all_values_of_the_field() {
+        let metrics = |lenbits, values: &[u64]| FieldMetrics {
+            lenbits,
+            value_counts: values.iter().map(|&value| (value, 1)).collect(),
+            ..Default::default()
+        };
+        assert_eq!(value_space(&metrics(4, &[0, 3])), 16);
+        assert_eq!(value_space(&metrics(12, &[])), 4096);
+        assert_eq!(value_space(&metrics(64, &[])), u64::MAX);
+    }
+}
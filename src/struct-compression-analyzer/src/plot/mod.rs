@@ -1,14 +1,28 @@
 //! Generates plots for analysis results.
 //!
 //! This module provides functions to create various plots based on the analysis
-//! results, using the `plotters` crate.
+//! results, using the `plotters` crate. Plots are written as PNG or SVG, see [`PlotOptions`].
+
+mod distribution;
+mod format;
+mod histogram;
+mod options;
+mod render;
+pub use distribution::generate_ratio_distribution_plot;
+pub use format::PlotFormat;
+pub use histogram::{generate_value_histogram_plot, MAX_HISTOGRAM_BITS};
+pub use options::{
+    ParseYAxisRangeError, PlotOptions, YAxisRange, DEFAULT_Y_AXIS_MAX, DEFAULT_Y_AXIS_MIN,
+};
 
 use crate::comparison::{
     compare_groups::GroupComparisonResult, split_comparison::SplitComparisonResult,
 };
-use crate::results::analysis_results::AnalysisResults;
+use crate::results::{analysis_results::AnalysisResults, FieldMetrics};
 use core::{error::Error, ops::Range};
 use plotters::{prelude::*, style::full_palette::PURPLE};
+use render::{render_ratio_plot, PlotData};
+use sanitize_filename::sanitize;
 use std::{fs, path::Path};
 
 /// Generates all plots for the analysis results.
@@ -20,6 +34,10 @@ use std::{fs, path::Path};
 ///
 /// * `results` - A slice of [`AnalysisResults`], one for each analyzed file.
 /// * `output_dir` - The directory where the plot files will be written.
+/// * `prefix` - Prefix for the names of the created subdirectories, allowing multiple runs to
+///   share the same `output_dir`. Use an empty string for no prefix.
+/// * `options` - The scale, image format and y axis range of the plots.
+///   The format also sets the file extension of the plots.
 ///
 /// # Returns
 ///
@@ -27,28 +45,34 @@ use std::{fs, path::Path};
 pub fn generate_plots(
     results: &[AnalysisResults],
     output_dir: &Path,
+    prefix: &str,
+    options: &PlotOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if results.is_empty() {
         return Ok(());
     }
+    let extension = options.format.extension();
 
-    let split_compare_dir = output_dir.join("split_comparison_plots");
+    let split_compare_dir = output_dir.join(format!("{prefix}split_comparison_plots"));
     fs::create_dir_all(&split_compare_dir)?;
 
     // Generate split comparison plot
     for (x, comparison) in results[0].split_comparisons.iter().enumerate() {
-        let output_path = split_compare_dir.join(format!("{}.png", comparison.name));
-        generate_ratio_split_comparison_plot(results, x, &output_path, false, false)?;
-
-        let output_path = split_compare_dir.join(format!("{}_with_estimate.png", comparison.name));
-        generate_ratio_split_comparison_plot(results, x, &output_path, false, true)?;
+        let output_path = split_compare_dir.join(format!("{}.{extension}", comparison.name));
+        generate_ratio_split_comparison_plot(results, x, &output_path, false, false, options)?;
 
         let output_path =
-            split_compare_dir.join(format!("{}_with_entropy_by_lzmatches.png", comparison.name));
-        generate_ratio_split_comparison_plot(results, x, &output_path, true, false)?;
+            split_compare_dir.join(format!("{}_with_estimate.{extension}", comparison.name));
+        generate_ratio_split_comparison_plot(results, x, &output_path, false, true, options)?;
+
+        let output_path = split_compare_dir.join(format!(
+            "{}_with_entropy_by_lzmatches.{extension}",
+            comparison.name
+        ));
+        generate_ratio_split_comparison_plot(results, x, &output_path, true, false, options)?;
     }
 
-    let custom_comparisons_dir = output_dir.join("custom_comparison_plots");
+    let custom_comparisons_dir = output_dir.join(format!("{prefix}custom_comparison_plots"));
     fs::create_dir_all(&custom_comparisons_dir)?;
 
     // Generate custom comparison plot
@@ -57,53 +81,94 @@ pub fn generate_plots(
         // Write data for individual groups.
         for (y, group_name) in comparison.group_names.iter().enumerate() {
             let output_path = custom_comparisons_dir.join(format!(
-                "{}_{}_{}.png",
+                "{}_{}_{}.{extension}",
                 comparison.name,
                 group_name.replace(' ', "_"),
                 y
             ));
-            generate_ratio_custom_comparison_plot(results, x, y..y + 1, &output_path, false)?;
+            generate_ratio_custom_comparison_plot(
+                results,
+                x,
+                y..y + 1,
+                &output_path,
+                false,
+                options,
+            )?;
 
             let output_path = custom_comparisons_dir.join(format!(
-                "{}_{}_{}_with_estimate.png",
+                "{}_{}_{}_with_estimate.{extension}",
                 comparison.name,
                 group_name.replace(' ', "_"),
                 y
             ));
-            generate_ratio_custom_comparison_plot(results, x, y..y + 1, &output_path, true)?;
+            generate_ratio_custom_comparison_plot(
+                results,
+                x,
+                y..y + 1,
+                &output_path,
+                true,
+                options,
+            )?;
         }
 
-        let output_path = custom_comparisons_dir.join(format!("{}.png", comparison.name));
+        let output_path = custom_comparisons_dir.join(format!("{}.{extension}", comparison.name));
         generate_ratio_custom_comparison_plot(
             results,
             x,
             0..comparison.group_names.len(),
             &output_path,
             false,
+            options,
         )?;
 
         let output_path =
-            custom_comparisons_dir.join(format!("{}_with_estimate.png", comparison.name));
+            custom_comparisons_dir.join(format!("{}_with_estimate.{extension}", comparison.name));
         generate_ratio_custom_comparison_plot(
             results,
             x,
             0..comparison.group_names.len(),
             &output_path,
             true,
+            options,
         )?;
     }
 
+    let output_path = output_dir.join(format!("{prefix}zstd_ratio_distribution.{extension}"));
+    generate_ratio_distribution_plot(results, &output_path, options)?;
+
+    let histograms_dir = output_dir.join(format!("{prefix}value_histogram_plots"));
+    fs::create_dir_all(&histograms_dir)?;
+
+    // Generate value histograms, of the values of all files
+    let mut field_paths: Vec<&String> = results[0].per_field.keys().collect();
+    field_paths.sort();
+    let mut skipped_fields = Vec::new();
+    for field_path in field_paths {
+        let fields: Vec<&FieldMetrics> = results
+            .iter()
+            .filter_map(|result| result.per_field.get(field_path))
+            .collect();
+        if fields[0].lenbits > MAX_HISTOGRAM_BITS {
+            skipped_fields.push(field_path.as_str());
+            continue;
+        }
+
+        let merged = FieldMetrics::try_merge_many(&fields)?;
+        let output_path = histograms_dir.join(format!("{}.{extension}", sanitize(field_path)));
+        generate_value_histogram_plot(&merged, &output_path, options)?;
+    }
+    if !skipped_fields.is_empty() {
+        eprintln!(
+            "Note: Skipped value histograms of fields wider than {} bits: {}",
+            MAX_HISTOGRAM_BITS,
+            skipped_fields.join(", ")
+        );
+    }
+
     // Add calls to other plot generation functions here in the future
     Ok(())
 }
 
-/// Struct to hold data and styling for a single plot line.
-struct PlotData {
-    label: String,
-    line_color: RGBColor,
-    data_points: Vec<(f64, f64)>,
-}
-
 /// Generates a line plot for the various columns from a split comparison.
 ///
 /// # Arguments
@@ -113,6 +178,7 @@ struct PlotData {
 /// * `output_path` - The path where the plot file will be written.
 /// * `include_entropy_by_lzmatches_column` - Includes column for (1 / lz_matches * entropy_ratio).
 /// * `include_estimate_column` - Includes column for (estimate_ratio).
+/// * `options` - The scale, image format and y axis range of the plot.
 ///
 /// # Returns
 ///
@@ -123,19 +189,12 @@ pub fn generate_ratio_split_comparison_plot(
     output_path: &Path,
     include_entropy_by_lzmatches_column: bool,
     include_estimate_column: bool,
+    options: &PlotOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if results.is_empty() || results[0].split_comparisons.is_empty() {
         return Ok(()); // No data to plot
     }
 
-    let root = create_drawing_area(results, output_path)?;
-
-    // Create the chart.
-    let mut chart = create_ratio_chart(results.len(), &root)?;
-
-    // Add labels (file indices).
-    draw_ratio_grid(results.len(), &mut chart)?;
-
     // Prepare plot data
     let mut plots: Vec<PlotData> = Vec::new();
 
@@ -206,14 +265,7 @@ pub fn generate_ratio_split_comparison_plot(
         });
     }
 
-    // Draw plots
-    for plot in plots {
-        draw_plot(&mut chart, &plot)?;
-    }
-
-    add_series_labels(&mut chart)?;
-    root.present()?;
-    Ok(())
+    render_ratio_plot(output_path, results.len(), &plots, options)
 }
 
 /// Generates the base colours that will be transformed by a gradient
@@ -283,6 +335,7 @@ fn generate_color_palette(
 /// * `group_indices` - The range of indices for the groups to compare.
 /// * `output_path` - The path where the plot file will be written.
 /// * `include_estimate_column` - Whether to include the estimate ratio column.
+/// * `options` - The scale, image format and y axis range of the plot.
 ///
 /// # Returns
 ///
@@ -293,19 +346,12 @@ pub fn generate_ratio_custom_comparison_plot(
     group_indices: Range<usize>,
     output_path: &Path,
     include_estimate_column: bool,
+    options: &PlotOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if results.is_empty() || results[0].split_comparisons.is_empty() {
         return Ok(()); // No data to plot
     }
 
-    let root = create_drawing_area(results, output_path)?;
-
-    // Create the chart.
-    let mut chart = create_ratio_chart(results.len(), &root)?;
-
-    // Add labels (file indices).
-    draw_ratio_grid(results.len(), &mut chart)?;
-
     // Prepare plot data
     let mut plots: Vec<PlotData> = Vec::new();
     let group_names = &results[0].custom_comparisons[0].group_names;
@@ -382,14 +428,7 @@ pub fn generate_ratio_custom_comparison_plot(
         }
     }
 
-    // Draw plots
-    for plot in plots {
-        draw_plot(&mut chart, &plot)?;
-    }
-
-    add_series_labels(&mut chart)?;
-    root.present()?;
-    Ok(())
+    render_ratio_plot(output_path, results.len(), &plots, options)
 }
 
 /// Calculates the data points for a plot.
@@ -428,121 +467,6 @@ where
     data_points
 }
 
-/// Draws a single plot line and its points.
-fn draw_plot<'a>(
-    chart: &mut ChartContext<
-        'a,
-        BitMapBackend<'a>,
-        Cartesian2d<plotters::coord::types::RangedCoordf64, plotters::coord::types::RangedCoordf64>,
-    >,
-    plot: &PlotData,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let line_color = plot.line_color;
-    let line_style = ShapeStyle::from(line_color).stroke_width(5);
-    let coord_style = ShapeStyle::from(BLACK).filled();
-
-    let plot_points = plot.data_points.clone();
-    chart
-        .draw_series(LineSeries::new(plot_points, line_style))?
-        .label(&plot.label)
-        .legend(move |(x, y)| {
-            PathElement::new(
-                vec![(x, y), (x + 20, y)],
-                ShapeStyle::from(line_color).stroke_width(5),
-            )
-        });
-
-    chart.draw_series(PointSeries::<_, _, Circle<_, _>, _>::new(
-        plot.data_points.clone(),
-        7.5,
-        coord_style,
-    ))?;
-
-    Ok(())
-}
-
-fn create_drawing_area<'a>(
-    results: &[AnalysisResults],
-    output_file: &'a Path,
-) -> Result<DrawingArea<BitMapBackend<'a>, plotters::coord::Shift>, Box<dyn std::error::Error>> {
-    // Auto adjust size such that each value has constant amount of sapce.
-    let width = results.len() * 64;
-    let root = BitMapBackend::new(output_file, (width as u32, 1440)).into_drawing_area();
-    root.fill(&WHITE)?;
-    Ok(root)
-}
-
-/// Creates a chart for plotting compression ratio information,
-/// with a fixed range of 0.6 to 1.20 in terms of compression ratio.
-fn create_ratio_chart<'a>(
-    num_results: usize,
-    root: &DrawingArea<BitMapBackend<'a>, plotters::coord::Shift>,
-) -> Result<
-    ChartContext<
-        'a,
-        BitMapBackend<'a>,
-        Cartesian2d<plotters::coord::types::RangedCoordf64, plotters::coord::types::RangedCoordf64>,
-    >,
-    Box<dyn std::error::Error>,
-> {
-    let chart: ChartContext<
-        '_,
-        BitMapBackend<'a>,
-        Cartesian2d<plotters::coord::types::RangedCoordf64, plotters::coord::types::RangedCoordf64>,
-    > = ChartBuilder::on(root)
-        .margin(5)
-        .x_label_area_size(80)
-        .y_label_area_size(80)
-        .build_cartesian_2d(
-            0f64..num_results as f64, // x axis range, one point per file
-            0.60f64..1.20f64,         // y axis range, adjust as needed
-        )?;
-    Ok(chart)
-}
-
-/// Draws the grid, including the labels for a graph which presents a compression ratio
-/// centered around 1.0
-fn draw_ratio_grid<'a>(
-    results_len: usize,
-    chart: &mut ChartContext<
-        'a,
-        BitMapBackend<'a>,
-        Cartesian2d<plotters::coord::types::RangedCoordf64, plotters::coord::types::RangedCoordf64>,
-    >,
-) -> Result<(), Box<dyn std::error::Error>> {
-    chart
-        .configure_mesh()
-        // Title
-        .axis_desc_style(("sans-serif", 40).into_font())
-        // y labels
-        .y_label_style(("sans-serif", 40).into_font())
-        // x labels
-        .x_labels(results_len)
-        .x_label_style(("sans-serif", 40).into_font())
-        .x_label_formatter(&|x| format!("{}", x))
-        .draw()?;
-    Ok(())
-}
-
-/// Adds the series labels to the current chart.
-/// i.e. the little box which shows lines and their corresponding names.
-fn add_series_labels<'a>(
-    chart: &mut ChartContext<
-        'a,
-        BitMapBackend<'a>,
-        Cartesian2d<plotters::coord::types::RangedCoordf64, plotters::coord::types::RangedCoordf64>,
-    >,
-) -> Result<(), Box<dyn std::error::Error>> {
-    chart
-        .configure_series_labels()
-        .label_font(("sans-serif", 40))
-        .background_style(WHITE.mix(0.8))
-        .border_style(BLACK)
-        .position(SeriesLabelPosition::UpperLeft)
-        .draw()?;
-    Ok(())
-}
-
 /// Calculates a ratio between two numbers, handling division by zero.
 ///
 /// # Arguments
@@ -560,3 +484,48 @@ pub(crate) fn calc_ratio_f64(child: u64, parent: u64) -> f64 {
         child as f64 / parent as f64
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{CompressionOptions, SchemaAnalyzer};
+    use crate::schema::Schema;
+
+    #[test]
+    fn writes_svg_plots() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+root: { type: group, fields: { a: 8, b: 8 } }
+analysis:
+  split_groups:
+    - { name: split, group_1: [a, b], group_2: [b, a] }
+"#,
+        )
+        .unwrap();
+        let results: Vec<_> = (1..4u8)
+            .map(|seed| {
+                let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+                for x in 0..64u8 {
+                    analyzer.add_entry(&[x.wrapping_mul(seed), x / 4]).unwrap();
+                }
+                analyzer.generate_results().unwrap()
+            })
+            .collect();
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let options = PlotOptions::default().with_format(PlotFormat::Svg);
+        generate_plots(&results, output_dir.path(), "", &options).unwrap();
+        let plot = fs::read_to_string(output_dir.path().join("split_comparison_plots/split.svg"));
+        let histogram = fs::read_to_string(output_dir.path().join("value_histogram_plots/a.svg"));
+
+        assert!(histogram.unwrap().contains("% of values"));
+
+        let plot = plot.unwrap();
+        let (width, height) = render::drawing_area_size(results.len(), options.scale);
+        assert!(plot.contains(&format!("width=\"{width}\" height=\"{height}\"")));
+        for label in ["zstd_ratio", "1 / lz_matches_ratio", "1 / entropy_ratio"] {
+            assert!(plot.contains(label), "{label}");
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Options for how plots are rendered, see [`PlotOptions`].
+
+use super::PlotFormat;
+use core::ops::Range;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Lower end of the default y axis range of ratio plots, see [`YAxisRange::Fixed`].
+pub const DEFAULT_Y_AXIS_MIN: f64 = 0.60;
+
+/// Upper end of the default y axis range of ratio plots, see [`YAxisRange::Fixed`].
+pub const DEFAULT_Y_AXIS_MAX: f64 = 1.20;
+
+/// Options for rendering plots.
+///
+/// # Example
+/// ```rust
+/// # use struct_compression_analyzer::plot::{PlotFormat, PlotOptions, YAxisRange};
+/// let options = PlotOptions::default()
+///     .with_format(PlotFormat::Svg)
+///     .with_y_range(YAxisRange::Auto);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlotOptions {
+    /// Multiplier applied to the canvas size, fonts, lines and margins.
+    /// Use `1.0` for the default resolution, `2.0` for double DPI, etc.
+    pub scale: f64,
+    /// The image format of the plots, which also sets their file extension.
+    pub format: PlotFormat,
+    /// The range of compression ratios shown on the y axis of ratio plots.
+    pub y_range: YAxisRange,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            format: PlotFormat::Png,
+            y_range: YAxisRange::default(),
+        }
+    }
+}
+
+impl PlotOptions {
+    /// Sets the multiplier applied to the canvas size, fonts, lines and margins.
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets the image format of the plots.
+    pub fn with_format(mut self, format: PlotFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the range of compression ratios shown on the y axis of ratio plots.
+    pub fn with_y_range(mut self, y_range: YAxisRange) -> Self {
+        self.y_range = y_range;
+        self
+    }
+}
+
+/// The range of the y axis of ratio plots.
+///
+/// Parsed from `auto`, or `MIN:MAX` for a fixed range (e.g. `0.4:1.6`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum YAxisRange {
+    /// A fixed range from `min` to `max`, points outside of it are clipped.
+    /// Defaults to [`DEFAULT_Y_AXIS_MIN`] to [`DEFAULT_Y_AXIS_MAX`].
+    Fixed { min: f64, max: f64 },
+    /// Fit the range to the points of each plot, with a small margin.
+    Auto,
+}
+
+impl Default for YAxisRange {
+    fn default() -> Self {
+        YAxisRange::Fixed {
+            min: DEFAULT_Y_AXIS_MIN,
+            max: DEFAULT_Y_AXIS_MAX,
+        }
+    }
+}
+
+/// Margin added above and below the points of an [`YAxisRange::Auto`] range,
+/// as a fraction of the distance between the lowest and highest point.
+const AUTO_MARGIN: f64 = 0.05;
+
+/// Margin added above and below the points of an [`YAxisRange::Auto`] range,
+/// if all points have the same value.
+const AUTO_MIN_MARGIN: f64 = 0.05;
+
+impl YAxisRange {
+    /// Returns the range of the y axis for a plot with the given y values.
+    ///
+    /// For [`YAxisRange::Auto`], infinite and NaN values (e.g. ratios against an empty group)
+    /// are ignored. If there are no other values, the default range is used.
+    pub fn resolve(self, values: impl IntoIterator<Item = f64>) -> Range<f64> {
+        let (min, max) = match self {
+            YAxisRange::Fixed { min, max } => return min..max,
+            YAxisRange::Auto => values
+                .into_iter()
+                .filter(|value| value.is_finite())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                    (min.min(value), max.max(value))
+                }),
+        };
+
+        if min > max {
+            return DEFAULT_Y_AXIS_MIN..DEFAULT_Y_AXIS_MAX;
+        }
+
+        // A range of 0 would have no height.
+        let margin = ((max - min) * AUTO_MARGIN).max(AUTO_MIN_MARGIN);
+        min - margin..max + margin
+    }
+}
+
+/// Error parsing a [`YAxisRange`].
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error(
+    "Invalid y axis range '{0}', expected 'auto' or 'MIN:MAX' with MIN below MAX, e.g. '0.4:1.6'"
+)]
+pub struct ParseYAxisRangeError(String);
+
+impl FromStr for YAxisRange {
+    type Err = ParseYAxisRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(YAxisRange::Auto);
+        }
+
+        let error = || ParseYAxisRangeError(s.to_string());
+        let (min, max) = s.split_once(':').ok_or_else(error)?;
+        let min: f64 = min.trim().parse().map_err(|_| error())?;
+        let max: f64 = max.trim().parse().map_err(|_| error())?;
+        if !(min.is_finite() && max.is_finite() && min < max) {
+            return Err(error());
+        }
+        Ok(YAxisRange::Fixed { min, max })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_y_axis_ranges() {
+        assert_eq!("auto".parse(), Ok(YAxisRange::Auto));
+        assert_eq!(
+            "0.4:1.6".parse(),
+            Ok(YAxisRange::Fixed { min: 0.4, max: 1.6 })
+        );
+        for invalid in ["", "1.0", "1.2:0.6", "1:1", "a:b", "0.6:inf"] {
+            assert!(invalid.parse::<YAxisRange>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn auto_range_fits_points() {
+        let fixed = YAxisRange::default().resolve([0.1, 5.0]);
+        assert_eq!(fixed, DEFAULT_Y_AXIS_MIN..DEFAULT_Y_AXIS_MAX);
+
+        let fitted = YAxisRange::Auto.resolve([0.5, f64::INFINITY, 1.5, f64::NAN]);
+        assert!(fitted.start < 0.5 && fitted.start > 0.4);
+        assert!(fitted.end > 1.5 && fitted.end < 1.6);
+
+        // All equal values still get an axis with some height.
+        let flat = YAxisRange::Auto.resolve([1.0, 1.0]);
+        assert!(flat.start < 1.0 && flat.end > 1.0);
+
+        let empty = YAxisRange::Auto.resolve([f64::INFINITY]);
+        assert_eq!(empty, DEFAULT_Y_AXIS_MIN..DEFAULT_Y_AXIS_MAX);
+    }
+}
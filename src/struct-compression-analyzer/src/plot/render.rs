@@ -0,0 +1,216 @@
+//! Renders the lines of a ratio plot to an image file, in the format of the [`PlotOptions`].
+//!
+//! The plots are drawn the same way for every backend, so PNG and SVG plots have the same
+//! size, series, labels and legend.
+
+use super::{PlotFormat, PlotOptions};
+use core::ops::Range;
+use plotters::{
+    coord::{types::RangedCoordf64, Shift},
+    prelude::*,
+};
+use std::path::Path;
+
+/// A chart of compression ratios, one point per file, drawn to a backend of type `DB`.
+type RatioChart<'a, DB> = ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>;
+
+/// Struct to hold data and styling for a single plot line.
+pub(super) struct PlotData {
+    pub(super) label: String,
+    pub(super) line_color: RGBColor,
+    pub(super) data_points: Vec<(f64, f64)>,
+}
+
+/// Renders a ratio plot of the given lines to `output_path`.
+///
+/// # Arguments
+///
+/// * `output_path` - The path where the plot file will be written.
+/// * `num_results` - The number of files, i.e. points per line.
+/// * `plots` - The lines to draw.
+/// * `options` - The scale, format and y axis range of the plot.
+pub(super) fn render_ratio_plot(
+    output_path: &Path,
+    num_results: usize,
+    plots: &[PlotData],
+    options: &PlotOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let size = drawing_area_size(num_results, options.scale);
+    match options.format {
+        PlotFormat::Png => draw_ratio_plot(
+            create_drawing_area(BitMapBackend::new(output_path, size))?,
+            num_results,
+            plots,
+            options,
+        ),
+        PlotFormat::Svg => draw_ratio_plot(
+            create_drawing_area(SVGBackend::new(output_path, size))?,
+            num_results,
+            plots,
+            options,
+        ),
+    }
+}
+
+/// Draws a ratio plot of the given lines to the given drawing area.
+fn draw_ratio_plot<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    num_results: usize,
+    plots: &[PlotData],
+    options: &PlotOptions,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let scale = options.scale;
+    let y_range = options.y_range.resolve(
+        plots
+            .iter()
+            .flat_map(|plot| plot.data_points.iter().map(|(_, y)| *y)),
+    );
+
+    // Create the chart.
+    let mut chart = create_ratio_chart(num_results, &root, y_range, scale)?;
+
+    // Add labels (file indices).
+    draw_ratio_grid(num_results, &mut chart, scale)?;
+
+    // Draw plots
+    for plot in plots {
+        draw_plot(&mut chart, plot, scale)?;
+    }
+
+    add_series_labels(&mut chart, scale)?;
+    root.present()?;
+    Ok(())
+}
+
+/// Draws a single plot line and its points.
+fn draw_plot<DB: DrawingBackend>(
+    chart: &mut RatioChart<'_, DB>,
+    plot: &PlotData,
+    scale: f64,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let line_color = plot.line_color;
+    let stroke_width = scaled(5.0, scale);
+    let legend_width = scaled(20.0, scale) as i32;
+    let line_style = ShapeStyle::from(line_color).stroke_width(stroke_width);
+    let coord_style = ShapeStyle::from(BLACK).filled();
+
+    let plot_points = plot.data_points.clone();
+    chart
+        .draw_series(LineSeries::new(plot_points, line_style))?
+        .label(&plot.label)
+        .legend(move |(x, y)| {
+            PathElement::new(
+                vec![(x, y), (x + legend_width, y)],
+                ShapeStyle::from(line_color).stroke_width(stroke_width),
+            )
+        });
+
+    chart.draw_series(PointSeries::<_, _, Circle<_, _>, _>::new(
+        plot.data_points.clone(),
+        7.5 * scale,
+        coord_style,
+    ))?;
+
+    Ok(())
+}
+
+/// Scales a pixel measurement (size, stroke width, etc.) by the given plot scale.
+/// The result is never smaller than 1 pixel.
+pub(super) fn scaled(value: f64, scale: f64) -> u32 {
+    ((value * scale).round() as u32).max(1)
+}
+
+/// Size of the drawing area of a plot of `num_results` files, in pixels.
+/// For SVG plots, this is the size of the canvas in user units.
+pub(super) fn drawing_area_size(num_results: usize, scale: f64) -> (u32, u32) {
+    // Auto adjust size such that each value has constant amount of sapce.
+    let width = scaled(num_results as f64 * 64.0, scale);
+    let height = scaled(1440.0, scale);
+    (width, height)
+}
+
+/// Creates the drawing area of a plot on the given backend, with a white background.
+pub(super) fn create_drawing_area<DB: DrawingBackend>(
+    backend: DB,
+) -> Result<DrawingArea<DB, Shift>, Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let root = backend.into_drawing_area();
+    root.fill(&WHITE)?;
+    Ok(root)
+}
+
+/// Creates a chart for plotting compression ratio information,
+/// with the given range of compression ratios on the y axis.
+fn create_ratio_chart<'a, DB: DrawingBackend>(
+    num_results: usize,
+    root: &'a DrawingArea<DB, Shift>,
+    y_range: Range<f64>,
+    scale: f64,
+) -> Result<RatioChart<'a, DB>, Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let chart = ChartBuilder::on(root)
+        .margin(scaled(5.0, scale))
+        .x_label_area_size(scaled(80.0, scale))
+        .y_label_area_size(scaled(80.0, scale))
+        .build_cartesian_2d(
+            0f64..num_results as f64, // x axis range, one point per file
+            y_range,
+        )?;
+    Ok(chart)
+}
+
+/// Draws the grid, including the labels for a graph which presents a compression ratio
+/// centered around 1.0
+fn draw_ratio_grid<DB: DrawingBackend>(
+    results_len: usize,
+    chart: &mut RatioChart<'_, DB>,
+    scale: f64,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let font_size = 40.0 * scale;
+    chart
+        .configure_mesh()
+        // Title
+        .axis_desc_style(("sans-serif", font_size).into_font())
+        // y labels
+        .y_label_style(("sans-serif", font_size).into_font())
+        // x labels
+        .x_labels(results_len)
+        .x_label_style(("sans-serif", font_size).into_font())
+        .x_label_formatter(&|x| format!("{}", x))
+        .draw()?;
+    Ok(())
+}
+
+/// Adds the series labels to the current chart.
+/// i.e. the little box which shows lines and their corresponding names.
+fn add_series_labels<'a, DB: DrawingBackend + 'a>(
+    chart: &mut RatioChart<'a, DB>,
+    scale: f64,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static,
+{
+    chart
+        .configure_series_labels()
+        .label_font(("sans-serif", 40.0 * scale))
+        .legend_area_size(scaled(30.0, scale))
+        .margin(scaled(10.0, scale))
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .position(SeriesLabelPosition::UpperLeft)
+        .draw()?;
+    Ok(())
+}
@@ -0,0 +1,369 @@
+use super::{run_stats::RunStats, ComputeAnalysisResultsError, FieldMetrics};
+use crate::{
+    analyzer::{AnalyzerFieldState, CompressionOptions, SchemaAnalyzer, SizeEstimationParameters},
+    comparison::{
+        auto_split::analyze_auto_splits,
+        compare_groups::{
+            analyze_byte_transpose_comparison, analyze_custom_comparisons, GroupComparisonResult,
+        },
+        multi_split::analyze_multi_split_comparisons,
+        split_comparison::{
+            make_split_comparison_result, normalize_group_length, FieldComparisonMetrics,
+            SplitComparisonResult,
+        },
+        GroundTruth,
+    },
+    schema::{BitOrder, Metadata, SplitComparison, ValueInterpretation},
+    utils::analyze_utils::{
+        calculate_file_entropy, calculate_windowed_entropy, get_entropy_coded_size,
+        get_writer_buffer,
+    },
+};
+use ahash::{AHashMap, HashMapExt};
+use lossless_transform_utils::match_estimator::estimate_num_lz_matches_fast;
+use rayon::prelude::*;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+mod print;
+mod print_comparisons;
+
+/// Final computed metrics for output
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct AnalysisResults {
+    /// Schema name
+    pub schema_metadata: Metadata,
+
+    /// Entropy of the whole file
+    pub file_entropy: f64,
+
+    /// LZ compression matches in the file
+    pub file_lz_matches: u64,
+
+    /// Actual size of the compressed data when compressed with zstandard
+    pub zstd_file_size: u64,
+
+    /// Original size of the uncompressed data
+    pub original_size: u64,
+
+    /// Field path → computed metrics
+    /// This is a map of `full_path` to [`FieldMetrics`], such that we
+    /// can easily merge the results of different fields down the road.
+    #[serde(serialize_with = "super::json::serialize_sorted_map")]
+    pub per_field: AHashMap<String, FieldMetrics>,
+
+    /// Split comparison results
+    pub split_comparisons: Vec<SplitComparisonResult>,
+
+    /// Custom group comparison results from schema-defined comparisons
+    pub custom_comparisons: Vec<GroupComparisonResult>,
+}
+
+/// Given a [`SchemaAnalyzer`] which has ingested all of the data to be calculated, via
+/// the [`SchemaAnalyzer::add_entry`] function, compute the analysis results.
+///
+/// This returns the results for all of the per-field metrics, as well as computing the
+/// various schema defined groups, such as 'split' groups and 'compare' groups.
+pub fn compute_analysis_results(
+    analyzer: &mut SchemaAnalyzer,
+) -> Result<AnalysisResults, ComputeAnalysisResultsError> {
+    // First calculate file entropy
+    let file_entropy = calculate_file_entropy(&analyzer.entries);
+    let file_lz_matches = estimate_num_lz_matches_fast(&analyzer.entries);
+
+    // Then calculate per-field entropy and lz matches.
+    // Each field's metrics are independent of the others, so fields are measured in parallel;
+    // the results are keyed by path, so they don't depend on the order fields finish in.
+    let options = &analyzer.compression_options;
+    let field_metrics: Vec<(String, FieldMetrics)> = analyzer
+        .field_states
+        .par_iter_mut()
+        .map(|(_, stats)| {
+            // Values of larger fields (and groups) are written in multiple parts.
+            let run_stats = (options.measure_run_stats && stats.lenbits <= 64)
+                .then(|| RunStats::from_writer(&mut stats.writer, stats.lenbits, stats.count));
+            let writer_buffer = get_writer_buffer(&mut stats.writer);
+            let entropy = calculate_file_entropy(writer_buffer);
+            let lz_matches = estimate_num_lz_matches_fast(writer_buffer);
+            let actual_size = options.zstd_compressed_size(writer_buffer);
+            // Fields may override the multipliers of the size estimator.
+            let estimated_size = options.estimate_size(SizeEstimationParameters {
+                name: &stats.full_path,
+                data_len: writer_buffer.len(),
+                data: Some(writer_buffer),
+                num_lz_matches: lz_matches,
+                entropy,
+                lz_match_multiplier: stats
+                    .lz_match_multiplier
+                    .unwrap_or(options.lz_match_multiplier),
+                entropy_multiplier: stats
+                    .entropy_multiplier
+                    .unwrap_or(options.entropy_multiplier),
+            });
+            let entropy_coded_size = options
+                .measure_entropy_coded_size
+                .then(|| get_entropy_coded_size(writer_buffer));
+            let entropy_order1 = options.order1_entropy(writer_buffer);
+            let windowed_entropy = match options.entropy_window {
+                Some(entries) => {
+                    let window_bytes = (entries * stats.lenbits as usize).div_ceil(8);
+                    calculate_windowed_entropy(writer_buffer, window_bytes)
+                }
+                None => Vec::new(),
+            };
+
+            // reduce memory usage from leftover analyzer.
+            stats.value_counts.shrink_to_fit();
+            let constant_value =
+                FieldMetrics::constant_value_of(&stats.value_counts, stats.value_counts_overflowed);
+            (
+                stats.full_path.clone(),
+                FieldMetrics {
+                    name: stats.name.clone(),
+                    full_path: stats.full_path.clone(),
+                    entropy,
+                    windowed_entropy,
+                    lz_matches: lz_matches as u64,
+                    bit_counts: stats.bit_counts.clone(),
+                    value_counts: stats.value_counts.clone(),
+                    value_counts_overflowed: stats.value_counts_overflowed,
+                    is_constant: constant_value.is_some(),
+                    constant_value,
+                    // Fields which don't count values have no transitions either.
+                    value_transitions: stats
+                        .value_transitions
+                        .clone()
+                        .filter(|_| !stats.value_counts.is_empty()),
+                    depth: stats.depth,
+                    count: stats.count,
+                    lenbits: stats.lenbits,
+                    bit_order: stats.bit_order,
+                    interpretation: stats.interpretation,
+                    reserved: stats.reserved,
+                    estimated_size: estimated_size as u64,
+                    zstd_size: actual_size,
+                    entropy_coded_size,
+                    entropy_order1,
+                    run_stats,
+                    reference_size: None,
+                    original_size: writer_buffer.len() as u64,
+                },
+            )
+        })
+        .collect();
+    let field_metrics: AHashMap<String, FieldMetrics> = field_metrics.into_iter().collect();
+
+    // Process split group comparisons
+    let split_comparisons = calc_split_comparisons(
+        &mut analyzer.field_states,
+        &analyzer.schema.analysis.split_groups,
+        &field_metrics,
+        analyzer.compression_options.clone(),
+    )?;
+
+    // Process custom group comparisons
+    let mut custom_comparisons = analyze_custom_comparisons(
+        analyzer.schema,
+        &mut analyzer.field_states,
+        analyzer.compression_options.clone(),
+    )?;
+
+    // Split comparisons of more than 2 groups, stored alongside the custom comparisons
+    custom_comparisons.extend(analyze_multi_split_comparisons(
+        &analyzer.schema.analysis.split_groups,
+        &mut analyzer.field_states,
+        &analyzer.compression_options,
+    )?);
+
+    // Automatic split searches, stored alongside the custom comparisons
+    custom_comparisons.extend(analyze_auto_splits(
+        analyzer.schema,
+        &mut analyzer.field_states,
+        analyzer.compression_options.clone(),
+    )?);
+
+    // Built-in comparisons, not defined by the schema
+    if analyzer.compression_options.byte_transpose_comparison {
+        custom_comparisons.extend(analyze_byte_transpose_comparison(
+            &analyzer.entries,
+            analyzer.schema.root.bits,
+            analyzer.compression_options.clone(),
+        )?);
+    }
+
+    Ok(AnalysisResults {
+        file_entropy,
+        file_lz_matches: file_lz_matches as u64,
+        per_field: field_metrics,
+        schema_metadata: analyzer.schema.metadata.clone(),
+        zstd_file_size: analyzer
+            .compression_options
+            .zstd_compressed_size(&analyzer.entries),
+        original_size: analyzer.entries.len() as u64,
+        split_comparisons,
+        custom_comparisons,
+    })
+}
+
+/// Calculates the comparison results between a series of field splits.
+///
+/// This function takes the [`SchemaAnalyzer`]'s intermediate state, that is, the
+/// state of each field (containing the data for each field), a list of split comparisons
+/// to make, and the individual metrics (results) for each field.
+///
+/// This then computes the comparison results for each split.
+///
+/// # Remarks
+/// This API is for internal use. It may change without notice.
+///
+/// # Arguments
+/// * `field_stats` - The current field states (analyzer working state)
+/// * `comparisons` - A slice of [`SplitComparison`] objects defining the splits to compare.
+/// * `field_metrics` - A reference to a hash map of field metrics.
+/// * `compression_options` - The compression options (zstd compression level, etc).
+///
+/// # Returns
+/// A vector of [`SplitComparisonResult`] objects containing the comparison results.
+/// Under [`CompressionOptions::strict`], an error if a group resolves to no fields.
+///
+/// [`SchemaAnalyzer`]: crate::analyzer::SchemaAnalyzer
+fn calc_split_comparisons(
+    field_stats: &mut AHashMap<String, AnalyzerFieldState>,
+    comparisons: &[SplitComparison],
+    field_metrics: &AHashMap<String, FieldMetrics>,
+    compression_options: CompressionOptions,
+) -> Result<Vec<SplitComparisonResult>, ComputeAnalysisResultsError> {
+    let mut split_comparisons = Vec::new();
+    // Comparisons of more than 2 groups are analyzed with the custom comparisons.
+    for comparison in comparisons.iter().filter(|c| !c.is_multi_group()) {
+        // Groups containing only nested groups without fields are 0 bits long,
+        // and would produce empty bytes (and meaningless metrics) without notice.
+        let mut empty_groups = Vec::new();
+        for (group, names) in [
+            ("group_1", &comparison.group_1),
+            ("group_2", &comparison.group_2),
+        ] {
+            let bits: u64 = names
+                .iter()
+                .filter_map(|name| field_stats.get(name))
+                .map(|stats| stats.lenbits as u64)
+                .sum();
+            if bits != 0 {
+                continue;
+            }
+
+            if compression_options.strict {
+                return Err(ComputeAnalysisResultsError::EmptySplitGroup {
+                    comparison: comparison.name.clone(),
+                    group: group.to_string(),
+                });
+            }
+            empty_groups.push(group.to_string());
+        }
+
+        let group1_bytes = split_group_bytes(field_stats, &comparison.group_1);
+        let mut group2_bytes = split_group_bytes(field_stats, &comparison.group_2);
+        let truncated_data_bytes = if comparison.normalize_length {
+            normalize_group_length(&mut group2_bytes, group1_bytes.len())
+        } else {
+            0
+        };
+
+        let mut group1_field_metrics: Vec<FieldComparisonMetrics> = Vec::new();
+        let mut group2_field_metrics: Vec<FieldComparisonMetrics> = Vec::new();
+        for path in &comparison.group_1 {
+            if let Some(metrics) = field_metrics.iter().find(|(_k, v)| v.name == *path) {
+                group1_field_metrics.push(metrics.1.clone().into());
+            }
+        }
+        for path in &comparison.group_2 {
+            if let Some(metrics) = field_metrics.iter().find(|(_k, v)| v.name == *path) {
+                group2_field_metrics.push(metrics.1.clone().into());
+            }
+        }
+
+        let mut result = make_split_comparison_result(
+            comparison.name.clone(),
+            comparison.description.clone(),
+            &group1_bytes,
+            &group2_bytes,
+            &comparison.group_1,
+            &(&comparison.group_2, comparison.normalize_length),
+            group1_field_metrics,
+            group2_field_metrics,
+            compression_options.clone(),
+            comparison.compression_estimation_group_1.clone(),
+            comparison.compression_estimation_group_2.clone(),
+        );
+        result.empty_groups = empty_groups;
+        result.truncated_data_bytes = truncated_data_bytes;
+        split_comparisons.push(result);
+    }
+    Ok(split_comparisons)
+}
+
+/// Concatenates the bytes of the listed fields (or groups) of a split comparison group,
+/// in listed order. Names which aren't fields or groups of the schema are skipped.
+pub(crate) fn split_group_bytes(
+    field_stats: &mut AHashMap<String, AnalyzerFieldState>,
+    names: &[String],
+) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for name in names {
+        if let Some(stats) = field_stats.get_mut(name) {
+            bytes.extend_from_slice(get_writer_buffer(&mut stats.writer));
+        }
+    }
+    bytes
+}
+
+impl AnalysisResults {
+    /// Whether every compared group was compressed by the given ground truth compressor,
+    /// i.e. the file was analyzed with the matching [`Compressor`].
+    ///
+    /// [`Compressor`]: crate::utils::compressor::Compressor
+    pub fn measures_ground_truth(&self, ground_truth: GroundTruth) -> bool {
+        let split_metrics = self
+            .split_comparisons
+            .iter()
+            .flat_map(|comparison| [&comparison.group1_metrics, &comparison.group2_metrics]);
+        let custom_metrics = self.custom_comparisons.iter().flat_map(|comparison| {
+            std::iter::once(&comparison.baseline_metrics).chain(&comparison.group_metrics)
+        });
+        split_metrics
+            .chain(custom_metrics)
+            .all(|metrics| metrics.is_measured_by(ground_truth))
+    }
+
+    /// Converts the file level statistics into a [`FieldMetrics`] object
+    /// which can be used for comparison with parent in places such as the
+    /// print function.
+    pub fn as_field_metrics(&self) -> FieldMetrics {
+        FieldMetrics {
+            name: String::new(),
+            full_path: String::new(),
+            depth: 0,
+            estimated_size: 0,
+            zstd_size: self.zstd_file_size,
+            entropy_coded_size: None,
+            entropy_order1: None,
+            run_stats: None,
+            reference_size: None,
+            original_size: self.original_size,
+            count: 0,
+            lenbits: 0,
+            entropy: self.file_entropy,
+            windowed_entropy: Vec::new(),
+            lz_matches: self.file_lz_matches,
+            bit_counts: Vec::new(),
+            bit_order: BitOrder::Default,
+            value_counts: FxHashMap::new(),
+            value_counts_overflowed: false,
+            is_constant: false,
+            constant_value: None,
+            value_transitions: None,
+            interpretation: ValueInterpretation::default(),
+            reserved: false,
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Printing the results of a single file.
+
+use super::print_comparisons::{
+    concise_print_custom_comparison, concise_print_split_comparison, detailed_print_comparison,
+};
+use super::AnalysisResults;
+use crate::results::{
+    json::write_json, print_auto_split_suggestions, print_bit_waste, print_constant_fields,
+    print_entropy_coded_size, print_field_entropy_order1, print_field_estimated_size,
+    print_field_metrics_bit_stats, print_field_metrics_value_stats, print_field_reference_size,
+    print_near_constant_bits, print_recommendations, print_run_stats,
+    reserved_fields::print_reserved_fields, FieldMetrics, PrintFormat,
+};
+use crate::{results::calculate_percentage, schema::Schema};
+use std::io::{self, Write};
+
+impl AnalysisResults {
+    pub fn print<W: Write>(
+        &self,
+        writer: &mut W,
+        schema: &Schema,
+        format: PrintFormat,
+        skip_misc_stats: bool,
+    ) -> io::Result<()> {
+        match format {
+            PrintFormat::Detailed => {
+                self.print_detailed(writer, schema, &self.as_field_metrics(), skip_misc_stats)
+            }
+            PrintFormat::Concise => {
+                self.print_concise(writer, schema, &self.as_field_metrics(), skip_misc_stats)
+            }
+            PrintFormat::Json => Ok(write_json(self, writer)?),
+        }
+    }
+
+    fn print_detailed<W: Write>(
+        &self,
+        writer: &mut W,
+        schema: &Schema,
+        file_metrics: &FieldMetrics,
+        skip_misc_stats: bool,
+    ) -> io::Result<()> {
+        writeln!(writer, "Schema: {}", self.schema_metadata.name)?;
+        writeln!(writer, "Description: {}", self.schema_metadata.description)?;
+        writeln!(writer, "File Entropy: {:.2} bits", self.file_entropy)?;
+        writeln!(writer, "File LZ Matches: {}", self.file_lz_matches)?;
+        writeln!(writer, "File Original Size: {}", self.original_size)?;
+        writeln!(writer, "File Compressed Size: {}", self.zstd_file_size)?;
+        writeln!(writer, "\nPer-field Metrics (in schema order):")?;
+
+        // Iterate through schema-defined fields in order
+        for field_path in schema.ordered_field_and_group_paths() {
+            self.detailed_print_field(writer, file_metrics, &field_path)?;
+        }
+
+        writeln!(writer, "\nSplit Group Comparisons:")?;
+        for comparison in &self.split_comparisons {
+            detailed_print_comparison(writer, comparison)?;
+        }
+
+        writeln!(writer, "\nCustom Group Comparisons:")?;
+        for comparison in &self.custom_comparisons {
+            concise_print_custom_comparison(writer, comparison)?;
+        }
+
+        print_constant_fields(writer, schema, &self.per_field)?;
+        print_reserved_fields(writer, schema, &self.per_field)?;
+        print_recommendations(writer, schema, &self.per_field)?;
+        print_auto_split_suggestions(writer, schema, &self.per_field, |name| {
+            self.custom_comparisons
+                .iter()
+                .find(|comparison| comparison.name == name)
+                .map(|comparison| {
+                    (
+                        &comparison.baseline_metrics,
+                        comparison.group_names.as_slice(),
+                        comparison.group_metrics.as_slice(),
+                    )
+                })
+        })?;
+
+        if !skip_misc_stats {
+            writeln!(writer, "\nField Value Stats: [as `value: probability %`]")?;
+            for field_path in schema.ordered_field_and_group_paths() {
+                self.concise_print_field_value_stats(writer, &field_path)?;
+            }
+
+            writeln!(
+                writer,
+                "\nField Bit Stats: [as `(zeros/ones) (percentage %) entropy`]"
+            )?;
+            for field_path in schema.ordered_field_and_group_paths() {
+                self.concise_print_field_bit_stats(writer, &field_path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn detailed_print_field<W: Write>(
+        &self,
+        writer: &mut W,
+        file_metrics: &FieldMetrics,
+        field_path: &str,
+    ) -> io::Result<()> {
+        if let Some(field) = self.per_field.get(field_path) {
+            // Indent based on field depth to show hierarchy
+            let indent = "  ".repeat(field.depth);
+            let parent_stats = field.parent_metrics_or(self, file_metrics);
+
+            // Calculate percentages
+            writeln!(
+                writer,
+                "{}{}: {:.2} bit entropy, {} LZ 3 Byte matches ({:.2}%)",
+                indent,
+                field.name,
+                field.entropy,
+                field.lz_matches,
+                calculate_percentage(field.lz_matches as f64, parent_stats.lz_matches as f64)
+            )?;
+            let padding = format!("{}{}", indent, field.name).len() + 2; // +2 for ": "
+            writeln!(
+                writer,
+                "{:padding$}Sizes: ZStandard/Original: {}/{} ({:.2}%/{:.2}%)",
+                "",
+                field.zstd_size,
+                field.original_size,
+                calculate_percentage(field.zstd_size as f64, parent_stats.zstd_size as f64),
+                calculate_percentage(
+                    field.original_size as f64,
+                    parent_stats.original_size as f64
+                )
+            )?;
+            print_field_estimated_size(writer, padding, field)?;
+            print_entropy_coded_size(writer, padding, field)?;
+            print_field_entropy_order1(writer, padding, field)?;
+            print_run_stats(writer, padding, field)?;
+            print_field_reference_size(writer, padding, field)?;
+            writeln!(
+                writer,
+                "{:padding$}{} bit, {} unique values, {:?}",
+                "",
+                field.lenbits,
+                field.value_counts.len(),
+                field.bit_order
+            )?;
+            print_near_constant_bits(writer, padding, field)?;
+            print_bit_waste(writer, padding, field)?;
+        }
+
+        Ok(())
+    }
+
+    fn print_concise<W: Write>(
+        &self,
+        writer: &mut W,
+        schema: &Schema,
+        file_metrics: &FieldMetrics,
+        skip_misc_stats: bool,
+    ) -> io::Result<()> {
+        writeln!(writer, "Schema: {}", self.schema_metadata.name)?;
+        writeln!(
+            writer,
+            "File: {:.2}bpb, {} LZ, {}/{} ({:.2}%/{:.2}%) (zstd/orig)",
+            self.file_entropy,
+            self.file_lz_matches,
+            self.zstd_file_size,
+            self.original_size,
+            calculate_percentage(self.zstd_file_size as f64, self.original_size as f64),
+            100.0
+        )?;
+
+        writeln!(writer, "\nField Metrics:")?;
+        for field_path in schema.ordered_field_and_group_paths() {
+            self.concise_print_field(writer, file_metrics, &field_path)?;
+        }
+
+        writeln!(writer, "\nSplit Group Comparisons:")?;
+        for comparison in &self.split_comparisons {
+            concise_print_split_comparison(writer, comparison)?;
+        }
+
+        writeln!(writer, "\nCustom Group Comparisons:")?;
+        for comparison in &self.custom_comparisons {
+            concise_print_custom_comparison(writer, comparison)?;
+        }
+
+        print_reserved_fields(writer, schema, &self.per_field)?;
+        print_recommendations(writer, schema, &self.per_field)?;
+        print_auto_split_suggestions(writer, schema, &self.per_field, |name| {
+            self.custom_comparisons
+                .iter()
+                .find(|comparison| comparison.name == name)
+                .map(|comparison| {
+                    (
+                        &comparison.baseline_metrics,
+                        comparison.group_names.as_slice(),
+                        comparison.group_metrics.as_slice(),
+                    )
+                })
+        })?;
+
+        if !skip_misc_stats {
+            writeln!(writer, "\nField Value Stats: [as `value: probability %`]")?;
+            for field_path in schema.ordered_field_and_group_paths() {
+                self.concise_print_field_value_stats(writer, &field_path)?;
+            }
+
+            writeln!(
+                writer,
+                "\nField Bit Stats: [as `(zeros/ones) (percentage %) entropy`]"
+            )?;
+            for field_path in schema.ordered_field_and_group_paths() {
+                self.concise_print_field_bit_stats(writer, &field_path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn concise_print_field<W: Write>(
+        &self,
+        writer: &mut W,
+        file_metrics: &FieldMetrics,
+        field_path: &str,
+    ) -> io::Result<()> {
+        if let Some(field) = self.per_field.get(field_path) {
+            let indent = "  ".repeat(field.depth);
+            let parent_stats = field.parent_metrics_or(self, file_metrics);
+
+            writeln!(
+                writer,
+                "{}{}: {:.2}bpb, {} LZ ({:.2}%), {}/{} ({:.2}%/{:.2}%) (zstd/orig), {}bit",
+                indent,
+                field.name,
+                field.entropy,
+                field.lz_matches,
+                calculate_percentage(field.lz_matches as f64, parent_stats.lz_matches as f64),
+                field.zstd_size,
+                field.original_size,
+                calculate_percentage(field.zstd_size as f64, parent_stats.zstd_size as f64),
+                calculate_percentage(
+                    field.original_size as f64,
+                    parent_stats.original_size as f64
+                ),
+                field.lenbits
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn concise_print_field_value_stats<W: Write>(
+        &self,
+        writer: &mut W,
+        field_path: &str,
+    ) -> io::Result<()> {
+        if let Some(field) = self.per_field.get(field_path) {
+            print_field_metrics_value_stats(writer, field)?;
+        }
+
+        Ok(())
+    }
+
+    fn concise_print_field_bit_stats<W: Write>(
+        &self,
+        writer: &mut W,
+        field_path: &str,
+    ) -> io::Result<()> {
+        if let Some(field) = self.per_field.get(field_path) {
+            print_field_metrics_bit_stats(writer, field)?;
+        }
+
+        Ok(())
+    }
+}
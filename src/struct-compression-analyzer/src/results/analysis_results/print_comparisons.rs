@@ -0,0 +1,218 @@
+//! Printing the split and custom comparisons of a single file.
+
+use crate::results::{
+    print_brotli_size, print_deflate_size, print_empty_split_groups, print_entropy_order1,
+    print_estimate_breakdown, print_reference_size, print_residual_entropy,
+    print_truncated_data_warning, print_zero_estimate_warning, print_zstd_compress_time,
+    print_zstd_dict_size,
+};
+use crate::{
+    comparison::{compare_groups::GroupComparisonResult, split_comparison::SplitComparisonResult},
+    results::calculate_percentage,
+};
+use std::io::{self, Write};
+
+pub(super) fn detailed_print_comparison<W: Write>(
+    writer: &mut W,
+    comparison: &SplitComparisonResult,
+) -> io::Result<()> {
+    concise_print_split_comparison(writer, comparison)
+}
+
+pub(super) fn concise_print_custom_comparison<W: Write>(
+    writer: &mut W,
+    comparison: &GroupComparisonResult,
+) -> io::Result<()> {
+    let base_lz = comparison.baseline_metrics.lz_matches;
+    let base_entropy = comparison.baseline_metrics.entropy;
+    let base_zstd = comparison.baseline_metrics.zstd_size;
+    let base_estimated = comparison.baseline_metrics.estimated_size;
+    let base_size = comparison.baseline_metrics.original_size;
+
+    writeln!(writer, "  {}: {}", comparison.name, comparison.description)?;
+    writeln!(writer, "    Base Group:")?;
+    writeln!(writer, "      Size: {}", base_size)?;
+    writeln!(
+        writer,
+        "      LZ, Entropy: ({}, {:.2})",
+        base_lz, base_entropy
+    )?;
+    if comparison.baseline_metrics.has_estimate {
+        writeln!(
+            writer,
+            "      Estimate/Zstd: {}/{}",
+            base_estimated, base_zstd
+        )?;
+    } else {
+        writeln!(writer, "      Zstd: {}", base_zstd)?;
+    }
+    print_estimate_breakdown(writer, "      ", &comparison.baseline_metrics)?;
+    print_brotli_size(writer, "      ", &comparison.baseline_metrics)?;
+    print_deflate_size(writer, "      ", &comparison.baseline_metrics)?;
+    print_zstd_dict_size(writer, "      ", &comparison.baseline_metrics)?;
+    print_entropy_order1(writer, "      ", &comparison.baseline_metrics)?;
+    print_reference_size(writer, "      ", &comparison.baseline_metrics)?;
+    print_zstd_compress_time(writer, "      ", &comparison.baseline_metrics)?;
+    print_zero_estimate_warning(writer, "      ", &comparison.baseline_metrics)?;
+
+    for (i, (group_name, metrics)) in comparison
+        .group_names
+        .iter()
+        .zip(&comparison.group_metrics)
+        .enumerate()
+    {
+        let comp_lz = metrics.lz_matches;
+        let comp_entropy = metrics.entropy;
+        let comp_zstd = metrics.zstd_size;
+        let comp_estimated = metrics.estimated_size;
+        let comp_size = metrics.original_size;
+
+        let ratio_zstd = calculate_percentage(comp_zstd as f64, base_zstd as f64);
+        let diff_zstd = comparison.differences[i].zstd_size;
+
+        writeln!(writer, "\n    {} Group:", group_name)?;
+        writeln!(writer, "      Size: {}", comp_size)?;
+        writeln!(
+            writer,
+            "      LZ, Entropy: ({}, {:.2})",
+            comp_lz, comp_entropy
+        )?;
+        if metrics.has_estimate {
+            writeln!(
+                writer,
+                "      Estimate/Zstd: {}/{}",
+                comp_estimated, comp_zstd
+            )?;
+        } else {
+            writeln!(writer, "      Zstd: {}", comp_zstd)?;
+        }
+        print_estimate_breakdown(writer, "      ", metrics)?;
+        print_brotli_size(writer, "      ", metrics)?;
+        print_deflate_size(writer, "      ", metrics)?;
+        print_zstd_dict_size(writer, "      ", metrics)?;
+        print_entropy_order1(writer, "      ", metrics)?;
+        print_reference_size(writer, "      ", metrics)?;
+        print_zstd_compress_time(writer, "      ", metrics)?;
+        print_zero_estimate_warning(writer, "      ", metrics)?;
+        writeln!(writer, "      Ratio zstd: {:.1}%", ratio_zstd)?;
+        writeln!(writer, "      Diff zstd: {}", diff_zstd)?;
+
+        if base_size != comp_size {
+            writeln!(writer, "      [WARNING!!] Sizes of base and comparison groups don't match!! They may vary by a few bytes due to padding.")?;
+            writeln!(writer, "      [WARNING!!] However if they vary extremely, your groups may be incorrect. base: {}, {}: {}", base_size, group_name, comp_size)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) fn concise_print_split_comparison<W: Write>(
+    writer: &mut W,
+    comparison: &SplitComparisonResult,
+) -> io::Result<()> {
+    let base_lz = comparison.group1_metrics.lz_matches;
+    let size_orig = comparison.group1_metrics.original_size;
+    let size_comp = comparison.group2_metrics.original_size;
+    let base_entropy = comparison.group1_metrics.entropy;
+
+    let base_zstd = comparison.group1_metrics.zstd_size;
+    let base_estimated = comparison.group1_metrics.estimated_size;
+
+    let comp_lz = comparison.group2_metrics.lz_matches;
+    let comp_entropy = comparison.group2_metrics.entropy;
+
+    let comp_zstd = comparison.group2_metrics.zstd_size;
+    let comp_estimated = comparison.group2_metrics.estimated_size;
+    let ratio_zstd = calculate_percentage(comp_zstd as f64, base_zstd as f64);
+    let diff_zstd = comparison.difference.zstd_size;
+
+    writeln!(writer, "  {}: {}", comparison.name, comparison.description)?;
+    writeln!(writer, "    Original Size: {}", size_orig)?;
+    writeln!(
+        writer,
+        "    Base LZ, Entropy: ({}, {:.2}):",
+        base_lz, base_entropy
+    )?;
+    writeln!(
+        writer,
+        "    Comp LZ, Entropy: ({}, {:.2}):",
+        comp_lz, comp_entropy
+    )?;
+    writeln!(
+        writer,
+        "    Base Group LZ, Entropy: ({:?}, {:?})",
+        comparison
+            .baseline_comparison_metrics
+            .iter()
+            .map(|m| m.lz_matches)
+            .collect::<Vec<_>>(),
+        comparison
+            .baseline_comparison_metrics
+            .iter()
+            .map(|m| format!("{:.2}", m.entropy))
+            .collect::<Vec<_>>()
+    )?;
+    writeln!(
+        writer,
+        "    Comp Group LZ, Entropy: ({:?}, {:?})",
+        comparison
+            .split_comparison_metrics
+            .iter()
+            .map(|m| m.lz_matches)
+            .collect::<Vec<_>>(),
+        comparison
+            .split_comparison_metrics
+            .iter()
+            .map(|m| format!("{:.2}", m.entropy))
+            .collect::<Vec<_>>()
+    )?;
+
+    if comparison.group1_metrics.has_estimate {
+        writeln!(
+            writer,
+            "    Base (est/zstd): {}/{}",
+            base_estimated, base_zstd
+        )?;
+    } else {
+        writeln!(writer, "    Base (zstd): {}", base_zstd)?;
+    }
+    print_estimate_breakdown(writer, "    Base ", &comparison.group1_metrics)?;
+    print_brotli_size(writer, "    Base ", &comparison.group1_metrics)?;
+    print_deflate_size(writer, "    Base ", &comparison.group1_metrics)?;
+    print_zstd_dict_size(writer, "    Base ", &comparison.group1_metrics)?;
+    print_entropy_order1(writer, "    Base ", &comparison.group1_metrics)?;
+    print_reference_size(writer, "    Base ", &comparison.group1_metrics)?;
+    print_zstd_compress_time(writer, "    Base ", &comparison.group1_metrics)?;
+    print_zero_estimate_warning(writer, "    Base ", &comparison.group1_metrics)?;
+
+    if comparison.group2_metrics.has_estimate {
+        writeln!(
+            writer,
+            "    Comp (est/zstd): {}/{}",
+            comp_estimated, comp_zstd
+        )?;
+    } else {
+        writeln!(writer, "    Comp (zstd): {}", comp_zstd)?;
+    }
+    print_estimate_breakdown(writer, "    Comp ", &comparison.group2_metrics)?;
+    print_brotli_size(writer, "    Comp ", &comparison.group2_metrics)?;
+    print_deflate_size(writer, "    Comp ", &comparison.group2_metrics)?;
+    print_zstd_dict_size(writer, "    Comp ", &comparison.group2_metrics)?;
+    print_entropy_order1(writer, "    Comp ", &comparison.group2_metrics)?;
+    print_reference_size(writer, "    Comp ", &comparison.group2_metrics)?;
+    print_zstd_compress_time(writer, "    Comp ", &comparison.group2_metrics)?;
+    print_zero_estimate_warning(writer, "    Comp ", &comparison.group2_metrics)?;
+
+    writeln!(writer, "    Ratio (zstd): {}", ratio_zstd)?;
+    writeln!(writer, "    Diff (zstd): {}", diff_zstd)?;
+    print_residual_entropy(writer, comparison.residual_entropy.as_ref())?;
+    print_empty_split_groups(writer, &comparison.empty_groups)?;
+    print_truncated_data_warning(writer, comparison.truncated_data_bytes)?;
+
+    if comparison.size_mismatch_bytes != 0 {
+        writeln!(writer, "    [WARNING!!] Sizes of both groups in bytes don't match!! They may vary by a few bytes due to padding.")?;
+        writeln!(writer, "    [WARNING!!] However if they vary extremely, your groups may be incorrect. group1: {}, group2: {} ({} bytes apart)", size_orig, size_comp, comparison.size_mismatch_bytes)?;
+    }
+
+    Ok(())
+}
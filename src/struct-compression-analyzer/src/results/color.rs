@@ -0,0 +1,81 @@
+//! ANSI coloring of the zstd ratios of comparisons in the printed merged results.
+//!
+//! When scanning many comparisons in a terminal, the ratio of each group against its baseline
+//! is colored: green below [`GOOD_RATIO_THRESHOLD`] (the split helps), red above
+//! [`BAD_RATIO_THRESHOLD`] (it hurts) and left as is otherwise. Whether colors are used is
+//! controlled by the [`ColorMode`] passed to [`MergedAnalysisResults::print`].
+//!
+//! [`MergedAnalysisResults::print`]: super::merged_analysis_results::MergedAnalysisResults::print
+
+use derive_more::FromStr;
+use std::io::{stdout, IsTerminal};
+
+/// Ratios (in %, compressed size of the group against its baseline) below this are green.
+pub const GOOD_RATIO_THRESHOLD: f64 = 99.0;
+
+/// Ratios (in %, compressed size of the group against its baseline) above this are red.
+pub const BAD_RATIO_THRESHOLD: f64 = 101.0;
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Whether to color the printed ratios.
+///
+/// Parsed case-insensitively from the variant name, e.g. `never`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromStr)]
+pub enum ColorMode {
+    /// Color if stdout is a terminal, and the `NO_COLOR` environment variable isn't set.
+    #[default]
+    Auto,
+    /// Always color, e.g. when piping into `less -R`.
+    Always,
+    /// Never color, e.g. when writing to a file.
+    Never,
+}
+
+impl ColorMode {
+    /// Whether colors should be written under this mode.
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorMode::Auto => std::env::var_os("NO_COLOR").is_none() && stdout().is_terminal(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+/// Wraps the formatted `text` of a `ratio` (in %) in the color of its threshold,
+/// or returns it unchanged if `colored` is false or the ratio is neutral.
+pub(crate) fn color_ratio(text: String, ratio: f64, colored: bool) -> String {
+    let color = if ratio < GOOD_RATIO_THRESHOLD {
+        GREEN
+    } else if ratio > BAD_RATIO_THRESHOLD {
+        RED
+    } else {
+        return text;
+    };
+
+    match colored {
+        true => format!("{color}{text}{RESET}"),
+        false => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratios_are_colored_by_threshold() {
+        assert_eq!(color_ratio("90%".into(), 90.0, true), "\x1b[32m90%\x1b[0m");
+        assert_eq!(
+            color_ratio("110%".into(), 110.0, true),
+            "\x1b[31m110%\x1b[0m"
+        );
+        assert_eq!(color_ratio("100%".into(), 100.0, true), "100%");
+        assert_eq!(color_ratio("90%".into(), 90.0, false), "90%");
+        assert!(!ColorMode::Never.enabled());
+        assert!(ColorMode::Always.enabled());
+    }
+}
@@ -0,0 +1,291 @@
+//! Measures redundancy shared between multiple files.
+//!
+//! Per-file LZ match counts and zstd sizes can't see data which repeats across files,
+//! e.g. identical headers or shared palettes. When assets are packed into a single archive
+//! however, the compressor can exploit that redundancy.
+//!
+//! This module concatenates the bytes of each field/group from (a sample of) multiple files
+//! and measures the combined stream, so the gain from cross-file redundancy can be reported
+//! separately from the per-file results.
+//!
+//! # Core Types
+//!
+//! - [`CrossFileSample`]: The raw bytes of a single file, retained after analysis.
+//! - [`CrossFileResults`]: The per-file totals versus the combined stream.
+//! - [`CrossFileMetrics`]: Metrics for a single field/group (or the whole file).
+//!
+//! # Remarks
+//!
+//! Retaining the bytes of every field for every file is expensive, since groups overlap
+//! with their children, so this is opt-in.
+
+use super::{analysis_results::AnalysisResults, calculate_percentage};
+use crate::{
+    analyzer::{CompressionOptions, SchemaAnalyzer},
+    schema::Schema,
+    utils::{analyze_utils::get_writer_buffer, constants::CHILD_MARKER},
+};
+use ahash::{AHashMap, RandomState};
+use lossless_transform_utils::match_estimator::estimate_num_lz_matches_fast;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// The raw bytes of a single analyzed file, retained for cross-file analysis.
+#[derive(Clone, Default)]
+pub struct CrossFileSample {
+    /// Raw data as fed into the analyzer.
+    pub entries: Vec<u8>,
+    /// Field path → bytes of the field/group in this file.
+    pub per_field: AHashMap<String, Vec<u8>>,
+}
+
+/// Metrics for a single stream, measured per file (and summed) as well as after
+/// concatenating the data of all files.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct CrossFileMetrics {
+    /// Sum of the LZ matches counted in each file individually.
+    pub per_file_lz_matches: u64,
+    /// LZ matches counted in the concatenation of all files.
+    pub combined_lz_matches: u64,
+    /// Sum of the zstd sizes of each file compressed individually.
+    pub per_file_zstd_size: u64,
+    /// Size of the concatenation of all files when compressed with zstd.
+    pub combined_zstd_size: u64,
+    /// Total size of the uncompressed data.
+    pub original_size: u64,
+}
+
+impl CrossFileMetrics {
+    /// Number of LZ matches only found when the files are concatenated.
+    pub fn cross_file_lz_matches(&self) -> i64 {
+        self.combined_lz_matches as i64 - self.per_file_lz_matches as i64
+    }
+
+    /// Number of bytes saved by compressing all files as one stream.
+    pub fn cross_file_zstd_savings(&self) -> i64 {
+        self.per_file_zstd_size as i64 - self.combined_zstd_size as i64
+    }
+}
+
+/// Results of measuring the concatenation of multiple files.
+#[derive(Clone, Default)]
+pub struct CrossFileResults {
+    /// Number of files included in the combined stream.
+    pub file_count: usize,
+    /// Metrics for the whole file.
+    pub file: CrossFileMetrics,
+    /// Field path → metrics for each field/group.
+    pub per_field: AHashMap<String, CrossFileMetrics>,
+}
+
+impl CrossFileSample {
+    /// Retains the bytes of all fields of an analyzer which has ingested a whole file.
+    ///
+    /// # Arguments
+    /// * `analyzer` - The analyzer to copy the bytes out of.
+    pub fn from_analyzer(analyzer: &mut SchemaAnalyzer) -> Self {
+        let mut per_field = AHashMap::with_capacity(analyzer.field_states.len());
+        for state in analyzer.field_states.values_mut() {
+            per_field.insert(
+                state.full_path.clone(),
+                get_writer_buffer(&mut state.writer).to_vec(),
+            );
+        }
+
+        Self {
+            entries: analyzer.entries.clone(),
+            per_field,
+        }
+    }
+}
+
+impl CrossFileResults {
+    /// Measures the concatenation of the given samples.
+    ///
+    /// # Arguments
+    /// * `samples` - The retained bytes of each file.
+    /// * `results` - The per-file results for the same files, in the same order as `samples`.
+    ///   Any items past the end of `samples` are ignored.
+    /// * `compression_options` - The zstd level and strategy used to compress the combined streams.
+    pub fn from_samples(
+        samples: &[CrossFileSample],
+        results: &[AnalysisResults],
+        compression_options: &CompressionOptions,
+    ) -> Self {
+        let results = &results[..samples.len().min(results.len())];
+        let samples = &samples[..results.len()];
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        let file = measure_combined(
+            samples.iter().map(|s| s.entries.as_slice()),
+            results
+                .iter()
+                .map(|r| (r.file_lz_matches, r.zstd_file_size)),
+            compression_options,
+        );
+
+        let per_field = samples[0]
+            .per_field
+            .par_iter()
+            .map(|(path, _)| {
+                let metrics = measure_combined(
+                    samples
+                        .iter()
+                        .filter_map(|s| s.per_field.get(path).map(|b| b.as_slice())),
+                    results
+                        .iter()
+                        .filter_map(|r| r.per_field.get(path))
+                        .map(|m| (m.lz_matches, m.zstd_size)),
+                    compression_options,
+                );
+                (path.clone(), metrics)
+            })
+            // Need to explicitly set inner AHashMap type, because AHashMap not supported.
+            .collect::<HashMap<String, CrossFileMetrics, RandomState>>()
+            .into();
+
+        Self {
+            file_count: samples.len(),
+            file,
+            per_field,
+        }
+    }
+
+    /// Print the cross-file results, with fields in schema order.
+    pub fn print<W: Write>(&self, writer: &mut W, schema: &Schema) -> io::Result<()> {
+        writeln!(
+            writer,
+            "Cross-File Redundancy ({} files): [as `per file -> combined`]",
+            self.file_count
+        )?;
+        print_metrics(writer, "File", 0, &self.file)?;
+
+        writeln!(writer, "\nField Metrics:")?;
+        for field_path in schema.ordered_field_and_group_paths() {
+            if let Some(metrics) = self.per_field.get(&field_path) {
+                let depth = field_path.matches(CHILD_MARKER).count();
+                let name = field_path
+                    .rsplit(CHILD_MARKER)
+                    .next()
+                    .unwrap_or(&field_path);
+                print_metrics(writer, name, depth, metrics)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn measure_combined<'a>(
+    streams: impl Iterator<Item = &'a [u8]>,
+    per_file: impl Iterator<Item = (u64, u64)>,
+    compression_options: &CompressionOptions,
+) -> CrossFileMetrics {
+    let combined: Vec<u8> = streams.flatten().copied().collect();
+    let (per_file_lz_matches, per_file_zstd_size) = per_file
+        .fold((0, 0), |(lz, zstd), (file_lz, file_zstd)| {
+            (lz + file_lz, zstd + file_zstd)
+        });
+
+    CrossFileMetrics {
+        per_file_lz_matches,
+        combined_lz_matches: estimate_num_lz_matches_fast(&combined) as u64,
+        per_file_zstd_size,
+        combined_zstd_size: compression_options.zstd_compressed_size(&combined),
+        original_size: combined.len() as u64,
+    }
+}
+
+fn print_metrics<W: Write>(
+    writer: &mut W,
+    name: &str,
+    depth: usize,
+    metrics: &CrossFileMetrics,
+) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+    writeln!(
+        writer,
+        "{}{}: {} -> {} LZ ({:+}), {} -> {} zstd ({} saved, {:.2}%), {} orig",
+        indent,
+        name,
+        metrics.per_file_lz_matches,
+        metrics.combined_lz_matches,
+        metrics.cross_file_lz_matches(),
+        metrics.per_file_zstd_size,
+        metrics.combined_zstd_size,
+        metrics.cross_file_zstd_savings(),
+        calculate_percentage(
+            metrics.combined_zstd_size as f64,
+            metrics.per_file_zstd_size as f64
+        ),
+        metrics.original_size
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::analyzer_with_entries;
+
+    fn analyze(schema: &Schema, data: &[u8]) -> (AnalysisResults, CrossFileSample) {
+        let mut analyzer =
+            analyzer_with_entries(schema, CompressionOptions::default(), data.chunks(2));
+        let results = analyzer.generate_results().unwrap();
+        (results, CrossFileSample::from_analyzer(&mut analyzer))
+    }
+
+    #[test]
+    fn detects_redundancy_between_identical_files() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    a: 8
+    b: 8
+"#,
+        )
+        .unwrap();
+
+        // Non-repeating data, so nothing to find within a single file.
+        let data: Vec<u8> = (0..=255u8).flat_map(|x| [x, x.wrapping_mul(7)]).collect();
+        let (results_1, sample_1) = analyze(&schema, &data);
+        let (results_2, sample_2) = analyze(&schema, &data);
+
+        let cross = CrossFileResults::from_samples(
+            &[sample_1, sample_2],
+            &[results_1.clone(), results_2],
+            &CompressionOptions::default(),
+        );
+
+        assert_eq!(cross.file_count, 2);
+        assert_eq!(cross.file.original_size, data.len() as u64 * 2);
+        assert_eq!(cross.file.per_file_zstd_size, results_1.zstd_file_size * 2);
+        assert!(cross.file.cross_file_zstd_savings() > 0);
+        assert!(cross.file.cross_file_lz_matches() > 0);
+
+        let a = cross.per_field.get("a").unwrap();
+        assert_eq!(a.original_size, 512);
+        assert!(a.cross_file_zstd_savings() > 0);
+    }
+
+    #[test]
+    fn ignores_results_without_samples() {
+        let schema =
+            Schema::from_yaml("version: '1.0'\nroot: { type: group, fields: { a: 8, b: 8 } }")
+                .unwrap();
+        let (results, sample) = analyze(&schema, &[1, 2, 3, 4]);
+
+        let cross = CrossFileResults::from_samples(
+            &[sample],
+            &[results.clone(), results],
+            &CompressionOptions::default().with_zstd_compression_level(3),
+        );
+        assert_eq!(cross.file_count, 1);
+        assert_eq!(cross.file.original_size, 4);
+    }
+}
@@ -0,0 +1,396 @@
+//! A report card of how well the size estimator predicts the actual compressed sizes.
+//!
+//! Combines the accuracy metrics of every comparison of a set of merged results into a
+//! single score and grade, so the trustworthiness of the current estimator settings
+//! (e.g. the `lz_match_multiplier` and `entropy_multiplier` of the schema) can be seen at a glance.
+//!
+//! Each comparison is scored on:
+//!
+//! - **Agreement**: How often the estimator picks the same group as the ground truth compressor.
+//! - **Relative error**: The mean absolute error of the estimated sizes, relative to the mean
+//!   actual size.
+//! - **Rank correlation**: Spearman's rank correlation between the estimated and actual size
+//!   ratios of every group against its baseline, across all files. This measures whether the
+//!   estimator orders the groups and files the same way, regardless of its absolute accuracy.
+//!
+//! The score of a comparison is the mean of `agreement`, `1 - relative error` (clamped to 0)
+//! and the rank correlation (clamped to 0, if available), which is then graded from `A` to `F`.
+//! The paired effect size (Cohen's d) of both the estimated and actual ratios is reported
+//! alongside, showing whether the estimator sees an improvement as consistently as the compressor.
+
+use super::merged_analysis_results::MergedAnalysisResults;
+use crate::comparison::{
+    stats::{calculate_effect_size, calculate_spearman_correlation, EffectSize},
+    GroundTruth, GroupComparisonMetrics,
+};
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// The accuracy of the estimator on a single comparison.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ComparisonScore {
+    /// Name of the comparison.
+    pub name: String,
+    /// `split` or `custom`.
+    pub kind: &'static str,
+    /// Share (`0.0..=1.0`) of files where the estimator agrees with the ground truth
+    /// on the better (split) or best (custom) group.
+    pub agreement: f64,
+    /// Mean absolute difference between the estimated and actual sizes of all groups, in bytes.
+    pub mean_absolute_error: f64,
+    /// [`Self::mean_absolute_error`] relative to the mean actual size of the groups.
+    pub relative_error: f64,
+    /// Spearman's rank correlation between the estimated and actual size ratios of the groups
+    /// against their baseline. [`None`] if there are fewer than 2 ratios, or all ratios are equal.
+    pub rank_correlation: Option<f64>,
+    /// Effect sizes of each group against its baseline, according to the estimator and the
+    /// ground truth compressor.
+    pub effect_sizes: Vec<GroupEffectSizes>,
+    /// Combined score (`0.0..=1.0`), see the [module documentation](self).
+    pub score: f64,
+    /// Letter grade of [`Self::score`].
+    pub grade: char,
+}
+
+/// The effect size of a group against its baseline, according to the estimated
+/// and actual sizes.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GroupEffectSizes {
+    /// Name of the group.
+    pub group: String,
+    /// Cohen's d of the estimated size ratios. [`None`] if there are fewer than 2 files
+    /// with a non-empty baseline.
+    pub estimated_cohens_d: Option<f64>,
+    /// Cohen's d of the actual size ratios. [`None`] if there are fewer than 2 files.
+    pub actual_cohens_d: Option<f64>,
+}
+
+/// The accuracy of the estimator on all comparisons of a set of merged results.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EstimatorReportCard {
+    /// Name of the compressor the estimates are compared against.
+    pub ground_truth: &'static str,
+    /// Number of files the results were merged from.
+    pub file_count: usize,
+    /// Score of each comparison; split comparisons first, then custom comparisons.
+    pub comparisons: Vec<ComparisonScore>,
+    /// Mean score of all comparisons. [`None`] if there are no comparisons.
+    pub overall_score: Option<f64>,
+    /// Letter grade of [`Self::overall_score`].
+    pub overall_grade: Option<char>,
+}
+
+impl EstimatorReportCard {
+    /// Scores the estimator on every comparison of the merged results.
+    pub fn new(merged: &MergedAnalysisResults) -> Self {
+        let results = &merged.original_results;
+        let ground_truth = merged.ground_truth;
+
+        let split = merged.split_comparisons.iter().enumerate().map(|(x, c)| {
+            let groups = results.iter().filter_map(|result| {
+                let comparison = result.split_comparisons.get(x)?;
+                Some((comparison.group1_metrics, [comparison.group2_metrics]))
+            });
+            score_comparison(
+                &c.name,
+                "split",
+                c.group_estimate_zstd_agreement_percentage / 100.0,
+                &["group_2".to_string()],
+                groups,
+                ground_truth,
+            )
+        });
+
+        let custom = merged.custom_comparisons.iter().enumerate().map(|(x, c)| {
+            let groups = results.iter().filter_map(|result| {
+                let comparison = result.custom_comparisons.get(x)?;
+                Some((
+                    comparison.baseline_metrics,
+                    comparison.group_metrics.iter().copied(),
+                ))
+            });
+            score_comparison(
+                &c.name,
+                "custom",
+                c.estimate_zstd_agreement_percentage,
+                &c.group_names,
+                groups,
+                ground_truth,
+            )
+        });
+
+        let comparisons: Vec<ComparisonScore> = split.chain(custom).collect();
+        let overall_score = (!comparisons.is_empty())
+            .then(|| comparisons.iter().map(|c| c.score).sum::<f64>() / comparisons.len() as f64);
+
+        Self {
+            ground_truth: ground_truth.name(),
+            file_count: merged.merged_file_count,
+            comparisons,
+            overall_score,
+            overall_grade: overall_score.map(grade),
+        }
+    }
+
+    /// Prints the report card as a table, one row per comparison.
+    pub fn print<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "Estimator Report Card ({} files, against {}):",
+            self.file_count, self.ground_truth
+        )?;
+        writeln!(
+            writer,
+            "{:<30}| Kind   | Agree  | Rel Err | Rank Corr | Score | Grade",
+            "Comparison"
+        )?;
+        writeln!(
+            writer,
+            "------------------------------|--------|--------|---------|-----------|-------|------"
+        )?;
+
+        for c in &self.comparisons {
+            let rank_correlation = c
+                .rank_correlation
+                .map_or_else(|| "N/A".to_string(), |rho| format!("{rho:.2}"));
+            writeln!(
+                writer,
+                "{:<30}| {:<7}| {:>5.1}% | {:>6.1}% | {:>9} | {:>5.2} | {}",
+                c.name,
+                c.kind,
+                c.agreement * 100.0,
+                c.relative_error * 100.0,
+                rank_correlation,
+                c.score,
+                c.grade
+            )?;
+
+            for effect in &c.effect_sizes {
+                if let (Some(estimated), Some(actual)) =
+                    (effect.estimated_cohens_d, effect.actual_cohens_d)
+                {
+                    let disagrees = estimated.signum() != actual.signum();
+                    writeln!(
+                        writer,
+                        "  {}: Cohen's d (est, actual): ({:.2}, {:.2}){}",
+                        effect.group,
+                        estimated,
+                        actual,
+                        if disagrees {
+                            " [direction disagrees]"
+                        } else {
+                            ""
+                        }
+                    )?;
+                }
+            }
+        }
+
+        match (self.overall_score, self.overall_grade) {
+            (Some(score), Some(grade)) => {
+                writeln!(writer, "\nOverall: {score:.2} (grade {grade})")
+            }
+            _ => writeln!(writer, "\nOverall: N/A (no comparisons)"),
+        }
+    }
+}
+
+/// Scores a comparison from the `(baseline, groups)` metrics of each file.
+fn score_comparison<G: IntoIterator<Item = GroupComparisonMetrics>>(
+    name: &str,
+    kind: &'static str,
+    agreement: f64,
+    group_names: &[String],
+    files: impl Iterator<Item = (GroupComparisonMetrics, G)>,
+    ground_truth: GroundTruth,
+) -> ComparisonScore {
+    let mut absolute_error = 0.0;
+    let mut actual_total = 0.0;
+    let mut sizes = 0usize;
+    let mut estimated_ratios: Vec<Vec<f64>> = vec![Vec::new(); group_names.len()];
+    let mut actual_ratios: Vec<Vec<f64>> = vec![Vec::new(); group_names.len()];
+
+    for (baseline, groups) in files {
+        if !baseline.has_estimate {
+            continue;
+        }
+
+        let mut add_error = |metrics: &GroupComparisonMetrics| {
+            let actual = metrics.actual_size(ground_truth) as f64;
+            absolute_error += (metrics.estimated_size as f64 - actual).abs();
+            actual_total += actual;
+            sizes += 1;
+        };
+
+        add_error(&baseline);
+        for (x, group) in groups.into_iter().enumerate() {
+            if !group.has_estimate || x >= group_names.len() {
+                continue;
+            }
+            add_error(&group);
+
+            // Ratios are paired by file, so a file is skipped if either baseline is empty.
+            let (estimated_baseline, actual_baseline) =
+                (baseline.estimated_size, baseline.actual_size(ground_truth));
+            if estimated_baseline == 0 || actual_baseline == 0 {
+                continue;
+            }
+            estimated_ratios[x].push(group.estimated_size as f64 / estimated_baseline as f64);
+            actual_ratios[x].push(group.actual_size(ground_truth) as f64 / actual_baseline as f64);
+        }
+    }
+
+    let mean_absolute_error = if sizes == 0 {
+        0.0
+    } else {
+        absolute_error / sizes as f64
+    };
+    let relative_error = if actual_total == 0.0 {
+        0.0
+    } else {
+        absolute_error / actual_total
+    };
+    let rank_correlation =
+        calculate_rank_correlation(&estimated_ratios.concat(), &actual_ratios.concat());
+
+    let cohens_d = |ratios: &[f64]| calculate_effect_size(ratios).map(|e: EffectSize| e.cohens_d);
+    let effect_sizes = group_names
+        .iter()
+        .enumerate()
+        .map(|(x, group)| GroupEffectSizes {
+            group: group.clone(),
+            estimated_cohens_d: cohens_d(&estimated_ratios[x]),
+            actual_cohens_d: cohens_d(&actual_ratios[x]),
+        })
+        .collect();
+
+    let mut components = vec![agreement, (1.0 - relative_error).max(0.0)];
+    components.extend(rank_correlation.map(|rho| rho.max(0.0)));
+    let score = components.iter().sum::<f64>() / components.len() as f64;
+
+    ComparisonScore {
+        name: name.to_string(),
+        kind,
+        agreement,
+        mean_absolute_error,
+        relative_error,
+        rank_correlation,
+        effect_sizes,
+        score,
+        grade: grade(score),
+    }
+}
+
+/// Letter grade of a score in `0.0..=1.0`.
+fn grade(score: f64) -> char {
+    match score {
+        s if s >= 0.9 => 'A',
+        s if s >= 0.8 => 'B',
+        s if s >= 0.7 => 'C',
+        s if s >= 0.6 => 'D',
+        _ => 'F',
+    }
+}
+
+/// Spearman's rank correlation between 2 equally long sets of values.
+/// Tied values are given the mean of their ranks.
+///
+/// # Returns
+/// The correlation (`-1.0..=1.0`), or [`None`] if there are fewer than 2 values,
+/// or either set has no variation.
+pub fn calculate_rank_correlation(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let correlation = calculate_spearman_correlation(xs, ys);
+    (!correlation.is_nan()).then_some(correlation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{CompressionOptions, SchemaAnalyzer};
+    use crate::results::analysis_results::AnalysisResults;
+    use crate::schema::Schema;
+
+    #[test]
+    fn rank_correlation_handles_order_and_ties() {
+        let xs = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(
+            calculate_rank_correlation(&xs, &[10.0, 20.0, 30.0, 40.0]),
+            Some(1.0)
+        );
+        assert_eq!(
+            calculate_rank_correlation(&xs, &[4.0, 3.0, 2.0, 1.0]),
+            Some(-1.0)
+        );
+        assert_eq!(calculate_rank_correlation(&xs, &[5.0, 5.0, 5.0, 5.0]), None);
+        assert_eq!(calculate_rank_correlation(&xs[..1], &[1.0]), None);
+    }
+
+    #[test]
+    fn grades_comparisons_of_merged_results() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    a: 8
+    b: 8
+analysis:
+  split_groups:
+    - name: a_b
+      group_1: [a, b]
+      group_2: [b, a]
+  compare_groups:
+    - name: only_a
+      baseline:
+        - { type: array, field: a }
+        - { type: array, field: b }
+      comparisons:
+        a:
+          - { type: array, field: a }
+"#,
+        )
+        .unwrap();
+
+        let results: Vec<AnalysisResults> = (1..=3u8)
+            .map(|seed| {
+                let options = CompressionOptions {
+                    lz_match_multiplier: 0.375,
+                    entropy_multiplier: 1.0,
+                    ..CompressionOptions::default()
+                };
+                let mut analyzer = SchemaAnalyzer::new(&schema, options);
+                let mut noise = seed as u32;
+                for x in 0..8192u32 {
+                    noise = noise.wrapping_mul(1664525).wrapping_add(1013904223);
+                    let a = (x / 64) as u8 % (seed * 4);
+                    analyzer.add_entry(&[a, (noise >> 24) as u8]).unwrap();
+                }
+                analyzer.generate_results().unwrap()
+            })
+            .collect();
+        let merged = MergedAnalysisResults::from_results(&results).unwrap();
+        let card = EstimatorReportCard::new(&merged);
+
+        assert_eq!(card.file_count, 3);
+        let kinds: Vec<&str> = card.comparisons.iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, vec!["split", "custom"]);
+        for comparison in &card.comparisons {
+            assert!((0.0..=1.0).contains(&comparison.agreement));
+            assert!((0.0..=1.0).contains(&comparison.score));
+            assert_eq!(comparison.grade, grade(comparison.score));
+            assert!(comparison.effect_sizes[0].actual_cohens_d.is_some());
+        }
+        // Removing a field makes every file much smaller; both sizes agree on that.
+        let custom = &card.comparisons[1];
+        assert_eq!(custom.effect_sizes[0].group, "a");
+        assert_eq!(custom.agreement, 1.0);
+        assert!(card.overall_score.is_some());
+
+        let mut output = Vec::new();
+        card.print(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Estimator Report Card (3 files, against Zstd):"));
+        assert!(output.contains("Overall: "));
+    }
+}
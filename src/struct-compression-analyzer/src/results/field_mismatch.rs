@@ -0,0 +1,168 @@
+//! Detects fields which are present in the results of some files, but not others.
+//!
+//! All files analyzed with the same schema normally have the same fields. When they don't
+//! (e.g. the schema changed between runs of saved results), the merged metrics of such a field
+//! only describe the files which have it. These fields are reported as
+//! [`FieldPresenceMismatch`]es, so a partial merge isn't mistaken for a full one.
+
+use super::analysis_results::AnalysisResults;
+use ahash::AHashSet;
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// A field which is present in the results of some files, but not others.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FieldPresenceMismatch {
+    /// Full path of the field.
+    pub full_path: String,
+    /// Indices of the files which have the field.
+    pub present_in: Vec<usize>,
+    /// Indices of the files which don't have the field.
+    pub missing_from: Vec<usize>,
+}
+
+/// The full paths of all fields of all results, in order of first appearance.
+pub(crate) fn all_field_paths(results: &[AnalysisResults]) -> Vec<&String> {
+    let mut seen = AHashSet::new();
+    results
+        .iter()
+        .flat_map(|result| result.per_field.keys())
+        .filter(|full_path| seen.insert(*full_path))
+        .collect()
+}
+
+/// Finds the fields which are present in the results of some files, but not others.
+///
+/// # Returns
+/// The mismatched fields, sorted by full path. Empty if all results have the same fields.
+pub fn find_field_mismatches(results: &[AnalysisResults]) -> Vec<FieldPresenceMismatch> {
+    let mut mismatches: Vec<FieldPresenceMismatch> = all_field_paths(results)
+        .into_iter()
+        .filter_map(|full_path| {
+            let (present_in, missing_from): (Vec<usize>, Vec<usize>) =
+                (0..results.len()).partition(|&x| results[x].per_field.contains_key(full_path));
+            (!missing_from.is_empty()).then(|| FieldPresenceMismatch {
+                full_path: full_path.clone(),
+                present_in,
+                missing_from,
+            })
+        })
+        .collect();
+
+    mismatches.sort_by(|a, b| a.full_path.cmp(&b.full_path));
+    mismatches
+}
+
+/// Prints a warning for each mismatched field.
+///
+/// # Arguments
+/// * `writer` - The writer to print to.
+/// * `mismatches` - The mismatched fields.
+/// * `file_label` - Returns the name of the file at an index.
+pub(crate) fn print_field_mismatches<W: Write>(
+    writer: &mut W,
+    mismatches: &[FieldPresenceMismatch],
+    file_label: impl Fn(usize) -> String,
+) -> io::Result<()> {
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(
+        writer,
+        "[WARNING!!] {} field(s) are missing from some files!! Their merged metrics only include the files which have them.",
+        mismatches.len()
+    )?;
+    for mismatch in mismatches {
+        let missing: Vec<String> = mismatch
+            .missing_from
+            .iter()
+            .map(|&x| file_label(x))
+            .collect();
+        writeln!(
+            writer,
+            "  {}: present in {} file(s), missing from {}",
+            mismatch.full_path,
+            mismatch.present_in.len(),
+            missing.join(", ")
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_entries, CompressionOptions};
+    use crate::results::merged_analysis_results::MergedAnalysisResults;
+    use crate::results::AnalysisMergeError;
+    use crate::schema::Schema;
+
+    fn analyze(fields: &str) -> AnalysisResults {
+        let schema = Schema::from_yaml(&format!(
+            "version: '1.0'\nroot:\n  type: group\n  fields:\n{fields}"
+        ))
+        .unwrap();
+        let entries = (0..32u8).map(|x| [x, x]);
+        analyze_entries(&schema, CompressionOptions::default(), entries)
+    }
+
+    #[test]
+    fn reports_fields_missing_from_some_files() {
+        let results = vec![
+            analyze("    a: 8\n    b: 8\n"),
+            analyze("    a: 8\n    c: 8\n"),
+        ];
+
+        let mismatches = find_field_mismatches(&results);
+        let paths: Vec<&str> = mismatches.iter().map(|m| m.full_path.as_str()).collect();
+        assert_eq!(paths, vec!["b", "c"]);
+        assert_eq!(mismatches[0].present_in, vec![0]);
+        assert_eq!(mismatches[0].missing_from, vec![1]);
+
+        // Fields of all files are merged, not only those of the first.
+        let merged = MergedAnalysisResults::from_results(&results).unwrap();
+        assert!(merged.per_field.contains_key("c"));
+        assert_eq!(merged.field_mismatches, mismatches);
+        assert!(matches!(
+            merged.ensure_matching_fields(),
+            Err(AnalysisMergeError::MismatchedFields(fields)) if fields == ["b", "c"]
+        ));
+
+        let mut output = Vec::new();
+        print_field_mismatches(&mut output, &mismatches, |x| format!("file{x}")).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("b: present in 1 file(s), missing from file1"));
+    }
+
+    #[test]
+    fn fields_only_in_zero_weight_files_are_merged_unweighted() {
+        let results = vec![
+            analyze("    a: 8\n    b: 8\n"),
+            analyze("    a: 8\n    c: 8\n"),
+            analyze("    a: 8\n    c: 8\n"),
+        ];
+
+        let merged =
+            MergedAnalysisResults::from_results_weighted(&results, &[1.0, 0.0, 0.0]).unwrap();
+        assert_eq!(
+            merged.per_field["c"].original_size,
+            results[1].per_field["c"].original_size
+        );
+        assert_eq!(
+            merged.per_field["a"].original_size,
+            results[0].per_field["a"].original_size
+        );
+    }
+
+    #[test]
+    fn matching_fields_are_not_reported() {
+        let results = vec![
+            analyze("    a: 8\n    b: 8\n"),
+            analyze("    a: 8\n    b: 8\n"),
+        ];
+        assert!(find_field_mismatches(&results).is_empty());
+        let merged = MergedAnalysisResults::from_results(&results).unwrap();
+        assert!(merged.ensure_matching_fields().is_ok());
+    }
+}
@@ -0,0 +1,169 @@
+//! Per-field metrics nested under their parent groups, following the schema hierarchy.
+//!
+//! The `per_field` maps of [`AnalysisResults`] and [`MergedAnalysisResults`] are flat,
+//! keyed by full path. [`FieldTree`] reconstructs the hierarchy of the schema from them,
+//! so the metrics can be serialized (e.g. as JSON) in a naturally navigable shape:
+//!
+//! ```json
+//! { "schema": "BC1", "fields": [
+//!     { "name": "colors", "full_path": "colors", ..., "children": [
+//!         { "name": "color0", "full_path": "colors.color0", ..., "children": [] }
+//!     ] }
+//! ] }
+//! ```
+//!
+//! [`AnalysisResults`]: super::analysis_results::AnalysisResults
+//! [`MergedAnalysisResults`]: super::merged_analysis_results::MergedAnalysisResults
+
+use super::FieldMetrics;
+use crate::schema::{BitOrder, Schema};
+use ahash::AHashMap;
+use serde::Serialize;
+
+/// The per-field metrics of a result, nested following the schema hierarchy.
+#[derive(Debug, Serialize)]
+pub struct FieldTree<'a> {
+    /// Name of the schema.
+    pub schema: &'a str,
+    /// The top level fields and groups, in schema order.
+    pub fields: Vec<FieldTreeNode<'a>>,
+}
+
+/// The metrics of a single field or group, with the metrics of its children.
+///
+/// This holds the scalar metrics of a [`FieldMetrics`]; the value and bit stats are left out,
+/// as they are better suited to the CSV reports.
+#[derive(Debug, Serialize)]
+pub struct FieldTreeNode<'a> {
+    /// See [`FieldMetrics::name`].
+    pub name: &'a str,
+    /// See [`FieldMetrics::full_path`].
+    pub full_path: &'a str,
+    /// See [`FieldMetrics::lenbits`].
+    pub lenbits: u32,
+    /// See [`FieldMetrics::count`].
+    pub count: u64,
+    /// See [`FieldMetrics::entropy`].
+    pub entropy: f64,
+    /// See [`FieldMetrics::lz_matches`].
+    pub lz_matches: u64,
+    /// See [`FieldMetrics::zstd_size`].
+    pub zstd_size: u64,
+    /// See [`FieldMetrics::original_size`].
+    pub original_size: u64,
+    /// See [`FieldMetrics::bit_order`].
+    pub bit_order: BitOrder,
+    /// Number of distinct values, see [`FieldMetrics::value_counts`].
+    pub unique_values: usize,
+    /// See [`FieldMetrics::value_counts_overflowed`].
+    pub unique_values_truncated: bool,
+    /// See [`FieldMetrics::reserved`].
+    pub reserved: bool,
+    /// The fields and groups of this group, in schema order. Empty for fields.
+    pub children: Vec<FieldTreeNode<'a>>,
+}
+
+impl<'a> FieldTree<'a> {
+    /// Nests the per-field metrics of a result following the hierarchy of the schema.
+    ///
+    /// # Arguments
+    /// * `schema` - The schema the results were produced with.
+    /// * `per_field` - Per-field metrics of the results, keyed by full path.
+    ///
+    /// Fields which are not in the schema are left out.
+    pub fn new(schema: &'a Schema, per_field: &'a AHashMap<String, FieldMetrics>) -> Self {
+        // Schema order is depth first, so parents always come before their children.
+        let ordered: Vec<&FieldMetrics> = schema
+            .ordered_field_and_group_paths()
+            .iter()
+            .filter_map(|path| per_field.get(path))
+            .collect();
+
+        Self {
+            schema: &schema.metadata.name,
+            fields: children_of(&ordered, None),
+        }
+    }
+}
+
+/// Builds the nodes of the fields whose parent is `parent` (or top level fields if [`None`]).
+fn children_of<'a>(ordered: &[&'a FieldMetrics], parent: Option<&str>) -> Vec<FieldTreeNode<'a>> {
+    ordered
+        .iter()
+        .filter(|field| field.parent_path() == parent)
+        .map(|field| FieldTreeNode {
+            name: &field.name,
+            full_path: &field.full_path,
+            lenbits: field.lenbits,
+            count: field.count,
+            entropy: field.entropy,
+            lz_matches: field.lz_matches,
+            zstd_size: field.zstd_size,
+            original_size: field.original_size,
+            bit_order: field.bit_order,
+            unique_values: field.value_counts.len(),
+            unique_values_truncated: field.value_counts_overflowed,
+            reserved: field.reserved,
+            children: children_of(ordered, Some(&field.full_path)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{CompressionOptions, SchemaAnalyzer};
+
+    #[test]
+    fn nests_fields_under_their_groups() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+metadata: { name: Tree }
+root:
+  type: group
+  fields:
+    header:
+      type: group
+      fields:
+        mode: 4
+        flags: 4
+    colors:
+      type: group
+      fields:
+        r: 8
+        inner:
+          type: group
+          fields:
+            g: 8
+"#,
+        )
+        .unwrap();
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        for x in 0..16u8 {
+            analyzer.add_entry(&[x, x, x]).unwrap();
+        }
+        let results = analyzer.generate_results().unwrap();
+
+        let tree = FieldTree::new(&schema, &results.per_field);
+        assert_eq!(tree.schema, "Tree");
+        let names = |nodes: &[FieldTreeNode]| -> Vec<String> {
+            nodes.iter().map(|n| n.full_path.to_string()).collect()
+        };
+        assert_eq!(names(&tree.fields), ["header", "colors"]);
+        assert_eq!(
+            names(&tree.fields[0].children),
+            ["header.mode", "header.flags"]
+        );
+        assert_eq!(
+            names(&tree.fields[1].children),
+            ["colors.r", "colors.inner"]
+        );
+        assert_eq!(
+            names(&tree.fields[1].children[1].children),
+            ["colors.inner.g"]
+        );
+        assert!(tree.fields[0].children[0].children.is_empty());
+        assert_eq!(tree.fields[0].children[1].unique_values, 16);
+    }
+}
@@ -0,0 +1,156 @@
+//! JSON export of analysis results, for machine consumption (e.g. CI dashboards).
+//!
+//! [`AnalysisResults`] and [`MergedAnalysisResults`] (and everything they contain) implement
+//! [`Serialize`]; [`write_json`] writes any of them as pretty printed JSON.
+//!
+//! The hash maps of the results (e.g. [`AnalysisResults::per_field`],
+//! [`FieldMetrics::value_counts`]) are serialized with their keys sorted, so the output of
+//! two runs over the same data is identical, and diffs between runs only show actual changes.
+//! JSON object keys must be strings, so [`FieldMetrics::value_transitions`] is serialized as
+//! a list of `[previous, current, count]` entries instead.
+//!
+//! [`MergedAnalysisResults::original_results`] is left out, as it repeats the per-file results;
+//! write those individually instead.
+//!
+//! [`AnalysisResults`]: super::analysis_results::AnalysisResults
+//! [`AnalysisResults::per_field`]: super::analysis_results::AnalysisResults::per_field
+//! [`MergedAnalysisResults`]: super::merged_analysis_results::MergedAnalysisResults
+//! [`MergedAnalysisResults::original_results`]: super::merged_analysis_results::MergedAnalysisResults::original_results
+//! [`FieldMetrics::value_counts`]: super::FieldMetrics::value_counts
+//! [`FieldMetrics::value_transitions`]: super::FieldMetrics::value_transitions
+
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::io::Write;
+
+/// (previous value, current value) → occurrence count, see [`FieldMetrics::value_transitions`].
+///
+/// [`FieldMetrics::value_transitions`]: super::FieldMetrics::value_transitions
+type ValueTransitions = FxHashMap<(u64, u64), u64>;
+
+/// Writes results as pretty printed JSON.
+///
+/// # Arguments
+/// * `results` - The results to write, e.g. an [`AnalysisResults`], a [`MergedAnalysisResults`]
+///   or a slice of [`AnalysisResults`].
+/// * `writer` - Where to write the JSON to.
+///
+/// [`AnalysisResults`]: super::analysis_results::AnalysisResults
+/// [`MergedAnalysisResults`]: super::merged_analysis_results::MergedAnalysisResults
+pub fn write_json<T: Serialize + ?Sized, W: Write>(
+    results: &T,
+    writer: W,
+) -> serde_json::Result<()> {
+    serde_json::to_writer_pretty(writer, results)
+}
+
+/// Serializes a hash map with its keys in ascending order.
+pub(crate) fn serialize_sorted_map<'a, M, K, V, S>(
+    map: &'a M,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+    K: Ord + Serialize + 'a,
+    V: Serialize + 'a,
+    S: Serializer,
+{
+    let mut entries: Vec<(&K, &V)> = map.into_iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    serializer.collect_map(entries)
+}
+
+/// Serializes value transitions as a list of `[previous, current, count]` entries,
+/// in ascending order of `(previous, current)`.
+pub(crate) fn serialize_transitions<S: Serializer>(
+    transitions: &Option<ValueTransitions>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let Some(transitions) = transitions else {
+        return serializer.serialize_none();
+    };
+
+    let mut entries: Vec<(u64, u64, u64)> = transitions
+        .iter()
+        .map(|(&(previous, current), &count)| (previous, current, count))
+        .collect();
+    entries.sort_unstable();
+    serializer.serialize_some(&entries)
+}
+
+/// Deserializes value transitions written by [`serialize_transitions`].
+pub(crate) fn deserialize_transitions<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<ValueTransitions>, D::Error> {
+    let entries = Option::<Vec<(u64, u64, u64)>>::deserialize(deserializer)?;
+    Ok(entries.map(|entries| {
+        entries
+            .into_iter()
+            .map(|(previous, current, count)| ((previous, current), count))
+            .collect()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_entries, CompressionOptions};
+    use crate::results::analysis_results::AnalysisResults;
+    use crate::results::merged_analysis_results::MergedAnalysisResults;
+    use crate::schema::Schema;
+
+    const SCHEMA: &str = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    a: 8
+    b: 8
+    c: 8
+analysis:
+  split_groups:
+    - { name: split, group_1: [a, b, c], group_2: [c, b, a] }
+"#;
+
+    fn analyze(schema: &Schema, seed: u8) -> AnalysisResults {
+        let options = CompressionOptions::default().with_value_transitions(true);
+        let entries = (0..64u8).map(|x| [x.wrapping_mul(seed), x % 7, x / 3]);
+        analyze_entries(schema, options, entries)
+    }
+
+    fn to_json<T: Serialize>(results: &T) -> String {
+        let mut json = Vec::new();
+        write_json(results, &mut json).unwrap();
+        String::from_utf8(json).unwrap()
+    }
+
+    #[test]
+    fn output_is_deterministic() {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let first = to_json(&analyze(&schema, 3));
+        let second = to_json(&analyze(&schema, 3));
+        assert_eq!(first, second);
+
+        let position = |key: &str| first.find(&format!("\"{key}\": {{")).unwrap();
+        assert!(position("a") < position("b") && position("b") < position("c"));
+
+        let value: serde_json::Value = serde_json::from_str(&first).unwrap();
+        assert_eq!(value["split_comparisons"][0]["name"], "split");
+        assert!(value["per_field"]["b"]["value_transitions"][0].is_array());
+    }
+
+    #[test]
+    fn writes_merged_results() {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let results = [analyze(&schema, 3), analyze(&schema, 5)];
+        let merged = MergedAnalysisResults::from_results(&results).unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&to_json(&merged)).unwrap();
+        assert_eq!(value["merged_file_count"], 2);
+        assert_eq!(value["ground_truth"], "Zstd");
+        assert!(value.get("original_results").is_none());
+        assert!(
+            value["split_comparisons"][0]["group_estimate_zstd_agreement_percentage"].is_number()
+        );
+    }
+}
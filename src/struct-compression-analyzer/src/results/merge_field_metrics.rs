@@ -0,0 +1,146 @@
+//! Merging the [`FieldMetrics`] of multiple files.
+
+use super::merge_weights::MergeWeights;
+use super::run_stats::RunStats;
+use super::{AnalysisMergeError, FieldMetrics};
+use rustc_hash::FxHashMap;
+
+impl FieldMetrics {
+    /// Merge multiple [`FieldMetrics`] objects into one.
+    /// This gives you an 'aggregate' result over a large data set.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The items to merge into a new instance.
+    pub fn try_merge_many(items: &[&Self]) -> Result<FieldMetrics, AnalysisMergeError> {
+        Self::try_merge_many_with_weights(items, &MergeWeights::uniform(items.len()))
+    }
+
+    /// Merge multiple [`FieldMetrics`] objects into one, using a weighted mean
+    /// for the averaged metrics (entropy, LZ matches, sizes).
+    ///
+    /// Counts (observed values, bit stats, value counts, value transitions) are summed as-is,
+    /// and the windows of [`Self::windowed_entropy`] are concatenated in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The items to merge into a new instance.
+    /// * `weights` - The weight of each item, in the same order as `items`.
+    pub fn try_merge_many_weighted(
+        items: &[&Self],
+        weights: &[f64],
+    ) -> Result<FieldMetrics, AnalysisMergeError> {
+        Self::try_merge_many_with_weights(items, &MergeWeights::new(weights, items.len())?)
+    }
+
+    pub(crate) fn try_merge_many_with_weights(
+        items: &[&Self],
+        weights: &MergeWeights,
+    ) -> Result<FieldMetrics, AnalysisMergeError> {
+        if items.is_empty() {
+            return Ok(FieldMetrics::default());
+        }
+
+        let first = items[0];
+
+        // Validate compatible field configurations
+        for other in items {
+            if first.lenbits != other.lenbits {
+                return Err(AnalysisMergeError::FieldLengthMismatch(
+                    first.lenbits,
+                    other.lenbits,
+                ));
+            }
+        }
+
+        let mut this = FieldMetrics {
+            name: first.name.clone(),
+            full_path: first.full_path.clone(),
+            depth: first.depth,
+            lenbits: first.lenbits,
+            bit_order: first.bit_order,
+            interpretation: first.interpretation,
+            reserved: first.reserved,
+            ..Default::default()
+        };
+        this.count = items.iter().map(|m| m.count).sum();
+        this.value_counts_overflowed = items.iter().any(|m| m.value_counts_overflowed);
+        // Constant only if every file has the same constant value.
+        this.constant_value = first
+            .constant_value
+            .filter(|value| items.iter().all(|m| m.constant_value == Some(*value)));
+        this.is_constant = this.constant_value.is_some();
+        this.entropy = weights.mean_f64(items.iter().map(|m| m.entropy));
+        this.windowed_entropy = items
+            .iter()
+            .flat_map(|m| m.windowed_entropy.iter().copied())
+            .collect();
+        this.lz_matches = weights.mean_u64(items.iter().map(|m| m.lz_matches));
+        this.estimated_size = weights.mean_u64(items.iter().map(|m| m.estimated_size));
+        this.zstd_size = weights.mean_u64(items.iter().map(|m| m.zstd_size));
+        // Only meaningful if every file measured it.
+        this.entropy_coded_size = items
+            .iter()
+            .all(|m| m.entropy_coded_size.is_some())
+            .then(|| weights.mean_u64(items.iter().map(|m| m.entropy_coded_size.unwrap_or(0))));
+        this.entropy_order1 = items
+            .iter()
+            .all(|m| m.entropy_order1.is_some())
+            .then(|| weights.mean_f64(items.iter().map(|m| m.entropy_order1.unwrap_or(0.0))));
+        this.run_stats = items
+            .iter()
+            .all(|m| m.run_stats.is_some())
+            .then(|| RunStats::merge(items.iter().filter_map(|m| m.run_stats.as_ref())));
+        this.reference_size = items
+            .iter()
+            .all(|m| m.reference_size.is_some())
+            .then(|| weights.mean_u64(items.iter().map(|m| m.reference_size.unwrap_or(0))));
+        this.original_size = weights.mean_u64(items.iter().map(|m| m.original_size));
+        this.merge_bit_stats_and_value_counts(items)?;
+        Ok(this)
+    }
+
+    fn merge_bit_stats_and_value_counts(
+        &mut self,
+        items: &[&Self],
+    ) -> Result<(), AnalysisMergeError> {
+        let mut bit_counts = items[0].bit_counts.clone();
+        let mut value_counts = items[0].value_counts.clone();
+
+        for other in items {
+            // Validate bit counts length
+            if bit_counts.len() != other.bit_counts.len() {
+                return Err(AnalysisMergeError::BitCountsDontMatch);
+            }
+
+            for (bit_offset, bit_stats) in other.bit_counts.iter().enumerate() {
+                let current = bit_counts
+                    .get_mut(bit_offset)
+                    .ok_or(AnalysisMergeError::BitCountsDontMatch)?;
+                current.ones += bit_stats.ones;
+                current.zeros += bit_stats.zeros;
+            }
+
+            // Add value counts from others into self
+            for (value, count) in &other.value_counts {
+                *value_counts.entry(*value).or_insert(0) += count;
+            }
+        }
+
+        self.bit_counts = bit_counts;
+        self.value_counts = value_counts;
+        self.value_transitions = merge_value_transitions(items);
+        Ok(())
+    }
+}
+
+/// Sums the value transitions of each item; only available if every item has them.
+fn merge_value_transitions(items: &[&FieldMetrics]) -> Option<FxHashMap<(u64, u64), u64>> {
+    let mut merged = FxHashMap::default();
+    for item in items {
+        for (pair, count) in item.value_transitions.as_ref()? {
+            *merged.entry(*pair).or_insert(0) += count;
+        }
+    }
+    Some(merged)
+}
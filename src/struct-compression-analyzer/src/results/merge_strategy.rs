@@ -0,0 +1,128 @@
+//! How much each file counts towards the averaged metrics of merged results.
+//!
+//! By default ([`MergeStrategy::Unweighted`]), every file counts equally, so a 10 byte file
+//! skews the merged entropy as much as a 10 MB file. [`MergeStrategy::WeightedByOriginalSize`]
+//! weights each file by its [`AnalysisResults::original_size`] instead, so the merged metrics
+//! describe the average byte of the data set rather than the average file.
+//!
+//! Either way, summed values and the estimator/zstd agreement percentages are count based,
+//! see [`MergedAnalysisResults::from_results_weighted`].
+//!
+//! [`AnalysisResults::original_size`]: super::analysis_results::AnalysisResults::original_size
+//! [`MergedAnalysisResults::from_results_weighted`]: super::merged_analysis_results::MergedAnalysisResults::from_results_weighted
+
+use super::analysis_results::AnalysisResults;
+use derive_more::FromStr;
+
+/// How the results of individual files are weighted when merging them.
+///
+/// Parsed case-insensitively from the variant name, e.g. `weightedbyoriginalsize`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromStr)]
+pub enum MergeStrategy {
+    /// Every file counts equally.
+    #[default]
+    Unweighted,
+    /// Each file counts in proportion to its size in bytes.
+    /// Falls back to [`Self::Unweighted`] if all files are empty.
+    WeightedByOriginalSize,
+}
+
+impl MergeStrategy {
+    /// The weight of each result under this strategy, in the same order as `results`,
+    /// or [`None`] if the results are not weighted.
+    pub fn weights(self, results: &[AnalysisResults]) -> Option<Vec<f64>> {
+        match self {
+            MergeStrategy::Unweighted => None,
+            MergeStrategy::WeightedByOriginalSize => {
+                // All-zero weights can't be merged with; count every (empty) file equally.
+                if results.iter().all(|result| result.original_size == 0) {
+                    return None;
+                }
+
+                Some(
+                    results
+                        .iter()
+                        .map(|result| result.original_size as f64)
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_entries, CompressionOptions};
+    use crate::results::merged_analysis_results::MergedAnalysisResults;
+    use crate::schema::Schema;
+
+    const SCHEMA: &str = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    a: 8
+analysis:
+  split_groups:
+    - { name: split, group_1: [a], group_2: [a] }
+"#;
+
+    #[test]
+    fn weights_files_by_size() {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let options = CompressionOptions::default();
+        let constant = analyze_entries(&schema, options.clone(), [7u8; 10].chunks(1));
+        let varied: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let varied = analyze_entries(&schema, options, varied.chunks(1));
+        let results = [constant, varied];
+
+        let unweighted =
+            MergedAnalysisResults::from_results_with_strategy(&results, MergeStrategy::default())
+                .unwrap();
+        let plain = MergedAnalysisResults::from_results(&results).unwrap();
+        assert_eq!(unweighted.file_entropy, plain.file_entropy);
+        assert_eq!(unweighted.original_size, 505);
+
+        let weighted = MergedAnalysisResults::from_results_with_strategy(
+            &results,
+            MergeStrategy::WeightedByOriginalSize,
+        )
+        .unwrap();
+        let expected_entropy =
+            (10.0 * results[0].file_entropy + 1000.0 * results[1].file_entropy) / 1010.0;
+        assert!((weighted.file_entropy - expected_entropy).abs() < 1e-9);
+        assert!(weighted.file_entropy > unweighted.file_entropy);
+        assert!((weighted.per_field["a"].entropy - expected_entropy).abs() < 1e-9);
+
+        // Agreement stays the share of files.
+        assert_eq!(
+            weighted.split_comparisons[0].group_estimate_zstd_agreement_percentage,
+            unweighted.split_comparisons[0].group_estimate_zstd_agreement_percentage
+        );
+        assert_eq!(
+            "WeightedByOriginalSize".parse(),
+            Ok(MergeStrategy::WeightedByOriginalSize)
+        );
+    }
+
+    #[test]
+    fn empty_files_fall_back_to_unweighted() {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let mut result = analyze_entries(&schema, CompressionOptions::default(), [[1u8], [2]]);
+        result.original_size = 0;
+        let results = [result.clone(), result];
+
+        assert_eq!(
+            MergeStrategy::WeightedByOriginalSize.weights(&results),
+            None
+        );
+        let weighted = MergedAnalysisResults::from_results_with_strategy(
+            &results,
+            MergeStrategy::WeightedByOriginalSize,
+        )
+        .unwrap();
+        let plain = MergedAnalysisResults::from_results(&results).unwrap();
+        assert_eq!(weighted.file_entropy, plain.file_entropy);
+    }
+}
@@ -0,0 +1,210 @@
+//! Weights used when averaging metrics while merging multiple results.
+//!
+//! Not all files should necessarily count equally towards the merged aggregate,
+//! e.g. assets may be weighted by how frequently they're used at runtime.
+
+use super::AnalysisMergeError;
+use crate::{
+    comparison::{
+        split_comparison::FieldComparisonMetrics, GroupComparisonMetrics, GroupDifference,
+    },
+    utils::analyze_utils::SizeEstimateBreakdown,
+};
+
+/// Weights of each item being merged, used to compute weighted means.
+///
+/// With uniform weights, the means are identical to the plain (truncated) integer means.
+pub(crate) struct MergeWeights {
+    weights: Vec<f64>,
+    total: f64,
+}
+
+impl MergeWeights {
+    /// Weights where every item counts equally.
+    pub(crate) fn uniform(count: usize) -> Self {
+        Self {
+            weights: vec![1.0; count],
+            total: count as f64,
+        }
+    }
+
+    /// Creates weights for `count` items.
+    ///
+    /// # Errors
+    ///
+    /// If the number of weights does not match `count`, any weight is negative or non-finite,
+    /// or all weights are zero.
+    pub(crate) fn new(weights: &[f64], count: usize) -> Result<Self, AnalysisMergeError> {
+        if weights.len() != count {
+            return Err(AnalysisMergeError::WeightCountMismatch {
+                expected: count,
+                found: weights.len(),
+            });
+        }
+
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err(AnalysisMergeError::InvalidWeights);
+        }
+
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 && count > 0 {
+            return Err(AnalysisMergeError::InvalidWeights);
+        }
+
+        Ok(Self {
+            weights: weights.to_vec(),
+            total,
+        })
+    }
+
+    /// Weighted mean of a value extracted from each item.
+    pub(crate) fn mean_f64(&self, values: impl Iterator<Item = f64>) -> f64 {
+        if self.total <= 0.0 {
+            return 0.0;
+        }
+
+        let sum: f64 = values.zip(&self.weights).map(|(v, w)| v * w).sum();
+        sum / self.total
+    }
+
+    /// Weighted mean of a value extracted from each item, truncated.
+    pub(crate) fn mean_u64(&self, values: impl Iterator<Item = u64>) -> u64 {
+        self.mean_f64(values.map(|v| v as f64)) as u64
+    }
+
+    /// Weighted mean of a value extracted from each item, truncated towards zero.
+    pub(crate) fn mean_i64(&self, values: impl Iterator<Item = i64>) -> i64 {
+        self.mean_f64(values.map(|v| v as f64)) as i64
+    }
+
+    /// Weights in the same order as the items which are merged.
+    pub(crate) fn as_slice(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Weighted mean of each of the metrics of a group.
+    pub(crate) fn mean_group_metrics(
+        &self,
+        items: &[GroupComparisonMetrics],
+    ) -> GroupComparisonMetrics {
+        GroupComparisonMetrics {
+            lz_matches: self.mean_u64(items.iter().map(|m| m.lz_matches)),
+            entropy: self.mean_f64(items.iter().map(|m| m.entropy)),
+            estimated_size: self.mean_u64(items.iter().map(|m| m.estimated_size)),
+            has_estimate: !items.is_empty() && items.iter().all(|m| m.has_estimate),
+            zstd_size: self.mean_u64(items.iter().map(|m| m.zstd_size)),
+            original_size: self.mean_u64(items.iter().map(|m| m.original_size)),
+            estimate_breakdown: self.mean_estimate_breakdown(items),
+            brotli_size: self.mean_optional_u64(items, |m| m.brotli_size),
+            deflate_size: self.mean_optional_u64(items, |m| m.deflate_size),
+            zstd_dict_size: self.mean_optional_u64(items, |m| m.zstd_dict_size),
+            entropy_order1: self.mean_optional_f64(items, |m| m.entropy_order1),
+            reference_size: self.mean_optional_u64(items, |m| m.reference_size),
+            zstd_compress_ns: self.mean_u64(items.iter().map(|m| m.zstd_compress_ns)),
+            zstd_calibration_size: self.mean_optional_u64(items, |m| m.zstd_calibration_size),
+        }
+    }
+
+    /// Weighted mean of an optional metric of each item; only available if every item has one.
+    fn mean_optional_f64(
+        &self,
+        items: &[GroupComparisonMetrics],
+        metric: impl Fn(&GroupComparisonMetrics) -> Option<f64>,
+    ) -> Option<f64> {
+        (!items.is_empty() && items.iter().all(|m| metric(m).is_some()))
+            .then(|| self.mean_f64(items.iter().filter_map(&metric)))
+    }
+
+    /// Weighted mean of an optional metric of each item, truncated;
+    /// only available if every item has one.
+    fn mean_optional_u64(
+        &self,
+        items: &[GroupComparisonMetrics],
+        metric: impl Fn(&GroupComparisonMetrics) -> Option<u64>,
+    ) -> Option<u64> {
+        self.mean_optional_f64(items, |m| metric(m).map(|v| v as f64))
+            .map(|v| v as u64)
+    }
+
+    /// Weighted mean of the estimate breakdowns; only available if every item has one.
+    fn mean_estimate_breakdown(
+        &self,
+        items: &[GroupComparisonMetrics],
+    ) -> Option<SizeEstimateBreakdown> {
+        let breakdowns: Vec<SizeEstimateBreakdown> = items
+            .iter()
+            .map(|m| m.estimate_breakdown)
+            .collect::<Option<_>>()?;
+
+        Some(SizeEstimateBreakdown {
+            lz_savings: self.mean_u64(breakdowns.iter().map(|b| b.lz_savings)),
+            bytes_after_lz: self.mean_u64(breakdowns.iter().map(|b| b.bytes_after_lz)),
+            entropy_bytes: self.mean_u64(breakdowns.iter().map(|b| b.entropy_bytes)),
+        })
+    }
+
+    /// Weighted mean of each of the metrics of a difference between groups.
+    pub(crate) fn mean_group_difference(&self, items: &[GroupDifference]) -> GroupDifference {
+        GroupDifference {
+            lz_matches: self.mean_i64(items.iter().map(|d| d.lz_matches)),
+            entropy: self.mean_f64(items.iter().map(|d| d.entropy)),
+            estimated_size: self.mean_i64(items.iter().map(|d| d.estimated_size)),
+            zstd_size: self.mean_i64(items.iter().map(|d| d.zstd_size)),
+            original_size: self.mean_i64(items.iter().map(|d| d.original_size)),
+        }
+    }
+
+    /// Weighted mean of each of the metrics of a field within a split comparison.
+    pub(crate) fn mean_field_comparison_metrics(
+        &self,
+        items: &[FieldComparisonMetrics],
+    ) -> FieldComparisonMetrics {
+        FieldComparisonMetrics {
+            lz_matches: self.mean_u64(items.iter().map(|m| m.lz_matches)),
+            entropy: self.mean_f64(items.iter().map(|m| m.entropy)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uniform_weights_match_integer_mean() {
+        let weights = MergeWeights::uniform(3);
+        assert_eq!(weights.mean_u64([1, 2, 4].into_iter()), 7 / 3);
+        assert_eq!(weights.mean_i64([-1, -2, -4].into_iter()), -7 / 3);
+        assert_eq!(weights.mean_f64([1.0, 2.0, 6.0].into_iter()), 3.0);
+    }
+
+    #[test]
+    fn computes_weighted_mean() {
+        let weights = MergeWeights::new(&[3.0, 1.0], 2).unwrap();
+        assert_eq!(weights.mean_u64([100, 200].into_iter()), 125);
+        assert_eq!(weights.mean_f64([1.0, 5.0].into_iter()), 2.0);
+    }
+
+    #[test]
+    fn rejects_invalid_weights() {
+        assert!(matches!(
+            MergeWeights::new(&[1.0], 2),
+            Err(AnalysisMergeError::WeightCountMismatch {
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            MergeWeights::new(&[1.0, -1.0], 2),
+            Err(AnalysisMergeError::InvalidWeights)
+        ));
+        assert!(matches!(
+            MergeWeights::new(&[0.0, 0.0], 2),
+            Err(AnalysisMergeError::InvalidWeights)
+        ));
+        assert!(matches!(
+            MergeWeights::new(&[f64::NAN, 1.0], 2),
+            Err(AnalysisMergeError::InvalidWeights)
+        ));
+    }
+}
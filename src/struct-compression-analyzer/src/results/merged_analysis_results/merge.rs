@@ -0,0 +1,227 @@
+//! Merging the results of multiple files.
+
+use super::merge_comparisons::{merge_custom_comparisons, merge_split_comparisons};
+use super::MergedAnalysisResults;
+use crate::results::{
+    analysis_results::AnalysisResults,
+    field_mismatch::{all_field_paths, find_field_mismatches},
+    merge_strategy::MergeStrategy,
+    merge_weights::MergeWeights,
+    AnalysisMergeError, FieldMetrics,
+};
+use ahash::RandomState;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+impl MergedAnalysisResults {
+    /// Create a new [`MergedAnalysisResults`] by merging multiple [`AnalysisResults`] instances.
+    /// This efficiently processes all results in a single operation rather than
+    /// incrementally merging them one by one.
+    pub fn from_results(results: &[AnalysisResults]) -> Result<Self, AnalysisMergeError> {
+        merge_analysis_results(results)
+    }
+
+    /// Create a new [`MergedAnalysisResults`] by merging multiple [`AnalysisResults`] instances,
+    /// using weighted means instead of plain means.
+    ///
+    /// Each averaged metric (entropy, LZ matches, sizes, comparison metrics) becomes
+    /// `sum(weight * value) / sum(weight)`, so the merged result describes the 'expected' file
+    /// under the given weighting (e.g. how often each file is loaded), rather than the average file
+    /// on disk. With all weights set to `1.0`, this is identical to [`Self::from_results`].
+    ///
+    /// Summed values (value counts, bit stats, [`FieldMetrics::count`]) and the estimator/zstd
+    /// agreement percentages (share of files) are not weighted.
+    ///
+    /// # Arguments
+    ///
+    /// * `results` - The results to merge.
+    /// * `weights` - The weight of each result, in the same order as `results`.
+    ///   Weights must be finite, non-negative and not all zero.
+    pub fn from_results_weighted(
+        results: &[AnalysisResults],
+        weights: &[f64],
+    ) -> Result<Self, AnalysisMergeError> {
+        merge_analysis_results_weighted(results, weights)
+    }
+
+    /// Create a new [`MergedAnalysisResults`] by merging multiple [`AnalysisResults`] instances,
+    /// weighting each result according to the given [`MergeStrategy`].
+    ///
+    /// With [`MergeStrategy::Unweighted`], this is identical to [`Self::from_results`].
+    pub fn from_results_with_strategy(
+        results: &[AnalysisResults],
+        strategy: MergeStrategy,
+    ) -> Result<Self, AnalysisMergeError> {
+        merge_analysis_results_with_strategy(results, strategy)
+    }
+
+    /// Combines the merged results of separate runs (e.g. of different directories) into one.
+    ///
+    /// The results of the files of all runs are merged again, so each run contributes to the
+    /// averages in proportion to its [`Self::merged_file_count`], and per-file statistics
+    /// (ratio statistics, outliers, effect sizes) are computed over the files of all runs.
+    /// Weights used when merging the individual runs are not kept.
+    ///
+    /// The [`Self::ground_truth`] of the first run is used. [`Self::file_names`] are kept
+    /// if every run has them.
+    ///
+    /// # Errors
+    ///
+    /// [`AnalysisMergeError::MismatchedRuns`] if the runs have different schemas or comparisons,
+    /// or a run doesn't hold the results of all of its files.
+    pub fn merge_merged(runs: &[MergedAnalysisResults]) -> Result<Self, AnalysisMergeError> {
+        let Some(first) = runs.first() else {
+            return Ok(Self::default());
+        };
+        for (x, run) in runs.iter().enumerate() {
+            if let Some(reason) = first.run_mismatch(run) {
+                return Err(AnalysisMergeError::MismatchedRuns { run: x, reason });
+            }
+        }
+
+        let results: Vec<AnalysisResults> = runs
+            .iter()
+            .flat_map(|run| run.original_results.iter().cloned())
+            .collect();
+        let file_names = match runs.iter().all(|run| !run.file_names.is_empty()) {
+            true => runs.iter().flat_map(|run| run.file_names.clone()).collect(),
+            false => Vec::new(),
+        };
+        Ok(Self::from_results(&results)?
+            .with_file_names(file_names)
+            .with_ground_truth(first.ground_truth))
+    }
+
+    /// Describes why another run can't be merged with this one, if it can't.
+    /// See [`Self::merge_merged`].
+    fn run_mismatch(&self, run: &MergedAnalysisResults) -> Option<String> {
+        if run.original_results.len() != run.merged_file_count {
+            return Some(format!(
+                "it holds the results of {} of its {} files",
+                run.original_results.len(),
+                run.merged_file_count
+            ));
+        }
+        if run.schema_metadata.name != self.schema_metadata.name {
+            return Some(format!(
+                "its schema is '{}' rather than '{}'",
+                run.schema_metadata.name, self.schema_metadata.name
+            ));
+        }
+
+        let split_names = |r: &MergedAnalysisResults| -> Vec<String> {
+            r.split_comparisons.iter().map(|c| c.name.clone()).collect()
+        };
+        if split_names(run) != split_names(self) {
+            return Some(format!(
+                "its split comparisons are {:?} rather than {:?}",
+                split_names(run),
+                split_names(self)
+            ));
+        }
+
+        let custom_names = |r: &MergedAnalysisResults| -> Vec<(String, Vec<String>)> {
+            r.custom_comparisons
+                .iter()
+                .map(|c| (c.name.clone(), c.group_names.clone()))
+                .collect()
+        };
+        if custom_names(run) != custom_names(self) {
+            return Some(format!(
+                "its custom comparisons (and their groups) are {:?} rather than {:?}",
+                custom_names(run),
+                custom_names(self)
+            ));
+        }
+        None
+    }
+}
+
+/// Create a new [`MergedAnalysisResults`] by merging multiple [`AnalysisResults`] instances.
+/// This efficiently processes all results in a single operation rather than
+/// incrementally merging them one by one.
+pub fn merge_analysis_results(
+    results: &[AnalysisResults],
+) -> Result<MergedAnalysisResults, AnalysisMergeError> {
+    merge_analysis_results_with_weights(results, &MergeWeights::uniform(results.len()))
+}
+
+/// Create a new [`MergedAnalysisResults`] by merging multiple [`AnalysisResults`] instances,
+/// where each result contributes to the averaged metrics in proportion to its weight.
+///
+/// See [`MergedAnalysisResults::from_results_weighted`] for details.
+pub fn merge_analysis_results_weighted(
+    results: &[AnalysisResults],
+    weights: &[f64],
+) -> Result<MergedAnalysisResults, AnalysisMergeError> {
+    merge_analysis_results_with_weights(results, &MergeWeights::new(weights, results.len())?)
+}
+
+/// Create a new [`MergedAnalysisResults`] by merging multiple [`AnalysisResults`] instances,
+/// weighting each result according to the given [`MergeStrategy`].
+///
+/// See [`MergedAnalysisResults::from_results_with_strategy`] for details.
+pub fn merge_analysis_results_with_strategy(
+    results: &[AnalysisResults],
+    strategy: MergeStrategy,
+) -> Result<MergedAnalysisResults, AnalysisMergeError> {
+    match strategy.weights(results) {
+        Some(weights) => merge_analysis_results_weighted(results, &weights),
+        None => merge_analysis_results(results),
+    }
+}
+
+fn merge_analysis_results_with_weights(
+    results: &[AnalysisResults],
+    weights: &MergeWeights,
+) -> Result<MergedAnalysisResults, AnalysisMergeError> {
+    let mut merged = MergedAnalysisResults::default();
+    if results.is_empty() {
+        return Ok(merged);
+    }
+
+    // Calculate (weighted) average of each field.
+    merged.file_entropy = weights.mean_f64(results.iter().map(|r| r.file_entropy));
+    merged.file_lz_matches = weights.mean_u64(results.iter().map(|r| r.file_lz_matches));
+    merged.zstd_file_size = weights.mean_u64(results.iter().map(|r| r.zstd_file_size));
+    merged.original_size = weights.mean_u64(results.iter().map(|r| r.original_size));
+    merged.merged_file_count = results.len();
+
+    // Merge field-level metrics in parallel.
+    // Fields of all files are merged, in case some files have fields others don't.
+    let first = &results[0];
+    merged.schema_metadata = first.schema_metadata.clone();
+    merged.field_mismatches = find_field_mismatches(results);
+
+    merged.per_field = all_field_paths(results)
+        .par_iter()
+        .map(|&full_path| {
+            // Get all matching `full_path` from all other elements as vec,
+            // keeping the weights aligned with the results that contain the field.
+            let (metrics_for_field, field_weights): (Vec<&FieldMetrics>, Vec<f64>) = results
+                .iter()
+                .zip(weights.as_slice())
+                .flat_map(|(results, weight)| {
+                    results.per_field.get(full_path).map(|m| (m, *weight))
+                })
+                .unzip();
+
+            // Weights were validated for all files, but those of the files with the field
+            // may sum to zero.
+            let weights = MergeWeights::new(&field_weights, metrics_for_field.len())
+                .unwrap_or_else(|_| MergeWeights::uniform(metrics_for_field.len()));
+
+            // Return merged FieldMetrics, or error.
+            FieldMetrics::try_merge_many_with_weights(&metrics_for_field, &weights)
+                .map(|merged| (full_path.clone(), merged))
+        })
+        // Convert into HashMap. Need to explicitly set inner AHashMap type, because AHashMap not supported.
+        .collect::<Result<HashMap<String, FieldMetrics, RandomState>, _>>()?
+        .into();
+
+    // Merge split comparisons
+    merged.split_comparisons = merge_split_comparisons(results, weights);
+    merged.custom_comparisons = merge_custom_comparisons(results, weights);
+    merged.original_results = results.to_vec();
+    Ok(merged)
+}
@@ -0,0 +1,337 @@
+//! Merging the split and custom comparisons of multiple files.
+
+use super::{MergedGroupComparisonResult, MergedSplitComparisonResult};
+use crate::comparison::{
+    split_comparison::{FieldComparisonMetrics, ResidualEntropy, SplitComparisonResult},
+    stats::{
+        calculate_custom_zstd_bytes_saved, calculate_custom_zstd_effect_size,
+        calculate_zstd_bytes_saved, calculate_zstd_effect_size, find_custom_zstd_ratio_extremes,
+        find_zstd_ratio_extremes,
+    },
+    GroundTruth,
+};
+use crate::results::{analysis_results::AnalysisResults, merge_weights::MergeWeights};
+
+pub(super) fn merge_split_comparisons(
+    items: &[AnalysisResults],
+    weights: &MergeWeights,
+) -> Vec<MergedSplitComparisonResult> {
+    if items.is_empty() || items[0].split_comparisons.is_empty() {
+        return Vec::new();
+    }
+
+    // Create vector to hold results
+    let comparisons_count = items[0].split_comparisons.len();
+    let mut merged_comparisons = Vec::with_capacity(comparisons_count);
+
+    // For each comparison in the first result...
+    for x in 0..comparisons_count {
+        merged_comparisons.push(merge_split_comparison(x, items, weights));
+    }
+
+    merged_comparisons
+}
+
+fn merge_split_comparison(
+    split_idx: usize,
+    items: &[AnalysisResults],
+    weights: &MergeWeights,
+) -> MergedSplitComparisonResult {
+    let comparisons: Vec<&SplitComparisonResult> = items
+        .iter()
+        .map(|item| &item.split_comparisons[split_idx])
+        .collect();
+
+    let mut merged = MergedSplitComparisonResult {
+        name: comparisons[0].name.clone(),
+        description: comparisons[0].description.clone(),
+        group1_metrics: weights.mean_group_metrics(
+            &comparisons
+                .iter()
+                .map(|c| c.group1_metrics)
+                .collect::<Vec<_>>(),
+        ),
+        group2_metrics: weights.mean_group_metrics(
+            &comparisons
+                .iter()
+                .map(|c| c.group2_metrics)
+                .collect::<Vec<_>>(),
+        ),
+        difference: weights
+            .mean_group_difference(&comparisons.iter().map(|c| c.difference).collect::<Vec<_>>()),
+        baseline_comparison_metrics: merge_field_comparison_metrics(
+            comparisons
+                .iter()
+                .map(|c| c.baseline_comparison_metrics.as_slice()),
+            weights,
+        ),
+        split_comparison_metrics: merge_field_comparison_metrics(
+            comparisons
+                .iter()
+                .map(|c| c.split_comparison_metrics.as_slice()),
+            weights,
+        ),
+        group_estimate_zstd_agreement_percentage: 0.0,
+        group_estimate_false_positive_percentage: 0.0,
+        group_estimate_correct_positive_percentage: 0.0,
+        residual_entropy: merge_residual_entropy(&comparisons, weights),
+        empty_groups: comparisons[0].empty_groups.clone(),
+        size_mismatch_bytes: comparisons
+            .iter()
+            .map(|c| c.size_mismatch_bytes)
+            .max()
+            .unwrap_or_default(),
+        truncated_data_bytes: comparisons
+            .iter()
+            .map(|c| c.truncated_data_bytes)
+            .max()
+            .unwrap_or_default(),
+        zstd_ratio_extremes: find_zstd_ratio_extremes(items, split_idx),
+        zstd_effect_size: calculate_zstd_effect_size(items, split_idx),
+        zstd_bytes_saved: calculate_zstd_bytes_saved(items, split_idx),
+    };
+
+    merged.update_estimate_agreement(items, split_idx, GroundTruth::default());
+    merged
+}
+
+impl MergedSplitComparisonResult {
+    /// Calculates the agreement, false positive and correct positive percentages between the
+    /// estimate and the ground truth compressor on which group compresses better.
+    /// These are the share of files, and are unaffected by weights.
+    pub(super) fn update_estimate_agreement(
+        &mut self,
+        items: &[AnalysisResults],
+        split_idx: usize,
+        ground_truth: GroundTruth,
+    ) {
+        let mut agreement_count = 0;
+        let mut total_count = 0;
+        let mut false_positive_count = 0;
+        let mut correct_positive_count = 0;
+        for item in items {
+            let comparison = &item.split_comparisons[split_idx];
+            let g1 = &comparison.group1_metrics;
+            let g2 = &comparison.group2_metrics;
+            if g1.has_estimate && g2.has_estimate {
+                total_count += 1;
+                let est_g2_better = g2.estimated_size < g1.estimated_size;
+                let actual_g2_better = g2.actual_size(ground_truth) < g1.actual_size(ground_truth);
+                if est_g2_better == actual_g2_better {
+                    agreement_count += 1;
+                }
+
+                // Count false positives: estimator thinks group 2 is better, but it's not
+                if est_g2_better && !actual_g2_better {
+                    false_positive_count += 1;
+                }
+
+                // Count correct positives: estimator thinks group 2 is better, and it is
+                if est_g2_better && actual_g2_better {
+                    correct_positive_count += 1;
+                }
+            }
+        }
+
+        let percentage = |count: usize| {
+            if total_count > 0 {
+                (count as f64 / total_count as f64) * 100.0
+            } else {
+                0.0
+            }
+        };
+        self.group_estimate_zstd_agreement_percentage = percentage(agreement_count);
+        self.group_estimate_false_positive_percentage = percentage(false_positive_count);
+        self.group_estimate_correct_positive_percentage = percentage(correct_positive_count);
+    }
+}
+
+/// Averages the residual entropy over the comparisons (files) where it's available.
+fn merge_residual_entropy(
+    comparisons: &[&SplitComparisonResult],
+    weights: &MergeWeights,
+) -> Option<ResidualEntropy> {
+    let (residuals, residual_weights): (Vec<ResidualEntropy>, Vec<f64>) = comparisons
+        .iter()
+        .zip(weights.as_slice())
+        .filter_map(|(c, weight)| c.residual_entropy.map(|r| (r, *weight)))
+        .unzip();
+
+    if residuals.is_empty() {
+        return None;
+    }
+
+    // Weights were validated for all files, but the subset of them may sum to zero.
+    let weights = MergeWeights::new(&residual_weights, residuals.len())
+        .unwrap_or_else(|_| MergeWeights::uniform(residuals.len()));
+    Some(ResidualEntropy {
+        xor: weights.mean_f64(residuals.iter().map(|r| r.xor)),
+        subtract: weights.mean_f64(residuals.iter().map(|r| r.subtract)),
+    })
+}
+
+/// Averages the per-field metrics of a split comparison, field by field.
+/// The number of fields is taken from the first item.
+fn merge_field_comparison_metrics<'a>(
+    items: impl Iterator<Item = &'a [FieldComparisonMetrics]> + Clone,
+    weights: &MergeWeights,
+) -> Vec<FieldComparisonMetrics> {
+    let field_count = items.clone().next().map_or(0, |first| first.len());
+    (0..field_count)
+        .map(|x| {
+            let field_metrics: Vec<FieldComparisonMetrics> = items
+                .clone()
+                .map(|metrics| metrics.get(x).copied().unwrap_or_default())
+                .collect();
+            weights.mean_field_comparison_metrics(&field_metrics)
+        })
+        .collect()
+}
+
+pub(super) fn merge_custom_comparisons(
+    items: &[AnalysisResults],
+    weights: &MergeWeights,
+) -> Vec<MergedGroupComparisonResult> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+
+    let comparisons_count = items[0].custom_comparisons.len();
+    let mut merged_comparisons = Vec::with_capacity(comparisons_count);
+
+    for x in 0..comparisons_count {
+        merged_comparisons.push(merge_custom_comparison(x, items, weights));
+    }
+
+    merged_comparisons
+}
+
+fn merge_custom_comparison(
+    index: usize,
+    items: &[AnalysisResults],
+    weights: &MergeWeights,
+) -> MergedGroupComparisonResult {
+    let first = &items[0].custom_comparisons[index];
+    let mut merged = MergedGroupComparisonResult {
+        name: first.name.clone(),
+        description: first.description.clone(),
+        baseline_metrics: weights.mean_group_metrics(
+            &items
+                .iter()
+                .map(|item| item.custom_comparisons[index].baseline_metrics)
+                .collect::<Vec<_>>(),
+        ),
+        group_names: first.group_names.clone(),
+        group_metrics: (0..first.group_metrics.len())
+            .map(|group_idx| {
+                weights.mean_group_metrics(
+                    &items
+                        .iter()
+                        .map(|item| item.custom_comparisons[index].group_metrics[group_idx])
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect(),
+        differences: (0..first.differences.len())
+            .map(|diff_idx| {
+                weights.mean_group_difference(
+                    &items
+                        .iter()
+                        .map(|item| item.custom_comparisons[index].differences[diff_idx])
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect(),
+        estimate_zstd_agreement_percentage: 0.0,
+        zstd_ratio_extremes: (0..first.group_metrics.len())
+            .map(|group_idx| find_custom_zstd_ratio_extremes(items, index, group_idx))
+            .collect(),
+        zstd_effect_sizes: (0..first.group_metrics.len())
+            .map(|group_idx| calculate_custom_zstd_effect_size(items, index, group_idx))
+            .collect(),
+        zstd_bytes_saved: calculate_custom_zstd_bytes_saved(items, index),
+    };
+
+    merged.estimate_zstd_agreement_percentage =
+        custom_estimate_agreement(items, index, GroundTruth::default());
+    merged
+}
+
+/// Calculates how often the estimate agrees with the ground truth compressor
+/// on which group (including baseline) has the smallest size, as a ratio of files.
+pub(super) fn custom_estimate_agreement(
+    items: &[AnalysisResults],
+    index: usize,
+    ground_truth: GroundTruth,
+) -> f64 {
+    // This measures how often our estimate correctly identifies the group with the smallest actual size
+    let mut agreement_count = 0;
+    let mut total_count = 0;
+
+    for item in items {
+        // Skip if estimated sizes are not available
+        if !item.custom_comparisons[index].baseline_metrics.has_estimate {
+            continue;
+        }
+
+        // Check if any group metrics are missing estimated sizes
+        let mut missing_estimates = false;
+        for group_metrics in &item.custom_comparisons[index].group_metrics {
+            if !group_metrics.has_estimate {
+                missing_estimates = true;
+                break;
+            }
+        }
+
+        if missing_estimates {
+            continue;
+        }
+
+        total_count += 1;
+
+        // Find the group with the smallest actual size (including baseline)
+        let mut smallest_actual_idx = -1; // -1 means baseline
+        let mut smallest_actual = item.custom_comparisons[index]
+            .baseline_metrics
+            .actual_size(ground_truth);
+
+        for (x, group_metrics) in item.custom_comparisons[index]
+            .group_metrics
+            .iter()
+            .enumerate()
+        {
+            if group_metrics.actual_size(ground_truth) < smallest_actual {
+                smallest_actual = group_metrics.actual_size(ground_truth);
+                smallest_actual_idx = x as i32;
+            }
+        }
+
+        // Find the group with the smallest estimated size (including baseline)
+        let mut smallest_est_idx = -1; // -1 means baseline
+        let mut smallest_est = item.custom_comparisons[index]
+            .baseline_metrics
+            .estimated_size;
+
+        for (x, group_metrics) in item.custom_comparisons[index]
+            .group_metrics
+            .iter()
+            .enumerate()
+        {
+            if group_metrics.estimated_size < smallest_est {
+                smallest_est = group_metrics.estimated_size;
+                smallest_est_idx = x as i32;
+            }
+        }
+
+        // Check if the estimates agree on which group is smallest
+        if smallest_actual_idx == smallest_est_idx {
+            agreement_count += 1;
+        }
+    }
+
+    if total_count > 0 {
+        agreement_count as f64 / total_count as f64
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,779 @@
+use super::{
+    analysis_results::AnalysisResults,
+    field_mismatch::FieldPresenceMismatch,
+    top_fields::{self, FieldMetric},
+    AnalysisMergeError, FieldMetrics,
+};
+use crate::{
+    comparison::{
+        compare_groups::GroupComparisonResult,
+        split_comparison::{
+            calculate_max_entropy_diff, calculate_max_entropy_diff_ratio, FieldComparisonMetrics,
+            ResidualEntropy, SplitComparisonResult,
+        },
+        stats::{EffectSize, ZstdRatioExtremes},
+        GroundTruth, GroupComparisonMetrics, GroupDifference,
+    },
+    schema::Metadata,
+};
+use ahash::AHashMap;
+use serde::Serialize;
+
+mod merge;
+mod merge_comparisons;
+mod print;
+mod print_comparisons;
+
+use merge_comparisons::custom_estimate_agreement;
+
+pub use merge::{
+    merge_analysis_results, merge_analysis_results_weighted, merge_analysis_results_with_strategy,
+};
+
+/// A struct that holds the aggregated results of multiple `AnalysisResults` instances.
+/// It contains the same fields as `AnalysisResults` but represents the merged data
+/// from multiple analyses. This is useful for analyzing results across multiple files
+/// or data instances to identify patterns and trends.
+#[derive(Clone, Default, Serialize)]
+pub struct MergedAnalysisResults {
+    /// Schema metadata
+    pub schema_metadata: Metadata,
+
+    /// Average entropy of the merged files
+    pub file_entropy: f64,
+
+    /// Average LZ compression matches in the merged files
+    pub file_lz_matches: u64,
+
+    /// Average actual size of the compressed data when compressed with zstandard
+    pub zstd_file_size: u64,
+
+    /// Average original size of the uncompressed data
+    pub original_size: u64,
+
+    /// Total number of files that were merged
+    pub merged_file_count: usize,
+
+    /// Field path → computed metrics (merged)
+    /// Maps each field's full path to the merged metrics across all analyzed files
+    #[serde(serialize_with = "super::json::serialize_sorted_map")]
+    pub per_field: AHashMap<String, FieldMetrics>,
+
+    /// Merged split comparison results
+    pub split_comparisons: Vec<MergedSplitComparisonResult>,
+
+    /// Merged custom group comparison results from schema-defined comparisons
+    pub custom_comparisons: Vec<MergedGroupComparisonResult>,
+
+    /// Original analysis results used to create this merged result.
+    /// This is used for calculating statistics across the individual results.
+    /// Not serialized, as it repeats the individual results.
+    #[serde(skip)]
+    pub original_results: Vec<AnalysisResults>,
+
+    /// Names of the merged files, in the same order as [`Self::original_results`].
+    /// Used to label per-file outliers when printing; file indices are printed if empty.
+    pub file_names: Vec<String>,
+
+    /// The compressor the estimator agreement percentages were computed against.
+    pub ground_truth: GroundTruth,
+
+    /// Fields which are present in some of the merged files, but not others.
+    /// The merged metrics of these fields only include the files which have them.
+    pub field_mismatches: Vec<FieldPresenceMismatch>,
+}
+
+/// The result of comparing 2 arbitrary groups of fields based on the schema,
+/// specifically for merged analysis results.
+///
+/// This is similar to [`SplitComparisonResult`] but includes additional information
+/// related to statistics over multiple files.
+#[derive(Clone, Default, Serialize)]
+pub struct MergedSplitComparisonResult {
+    /// The name of the group comparison. (Copied from schema)
+    pub name: String,
+    /// A description of the group comparison. (Copied from schema)
+    pub description: String,
+    /// The metrics for the first group.
+    pub group1_metrics: GroupComparisonMetrics,
+    /// The metrics for the second group.
+    pub group2_metrics: GroupComparisonMetrics,
+    /// Comparison between group 2 and group 1.
+    pub difference: GroupDifference,
+    /// The statistics for the individual fields of the baseline group.
+    pub baseline_comparison_metrics: Vec<FieldComparisonMetrics>,
+    /// The statistics for the individual fields of the split group.
+    pub split_comparison_metrics: Vec<FieldComparisonMetrics>,
+    /// Ratio of how often the estimates and [`MergedAnalysisResults::ground_truth`] sizes
+    /// (zstd by default) agree on which group compresses better.
+    pub group_estimate_zstd_agreement_percentage: f64,
+    /// Percentage of false positives: cases where the estimator predicted an improvement
+    /// (group 2 better than group 1) but the actual (ground truth) compression showed no improvement.
+    pub group_estimate_false_positive_percentage: f64,
+    /// Percentage of correct positives: cases where the estimator predicted an improvement
+    /// (group 2 better than group 1) and the actual (ground truth) compression confirmed this improvement.
+    pub group_estimate_correct_positive_percentage: f64,
+    /// Average entropy of the residual between both groups, over the files where
+    /// both groups had equal length. `None` if no such file exists.
+    pub residual_entropy: Option<ResidualEntropy>,
+    /// The groups which resolve to no fields. (Copied from the first file, as this depends only on the schema)
+    pub empty_groups: Vec<String>,
+    /// The largest [`SplitComparisonResult::size_mismatch_bytes`] of all files.
+    pub size_mismatch_bytes: u64,
+    /// The largest [`SplitComparisonResult::truncated_data_bytes`] of all files.
+    pub truncated_data_bytes: u64,
+    /// The files which benefited the most and least from splitting (by zstd ratio).
+    pub zstd_ratio_extremes: Option<ZstdRatioExtremes>,
+    /// Effect size of group 2 against group 1, from the per-file zstd ratios.
+    /// `None` if fewer than 2 files were merged.
+    pub zstd_effect_size: Option<EffectSize>,
+    /// Total zstd bytes saved across all files, when each file uses the better of both groups.
+    pub zstd_bytes_saved: u64,
+}
+
+/// Contains the merged results of comparing custom field groupings defined in the schema.
+/// This extends [`GroupComparisonResult`] with additional metrics that are calculated when merging multiple results.
+#[derive(Clone, Serialize)]
+pub struct MergedGroupComparisonResult {
+    /// The name of the group comparison. (Copied from schema)
+    pub name: String,
+    /// A description of the group comparison. (Copied from schema)
+    pub description: String,
+    /// Metrics for the baseline group.
+    pub baseline_metrics: GroupComparisonMetrics,
+    /// Names of the comparison groups in order they were specified in the schema
+    pub group_names: Vec<String>,
+    /// Metrics for the comparison groups in schema order
+    pub group_metrics: Vec<GroupComparisonMetrics>,
+    /// Comparison between other groups and first (baseline) group.
+    pub differences: Vec<GroupDifference>,
+    /// Percentage of times that the estimate agrees with the ground truth compressor (zstd by default)
+    /// about which group (including baseline) has the smallest size
+    pub estimate_zstd_agreement_percentage: f64,
+    /// The files which benefited the most and least from each comparison group (by zstd ratio),
+    /// in schema order.
+    pub zstd_ratio_extremes: Vec<Option<ZstdRatioExtremes>>,
+    /// Effect size of each comparison group against the baseline, from the per-file
+    /// zstd ratios, in schema order. `None` if fewer than 2 files were merged.
+    pub zstd_effect_sizes: Vec<Option<EffectSize>>,
+    /// Total zstd bytes saved across all files, when each file uses its smallest group
+    /// (including the baseline).
+    pub zstd_bytes_saved: u64,
+}
+
+impl MergedAnalysisResults {
+    /// Create a new [`MergedAnalysisResults`] instance from a single [`AnalysisResults`].
+    /// This serves as the starting point for merging multiple result sets.
+    pub fn new(results: &AnalysisResults) -> Self {
+        MergedAnalysisResults {
+            schema_metadata: results.schema_metadata.clone(),
+            file_entropy: results.file_entropy,
+            file_lz_matches: results.file_lz_matches,
+            zstd_file_size: results.zstd_file_size,
+            original_size: results.original_size,
+            merged_file_count: 1,
+            per_field: results.per_field.clone(),
+            split_comparisons: MergedSplitComparisonResult::from_split_comparisons(
+                &results.split_comparisons,
+            ),
+            custom_comparisons: MergedGroupComparisonResult::from_group_comparisons(
+                &results.custom_comparisons,
+            ),
+            original_results: vec![results.clone()],
+            file_names: Vec::new(),
+            ground_truth: GroundTruth::default(),
+            field_mismatches: Vec::new(),
+        }
+    }
+
+    /// Sets the names of the merged files, used to label per-file outliers when printing.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_names` - Name of each file, in the same order as [`Self::original_results`].
+    pub fn with_file_names(mut self, file_names: Vec<String>) -> Self {
+        self.file_names = file_names;
+        self
+    }
+
+    /// Recomputes the estimator agreement, false positive and correct positive percentages
+    /// of all comparisons, treating the sizes of the given compressor as the actual sizes.
+    ///
+    /// Merging uses [`GroundTruth::Zstd`] by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `ground_truth` - The compressor to check the size estimator against.
+    pub fn with_ground_truth(mut self, ground_truth: GroundTruth) -> Self {
+        self.ground_truth = ground_truth;
+        for (x, comparison) in self.split_comparisons.iter_mut().enumerate() {
+            comparison.update_estimate_agreement(&self.original_results, x, ground_truth);
+        }
+        for (x, comparison) in self.custom_comparisons.iter_mut().enumerate() {
+            comparison.estimate_zstd_agreement_percentage =
+                custom_estimate_agreement(&self.original_results, x, ground_truth);
+        }
+        self
+    }
+
+    /// Returns an error if any field is present in some of the merged files, but not others.
+    /// See [`Self::field_mismatches`].
+    pub fn ensure_matching_fields(&self) -> Result<(), AnalysisMergeError> {
+        if self.field_mismatches.is_empty() {
+            return Ok(());
+        }
+        Err(AnalysisMergeError::MismatchedFields(
+            self.field_mismatches
+                .iter()
+                .map(|mismatch| mismatch.full_path.clone())
+                .collect(),
+        ))
+    }
+
+    /// Total zstd compressed size of all merged files.
+    pub fn total_zstd_file_size(&self) -> u64 {
+        self.original_results.iter().map(|r| r.zstd_file_size).sum()
+    }
+
+    /// The comparison (split or custom) which saves the most zstd bytes across all files,
+    /// as `(name, bytes saved)`. [`None`] if no comparison saves any bytes.
+    ///
+    /// This is a theoretical best-of-transforms figure; comparisons may transform
+    /// overlapping fields, so their savings are not additive.
+    pub fn best_zstd_savings(&self) -> Option<(&str, u64)> {
+        let split = self
+            .split_comparisons
+            .iter()
+            .map(|c| (c.name.as_str(), c.zstd_bytes_saved));
+        let custom = self
+            .custom_comparisons
+            .iter()
+            .map(|c| (c.name.as_str(), c.zstd_bytes_saved));
+
+        split
+            .chain(custom)
+            .filter(|(_, bytes_saved)| *bytes_saved > 0)
+            .fold(None, |best: Option<(&str, u64)>, candidate| match best {
+                Some(best) if best.1 >= candidate.1 => Some(best),
+                _ => Some(candidate),
+            })
+    }
+
+    /// The zstd ratio of each comparison (split or custom) to its baseline, as `(name, ratio)`,
+    /// in schema order; below 1.0 means the comparison compresses better than its baseline.
+    ///
+    /// The ratio is that of the merged (mean) zstd sizes, as printed. Split comparisons compare
+    /// group 2 to group 1; custom comparisons use their best (smallest) group. Comparisons
+    /// with an empty baseline, or without groups, are omitted.
+    pub fn comparison_zstd_ratios(&self) -> Vec<(&str, f64)> {
+        let split = self.split_comparisons.iter().map(|c| {
+            (
+                c.name.as_str(),
+                c.group1_metrics.zstd_size,
+                Some(c.group2_metrics.zstd_size),
+            )
+        });
+        let custom = self.custom_comparisons.iter().map(|c| {
+            (
+                c.name.as_str(),
+                c.baseline_metrics.zstd_size,
+                c.group_metrics.iter().map(|g| g.zstd_size).min(),
+            )
+        });
+
+        split
+            .chain(custom)
+            .filter_map(|(name, baseline, best)| match (baseline, best) {
+                (0, _) | (_, None) => None,
+                (baseline, Some(best)) => Some((name, best as f64 / baseline as f64)),
+            })
+            .collect()
+    }
+
+    /// Up to `n` fields (not groups) with the highest `metric`, highest first.
+    /// Ties are broken by full path. See [`top_fields`].
+    pub fn top_fields_by(&self, metric: FieldMetric, n: usize) -> Vec<&FieldMetrics> {
+        top_fields::top_fields_by(&self.per_field, metric, n)
+    }
+
+    /// Convert the merged file statistics into a `FieldMetrics` object for comparisons
+    pub fn as_field_metrics(&self) -> FieldMetrics {
+        FieldMetrics {
+            name: String::new(),
+            full_path: String::new(),
+            depth: 0,
+            estimated_size: 0,
+            zstd_size: self.zstd_file_size,
+            entropy_coded_size: None,
+            entropy_order1: None,
+            run_stats: None,
+            reference_size: None,
+            original_size: self.original_size,
+            count: 0,
+            lenbits: 0,
+            entropy: self.file_entropy,
+            windowed_entropy: Vec::new(),
+            lz_matches: self.file_lz_matches,
+            bit_counts: Vec::new(),
+            bit_order: crate::schema::BitOrder::Default,
+            value_counts: rustc_hash::FxHashMap::default(),
+            value_counts_overflowed: false,
+            is_constant: false,
+            constant_value: None,
+            value_transitions: None,
+            interpretation: crate::schema::ValueInterpretation::default(),
+            reserved: false,
+        }
+    }
+}
+
+/// Helper functions around [`MergedSplitComparisonResult`]
+impl MergedSplitComparisonResult {
+    /// Create a new [`MergedSplitComparisonResult`] from a [`SplitComparisonResult`]
+    pub fn from_split_comparison(result: &SplitComparisonResult) -> Self {
+        Self {
+            name: result.name.clone(),
+            description: result.description.clone(),
+            group1_metrics: result.group1_metrics,
+            group2_metrics: result.group2_metrics,
+            difference: result.difference,
+            baseline_comparison_metrics: result.baseline_comparison_metrics.clone(),
+            split_comparison_metrics: result.split_comparison_metrics.clone(),
+            group_estimate_zstd_agreement_percentage: 0.0,
+            group_estimate_false_positive_percentage: 0.0,
+            group_estimate_correct_positive_percentage: 0.0,
+            residual_entropy: result.residual_entropy,
+            empty_groups: result.empty_groups.clone(),
+            size_mismatch_bytes: result.size_mismatch_bytes,
+            truncated_data_bytes: result.truncated_data_bytes,
+            zstd_ratio_extremes: None,
+            zstd_effect_size: None,
+            zstd_bytes_saved: result.zstd_bytes_saved(),
+        }
+    }
+
+    /// Convert a Vec of SplitComparisonResult to a Vec of MergedSplitComparisonResult
+    pub fn from_split_comparisons(results: &[SplitComparisonResult]) -> Vec<Self> {
+        results.iter().map(Self::from_split_comparison).collect()
+    }
+
+    /// Ratio between the max and min entropy of the baseline fields.
+    pub fn baseline_max_entropy_diff_ratio(&self) -> f64 {
+        calculate_max_entropy_diff_ratio(&self.baseline_comparison_metrics)
+    }
+
+    /// Maximum difference between the entropy of the baseline fields.
+    pub fn baseline_max_entropy_diff(&self) -> f64 {
+        calculate_max_entropy_diff(&self.baseline_comparison_metrics)
+    }
+
+    /// Maximum difference between the entropy of the split fields.
+    pub fn split_max_entropy_diff(&self) -> f64 {
+        calculate_max_entropy_diff(&self.split_comparison_metrics)
+    }
+
+    /// Ratio between the max and min entropy of the split fields.
+    pub fn split_max_entropy_diff_ratio(&self) -> f64 {
+        calculate_max_entropy_diff_ratio(&self.split_comparison_metrics)
+    }
+
+    /// Convert to a [`SplitComparisonResult`] (primarily for backward compatibility)
+    pub fn to_split_comparison(&self) -> SplitComparisonResult {
+        SplitComparisonResult {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            group1_metrics: self.group1_metrics,
+            group2_metrics: self.group2_metrics,
+            difference: self.difference,
+            baseline_comparison_metrics: self.baseline_comparison_metrics.clone(),
+            split_comparison_metrics: self.split_comparison_metrics.clone(),
+            residual_entropy: self.residual_entropy,
+            empty_groups: self.empty_groups.clone(),
+            size_mismatch_bytes: self.size_mismatch_bytes,
+            truncated_data_bytes: self.truncated_data_bytes,
+        }
+    }
+}
+
+impl MergedGroupComparisonResult {
+    fn from_group_comparisons(
+        custom_comparisons: &[GroupComparisonResult],
+    ) -> Vec<MergedGroupComparisonResult> {
+        custom_comparisons
+            .iter()
+            .map(Self::from_group_comparison)
+            .collect()
+    }
+
+    fn from_group_comparison(comparison: &GroupComparisonResult) -> Self {
+        MergedGroupComparisonResult {
+            name: comparison.name.clone(),
+            description: comparison.description.clone(),
+            baseline_metrics: comparison.baseline_metrics,
+            group_names: comparison.group_names.clone(),
+            group_metrics: comparison.group_metrics.clone(),
+            differences: comparison.differences.clone(),
+            estimate_zstd_agreement_percentage: 0.0,
+            zstd_ratio_extremes: Vec::new(),
+            zstd_effect_sizes: Vec::new(),
+            zstd_bytes_saved: comparison.zstd_bytes_saved(),
+        }
+    }
+}
+
+impl From<GroupComparisonResult> for MergedGroupComparisonResult {
+    fn from(result: GroupComparisonResult) -> Self {
+        let zstd_bytes_saved = result.zstd_bytes_saved();
+        Self {
+            name: result.name,
+            description: result.description,
+            baseline_metrics: result.baseline_metrics,
+            group_names: result.group_names,
+            group_metrics: result.group_metrics,
+            differences: result.differences,
+            estimate_zstd_agreement_percentage: 0.0,
+            zstd_ratio_extremes: Vec::new(),
+            zstd_effect_sizes: Vec::new(),
+            zstd_bytes_saved,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_entries, CompressionOptions};
+    use crate::comparison::stats::calculate_effect_size;
+    use crate::results::{color::ColorMode, PrintFormat};
+    use crate::schema::Schema;
+
+    fn analyze(schema: &Schema, data: &[u8]) -> AnalysisResults {
+        analyze_entries(schema, CompressionOptions::default(), data.chunks(2))
+    }
+
+    const SCHEMA: &str = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    a: 8
+    b: 8
+analysis:
+  split_groups:
+    - name: a_b
+      group_1: [a, b]
+      group_2: [b, a]
+"#;
+
+    fn results() -> Vec<AnalysisResults> {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+
+        vec![
+            analyze(&schema, &[1, 2, 3, 4]),
+            analyze(&schema, &(0..=255u8).collect::<Vec<_>>()),
+        ]
+    }
+
+    #[test]
+    fn weighted_merge_uses_weighted_means() {
+        let results = results();
+        let merged = MergedAnalysisResults::from_results_weighted(&results, &[3.0, 1.0]).unwrap();
+        let weighted_mean = |a: u64, b: u64| (a as f64 * 3.0 + b as f64) / 4.0;
+
+        assert_eq!(merged.merged_file_count, 2);
+        assert_eq!(
+            merged.original_size,
+            weighted_mean(results[0].original_size, results[1].original_size) as u64
+        );
+        assert_eq!(
+            merged.zstd_file_size,
+            weighted_mean(results[0].zstd_file_size, results[1].zstd_file_size) as u64
+        );
+        assert!(
+            (merged.file_entropy - (results[0].file_entropy * 3.0 + results[1].file_entropy) / 4.0)
+                .abs()
+                < 1e-9
+        );
+
+        let field = &merged.per_field["a"];
+        assert_eq!(
+            field.original_size,
+            weighted_mean(
+                results[0].per_field["a"].original_size,
+                results[1].per_field["a"].original_size
+            ) as u64
+        );
+
+        let split = &merged.split_comparisons[0];
+        assert_eq!(
+            split.group1_metrics.zstd_size,
+            weighted_mean(
+                results[0].split_comparisons[0].group1_metrics.zstd_size,
+                results[1].split_comparisons[0].group1_metrics.zstd_size
+            ) as u64
+        );
+    }
+
+    #[test]
+    fn uniform_weights_match_unweighted_merge() {
+        let results = results();
+        let weighted = MergedAnalysisResults::from_results_weighted(&results, &[1.0, 1.0]).unwrap();
+        let unweighted = MergedAnalysisResults::from_results(&results).unwrap();
+
+        assert_eq!(weighted.original_size, unweighted.original_size);
+        assert_eq!(weighted.zstd_file_size, unweighted.zstd_file_size);
+        assert_eq!(weighted.file_lz_matches, unweighted.file_lz_matches);
+        assert_eq!(
+            weighted.split_comparisons[0].difference,
+            unweighted.split_comparisons[0].difference
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_weights() {
+        let results = results();
+        assert!(matches!(
+            MergedAnalysisResults::from_results_weighted(&results, &[1.0]),
+            Err(AnalysisMergeError::WeightCountMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn merging_runs_weights_them_by_file_count() {
+        let results = results();
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        let run_1 = MergedAnalysisResults::from_results(&results)
+            .unwrap()
+            .with_file_names(names(&["small", "large"]));
+        let run_2 = MergedAnalysisResults::from_results(&results[1..])
+            .unwrap()
+            .with_file_names(names(&["large 2"]));
+
+        let merged = MergedAnalysisResults::merge_merged(&[run_1, run_2]).unwrap();
+        assert_eq!(merged.merged_file_count, 3);
+        assert_eq!(merged.original_results.len(), 3);
+        assert_eq!(merged.file_names, ["small", "large", "large 2"]);
+        assert_eq!(
+            merged.original_size,
+            (results[0].original_size + 2 * results[1].original_size) / 3
+        );
+        assert_eq!(
+            merged.split_comparisons[0].group1_metrics.zstd_size,
+            (results[0].split_comparisons[0].group1_metrics.zstd_size
+                + 2 * results[1].split_comparisons[0].group1_metrics.zstd_size)
+                / 3
+        );
+    }
+
+    #[test]
+    fn merging_runs_rejects_different_comparisons() {
+        let run_1 = MergedAnalysisResults::from_results(&results()).unwrap();
+        let schema = Schema::from_yaml(&SCHEMA.replace("name: a_b", "name: b_a")).unwrap();
+        let run_2 = MergedAnalysisResults::new(&analyze(&schema, &[1, 2, 3, 4]));
+
+        assert!(matches!(
+            MergedAnalysisResults::merge_merged(&[run_1, run_2]),
+            Err(AnalysisMergeError::MismatchedRuns { run: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn records_best_and_worst_file_per_comparison() {
+        let results = results();
+        let merged = MergedAnalysisResults::from_results(&results)
+            .unwrap()
+            .with_file_names(vec!["small".to_string(), "large".to_string()]);
+
+        let ratio = |r: &AnalysisResults| {
+            let c = &r.split_comparisons[0];
+            c.group2_metrics.zstd_size as f64 / c.group1_metrics.zstd_size as f64
+        };
+        let (ratio_0, ratio_1) = (ratio(&results[0]), ratio(&results[1]));
+        let extremes = merged.split_comparisons[0].zstd_ratio_extremes.unwrap();
+        assert_eq!(extremes.best_ratio, ratio_0.min(ratio_1));
+        assert_eq!(extremes.worst_ratio, ratio_0.max(ratio_1));
+        assert_eq!(
+            extremes.best_file_index,
+            if ratio_1 < ratio_0 { 1 } else { 0 }
+        );
+
+        let mut output = Vec::new();
+        merged
+            .print(
+                &mut output,
+                &Schema::from_yaml(SCHEMA).unwrap(),
+                PrintFormat::Concise,
+                true,
+                ColorMode::Never,
+            )
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Best File (zstd ratio): "));
+        assert!(output.contains("Worst File (zstd ratio): "));
+        assert!(output.contains("Base Est/Zstd correlation: r="));
+        assert!(output.contains("Comp Est/Zstd correlation: r="));
+    }
+
+    #[test]
+    fn effect_size_distinguishes_consistent_from_noisy_savings() {
+        // Both save 10% on average.
+        let consistent = calculate_effect_size(&[0.89, 0.90, 0.91]).unwrap();
+        let noisy = calculate_effect_size(&[0.5, 0.9, 1.3]).unwrap();
+        assert!((consistent.mean_difference - 0.1).abs() < 1e-9);
+        assert!((noisy.mean_difference - 0.1).abs() < 1e-9);
+        assert!((consistent.cohens_d - 10.0).abs() < 1e-6);
+        assert!((noisy.cohens_d - 0.25).abs() < 1e-6);
+        assert_eq!(consistent.magnitude(), "large");
+        assert_eq!(noisy.magnitude(), "small");
+        // The noisy interval includes 'no saving', the consistent one doesn't.
+        assert!(consistent.confidence_low > 0.0);
+        assert!(noisy.confidence_low < 0.0 && noisy.confidence_high > 0.0);
+        assert!(calculate_effect_size(&[0.9]).is_none());
+
+        let merged = MergedAnalysisResults::from_results(&results()).unwrap();
+        assert_eq!(
+            merged.split_comparisons[0].zstd_effect_size.unwrap().count,
+            2
+        );
+    }
+
+    #[test]
+    fn agreement_is_measured_against_ground_truth() {
+        let mut results = results();
+        // Group 2 actually compresses better in both files,
+        // but the estimator only predicts this for the first one.
+        for (result, estimated_size) in results.iter_mut().zip([50, 150]) {
+            let split = &mut result.split_comparisons[0];
+            split.group1_metrics.zstd_size = 100;
+            split.group2_metrics.zstd_size = 90;
+            split.group1_metrics.estimated_size = 100;
+            split.group2_metrics.estimated_size = estimated_size;
+        }
+
+        let merged = MergedAnalysisResults::from_results(&results).unwrap();
+        let split = &merged.split_comparisons[0];
+        assert_eq!(merged.ground_truth, GroundTruth::Zstd);
+        assert_eq!(split.group_estimate_zstd_agreement_percentage, 50.0);
+        assert_eq!(split.group_estimate_false_positive_percentage, 0.0);
+        assert_eq!(split.group_estimate_correct_positive_percentage, 50.0);
+
+        let recomputed = merged.clone().with_ground_truth(GroundTruth::Zstd);
+        let recomputed_split = &recomputed.split_comparisons[0];
+        assert_eq!(
+            recomputed_split.group_estimate_zstd_agreement_percentage,
+            split.group_estimate_zstd_agreement_percentage
+        );
+        assert_eq!(
+            recomputed_split.group_estimate_false_positive_percentage,
+            split.group_estimate_false_positive_percentage
+        );
+        assert_eq!("zstd".parse::<GroundTruth>().unwrap(), GroundTruth::Zstd);
+    }
+
+    #[test]
+    fn agreement_is_measured_against_brotli_sizes() {
+        let mut results = results();
+        // Zstd prefers group 2 in both files, Brotli only in the first; so does the estimator.
+        for (result, (estimated_size, brotli_size)) in
+            results.iter_mut().zip([(50, 90), (150, 110)])
+        {
+            let split = &mut result.split_comparisons[0];
+            split.group1_metrics.zstd_size = 100;
+            split.group2_metrics.zstd_size = 90;
+            split.group1_metrics.brotli_size = Some(100);
+            split.group2_metrics.brotli_size = Some(brotli_size);
+            split.group1_metrics.estimated_size = 100;
+            split.group2_metrics.estimated_size = estimated_size;
+        }
+
+        let zstd = MergedAnalysisResults::from_results(&results).unwrap();
+        assert_eq!(
+            zstd.split_comparisons[0].group_estimate_zstd_agreement_percentage,
+            50.0
+        );
+        assert_eq!(
+            zstd.split_comparisons[0].group2_metrics.brotli_size,
+            Some(100)
+        );
+
+        let brotli = zstd.with_ground_truth(GroundTruth::Brotli);
+        assert_eq!(
+            brotli.split_comparisons[0].group_estimate_zstd_agreement_percentage,
+            100.0
+        );
+        assert_eq!(
+            "brotli".parse::<GroundTruth>().unwrap(),
+            GroundTruth::Brotli
+        );
+    }
+
+    #[test]
+    fn zero_estimates_count_towards_agreement() {
+        let mut results = results();
+        // The estimator ran and estimated 0 for group 2 of the first file; it is a valid estimate.
+        for (result, has_estimate) in results.iter_mut().zip([true, false]) {
+            let split = &mut result.split_comparisons[0];
+            split.group1_metrics.zstd_size = 100;
+            split.group2_metrics.zstd_size = 90;
+            split.group1_metrics.estimated_size = 100;
+            split.group2_metrics.estimated_size = 0;
+            split.group2_metrics.has_estimate = has_estimate;
+        }
+
+        // Only the first file has estimates for both groups.
+        let merged = MergedAnalysisResults::from_results(&results).unwrap();
+        let split = &merged.split_comparisons[0];
+        assert_eq!(split.group_estimate_zstd_agreement_percentage, 100.0);
+        assert_eq!(split.group_estimate_correct_positive_percentage, 100.0);
+    }
+
+    #[test]
+    fn reports_best_achievable_zstd_savings() {
+        let mut results = results();
+        for (result, (group1, group2)) in results.iter_mut().zip([(100, 90), (200, 250)]) {
+            let split = &mut result.split_comparisons[0];
+            split.group1_metrics.zstd_size = group1;
+            split.group2_metrics.zstd_size = group2;
+        }
+
+        // Only the first file benefits from splitting; the second keeps group 1.
+        let merged = MergedAnalysisResults::from_results(&results).unwrap();
+        assert_eq!(merged.split_comparisons[0].zstd_bytes_saved, 10);
+        assert_eq!(merged.best_zstd_savings(), Some(("a_b", 10)));
+        assert_eq!(
+            merged.total_zstd_file_size(),
+            results[0].zstd_file_size + results[1].zstd_file_size
+        );
+
+        let mut output = Vec::new();
+        merged
+            .print(
+                &mut output,
+                &Schema::from_yaml(SCHEMA).unwrap(),
+                PrintFormat::Concise,
+                true,
+                ColorMode::Never,
+            )
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Best Achievable Savings (zstd): 10 of "));
+        assert!(output.contains("via 'a_b'"));
+    }
+
+    #[test]
+    fn reports_zstd_ratio_of_each_comparison() {
+        let mut results = results();
+        for (result, (group1, group2)) in results.iter_mut().zip([(100, 90), (200, 250)]) {
+            let split = &mut result.split_comparisons[0];
+            split.group1_metrics.zstd_size = group1;
+            split.group2_metrics.zstd_size = group2;
+        }
+
+        // Ratio of the mean sizes: 170 / 150.
+        let mut merged = MergedAnalysisResults::from_results(&results).unwrap();
+        assert_eq!(merged.comparison_zstd_ratios(), [("a_b", 170.0 / 150.0)]);
+
+        merged.split_comparisons[0].group1_metrics.zstd_size = 0;
+        assert!(merged.comparison_zstd_ratios().is_empty());
+    }
+}
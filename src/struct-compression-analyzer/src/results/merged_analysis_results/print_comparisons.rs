@@ -0,0 +1,457 @@
+//! Printing the split and custom comparisons of merged results.
+
+use super::{MergedAnalysisResults, MergedGroupComparisonResult, MergedSplitComparisonResult};
+use crate::results::{
+    color::color_ratio, print_brotli_size, print_deflate_size, print_empty_split_groups,
+    print_entropy_order1, print_estimate_breakdown, print_reference_size, print_residual_entropy,
+    print_truncated_data_warning, print_zero_estimate_warning, print_zstd_compress_time,
+    print_zstd_dict_size,
+};
+use crate::{
+    comparison::stats::{
+        calculate_custom_zstd_ratio_stats, calculate_estimate_correlation,
+        calculate_zstd_ratio_stats, format_effect_size, format_size_correlation, format_stats,
+        EffectSize, SizeCorrelation, ZstdRatioExtremes,
+    },
+    results::calculate_percentage,
+};
+use std::io::{self, Write};
+
+impl MergedAnalysisResults {
+    pub(super) fn detailed_print_comparison<W: Write>(
+        &self,
+        writer: &mut W,
+        comparison: &MergedSplitComparisonResult,
+        colored: bool,
+    ) -> io::Result<()> {
+        self.concise_print_split_comparison(writer, comparison, colored)
+    }
+
+    pub(super) fn concise_print_split_comparison<W: Write>(
+        &self,
+        writer: &mut W,
+        comparison: &MergedSplitComparisonResult,
+        colored: bool,
+    ) -> io::Result<()> {
+        let base_lz = comparison.group1_metrics.lz_matches;
+        let size_orig = comparison.group1_metrics.original_size;
+        let size_comp = comparison.group2_metrics.original_size;
+        let base_entropy = comparison.group1_metrics.entropy;
+
+        let base_zstd = comparison.group1_metrics.zstd_size;
+        let base_estimated = comparison.group1_metrics.estimated_size;
+
+        let comp_lz = comparison.group2_metrics.lz_matches;
+        let comp_entropy = comparison.group2_metrics.entropy;
+
+        let comp_zstd = comparison.group2_metrics.zstd_size;
+        let comp_estimated = comparison.group2_metrics.estimated_size;
+
+        let ratio_zstd = calculate_percentage(comp_zstd as f64, base_zstd as f64);
+        let diff_zstd = comparison.difference.zstd_size;
+
+        writeln!(writer, "  {}: {}", comparison.name, comparison.description)?;
+        writeln!(writer, "    Original Size: {}", size_orig)?;
+        writeln!(
+            writer,
+            "    Base LZ, Entropy: ({}, {:.2})",
+            base_lz, base_entropy
+        )?;
+        writeln!(
+            writer,
+            "    Comp LZ, Entropy: ({}, {:.2})",
+            comp_lz, comp_entropy
+        )?;
+        writeln!(
+            writer,
+            "    Base Group LZ, Entropy: ({:?}, {:?})",
+            comparison
+                .baseline_comparison_metrics
+                .iter()
+                .map(|m| m.lz_matches)
+                .collect::<Vec<_>>(),
+            comparison
+                .baseline_comparison_metrics
+                .iter()
+                .map(|m| format!("{:.2}", m.entropy))
+                .collect::<Vec<_>>()
+        )?;
+        writeln!(
+            writer,
+            "    Comp Group LZ, Entropy: ({:?}, {:?})",
+            comparison
+                .split_comparison_metrics
+                .iter()
+                .map(|m| m.lz_matches)
+                .collect::<Vec<_>>(),
+            comparison
+                .split_comparison_metrics
+                .iter()
+                .map(|m| format!("{:.2}", m.entropy))
+                .collect::<Vec<_>>()
+        )?;
+
+        if comparison.group1_metrics.has_estimate {
+            writeln!(
+                writer,
+                "    Base (est/zstd): {}/{}",
+                base_estimated, base_zstd
+            )?;
+        } else {
+            writeln!(writer, "    Base (zstd): {}", base_zstd)?;
+        }
+        print_estimate_breakdown(writer, "    Base ", &comparison.group1_metrics)?;
+        print_brotli_size(writer, "    Base ", &comparison.group1_metrics)?;
+        print_deflate_size(writer, "    Base ", &comparison.group1_metrics)?;
+        print_zstd_dict_size(writer, "    Base ", &comparison.group1_metrics)?;
+        print_entropy_order1(writer, "    Base ", &comparison.group1_metrics)?;
+        print_reference_size(writer, "    Base ", &comparison.group1_metrics)?;
+        print_zstd_compress_time(writer, "    Base ", &comparison.group1_metrics)?;
+        print_zero_estimate_warning(writer, "    Base ", &comparison.group1_metrics)?;
+
+        if comparison.group2_metrics.has_estimate {
+            writeln!(
+                writer,
+                "    Comp (est/zstd): {}/{}",
+                comp_estimated, comp_zstd
+            )?;
+        } else {
+            writeln!(writer, "    Comp (zstd): {}", comp_zstd)?;
+        }
+        print_estimate_breakdown(writer, "    Comp ", &comparison.group2_metrics)?;
+        print_brotli_size(writer, "    Comp ", &comparison.group2_metrics)?;
+        print_deflate_size(writer, "    Comp ", &comparison.group2_metrics)?;
+        print_zstd_dict_size(writer, "    Comp ", &comparison.group2_metrics)?;
+        print_entropy_order1(writer, "    Comp ", &comparison.group2_metrics)?;
+        print_reference_size(writer, "    Comp ", &comparison.group2_metrics)?;
+        print_zstd_compress_time(writer, "    Comp ", &comparison.group2_metrics)?;
+        print_zero_estimate_warning(writer, "    Comp ", &comparison.group2_metrics)?;
+
+        writeln!(
+            writer,
+            "    Ratio (zstd): {}",
+            color_ratio(ratio_zstd.to_string(), ratio_zstd, colored)
+        )?;
+        writeln!(writer, "    Diff (zstd): {}", diff_zstd)?;
+        print_residual_entropy(writer, comparison.residual_entropy.as_ref())?;
+        print_empty_split_groups(writer, &comparison.empty_groups)?;
+        print_truncated_data_warning(writer, comparison.truncated_data_bytes)?;
+        writeln!(
+            writer,
+            "    Est/{} Agreement on Better Group: {:.1}%",
+            self.ground_truth.name(),
+            comparison.group_estimate_zstd_agreement_percentage
+        )?;
+        writeln!(
+            writer,
+            "    Est/{} False Positives: {:.1}%",
+            self.ground_truth.name(),
+            comparison.group_estimate_false_positive_percentage
+        )?;
+        writeln!(
+            writer,
+            "    Est/{} Correct Positives: {:.1}%",
+            self.ground_truth.name(),
+            comparison.group_estimate_correct_positive_percentage
+        )?;
+
+        // If we have enough files for statistics, show the detailed stats
+        writeln!(writer, "    Zstd Ratio Statistics:")?;
+
+        // Find the index of this comparison in the split_comparisons array
+        let comp_index = self
+            .split_comparisons
+            .iter()
+            .position(|c| c.name == comparison.name)
+            .unwrap_or(0);
+
+        // Calculate and print the zstd ratio statistics
+        if let Some(stats) = calculate_zstd_ratio_stats(&self.original_results, comp_index) {
+            writeln!(writer, "    * {}", format_stats(&stats))?;
+        } else {
+            writeln!(writer, "    * No statistics available (insufficient data)")?;
+        }
+        self.print_zstd_ratio_extremes(writer, "    ", comparison.zstd_ratio_extremes.as_ref())?;
+        print_zstd_effect_size(writer, "    ", comparison.zstd_effect_size.as_ref())?;
+        let split_comparisons = || {
+            self.original_results
+                .iter()
+                .filter_map(|r| r.split_comparisons.get(comp_index))
+        };
+        print_estimate_correlation(
+            writer,
+            "    Base ",
+            calculate_estimate_correlation(split_comparisons().map(|c| &c.group1_metrics)),
+        )?;
+        print_estimate_correlation(
+            writer,
+            "    Comp ",
+            calculate_estimate_correlation(split_comparisons().map(|c| &c.group2_metrics)),
+        )?;
+        writeln!(
+            writer,
+            "    Total Bytes Saved (zstd, all files): {}",
+            comparison.zstd_bytes_saved
+        )?;
+
+        if comparison.size_mismatch_bytes != 0 {
+            writeln!(writer, "    [WARNING!!] Sizes of both groups in bytes don't match!! They may vary by a few bytes due to padding.")?;
+            writeln!(writer, "    [WARNING!!] However if they vary extremely, your groups may be incorrect. group1: {}, group2: {}, largest mismatch in a file: {} bytes", size_orig, size_comp, comparison.size_mismatch_bytes)?;
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn concise_print_custom_comparison<W: Write>(
+        &self,
+        writer: &mut W,
+        comparison: &MergedGroupComparisonResult,
+        colored: bool,
+    ) -> io::Result<()> {
+        let base_lz = comparison.baseline_metrics.lz_matches;
+        let base_entropy = comparison.baseline_metrics.entropy;
+        let base_zstd = comparison.baseline_metrics.zstd_size;
+        let base_estimated = comparison.baseline_metrics.estimated_size;
+        let base_size = comparison.baseline_metrics.original_size;
+
+        writeln!(writer, "  {}: {}", comparison.name, comparison.description)?;
+        writeln!(
+            writer,
+            "    Overall Est/{} Agreement on Best Group: {:.1}%",
+            self.ground_truth.name(),
+            comparison.estimate_zstd_agreement_percentage * 100.0
+        )?;
+        writeln!(
+            writer,
+            "    Total Bytes Saved (zstd, all files, best group): {}",
+            comparison.zstd_bytes_saved
+        )?;
+        writeln!(writer, "    Base Group:")?;
+        writeln!(writer, "      Size: {}", base_size)?;
+        writeln!(
+            writer,
+            "      LZ, Entropy: ({}, {:.2})",
+            base_lz, base_entropy
+        )?;
+        if comparison.baseline_metrics.has_estimate {
+            writeln!(
+                writer,
+                "      Base (est/zstd): {}/{}",
+                base_estimated, base_zstd
+            )?;
+        } else {
+            writeln!(writer, "      Base (zstd): {}", base_zstd)?;
+        }
+        print_estimate_breakdown(writer, "      Base ", &comparison.baseline_metrics)?;
+        print_brotli_size(writer, "      Base ", &comparison.baseline_metrics)?;
+        print_deflate_size(writer, "      Base ", &comparison.baseline_metrics)?;
+        print_zstd_dict_size(writer, "      Base ", &comparison.baseline_metrics)?;
+        print_entropy_order1(writer, "      Base ", &comparison.baseline_metrics)?;
+        print_reference_size(writer, "      Base ", &comparison.baseline_metrics)?;
+        print_zstd_compress_time(writer, "      Base ", &comparison.baseline_metrics)?;
+        print_zero_estimate_warning(writer, "      Base ", &comparison.baseline_metrics)?;
+
+        // Find the index of this comparison in the custom_comparisons array
+        let comp_index = self
+            .custom_comparisons
+            .iter()
+            .position(|c| c.name == comparison.name);
+        let custom_comparisons = || {
+            self.original_results
+                .iter()
+                .filter_map(move |r| r.custom_comparisons.get(comp_index?))
+        };
+        print_estimate_correlation(
+            writer,
+            "      Base ",
+            calculate_estimate_correlation(custom_comparisons().map(|c| &c.baseline_metrics)),
+        )?;
+
+        for (x, (group_name, metrics)) in comparison
+            .group_names
+            .iter()
+            .zip(&comparison.group_metrics)
+            .enumerate()
+        {
+            let comp_lz = metrics.lz_matches;
+            let comp_entropy = metrics.entropy;
+            let comp_zstd = metrics.zstd_size;
+            let comp_estimated = metrics.estimated_size;
+            let comp_size = metrics.original_size;
+
+            let ratio_zstd = calculate_percentage(comp_zstd as f64, base_zstd as f64);
+            let diff_zstd = comparison.differences[x].zstd_size;
+
+            writeln!(writer, "\n    {} Group:", group_name)?;
+            writeln!(writer, "      Size: {}", comp_size)?;
+            writeln!(
+                writer,
+                "      LZ, Entropy: ({}, {:.2})",
+                comp_lz, comp_entropy
+            )?;
+            if metrics.has_estimate {
+                writeln!(
+                    writer,
+                    "      Comp (est/zstd): {}/{}",
+                    comp_estimated, comp_zstd
+                )?;
+            } else {
+                writeln!(writer, "      Comp (zstd): {}", comp_zstd)?;
+            }
+            print_estimate_breakdown(writer, "      Comp ", metrics)?;
+            print_brotli_size(writer, "      Comp ", metrics)?;
+            print_deflate_size(writer, "      Comp ", metrics)?;
+            print_zstd_dict_size(writer, "      Comp ", metrics)?;
+            print_entropy_order1(writer, "      Comp ", metrics)?;
+            print_reference_size(writer, "      Comp ", metrics)?;
+            print_zstd_compress_time(writer, "      Comp ", metrics)?;
+            print_zero_estimate_warning(writer, "      Comp ", metrics)?;
+            writeln!(
+                writer,
+                "      Ratio (zstd): {}",
+                color_ratio(format!("{:.1}%", ratio_zstd), ratio_zstd, colored)
+            )?;
+            writeln!(writer, "      Diff (zstd): {}", diff_zstd)?;
+
+            if let Some(comp_index) = comp_index {
+                // Calculate and print the zstd ratio statistics for this group
+                if let Some(stats) =
+                    calculate_custom_zstd_ratio_stats(&self.original_results, comp_index, x)
+                {
+                    writeln!(writer, "      Zstd Ratio Statistics:")?;
+                    writeln!(writer, "      * {}", format_stats(&stats))?;
+                }
+            }
+            self.print_zstd_ratio_extremes(
+                writer,
+                "      ",
+                comparison
+                    .zstd_ratio_extremes
+                    .get(x)
+                    .copied()
+                    .flatten()
+                    .as_ref(),
+            )?;
+            print_zstd_effect_size(
+                writer,
+                "      ",
+                comparison
+                    .zstd_effect_sizes
+                    .get(x)
+                    .copied()
+                    .flatten()
+                    .as_ref(),
+            )?;
+            print_estimate_correlation(
+                writer,
+                "      ",
+                calculate_estimate_correlation(
+                    custom_comparisons().filter_map(|c| c.group_metrics.get(x)),
+                ),
+            )?;
+
+            if base_size != comp_size {
+                writeln!(writer, "      [WARNING!!] Sizes of base and comparison groups don't match!! They may vary by a few bytes due to padding.")?;
+                writeln!(writer, "      [WARNING!!] However if they vary extremely, your groups may be incorrect. base: {}, {}: {}", base_size, group_name, comp_size)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prints the headline of the comparison which saves the most zstd bytes across all files.
+    pub(super) fn print_best_zstd_savings<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let Some((name, bytes_saved)) = self.best_zstd_savings() else {
+            return Ok(());
+        };
+
+        let total_zstd_size = self.total_zstd_file_size();
+        writeln!(
+            writer,
+            "Best Achievable Savings (zstd): {} of {} bytes ({:.2}%) via '{}'",
+            bytes_saved,
+            total_zstd_size,
+            calculate_percentage(bytes_saved as f64, total_zstd_size as f64),
+            name
+        )?;
+        writeln!(
+            writer,
+            "  (Theoretical best of a single comparison, using its best group per file. Savings of different comparisons may overlap, and don't add up.)"
+        )
+    }
+
+    /// Prints the files which benefited the most and least from a comparison.
+    fn print_zstd_ratio_extremes<W: Write>(
+        &self,
+        writer: &mut W,
+        indent: &str,
+        extremes: Option<&ZstdRatioExtremes>,
+    ) -> io::Result<()> {
+        let Some(extremes) = extremes else {
+            return Ok(());
+        };
+
+        writeln!(
+            writer,
+            "{}Best File (zstd ratio): {} ({:.3})",
+            indent,
+            self.file_label(extremes.best_file_index),
+            extremes.best_ratio
+        )?;
+        writeln!(
+            writer,
+            "{}Worst File (zstd ratio): {} ({:.3})",
+            indent,
+            self.file_label(extremes.worst_file_index),
+            extremes.worst_ratio
+        )
+    }
+
+    /// Name of the file at the given index, or the index itself if the name is unknown.
+    pub(super) fn file_label(&self, file_index: usize) -> String {
+        self.file_names
+            .get(file_index)
+            .cloned()
+            .unwrap_or_else(|| format!("#{}", file_index))
+    }
+}
+
+/// Prints the confidence interval of the mean zstd ratio and the effect size of a comparison
+/// group against its baseline, if available.
+fn print_zstd_effect_size<W: Write>(
+    writer: &mut W,
+    indent: &str,
+    effect_size: Option<&EffectSize>,
+) -> io::Result<()> {
+    let Some(effect_size) = effect_size else {
+        return writeln!(writer, "{indent}Mean ratio 95% CI: insufficient data");
+    };
+
+    let (low, high) = effect_size.ratio_confidence_interval();
+    writeln!(writer, "{indent}Mean ratio 95% CI: [{low:.3}, {high:.3}]")?;
+    writeln!(
+        writer,
+        "{}Effect Size (zstd ratio): {}",
+        indent,
+        format_effect_size(effect_size)
+    )
+}
+
+/// Prints the correlation between the estimated and zstd sizes of a group across files,
+/// if any file has an estimate.
+fn print_estimate_correlation<W: Write>(
+    writer: &mut W,
+    prefix: &str,
+    correlation: Option<SizeCorrelation>,
+) -> io::Result<()> {
+    let Some(correlation) = correlation else {
+        return Ok(());
+    };
+
+    writeln!(
+        writer,
+        "{}Est/Zstd correlation: {}",
+        prefix,
+        format_size_correlation(&correlation)
+    )
+}
@@ -34,6 +34,10 @@
 //!   - [`MergedAnalysisResults::print()`]: Display merged results
 //!   - [`MergedAnalysisResults::as_field_metrics()`]: Convert file statistics to field metrics
 //!
+//! - [`CrossFileResults`]: Redundancy found across files when concatenated into one stream
+//!   - [`CrossFileResults::from_samples()`]: Measure the concatenation of multiple files
+//!   - [`CrossFileResults::print()`]: Display cross-file results
+//!
 //! - [`FieldMetrics`]: Per-field analysis data
 //!   - [`FieldMetrics::parent_path()`]: Get path of parent field
 //!   - [`FieldMetrics::parent_metrics_or()`]: Get metrics of parent field
@@ -64,6 +68,7 @@
 //!
 //! - [`Detailed`]: Comprehensive analysis with full metrics
 //! - [`Concise`]: Condensed summary of key statistics
+//! - [`Json`]: All results as JSON, for machine consumption, see [`json`]
 //!
 //! Groups of results (multiple files) can also be displayed via one of the
 //! other modules.
@@ -95,6 +100,7 @@
 //! [`PrintFormat`]: crate::results::PrintFormat
 //! [`Detailed`]: crate::results::PrintFormat::Detailed
 //! [`Concise`]: crate::results::PrintFormat::Concise
+//! [`Json`]: crate::results::PrintFormat::Json
 //! [`CSV`]: crate::csv
 //! [`Plot`]: crate::plot
 //! [`SchemaAnalyzer`]: crate::analyzer::SchemaAnalyzer
@@ -103,21 +109,59 @@
 //! [`MergedAnalysisResults::from_results()`]: crate::results::merged_analysis_results::MergedAnalysisResults::from_results
 //! [`MergedAnalysisResults::print()`]: crate::results::merged_analysis_results::MergedAnalysisResults::print
 //! [`MergedAnalysisResults::as_field_metrics()`]: crate::results::merged_analysis_results::MergedAnalysisResults::as_field_metrics
+//! [`CrossFileResults`]: crate::results::cross_file_results::CrossFileResults
+//! [`CrossFileResults::from_samples()`]: crate::results::cross_file_results::CrossFileResults::from_samples
+//! [`CrossFileResults::print()`]: crate::results::cross_file_results::CrossFileResults::print
 
 pub mod analysis_results;
+pub mod color;
+pub mod cross_file_results;
+pub mod estimator_report;
+pub mod field_mismatch;
+pub mod field_tree;
+pub mod json;
+mod merge_field_metrics;
+pub mod merge_strategy;
+mod merge_weights;
 pub mod merged_analysis_results;
+mod print_fields;
+mod print_sizes;
+pub mod reference_comparison;
+pub mod reference_sizes;
+pub mod reserved_fields;
+pub mod run_history;
+pub mod run_stats;
+pub mod saved_results;
+pub mod top_fields;
+
+pub(crate) use print_fields::{
+    print_auto_split_suggestions, print_bit_waste, print_constant_fields, print_entropy_coded_size,
+    print_field_entropy_order1, print_field_estimated_size, print_field_metrics_bit_stats,
+    print_field_metrics_value_stats, print_field_reference_size, print_near_constant_bits,
+    print_recommendations, print_run_stats,
+};
+pub(crate) use print_sizes::{
+    print_brotli_size, print_deflate_size, print_empty_split_groups, print_entropy_order1,
+    print_estimate_breakdown, print_reference_size, print_residual_entropy,
+    print_truncated_data_warning, print_zero_estimate_warning, print_zstd_compress_time,
+    print_zstd_dict_size,
+};
 
 use crate::analyzer::BitStats;
 use crate::comparison::compare_groups::GroupComparisonError;
 use crate::results::analysis_results::AnalysisResults;
-use crate::schema::BitOrder;
+use crate::schema::{sign_extend, BitOrder, ValueInterpretation};
 use crate::utils::constants::CHILD_MARKER;
 use derive_more::FromStr;
 use merged_analysis_results::MergedAnalysisResults;
+use run_stats::RunStats;
 use rustc_hash::FxHashMap;
-use std::io::{self, Write};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// Minimum [`FieldMetrics::value_uniformity`] for a field to be considered near-uniform.
+pub const NEAR_UNIFORM_THRESHOLD: f64 = 0.95;
+
 /// Error type for when merging analysis results fails.
 #[derive(Debug, Error)]
 pub enum AnalysisMergeError {
@@ -129,6 +173,18 @@ This indicates inconsistent input data, or merging of results that were computed
 
     #[error("Field length mismatch: {0} != {1}. This indicates inconsistent, different or incorrect input data.")]
     FieldLengthMismatch(u32, u32),
+
+    #[error("Number of weights ({found}) does not match the number of results being merged ({expected}).")]
+    WeightCountMismatch { expected: usize, found: usize },
+
+    #[error("Merge weights must be finite, non-negative and not all zero.")]
+    InvalidWeights,
+
+    #[error("Fields are present in some files, but not others: {}. Were the files analyzed with different schemas?", .0.join(", "))]
+    MismatchedFields(Vec<String>),
+
+    #[error("Run {run} can't be merged with the first run, as {reason}. Were the runs analyzed with different schemas?")]
+    MismatchedRuns { run: usize, reason: String },
 }
 
 /// Error type for when something goes wrong when computing the final analysis results.
@@ -136,10 +192,13 @@ This indicates inconsistent input data, or merging of results that were computed
 pub enum ComputeAnalysisResultsError {
     #[error(transparent)]
     GroupComparisonError(#[from] GroupComparisonError),
+
+    #[error("{group} of split comparison '{comparison}' resolves to no fields. Does it only list groups without any fields?")]
+    EmptySplitGroup { comparison: String, group: String },
 }
 
 /// Complete analysis metrics for a single field
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct FieldMetrics {
     /// Name of the field or group
     pub name: String,
@@ -153,108 +212,108 @@ pub struct FieldMetrics {
     pub lenbits: u32,
     /// Shannon entropy in bits
     pub entropy: f64,
+    /// Shannon entropy in bits of each window of [`CompressionOptions::entropy_window`]
+    /// entries, in file order. Empty unless requested.
+    /// When merging, the windows of all files are concatenated in order.
+    ///
+    /// [`CompressionOptions::entropy_window`]: crate::analyzer::CompressionOptions::entropy_window
+    #[serde(default)]
+    pub windowed_entropy: Vec<f64>,
     /// LZ compression matches in the field
     pub lz_matches: u64,
     /// Bit-level statistics. Index of tuple is bit offset.
     pub bit_counts: Vec<BitStats>,
     /// The order of the bits within the field
     pub bit_order: BitOrder,
+    /// How values of the field are rendered in value stats
+    pub interpretation: ValueInterpretation,
+    /// Whether the field is a reserved region, which should be constant.
+    /// See [`reserved_fields`] for how this is checked.
+    #[serde(default)]
+    pub reserved: bool,
     /// Value → occurrence count
     /// Count of occurrences for each observed value.
+    #[serde(serialize_with = "json::serialize_sorted_map")]
     pub value_counts: FxHashMap<u64, u64>,
+    /// Whether [`Self::value_counts`] is truncated, because the field had more than
+    /// [`CompressionOptions::max_distinct_values`] distinct values (in any merged file).
+    /// Values seen after reaching the limit are not counted.
+    ///
+    /// [`CompressionOptions::max_distinct_values`]: crate::analyzer::CompressionOptions::max_distinct_values
+    #[serde(default)]
+    pub value_counts_overflowed: bool,
+    /// Whether the field holds the same value in every record (and every merged file),
+    /// making it a candidate for hoisting out of the struct. Determined from
+    /// [`Self::value_counts`], so only fields whose values are counted (up to 16 bits) qualify.
+    #[serde(default)]
+    pub is_constant: bool,
+    /// The value of the field if [`Self::is_constant`].
+    #[serde(default)]
+    pub constant_value: Option<u64>,
+    /// (previous value, current value) → occurrence count.
+    /// Only available if requested via [`CompressionOptions::track_value_transitions`],
+    /// and the field had at most [`CompressionOptions::max_value_transitions`] distinct transitions.
+    ///
+    /// [`CompressionOptions::track_value_transitions`]: crate::analyzer::CompressionOptions::track_value_transitions
+    /// [`CompressionOptions::max_value_transitions`]: crate::analyzer::CompressionOptions::max_value_transitions
+    #[serde(
+        serialize_with = "json::serialize_transitions",
+        deserialize_with = "json::deserialize_transitions"
+    )]
+    pub value_transitions: Option<FxHashMap<(u64, u64), u64>>,
+    /// Size estimated by the size estimator function, using the multipliers of the field
+    /// if it overrides them.
+    #[serde(default)]
+    pub estimated_size: u64,
     /// Actual size of the compressed data when compressed with zstandard
     pub zstd_size: u64,
+    /// Size of the data when coded with an order-0 range coder, using the observed
+    /// byte frequencies. A second, achievable estimate next to [`Self::theoretical_min_size`].
+    /// Only available if requested via [`CompressionOptions::measure_entropy_coded_size`].
+    ///
+    /// [`CompressionOptions::measure_entropy_coded_size`]: crate::analyzer::CompressionOptions::measure_entropy_coded_size
+    #[serde(default)]
+    pub entropy_coded_size: Option<u64>,
+    /// Entropy of each byte given the previous byte (H(X<sub>n</sub> | X<sub>n-1</sub>)),
+    /// in bits per byte; a tighter floor than [`Self::entropy`] for correlated bytes.
+    /// Only available if requested via [`CompressionOptions::measure_order1_entropy`].
+    ///
+    /// [`CompressionOptions::measure_order1_entropy`]: crate::analyzer::CompressionOptions::measure_order1_entropy
+    #[serde(default)]
+    pub entropy_order1: Option<f64>,
+    /// Statistics of the runs of identical consecutive values, for fields of up to 64 bits.
+    /// Only available if requested via [`CompressionOptions::measure_run_stats`].
+    ///
+    /// [`CompressionOptions::measure_run_stats`]: crate::analyzer::CompressionOptions::measure_run_stats
+    #[serde(default)]
+    pub run_stats: Option<RunStats>,
+    /// Size achieved by an external codec, to compare the estimated and zstd sizes against.
+    /// Only available if supplied via [`ReferenceSizes`].
+    ///
+    /// [`ReferenceSizes`]: reference_sizes::ReferenceSizes
+    #[serde(default)]
+    pub reference_size: Option<u64>,
     /// Original size of the data before compression
     pub original_size: u64,
 }
 
 impl FieldMetrics {
-    /// Merge multiple [`FieldMetrics`] objects into one.
-    /// This gives you an 'aggregate' result over a large data set.
-    ///
-    /// # Arguments
-    ///
-    /// * `items` - The items to merge into a new instance.
-    pub fn try_merge_many(items: &[&Self]) -> Result<FieldMetrics, AnalysisMergeError> {
-        if items.is_empty() {
-            return Ok(FieldMetrics::default());
-        }
-
-        let first = items[0];
-
-        // Validate compatible field configurations
-        for other in items {
-            if first.lenbits != other.lenbits {
-                return Err(AnalysisMergeError::FieldLengthMismatch(
-                    first.lenbits,
-                    other.lenbits,
-                ));
-            }
-        }
-
-        // Average over all items
-        let total_items = items.len();
-        let mut total_count = 0;
-        let mut total_entropy = 0.0;
-        let mut total_lz_matches = 0;
-        let mut total_zstd_size = 0;
-        let mut total_original_size = 0;
-
-        for metrics in items {
-            total_count += metrics.count;
-            total_entropy += metrics.entropy;
-            total_lz_matches += metrics.lz_matches;
-            total_zstd_size += metrics.zstd_size;
-            total_original_size += metrics.original_size;
-        }
-
-        let mut this = FieldMetrics {
-            name: first.name.clone(),
-            full_path: first.full_path.clone(),
-            depth: first.depth,
-            lenbits: first.lenbits,
-            bit_order: first.bit_order,
-            ..Default::default()
-        };
-        this.count = total_count;
-        this.entropy = total_entropy / total_items as f64;
-        this.lz_matches = total_lz_matches / total_items as u64;
-        this.zstd_size = total_zstd_size / total_items as u64;
-        this.original_size = total_original_size / total_items as u64;
-        this.merge_bit_stats_and_value_counts(items)?;
-        Ok(this)
-    }
-
-    fn merge_bit_stats_and_value_counts(
-        &mut self,
-        items: &[&Self],
-    ) -> Result<(), AnalysisMergeError> {
-        let mut bit_counts = items[0].bit_counts.clone();
-        let mut value_counts = items[0].value_counts.clone();
-
-        for other in items {
-            // Validate bit counts length
-            if bit_counts.len() != other.bit_counts.len() {
-                return Err(AnalysisMergeError::BitCountsDontMatch);
-            }
-
-            for (bit_offset, bit_stats) in other.bit_counts.iter().enumerate() {
-                let current = bit_counts
-                    .get_mut(bit_offset)
-                    .ok_or(AnalysisMergeError::BitCountsDontMatch)?;
-                current.ones += bit_stats.ones;
-                current.zeros += bit_stats.zeros;
-            }
-
-            // Add value counts from others into self
-            for (value, count) in &other.value_counts {
-                *value_counts.entry(*value).or_insert(0) += count;
-            }
+    /// Returns the value of a field with the given value counts, if it only ever held one value.
+    /// Truncated counts ([`Self::value_counts_overflowed`]) are never constant.
+    pub(crate) fn constant_value_of(
+        value_counts: &FxHashMap<u64, u64>,
+        overflowed: bool,
+    ) -> Option<u64> {
+        match value_counts.len() {
+            1 if !overflowed => value_counts.keys().next().copied(),
+            _ => None,
         }
+    }
 
-        self.bit_counts = bit_counts;
-        self.value_counts = value_counts;
-        Ok(())
+    /// Returns the smallest size the data of the field can be coded to with an order-0
+    /// model, according to its [`Self::entropy`] (in bytes, rounded up).
+    pub fn theoretical_min_size(&self) -> u64 {
+        (self.entropy * self.original_size as f64 / 8.0).ceil() as u64
     }
 
     /// Returns the parent path of the current field.
@@ -290,12 +349,133 @@ impl FieldMetrics {
         parent_stats
     }
 
+    /// How close the distribution of the values of this field is to uniform (incompressible),
+    /// as the entropy of the observed values normalized to `0.0..=1.0`.
+    ///
+    /// The maximum entropy is bounded by both the bit length of the field and the number of
+    /// observed values; since e.g. 1000 distinct 16-bit values can't exceed ~10 bits of entropy.
+    ///
+    /// Returns `None` if value counts were not collected, or all values are identical.
+    pub fn value_uniformity(&self) -> Option<f64> {
+        let total: u64 = self.value_counts.values().sum();
+        let max_entropy = (self.lenbits as f64).min((total as f64).log2());
+        if self.value_counts.len() < 2 || max_entropy <= 0.0 {
+            return None;
+        }
+
+        let entropy: f64 = self
+            .value_counts
+            .values()
+            .map(|&count| {
+                let probability = count as f64 / total as f64;
+                -probability * probability.log2()
+            })
+            .sum();
+        Some((entropy / max_entropy).min(1.0))
+    }
+
+    /// Whether the values of this field are (nearly) uniformly distributed, i.e. the field
+    /// is essentially incompressible and may be better stored in a separate, uncompressed stream.
+    pub fn is_near_uniform(&self) -> bool {
+        self.value_uniformity()
+            .is_some_and(|uniformity| uniformity >= NEAR_UNIFORM_THRESHOLD)
+    }
+
+    /// The minimum number of bits needed to store every observed value of this field,
+    /// based on the largest observed value. For [`ValueInterpretation::Sint`] fields, this
+    /// is the two's complement width (including the sign bit) covering the observed range.
+    ///
+    /// Returns `None` if value counts were not collected or are truncated
+    /// ([`Self::value_counts_overflowed`]), or the field is a
+    /// [`ValueInterpretation::Float16`], whose bits can't be narrowed this way.
+    pub fn min_bits_observed(&self) -> Option<u32> {
+        if self.value_counts_overflowed {
+            return None;
+        }
+
+        let values = self.value_counts.keys().copied();
+        let bits = match self.interpretation {
+            ValueInterpretation::Float16 => return None,
+            ValueInterpretation::Sint => values
+                .map(|value| {
+                    let value = sign_extend(value, self.lenbits);
+                    // Magnitude bits, plus the sign bit.
+                    let magnitude = if value < 0 { !value } else { value };
+                    u64::BITS - magnitude.leading_zeros() + 1
+                })
+                .max()?,
+            ValueInterpretation::Uint | ValueInterpretation::Fixed(_) => values
+                .map(|value| u64::BITS - value.leading_zeros())
+                .max()?,
+        };
+
+        // A field always needs at least one bit, even if it's always 0.
+        Some(bits.max(1))
+    }
+
+    /// Number of bits which could be saved by packing the observed values tightly, i.e. giving
+    /// each unique value its own code: `lenbits - ceil(log2(unique values))`.
+    /// A field with a single unique value wastes all of its bits.
+    ///
+    /// Unlike [`Self::min_bits_observed`], this ignores the magnitude of the values, so it also
+    /// counts the bits saved by remapping sparse values to a dense index.
+    ///
+    /// Returns `None` if no values were counted (e.g. the field is wider than 16 bits, or had no
+    /// observations) or the counts are truncated ([`Self::value_counts_overflowed`]).
+    pub fn bit_waste(&self) -> Option<u32> {
+        if self.value_counts_overflowed || self.value_counts.is_empty() {
+            return None;
+        }
+
+        let needed_bits = (self.value_counts.len() as u64)
+            .next_power_of_two()
+            .trailing_zeros();
+        Some(self.lenbits.saturating_sub(needed_bits))
+    }
+
+    /// Number of bits where one value makes up more than 99% of the observations, i.e. bits which
+    /// are (almost) dead. See [`BitStats::is_near_constant`].
+    ///
+    /// Returns `None` if bit stats were not collected, i.e. the field is wider than 64 bits.
+    pub fn near_constant_bits(&self) -> Option<usize> {
+        if self.bit_counts.len() != self.lenbits as usize {
+            return None;
+        }
+
+        Some(
+            self.bit_counts
+                .iter()
+                .filter(|bit| bit.is_near_constant())
+                .count(),
+        )
+    }
+
+    /// Whether fewer bits than [`Self::lenbits`] would suffice to store all observed values.
+    /// See [`Self::min_bits_observed`].
+    pub fn has_unused_bits(&self) -> bool {
+        self.min_bits_observed()
+            .is_some_and(|min_bits| min_bits < self.lenbits)
+    }
+
+    /// Renders a raw value of this field according to its [`Self::interpretation`].
+    pub fn format_value(&self, value: u64) -> String {
+        self.interpretation.format_value(value, self.lenbits)
+    }
+
     /// Get sorted value counts descending (value, count)
     pub fn sorted_value_counts(&self) -> Vec<(&u64, &u64)> {
         let mut counts: Vec<_> = self.value_counts.iter().collect();
         counts.sort_by(|a, b| b.1.cmp(a.1));
         counts
     }
+
+    /// Get value transitions sorted by previous value, then current value,
+    /// as `((previous, current), count)`. Empty if transitions weren't tracked.
+    pub fn sorted_value_transitions(&self) -> Vec<(&(u64, u64), &u64)> {
+        let mut transitions: Vec<_> = self.value_transitions.iter().flatten().collect();
+        transitions.sort_by_key(|(pair, _)| **pair);
+        transitions
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default, FromStr)]
@@ -303,6 +483,8 @@ pub enum PrintFormat {
     #[default]
     Detailed,
     Concise,
+    /// Machine readable JSON, see [`json`].
+    Json,
 }
 
 // Helper function to calculate percentage
@@ -314,53 +496,539 @@ pub(crate) fn calculate_percentage(child: f64, parent: f64) -> f64 {
     }
 }
 
-pub(crate) fn print_field_metrics_value_stats<W: Write>(
-    writer: &mut W,
-    field: &FieldMetrics,
-) -> io::Result<()> {
-    // Print field name with indent
-    let indent = "  ".repeat(field.depth);
-    writeln!(writer, "{}{} ({} bits)", indent, field.name, field.lenbits)?;
-
-    // Print value statistics
-    let counts = field.sorted_value_counts();
-    if !counts.is_empty() {
-        let total_values: u64 = counts.iter().map(|(_, &c)| c).sum();
-        for (val, &count) in counts.iter().take(5) {
-            let pct = (count as f32 / total_values as f32) * 100.0;
-            writeln!(writer, "{}    {}: {:.1}%", indent, val, pct)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{CompressionOptions, SchemaAnalyzer, SizeEstimationParameters};
+    use crate::comparison::GroupComparisonMetrics;
+    use crate::schema::Schema;
+    use crate::utils::analyze_utils::calculate_file_entropy;
+
+    fn metrics_with_counts(lenbits: u32, counts: &[(u64, u64)]) -> FieldMetrics {
+        FieldMetrics {
+            lenbits,
+            value_counts: counts.iter().copied().collect(),
+            ..Default::default()
         }
     }
 
-    Ok(())
-}
+    #[test]
+    fn uniform_values_are_near_uniform() {
+        let counts: Vec<(u64, u64)> = (0..256).map(|x| (x, 10)).collect();
+        let metrics = metrics_with_counts(8, &counts);
+        assert!((metrics.value_uniformity().unwrap() - 1.0).abs() < 1e-9);
+        assert!(metrics.is_near_uniform());
+    }
+
+    #[test]
+    fn skewed_values_are_not_near_uniform() {
+        let metrics = metrics_with_counts(8, &[(0, 1000), (1, 1), (2, 1)]);
+        assert!(metrics.value_uniformity().unwrap() < 0.1);
+        assert!(!metrics.is_near_uniform());
+    }
+
+    #[test]
+    fn uniformity_is_bounded_by_sample_count() {
+        // 16 distinct 16-bit values can at most have 4 bits of entropy.
+        let counts: Vec<(u64, u64)> = (0..16).map(|x| (x * 1000, 1)).collect();
+        let metrics = metrics_with_counts(16, &counts);
+        assert!(metrics.is_near_uniform());
+    }
 
-pub(crate) fn print_field_metrics_bit_stats<W: Write>(
-    writer: &mut W,
-    field: &FieldMetrics,
-) -> io::Result<()> {
-    let indent = "  ".repeat(field.depth);
-    writeln!(writer, "{}{} ({} bits)", indent, field.name, field.lenbits)?;
-
-    // If we didn't collect the bits, skip printing.
-    if field.bit_counts.len() != field.lenbits as usize {
-        return Ok(());
-    }
-
-    for i in 0..field.lenbits {
-        let bit_stats = &field.bit_counts[i as usize];
-        let total = bit_stats.zeros + bit_stats.ones;
-        let percentage = if total > 0 {
-            (bit_stats.ones as f64 / total as f64) * 100.0
-        } else {
-            0.0
+    #[test]
+    fn uniformity_unavailable_without_value_counts() {
+        assert_eq!(metrics_with_counts(32, &[]).value_uniformity(), None);
+        assert_eq!(metrics_with_counts(8, &[(5, 100)]).value_uniformity(), None);
+    }
+
+    #[test]
+    fn truncated_value_counts_dont_suggest_narrowing() {
+        let metrics = FieldMetrics {
+            value_counts_overflowed: true,
+            ..metrics_with_counts(16, &[(1, 10), (2, 10)])
         };
-        writeln!(
-            writer,
-            "{}  Bit {}: ({}/{}) ({:.1}%)",
-            indent, i, bit_stats.zeros, bit_stats.ones, percentage
-        )?;
+        assert_eq!(metrics.min_bits_observed(), None);
+
+        let mut output = Vec::new();
+        print_field_metrics_value_stats(&mut output, &metrics).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(
+            output.contains("[truncated: too many distinct values, only 2 of them are counted]")
+        );
+    }
+
+    #[test]
+    fn renders_fixed_point_value_stats_as_decimals() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    scale: { type: field, bits: 16, fixed_point: { integer_bits: 8, fraction_bits: 8 } }
+"#,
+        )
+        .unwrap();
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        for value in [0x0180u16, 0x0180, 0x0180, 0x0040] {
+            analyzer.add_entry(&value.to_be_bytes()).unwrap();
+        }
+        let results = analyzer.generate_results().unwrap();
+
+        // The counts stay keyed by the raw values.
+        let scale = &results.per_field["scale"];
+        assert_eq!(scale.value_counts.get(&0x0180), Some(&3));
+
+        let mut output = Vec::new();
+        print_field_metrics_value_stats(&mut output, scale).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "scale (16 bits)\n    1.5: 75.0%\n    0.25: 25.0%\n"
+        );
     }
 
-    Ok(())
+    #[test]
+    fn counts_near_constant_bits() {
+        let bit = |zeros, ones| BitStats { zeros, ones };
+        let metrics = FieldMetrics {
+            lenbits: 3,
+            bit_counts: vec![bit(1000, 0), bit(500, 500), bit(1, 999)],
+            ..Default::default()
+        };
+        assert_eq!(metrics.near_constant_bits(), Some(2));
+
+        // Bit stats aren't collected for fields over 64 bits.
+        let wide = FieldMetrics {
+            lenbits: 65,
+            ..Default::default()
+        };
+        assert_eq!(wide.near_constant_bits(), None);
+    }
+
+    #[test]
+    fn merges_with_weighted_means() {
+        let a = FieldMetrics {
+            lenbits: 8,
+            count: 10,
+            entropy: 2.0,
+            lz_matches: 100,
+            zstd_size: 40,
+            original_size: 80,
+            ..Default::default()
+        };
+        let b = FieldMetrics {
+            entropy: 6.0,
+            lz_matches: 300,
+            zstd_size: 80,
+            original_size: 160,
+            ..a.clone()
+        };
+
+        let merged = FieldMetrics::try_merge_many_weighted(&[&a, &b], &[3.0, 1.0]).unwrap();
+        assert_eq!(merged.entropy, 3.0);
+        assert_eq!(merged.lz_matches, 150);
+        assert_eq!(merged.zstd_size, 50);
+        assert_eq!(merged.original_size, 100);
+        // Counts are summed, not weighted.
+        assert_eq!(merged.count, 20);
+
+        let unweighted = FieldMetrics::try_merge_many(&[&a, &b]).unwrap();
+        assert_eq!(unweighted.entropy, 4.0);
+        assert_eq!(unweighted.lz_matches, 200);
+    }
+
+    #[test]
+    fn entropy_coded_size_is_measured_on_request() {
+        let schema = Schema::from_yaml(
+            "version: '1.0'\nroot: { type: group, fields: { low: 4, high: 4 } }\n",
+        )
+        .unwrap();
+        let analyze = |options: CompressionOptions| {
+            let mut analyzer = SchemaAnalyzer::new(&schema, options);
+            for x in 0..64u8 {
+                analyzer.add_entry(&[x.wrapping_mul(37)]).unwrap();
+            }
+            analyzer.generate_results().unwrap()
+        };
+
+        let skipped = analyze(CompressionOptions::default());
+        assert_eq!(skipped.per_field["low"].entropy_coded_size, None);
+
+        let measured = analyze(CompressionOptions::default().with_entropy_coded_size(true));
+        let low = &measured.per_field["low"];
+        let coded_size = low.entropy_coded_size.unwrap();
+        assert!(coded_size >= low.theoretical_min_size());
+        assert!(coded_size <= low.original_size + 8);
+
+        // Merged results only have it when every file measured it.
+        let merged = FieldMetrics::try_merge_many(&[low, low]).unwrap();
+        assert_eq!(merged.entropy_coded_size, Some(coded_size));
+        let merged = FieldMetrics::try_merge_many(&[low, &skipped.per_field["low"]]).unwrap();
+        assert_eq!(merged.entropy_coded_size, None);
+    }
+
+    #[test]
+    fn order1_entropy_is_calculated_on_request() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+root: { type: group, fields: { a: 8, b: 8 } }
+analysis:
+  split_groups:
+    - { name: ab, group_1: [a, b], group_2: [b, a] }
+"#,
+        )
+        .unwrap();
+        let analyze = |options: CompressionOptions| {
+            let mut analyzer = SchemaAnalyzer::new(&schema, options);
+            for x in 0..=255u8 {
+                analyzer.add_entry(&[x, x.wrapping_add(1)]).unwrap();
+            }
+            analyzer.generate_results().unwrap()
+        };
+
+        let skipped = analyze(CompressionOptions::default());
+        assert_eq!(skipped.per_field["a"].entropy_order1, None);
+        assert_eq!(
+            skipped.split_comparisons[0].group1_metrics.entropy_order1,
+            None
+        );
+
+        let measured = analyze(CompressionOptions::default().with_order1_entropy(true));
+        // Each value of 'a' follows from the previous one, so there's nothing left to code.
+        let a = &measured.per_field["a"];
+        assert_eq!(a.entropy_order1, Some(0.0));
+        assert!(a.entropy > 7.9);
+        let group = &measured.split_comparisons[0].group1_metrics;
+        assert!(group.entropy_order1.unwrap() < group.entropy);
+
+        let mut output = Vec::new();
+        print_field_entropy_order1(&mut output, 2, a).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "  Entropy Order-1/Order-0: 0.00/8.00\n"
+        );
+    }
+
+    #[test]
+    fn constant_fields_are_detected_across_files() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+root: { type: group, fields: { a: 4, c: 4 } }
+"#,
+        )
+        .unwrap();
+        let analyze = |c: u8| {
+            let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+            for a in 0..8u8 {
+                analyzer.add_entry(&[(a << 4) | c]).unwrap();
+            }
+            analyzer.generate_results().unwrap()
+        };
+
+        let results = analyze(5);
+        assert!(!results.per_field["a"].is_constant);
+        assert_eq!(results.per_field["a"].constant_value, None);
+        assert!(results.per_field["c"].is_constant);
+        assert_eq!(results.per_field["c"].constant_value, Some(5));
+
+        let mut output = Vec::new();
+        results
+            .print(&mut output, &schema, PrintFormat::Detailed, true)
+            .unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("Constant fields: c = 5\n"));
+
+        // Constant in each file, but with a different value.
+        let same = MergedAnalysisResults::from_results(&[analyze(5), analyze(5)]).unwrap();
+        assert_eq!(same.per_field["c"].constant_value, Some(5));
+        let different = MergedAnalysisResults::from_results(&[analyze(5), analyze(6)]).unwrap();
+        assert!(!different.per_field["c"].is_constant);
+        assert_eq!(different.per_field["c"].constant_value, None);
+    }
+
+    #[test]
+    fn run_stats_are_calculated_on_request() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+root: { type: group, fields: { a: 3, b: 5 } }
+"#,
+        )
+        .unwrap();
+        let analyze = |options: CompressionOptions| {
+            let mut analyzer = SchemaAnalyzer::new(&schema, options);
+            // 'a' is 1, 1, 1, 2, 2, 5; 'b' counts up, so never repeats.
+            for (b, a) in [1u8, 1, 1, 2, 2, 5].into_iter().enumerate() {
+                analyzer.add_entry(&[(a << 5) | b as u8]).unwrap();
+            }
+            analyzer.generate_results().unwrap()
+        };
+
+        assert_eq!(
+            analyze(CompressionOptions::default()).per_field["a"].run_stats,
+            None
+        );
+
+        let measured = analyze(CompressionOptions::default().with_run_stats(true));
+        let a = &measured.per_field["a"];
+        assert_eq!(
+            a.run_stats,
+            Some(RunStats {
+                runs: 3,
+                max_run_length: 3,
+                mean_run_length: 2.0
+            })
+        );
+        assert_eq!(measured.per_field["b"].run_stats.unwrap().runs, 6);
+
+        let mut output = Vec::new();
+        print_run_stats(&mut output, 2, a).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "  Runs: 3 (Max Length: 3, Mean Length: 2.00)\n"
+        );
+    }
+
+    #[test]
+    fn zstd_time_is_measured_on_request() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+root: { type: group, fields: { a: 8, b: 8 } }
+analysis:
+  split_groups:
+    - { name: ab, group_1: [a, b], group_2: [b, a] }
+"#,
+        )
+        .unwrap();
+        let analyze = |options: CompressionOptions| {
+            let mut analyzer = SchemaAnalyzer::new(&schema, options);
+            for x in 0..=255u8 {
+                analyzer.add_entry(&[x, x.wrapping_mul(7)]).unwrap();
+            }
+            analyzer.generate_results().unwrap()
+        };
+
+        let skipped = analyze(CompressionOptions::default());
+        let group = &skipped.split_comparisons[0].group1_metrics;
+        assert_eq!(group.zstd_compress_ns, 0);
+        let mut output = Vec::new();
+        print_zstd_compress_time(&mut output, "", group).unwrap();
+        assert!(output.is_empty());
+
+        let measured = analyze(CompressionOptions::default().with_zstd_timing(true));
+        let group = &measured.split_comparisons[0].group1_metrics;
+        assert!(group.zstd_compress_ns > 0);
+        // Timing doesn't change the measured size.
+        assert_eq!(
+            group.zstd_size,
+            skipped.split_comparisons[0].group1_metrics.zstd_size
+        );
+
+        let mut output = Vec::new();
+        print_zstd_compress_time(&mut output, "", group).unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .starts_with("ZStandard Time: "));
+    }
+
+    #[test]
+    fn fields_are_measured_independently_in_parallel() {
+        let schema = Schema::from_yaml(
+            "version: '1.0'\nroot: { type: group, fields: { a: 8, b: 8, c: 8, d: 8, e: 8, f: 8, g: 8, h: 8 } }\n",
+        )
+        .unwrap();
+        let entries: Vec<[u8; 8]> = (0..1024u32)
+            .map(|x| core::array::from_fn(|field| (x * (field as u32 + 1) / 3) as u8))
+            .collect();
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        for entry in &entries {
+            analyzer.add_entry(entry).unwrap();
+        }
+        let results = analyzer.generate_results().unwrap();
+
+        // Each field's metrics are those of its own bytes, regardless of which thread measured it.
+        for (field, name) in ["a", "b", "c", "d", "e", "f", "g", "h"].iter().enumerate() {
+            let bytes: Vec<u8> = entries.iter().map(|entry| entry[field]).collect();
+            let metrics = &results.per_field[*name];
+            assert_eq!(
+                metrics.zstd_size,
+                CompressionOptions::default().zstd_compressed_size(&bytes)
+            );
+            assert_eq!(metrics.entropy, calculate_file_entropy(&bytes));
+            assert_eq!(metrics.original_size, bytes.len() as u64);
+        }
+    }
+
+    #[test]
+    fn fields_can_override_estimator_multipliers() {
+        let schema = Schema::from_yaml(
+            r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    plain: 8
+    tuned: { type: field, bits: 8, lz_match_multiplier: 0.0, entropy_multiplier: 2.0 }
+"#,
+        )
+        .unwrap();
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        for x in 0..64u8 {
+            analyzer
+                .add_entry(&[x.wrapping_mul(37), x.wrapping_mul(37)])
+                .unwrap();
+        }
+        let results = analyzer.generate_results().unwrap();
+
+        // Both fields hold the same bytes, so only the multipliers differ.
+        let plain = &results.per_field["plain"];
+        let tuned = &results.per_field["tuned"];
+        let estimate = |lz_match_multiplier: f64, entropy_multiplier: f64| {
+            CompressionOptions::default().estimate_size(SizeEstimationParameters {
+                name: "",
+                data_len: plain.original_size as usize,
+                data: None,
+                num_lz_matches: plain.lz_matches as usize,
+                entropy: plain.entropy,
+                lz_match_multiplier,
+                entropy_multiplier,
+            }) as u64
+        };
+        let defaults = CompressionOptions::default();
+        assert_eq!(
+            plain.estimated_size,
+            estimate(defaults.lz_match_multiplier, defaults.entropy_multiplier)
+        );
+        assert_eq!(tuned.estimated_size, estimate(0.0, 2.0));
+        assert_ne!(plain.estimated_size, tuned.estimated_size);
+    }
+
+    #[test]
+    fn windowed_entropy_is_recorded_on_request() {
+        let schema =
+            Schema::from_yaml("version: '1.0'\nroot: { type: group, fields: { value: 8 } }\n")
+                .unwrap();
+        let analyze = |options: CompressionOptions| {
+            let mut analyzer = SchemaAnalyzer::new(&schema, options);
+            // A constant first half, then 32 distinct values.
+            for x in 0..64u8 {
+                analyzer.add_entry(&[if x < 32 { 0 } else { x }]).unwrap();
+            }
+            analyzer.generate_results().unwrap()
+        };
+
+        let skipped = analyze(CompressionOptions::default());
+        assert!(skipped.per_field["value"].windowed_entropy.is_empty());
+
+        let measured = analyze(CompressionOptions::default().with_entropy_window(Some(16)));
+        let value = &measured.per_field["value"];
+        assert_eq!(value.windowed_entropy, [0.0, 0.0, 4.0, 4.0]);
+
+        // Merged windows follow the order of the files.
+        let merged = FieldMetrics::try_merge_many(&[value, value]).unwrap();
+        assert_eq!(merged.windowed_entropy.len(), 8);
+        assert_eq!(merged.windowed_entropy[4..], value.windowed_entropy);
+    }
+
+    #[test]
+    fn zero_estimate_is_distinguished_from_no_estimate() {
+        let options = CompressionOptions::default().with_size_estimator_fn(|_| 0);
+        let metrics = GroupComparisonMetrics::from_bytes(&[1, 2, 3, 4], "test", &options);
+        assert!(metrics.has_estimate);
+        assert_eq!(metrics.estimated_size, 0);
+        assert!(metrics.has_zero_estimate());
+
+        let mut output = Vec::new();
+        print_zero_estimate_warning(&mut output, "    Base ", &metrics).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output,
+            "    [WARNING!!] Base estimated size is 0 for a group of 4 bytes!! Check the estimator parameters.\n"
+        );
+
+        // No estimate, or an empty group, is not suspicious.
+        let no_estimate = GroupComparisonMetrics {
+            original_size: 4,
+            ..Default::default()
+        };
+        assert!(!no_estimate.has_zero_estimate());
+        let empty = GroupComparisonMetrics::from_bytes(&[], "test", &options);
+        assert!(!empty.has_zero_estimate());
+
+        let mut output = Vec::new();
+        print_zero_estimate_warning(&mut output, "    Base ", &no_estimate).unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn min_bits_observed_uses_largest_value() {
+        let metrics = metrics_with_counts(8, &[(0, 10), (17, 5), (31, 1)]);
+        assert_eq!(metrics.min_bits_observed(), Some(5));
+        assert!(metrics.has_unused_bits());
+
+        let metrics = metrics_with_counts(8, &[(0, 10), (255, 1)]);
+        assert_eq!(metrics.min_bits_observed(), Some(8));
+        assert!(!metrics.has_unused_bits());
+
+        // Constant zero still needs a bit.
+        assert_eq!(
+            metrics_with_counts(8, &[(0, 10)]).min_bits_observed(),
+            Some(1)
+        );
+        assert_eq!(metrics_with_counts(32, &[]).min_bits_observed(), None);
+    }
+
+    #[test]
+    fn bit_waste_counts_bits_beyond_unique_values() {
+        // 3 unique values need 2 bits, regardless of their magnitude.
+        let metrics = metrics_with_counts(8, &[(0, 5), (100, 1), (255, 1)]);
+        assert_eq!(metrics.bit_waste(), Some(6));
+        // A single unique value needs no bits at all.
+        assert_eq!(metrics_with_counts(8, &[(7, 10)]).bit_waste(), Some(8));
+        // Every value is used.
+        let counts: Vec<(u64, u64)> = (0..16).map(|x| (x, 1)).collect();
+        assert_eq!(metrics_with_counts(4, &counts).bit_waste(), Some(0));
+
+        // Nothing observed, or counts which don't cover every value.
+        assert_eq!(metrics_with_counts(8, &[]).bit_waste(), None);
+        let truncated = FieldMetrics {
+            value_counts_overflowed: true,
+            ..metrics_with_counts(16, &[(1, 10), (2, 10)])
+        };
+        assert_eq!(truncated.bit_waste(), None);
+
+        let mut output = Vec::new();
+        print_bit_waste(&mut output, 2, &metrics).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "  6/8 wasted bits (3 unique values fit in 2 bits)\n"
+        );
+    }
+
+    #[test]
+    fn min_bits_observed_handles_signed_values() {
+        let signed = |counts: &[(u64, u64)]| FieldMetrics {
+            interpretation: ValueInterpretation::Sint,
+            ..metrics_with_counts(8, counts)
+        };
+
+        // -16..=15 fits in 5 bits of two's complement; 0xF0 is -16, 0xFF is -1.
+        assert_eq!(
+            signed(&[(0xF0, 1), (0xFF, 1), (15, 1)]).min_bits_observed(),
+            Some(5)
+        );
+        // 16 needs a 6th bit for the sign.
+        assert_eq!(signed(&[(16, 1)]).min_bits_observed(), Some(6));
+        // -128 needs all 8 bits.
+        assert!(!signed(&[(0x80, 1)]).has_unused_bits());
+        // As unsigned, -1 (0xFF) needs all 8 bits.
+        assert_eq!(
+            metrics_with_counts(8, &[(0xFF, 1)]).min_bits_observed(),
+            Some(8)
+        );
+    }
 }
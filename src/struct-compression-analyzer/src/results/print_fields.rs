@@ -0,0 +1,372 @@
+//! Printers for the per-field sections of the results.
+
+use super::{calculate_percentage, FieldMetrics};
+use crate::comparison::auto_split::{
+    auto_split_comparison_name, rank_layouts, split_layouts, suggested_schema_snippet,
+};
+use crate::comparison::GroupComparisonMetrics;
+use crate::plot::calc_ratio_f64;
+use crate::schema::Schema;
+use ahash::{AHashMap, AHashSet};
+use std::io::{self, Write};
+
+pub(crate) fn print_field_metrics_value_stats<W: Write>(
+    writer: &mut W,
+    field: &FieldMetrics,
+) -> io::Result<()> {
+    // Print field name with indent
+    let indent = "  ".repeat(field.depth);
+    writeln!(writer, "{}{} ({} bits)", indent, field.name, field.lenbits)?;
+
+    // Print value statistics
+    let counts = field.sorted_value_counts();
+    if field.value_counts_overflowed {
+        writeln!(
+            writer,
+            "{}    [truncated: too many distinct values, only {} of them are counted]",
+            indent,
+            counts.len()
+        )?;
+    }
+    if !counts.is_empty() {
+        let total_values: u64 = counts.iter().map(|(_, &c)| c).sum();
+        for (val, &count) in counts.iter().take(5) {
+            let pct = (count as f32 / total_values as f32) * 100.0;
+            writeln!(
+                writer,
+                "{}    {}: {:.1}%",
+                indent,
+                field.format_value(**val),
+                pct
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the fields (not groups) which hold the same value in every record, in schema order.
+/// Reserved fields are reported separately, see `print_reserved_fields`.
+pub(crate) fn print_constant_fields<W: Write>(
+    writer: &mut W,
+    schema: &Schema,
+    per_field: &AHashMap<String, FieldMetrics>,
+) -> io::Result<()> {
+    let groups: AHashSet<&str> = per_field
+        .values()
+        .filter_map(|field| field.parent_path())
+        .collect();
+    let constant: Vec<String> = schema
+        .ordered_field_and_group_paths()
+        .iter()
+        .filter_map(|path| per_field.get(path))
+        .filter(|field| !field.reserved && !groups.contains(field.full_path.as_str()))
+        .filter_map(|field| {
+            let value = field.constant_value?;
+            Some(format!(
+                "{} = {}",
+                field.full_path,
+                field.format_value(value)
+            ))
+        })
+        .collect();
+
+    match constant.is_empty() {
+        true => writeln!(writer, "\nConstant fields: None"),
+        false => writeln!(writer, "\nConstant fields: {}", constant.join(", ")),
+    }
+}
+
+/// Prints recommendations derived from the per-field metrics, in schema order.
+pub(crate) fn print_recommendations<W: Write>(
+    writer: &mut W,
+    schema: &Schema,
+    per_field: &AHashMap<String, FieldMetrics>,
+) -> io::Result<()> {
+    writeln!(writer, "\nRecommendations:")?;
+    let mut any = false;
+    for field_path in schema.ordered_field_and_group_paths() {
+        // Reserved fields are reported separately, see `print_reserved_fields`.
+        let Some(field) = per_field.get(&field_path).filter(|field| !field.reserved) else {
+            continue;
+        };
+
+        if field.is_near_uniform() {
+            any = true;
+            writeln!(
+                writer,
+                "  {}: near-uniform value distribution ({:.1}% of max entropy). Likely incompressible; consider storing it in a separate, uncompressed stream.",
+                field.full_path,
+                field.value_uniformity().unwrap_or_default() * 100.0
+            )?;
+        }
+
+        if field.has_unused_bits() {
+            any = true;
+            writeln!(
+                writer,
+                "  {}: observed values only need {} of {} bits. Consider narrowing the field.",
+                field.full_path,
+                field.min_bits_observed().unwrap_or_default(),
+                field.lenbits
+            )?;
+        }
+    }
+
+    if !any {
+        writeln!(writer, "  None")?;
+    }
+
+    Ok(())
+}
+
+/// Number of the best layouts printed for each [`AutoSplit`](crate::schema::AutoSplit).
+const AUTO_SPLIT_PRINTED_LAYOUTS: usize = 3;
+
+/// Prints the best layouts found by each [`AutoSplit`](crate::schema::AutoSplit) in the
+/// schema, along with a schema snippet for the best layout.
+///
+/// # Arguments
+/// * `per_field` - The per-field metrics, used to obtain the size of the split fields.
+/// * `find_comparison` - Returns the baseline metrics, group names and group metrics of the
+///   comparison with the given name.
+pub(crate) fn print_auto_split_suggestions<'a, W: Write>(
+    writer: &mut W,
+    schema: &Schema,
+    per_field: &AHashMap<String, FieldMetrics>,
+    find_comparison: impl Fn(
+        &str,
+    ) -> Option<(
+        &'a GroupComparisonMetrics,
+        &'a [String],
+        &'a [GroupComparisonMetrics],
+    )>,
+) -> io::Result<()> {
+    if schema.analysis.auto_split.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(
+        writer,
+        "\nAuto Split Suggestions: [as `layout: zstd (% of as-is)`]"
+    )?;
+    for auto_split in &schema.analysis.auto_split {
+        let Some((baseline, group_names, group_metrics)) =
+            find_comparison(&auto_split_comparison_name(&auto_split.field))
+        else {
+            continue;
+        };
+
+        let ranked = rank_layouts(group_metrics);
+        writeln!(
+            writer,
+            "  {}: {} layouts, {} zstd as-is",
+            auto_split.field,
+            ranked.len(),
+            baseline.zstd_size
+        )?;
+        for &x in ranked.iter().take(AUTO_SPLIT_PRINTED_LAYOUTS) {
+            writeln!(
+                writer,
+                "    {}: {} ({:.2}%)",
+                group_names[x],
+                group_metrics[x].zstd_size,
+                calculate_percentage(group_metrics[x].zstd_size as f64, baseline.zstd_size as f64)
+            )?;
+        }
+
+        let best = ranked
+            .first()
+            .filter(|&&x| group_metrics[x].zstd_size < baseline.zstd_size);
+        let widths = per_field
+            .values()
+            .find(|field| field.name == auto_split.field)
+            .and_then(|field| {
+                split_layouts(field.lenbits, auto_split.granularity, auto_split.max_splits).ok()
+            });
+        match (best, widths) {
+            (Some(&x), Some(layouts)) => {
+                writeln!(writer, "    Suggested schema snippet:")?;
+                for line in suggested_schema_snippet(&auto_split.field, &layouts[x]).lines() {
+                    writeln!(writer, "      {}", line)?;
+                }
+            }
+            _ => writeln!(
+                writer,
+                "    No layout compresses better than the field as-is."
+            )?,
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn print_field_metrics_bit_stats<W: Write>(
+    writer: &mut W,
+    field: &FieldMetrics,
+) -> io::Result<()> {
+    let indent = "  ".repeat(field.depth);
+    writeln!(writer, "{}{} ({} bits)", indent, field.name, field.lenbits)?;
+
+    // If we didn't collect the bits, skip printing.
+    if field.bit_counts.len() != field.lenbits as usize {
+        return Ok(());
+    }
+
+    for i in 0..field.lenbits {
+        let bit_stats = &field.bit_counts[i as usize];
+        let total = bit_stats.zeros + bit_stats.ones;
+        let percentage = if total > 0 {
+            (bit_stats.ones as f64 / total as f64) * 100.0
+        } else {
+            0.0
+        };
+        writeln!(
+            writer,
+            "{}  Bit {}: ({}/{}) ({:.1}%) {:.3}",
+            indent,
+            i,
+            bit_stats.zeros,
+            bit_stats.ones,
+            percentage,
+            bit_stats.entropy()
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Prints the estimated size of a field next to its zstd size.
+/// See [`FieldMetrics::estimated_size`].
+pub(crate) fn print_field_estimated_size<W: Write>(
+    writer: &mut W,
+    padding: usize,
+    field: &FieldMetrics,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{:padding$}Estimated/ZStandard: {}/{} ({:.2}%)",
+        "",
+        field.estimated_size,
+        field.zstd_size,
+        calculate_percentage(field.estimated_size as f64, field.zstd_size as f64)
+    )
+}
+
+/// Prints the number of near-constant bits of a field, if its bit stats were collected.
+/// See [`FieldMetrics::near_constant_bits`].
+pub(crate) fn print_near_constant_bits<W: Write>(
+    writer: &mut W,
+    padding: usize,
+    field: &FieldMetrics,
+) -> io::Result<()> {
+    let Some(near_constant) = field.near_constant_bits() else {
+        return Ok(());
+    };
+
+    writeln!(
+        writer,
+        "{:padding$}{}/{} near-constant bits (>99% one value)",
+        "", near_constant, field.lenbits
+    )
+}
+
+/// Prints how many bits of a field tightly packing its unique values would save,
+/// if its values were counted. See [`FieldMetrics::bit_waste`].
+pub(crate) fn print_bit_waste<W: Write>(
+    writer: &mut W,
+    padding: usize,
+    field: &FieldMetrics,
+) -> io::Result<()> {
+    let Some(bit_waste) = field.bit_waste() else {
+        return Ok(());
+    };
+
+    writeln!(
+        writer,
+        "{:padding$}{}/{} wasted bits ({} unique values fit in {} bits)",
+        "",
+        bit_waste,
+        field.lenbits,
+        field.value_counts.len(),
+        field.lenbits - bit_waste
+    )
+}
+
+/// Prints the order-0 range coded size of a field next to its theoretical minimum and
+/// zstd size, if it was measured. See [`FieldMetrics::entropy_coded_size`].
+pub(crate) fn print_entropy_coded_size<W: Write>(
+    writer: &mut W,
+    padding: usize,
+    field: &FieldMetrics,
+) -> io::Result<()> {
+    let Some(coded_size) = field.entropy_coded_size else {
+        return Ok(());
+    };
+
+    writeln!(
+        writer,
+        "{:padding$}Order-0 Coded/Theoretical Min/ZStandard: {}/{}/{}",
+        "",
+        coded_size,
+        field.theoretical_min_size(),
+        field.zstd_size
+    )
+}
+
+/// Prints the order-1 entropy of a field next to its order-0 entropy, if it was calculated.
+/// See [`FieldMetrics::entropy_order1`].
+pub(crate) fn print_field_entropy_order1<W: Write>(
+    writer: &mut W,
+    padding: usize,
+    field: &FieldMetrics,
+) -> io::Result<()> {
+    let Some(entropy_order1) = field.entropy_order1 else {
+        return Ok(());
+    };
+
+    writeln!(
+        writer,
+        "{:padding$}Entropy Order-1/Order-0: {:.2}/{:.2}",
+        "", entropy_order1, field.entropy
+    )
+}
+
+/// Prints the run-length statistics of a field, if they were calculated.
+/// See [`FieldMetrics::run_stats`].
+pub(crate) fn print_run_stats<W: Write>(
+    writer: &mut W,
+    padding: usize,
+    field: &FieldMetrics,
+) -> io::Result<()> {
+    let Some(run_stats) = &field.run_stats else {
+        return Ok(());
+    };
+
+    writeln!(
+        writer,
+        "{:padding$}Runs: {} (Max Length: {}, Mean Length: {:.2})",
+        "", run_stats.runs, run_stats.max_run_length, run_stats.mean_run_length
+    )
+}
+
+/// Prints the reference size of a field, and the ratios of its zstd and estimated sizes to it,
+/// if it was supplied. See [`FieldMetrics::reference_size`].
+pub(crate) fn print_field_reference_size<W: Write>(
+    writer: &mut W,
+    padding: usize,
+    field: &FieldMetrics,
+) -> io::Result<()> {
+    let Some(reference_size) = field.reference_size else {
+        return Ok(());
+    };
+
+    writeln!(
+        writer,
+        "{:padding$}Reference: {} (ZStandard/Reference: {:.3}, Estimated/Reference: {:.3})",
+        "",
+        reference_size,
+        calc_ratio_f64(field.zstd_size, reference_size),
+        calc_ratio_f64(field.estimated_size, reference_size)
+    )
+}
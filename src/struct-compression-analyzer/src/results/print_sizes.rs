@@ -0,0 +1,220 @@
+//! Printers for the size and compression sections of the file and group results.
+
+use crate::comparison::split_comparison::ResidualEntropy;
+use crate::comparison::GroupComparisonMetrics;
+use crate::plot::calc_ratio_f64;
+use std::io::{self, Write};
+
+/// Prints the intermediate terms of the size estimate of a group, if they were recorded.
+///
+/// # Arguments
+/// * `prefix` - Indentation and label to print before the terms, e.g. `"    Base "`.
+pub(crate) fn print_estimate_breakdown<W: Write>(
+    writer: &mut W,
+    prefix: &str,
+    metrics: &GroupComparisonMetrics,
+) -> io::Result<()> {
+    if let Some(breakdown) = &metrics.estimate_breakdown {
+        writeln!(
+            writer,
+            "{}Estimate Terms: {} LZ savings, {} after LZ, {} after entropy",
+            prefix, breakdown.lz_savings, breakdown.bytes_after_lz, breakdown.entropy_bytes
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Prints the Brotli compressed size of a group, if it was measured.
+/// See [`GroupComparisonMetrics::brotli_size`].
+///
+/// # Arguments
+/// * `prefix` - Indentation and label of the group, e.g. `"    Base "`.
+pub(crate) fn print_brotli_size<W: Write>(
+    writer: &mut W,
+    prefix: &str,
+    metrics: &GroupComparisonMetrics,
+) -> io::Result<()> {
+    if let Some(brotli_size) = metrics.brotli_size {
+        writeln!(writer, "{}Brotli: {}", prefix, brotli_size)?;
+    }
+
+    Ok(())
+}
+
+/// Prints the Deflate compressed size of a group, if it was measured.
+/// See [`GroupComparisonMetrics::deflate_size`].
+///
+/// # Arguments
+/// * `prefix` - Indentation and label of the group, e.g. `"    Base "`.
+pub(crate) fn print_deflate_size<W: Write>(
+    writer: &mut W,
+    prefix: &str,
+    metrics: &GroupComparisonMetrics,
+) -> io::Result<()> {
+    if let Some(deflate_size) = metrics.deflate_size {
+        writeln!(writer, "{}Deflate: {}", prefix, deflate_size)?;
+    }
+
+    Ok(())
+}
+
+/// Prints the zstd compressed size of a group with the shared dictionary, if it was measured.
+/// See [`GroupComparisonMetrics::zstd_dict_size`].
+///
+/// # Arguments
+/// * `prefix` - Indentation and label of the group, e.g. `"    Base "`.
+pub(crate) fn print_zstd_dict_size<W: Write>(
+    writer: &mut W,
+    prefix: &str,
+    metrics: &GroupComparisonMetrics,
+) -> io::Result<()> {
+    if let Some(zstd_dict_size) = metrics.zstd_dict_size {
+        writeln!(
+            writer,
+            "{}ZStandard (Dictionary): {}",
+            prefix, zstd_dict_size
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Prints a warning if the estimator ran, but estimated a size of 0 for a group which is not empty.
+/// See [`GroupComparisonMetrics::has_zero_estimate`].
+///
+/// # Arguments
+/// * `prefix` - Indentation and label of the group, e.g. `"    Base "`.
+pub(crate) fn print_zero_estimate_warning<W: Write>(
+    writer: &mut W,
+    prefix: &str,
+    metrics: &GroupComparisonMetrics,
+) -> io::Result<()> {
+    if !metrics.has_zero_estimate() {
+        return Ok(());
+    }
+
+    let label = prefix.trim_start();
+    let indent = &prefix[..prefix.len() - label.len()];
+    writeln!(
+        writer,
+        "{}[WARNING!!] {}estimated size is 0 for a group of {} bytes!! Check the estimator parameters.",
+        indent, label, metrics.original_size
+    )
+}
+
+/// Prints the reference size of a group, and the ratios of its zstd and estimated sizes to it,
+/// if it was supplied. See [`GroupComparisonMetrics::reference_size`].
+///
+/// # Arguments
+/// * `prefix` - Indentation and label of the group, e.g. `"    Base "`.
+pub(crate) fn print_reference_size<W: Write>(
+    writer: &mut W,
+    prefix: &str,
+    metrics: &GroupComparisonMetrics,
+) -> io::Result<()> {
+    let Some(reference_size) = metrics.reference_size else {
+        return Ok(());
+    };
+
+    write!(
+        writer,
+        "{}Reference: {} (ZStandard/Reference: {:.3}",
+        prefix,
+        reference_size,
+        calc_ratio_f64(metrics.zstd_size, reference_size)
+    )?;
+    if metrics.has_estimate {
+        write!(
+            writer,
+            ", Estimated/Reference: {:.3}",
+            calc_ratio_f64(metrics.estimated_size, reference_size)
+        )?;
+    }
+    writeln!(writer, ")")
+}
+
+/// Prints the order-1 entropy of a group, if it was calculated.
+/// See [`GroupComparisonMetrics::entropy_order1`].
+///
+/// # Arguments
+/// * `prefix` - Indentation and label of the group, e.g. `"    Base "`.
+pub(crate) fn print_entropy_order1<W: Write>(
+    writer: &mut W,
+    prefix: &str,
+    metrics: &GroupComparisonMetrics,
+) -> io::Result<()> {
+    if let Some(entropy_order1) = metrics.entropy_order1 {
+        writeln!(
+            writer,
+            "{}Entropy Order-1/Order-0: {:.2}/{:.2}",
+            prefix, entropy_order1, metrics.entropy
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Prints the time taken to compress a group with zstd, and the resulting throughput,
+/// if it was measured ([`CompressionOptions::measure_zstd_time`]).
+///
+/// [`CompressionOptions::measure_zstd_time`]: crate::analyzer::CompressionOptions::measure_zstd_time
+pub(crate) fn print_zstd_compress_time<W: Write>(
+    writer: &mut W,
+    prefix: &str,
+    metrics: &GroupComparisonMetrics,
+) -> io::Result<()> {
+    if metrics.zstd_compress_ns != 0 {
+        // bytes per nanosecond * 1000 = MB/s
+        let throughput = metrics.original_size as f64 * 1000.0 / metrics.zstd_compress_ns as f64;
+        writeln!(
+            writer,
+            "{}ZStandard Time: {:.3}ms ({:.1} MB/s)",
+            prefix,
+            metrics.zstd_compress_ns as f64 / 1_000_000.0,
+            throughput
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Prints a warning for each group of a split comparison which resolves to no fields.
+pub(crate) fn print_empty_split_groups<W: Write>(
+    writer: &mut W,
+    empty_groups: &[String],
+) -> io::Result<()> {
+    for group in empty_groups {
+        writeln!(writer, "    [WARNING!!] {} resolves to no fields!! The results of this comparison are meaningless.", group)?;
+    }
+    Ok(())
+}
+
+/// Prints a warning if normalizing the length of a split comparison discarded data of group 2,
+/// see [`SplitComparisonResult::truncated_data_bytes`].
+///
+/// [`SplitComparisonResult::truncated_data_bytes`]: crate::comparison::split_comparison::SplitComparisonResult::truncated_data_bytes
+pub(crate) fn print_truncated_data_warning<W: Write>(
+    writer: &mut W,
+    truncated_data_bytes: u64,
+) -> io::Result<()> {
+    if truncated_data_bytes != 0 {
+        writeln!(writer, "    [WARNING!!] normalize_length discarded {} non-zero bytes of group 2!! These are data rather than padding, so group 2 is missing data and the results are skewed.", truncated_data_bytes)?;
+    }
+    Ok(())
+}
+
+/// Prints the [`ResidualEntropy`] of a split comparison, if the groups had equal length.
+pub(crate) fn print_residual_entropy<W: Write>(
+    writer: &mut W,
+    residual: Option<&ResidualEntropy>,
+) -> io::Result<()> {
+    match residual {
+        Some(residual) => writeln!(
+            writer,
+            "    Residual Entropy (xor, sub): ({:.2}, {:.2})",
+            residual.xor, residual.subtract
+        ),
+        None => writeln!(writer, "    Residual Entropy: N/A (group lengths differ)"),
+    }
+}
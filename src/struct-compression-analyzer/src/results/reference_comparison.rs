@@ -0,0 +1,141 @@
+//! Compares the results of each file against those of a reference file.
+//!
+//! Instead of averaging all files, a known-good file (e.g. a reference build) is used as the
+//! baseline, and every other file is reported as a difference from it. This uses the same
+//! difference logic as the [`run_history`] module, with each file treated as a run.
+//!
+//! [`run_history`]: crate::results::run_history
+
+use super::{
+    analysis_results::AnalysisResults,
+    run_history::{RunDiff, RunSummary},
+};
+use crate::schema::Schema;
+use std::io::{self, Write};
+
+/// The differences of a set of files against a reference file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReferenceComparison {
+    /// Label of the reference file.
+    pub reference_label: String,
+    /// Label and difference of every other file, in the order they were given.
+    pub files: Vec<(String, RunDiff)>,
+}
+
+impl ReferenceComparison {
+    /// Compares files against a reference file.
+    ///
+    /// # Arguments
+    /// * `schema` - The schema the results were made with, used to order the fields.
+    /// * `results` - The results of all files, including the reference file.
+    /// * `labels` - The labels of the files (e.g. their paths), in the same order as `results`.
+    /// * `reference_index` - Index of the reference file in `results`.
+    ///
+    /// # Panics
+    /// If `reference_index` is out of bounds, or `labels` and `results` differ in length.
+    pub fn new(
+        schema: &Schema,
+        results: &[AnalysisResults],
+        labels: &[String],
+        reference_index: usize,
+    ) -> Self {
+        assert_eq!(results.len(), labels.len());
+        let summarize =
+            |x: usize| RunSummary::from_results(&results[x], schema, labels[x].clone(), 0);
+        let reference = summarize(reference_index);
+
+        let files = (0..results.len())
+            .filter(|&x| x != reference_index)
+            .map(|x| (labels[x].clone(), summarize(x).diff(&reference)))
+            .collect();
+
+        Self {
+            reference_label: labels[reference_index].clone(),
+            files,
+        }
+    }
+
+    /// The files whose zstd size is larger than the reference, i.e. which regressed.
+    pub fn regressed_files(&self) -> impl Iterator<Item = &str> {
+        self.files
+            .iter()
+            .filter(|(_, diff)| diff.zstd_file_size.delta().is_some_and(|delta| delta > 0))
+            .map(|(label, _)| label.as_str())
+    }
+
+    /// Prints the differences of all files.
+    /// Unchanged items are only printed if `show_unchanged` is set.
+    pub fn print<W: Write>(&self, writer: &mut W, show_unchanged: bool) -> io::Result<()> {
+        writeln!(
+            writer,
+            "Results Relative to Reference File '{}': [as `reference -> file zstd (diff, % of reference)`]",
+            self.reference_label
+        )?;
+
+        let regressed: Vec<&str> = self.regressed_files().collect();
+        if regressed.is_empty() {
+            writeln!(writer, "  Regressed Files: None")?;
+        } else {
+            writeln!(writer, "  Regressed Files: {}", regressed.join(", "))?;
+        }
+
+        for (label, diff) in &self.files {
+            writeln!(writer, "\n  {}:", label)?;
+            diff.print_changes(writer, "    ", show_unchanged)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_entries, CompressionOptions};
+
+    const SCHEMA: &str = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    a: 8
+    b: 8
+"#;
+
+    fn analyze(schema: &Schema, distinct_values: u8) -> AnalysisResults {
+        let entries = (0..=255u8).map(|x| [x % distinct_values, x.wrapping_mul(31)]);
+        analyze_entries(schema, CompressionOptions::default(), entries)
+    }
+
+    #[test]
+    fn reports_files_relative_to_reference() {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let results = vec![
+            analyze(&schema, 200),
+            analyze(&schema, 2),
+            analyze(&schema, 2),
+        ];
+        let labels: Vec<String> = ["worse", "reference", "same"].map(String::from).to_vec();
+        let comparison = ReferenceComparison::new(&schema, &results, &labels, 1);
+
+        assert_eq!(comparison.reference_label, "reference");
+        let file_labels: Vec<&str> = comparison.files.iter().map(|(l, _)| l.as_str()).collect();
+        assert_eq!(file_labels, vec!["worse", "same"]);
+        assert_eq!(
+            comparison.regressed_files().collect::<Vec<_>>(),
+            vec!["worse"]
+        );
+        assert!(!comparison.files[1].1.has_changes());
+
+        let worse = &comparison.files[0].1;
+        assert_eq!(worse.per_field[0].name, "a");
+        assert!(worse.per_field[0].delta().unwrap() > 0);
+        assert_eq!(worse.per_field[1].delta(), Some(0));
+
+        let mut output = Vec::new();
+        comparison.print(&mut output, false).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Regressed Files: worse"));
+        assert!(output.contains("  same:\n    No changes."));
+    }
+}
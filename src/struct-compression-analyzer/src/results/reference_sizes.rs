@@ -0,0 +1,234 @@
+//! Sizes achieved by an external codec, to compare the estimates and zstd sizes against.
+//!
+//! When the size a real codec achieves on a field is already known, it can be supplied in a
+//! sidecar file and loaded into the results as [`FieldMetrics::reference_size`] and
+//! [`GroupComparisonMetrics::reference_size`]. The printed results and CSVs then include the
+//! ratios of the zstd and estimated sizes to it, and the brute forcer can fit the estimator to it
+//! instead of zstd (see [`BruteForceConfig::use_reference_size`]).
+//!
+//! The sidecar is YAML (or JSON, which is valid YAML), e.g.:
+//!
+//! ```yaml
+//! # Fields and groups of the schema, by full path.
+//! fields:
+//!   colors: 5120
+//!   colors.r: 1400
+//! # Groups of split comparisons, by comparison name.
+//! split_comparisons:
+//!   colors_split: { group_1: 5120, group_2: 4700 }
+//! # Baseline and groups of custom comparisons, by comparison name.
+//! custom_comparisons:
+//!   transpose: { baseline: 5120, groups: { transposed: 4500 } }
+//! ```
+//!
+//! Every size is optional; names which aren't in the results are an error, to catch typos.
+//!
+//! [`FieldMetrics::reference_size`]: super::FieldMetrics::reference_size
+//! [`GroupComparisonMetrics::reference_size`]: crate::comparison::GroupComparisonMetrics::reference_size
+//! [`BruteForceConfig::use_reference_size`]: crate::brute_force::BruteForceConfig::use_reference_size
+
+use super::analysis_results::AnalysisResults;
+use ahash::AHashMap;
+use serde::{Deserialize, Serialize};
+use std::{fs::File, io::BufReader, path::Path};
+use thiserror::Error;
+
+/// Errors that can occur while loading or applying [`ReferenceSizes`].
+#[derive(Debug, Error)]
+pub enum ReferenceSizesError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse reference sizes: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("Reference size given for unknown field or group '{0}'")]
+    UnknownField(String),
+
+    #[error("Reference size given for unknown split comparison '{0}'")]
+    UnknownSplitComparison(String),
+
+    #[error("Reference size given for unknown custom comparison '{0}'")]
+    UnknownCustomComparison(String),
+
+    #[error(
+        "Reference size given for unknown group '{group}' of custom comparison '{comparison}'"
+    )]
+    UnknownCustomGroup { comparison: String, group: String },
+}
+
+/// Reference sizes of the groups of a split comparison.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SplitReferenceSizes {
+    /// Reference size of `group_1`.
+    pub group_1: Option<u64>,
+    /// Reference size of `group_2`.
+    pub group_2: Option<u64>,
+}
+
+/// Reference sizes of the baseline and groups of a custom comparison.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CustomReferenceSizes {
+    /// Reference size of the baseline.
+    pub baseline: Option<u64>,
+    /// Reference sizes of the comparison groups, by group name.
+    pub groups: AHashMap<String, u64>,
+}
+
+/// Reference sizes of the fields, groups and comparisons of a single file (or region).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReferenceSizes {
+    /// Reference sizes of fields and groups of the schema, by full path.
+    pub fields: AHashMap<String, u64>,
+    /// Reference sizes of split comparisons, by comparison name.
+    pub split_comparisons: AHashMap<String, SplitReferenceSizes>,
+    /// Reference sizes of custom comparisons, by comparison name.
+    pub custom_comparisons: AHashMap<String, CustomReferenceSizes>,
+}
+
+impl ReferenceSizes {
+    /// Parses reference sizes from YAML (or JSON).
+    pub fn from_yaml(content: &str) -> Result<Self, ReferenceSizesError> {
+        Ok(serde_yaml::from_str(content)?)
+    }
+
+    /// Loads reference sizes from a YAML (or JSON) file.
+    pub fn load(path: &Path) -> Result<Self, ReferenceSizesError> {
+        Ok(serde_yaml::from_reader(BufReader::new(File::open(path)?))?)
+    }
+
+    /// Sets the reference sizes of the matching fields and comparison groups of `results`.
+    ///
+    /// # Errors
+    ///
+    /// If a field, comparison or group with a reference size is not in the results.
+    pub fn apply(&self, results: &mut AnalysisResults) -> Result<(), ReferenceSizesError> {
+        for (path, &size) in &self.fields {
+            let field = results
+                .per_field
+                .get_mut(path)
+                .ok_or_else(|| ReferenceSizesError::UnknownField(path.clone()))?;
+            field.reference_size = Some(size);
+        }
+
+        for (name, sizes) in &self.split_comparisons {
+            let comparison = results
+                .split_comparisons
+                .iter_mut()
+                .find(|comparison| &comparison.name == name)
+                .ok_or_else(|| ReferenceSizesError::UnknownSplitComparison(name.clone()))?;
+            comparison.group1_metrics.reference_size = sizes.group_1;
+            comparison.group2_metrics.reference_size = sizes.group_2;
+        }
+
+        for (name, sizes) in &self.custom_comparisons {
+            let comparison = results
+                .custom_comparisons
+                .iter_mut()
+                .find(|comparison| &comparison.name == name)
+                .ok_or_else(|| ReferenceSizesError::UnknownCustomComparison(name.clone()))?;
+            comparison.baseline_metrics.reference_size = sizes.baseline;
+            for (group, &size) in &sizes.groups {
+                let index = comparison
+                    .group_names
+                    .iter()
+                    .position(|group_name| group_name == group)
+                    .ok_or_else(|| ReferenceSizesError::UnknownCustomGroup {
+                        comparison: name.clone(),
+                        group: group.clone(),
+                    })?;
+                comparison.group_metrics[index].reference_size = Some(size);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_entries, CompressionOptions};
+    use crate::schema::Schema;
+
+    const SCHEMA: &str = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    a: 8
+    b: 8
+analysis:
+  split_groups:
+    - name: a_b
+      group_1: [a, b]
+      group_2: [b, a]
+  compare_groups:
+    - name: swap
+      baseline:
+        - { type: array, field: a }
+        - { type: array, field: b }
+      comparisons:
+        swapped:
+          - { type: array, field: b }
+          - { type: array, field: a }
+"#;
+
+    fn analyze() -> AnalysisResults {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let entries = (0..64u8).map(|x| [x, x.wrapping_mul(3)]);
+        analyze_entries(&schema, CompressionOptions::default(), entries)
+    }
+
+    #[test]
+    fn applies_sizes_to_fields_and_comparisons() {
+        // JSON is valid YAML.
+        let sizes = ReferenceSizes::from_yaml(
+            r#"{
+                "fields": { "a": 40 },
+                "split_comparisons": { "a_b": { "group_2": 90 } },
+                "custom_comparisons": { "swap": { "baseline": 100, "groups": { "swapped": 95 } } }
+            }"#,
+        )
+        .unwrap();
+        let mut results = analyze();
+        sizes.apply(&mut results).unwrap();
+
+        assert_eq!(results.per_field["a"].reference_size, Some(40));
+        assert_eq!(results.per_field["b"].reference_size, None);
+        let split = &results.split_comparisons[0];
+        assert_eq!(split.group1_metrics.reference_size, None);
+        assert_eq!(split.group2_metrics.reference_size, Some(90));
+        let custom = &results.custom_comparisons[0];
+        assert_eq!(custom.baseline_metrics.reference_size, Some(100));
+        assert_eq!(custom.group_metrics[0].reference_size, Some(95));
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        let mut results = analyze();
+        let apply = |yaml: &str, results: &mut AnalysisResults| {
+            ReferenceSizes::from_yaml(yaml).unwrap().apply(results)
+        };
+
+        assert!(matches!(
+            apply("fields: { c: 1 }", &mut results),
+            Err(ReferenceSizesError::UnknownField(field)) if field == "c"
+        ));
+        assert!(matches!(
+            apply("split_comparisons: { b_a: { group_1: 1 } }", &mut results),
+            Err(ReferenceSizesError::UnknownSplitComparison(_))
+        ));
+        assert!(matches!(
+            apply(
+                "custom_comparisons: { swap: { groups: { swaped: 1 } } }",
+                &mut results
+            ),
+            Err(ReferenceSizesError::UnknownCustomGroup { .. })
+        ));
+        assert!(ReferenceSizes::from_yaml("field: { a: 1 }").is_err());
+    }
+}
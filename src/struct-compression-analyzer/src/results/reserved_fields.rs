@@ -0,0 +1,198 @@
+//! Checks whether the fields marked as `reserved` in the schema are really constant.
+//!
+//! Reserved (or unknown) regions of a format are expected to hold the same value in every
+//! record, usually 0. When they don't, they may carry data which isn't documented yet;
+//! that is worth knowing when reverse engineering a format.
+//!
+//! Constancy is determined from the bit stats of the field: a field is constant if every
+//! bit is always 0 or always 1, across all records (and all merged files).
+
+use super::FieldMetrics;
+use crate::{
+    schema::{BitOrder, Schema},
+    utils::analyze_utils::reverse_bits,
+};
+use ahash::AHashMap;
+use std::io::{self, Write};
+
+/// Whether a reserved field holds the same value in every record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReservedFieldStatus {
+    /// Every record has the same value.
+    Constant {
+        /// The value of the field.
+        value: u64,
+    },
+    /// Records have different values.
+    Varies {
+        /// Number of bits which aren't the same in every record.
+        varying_bits: u32,
+        /// Number of distinct values, if they were counted (fields of up to 16 bits).
+        distinct_values: Option<usize>,
+    },
+    /// The field is too wide (more than 64 bits) to have bit stats, so it can't be checked.
+    /// Splitting it into multiple reserved fields allows checking it.
+    Unchecked,
+}
+
+impl FieldMetrics {
+    /// Checks whether the field holds the same value in every record.
+    /// Intended for fields marked as [`Self::reserved`], but works for any field.
+    pub fn reserved_status(&self) -> ReservedFieldStatus {
+        if self.bit_counts.is_empty() && self.lenbits > 0 {
+            return ReservedFieldStatus::Unchecked;
+        }
+
+        let varying_bits = self
+            .bit_counts
+            .iter()
+            .filter(|bit| bit.zeros > 0 && bit.ones > 0)
+            .count() as u32;
+        if varying_bits > 0 {
+            let counted = !self.value_counts.is_empty() && !self.value_counts_overflowed;
+            return ReservedFieldStatus::Varies {
+                varying_bits,
+                distinct_values: counted.then_some(self.value_counts.len()),
+            };
+        }
+
+        // Bit stats are in read order; value counts (and hence rendered values) are not.
+        let bits = self
+            .bit_counts
+            .iter()
+            .fold(0u64, |value, bit| (value << 1) | (bit.ones > 0) as u64);
+        let value = if self.bit_order == BitOrder::Lsb {
+            reverse_bits(self.lenbits, bits)
+        } else {
+            bits
+        };
+        ReservedFieldStatus::Constant { value }
+    }
+}
+
+/// Prints the status of each reserved field, in schema order.
+/// Prints nothing if the schema has no reserved fields.
+pub(crate) fn print_reserved_fields<W: Write>(
+    writer: &mut W,
+    schema: &Schema,
+    per_field: &AHashMap<String, FieldMetrics>,
+) -> io::Result<()> {
+    let mut reserved = schema
+        .ordered_field_and_group_paths()
+        .into_iter()
+        .filter_map(|path| per_field.get(&path))
+        .filter(|field| field.reserved)
+        .peekable();
+    if reserved.peek().is_none() {
+        return Ok(());
+    }
+
+    writeln!(writer, "\nReserved Fields: [should be constant]")?;
+    for field in reserved {
+        match field.reserved_status() {
+            ReservedFieldStatus::Constant { value } => writeln!(
+                writer,
+                "  {}: constant ({})",
+                field.full_path,
+                field.format_value(value)
+            )?,
+            ReservedFieldStatus::Varies {
+                varying_bits,
+                distinct_values,
+            } => {
+                let distinct = distinct_values
+                    .map(|count| format!(", {} distinct values", count))
+                    .unwrap_or_default();
+                writeln!(
+                    writer,
+                    "  [WARNING!!] {}: not constant!! {} of {} bits vary{}. It may carry data.",
+                    field.full_path, varying_bits, field.lenbits, distinct
+                )?
+            }
+            ReservedFieldStatus::Unchecked => writeln!(
+                writer,
+                "  {}: not checked, fields over 64 bits have no bit stats",
+                field.full_path
+            )?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{CompressionOptions, SchemaAnalyzer};
+    use crate::schema::SchemaError;
+
+    const SCHEMA: &str = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    data: 8
+    zero: { type: field, bits: 4, reserved: true }
+    ones: { type: field, bits: 4, reserved: true, bit_order: lsb }
+    hidden: { type: field, bits: 8, reserved: true }
+"#;
+
+    #[test]
+    fn reports_whether_reserved_fields_are_constant() {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        for x in 0..16u8 {
+            analyzer.add_entry(&[x, 0b0000_0001, x % 3]).unwrap();
+        }
+        let results = analyzer.generate_results().unwrap();
+
+        assert_eq!(
+            results.per_field["zero"].reserved_status(),
+            ReservedFieldStatus::Constant { value: 0 }
+        );
+        assert_eq!(
+            results.per_field["ones"].reserved_status(),
+            ReservedFieldStatus::Constant { value: 0b1000 }
+        );
+        assert_eq!(
+            results.per_field["hidden"].reserved_status(),
+            ReservedFieldStatus::Varies {
+                varying_bits: 2,
+                distinct_values: Some(3)
+            }
+        );
+
+        let mut output = Vec::new();
+        print_reserved_fields(&mut output, &schema, &results.per_field).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("  zero: constant (0)"));
+        assert!(output
+            .contains("[WARNING!!] hidden: not constant!! 2 of 8 bits vary, 3 distinct values."));
+        assert!(!output.contains("  data:"));
+    }
+
+    #[test]
+    fn reserved_fields_cant_be_compared() {
+        let split = format!(
+            "{SCHEMA}analysis:\n  split_groups:\n    - {{ name: s, group_1: [data, hidden], group_2: [hidden, data] }}\n"
+        );
+        assert!(matches!(
+            Schema::from_yaml(&split),
+            Err(SchemaError::ReservedFieldInComparison { comparison, field })
+                if comparison == "s" && field == "hidden"
+        ));
+
+        let custom = format!(
+            "{SCHEMA}analysis:\n  compare_groups:\n    - name: c\n      baseline: [{{ type: array, field: data }}]\n      comparisons:\n        g: [{{ type: struct, fields: [{{ type: field, field: data }}, {{ type: field, field: zero }}] }}]\n"
+        );
+        assert!(matches!(
+            Schema::from_yaml(&custom),
+            Err(SchemaError::ReservedFieldInComparison { field, .. }) if field == "zero"
+        ));
+
+        // Skipping a reserved field is how it's left out of a struct.
+        let skipped = format!(
+            "{SCHEMA}analysis:\n  compare_groups:\n    - name: c\n      baseline: [{{ type: array, field: data }}]\n      comparisons:\n        g: [{{ type: struct, fields: [{{ type: field, field: data }}, {{ type: skip, field: zero, bits: 4 }}] }}]\n"
+        );
+        assert!(Schema::from_yaml(&skipped).is_ok());
+    }
+}
@@ -0,0 +1,465 @@
+//! A rolling history of run summaries, for tracking how results change between runs.
+//!
+//! Each run of a directory is summarized as a [`RunSummary`], which holds the (average)
+//! zstd sizes of the file, each field and each comparison group. The summaries are kept in a
+//! [`RunHistory`] file; on every run, the new summary is compared against the previous one with
+//! [`RunSummary::diff`], then appended to the history, dropping the oldest runs once the
+//! history is full.
+//!
+//! The history is saved as YAML.
+
+use super::{
+    analysis_results::AnalysisResults, merged_analysis_results::MergedAnalysisResults, FieldMetrics,
+};
+use crate::comparison::GroupComparisonMetrics;
+use crate::results::calculate_percentage;
+use crate::schema::Schema;
+use ahash::AHashMap;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use thiserror::Error;
+
+/// Version of the run history format.
+/// Bumped whenever the saved data changes in an incompatible way.
+pub const RUN_HISTORY_VERSION: u32 = 1;
+
+/// Default maximum number of runs kept in a [`RunHistory`].
+pub const DEFAULT_HISTORY_LENGTH: usize = 10;
+
+/// Errors that can occur while saving or loading a [`RunHistory`].
+#[derive(Debug, Error)]
+pub enum RunHistoryError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to (de)serialize run history: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("Unsupported run history version {found}, expected {expected}. Delete the history file to start a new one.")]
+    UnsupportedVersion { expected: u32, found: u32 },
+}
+
+/// Summary of the merged results of a single run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunSummary {
+    /// Label of the run, e.g. the analyzed directory.
+    pub label: String,
+    /// Time the run finished at, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Number of files merged in the run.
+    pub file_count: usize,
+    /// Average original size of the files.
+    pub original_size: u64,
+    /// Average zstd size of the files.
+    pub zstd_file_size: u64,
+    /// Field path → average zstd size of the field, in schema order.
+    pub per_field: IndexMap<String, u64>,
+    /// `comparison/group` → average zstd size of the group, in schema order.
+    /// Split comparisons use `group_1` and `group_2` as group names; custom comparisons
+    /// use `baseline` and the names of their groups.
+    pub comparisons: IndexMap<String, u64>,
+}
+
+impl RunSummary {
+    /// Summarizes the merged results of a run.
+    ///
+    /// # Arguments
+    /// * `results` - The merged results of the run.
+    /// * `schema` - The schema the results were made with, used to order the fields.
+    /// * `label` - Label of the run, e.g. the analyzed directory.
+    /// * `timestamp` - Time the run finished at, in seconds since the Unix epoch.
+    pub fn new(
+        results: &MergedAnalysisResults,
+        schema: &Schema,
+        label: String,
+        timestamp: u64,
+    ) -> Self {
+        let mut comparisons = IndexMap::new();
+        for comparison in &results.split_comparisons {
+            insert_split_sizes(
+                &mut comparisons,
+                &comparison.name,
+                &comparison.group1_metrics,
+                &comparison.group2_metrics,
+            );
+        }
+        for comparison in &results.custom_comparisons {
+            insert_custom_sizes(
+                &mut comparisons,
+                &comparison.name,
+                &comparison.baseline_metrics,
+                &comparison.group_names,
+                &comparison.group_metrics,
+            );
+        }
+
+        Self {
+            label,
+            timestamp,
+            file_count: results.merged_file_count,
+            original_size: results.original_size,
+            zstd_file_size: results.zstd_file_size,
+            per_field: field_sizes(schema, &results.per_field),
+            comparisons,
+        }
+    }
+
+    /// Summarizes the results of a single file.
+    ///
+    /// # Arguments
+    /// * `results` - The results of the file.
+    /// * `schema` - The schema the results were made with, used to order the fields.
+    /// * `label` - Label of the file, e.g. its path.
+    /// * `timestamp` - Time the file was analyzed at, in seconds since the Unix epoch.
+    pub fn from_results(
+        results: &AnalysisResults,
+        schema: &Schema,
+        label: String,
+        timestamp: u64,
+    ) -> Self {
+        let mut comparisons = IndexMap::new();
+        for comparison in &results.split_comparisons {
+            insert_split_sizes(
+                &mut comparisons,
+                &comparison.name,
+                &comparison.group1_metrics,
+                &comparison.group2_metrics,
+            );
+        }
+        for comparison in &results.custom_comparisons {
+            insert_custom_sizes(
+                &mut comparisons,
+                &comparison.name,
+                &comparison.baseline_metrics,
+                &comparison.group_names,
+                &comparison.group_metrics,
+            );
+        }
+
+        Self {
+            label,
+            timestamp,
+            file_count: 1,
+            original_size: results.original_size,
+            zstd_file_size: results.zstd_file_size,
+            per_field: field_sizes(schema, &results.per_field),
+            comparisons,
+        }
+    }
+
+    /// Computes the difference between this run and a previous run.
+    pub fn diff(&self, previous: &RunSummary) -> RunDiff {
+        RunDiff {
+            previous_label: previous.label.clone(),
+            previous_timestamp: previous.timestamp,
+            zstd_file_size: SizeChange::new(
+                "file".to_string(),
+                Some(previous.zstd_file_size),
+                Some(self.zstd_file_size),
+            ),
+            per_field: diff_sizes(&previous.per_field, &self.per_field),
+            comparisons: diff_sizes(&previous.comparisons, &self.comparisons),
+        }
+    }
+}
+
+/// The zstd sizes of the fields, in schema order.
+fn field_sizes(
+    schema: &Schema,
+    per_field: &AHashMap<String, FieldMetrics>,
+) -> IndexMap<String, u64> {
+    schema
+        .ordered_field_and_group_paths()
+        .into_iter()
+        .filter_map(|path| {
+            let zstd_size = per_field.get(&path)?.zstd_size;
+            Some((path, zstd_size))
+        })
+        .collect()
+}
+
+fn insert_split_sizes(
+    comparisons: &mut IndexMap<String, u64>,
+    name: &str,
+    group1_metrics: &GroupComparisonMetrics,
+    group2_metrics: &GroupComparisonMetrics,
+) {
+    comparisons.insert(format!("{}/group_1", name), group1_metrics.zstd_size);
+    comparisons.insert(format!("{}/group_2", name), group2_metrics.zstd_size);
+}
+
+fn insert_custom_sizes(
+    comparisons: &mut IndexMap<String, u64>,
+    name: &str,
+    baseline_metrics: &GroupComparisonMetrics,
+    group_names: &[String],
+    group_metrics: &[GroupComparisonMetrics],
+) {
+    comparisons.insert(format!("{}/baseline", name), baseline_metrics.zstd_size);
+    for (group_name, metrics) in group_names.iter().zip(group_metrics) {
+        comparisons.insert(format!("{}/{}", name, group_name), metrics.zstd_size);
+    }
+}
+
+/// Pairs up the sizes of two runs by name, in the order of the current run;
+/// items only present in the previous run come last.
+fn diff_sizes(
+    previous: &IndexMap<String, u64>,
+    current: &IndexMap<String, u64>,
+) -> Vec<SizeChange> {
+    let mut changes: Vec<SizeChange> = current
+        .iter()
+        .map(|(name, &size)| SizeChange::new(name.clone(), previous.get(name).copied(), Some(size)))
+        .collect();
+    changes.extend(
+        previous
+            .iter()
+            .filter(|(name, _)| !current.contains_key(*name))
+            .map(|(name, &size)| SizeChange::new(name.clone(), Some(size), None)),
+    );
+    changes
+}
+
+/// The change of a single zstd size between two runs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SizeChange {
+    /// Name of the item, e.g. a field path.
+    pub name: String,
+    /// Size in the previous run, [`None`] if the item is new.
+    pub previous: Option<u64>,
+    /// Size in the current run, [`None`] if the item was removed.
+    pub current: Option<u64>,
+}
+
+impl SizeChange {
+    pub fn new(name: String, previous: Option<u64>, current: Option<u64>) -> Self {
+        Self {
+            name,
+            previous,
+            current,
+        }
+    }
+
+    /// Difference in bytes (current - previous), if the item is present in both runs.
+    pub fn delta(&self) -> Option<i64> {
+        Some(self.current? as i64 - self.previous? as i64)
+    }
+
+    fn print<W: Write>(&self, writer: &mut W, indent: &str) -> io::Result<()> {
+        match (self.previous, self.current) {
+            (Some(previous), Some(current)) => writeln!(
+                writer,
+                "{}{}: {} -> {} ({:+}, {:.2}%)",
+                indent,
+                self.name,
+                previous,
+                current,
+                current as i64 - previous as i64,
+                calculate_percentage(current as f64, previous as f64)
+            ),
+            (None, Some(current)) => writeln!(writer, "{}{}: {} (new)", indent, self.name, current),
+            (Some(previous), None) => {
+                writeln!(writer, "{}{}: {} (removed)", indent, self.name, previous)
+            }
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// The difference between a run and the previous run, see [`RunSummary::diff`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunDiff {
+    /// Label of the previous run.
+    pub previous_label: String,
+    /// Time the previous run finished at, in seconds since the Unix epoch.
+    pub previous_timestamp: u64,
+    /// Change of the average zstd size of the files.
+    pub zstd_file_size: SizeChange,
+    /// Changes of the field sizes.
+    pub per_field: Vec<SizeChange>,
+    /// Changes of the comparison group sizes.
+    pub comparisons: Vec<SizeChange>,
+}
+
+impl RunDiff {
+    /// Whether any size changed, or any item was added or removed.
+    pub fn has_changes(&self) -> bool {
+        std::iter::once(&self.zstd_file_size)
+            .chain(&self.per_field)
+            .chain(&self.comparisons)
+            .any(|change| change.delta() != Some(0))
+    }
+
+    /// Prints the difference. Unchanged items are only printed if `show_unchanged` is set.
+    pub fn print<W: Write>(&self, writer: &mut W, show_unchanged: bool) -> io::Result<()> {
+        writeln!(
+            writer,
+            "Changes since previous run '{}' (at {}): [as `previous -> current zstd (diff, % of previous)`]",
+            self.previous_label, self.previous_timestamp
+        )?;
+        self.print_changes(writer, "  ", show_unchanged)
+    }
+
+    /// Prints the changed sizes only, without a header.
+    /// Unchanged items are only printed if `show_unchanged` is set.
+    pub fn print_changes<W: Write>(
+        &self,
+        writer: &mut W,
+        indent: &str,
+        show_unchanged: bool,
+    ) -> io::Result<()> {
+        if !self.has_changes() {
+            writeln!(writer, "{}No changes.", indent)?;
+            return Ok(());
+        }
+
+        let nested_indent = format!("{indent}  ");
+        self.zstd_file_size.print(writer, indent)?;
+        for (title, changes) in [
+            ("Fields", &self.per_field),
+            ("Comparisons", &self.comparisons),
+        ] {
+            let changes: Vec<&SizeChange> = changes
+                .iter()
+                .filter(|change| show_unchanged || change.delta() != Some(0))
+                .collect();
+            if changes.is_empty() {
+                continue;
+            }
+
+            writeln!(writer, "{}{}:", indent, title)?;
+            for change in changes {
+                change.print(writer, &nested_indent)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A rolling history of [`RunSummary`] entries, oldest first.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunHistory {
+    /// Version of the format, see [`RUN_HISTORY_VERSION`].
+    pub version: u32,
+    /// The summaries of the runs, oldest first.
+    pub runs: Vec<RunSummary>,
+}
+
+impl Default for RunHistory {
+    fn default() -> Self {
+        Self {
+            version: RUN_HISTORY_VERSION,
+            runs: Vec::new(),
+        }
+    }
+}
+
+impl RunHistory {
+    /// Loads a history previously saved with [`Self::save`].
+    /// Returns an empty history if the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self, RunHistoryError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let reader = BufReader::new(File::open(path)?);
+        let history: Self = serde_yaml::from_reader(reader)?;
+        if history.version != RUN_HISTORY_VERSION {
+            return Err(RunHistoryError::UnsupportedVersion {
+                expected: RUN_HISTORY_VERSION,
+                found: history.version,
+            });
+        }
+        Ok(history)
+    }
+
+    /// Saves the history to a file.
+    pub fn save(&self, path: &Path) -> Result<(), RunHistoryError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_yaml::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// The most recent run, if any.
+    pub fn previous(&self) -> Option<&RunSummary> {
+        self.runs.last()
+    }
+
+    /// Appends a run, dropping the oldest runs so at most `max_length` runs are kept.
+    pub fn push(&mut self, summary: RunSummary, max_length: usize) {
+        self.runs.push(summary);
+        let excess = self.runs.len().saturating_sub(max_length);
+        self.runs.drain(..excess);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(label: &str, file_size: u64, fields: &[(&str, u64)]) -> RunSummary {
+        RunSummary {
+            label: label.to_string(),
+            timestamp: 0,
+            file_count: 1,
+            original_size: 100,
+            zstd_file_size: file_size,
+            per_field: fields
+                .iter()
+                .map(|(name, size)| (name.to_string(), *size))
+                .collect(),
+            comparisons: IndexMap::new(),
+        }
+    }
+
+    #[test]
+    fn diff_pairs_items_by_name() {
+        let previous = summary("a", 50, &[("x", 10), ("y", 20)]);
+        let current = summary("b", 45, &[("y", 15), ("z", 5)]);
+        let diff = current.diff(&previous);
+
+        assert_eq!(diff.previous_label, "a");
+        assert_eq!(diff.zstd_file_size.delta(), Some(-5));
+        assert_eq!(
+            diff.per_field,
+            vec![
+                SizeChange::new("y".into(), Some(20), Some(15)),
+                SizeChange::new("z".into(), None, Some(5)),
+                SizeChange::new("x".into(), Some(10), None),
+            ]
+        );
+        assert!(diff.has_changes());
+        assert!(!current.diff(&current).has_changes());
+    }
+
+    #[test]
+    fn history_keeps_most_recent_runs() {
+        let mut history = RunHistory::default();
+        for x in 0..5 {
+            history.push(summary(&x.to_string(), x, &[]), 3);
+        }
+
+        let labels: Vec<&str> = history.runs.iter().map(|run| run.label.as_str()).collect();
+        assert_eq!(labels, vec!["2", "3", "4"]);
+        assert_eq!(history.previous().unwrap().label, "4");
+    }
+
+    #[test]
+    fn history_roundtrips_and_starts_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.yaml");
+        assert_eq!(RunHistory::load(&path).unwrap(), RunHistory::default());
+
+        let mut history = RunHistory::default();
+        history.push(summary("a", 50, &[("x", 10)]), DEFAULT_HISTORY_LENGTH);
+        history.save(&path).unwrap();
+        let loaded = RunHistory::load(&path).unwrap();
+
+        assert_eq!(loaded, history);
+    }
+}
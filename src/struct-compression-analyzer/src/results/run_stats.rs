@@ -0,0 +1,102 @@
+//! Statistics of the runs of identical consecutive values of a field.
+//!
+//! Entropy and LZ matches don't directly show whether a field has long runs of the same
+//! value, which codecs using run-length encoding (RLE) benefit from. [`RunStats`] counts them,
+//! in a single pass over the values of the field, if requested via
+//! [`CompressionOptions::measure_run_stats`].
+//!
+//! [`CompressionOptions::measure_run_stats`]: crate::analyzer::CompressionOptions::measure_run_stats
+
+use crate::utils::analyze_utils::{bit_writer_to_reader, BitWriterContainer};
+use serde::{Deserialize, Serialize};
+
+/// Run-length statistics of the values of a field.
+/// A run is a maximal sequence of consecutive identical values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunStats {
+    /// Number of runs. Equals the number of values if no value repeats its predecessor.
+    pub runs: u64,
+    /// Length of the longest run, in values.
+    pub max_run_length: u64,
+    /// Mean length of a run, in values.
+    pub mean_run_length: f64,
+}
+
+impl RunStats {
+    /// Calculates the run statistics of a sequence of values.
+    pub fn from_values(values: impl IntoIterator<Item = u64>) -> Self {
+        let mut stats = RunStats::default();
+        let mut num_values = 0u64;
+        let mut previous = None;
+        let mut run_length = 0u64;
+        for value in values {
+            num_values += 1;
+            if previous == Some(value) {
+                run_length += 1;
+            } else {
+                stats.runs += 1;
+                run_length = 1;
+                previous = Some(value);
+            }
+            stats.max_run_length = stats.max_run_length.max(run_length);
+        }
+
+        if stats.runs > 0 {
+            stats.mean_run_length = num_values as f64 / stats.runs as f64;
+        }
+        stats
+    }
+
+    /// Calculates the run statistics of the values written to a field's bitstream.
+    ///
+    /// # Arguments
+    /// * `writer` - The bitstream of the field.
+    /// * `bits` - The size of each value in bits, up to 64.
+    /// * `count` - The number of values in the bitstream.
+    pub(crate) fn from_writer(writer: &mut BitWriterContainer, bits: u32, count: u64) -> Self {
+        let mut reader = bit_writer_to_reader(writer);
+        Self::from_values((0..count).map_while(|_| reader.read(bits).ok()))
+    }
+
+    /// Merges the statistics of multiple files, as if their values were one sequence
+    /// without runs spanning files. Runs are summed, and the mean is over all runs.
+    pub fn merge<'a>(items: impl IntoIterator<Item = &'a RunStats>) -> Self {
+        let mut merged = RunStats::default();
+        let mut num_values = 0.0;
+        for item in items {
+            merged.runs += item.runs;
+            merged.max_run_length = merged.max_run_length.max(item.max_run_length);
+            num_values += item.mean_run_length * item.runs as f64;
+        }
+
+        if merged.runs > 0 {
+            merged.mean_run_length = num_values / merged.runs as f64;
+        }
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_runs_of_identical_values() {
+        let stats = RunStats::from_values([1, 1, 1, 2, 3, 3, 1]);
+        assert_eq!(stats.runs, 4);
+        assert_eq!(stats.max_run_length, 3);
+        assert_eq!(stats.mean_run_length, 7.0 / 4.0);
+
+        assert_eq!(RunStats::from_values([]), RunStats::default());
+    }
+
+    #[test]
+    fn merged_mean_is_over_all_runs() {
+        let a = RunStats::from_values([5; 6]);
+        let b = RunStats::from_values([1, 2]);
+        let merged = RunStats::merge([&a, &b]);
+        assert_eq!(merged.runs, 3);
+        assert_eq!(merged.max_run_length, 6);
+        assert_eq!(merged.mean_run_length, 8.0 / 3.0);
+    }
+}
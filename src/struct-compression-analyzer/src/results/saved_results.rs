@@ -0,0 +1,356 @@
+//! Saves and loads the per-file analysis results of a directory, so reports (CSVs, plots)
+//! can be regenerated without analyzing the files again.
+//!
+//! Only the per-file [`AnalysisResults`] (and the information needed to merge them) are saved;
+//! the [`MergedAnalysisResults`] are recomputed from them on load, via
+//! [`SavedResults::to_merged_results`]. All reports are derived from those per-file results:
+//!
+//! - Plots ([`generate_plots`]) use the per-file results directly, one point per file.
+//! - CSVs ([`write_all_csvs`]) use the per-file results for the per-field and comparison CSVs,
+//!   and the merged results for the value, bit and transition stats.
+//!
+//! The results are saved as YAML; compressed with zstd if the path ends in `.zst`, which
+//! makes the files of large directories much smaller. The source of the schema may be saved
+//! alongside them (see [`SavedResults::with_schema`]), so the results can be reported on
+//! without the original schema file.
+//!
+//! [`generate_plots`]: crate::plot::generate_plots
+//! [`write_all_csvs`]: crate::csv::write_all_csvs
+
+use super::{
+    analysis_results::AnalysisResults, merged_analysis_results::MergedAnalysisResults,
+    AnalysisMergeError,
+};
+use crate::schema::{Schema, SchemaError};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Version of the saved results format.
+/// Bumped whenever the saved data changes in an incompatible way.
+pub const SAVED_RESULTS_VERSION: u32 = 3;
+
+/// zstd level the results are compressed with; YAML compresses well even at low levels.
+const SAVED_RESULTS_ZSTD_LEVEL: i32 = 3;
+
+/// Errors that can occur while saving or loading [`SavedResults`].
+#[derive(Debug, Error)]
+pub enum SavedResultsError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to (de)serialize results: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("Unsupported saved results version {found}, expected {expected}. Re-run the analysis to save new results.")]
+    UnsupportedVersion { expected: u32, found: u32 },
+
+    #[error("Saved results are inconsistent: {results} results, but {file_paths} file paths and {file_names} file names")]
+    MismatchedLengths {
+        results: usize,
+        file_paths: usize,
+        file_names: usize,
+    },
+}
+
+/// The per-file analysis results of a directory, as saved to disk.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedResults {
+    /// Version of the format, see [`SAVED_RESULTS_VERSION`].
+    pub version: u32,
+    /// Paths of the analyzed files, in the same order as [`Self::results`].
+    pub file_paths: Vec<PathBuf>,
+    /// Names of the analyzed files used when printing,
+    /// see [`MergedAnalysisResults::file_names`].
+    pub file_names: Vec<String>,
+    /// Weights used for merging the results, if the results were merged with weights.
+    pub weights: Option<Vec<f64>>,
+    /// The analysis results of each file.
+    pub results: Vec<AnalysisResults>,
+    /// Source (YAML) of the schema the files were analyzed with, if saved.
+    pub schema: Option<String>,
+}
+
+impl SavedResults {
+    /// Creates the saved results for the results of a set of files.
+    ///
+    /// # Arguments
+    /// * `file_paths` - Paths of the analyzed files, in the same order as `results`.
+    /// * `file_names` - Names of the files used when printing.
+    /// * `weights` - Weights to merge the results with, if any.
+    /// * `results` - The analysis results of each file.
+    pub fn new(
+        file_paths: Vec<PathBuf>,
+        file_names: Vec<String>,
+        weights: Option<Vec<f64>>,
+        results: Vec<AnalysisResults>,
+    ) -> Self {
+        Self {
+            version: SAVED_RESULTS_VERSION,
+            file_paths,
+            file_names,
+            weights,
+            results,
+            schema: None,
+        }
+    }
+
+    /// Saves the source (YAML) of the schema the files were analyzed with alongside the results.
+    /// Schemas with `!include` directives must be resolved first, see
+    /// [`Schema::load_yaml_from_file`].
+    pub fn with_schema(mut self, schema: String) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Parses the saved schema, if any.
+    pub fn load_schema(&self) -> Result<Option<Schema>, SchemaError> {
+        self.schema.as_deref().map(Schema::from_yaml).transpose()
+    }
+
+    /// Saves the results to a file, compressed with zstd if the path ends in `.zst`.
+    pub fn save(&self, path: &Path) -> Result<(), SavedResultsError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        if is_compressed(path) {
+            let mut encoder = zstd::Encoder::new(writer, SAVED_RESULTS_ZSTD_LEVEL)?;
+            serde_yaml::to_writer(&mut encoder, self)?;
+            encoder.finish()?.flush()?;
+        } else {
+            serde_yaml::to_writer(&mut writer, self)?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Loads results previously saved with [`Self::save`].
+    /// Fails if there isn't exactly one file path and name per result.
+    pub fn load(path: &Path) -> Result<Self, SavedResultsError> {
+        let reader = BufReader::new(File::open(path)?);
+        let saved: Self = if is_compressed(path) {
+            serde_yaml::from_reader(zstd::Decoder::with_buffer(reader)?)?
+        } else {
+            serde_yaml::from_reader(reader)?
+        };
+        if saved.version != SAVED_RESULTS_VERSION {
+            return Err(SavedResultsError::UnsupportedVersion {
+                expected: SAVED_RESULTS_VERSION,
+                found: saved.version,
+            });
+        }
+        if saved.file_paths.len() != saved.results.len()
+            || saved.file_names.len() != saved.results.len()
+        {
+            return Err(SavedResultsError::MismatchedLengths {
+                results: saved.results.len(),
+                file_paths: saved.file_paths.len(),
+                file_names: saved.file_names.len(),
+            });
+        }
+        Ok(saved)
+    }
+
+    /// Merges the saved per-file results, with the saved weights, if any.
+    pub fn to_merged_results(&self) -> Result<MergedAnalysisResults, AnalysisMergeError> {
+        let merged = match &self.weights {
+            Some(weights) => MergedAnalysisResults::from_results_weighted(&self.results, weights)?,
+            None => MergedAnalysisResults::from_results(&self.results)?,
+        };
+        Ok(merged.with_file_names(self.file_names.clone()))
+    }
+}
+
+/// Whether results saved to (or loaded from) `path` are compressed with zstd.
+fn is_compressed(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "zst")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_entries, CompressionOptions};
+    use crate::schema::Schema;
+
+    const SCHEMA: &str = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    a: 8
+    b: { type: field, bits: 8, interpretation: sint }
+analysis:
+  split_groups:
+    - name: a_b
+      group_1: [a, b]
+      group_2: [b, a]
+  compare_groups:
+    - name: swap
+      baseline:
+        - { type: array, field: a }
+        - { type: array, field: b }
+      comparisons:
+        swapped:
+          - { type: array, field: b }
+          - { type: array, field: a }
+"#;
+
+    fn analyze(schema: &Schema, seed: u8) -> AnalysisResults {
+        let options = CompressionOptions::default()
+            .with_value_transitions(true)
+            .with_estimate_breakdown(true);
+        let entries = (0..64u8).map(|x| [x.wrapping_add(seed), x.wrapping_mul(3)]);
+        analyze_entries(schema, options, entries)
+    }
+
+    #[test]
+    fn save_and_load_roundtrip() {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let saved = SavedResults::new(
+            vec![PathBuf::from("dir/a.bin"), PathBuf::from("dir/b.bin")],
+            vec!["a.bin".into(), "b.bin".into()],
+            Some(vec![1.0, 3.0]),
+            vec![analyze(&schema, 0), analyze(&schema, 7)],
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.yaml");
+        saved.save(&path).unwrap();
+        let loaded = SavedResults::load(&path).unwrap();
+
+        assert_eq!(loaded.file_paths, saved.file_paths);
+        assert_eq!(loaded.file_names, saved.file_names);
+        assert_eq!(loaded.weights, saved.weights);
+        assert_eq!(loaded.results.len(), 2);
+        for (loaded, saved) in loaded.results.iter().zip(&saved.results) {
+            assert_eq!(loaded.zstd_file_size, saved.zstd_file_size);
+            assert_eq!(loaded.per_field.len(), saved.per_field.len());
+            let (field, saved_field) = (&loaded.per_field["b"], &saved.per_field["b"]);
+            assert_eq!(field.value_counts, saved_field.value_counts);
+            assert_eq!(field.value_transitions, saved_field.value_transitions);
+            assert_eq!(field.interpretation, saved_field.interpretation);
+            assert_eq!(
+                loaded.split_comparisons[0].group2_metrics,
+                saved.split_comparisons[0].group2_metrics
+            );
+            assert_eq!(
+                loaded.custom_comparisons[0].group_metrics,
+                saved.custom_comparisons[0].group_metrics
+            );
+        }
+
+        let merged = loaded.to_merged_results().unwrap();
+        let expected = saved.to_merged_results().unwrap();
+        assert_eq!(merged.file_names, expected.file_names);
+        assert_eq!(
+            merged.per_field["a"].entropy,
+            expected.per_field["a"].entropy
+        );
+        assert_eq!(merged.zstd_file_size, expected.zstd_file_size);
+    }
+
+    #[test]
+    fn compressed_results_include_schema() {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let saved = SavedResults::new(
+            vec![PathBuf::from("a.bin")],
+            vec!["a.bin".into()],
+            None,
+            vec![analyze(&schema, 0)],
+        )
+        .with_schema(SCHEMA.to_string());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.yaml.zst");
+        saved.save(&path).unwrap();
+        let compressed = std::fs::read(&path).unwrap();
+        let loaded = SavedResults::load(&path).unwrap();
+
+        // zstd frame magic number
+        assert_eq!(compressed[..4], [0x28, 0xB5, 0x2F, 0xFD]);
+        assert_eq!(
+            loaded.results[0].zstd_file_size,
+            saved.results[0].zstd_file_size
+        );
+        let loaded_schema = loaded.load_schema().unwrap().unwrap();
+        assert_eq!(
+            loaded_schema.ordered_field_and_group_paths(),
+            schema.ordered_field_and_group_paths()
+        );
+        assert!(SavedResults::new(Vec::new(), Vec::new(), None, Vec::new())
+            .load_schema()
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn saved_schema_with_includes_reloads_without_them() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("schema.yaml"),
+            SCHEMA.replace("    a: 8\n", "    <<: !include fields.yaml\n"),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("fields.yaml"), "{ a: 8 }").unwrap();
+        let yaml = Schema::load_yaml_from_file(&dir.path().join("schema.yaml"));
+
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let saved = SavedResults::new(
+            vec![PathBuf::from("a.bin")],
+            vec!["a.bin".into()],
+            None,
+            vec![analyze(&schema, 0)],
+        )
+        .with_schema(yaml.unwrap());
+        let path = dir.path().join("results.yaml");
+        saved.save(&path).unwrap();
+        let loaded = SavedResults::load(&path);
+
+        let loaded_schema = loaded.unwrap().load_schema().unwrap().unwrap();
+        assert_eq!(
+            loaded_schema.ordered_field_and_group_paths(),
+            schema.ordered_field_and_group_paths()
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_version() {
+        let mut saved = SavedResults::new(Vec::new(), Vec::new(), None, Vec::new());
+        saved.version = SAVED_RESULTS_VERSION + 1;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.yaml");
+        saved.save(&path).unwrap();
+        let result = SavedResults::load(&path);
+
+        assert!(matches!(
+            result,
+            Err(SavedResultsError::UnsupportedVersion { found, .. }) if found == SAVED_RESULTS_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn rejects_mismatched_lengths() {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let saved = SavedResults::new(
+            vec![PathBuf::from("a.bin")],
+            vec!["a.bin".into(), "b.bin".into()],
+            None,
+            vec![analyze(&schema, 0), analyze(&schema, 1)],
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.yaml");
+        saved.save(&path).unwrap();
+        let result = SavedResults::load(&path);
+
+        assert!(matches!(
+            result,
+            Err(SavedResultsError::MismatchedLengths {
+                results: 2,
+                file_paths: 1,
+                file_names: 2
+            })
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+//! Ranking of the fields which contribute the most to a metric, e.g. the compressed size.
+//!
+//! Only fields are ranked, not groups; a group's metrics include those of its fields,
+//! so groups would otherwise crowd out the fields which actually contribute the most.
+//!
+//! Rankings are computed from the `per_field` map of the results alone, see
+//! [`MergedAnalysisResults::top_fields_by`].
+//!
+//! [`MergedAnalysisResults::top_fields_by`]: super::merged_analysis_results::MergedAnalysisResults::top_fields_by
+
+use super::{calculate_percentage, FieldMetrics};
+use ahash::{AHashMap, AHashSet};
+use derive_more::FromStr;
+use std::io::{self, Write};
+
+/// Default number of fields printed in the top fields section.
+pub const DEFAULT_TOP_FIELDS: usize = 5;
+
+/// A metric of [`FieldMetrics`] fields can be ranked by.
+///
+/// Parsed case-insensitively from the variant name, e.g. `zstdsize`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromStr)]
+pub enum FieldMetric {
+    /// [`FieldMetrics::zstd_size`]
+    #[default]
+    ZstdSize,
+    /// [`FieldMetrics::entropy`]
+    Entropy,
+    /// [`FieldMetrics::lz_matches`]
+    LzMatches,
+    /// [`FieldMetrics::original_size`]
+    OriginalSize,
+}
+
+impl FieldMetric {
+    /// The value of this metric for a field.
+    pub fn value(self, metrics: &FieldMetrics) -> f64 {
+        match self {
+            FieldMetric::ZstdSize => metrics.zstd_size as f64,
+            FieldMetric::Entropy => metrics.entropy,
+            FieldMetric::LzMatches => metrics.lz_matches as f64,
+            FieldMetric::OriginalSize => metrics.original_size as f64,
+        }
+    }
+
+    /// Human readable name of the metric, e.g. for section headings.
+    pub fn label(self) -> &'static str {
+        match self {
+            FieldMetric::ZstdSize => "compressed size",
+            FieldMetric::Entropy => "entropy",
+            FieldMetric::LzMatches => "LZ matches",
+            FieldMetric::OriginalSize => "original size",
+        }
+    }
+
+    /// Formats a value of this metric, with its unit.
+    fn format(self, value: f64) -> String {
+        match self {
+            FieldMetric::ZstdSize | FieldMetric::OriginalSize => format!("{value} bytes"),
+            FieldMetric::Entropy => format!("{value:.2} bits"),
+            FieldMetric::LzMatches => format!("{value}"),
+        }
+    }
+}
+
+/// Returns up to `n` fields (not groups) of `per_field` with the highest `metric`, highest first.
+/// Ties are broken by full path, so the ranking doesn't depend on the order of the map.
+pub fn top_fields_by(
+    per_field: &AHashMap<String, FieldMetrics>,
+    metric: FieldMetric,
+    n: usize,
+) -> Vec<&FieldMetrics> {
+    let groups: AHashSet<&str> = per_field
+        .values()
+        .filter_map(|field| field.parent_path())
+        .collect();
+    let mut fields: Vec<&FieldMetrics> = per_field
+        .values()
+        .filter(|field| !groups.contains(field.full_path.as_str()))
+        .collect();
+
+    fields.sort_by(|a, b| {
+        metric
+            .value(b)
+            .total_cmp(&metric.value(a))
+            .then_with(|| a.full_path.cmp(&b.full_path))
+    });
+    fields.truncate(n);
+    fields
+}
+
+/// Prints the `n` fields with the highest `metric`.
+/// Sizes and LZ matches are followed by their share of the total of all fields.
+pub(crate) fn print_top_fields<W: Write>(
+    writer: &mut W,
+    per_field: &AHashMap<String, FieldMetrics>,
+    metric: FieldMetric,
+    n: usize,
+) -> io::Result<()> {
+    let all_fields = top_fields_by(per_field, metric, usize::MAX);
+    if all_fields.is_empty() {
+        return Ok(());
+    }
+
+    // Entropy is per bit, so it doesn't add up across fields.
+    let total = (metric != FieldMetric::Entropy)
+        .then(|| all_fields.iter().map(|f| metric.value(f)).sum::<f64>());
+    let fields = &all_fields[..n.min(all_fields.len())];
+    writeln!(
+        writer,
+        "\nTop {} fields by {}:",
+        fields.len(),
+        metric.label()
+    )?;
+    for (rank, field) in fields.iter().enumerate() {
+        let value = metric.value(field);
+        write!(
+            writer,
+            "  {}. {}: {}",
+            rank + 1,
+            field.full_path,
+            metric.format(value)
+        )?;
+        match total {
+            Some(total) => writeln!(writer, " ({:.2}%)", calculate_percentage(value, total))?,
+            None => writeln!(writer)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(full_path: &str, zstd_size: u64, entropy: f64) -> (String, FieldMetrics) {
+        let metrics = FieldMetrics {
+            full_path: full_path.to_string(),
+            zstd_size,
+            entropy,
+            ..Default::default()
+        };
+        (full_path.to_string(), metrics)
+    }
+
+    #[test]
+    fn ranks_fields_but_not_groups() {
+        let per_field: AHashMap<String, FieldMetrics> = [
+            field("color", 300, 6.0),
+            field("color.r", 100, 5.0),
+            field("color.g", 150, 7.0),
+            field("color.b", 100, 4.0),
+            field("alpha", 50, 1.0),
+        ]
+        .into_iter()
+        .collect();
+
+        let paths = |fields: Vec<&FieldMetrics>| -> Vec<String> {
+            fields.iter().map(|f| f.full_path.clone()).collect()
+        };
+        // 'color.b' and 'color.r' tie, and are ordered by path.
+        assert_eq!(
+            paths(top_fields_by(&per_field, FieldMetric::ZstdSize, 3)),
+            ["color.g", "color.b", "color.r"]
+        );
+        assert_eq!(
+            paths(top_fields_by(&per_field, FieldMetric::Entropy, 10)),
+            ["color.g", "color.r", "color.b", "alpha"]
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Comparison, estimation and auto split settings of the schema.
+
+use super::GroupComponent;
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::analyzer::CompressionOptions;
+
+/// Parameters for estimating compression size
+#[derive(Debug, Deserialize, Clone)]
+pub struct CompressionEstimationParams {
+    /// Multiplier for LZ matches in size estimation (default: 0.375)
+    #[serde(default = "default_lz_match_multiplier")]
+    pub lz_match_multiplier: f64,
+    /// Multiplier for entropy in size estimation (default: 1.0)
+    #[serde(default = "default_entropy_multiplier")]
+    pub entropy_multiplier: f64,
+}
+
+impl CompressionEstimationParams {
+    pub fn new(options: &CompressionOptions) -> Self {
+        Self {
+            lz_match_multiplier: options.lz_match_multiplier,
+            entropy_multiplier: options.entropy_multiplier,
+        }
+    }
+}
+
+/// Configuration for comparing field groups
+#[derive(Debug, Deserialize)]
+pub struct SplitComparison {
+    /// Friendly name for this comparison.
+    pub name: String,
+    /// First group path to compare. This is the 'baseline'.
+    #[serde(default)]
+    pub group_1: Vec<String>,
+    /// Second group path to compare. This is the group compared against the baseline (group_1).
+    #[serde(default)]
+    pub group_2: Vec<String>,
+    /// Alternative to [`Self::group_1`] and [`Self::group_2`], for comparing more than 2 layouts
+    /// at once. The first group is the baseline, and each other group is compared against it.
+    ///
+    /// These comparisons are reported alongside the custom comparisons, as a
+    /// [`GroupComparisonResult`] with groups named `group_2`, `group_3`, etc.
+    ///
+    /// [`GroupComparisonResult`]: crate::comparison::compare_groups::GroupComparisonResult
+    #[serde(default)]
+    pub groups: Vec<Vec<String>>,
+    /// Optional description of the comparison
+    #[serde(default)]
+    pub description: String,
+    /// Compression estimation parameters for group 1
+    #[serde(default)]
+    pub compression_estimation_group_1: Option<CompressionEstimationParams>,
+    /// Compression estimation parameters for group 2
+    #[serde(default)]
+    pub compression_estimation_group_2: Option<CompressionEstimationParams>,
+    /// Brings group 2 to the length (in bytes) of group 1 before comparing them, so padding
+    /// differences don't skew the comparison. Group 2 is padded with zero bytes if shorter,
+    /// or truncated if longer; group 1 (the baseline) is never changed.
+    ///
+    /// Discarded non-zero bytes are data rather than padding, and are reported in
+    /// [`SplitComparisonResult::truncated_data_bytes`].
+    /// Can't be used with [`Self::groups`].
+    ///
+    /// [`SplitComparisonResult::truncated_data_bytes`]: crate::comparison::split_comparison::SplitComparisonResult::truncated_data_bytes
+    #[serde(default)]
+    pub normalize_length: bool,
+}
+
+impl SplitComparison {
+    /// Whether the comparison lists its groups in [`Self::groups`],
+    /// rather than [`Self::group_1`] and [`Self::group_2`].
+    pub fn is_multi_group(&self) -> bool {
+        !self.groups.is_empty()
+    }
+
+    /// All groups of the comparison, baseline first, in either form.
+    pub fn all_groups(&self) -> impl Iterator<Item = &Vec<String>> {
+        [&self.group_1, &self.group_2]
+            .into_iter()
+            .filter(|_| !self.is_multi_group())
+            .chain(&self.groups)
+    }
+}
+
+/// Configuration for custom field group comparisons
+#[derive(Debug, Deserialize)]
+pub struct CustomComparison {
+    /// Unique identifier for this comparison
+    pub name: String,
+
+    /// Baseline group definition
+    pub baseline: Vec<GroupComponent>,
+
+    /// Comparison group definitions with names
+    pub comparisons: IndexMap<String, Vec<GroupComponent>>,
+
+    /// Human-readable description
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Configuration for automatically searching the best split of a field.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AutoSplit {
+    /// Name of the field to split.
+    pub field: String,
+    /// Split points are placed at multiples of this many bits, e.g. 8 for bytes, 4 for nibbles.
+    #[serde(default = "default_auto_split_granularity")]
+    pub granularity: u32,
+    /// Maximum number of split points in a single layout.
+    #[serde(default = "default_auto_split_max_splits")]
+    pub max_splits: u32,
+}
+
+fn default_auto_split_granularity() -> u32 {
+    8
+}
+
+fn default_auto_split_max_splits() -> u32 {
+    2
+}
+
+pub(crate) fn default_lz_match_multiplier() -> f64 {
+    0.375
+}
+
+pub(crate) fn default_entropy_multiplier() -> f64 {
+    1.0
+}
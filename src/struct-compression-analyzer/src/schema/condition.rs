@@ -0,0 +1,125 @@
+//! Conditions of conditional offsets and skipped fields, and the byte order they are read in.
+
+use super::BitOrder;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::analyze_utils::reverse_bits;
+
+/// Defines a single condition for offset selection
+///
+/// # Examples
+///
+/// ```yaml
+/// byte_offset: 0x00
+/// bit_offset: 0
+/// bits: 32
+/// value: 0x44445320  # DDS magic
+/// ```
+///
+/// The same magic, stored little-endian (e.g. read with a `u32` from a DDS file):
+///
+/// ```yaml
+/// byte_offset: 0x00
+/// bit_offset: 0
+/// bits: 32
+/// value: 0x20534444  # ' SDD'
+/// endian: little
+/// ```
+#[derive(Debug, PartialEq, Clone, serde::Deserialize)]
+pub struct Condition {
+    /// Byte offset from start of structure
+    pub byte_offset: u64,
+    /// Bit offset within the byte (0-7, left to right)
+    pub bit_offset: u8,
+    /// Number of bits to compare (1-32)
+    pub bits: u8,
+    /// Expected value, in the byte order of [`Self::endian`]
+    pub value: u64,
+    /// Bit order of the condition
+    #[serde(default)]
+    pub bit_order: BitOrder,
+    /// Byte order of the compared bits. [`Endian::Little`] requires whole bytes.
+    #[serde(default)]
+    pub endian: Endian,
+}
+
+impl Condition {
+    /// Whether the bits read (MSB first) at the position of the condition match its value.
+    ///
+    /// The bytes of the read bits are swapped first for [`Endian::Little`],
+    /// then their bit order reversed for [`BitOrder::Lsb`].
+    pub(crate) fn matches(&self, read: u64) -> bool {
+        decode_value(read, self.bits, self.endian, self.bit_order) == self.value
+    }
+}
+
+/// Converts bits read MSB first into a value, by swapping their bytes for [`Endian::Little`],
+/// then reversing their bit order for [`BitOrder::Lsb`].
+pub(super) fn decode_value(read: u64, bits: u8, endian: Endian, bit_order: BitOrder) -> u64 {
+    let bits = bits as u32;
+    let mut value = endian.read_value(read, bits);
+    if bit_order == BitOrder::Lsb {
+        value = reverse_bits(bits, value);
+    }
+    value
+}
+
+/// Byte order of the value of a [`Condition`], or of a field (`byte_order`).
+///
+/// - `Big`: Most significant byte first, i.e. the same order as seen in a hex editor
+/// - `Little`: Least significant byte first
+///
+/// Fields which aren't a whole number of bytes (or are over 64 bits) are always read as-is.
+///
+/// # Examples
+///
+/// ```yaml
+/// endian: big     # Default
+/// endian: little  # e.g. magic numbers read as integers on x86
+/// byte_order: little # e.g. a field holding a little endian `u16`
+/// ```
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Endian {
+    #[default]
+    Big,
+    Little,
+}
+
+impl Endian {
+    /// Converts `bits` bits read in this byte order into a value, i.e. swaps their bytes
+    /// for [`Endian::Little`]. `bits` should be a multiple of 8, up to 64.
+    pub(crate) fn read_value(self, read: u64, bits: u32) -> u64 {
+        match self {
+            Endian::Big => read,
+            Endian::Little => read.swap_bytes().checked_shr(64 - bits).unwrap_or(0),
+        }
+    }
+}
+
+/// Defines conditional offset selection rules
+///
+/// # Examples
+///
+/// ```yaml
+/// - offset: 0x94  # BC7 data offset
+///   conditions:
+///     - byte_offset: 0x00
+///       bit_offset: 0
+///       bits: 32
+///       value: 0x44445320
+///     - byte_offset: 0x54
+///       bit_offset: 0
+///       bits: 32
+///       value: 0x44583130
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConditionalOffset {
+    /// Target offset to use if conditions match
+    pub offset: u64,
+    /// Length of the region at the offset, if not until the end of the file
+    #[serde(default)]
+    pub length: Option<u64>,
+    /// List of conditions that must all be satisfied
+    pub conditions: Vec<Condition>,
+}
@@ -0,0 +1,153 @@
+//! Elements of varying size, whose size is selected by a field in their header.
+//! See [`ElementSize`].
+
+use super::{decode_value, BitOrder, Endian, Schema, SchemaError};
+use bitstream_io::{BigEndian, BitRead, BitReader};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    io::{Cursor, SeekFrom},
+};
+
+/// Selects the size of each element from a field in its header, e.g. a mode or a count.
+///
+/// The selector field is read like a [`Condition`], relative to the start of each element,
+/// and its value is looked up in [`Self::sizes`].
+///
+/// # Examples
+///
+/// ```yaml
+/// element_size:
+///   byte_offset: 0
+///   bit_offset: 0
+///   bits: 2
+///   sizes:
+///     0: 128  # mode 0 elements are 16 bytes
+///     1: 64   # mode 1 elements are 8 bytes
+/// ```
+///
+/// [`Condition`]: super::Condition
+#[derive(Debug, Clone, Deserialize)]
+pub struct ElementSize {
+    /// Byte offset of the selector field from the start of the element
+    pub byte_offset: u64,
+    /// Bit offset within the byte (0-7, left to right)
+    pub bit_offset: u8,
+    /// Number of bits of the selector field (1-64)
+    pub bits: u8,
+    /// Bit order of the selector field
+    #[serde(default)]
+    pub bit_order: BitOrder,
+    /// Byte order of the selector field. [`Endian::Little`] requires whole bytes.
+    #[serde(default)]
+    pub endian: Endian,
+    /// Size of the element in bits, for each value of the selector field.
+    /// Sizes must be whole bytes, and contain the selector field.
+    pub sizes: BTreeMap<u64, u32>,
+}
+
+impl ElementSize {
+    /// Number of bytes at the start of an element needed to read the selector field.
+    pub fn header_bytes(&self) -> usize {
+        (self.end_bit() as usize).div_ceil(8)
+    }
+
+    /// Reads the selector field from the start of `element`,
+    /// or [`None`] if `element` is shorter than [`Self::header_bytes`].
+    pub fn read_selector(&self, element: &[u8]) -> Option<u64> {
+        let mut reader = BitReader::endian(Cursor::new(element), BigEndian);
+        reader
+            .seek_bits(SeekFrom::Start(self.end_bit() - self.bits as u64))
+            .ok()?;
+        let read = reader.read_var::<u64>(self.bits as u32).ok()?;
+        Some(decode_value(read, self.bits, self.endian, self.bit_order))
+    }
+
+    /// Bit after the end of the selector field.
+    fn end_bit(&self) -> u64 {
+        (self.byte_offset * 8) + self.bit_offset as u64 + self.bits as u64
+    }
+
+    /// Ensures the selector field can be read, and every size is whole bytes containing it.
+    fn validate(&self) -> Result<(), &'static str> {
+        if self.bits == 0 || self.bits > 64 {
+            return Err("must read between 1 and 64 bits");
+        }
+        if self.endian == Endian::Little && !self.bits.is_multiple_of(8) {
+            return Err("must read a multiple of 8 bits to be little endian");
+        }
+        if self.sizes.is_empty() {
+            return Err("needs at least one size");
+        }
+        if self.sizes.values().any(|&size| !size.is_multiple_of(8)) {
+            return Err("sizes must be whole bytes (multiples of 8 bits)");
+        }
+        if self
+            .sizes
+            .values()
+            .any(|&size| (size as u64) < self.end_bit())
+        {
+            return Err("sizes must be large enough to contain the selector field");
+        }
+        Ok(())
+    }
+}
+
+impl Schema {
+    /// Ensures the [`ElementSize`] is valid, and not combined with a continuous bitstream,
+    /// whose records aren't split into elements.
+    pub(super) fn validate_element_size(&self) -> Result<(), SchemaError> {
+        let Some(element_size) = &self.element_size else {
+            return Ok(());
+        };
+        if self.continuous {
+            return Err(SchemaError::InvalidElementSize(
+                "can't be used with `continuous`",
+            ));
+        }
+        element_size
+            .validate()
+            .map_err(SchemaError::InvalidElementSize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn element_sizes_are_validated() {
+        let schema = |continuous: bool, sizes: &str| {
+            format!(
+                r#"
+version: '1.0'
+continuous: {continuous}
+element_size: {{ byte_offset: 1, bit_offset: 0, bits: 4, sizes: {sizes} }}
+root: {{ type: group, fields: {{ a: 8 }} }}
+"#
+            )
+        };
+
+        let element_size = Schema::from_yaml(&schema(false, "{ 0: 64, 15: 128 }"))
+            .unwrap()
+            .element_size
+            .unwrap();
+        assert_eq!(element_size.sizes[&15], 128);
+        assert_eq!(element_size.header_bytes(), 2);
+        assert_eq!(element_size.read_selector(&[0, 0xF0]), Some(15));
+        assert_eq!(element_size.read_selector(&[0]), None);
+
+        // Not whole bytes, too small for the selector, no sizes, and a continuous bitstream.
+        for (continuous, sizes) in [
+            (false, "{ 0: 60 }"),
+            (false, "{ 0: 8 }"),
+            (false, "{}"),
+            (true, "{ 0: 64 }"),
+        ] {
+            assert!(matches!(
+                Schema::from_yaml(&schema(continuous, sizes)),
+                Err(SchemaError::InvalidElementSize(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,458 @@
+//! Fields and groups of the schema.
+
+use super::{BitOrder, Condition, Endian, FixedPoint, GroupComponent, ValueInterpretation};
+use ahash::AHashMap;
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+/// Allows us to define a nested item as either a field or group
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum FieldDefinition {
+    Field(Field),
+    Group(Group),
+}
+
+/// A single field definition
+#[derive(Debug)]
+pub struct Field {
+    pub bits: u32,
+    pub description: String,
+    pub bit_order: BitOrder,
+    /// Byte order of the value of the field, see [`Endian`].
+    /// Inherited from the parent group (and ultimately the schema) if not set.
+    pub byte_order: Option<Endian>,
+    pub skip_if_not: Vec<Condition>,
+    /// The field is skipped if any of these conditions match.
+    /// Takes precedence over [`Self::skip_if_not`].
+    pub skip_if: Vec<Condition>,
+    pub skip_frequency_analysis: bool,
+    pub interpretation: ValueInterpretation,
+    /// Marks a reserved (or unknown) region which should be constant.
+    /// It is analyzed, but can't be used in comparisons.
+    pub reserved: bool,
+    /// Multiplier for LZ matches when estimating the size of this field.
+    /// Uses [`CompressionOptions::lz_match_multiplier`] if not set.
+    ///
+    /// [`CompressionOptions::lz_match_multiplier`]: crate::analyzer::CompressionOptions::lz_match_multiplier
+    pub lz_match_multiplier: Option<f64>,
+    /// Multiplier for entropy when estimating the size of this field.
+    /// Uses [`CompressionOptions::entropy_multiplier`] if not set.
+    ///
+    /// [`CompressionOptions::entropy_multiplier`]: crate::analyzer::CompressionOptions::entropy_multiplier
+    pub entropy_multiplier: Option<f64>,
+}
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum FieldRepr {
+            Shorthand(u32),
+            Extended {
+                bits: u32,
+                #[serde(default)]
+                description: String,
+                #[serde(default)]
+                #[serde(rename = "bit_order")]
+                bit_order: BitOrder,
+                #[serde(default)]
+                byte_order: Option<Endian>,
+                #[serde(default)]
+                skip_if_not: Vec<Condition>,
+                #[serde(default)]
+                skip_if: Vec<Condition>,
+                #[serde(default)]
+                skip_frequency_analysis: bool,
+                #[serde(default)]
+                interpretation: Option<ValueInterpretation>,
+                /// Shorthand for `interpretation: sint`.
+                #[serde(default)]
+                signed: bool,
+                /// Alternative to `interpretation: fixed(q)`, see [`FixedPoint`].
+                #[serde(default)]
+                fixed_point: Option<FixedPoint>,
+                #[serde(default)]
+                reserved: bool,
+                #[serde(default)]
+                lz_match_multiplier: Option<f64>,
+                #[serde(default)]
+                entropy_multiplier: Option<f64>,
+            },
+        }
+
+        // The magic that allows for either shorthand or extended notation
+        match FieldRepr::deserialize(deserializer)? {
+            FieldRepr::Shorthand(size) => Ok(Field {
+                bits: size,
+                description: String::new(),
+                bit_order: BitOrder::default(),
+                byte_order: None,
+                skip_if_not: Vec::new(),
+                skip_if: Vec::new(),
+                skip_frequency_analysis: false,
+                interpretation: ValueInterpretation::default(),
+                reserved: false,
+                lz_match_multiplier: None,
+                entropy_multiplier: None,
+            }),
+            FieldRepr::Extended {
+                bits,
+                description,
+                bit_order,
+                byte_order,
+                skip_if_not,
+                skip_if,
+                skip_frequency_analysis,
+                interpretation,
+                signed,
+                fixed_point,
+                reserved,
+                lz_match_multiplier,
+                entropy_multiplier,
+            } => {
+                let interpretation = match (signed, interpretation) {
+                    (false, interpretation) => interpretation.unwrap_or_default(),
+                    (true, None | Some(ValueInterpretation::Sint)) => ValueInterpretation::Sint,
+                    (true, Some(interpretation)) => {
+                        return Err(serde::de::Error::custom(format!(
+                            "A 'signed' field can't have the '{:?}' interpretation, use 'sint' or omit it",
+                            interpretation
+                        )));
+                    }
+                };
+                let interpretation = match fixed_point {
+                    None => interpretation,
+                    Some(_) if signed || interpretation != ValueInterpretation::default() => {
+                        return Err(serde::de::Error::custom(
+                            "A 'fixed_point' field can't also be 'signed' or have an 'interpretation'",
+                        ));
+                    }
+                    Some(fixed_point) => fixed_point
+                        .interpretation(bits)
+                        .map_err(serde::de::Error::custom)?,
+                };
+                if interpretation == ValueInterpretation::Float16 && bits != 16 {
+                    return Err(serde::de::Error::custom(format!(
+                        "The 'float16' interpretation requires a 16 bit field, but the field has {} bits",
+                        bits
+                    )));
+                }
+
+                Ok(Field {
+                    bits,
+                    description,
+                    bit_order,
+                    byte_order,
+                    skip_if_not,
+                    skip_if,
+                    skip_frequency_analysis,
+                    interpretation,
+                    reserved,
+                    lz_match_multiplier,
+                    entropy_multiplier,
+                })
+            }
+        }
+    }
+}
+
+/// Group of related fields or components
+///
+/// Represents a logical grouping of fields in the bit-packed structure.
+/// Groups can contain both individual fields and nested sub-groups.
+///
+/// # Fields
+/// - `_type`: Must be "group" (validated during parsing)
+/// - `description`: Optional description of the group's purpose
+/// - `fields`: Map of field names to their definitions (fields or sub-groups)
+///
+/// # Examples
+/// ```yaml
+/// root:
+///   type: group
+///   description: Main structure
+///   fields:
+///     header:
+///       type: group
+///       fields:
+///         mode: 2
+///         partition: 4
+///     colors:
+///       type: group
+///       fields:
+///         r:
+///           type: group
+///           fields:
+///             R0: 5
+///             R1: 5
+/// ```
+#[derive(Debug, Default)]
+pub struct Group {
+    _type: String,
+    pub description: String,
+    pub fields: IndexMap<String, FieldDefinition>,
+    /// Total bits calculated from children fields/groups
+    pub bits: u32,
+    /// The bit order of this group.
+    /// Inherited by all the children unless explicitly overwritten.
+    pub bit_order: BitOrder,
+    /// The byte order of the fields of this group, see [`Endian`].
+    /// Inherited by all the children unless explicitly overwritten.
+    pub byte_order: Option<Endian>,
+    pub skip_if_not: Vec<Condition>,
+    /// The group is skipped if any of these conditions match.
+    /// Takes precedence over [`Self::skip_if_not`].
+    pub skip_if: Vec<Condition>,
+    pub skip_frequency_analysis: bool,
+}
+
+impl<'de> Deserialize<'de> for Group {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct GroupRepr {
+            #[serde(rename = "type")]
+            _type: String,
+            #[serde(default)]
+            description: String,
+            #[serde(default)]
+            bit_order: BitOrder,
+            #[serde(default)]
+            byte_order: Option<Endian>,
+            #[serde(default, deserialize_with = "deserialize_unique_fields")]
+            fields: IndexMap<String, FieldDefinition>,
+            #[serde(default)]
+            skip_if_not: Vec<Condition>,
+            #[serde(default)]
+            skip_if: Vec<Condition>,
+            #[serde(default)]
+            skip_frequency_analysis: bool,
+        }
+
+        let group = GroupRepr::deserialize(deserializer)?;
+        if group._type != "group" {
+            return Err(serde::de::Error::custom(format!(
+                "Invalid group type: {} (must be 'group')",
+                group._type
+            )));
+        }
+
+        // Calculate total bits from children
+        // This is recursive. Deserialize of child would have calculated this for the child.
+        let bits = group
+            .fields
+            .values()
+            .map(|fd| match fd {
+                FieldDefinition::Field(f) => f.bits,
+                FieldDefinition::Group(g) => g.bits,
+            })
+            .sum();
+
+        // Create the group with its own bit_order
+        let mut group = Group {
+            _type: group._type,
+            description: group.description,
+            fields: group.fields,
+            bits,
+            bit_order: group.bit_order,
+            byte_order: group.byte_order,
+            skip_if_not: group.skip_if_not,
+            skip_if: group.skip_if,
+            skip_frequency_analysis: group.skip_frequency_analysis,
+        };
+
+        // Propagate bit_order to children if not explicitly set
+        let bit_order = group.bit_order;
+        propagate_bit_order(&mut group, bit_order);
+        let byte_order = group.byte_order;
+        propagate_byte_order(&mut group, byte_order);
+
+        Ok(group)
+    }
+}
+
+/// Deserializes the fields of a group, rejecting fields with duplicate names.
+///
+/// A plain [`IndexMap`] would silently keep only the last of the duplicates.
+fn deserialize_unique_fields<'de, D>(
+    deserializer: D,
+) -> Result<IndexMap<String, FieldDefinition>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct UniqueFieldsVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for UniqueFieldsVisitor {
+        type Value = IndexMap<String, FieldDefinition>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a map of field names to field definitions")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: serde::de::MapAccess<'de>,
+        {
+            let mut fields = IndexMap::new();
+            while let Some((name, field)) = map.next_entry::<String, FieldDefinition>()? {
+                if fields.contains_key(&name) {
+                    return Err(serde::de::Error::custom(format!(
+                        "Duplicate field name '{}' within the same group",
+                        name
+                    )));
+                }
+                fields.insert(name, field);
+            }
+            Ok(fields)
+        }
+    }
+
+    deserializer.deserialize_map(UniqueFieldsVisitor)
+}
+
+/// Collects the names of the fields read by the components, including nested ones.
+pub(super) fn collect_read_fields<'a>(components: &'a [GroupComponent], fields: &mut Vec<&'a str>) {
+    for component in components {
+        match component {
+            GroupComponent::Array(array) => fields.push(&array.field),
+            GroupComponent::Field(field) => fields.push(&field.field),
+            GroupComponent::Struct(group) => collect_read_fields(&group.fields, fields),
+            GroupComponent::Rle(rle) => collect_read_fields(&rle.components, fields),
+            GroupComponent::Transform(transform) => fields.push(&transform.field),
+            GroupComponent::ByteSplit(split) => fields.push(&split.field),
+            GroupComponent::Padding(_) | GroupComponent::Skip(_) => {}
+        }
+    }
+}
+
+/// Collects the names of all fields read or skipped by the components, including nested ones.
+pub(super) fn collect_referenced_fields<'a>(
+    components: &'a [GroupComponent],
+    fields: &mut Vec<&'a str>,
+) {
+    for component in components {
+        match component {
+            GroupComponent::Array(array) => fields.push(&array.field),
+            GroupComponent::Field(field) => fields.push(&field.field),
+            GroupComponent::Skip(skip) => fields.push(&skip.field),
+            GroupComponent::Struct(group) => collect_referenced_fields(&group.fields, fields),
+            GroupComponent::Rle(rle) => collect_referenced_fields(&rle.components, fields),
+            GroupComponent::Transform(transform) => fields.push(&transform.field),
+            GroupComponent::ByteSplit(split) => fields.push(&split.field),
+            GroupComponent::Padding(_) => {}
+        }
+    }
+}
+
+impl Group {
+    /// Collects the size in bits of all fields and groups within this group, by name.
+    pub(super) fn collect_field_bits<'a>(&'a self, bits: &mut AHashMap<&'a str, u32>) {
+        for (name, item) in &self.fields {
+            match item {
+                FieldDefinition::Field(field) => {
+                    bits.insert(name, field.bits);
+                }
+                FieldDefinition::Group(group) => {
+                    bits.insert(name, group.bits);
+                    group.collect_field_bits(bits);
+                }
+            }
+        }
+    }
+
+    /// Collects the names of all fields marked as [`Field::reserved`], including nested ones.
+    pub(super) fn collect_reserved_fields<'a>(&'a self, names: &mut Vec<&'a str>) {
+        for (name, item) in &self.fields {
+            match item {
+                FieldDefinition::Field(field) if field.reserved => names.push(name),
+                FieldDefinition::Field(_) => {}
+                FieldDefinition::Group(group) => group.collect_reserved_fields(names),
+            }
+        }
+    }
+
+    /// Collects the `skip_if_not` and `skip_if` conditions of this group and all fields within it.
+    pub(super) fn collect_conditions<'a>(&'a self, conditions: &mut Vec<&'a Condition>) {
+        conditions.extend(&self.skip_if_not);
+        conditions.extend(&self.skip_if);
+        for item in self.fields.values() {
+            match item {
+                FieldDefinition::Field(field) => {
+                    conditions.extend(&field.skip_if_not);
+                    conditions.extend(&field.skip_if);
+                }
+                FieldDefinition::Group(group) => group.collect_conditions(conditions),
+            }
+        }
+    }
+
+    /// Collects a list of field paths in schema order
+    /// This includes both fields and groups
+    pub(super) fn collect_field_paths(&self, paths: &mut Vec<String>, parent_path: &str) {
+        for (name, item) in &self.fields {
+            match item {
+                FieldDefinition::Field(_) => {
+                    let full_path = if parent_path.is_empty() {
+                        name
+                    } else {
+                        &format!("{}.{}", parent_path, name)
+                    };
+                    paths.push(full_path.clone());
+                }
+                FieldDefinition::Group(g) => {
+                    let new_parent = if parent_path.is_empty() {
+                        name
+                    } else {
+                        &format!("{}.{}", parent_path, name)
+                    };
+                    paths.push(new_parent.clone());
+                    g.collect_field_paths(paths, new_parent);
+                }
+            }
+        }
+    }
+}
+
+/// Recursively propagates bit_order to child fields and groups
+fn propagate_bit_order(group: &mut Group, parent_bit_order: BitOrder) {
+    for (_, field_def) in group.fields.iter_mut() {
+        match field_def {
+            FieldDefinition::Field(field) => {
+                // Only inherit if field has default bit_order
+                if field.bit_order == BitOrder::Default {
+                    field.bit_order = parent_bit_order;
+                }
+            }
+            FieldDefinition::Group(child_group) => {
+                // Only inherit if child group has default bit_order
+                if child_group.bit_order == BitOrder::Default {
+                    child_group.bit_order = parent_bit_order;
+                }
+                // Recursively propagate to nested groups
+                propagate_bit_order(child_group, child_group.bit_order);
+            }
+        }
+    }
+}
+
+/// Recursively propagates byte_order to child fields and groups which don't set their own.
+pub(super) fn propagate_byte_order(group: &mut Group, parent_byte_order: Option<Endian>) {
+    for (_, field_def) in group.fields.iter_mut() {
+        match field_def {
+            FieldDefinition::Field(field) => {
+                field.byte_order = field.byte_order.or(parent_byte_order);
+            }
+            FieldDefinition::Group(child_group) => {
+                child_group.byte_order = child_group.byte_order.or(parent_byte_order);
+                propagate_byte_order(child_group, child_group.byte_order);
+            }
+        }
+    }
+}
@@ -0,0 +1,372 @@
+//! Components of the groups of split and custom comparisons.
+
+use super::{default_entropy_multiplier, default_lz_match_multiplier, sign_extend};
+use serde::{Deserialize, Serialize};
+
+use crate::analyzer::AnalyzerFieldState;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type")] // Use "type" field as variant discriminant
+pub enum GroupComponent {
+    /// Array of field values
+    #[serde(rename = "array")]
+    Array(GroupComponentArray),
+
+    /// Structured group of components
+    #[serde(rename = "struct")]
+    Struct(GroupComponentStruct),
+
+    /// Run-length encoded bytes of other components
+    #[serde(rename = "rle")]
+    Rle(GroupComponentRle),
+
+    /// Transformed (e.g. delta coded) values of a field
+    #[serde(rename = "transform")]
+    Transform(GroupComponentTransform),
+
+    /// The bytes of a field's values, split into a plane per byte position
+    #[serde(rename = "byte_split")]
+    ByteSplit(GroupComponentByteSplit),
+
+    /// Padding bits.
+    /// This should only be used from within structs.
+    #[serde(rename = "padding")]
+    Padding(GroupComponentPadding),
+
+    /// Read the data from a field, once.
+    /// This should only be used from within structs.
+    #[serde(rename = "field")]
+    Field(GroupComponentField),
+
+    /// Skip a number of bits from a field.
+    /// This should only be used from within structs.
+    #[serde(rename = "skip")]
+    Skip(GroupComponentSkip),
+}
+
+/// Reads all values of a single field until end of input.
+/// i.e. `R0`, `R0`, `R0` etc. until all R0 values are read.
+///
+/// ```yaml
+/// - { type: array, field: R } # reads all 'R' values from input
+/// ```
+///
+/// This is read in a loop until no more bytes are written to output.  
+/// Alternatively, you can read only some bits at a time using the `bits` field.  
+///
+/// ```yaml
+/// - { type: array, field: R, offset: 2, bits: 4 } # read slice [2-6] for 'R' values from input
+/// ```
+///
+/// Allowed properties:
+///
+/// - `offset`: Number of bits to skip before reading `bits`.
+/// - `bits`: Number of bits to read (default: size of field)
+/// - `field`: Field name
+/// - `element_offset`: Read from the element this many elements after (or before, if negative)
+///   the current one (default: 0)
+/// - `edge`: What to do when `element_offset` points past either end of the array;
+///   `skip` (default) or `clamp`, see [`ArrayEdge`]
+/// - `exclude_bits`: Offsets of bits of the field to leave out, see below (default: none)
+///
+/// The `offset` and `bits` properties allow you to read a slice of a field.
+/// Regardless of the slice read however, after each read is done, the stream will be advanced to the
+/// next field.
+///
+/// The `element_offset` property lets a read pull bits from a neighbouring element, e.g. to follow the
+/// top bits of each element with the low bits of the next one:
+///
+/// ```yaml
+/// - { type: array, field: R, offset: 4, bits: 4 } # top bits of R0, R1, R2, ...
+/// - { type: array, field: R, bits: 4, element_offset: 1 } # low bits of R1, R2, R3, ...
+/// ```
+///
+/// The `exclude_bits` property drops individual bits from the middle of the bits read, e.g. to
+/// measure the impact of a noisy bit. The remaining bits of each element are written
+/// back to back, so each element is as many bits shorter as were excluded:
+///
+/// ```yaml
+/// - { type: array, field: R, exclude_bits: [0] } # R without its first (e.g. sign) bit
+/// ```
+///
+/// Offsets count from the first bit of the field, like `offset` and the bit stats, and must
+/// be within the bits read.
+///
+/// Note: The `Array` type can be represented as `Struct` technically speaking, this is
+/// actually a shorthand.
+#[derive(Debug, Deserialize, Clone)]
+pub struct GroupComponentArray {
+    /// Name of the field to pull the data from.
+    pub field: String,
+    /// Offset in the field from which to read.
+    #[serde(default)]
+    pub offset: u32,
+    /// The number of bits to read from the field.
+    #[serde(default)]
+    pub bits: u32,
+    /// Index of the element to read from, relative to the current element.
+    #[serde(default)]
+    pub element_offset: i32,
+    /// What to do with elements whose [`Self::element_offset`] is out of bounds.
+    #[serde(default)]
+    pub edge: ArrayEdge,
+    /// Offsets (from the start of the field) of bits to leave out of each element.
+    #[serde(default)]
+    pub exclude_bits: Vec<u32>,
+    /// Multiplier for LZ matches in size estimation
+    #[serde(default = "default_lz_match_multiplier")]
+    pub lz_match_multiplier: f64,
+    /// Multiplier for entropy in size estimation
+    #[serde(default = "default_entropy_multiplier")]
+    pub entropy_multiplier: f64,
+}
+
+/// What an array read with an [`element_offset`] does for elements whose neighbour is
+/// past either end of the array.
+///
+/// [`element_offset`]: GroupComponentArray::element_offset
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrayEdge {
+    /// Nothing is written for these elements.
+    #[default]
+    Skip,
+    /// The first or last element is read instead.
+    Clamp,
+}
+
+impl Default for GroupComponentArray {
+    fn default() -> Self {
+        Self {
+            field: String::new(),
+            offset: 0,
+            bits: 0,
+            element_offset: 0,
+            edge: ArrayEdge::Skip,
+            exclude_bits: Vec::new(),
+            lz_match_multiplier: default_lz_match_multiplier(),
+            entropy_multiplier: default_entropy_multiplier(),
+        }
+    }
+}
+
+impl GroupComponentArray {
+    /// Retrieve the number of bits to read from the field.
+    /// Either directly from the [`GroupComponentArray`] or if not specified, from the [`AnalyzerFieldState`].
+    pub fn get_bits(&self, field: &AnalyzerFieldState) -> u32 {
+        if self.bits == 0 {
+            field.lenbits
+        } else {
+            self.bits
+        }
+    }
+}
+
+/// Structured group of components
+///
+/// ```yaml
+/// - type: struct # R0 G0 B0. Repeats until no data written.
+///   fields:
+///     - { type: field, field: R } # reads 1 'R' value from input
+///     - { type: field, field: G } # reads 1 'G' value from input
+///     - { type: field, field: B } # reads 1 'B' value from input
+/// ```
+///
+/// Allowed properties:
+///
+/// - `fields`: Array of field names
+#[derive(Debug, Deserialize, Clone)]
+pub struct GroupComponentStruct {
+    /// Array of field names
+    pub fields: Vec<GroupComponent>,
+    /// Multiplier for LZ matches in size estimation
+    #[serde(default = "default_lz_match_multiplier")]
+    pub lz_match_multiplier: f64,
+    /// Multiplier for entropy in size estimation
+    #[serde(default = "default_entropy_multiplier")]
+    pub entropy_multiplier: f64,
+}
+
+/// Run-length encodes the bytes written by other components.
+///
+/// ```yaml
+/// - type: rle # (length, value) pairs of the bytes of all 'R' values
+///   components:
+///     - { type: array, field: R }
+/// ```
+///
+/// The bytes of the components are encoded as `(length, value)` byte pairs, where `length`
+/// is the number of times (1-255) `value` repeats. Longer runs are split into multiple pairs.
+/// i.e. `AA AA AA BB` is encoded as `03 AA 01 BB`.
+///
+/// Compare this against the components as-is to evaluate whether run-length
+/// encoding them would help.
+///
+/// Allowed properties:
+///
+/// - `components`: Components to encode. Same rules as top level components.
+#[derive(Debug, Deserialize, Clone)]
+pub struct GroupComponentRle {
+    /// Components whose bytes are encoded
+    pub components: Vec<GroupComponent>,
+}
+
+/// Transforms the values of a field, e.g. by delta coding them, and writes the results.
+///
+/// ```yaml
+/// - { type: transform, field: R, op: delta } # R0, R1 - R0, R2 - R1, ...
+/// ```
+///
+/// Transforms operate per element, across the sequence of the field's values, i.e. each value
+/// is transformed relative to the value of the same field in the previous element.
+/// Each result is written with the same number of bits as the field, wrapping on overflow.
+///
+/// Compare this against an array of the field to evaluate whether the transform would
+/// make it more compressible.
+///
+/// Allowed properties:
+///
+/// - `field`: Field name
+/// - `op`: The transform to apply, see [`TransformOp`]
+#[derive(Debug, Deserialize, Clone)]
+pub struct GroupComponentTransform {
+    /// Name of the field whose values are transformed.
+    pub field: String,
+    /// The transform applied to each value.
+    pub op: TransformOp,
+}
+
+/// Splits the values of a multi-byte field into planes of their bytes, i.e. writes the first
+/// byte of every value, then the second byte of every value, and so on.
+///
+/// ```yaml
+/// - { type: byte_split, field: R } # R0[0] R1[0] R2[0] ... R0[1] R1[1] R2[1] ...
+/// ```
+///
+/// Bytes are numbered from the most significant (0) to the least significant, after applying
+/// the field's `byte_order`. If the field isn't a whole number of bytes, the first byte is partial.
+///
+/// Compare this against an array of the field to evaluate whether splitting it into byte planes
+/// would make it more compressible.
+///
+/// Allowed properties:
+///
+/// - `field`: Field name. Fields of up to 64 bits are supported.
+/// - `planes`: Byte positions to write, in order (default: all, from the most significant)
+#[derive(Debug, Deserialize, Clone)]
+pub struct GroupComponentByteSplit {
+    /// Name of the field whose values are split.
+    pub field: String,
+    /// The byte positions written, in order. All byte positions if empty.
+    #[serde(default)]
+    pub planes: Vec<u32>,
+}
+
+/// A transform of the values of a field, see [`GroupComponentTransform`].
+///
+/// `prev` is the previous value of the field, or 0 for the first value.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransformOp {
+    /// `value - prev`, i.e. delta coding.
+    Delta,
+    /// `value ^ prev`.
+    XorPrev,
+    /// Maps the value, as a two's complement number, to an unsigned number so small
+    /// magnitudes become small values: 0, -1, 1, -2, 2 become 0, 1, 2, 3, 4.
+    #[serde(rename = "zigzag")]
+    ZigZag,
+}
+
+impl TransformOp {
+    /// Transforms a `bits` bit `value`, given the previous value of the field.
+    pub fn apply(self, value: u64, prev: u64, bits: u32) -> u64 {
+        let mask = u64::MAX >> (64 - bits.clamp(1, 64));
+        let result = match self {
+            TransformOp::Delta => value.wrapping_sub(prev),
+            TransformOp::XorPrev => value ^ prev,
+            TransformOp::ZigZag => {
+                let value = sign_extend(value, bits);
+                ((value << 1) ^ (value >> 63)) as u64
+            }
+        };
+        result & mask
+    }
+}
+
+/// Padding bits  
+/// This should only be used from within structs.
+///
+/// ```yaml
+/// - { type: padding, bits: 4, value: 0 } # appends 4 padding bits
+/// - { type: padding, bits: 12, pattern: [0xAB] } # appends 0xAB, then 0xB
+/// ```
+///
+/// Allowed properties:
+///
+/// - `bits`: Number of bits to insert
+/// - `value`: Value to insert in those bits
+/// - `pattern`: Bytes to insert in those bits instead of `value`, repeated as needed
+#[derive(Debug, Deserialize, Clone)]
+pub struct GroupComponentPadding {
+    /// Number of bits to insert
+    pub bits: u8,
+    /// Value to insert in those bits
+    #[serde(default)]
+    pub value: u8,
+    /// Bytes to insert in those bits, in place of [`Self::value`] when not empty.
+    /// Each byte fills the next 8 bits, cycling back to the first byte once all are used.
+    /// The last byte is truncated to its lowest bits if fewer than 8 bits remain,
+    /// the same way [`Self::value`] is truncated to [`Self::bits`].
+    #[serde(default)]
+    pub pattern: Vec<u8>,
+}
+
+/// Skip a number of bits from a field.
+/// This should only be used from within structs.
+///
+/// ```yaml
+/// - { type: skip, field: R, bits: 4 } # skips 4 bits from the 'R' field
+/// ```
+///
+/// Allowed properties:
+///
+/// - `field`: Field name
+/// - `bits`: Number of bits to skip
+#[derive(Debug, Deserialize, Clone)]
+pub struct GroupComponentSkip {
+    /// Name of the field to skip bits from.
+    pub field: String,
+    /// Number of bits to skip from the field.
+    pub bits: u32,
+}
+
+/// Read the data from a field, once.
+/// This should only be used from within structs.
+///
+/// ```yaml
+/// - { type: field, field: R } # reads 1 'R' value from input
+/// ```
+///
+/// Allowed properties:
+///
+/// - `field`: Field name
+/// - `bits`: Number of bits to read (default: size of field)
+#[derive(Debug, Deserialize, Clone)]
+pub struct GroupComponentField {
+    /// Name of the field
+    pub field: String,
+    /// Number of bits to read from the field
+    #[serde(default)]
+    pub bits: u32,
+}
+
+impl GroupComponentField {
+    /// Assign the number of bits to read from the field.
+    /// Either keep value from [`GroupComponentField`] if manually specified, or override from the parameter.
+    pub fn set_bits(&mut self, default: u32) {
+        if self.bits == 0 {
+            self.bits = default
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Resolution of `!include` directives, which split a schema across several YAML files.
+//! See [`Schema::load_from_file`] for where they can be used.
+//!
+//! [`Schema::load_from_file`]: super::Schema::load_from_file
+
+use super::SchemaError;
+use std::path::{Path, PathBuf};
+
+/// Tag of a value which is replaced by the contents of another YAML file.
+/// See [`Schema::load_from_file`].
+///
+/// [`Schema::load_from_file`]: super::Schema::load_from_file
+const INCLUDE_TAG: &str = "include";
+
+/// Key whose (included) mapping is merged into the mapping containing it.
+const MERGE_KEY: &str = "<<";
+
+/// Resolves the `!include` directives of a YAML document relative to `dir`, returning the
+/// document with the included files inlined.
+/// Documents without includes are returned unchanged, so they are parsed exactly as before.
+///
+/// # Arguments
+/// * `content` - The YAML document.
+/// * `dir` - Directory of the file holding `content`; included paths are relative to it.
+/// * `stack` - Canonical paths of the files being included, to detect cyclic includes.
+pub(super) fn resolve_yaml_includes(
+    content: String,
+    dir: &Path,
+    mut stack: Vec<PathBuf>,
+) -> Result<String, SchemaError> {
+    let mut value = serde_yaml::from_str(&content)?;
+    match resolve_includes(&mut value, dir, &mut stack)? {
+        true => Ok(serde_yaml::to_string(&value)?),
+        false => Ok(content),
+    }
+}
+
+/// Whether a YAML value is an `!include` directive.
+fn is_include(value: &serde_yaml::Value) -> bool {
+    matches!(value, serde_yaml::Value::Tagged(tagged) if tagged.tag == INCLUDE_TAG)
+}
+
+/// Resolves the `!include` directives within a YAML value, in place.
+/// See [`Schema::load_from_file`] for where they can be used.
+///
+/// # Arguments
+/// * `value` - The value to resolve the includes of.
+/// * `dir` - Directory of the file holding `value`; included paths are relative to it.
+/// * `stack` - Canonical paths of the files currently being included, outermost first.
+///
+/// # Returns
+/// Whether any include was resolved.
+///
+/// [`Schema::load_from_file`]: super::Schema::load_from_file
+fn resolve_includes(
+    value: &mut serde_yaml::Value,
+    dir: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<bool, SchemaError> {
+    use serde_yaml::Value;
+    match value {
+        Value::Tagged(tagged) if tagged.tag == INCLUDE_TAG => {
+            let Value::String(path) = &tagged.value else {
+                return Err(SchemaError::InvalidInclude(format!(
+                    "expected a file path, got {:?}",
+                    tagged.value
+                )));
+            };
+            *value = load_include(&dir.join(path), stack)?;
+            Ok(true)
+        }
+        Value::Tagged(tagged) => resolve_includes(&mut tagged.value, dir, stack),
+        Value::Sequence(items) => {
+            let mut resolved = false;
+            let mut spliced = Vec::with_capacity(items.len());
+            for mut item in items.drain(..) {
+                let included = is_include(&item);
+                resolved |= resolve_includes(&mut item, dir, stack)?;
+                match item {
+                    // An included list is spliced into the list containing it.
+                    Value::Sequence(items) if included => spliced.extend(items),
+                    item => spliced.push(item),
+                }
+            }
+            *items = spliced;
+            Ok(resolved)
+        }
+        Value::Mapping(mapping) => {
+            let merge = mapping.get(MERGE_KEY).is_some_and(is_include);
+            let mut resolved = false;
+            for (_, item) in mapping.iter_mut() {
+                resolved |= resolve_includes(item, dir, stack)?;
+            }
+            if !merge {
+                return Ok(resolved);
+            }
+
+            // The entries of the included mapping take the place of the merge key, in order,
+            // unless the including mapping has its own entry with the same key.
+            let local = std::mem::take(mapping);
+            for (key, item) in &local {
+                if key.as_str() != Some(MERGE_KEY) {
+                    mapping.insert(key.clone(), item.clone());
+                    continue;
+                }
+
+                let Value::Mapping(included) = item else {
+                    return Err(SchemaError::InvalidInclude(format!(
+                        "`{MERGE_KEY}` must include a mapping"
+                    )));
+                };
+                for (key, item) in included {
+                    if !local.contains_key(key) {
+                        mapping.insert(key.clone(), item.clone());
+                    }
+                }
+            }
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Loads an included YAML file, resolving its own includes.
+///
+/// # Arguments
+/// * `path` - Path of the included file.
+/// * `stack` - Canonical paths of the files currently being included, outermost first.
+fn load_include(path: &Path, stack: &mut Vec<PathBuf>) -> Result<serde_yaml::Value, SchemaError> {
+    let include_error = |source| SchemaError::Include {
+        path: path.to_path_buf(),
+        source,
+    };
+    let canonical = path.canonicalize().map_err(include_error)?;
+    if stack.contains(&canonical) {
+        let chain = stack.iter().chain([&canonical]);
+        return Err(SchemaError::CyclicInclude(
+            chain.map(|path| path.display().to_string()).collect(),
+        ));
+    }
+
+    let content = std::fs::read_to_string(&canonical).map_err(include_error)?;
+    let mut value = serde_yaml::from_str(&content)?;
+    let dir = canonical.parent().unwrap_or(Path::new("")).to_path_buf();
+    stack.push(canonical);
+    resolve_includes(&mut value, &dir, stack)?;
+    stack.pop();
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{FieldDefinition, Schema, SchemaError};
+
+    /// Writes the given files to a fresh temporary directory, removed when dropped.
+    fn write_files(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("shared")).unwrap();
+        for (file, content) in files {
+            std::fs::write(dir.path().join(file), content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn resolves_includes_relative_to_the_including_file() {
+        let dir = write_files(&[
+            (
+                "schema.yaml",
+                r#"
+version: '1.0'
+root: !include shared/root.yaml
+analysis:
+  split_groups:
+    - !include shared/splits.yaml
+    - name: local
+      group_1: [colors.r]
+      group_2: [colors.r]
+"#,
+            ),
+            (
+                "shared/root.yaml",
+                r#"
+type: group
+fields:
+  header: 8
+  colors:
+    type: group
+    fields:
+      <<: !include colors.yaml
+      b: 4
+"#,
+            ),
+            ("shared/colors.yaml", "{ r: 4, g: 4, b: 8 }"),
+            (
+                "shared/splits.yaml",
+                "[{ name: shared, group_1: [colors.r], group_2: [colors.g] }]",
+            ),
+        ]);
+        let schema = Schema::load_from_file(&dir.path().join("schema.yaml")).unwrap();
+
+        // Included entries take the place of `<<`, but local ones win.
+        assert_eq!(
+            schema.ordered_field_and_group_paths(),
+            ["header", "colors", "colors.r", "colors.g", "colors.b"]
+        );
+        let Some(FieldDefinition::Group(colors)) = schema.root.fields.get("colors") else {
+            panic!("Expected colors group");
+        };
+        let Some(FieldDefinition::Field(b)) = colors.fields.get("b") else {
+            panic!("Expected b field");
+        };
+        assert_eq!(b.bits, 4);
+
+        let names: Vec<_> = schema
+            .analysis
+            .split_groups
+            .iter()
+            .map(|split| split.name.as_str())
+            .collect();
+        assert_eq!(names, ["shared", "local"]);
+    }
+
+    #[test]
+    fn rejects_cyclic_includes() {
+        let dir = write_files(&[
+            (
+                "schema.yaml",
+                "version: '1.0'\nroot: !include shared/root.yaml\n",
+            ),
+            (
+                "shared/root.yaml",
+                "type: group\nfields: { a: !include ../schema.yaml }\n",
+            ),
+        ]);
+        let result = Schema::load_from_file(&dir.path().join("schema.yaml"));
+
+        let Err(SchemaError::CyclicInclude(chain)) = result else {
+            panic!("Expected cyclic include error, got {result:?}");
+        };
+        assert_eq!(chain.len(), 3);
+        assert!(chain[0].ends_with("schema.yaml"));
+        assert!(chain[1].ends_with("root.yaml"));
+        assert_eq!(chain[2], chain[0]);
+    }
+
+    #[test]
+    fn reader_parses_the_same_schema_as_file() {
+        let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    header: 8
+    colors:
+      type: group
+      fields: { r: 4, g: 4, b: 8 }
+analysis:
+  split_groups:
+    - { name: colors, group_1: [colors.r], group_2: [colors.g] }
+"#;
+        let dir = write_files(&[("schema.yaml", yaml)]);
+        let from_file = Schema::load_from_file(&dir.path().join("schema.yaml")).unwrap();
+        let from_reader = Schema::from_reader(yaml.as_bytes()).unwrap();
+
+        assert_eq!(
+            from_reader.ordered_field_and_group_paths(),
+            from_file.ordered_field_and_group_paths()
+        );
+        assert_eq!(
+            from_reader.analysis.split_groups[0].name,
+            from_file.analysis.split_groups[0].name
+        );
+        assert_eq!(from_reader.root.byte_order, from_file.root.byte_order);
+    }
+}
@@ -21,6 +21,8 @@
 //!
 //! - [`Schema::from_yaml()`]: Parse schema from YAML string
 //! - [`Schema::load_from_file()`]: Load and parse schema from file path
+//! - [`Schema::load_yaml_from_file()`]: Load schema YAML from file path, with includes resolved
+//! - [`Schema::from_reader()`]: Read and parse schema from a reader, e.g. standard input
 //!
 //! ### Group Component Methods
 //!
@@ -36,6 +38,9 @@
 //! - [GroupComponent]: Enum representing different types of group components
 //!   - [GroupComponentArray]: Array of field values
 //!   - [GroupComponentStruct]: Structured group of components
+//!   - [GroupComponentRle]: Run-length encoded group of components
+//!   - [GroupComponentTransform]: Transformed values of a field
+//!   - [GroupComponentByteSplit]: Planes of the bytes of a field
 //!   - [GroupComponentPadding]: Padding bits
 //!   - [GroupComponentSkip]: Skip bits
 //!
@@ -43,6 +48,10 @@
 //!
 //! - [SchemaError]: Error types for schema parsing and validation
 //!
+//! ### Element Sizes
+//!
+//! - [ElementSize]: Selects the size of each element from a field in its header
+//!
 //! ## Main Components
 //!
 //! - **Schema**: The root configuration containing:
@@ -80,11 +89,38 @@
 //! let schema_from_str = Schema::from_yaml(&yaml).unwrap();
 //! ```
 
-use indexmap::IndexMap;
-use serde::Deserialize;
-use std::path::Path;
-
-use crate::analyzer::{AnalyzerFieldState, CompressionOptions};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use crate::utils::size_estimate_expression::SizeEstimateExpression;
+pub use element_size::ElementSize;
+use include::resolve_yaml_includes;
+
+mod comparison;
+mod condition;
+mod element_size;
+mod field;
+mod group_component;
+mod include;
+mod validation;
+mod value;
+
+pub(crate) use comparison::{default_entropy_multiplier, default_lz_match_multiplier};
+pub use comparison::{AutoSplit, CompressionEstimationParams, CustomComparison, SplitComparison};
+use condition::decode_value;
+pub use condition::{Condition, ConditionalOffset, Endian};
+use field::{collect_read_fields, collect_referenced_fields, propagate_byte_order};
+pub use field::{Field, FieldDefinition, Group};
+pub use group_component::{
+    ArrayEdge, GroupComponent, GroupComponentArray, GroupComponentByteSplit, GroupComponentField,
+    GroupComponentPadding, GroupComponentRle, GroupComponentSkip, GroupComponentStruct,
+    GroupComponentTransform, TransformOp,
+};
+pub(crate) use value::sign_extend;
+pub use value::{BitOrder, FixedPoint, ValueInterpretation};
 
 /// Represents the complete schema configuration for a bit-packed structure to analyze.
 ///
@@ -113,9 +149,29 @@ pub struct Schema {
     /// - `Lsb`: First bit is the low bit (0)
     #[serde(default)]
     pub bit_order: BitOrder,
+    /// Byte order of the values of fields which are multiple bytes long.
+    /// Inherited by all groups and fields, unless they set their own.
+    #[serde(default)]
+    pub byte_order: Endian,
     /// Conditional offsets for the schema
     #[serde(default)]
     pub conditional_offsets: Vec<ConditionalOffset>,
+    /// Treat the input as one continuous bitstream, where records are packed back-to-back
+    /// without padding to a byte boundary. Fields may then straddle the boundary between records.
+    ///
+    /// When enabled, data should be fed in via [`SchemaAnalyzer::add_bitstream`].
+    ///
+    /// [`SchemaAnalyzer::add_bitstream`]: crate::analyzer::SchemaAnalyzer::add_bitstream
+    #[serde(default)]
+    pub continuous: bool,
+    /// Selects the size of each element from a field in its header, for formats whose
+    /// elements vary in size. If not set, every element is [`Group::bits`] of the root long.
+    ///
+    /// When set, data should be fed in via [`SchemaAnalyzer::add_sized_entries`].
+    ///
+    /// [`SchemaAnalyzer::add_sized_entries`]: crate::analyzer::SchemaAnalyzer::add_sized_entries
+    #[serde(default)]
+    pub element_size: Option<ElementSize>,
     /// Configuration for analysis operations and output grouping
     #[serde(default)]
     pub analysis: AnalysisConfig,
@@ -126,7 +182,7 @@ pub struct Schema {
 /// Metadata about the schema
 ///
 /// Contains user-provided information about the schema's purpose and structure.
-#[derive(Clone, Debug, Deserialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct Metadata {
     /// Name of the schema
     #[serde(default)]
@@ -177,562 +233,36 @@ pub struct AnalysisConfig {
     /// ```
     #[serde(default)]
     pub compare_groups: Vec<CustomComparison>,
-}
-
-/// Parameters for estimating compression size
-#[derive(Debug, Deserialize, Clone)]
-pub struct CompressionEstimationParams {
-    /// Multiplier for LZ matches in size estimation (default: 0.375)
-    #[serde(default = "default_lz_match_multiplier")]
-    pub lz_match_multiplier: f64,
-    /// Multiplier for entropy in size estimation (default: 1.0)
-    #[serde(default = "default_entropy_multiplier")]
-    pub entropy_multiplier: f64,
-}
-
-impl CompressionEstimationParams {
-    pub fn new(options: &CompressionOptions) -> Self {
-        Self {
-            lz_match_multiplier: options.lz_match_multiplier,
-            entropy_multiplier: options.entropy_multiplier,
-        }
-    }
-}
-
-/// Configuration for comparing field groups
-#[derive(Debug, Deserialize)]
-pub struct SplitComparison {
-    /// Friendly name for this comparison.
-    pub name: String,
-    /// First group path to compare. This is the 'baseline'.
-    pub group_1: Vec<String>,
-    /// Second group path to compare. This is the group compared against the baseline (group_1).
-    pub group_2: Vec<String>,
-    /// Optional description of the comparison
-    #[serde(default)]
-    pub description: String,
-    /// Compression estimation parameters for group 1
-    #[serde(default)]
-    pub compression_estimation_group_1: Option<CompressionEstimationParams>,
-    /// Compression estimation parameters for group 2
-    #[serde(default)]
-    pub compression_estimation_group_2: Option<CompressionEstimationParams>,
-}
-
-/// Configuration for custom field group comparisons
-#[derive(Debug, Deserialize)]
-pub struct CustomComparison {
-    /// Unique identifier for this comparison
-    pub name: String,
-
-    /// Baseline group definition
-    pub baseline: Vec<GroupComponent>,
-
-    /// Comparison group definitions with names
-    pub comparisons: IndexMap<String, Vec<GroupComponent>>,
-
-    /// Human-readable description
-    #[serde(default)]
-    pub description: String,
-}
-
-pub(crate) fn default_lz_match_multiplier() -> f64 {
-    0.375
-}
-
-pub(crate) fn default_entropy_multiplier() -> f64 {
-    1.0
-}
-
-#[derive(Debug, Deserialize, Clone)]
-#[serde(tag = "type")] // Use "type" field as variant discriminant
-pub enum GroupComponent {
-    /// Array of field values
-    #[serde(rename = "array")]
-    Array(GroupComponentArray),
-
-    /// Structured group of components
-    #[serde(rename = "struct")]
-    Struct(GroupComponentStruct),
-
-    /// Padding bits.
-    /// This should only be used from within structs.
-    #[serde(rename = "padding")]
-    Padding(GroupComponentPadding),
-
-    /// Read the data from a field, once.
-    /// This should only be used from within structs.
-    #[serde(rename = "field")]
-    Field(GroupComponentField),
-
-    /// Skip a number of bits from a field.
-    /// This should only be used from within structs.
-    #[serde(rename = "skip")]
-    Skip(GroupComponentSkip),
-}
-
-/// Reads all values of a single field until end of input.
-/// i.e. `R0`, `R0`, `R0` etc. until all R0 values are read.
-///
-/// ```yaml
-/// - { type: array, field: R } # reads all 'R' values from input
-/// ```
-///
-/// This is read in a loop until no more bytes are written to output.  
-/// Alternatively, you can read only some bits at a time using the `bits` field.  
-///
-/// ```yaml
-/// - { type: array, field: R, offset: 2, bits: 4 } # read slice [2-6] for 'R' values from input
-/// ```
-///
-/// Allowed properties:
-///
-/// - `offset`: Number of bits to skip before reading `bits`.
-/// - `bits`: Number of bits to read (default: size of field)
-/// - `field`: Field name
-///
-/// The `offset` and `bits` properties allow you to read a slice of a field.
-/// Regardless of the slice read however, after each read is done, the stream will be advanced to the
-/// next field.
-///
-/// Note: The `Array` type can be represented as `Struct` technically speaking, this is
-/// actually a shorthand.
-#[derive(Debug, Deserialize, Clone)]
-pub struct GroupComponentArray {
-    /// Name of the field to pull the data from.
-    pub field: String,
-    /// Offset in the field from which to read.
-    #[serde(default)]
-    pub offset: u32,
-    /// The number of bits to read from the field.
-    #[serde(default)]
-    pub bits: u32,
-    /// Multiplier for LZ matches in size estimation
-    #[serde(default = "default_lz_match_multiplier")]
-    pub lz_match_multiplier: f64,
-    /// Multiplier for entropy in size estimation
-    #[serde(default = "default_entropy_multiplier")]
-    pub entropy_multiplier: f64,
-}
-
-impl Default for GroupComponentArray {
-    fn default() -> Self {
-        Self {
-            field: String::new(),
-            offset: 0,
-            bits: 0,
-            lz_match_multiplier: default_lz_match_multiplier(),
-            entropy_multiplier: default_entropy_multiplier(),
-        }
-    }
-}
-
-impl GroupComponentArray {
-    /// Retrieve the number of bits to read from the field.
-    /// Either directly from the [`GroupComponentArray`] or if not specified, from the [`AnalyzerFieldState`].
-    pub fn get_bits(&self, field: &AnalyzerFieldState) -> u32 {
-        if self.bits == 0 {
-            field.lenbits
-        } else {
-            self.bits
-        }
-    }
-}
-
-/// Structured group of components
-///
-/// ```yaml
-/// - type: struct # R0 G0 B0. Repeats until no data written.
-///   fields:
-///     - { type: field, field: R } # reads 1 'R' value from input
-///     - { type: field, field: G } # reads 1 'G' value from input
-///     - { type: field, field: B } # reads 1 'B' value from input
-/// ```
-///
-/// Allowed properties:
-///
-/// - `fields`: Array of field names
-#[derive(Debug, Deserialize, Clone)]
-pub struct GroupComponentStruct {
-    /// Array of field names
-    pub fields: Vec<GroupComponent>,
-    /// Multiplier for LZ matches in size estimation
-    #[serde(default = "default_lz_match_multiplier")]
-    pub lz_match_multiplier: f64,
-    /// Multiplier for entropy in size estimation
-    #[serde(default = "default_entropy_multiplier")]
-    pub entropy_multiplier: f64,
-}
-
-/// Padding bits  
-/// This should only be used from within structs.
-///
-/// ```yaml
-/// - { type: padding, bits: 4, value: 0 } # appends 4 padding bits
-/// ```
-///
-/// Allowed properties:
-///
-/// - `bits`: Number of bits to insert
-/// - `value`: Value to insert in those bits
-#[derive(Debug, Deserialize, Clone)]
-pub struct GroupComponentPadding {
-    /// Number of bits to insert
-    pub bits: u8,
-    /// Value to insert in those bits
-    #[serde(default)]
-    pub value: u8,
-}
-
-/// Skip a number of bits from a field.
-/// This should only be used from within structs.
-///
-/// ```yaml
-/// - { type: skip, field: R, bits: 4 } # skips 4 bits from the 'R' field
-/// ```
-///
-/// Allowed properties:
-///
-/// - `field`: Field name
-/// - `bits`: Number of bits to skip
-#[derive(Debug, Deserialize, Clone)]
-pub struct GroupComponentSkip {
-    /// Name of the field to skip bits from.
-    pub field: String,
-    /// Number of bits to skip from the field.
-    pub bits: u32,
-}
-
-/// Read the data from a field, once.
-/// This should only be used from within structs.
-///
-/// ```yaml
-/// - { type: field, field: R } # reads 1 'R' value from input
-/// ```
-///
-/// Allowed properties:
-///
-/// - `field`: Field name
-/// - `bits`: Number of bits to read (default: size of field)
-#[derive(Debug, Deserialize, Clone)]
-pub struct GroupComponentField {
-    /// Name of the field
-    pub field: String,
-    /// Number of bits to read from the field
-    #[serde(default)]
-    pub bits: u32,
-}
-
-impl GroupComponentField {
-    /// Assign the number of bits to read from the field.
-    /// Either keep value from [`GroupComponentField`] if manually specified, or override from the parameter.
-    pub fn set_bits(&mut self, default: u32) {
-        if self.bits == 0 {
-            self.bits = default
-        }
-    }
-}
-
-/// Allows us to define a nested item as either a field or group
-#[derive(Debug, Deserialize)]
-#[serde(untagged)]
-#[non_exhaustive]
-pub enum FieldDefinition {
-    Field(Field),
-    Group(Group),
-}
-
-/// A single field definition
-#[derive(Debug)]
-pub struct Field {
-    pub bits: u32,
-    pub description: String,
-    pub bit_order: BitOrder,
-    pub skip_if_not: Vec<Condition>,
-    pub skip_frequency_analysis: bool,
-}
-
-impl<'de> Deserialize<'de> for Field {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        #[derive(Deserialize)]
-        #[serde(untagged)]
-        enum FieldRepr {
-            Shorthand(u32),
-            Extended {
-                bits: u32,
-                #[serde(default)]
-                description: String,
-                #[serde(default)]
-                #[serde(rename = "bit_order")]
-                bit_order: BitOrder,
-                #[serde(default)]
-                skip_if_not: Vec<Condition>,
-                #[serde(default)]
-                skip_frequency_analysis: bool,
-            },
-        }
-
-        // The magic that allows for either shorthand or extended notation
-        match FieldRepr::deserialize(deserializer)? {
-            FieldRepr::Shorthand(size) => Ok(Field {
-                bits: size,
-                description: String::new(),
-                bit_order: BitOrder::default(),
-                skip_if_not: Vec::new(),
-                skip_frequency_analysis: false,
-            }),
-            FieldRepr::Extended {
-                bits,
-                description,
-                bit_order,
-                skip_if_not,
-                skip_frequency_analysis,
-            } => Ok(Field {
-                bits,
-                description,
-                bit_order,
-                skip_if_not,
-                skip_frequency_analysis,
-            }),
-        }
-    }
-}
-
-/// Group of related fields or components
-///
-/// Represents a logical grouping of fields in the bit-packed structure.
-/// Groups can contain both individual fields and nested sub-groups.
-///
-/// # Fields
-/// - `_type`: Must be "group" (validated during parsing)
-/// - `description`: Optional description of the group's purpose
-/// - `fields`: Map of field names to their definitions (fields or sub-groups)
-///
-/// # Examples
-/// ```yaml
-/// root:
-///   type: group
-///   description: Main structure
-///   fields:
-///     header:
-///       type: group
-///       fields:
-///         mode: 2
-///         partition: 4
-///     colors:
-///       type: group
-///       fields:
-///         r:
-///           type: group
-///           fields:
-///             R0: 5
-///             R1: 5
-/// ```
-#[derive(Debug, Default)]
-pub struct Group {
-    _type: String,
-    pub description: String,
-    pub fields: IndexMap<String, FieldDefinition>,
-    /// Total bits calculated from children fields/groups
-    pub bits: u32,
-    /// The bit order of this group.
-    /// Inherited by all the children unless explicitly overwritten.
-    pub bit_order: BitOrder,
-    pub skip_if_not: Vec<Condition>,
-    pub skip_frequency_analysis: bool,
-}
-
-impl<'de> Deserialize<'de> for Group {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        #[derive(Deserialize)]
-        struct GroupRepr {
-            #[serde(rename = "type")]
-            _type: String,
-            #[serde(default)]
-            description: String,
-            #[serde(default)]
-            bit_order: BitOrder,
-            #[serde(default)]
-            fields: IndexMap<String, FieldDefinition>,
-            #[serde(default)]
-            skip_if_not: Vec<Condition>,
-            #[serde(default)]
-            skip_frequency_analysis: bool,
-        }
-
-        let group = GroupRepr::deserialize(deserializer)?;
-        if group._type != "group" {
-            return Err(serde::de::Error::custom(format!(
-                "Invalid group type: {} (must be 'group')",
-                group._type
-            )));
-        }
-
-        // Calculate total bits from children
-        // This is recursive. Deserialize of child would have calculated this for the child.
-        let bits = group
-            .fields
-            .values()
-            .map(|fd| match fd {
-                FieldDefinition::Field(f) => f.bits,
-                FieldDefinition::Group(g) => g.bits,
-            })
-            .sum();
-
-        // Create the group with its own bit_order
-        let mut group = Group {
-            _type: group._type,
-            description: group.description,
-            fields: group.fields,
-            bits,
-            bit_order: group.bit_order,
-            skip_if_not: group.skip_if_not,
-            skip_frequency_analysis: group.skip_frequency_analysis,
-        };
-
-        // Propagate bit_order to children if not explicitly set
-        let bit_order = group.bit_order;
-        propagate_bit_order(&mut group, bit_order);
-
-        Ok(group)
-    }
-}
-
-impl Group {
-    /// Collects a list of field paths in schema order
-    /// This includes both fields and groups
-    fn collect_field_paths(&self, paths: &mut Vec<String>, parent_path: &str) {
-        for (name, item) in &self.fields {
-            match item {
-                FieldDefinition::Field(_) => {
-                    let full_path = if parent_path.is_empty() {
-                        name
-                    } else {
-                        &format!("{}.{}", parent_path, name)
-                    };
-                    paths.push(full_path.clone());
-                }
-                FieldDefinition::Group(g) => {
-                    let new_parent = if parent_path.is_empty() {
-                        name
-                    } else {
-                        &format!("{}.{}", parent_path, name)
-                    };
-                    paths.push(new_parent.clone());
-                    g.collect_field_paths(paths, new_parent);
-                }
-            }
-        }
-    }
-}
-
-/// Bit ordering specification for field values
-///
-/// Determines how bits are interpreted within a field:
-/// - `Msb`: Most significant bit first (default)
-/// - `Lsb`: Least significant bit first
-///
-/// # Examples
-///
-/// ```yaml
-/// bit_order: msb  # Default, bits are read left-to-right
-/// bit_order: lsb  # Bits are read right-to-left
-/// ```
-#[derive(Debug, Deserialize, Default, PartialEq, Eq, Clone, Copy)]
-#[serde(rename_all = "snake_case")]
-pub enum BitOrder {
-    /// Not initialized. If not set down the road, defaults to [Msb](BitOrder::Msb)
-    #[default]
-    Default,
-    Msb,
-    Lsb,
-}
-
-impl BitOrder {
-    pub fn get_with_default_resolve(self) -> BitOrder {
-        if self == BitOrder::Default {
-            BitOrder::Msb
-        } else {
-            self
-        }
-    }
-}
-
-/// Recursively propagates bit_order to child fields and groups
-fn propagate_bit_order(group: &mut Group, parent_bit_order: BitOrder) {
-    for (_, field_def) in group.fields.iter_mut() {
-        match field_def {
-            FieldDefinition::Field(field) => {
-                // Only inherit if field has default bit_order
-                if field.bit_order == BitOrder::Default {
-                    field.bit_order = parent_bit_order;
-                }
-            }
-            FieldDefinition::Group(child_group) => {
-                // Only inherit if child group has default bit_order
-                if child_group.bit_order == BitOrder::Default {
-                    child_group.bit_order = parent_bit_order;
-                }
-                // Recursively propagate to nested groups
-                propagate_bit_order(child_group, child_group.bit_order);
-            }
-        }
-    }
-}
 
-/// Defines a single condition for offset selection
-///
-/// # Examples
-///
-/// ```yaml
-/// byte_offset: 0x00
-/// bit_offset: 0
-/// bits: 32
-/// value: 0x44445320  # DDS magic
-/// ```
-#[derive(Debug, PartialEq, Clone, serde::Deserialize)]
-pub struct Condition {
-    /// Byte offset from start of structure
-    pub byte_offset: u64,
-    /// Bit offset within the byte (0-7, left to right)
-    pub bit_offset: u8,
-    /// Number of bits to compare (1-32)
-    pub bits: u8,
-    /// Expected value in big-endian byte order
-    pub value: u64,
-    /// Bit order of the condition
+    /// Fields to automatically search the best split of. Each field is split at every
+    /// combination of bit boundaries, and each part is grouped separately (like an `array`
+    /// per part); the layouts are then ranked by their zstd size.
+    ///
+    /// # Example
+    /// ```yaml
+    /// auto_split:
+    ///   - field: color        # a 32-bit field
+    ///     granularity: 8      # try splitting at byte boundaries (default: 8)
+    ///     max_splits: 2       # into at most 3 parts (default: 2)
+    /// ```
+    ///
+    /// See [`crate::comparison::auto_split`] for how the layouts are enumerated.
     #[serde(default)]
-    pub bit_order: BitOrder,
-}
+    pub auto_split: Vec<AutoSplit>,
 
-/// Defines conditional offset selection rules
-///
-/// # Examples
-///
-/// ```yaml
-/// - offset: 0x94  # BC7 data offset
-///   conditions:
-///     - byte_offset: 0x00
-///       bit_offset: 0
-///       bits: 32
-///       value: 0x44445320
-///     - byte_offset: 0x54
-///       bit_offset: 0
-///       bits: 32
-///       value: 0x44583130
-/// ```
-#[derive(Debug, Clone, Deserialize)]
-pub struct ConditionalOffset {
-    /// Target offset to use if conditions match
-    pub offset: u64,
-    /// List of conditions that must all be satisfied
-    pub conditions: Vec<Condition>,
+    /// Formula used to estimate the compressed size of groups, in bytes.
+    /// When not specified, the default estimator is used.
+    ///
+    /// See [`SizeEstimateExpression`] for the supported syntax and variables.
+    ///
+    /// # Example
+    /// ```yaml
+    /// size_estimate: "entropy * len / 8 - lz * 0.3"
+    /// ```
+    ///
+    /// `size_estimator` is accepted as an alias.
+    #[serde(default, alias = "size_estimator")]
+    pub size_estimate: Option<SizeEstimateExpression>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -745,6 +275,44 @@ pub enum SchemaError {
     Io(#[from] std::io::Error),
     #[error("Invalid group type: {0} (must be 'group')")]
     InvalidGroupType(String),
+    #[error("Duplicate field path(s) in schema: {}", .0.join(", "))]
+    DuplicateFieldPaths(Vec<String>),
+    #[error("Comparison '{comparison}' uses reserved field '{field}'. Reserved fields can only be skipped in comparisons.")]
+    ReservedFieldInComparison { comparison: String, field: String },
+    #[error("Split comparison '{comparison}' {reason}")]
+    InvalidSplitComparison {
+        comparison: String,
+        reason: &'static str,
+    },
+    #[error("`element_size` {0}")]
+    InvalidElementSize(&'static str),
+    #[error("Condition at byte offset {byte_offset:#X} {reason}")]
+    InvalidCondition {
+        byte_offset: u64,
+        reason: &'static str,
+    },
+    #[error("Failed to include '{}': {source}", .path.display())]
+    Include {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid `!include`: {0}")]
+    InvalidInclude(String),
+    #[error("Cyclic `!include`: {}", .0.join(" -> "))]
+    CyclicInclude(Vec<String>),
+}
+
+/// A problem found by [`Schema::validate`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum SchemaValidationError {
+    #[error(
+        "Comparison '{comparison}' references field '{field}', which doesn't exist in the schema"
+    )]
+    UnknownField { comparison: String, field: String },
+    #[error("Split comparison '{comparison}' has groups of different sizes (in bits): {bits:?}")]
+    SplitGroupSizeMismatch { comparison: String, bits: Vec<u32> },
+    #[error("Comparison name '{0}' is used more than once")]
+    DuplicateComparisonName(String),
 }
 
 impl Schema {
@@ -756,25 +324,74 @@ impl Schema {
     /// # Returns
     /// * `Result<Self, SchemaError>` - Resulting schema or error
     pub fn from_yaml(content: &str) -> Result<Self, SchemaError> {
-        let schema: Schema = serde_yaml::from_str(content)?;
+        let mut schema: Schema = serde_yaml::from_str(content)?;
+        let byte_order = *schema.root.byte_order.get_or_insert(schema.byte_order);
+        propagate_byte_order(&mut schema.root, Some(byte_order));
 
         if schema.version != "1.0" {
             return Err(SchemaError::InvalidVersion);
         }
 
+        schema.validate_unique_field_paths()?;
+        schema.validate_split_comparisons()?;
+        schema.validate_reserved_fields_not_compared()?;
+        schema.validate_conditions()?;
+        schema.validate_element_size()?;
         Ok(schema)
     }
 
     /// Loads and parses a schema from a YAML file.
     ///
+    /// Before parsing, `!include path.yaml` directives are replaced by the contents of the
+    /// given file, relative to the file containing the directive. An include can be:
+    ///
+    /// - Any value, e.g. `fields: !include colors.yaml`.
+    /// - A list item; an included list is spliced into the list containing it.
+    /// - The value of a `<<` key; the included mapping is merged into the mapping containing
+    ///   it, in place of the key. Entries of the including mapping take precedence.
+    ///
+    /// Includes may be nested; cyclic includes are rejected with [`SchemaError::CyclicInclude`].
+    /// Anchors and aliases work within each file as usual, but not across files.
+    ///
     /// # Arguments
     /// * `path` - Path to the schema YAML file
     ///
     /// # Returns
     /// * `Result<Self, SchemaError>` - Resulting schema or error
     pub fn load_from_file(path: &Path) -> Result<Self, SchemaError> {
+        Self::from_yaml(&Self::load_yaml_from_file(path)?)
+    }
+
+    /// Reads the YAML of a schema file with its `!include` directives resolved
+    /// (see [`Self::load_from_file`]), so it can be parsed by [`Self::from_yaml`] without the
+    /// included files, e.g. after being saved alongside the results.
+    /// Schemas without includes are returned unchanged.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the schema YAML file
+    ///
+    /// # Returns
+    /// * `Result<String, SchemaError>` - Resolved schema YAML or error
+    pub fn load_yaml_from_file(path: &Path) -> Result<String, SchemaError> {
         let content = std::fs::read_to_string(path)?;
-        Self::from_yaml(&content)
+        let dir = path.parent().unwrap_or(Path::new(""));
+        resolve_yaml_includes(content, dir, vec![path.canonicalize()?])
+    }
+
+    /// Reads and parses a schema from YAML, e.g. piped in through standard input.
+    ///
+    /// `!include` directives (see [`Self::load_from_file`]) are resolved relative to the
+    /// current directory.
+    ///
+    /// # Arguments
+    /// * `reader` - Source of the schema YAML
+    ///
+    /// # Returns
+    /// * `Result<Self, SchemaError>` - Resulting schema or error
+    pub fn from_reader(mut reader: impl Read) -> Result<Self, SchemaError> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+        Self::from_yaml(&resolve_yaml_includes(content, Path::new(""), Vec::new())?)
     }
 
     /// Collects a list of field (and group) paths in schema order.
@@ -1129,6 +746,40 @@ bit_order: msb
             });
         }
 
+        #[test]
+        fn inherits_byte_order_from_schema_and_parent() {
+            let yaml = r#"
+version: '1.0'
+byte_order: little
+root:
+    type: group
+    fields:
+        a: 16
+        b: { type: field, bits: 16, byte_order: big }
+        subgroup:
+            type: group
+            byte_order: big
+            fields:
+                c: 16
+                d: { type: field, bits: 16, byte_order: little }
+"#;
+            test_schema!(yaml, |schema: Schema| {
+                let byte_order = |group: &Group, name: &str| match group.fields.get(name) {
+                    Some(FieldDefinition::Field(f)) => f.byte_order,
+                    Some(FieldDefinition::Group(g)) => g.byte_order,
+                    None => panic!("Expected {name}"),
+                };
+                assert_eq!(byte_order(&schema.root, "a"), Some(Endian::Little));
+                assert_eq!(byte_order(&schema.root, "b"), Some(Endian::Big));
+                let Some(FieldDefinition::Group(subgroup)) = schema.root.fields.get("subgroup")
+                else {
+                    panic!("Expected subgroup");
+                };
+                assert_eq!(byte_order(subgroup, "c"), Some(Endian::Big));
+                assert_eq!(byte_order(subgroup, "d"), Some(Endian::Little));
+            });
+        }
+
         #[test]
         fn uses_default_bit_order_when_not_specified() {
             let yaml = r#"
@@ -1178,10 +829,45 @@ root: { type: group, fields: {} }
 "#;
             test_schema!(yaml, |schema: Schema| {
                 assert!(schema.analysis.split_groups.is_empty());
+                assert!(schema.analysis.size_estimate.is_none());
             });
         }
     }
 
+    mod size_estimate_tests {
+        use super::*;
+
+        #[test]
+        fn parses_size_estimate_expression() {
+            let yaml = r#"
+version: '1.0'
+analysis:
+  size_estimate: "entropy*len/8 + lz*0.3"
+root: { type: group, fields: {} }
+"#;
+            test_schema!(yaml, |schema: Schema| {
+                let expression = schema.analysis.size_estimate.unwrap();
+                assert_eq!(expression.source(), "entropy*len/8 + lz*0.3");
+            });
+
+            let yaml = yaml.replace("size_estimate:", "size_estimator:");
+            test_schema!(&yaml, |schema: Schema| {
+                assert!(schema.analysis.size_estimate.is_some());
+            });
+        }
+
+        #[test]
+        fn rejects_invalid_size_estimate_expression() {
+            let yaml = r#"
+version: '1.0'
+analysis:
+  size_estimate: "entropy * bytes"
+root: { type: group, fields: {} }
+"#;
+            assert!(Schema::from_yaml(yaml).is_err());
+        }
+    }
+
     // Conditional Offset Tests
     mod conditional_offset_tests {
         use super::*;
@@ -1286,6 +972,151 @@ bit_order: msb
             assert_eq!(magic_field.skip_if_not[0].value, 0x44583130);
             assert_eq!(schema.bit_order, BitOrder::Msb);
         }
+
+        #[test]
+        fn supports_skip_if_conditions() {
+            let yaml = r#"
+version: '1.0'
+metadata:
+  name: Minimal Schema
+root:
+  type: group
+  fields:
+    header:
+      type: group
+      skip_if:
+        - byte_offset: 0x00
+          bit_offset: 0
+          bits: 32
+          value: 0x44445320
+      fields:
+        magic:
+          type: field
+          bits: 32
+          skip_if:
+            - byte_offset: 0x54
+              bit_offset: 0
+              bits: 32
+              value: 0x44583130
+          skip_if_not:
+            - byte_offset: 0x00
+              bit_offset: 0
+              bits: 8
+              value: 0x44
+bit_order: msb
+"#;
+
+            let schema = Schema::from_yaml(yaml).unwrap();
+            let header_group = match &schema.root.fields["header"] {
+                FieldDefinition::Field(_field) => panic!("Expected group, got field"),
+                FieldDefinition::Group(group) => group,
+            };
+            let magic_field = match &header_group.fields["magic"] {
+                FieldDefinition::Field(field) => field,
+                FieldDefinition::Group(_group) => panic!("Expected field, got group"),
+            };
+
+            // Test group-level conditions
+            assert_eq!(header_group.skip_if.len(), 1);
+            assert!(header_group.skip_if_not.is_empty());
+            assert_eq!(header_group.skip_if[0].byte_offset, 0x00);
+            assert_eq!(header_group.skip_if[0].value, 0x44445320);
+
+            // Test field-level conditions, alongside `skip_if_not`
+            assert_eq!(magic_field.skip_if.len(), 1);
+            assert_eq!(magic_field.skip_if[0].byte_offset, 0x54);
+            assert_eq!(magic_field.skip_if[0].value, 0x44583130);
+            assert_eq!(magic_field.skip_if_not.len(), 1);
+            assert_eq!(magic_field.skip_if_not[0].value, 0x44);
+        }
+
+        #[test]
+        fn little_endian_conditions_must_compare_whole_bytes() {
+            let schema = |bits: u8| {
+                format!(
+                    r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    magic:
+      type: field
+      bits: 32
+      skip_if_not:
+        - {{ byte_offset: 0, bit_offset: 0, bits: {bits}, value: 0x4444, endian: little }}
+"#
+                )
+            };
+
+            let parsed = Schema::from_yaml(&schema(16)).unwrap();
+            let FieldDefinition::Field(magic) = &parsed.root.fields["magic"] else {
+                panic!("Expected field, got group");
+            };
+            assert_eq!(magic.skip_if_not[0].endian, Endian::Little);
+            assert!(matches!(
+                Schema::from_yaml(&schema(12)),
+                Err(SchemaError::InvalidCondition { byte_offset: 0, .. })
+            ));
+        }
+    }
+
+    mod validate_tests {
+        use super::*;
+
+        #[test]
+        fn valid_schema_has_no_problems() {
+            let yaml = r#"
+version: '1.0'
+root: { type: group, fields: { colors: { type: group, fields: { r: 4, g: 4 } } } }
+analysis:
+  split_groups:
+    - { name: split, group_1: [colors], group_2: [r, g] }
+  compare_groups:
+    - name: custom
+      baseline: [{ type: array, field: r }]
+      comparisons:
+        skip: [{ type: struct, fields: [{ type: skip, field: g, bits: 4 }, { type: field, field: r }] }]
+"#;
+            assert_eq!(Schema::from_yaml(yaml).unwrap().validate(), Ok(()));
+        }
+
+        #[test]
+        fn collects_all_problems() {
+            let yaml = r#"
+version: '1.0'
+root: { type: group, fields: { r: 4, g: 4, b: 8 } }
+analysis:
+  split_groups:
+    - { name: typo, group_1: [r, g], group_2: [gg, r] }
+    - { name: sizes, group_1: [r, g], group_2: [b, r] }
+  compare_groups:
+    - name: typo
+      baseline: [{ type: array, field: bb }]
+      comparisons:
+        skip: [{ type: struct, fields: [{ type: skip, field: x, bits: 4 }] }]
+  auto_split:
+    - field: rgb
+"#;
+            let errors = Schema::from_yaml(yaml).unwrap().validate().unwrap_err();
+            let unknown = |comparison: &str, field: &str| SchemaValidationError::UnknownField {
+                comparison: comparison.to_string(),
+                field: field.to_string(),
+            };
+            assert_eq!(
+                errors,
+                vec![
+                    unknown("typo", "gg"),
+                    unknown("typo", "bb"),
+                    unknown("typo", "x"),
+                    unknown("auto_split", "rgb"),
+                    SchemaValidationError::SplitGroupSizeMismatch {
+                        comparison: "sizes".to_string(),
+                        bits: vec![8, 12],
+                    },
+                    SchemaValidationError::DuplicateComparisonName("typo".to_string()),
+                ]
+            );
+        }
     }
 
     mod split_compare_tests {
@@ -1452,6 +1283,7 @@ root:
                         GroupComponent::Padding(padding) => {
                             assert_eq!(padding.bits, 1);
                             assert_eq!(padding.value, 0);
+                            assert!(padding.pattern.is_empty());
                         }
                         _ => unreachable!("Expected a padding type"),
                     }
@@ -1547,4 +1379,189 @@ root:
             assert!(comparisons[0].description.is_empty());
         }
     }
+
+    mod duplicate_path_tests {
+        use super::*;
+
+        #[test]
+        fn rejects_identically_named_sibling_fields() {
+            let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    r: 5
+    r: 6
+"#;
+            let err = Schema::from_yaml(yaml).unwrap_err();
+            assert!(matches!(err, SchemaError::YamlError(_)));
+            assert!(err.to_string().contains("Duplicate field name 'r'"));
+        }
+
+        #[test]
+        fn rejects_identically_named_fields_in_nested_group() {
+            let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    colors:
+      type: group
+      fields:
+        r: 5
+        r: 6
+"#;
+            let err = Schema::from_yaml(yaml).unwrap_err();
+            assert!(matches!(err, SchemaError::YamlError(_)));
+        }
+
+        #[test]
+        fn rejects_colliding_nested_paths() {
+            let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    colors.r: 5
+    colors:
+      type: group
+      fields:
+        r: 5
+        g: 6
+"#;
+            match Schema::from_yaml(yaml) {
+                Err(SchemaError::DuplicateFieldPaths(paths)) => {
+                    assert_eq!(paths, vec!["colors.r".to_string()])
+                }
+                other => panic!("Expected duplicate field paths, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn allows_same_name_in_different_groups() {
+            let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    color0:
+      type: group
+      fields:
+        r: 5
+    color1:
+      type: group
+      fields:
+        r: 5
+"#;
+            assert!(Schema::from_yaml(yaml).is_ok());
+        }
+    }
+
+    mod interpretation_tests {
+        use super::*;
+
+        #[test]
+        fn renders_known_half_float_bit_patterns() {
+            let float16 = ValueInterpretation::Float16;
+            assert_eq!(float16.format_value(0x3C00, 16), "1");
+            assert_eq!(float16.format_value(0xC000, 16), "-2");
+            assert_eq!(float16.format_value(0x3800, 16), "0.5");
+            assert_eq!(float16.format_value(0x7BFF, 16), "65504");
+            assert_eq!(float16.format_value(0x0000, 16), "0");
+            assert_eq!(float16.format_value(0x0001, 16), "0.000000059604645");
+            assert_eq!(float16.format_value(0x7C00, 16), "inf");
+            assert_eq!(float16.format_value(0xFC00, 16), "-inf");
+            assert_eq!(float16.format_value(0x7E00, 16), "NaN");
+        }
+
+        #[test]
+        fn renders_signed_and_fixed_point_values() {
+            assert_eq!(ValueInterpretation::Uint.format_value(0b1111, 4), "15");
+            assert_eq!(ValueInterpretation::Sint.format_value(0b1111, 4), "-1");
+            assert_eq!(ValueInterpretation::Sint.format_value(0b0111, 4), "7");
+            assert_eq!(ValueInterpretation::Fixed(8).format_value(0x180, 16), "1.5");
+            assert_eq!(ValueInterpretation::Fixed(0).format_value(3, 16), "3");
+        }
+
+        #[test]
+        fn parses_interpretation_of_fields() {
+            let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    plain: 8
+    delta: { type: field, bits: 8, interpretation: sint }
+    height: { type: field, bits: 16, interpretation: float16 }
+    position: { type: field, bits: 16, interpretation: fixed(4) }
+    offset: { type: field, bits: 4, signed: true }
+    scale: { type: field, bits: 16, fixed_point: { integer_bits: 8, fraction_bits: 8 } }
+"#;
+            test_schema!(yaml, |schema: Schema| {
+                let interpretation = |name: &str| match &schema.root.fields[name] {
+                    FieldDefinition::Field(field) => field.interpretation,
+                    _ => panic!("Expected field"),
+                };
+                assert_eq!(interpretation("plain"), ValueInterpretation::Uint);
+                assert_eq!(interpretation("delta"), ValueInterpretation::Sint);
+                assert_eq!(interpretation("height"), ValueInterpretation::Float16);
+                assert_eq!(interpretation("position"), ValueInterpretation::Fixed(4));
+                assert_eq!(interpretation("offset"), ValueInterpretation::Sint);
+                assert_eq!(interpretation("scale"), ValueInterpretation::Fixed(8));
+            });
+        }
+
+        #[test]
+        fn rejects_invalid_interpretations() {
+            assert!("fixed(x)".parse::<ValueInterpretation>().is_err());
+            assert!("float32".parse::<ValueInterpretation>().is_err());
+
+            let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    height: { type: field, bits: 8, interpretation: float16 }
+"#;
+            assert!(Schema::from_yaml(yaml).is_err());
+
+            let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    delta: { type: field, bits: 8, signed: true, interpretation: fixed(4) }
+"#;
+            assert!(Schema::from_yaml(yaml).is_err());
+
+            // Q4.4 doesn't fit a 16 bit field.
+            let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    scale: { type: field, bits: 16, fixed_point: { integer_bits: 4, fraction_bits: 4 } }
+"#;
+            assert!(Schema::from_yaml(yaml).is_err());
+
+            let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    scale: { type: field, bits: 8, fixed_point: { integer_bits: 4, fraction_bits: 4 }, signed: true }
+"#;
+            assert!(Schema::from_yaml(yaml).is_err());
+
+            // The total number of bits overflows.
+            let yaml = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    scale: { type: field, bits: 8, fixed_point: { integer_bits: 4294967295, fraction_bits: 9 } }
+"#;
+            assert!(Schema::from_yaml(yaml).is_err());
+        }
+    }
 }
@@ -0,0 +1,211 @@
+//! Validation of schemas, on load and on request.
+
+use super::{
+    collect_read_fields, collect_referenced_fields, Condition, Endian, Schema, SchemaError,
+    SchemaValidationError,
+};
+use ahash::{AHashMap, AHashSet};
+
+impl Schema {
+    /// Ensures that little endian conditions (of conditional offsets, `skip_if_not` and `skip_if`)
+    /// compare whole bytes, as their bytes are swapped.
+    pub(super) fn validate_conditions(&self) -> Result<(), SchemaError> {
+        let mut conditions: Vec<&Condition> = self
+            .conditional_offsets
+            .iter()
+            .flat_map(|offset| &offset.conditions)
+            .collect();
+        self.root.collect_conditions(&mut conditions);
+
+        match conditions
+            .iter()
+            .find(|c| c.endian == Endian::Little && !c.bits.is_multiple_of(8))
+        {
+            Some(condition) => Err(SchemaError::InvalidCondition {
+                byte_offset: condition.byte_offset,
+                reason: "must compare a multiple of 8 bits to be little endian",
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Ensures that no two fields (or groups) resolve to the same full path,
+    /// e.g. a field named `colors.r` next to a `colors` group with an `r` field.
+    ///
+    /// Results are keyed by path, so such fields would otherwise overwrite one another.
+    pub(super) fn validate_unique_field_paths(&self) -> Result<(), SchemaError> {
+        let mut seen = AHashSet::new();
+        let mut duplicates = Vec::new();
+        for path in self.ordered_field_and_group_paths() {
+            if !seen.insert(path.clone()) && !duplicates.contains(&path) {
+                duplicates.push(path);
+            }
+        }
+
+        if duplicates.is_empty() {
+            Ok(())
+        } else {
+            Err(SchemaError::DuplicateFieldPaths(duplicates))
+        }
+    }
+
+    /// Ensures that each split comparison uses either `group_1` and `group_2`, or `groups`.
+    pub(super) fn validate_split_comparisons(&self) -> Result<(), SchemaError> {
+        for split in &self.analysis.split_groups {
+            let reason = if !split.is_multi_group() {
+                if split.group_1.is_empty() || split.group_2.is_empty() {
+                    "needs both `group_1` and `group_2`, or `groups`"
+                } else {
+                    continue;
+                }
+            } else if !split.group_1.is_empty() || !split.group_2.is_empty() {
+                "can't have both `groups` and `group_1`/`group_2`"
+            } else if split.groups.len() < 2 {
+                "needs at least 2 `groups`"
+            } else if split.compression_estimation_group_1.is_some()
+                || split.compression_estimation_group_2.is_some()
+            {
+                "can't set `compression_estimation_group_1`/`compression_estimation_group_2` with `groups`"
+            } else if split.normalize_length {
+                "can't set `normalize_length` with `groups`"
+            } else {
+                continue;
+            };
+
+            return Err(SchemaError::InvalidSplitComparison {
+                comparison: split.name.clone(),
+                reason,
+            });
+        }
+        Ok(())
+    }
+
+    /// Ensures that no comparison reads a reserved field, so they stay excluded from comparisons.
+    /// Skipping a reserved field (`skip` component) is allowed.
+    pub(super) fn validate_reserved_fields_not_compared(&self) -> Result<(), SchemaError> {
+        let mut reserved = Vec::new();
+        self.root.collect_reserved_fields(&mut reserved);
+        if reserved.is_empty() {
+            return Ok(());
+        }
+
+        let error = |comparison: &str, field: &str| SchemaError::ReservedFieldInComparison {
+            comparison: comparison.to_string(),
+            field: field.to_string(),
+        };
+        let analysis = &self.analysis;
+        for split in &analysis.split_groups {
+            if let Some(field) = split
+                .all_groups()
+                .flatten()
+                .find(|field| reserved.contains(&field.as_str()))
+            {
+                return Err(error(&split.name, field));
+            }
+        }
+
+        for custom in &analysis.compare_groups {
+            let mut fields = Vec::new();
+            for components in core::iter::once(&custom.baseline).chain(custom.comparisons.values())
+            {
+                collect_read_fields(components, &mut fields);
+            }
+            if let Some(field) = fields.into_iter().find(|field| reserved.contains(field)) {
+                return Err(error(&custom.name, field));
+            }
+        }
+
+        for auto_split in &analysis.auto_split {
+            if reserved.contains(&auto_split.field.as_str()) {
+                return Err(error("auto_split", &auto_split.field));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the schema for mistakes which would otherwise only surface during (or after)
+    /// analysis, collecting all of them rather than stopping at the first:
+    ///
+    /// - Every field referenced by `split_groups`, `compare_groups` and `auto_split` exists.
+    /// - The groups of each split comparison add up to the same number of bits.
+    /// - No two comparisons (split or custom) share a name.
+    ///
+    /// Problems which make the schema unusable are already rejected by [`Self::from_yaml`].
+    /// `skip_if_not` and `skip_if` conditions refer to bit offsets, not fields,
+    /// so they're not checked here.
+    ///
+    /// # Returns
+    /// * `Result<(), Vec<SchemaValidationError>>` - All problems found
+    pub fn validate(&self) -> Result<(), Vec<SchemaValidationError>> {
+        let mut field_bits = AHashMap::new();
+        self.root.collect_field_bits(&mut field_bits);
+
+        let mut errors = Vec::new();
+        let mut check_fields = |comparison: &str, fields: Vec<&str>| {
+            let unknown: Vec<_> = fields
+                .into_iter()
+                .filter(|field| !field_bits.contains_key(field))
+                .map(|field| SchemaValidationError::UnknownField {
+                    comparison: comparison.to_string(),
+                    field: field.to_string(),
+                })
+                .collect();
+            let all_exist = unknown.is_empty();
+            errors.extend(unknown);
+            all_exist
+        };
+
+        let analysis = &self.analysis;
+        let mut split_errors = Vec::new();
+        for split in &analysis.split_groups {
+            let fields = split.all_groups().flatten().map(String::as_str).collect();
+            // Sizes of groups with unknown fields are meaningless.
+            if !check_fields(&split.name, fields) {
+                continue;
+            }
+
+            let bits: Vec<u32> = split
+                .all_groups()
+                .map(|group| group.iter().map(|field| field_bits[field.as_str()]).sum())
+                .collect();
+            if bits.windows(2).any(|pair| pair[0] != pair[1]) {
+                split_errors.push(SchemaValidationError::SplitGroupSizeMismatch {
+                    comparison: split.name.clone(),
+                    bits,
+                });
+            }
+        }
+
+        for custom in &analysis.compare_groups {
+            let mut fields = Vec::new();
+            for components in core::iter::once(&custom.baseline).chain(custom.comparisons.values())
+            {
+                collect_referenced_fields(components, &mut fields);
+            }
+            check_fields(&custom.name, fields);
+        }
+
+        for auto_split in &analysis.auto_split {
+            check_fields("auto_split", vec![auto_split.field.as_str()]);
+        }
+
+        errors.extend(split_errors);
+        let mut seen = AHashSet::new();
+        let names = analysis
+            .split_groups
+            .iter()
+            .map(|split| &split.name)
+            .chain(analysis.compare_groups.iter().map(|custom| &custom.name));
+        for name in names {
+            let duplicate = SchemaValidationError::DuplicateComparisonName(name.clone());
+            if !seen.insert(name) && !errors.contains(&duplicate) {
+                errors.push(duplicate);
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+}
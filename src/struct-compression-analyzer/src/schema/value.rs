@@ -0,0 +1,192 @@
+//! Bit order and interpretation of the values of fields.
+
+use serde::{Deserialize, Serialize};
+
+/// Bit ordering specification for field values
+///
+/// Determines how bits are interpreted within a field:
+/// - `Msb`: Most significant bit first (default)
+/// - `Lsb`: Least significant bit first
+///
+/// # Examples
+///
+/// ```yaml
+/// bit_order: msb  # Default, bits are read left-to-right
+/// bit_order: lsb  # Bits are read right-to-left
+/// ```
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum BitOrder {
+    /// Not initialized. If not set down the road, defaults to [Msb](BitOrder::Msb)
+    #[default]
+    Default,
+    Msb,
+    Lsb,
+}
+
+impl BitOrder {
+    pub fn get_with_default_resolve(self) -> BitOrder {
+        if self == BitOrder::Default {
+            BitOrder::Msb
+        } else {
+            self
+        }
+    }
+}
+
+/// How the raw value of a field is rendered in value stats (printed output and CSV).
+///
+/// This is purely presentational; all comparisons use the raw bits of the field.
+///
+/// # Examples
+///
+/// ```yaml
+/// interpretation: uint     # Default, unsigned integer
+/// interpretation: sint     # Two's complement signed integer, of the field's bit count
+/// interpretation: float16  # IEEE 754 half precision float (16 bit fields only)
+/// interpretation: fixed(8) # Unsigned fixed point number with 8 fractional bits
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum ValueInterpretation {
+    /// Unsigned integer.
+    #[default]
+    Uint,
+    /// Two's complement signed integer.
+    Sint,
+    /// IEEE 754 half precision (binary16) float.
+    Float16,
+    /// Unsigned fixed point number with the given number of fractional bits.
+    Fixed(u8),
+}
+
+impl ValueInterpretation {
+    /// Renders a raw value of a field with the given number of bits.
+    pub fn format_value(self, value: u64, bits: u32) -> String {
+        match self {
+            ValueInterpretation::Uint => value.to_string(),
+            ValueInterpretation::Sint => sign_extend(value, bits).to_string(),
+            ValueInterpretation::Float16 => half_to_f32(value as u16).to_string(),
+            ValueInterpretation::Fixed(fraction_bits) => {
+                (value as f64 / 2f64.powi(fraction_bits as i32)).to_string()
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for ValueInterpretation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "uint" => Ok(ValueInterpretation::Uint),
+            "sint" => Ok(ValueInterpretation::Sint),
+            "float16" => Ok(ValueInterpretation::Float16),
+            other => other
+                .strip_prefix("fixed(")
+                .and_then(|rest| rest.strip_suffix(')'))
+                .and_then(|fraction_bits| fraction_bits.trim().parse::<u8>().ok())
+                .filter(|fraction_bits| *fraction_bits <= 64)
+                .map(ValueInterpretation::Fixed)
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid interpretation '{}', expected 'uint', 'sint', 'float16' or 'fixed(q)'",
+                        other
+                    )
+                }),
+        }
+    }
+}
+
+impl core::fmt::Display for ValueInterpretation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ValueInterpretation::Uint => write!(f, "uint"),
+            ValueInterpretation::Sint => write!(f, "sint"),
+            ValueInterpretation::Float16 => write!(f, "float16"),
+            ValueInterpretation::Fixed(fraction_bits) => write!(f, "fixed({})", fraction_bits),
+        }
+    }
+}
+
+impl Serialize for ValueInterpretation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ValueInterpretation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Layout of an unsigned fixed point field, in Q notation (e.g. Q4.4 or Q8.8).
+///
+/// Equivalent to `interpretation: fixed(fraction_bits)`, but also checks the layout against
+/// the size of the field.
+///
+/// # Examples
+///
+/// ```yaml
+/// scale: { type: field, bits: 16, fixed_point: { integer_bits: 8, fraction_bits: 8 } }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FixedPoint {
+    /// Number of bits before the binary point.
+    pub integer_bits: u32,
+    /// Number of bits after the binary point.
+    pub fraction_bits: u8,
+}
+
+impl FixedPoint {
+    /// Returns the [`ValueInterpretation`] rendering values with this layout,
+    /// or an error if it doesn't cover exactly the `bits` of the field.
+    pub fn interpretation(self, bits: u32) -> Result<ValueInterpretation, String> {
+        if self.integer_bits.checked_add(self.fraction_bits as u32) != Some(bits) {
+            return Err(format!(
+                "A Q{}.{} 'fixed_point' field needs {} bits, but the field has {} bits",
+                self.integer_bits,
+                self.fraction_bits,
+                self.integer_bits as u64 + self.fraction_bits as u64,
+                bits
+            ));
+        }
+
+        Ok(ValueInterpretation::Fixed(self.fraction_bits))
+    }
+}
+
+/// Interprets the lowest `bits` bits of `value` as a two's complement number.
+pub(crate) fn sign_extend(value: u64, bits: u32) -> i64 {
+    if bits == 0 || bits >= 64 {
+        return value as i64;
+    }
+
+    let shift = 64 - bits;
+    ((value << shift) as i64) >> shift
+}
+
+/// Converts an IEEE 754 half precision float to a single precision float.
+fn half_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1F) as i32;
+    let mantissa = (half & 0x3FF) as f32;
+
+    match exponent {
+        // Zero and subnormals
+        0 => sign * mantissa * 2f32.powi(-24),
+        // Infinity and NaN
+        0x1F if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1F => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
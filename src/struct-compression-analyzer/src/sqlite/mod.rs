@@ -0,0 +1,395 @@
+//! Exports merged analysis results to an SQLite database, for querying results across many runs.
+//!
+//! Only available with the `sqlite` cargo feature, which builds SQLite (via [`rusqlite`]).
+//!
+//! The tables are created if they don't exist yet, any rows previously written under the
+//! same run label are replaced, and the results of the run are inserted, all in a single
+//! transaction. The database can then be queried with any SQLite client, e.g. the `sqlite3` CLI:
+//!
+//! ```text
+//! sqlite3 results.db "SELECT run_label, zstd_size FROM per_field WHERE full_path = 'colors'"
+//! ```
+//!
+//! # Tables
+//!
+//! - `run`: One row per run, keyed by the run label.
+//! - `per_field`: Merged metrics of each field and group.
+//! - `split_comparison`: Merged results of each split comparison.
+//! - `custom_comparison`: Merged results of each custom comparison.
+//! - `custom_comparison_group`: Metrics of each group of a custom comparison,
+//!   with the baseline as `group_index` 0.
+//!
+//! All tables other than `run` have a `run_label` column referencing `run.label`.
+//! Estimated sizes are `NULL` for groups which have no estimate, as are non-finite
+//! (e.g. NaN) values.
+
+use crate::comparison::GroupComparisonMetrics;
+use crate::csv::ordered_field_paths;
+use crate::results::merged_analysis_results::MergedAnalysisResults;
+use crate::schema::Schema;
+use rusqlite::{params, Connection, Transaction};
+use std::path::Path;
+
+/// Statements creating the tables, if they don't exist yet.
+const CREATE_TABLES: &str = "\
+CREATE TABLE IF NOT EXISTS run (
+    label TEXT PRIMARY KEY,
+    schema_name TEXT NOT NULL,
+    file_count INTEGER NOT NULL,
+    file_entropy REAL,
+    file_lz_matches INTEGER NOT NULL,
+    zstd_file_size INTEGER NOT NULL,
+    original_size INTEGER NOT NULL,
+    ground_truth TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS per_field (
+    run_label TEXT NOT NULL REFERENCES run(label),
+    full_path TEXT NOT NULL,
+    name TEXT NOT NULL,
+    depth INTEGER NOT NULL,
+    count INTEGER NOT NULL,
+    lenbits INTEGER NOT NULL,
+    entropy REAL,
+    lz_matches INTEGER NOT NULL,
+    zstd_size INTEGER NOT NULL,
+    original_size INTEGER NOT NULL,
+    PRIMARY KEY (run_label, full_path)
+);
+CREATE TABLE IF NOT EXISTS split_comparison (
+    run_label TEXT NOT NULL REFERENCES run(label),
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    group1_lz_matches INTEGER NOT NULL,
+    group1_entropy REAL,
+    group1_estimated_size INTEGER,
+    group1_zstd_size INTEGER NOT NULL,
+    group1_original_size INTEGER NOT NULL,
+    group2_lz_matches INTEGER NOT NULL,
+    group2_entropy REAL,
+    group2_estimated_size INTEGER,
+    group2_zstd_size INTEGER NOT NULL,
+    group2_original_size INTEGER NOT NULL,
+    zstd_bytes_saved INTEGER NOT NULL,
+    estimate_agreement_percentage REAL,
+    estimate_false_positive_percentage REAL,
+    estimate_correct_positive_percentage REAL,
+    PRIMARY KEY (run_label, name)
+);
+CREATE TABLE IF NOT EXISTS custom_comparison (
+    run_label TEXT NOT NULL REFERENCES run(label),
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    zstd_bytes_saved INTEGER NOT NULL,
+    estimate_agreement_percentage REAL,
+    PRIMARY KEY (run_label, name)
+);
+CREATE TABLE IF NOT EXISTS custom_comparison_group (
+    run_label TEXT NOT NULL REFERENCES run(label),
+    comparison TEXT NOT NULL,
+    group_index INTEGER NOT NULL,
+    group_name TEXT NOT NULL,
+    lz_matches INTEGER NOT NULL,
+    entropy REAL,
+    estimated_size INTEGER,
+    zstd_size INTEGER NOT NULL,
+    original_size INTEGER NOT NULL,
+    PRIMARY KEY (run_label, comparison, group_index)
+);
+";
+
+/// Tables holding the results of a run, i.e. which reference the `run` table.
+const RUN_TABLES: [&str; 4] = [
+    "per_field",
+    "split_comparison",
+    "custom_comparison",
+    "custom_comparison_group",
+];
+
+/// Writes the merged results of a run to an SQLite database, creating it if needed.
+///
+/// # Arguments
+///
+/// * `merged_results` - The merged results of all files in the run.
+/// * `schema` - The schema used for the analysis; fields are written in schema order.
+/// * `run_label` - Label identifying the run. Rows previously written with the same label are replaced.
+/// * `database_path` - The path of the SQLite database.
+///
+/// # Returns
+///
+/// * `rusqlite::Result<()>` - Ok if successful, otherwise an error.
+pub fn write_sqlite(
+    merged_results: &MergedAnalysisResults,
+    schema: &Schema,
+    run_label: &str,
+    database_path: &Path,
+) -> rusqlite::Result<()> {
+    let mut connection = Connection::open(database_path)?;
+    write_sqlite_to(&mut connection, merged_results, schema, run_label)
+}
+
+/// Writes the merged results of a run to an open SQLite database.
+/// See [`write_sqlite`] for details.
+pub fn write_sqlite_to(
+    connection: &mut Connection,
+    merged_results: &MergedAnalysisResults,
+    schema: &Schema,
+    run_label: &str,
+) -> rusqlite::Result<()> {
+    let transaction = connection.transaction()?;
+    transaction.execute_batch(CREATE_TABLES)?;
+
+    // Replace any previous results of this run.
+    for table in RUN_TABLES {
+        transaction.execute(
+            &format!("DELETE FROM {} WHERE run_label = ?1", table),
+            [run_label],
+        )?;
+    }
+    transaction.execute("DELETE FROM run WHERE label = ?1", [run_label])?;
+
+    transaction.execute(
+        "INSERT INTO run VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            run_label,
+            merged_results.schema_metadata.name,
+            merged_results.merged_file_count,
+            real(merged_results.file_entropy),
+            merged_results.file_lz_matches,
+            merged_results.zstd_file_size,
+            merged_results.original_size,
+            merged_results.ground_truth.name(),
+        ],
+    )?;
+
+    for path in ordered_field_paths(schema, &merged_results.per_field) {
+        let field = &merged_results.per_field[path];
+        transaction.execute(
+            "INSERT INTO per_field VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                run_label,
+                field.full_path,
+                field.name,
+                field.depth,
+                field.count,
+                field.lenbits,
+                real(field.entropy),
+                field.lz_matches,
+                field.zstd_size,
+                field.original_size,
+            ],
+        )?;
+    }
+
+    for comparison in &merged_results.split_comparisons {
+        let (group1, group2) = (&comparison.group1_metrics, &comparison.group2_metrics);
+        transaction.execute(
+            "INSERT INTO split_comparison VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, \
+             ?12, ?13, ?14, ?15, ?16, ?17)",
+            params![
+                run_label,
+                comparison.name,
+                comparison.description,
+                group1.lz_matches,
+                real(group1.entropy),
+                estimated_size(group1),
+                group1.zstd_size,
+                group1.original_size,
+                group2.lz_matches,
+                real(group2.entropy),
+                estimated_size(group2),
+                group2.zstd_size,
+                group2.original_size,
+                comparison.zstd_bytes_saved,
+                real(comparison.group_estimate_zstd_agreement_percentage),
+                real(comparison.group_estimate_false_positive_percentage),
+                real(comparison.group_estimate_correct_positive_percentage),
+            ],
+        )?;
+    }
+
+    for comparison in &merged_results.custom_comparisons {
+        transaction.execute(
+            "INSERT INTO custom_comparison VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                run_label,
+                comparison.name,
+                comparison.description,
+                comparison.zstd_bytes_saved,
+                real(comparison.estimate_zstd_agreement_percentage * 100.0),
+            ],
+        )?;
+
+        let groups = core::iter::once(("baseline", &comparison.baseline_metrics)).chain(
+            comparison
+                .group_names
+                .iter()
+                .map(String::as_str)
+                .zip(&comparison.group_metrics),
+        );
+        for (group_index, (group_name, metrics)) in groups.enumerate() {
+            insert_custom_group(
+                &transaction,
+                run_label,
+                &comparison.name,
+                group_index,
+                group_name,
+                metrics,
+            )?;
+        }
+    }
+
+    transaction.commit()
+}
+
+/// Inserts the metrics of a group of a custom comparison.
+fn insert_custom_group(
+    transaction: &Transaction,
+    run_label: &str,
+    comparison: &str,
+    group_index: usize,
+    group_name: &str,
+    metrics: &GroupComparisonMetrics,
+) -> rusqlite::Result<()> {
+    transaction.execute(
+        "INSERT INTO custom_comparison_group VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            run_label,
+            comparison,
+            group_index,
+            group_name,
+            metrics.lz_matches,
+            real(metrics.entropy),
+            estimated_size(metrics),
+            metrics.zstd_size,
+            metrics.original_size,
+        ],
+    )?;
+    Ok(())
+}
+
+/// The estimated size of a group, or `NULL` if it has no estimate.
+fn estimated_size(metrics: &GroupComparisonMetrics) -> Option<u64> {
+    metrics.has_estimate.then_some(metrics.estimated_size)
+}
+
+/// A float as an SQL value; SQL has no representation for NaN or infinity,
+/// so those are written as `NULL`.
+fn real(value: f64) -> Option<f64> {
+    value.is_finite().then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_entries, CompressionOptions};
+
+    const SCHEMA: &str = r#"
+version: '1.0'
+metadata: { name: "Test's Schema" }
+root:
+  type: group
+  fields:
+    a: 8
+    b: 8
+analysis:
+  split_groups:
+    - name: a_b
+      group_1: [a, b]
+      group_2: [b, a]
+  compare_groups:
+    - name: swap
+      baseline:
+        - { type: array, field: a }
+        - { type: array, field: b }
+      comparisons:
+        swapped:
+          - { type: array, field: b }
+          - { type: array, field: a }
+"#;
+
+    fn write(connection: &mut Connection, run_label: &str) {
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let entries = (0..64u8).map(|x| [x, x.wrapping_mul(3)]);
+        let results = vec![analyze_entries(
+            &schema,
+            CompressionOptions::default(),
+            entries,
+        )];
+        let merged = MergedAnalysisResults::from_results(&results).unwrap();
+        write_sqlite_to(connection, &merged, &schema, run_label).unwrap();
+    }
+
+    fn count(connection: &Connection, table: &str, run_label: &str) -> usize {
+        let label_column = if table == "run" { "label" } else { "run_label" };
+        connection
+            .query_row(
+                &format!("SELECT COUNT(*) FROM {table} WHERE {label_column} = ?1"),
+                [run_label],
+                |row| row.get(0),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn writes_a_row_per_result() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        write(&mut connection, "run 1");
+
+        assert_eq!(count(&connection, "run", "run 1"), 1);
+        assert_eq!(count(&connection, "per_field", "run 1"), 2);
+        assert_eq!(count(&connection, "split_comparison", "run 1"), 1);
+        assert_eq!(count(&connection, "custom_comparison", "run 1"), 1);
+        assert_eq!(count(&connection, "custom_comparison_group", "run 1"), 2);
+
+        let (schema_name, file_count): (String, u64) = connection
+            .query_row("SELECT schema_name, file_count FROM run", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(schema_name, "Test's Schema");
+        assert_eq!(file_count, 1);
+        let groups: Vec<String> = connection
+            .prepare("SELECT group_name FROM custom_comparison_group ORDER BY group_index")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(groups, ["baseline", "swapped"]);
+    }
+
+    #[test]
+    fn replaces_previous_rows_of_the_same_run() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        write(&mut connection, "it's");
+        write(&mut connection, "it's");
+        write(&mut connection, "other");
+
+        for table in RUN_TABLES.into_iter().chain(["run"]) {
+            assert_eq!(
+                count(&connection, table, "it's"),
+                count(&connection, table, "other"),
+                "{table}"
+            );
+        }
+        assert_eq!(count(&connection, "run", "it's"), 1);
+    }
+
+    #[test]
+    fn writes_missing_values_as_null() {
+        assert_eq!(real(1.0), Some(1.0));
+        assert_eq!(real(f64::NAN), None);
+        assert_eq!(real(f64::INFINITY), None);
+
+        let metrics = GroupComparisonMetrics {
+            estimated_size: 0,
+            ..Default::default()
+        };
+        assert_eq!(estimated_size(&metrics), None);
+        let metrics = GroupComparisonMetrics {
+            has_estimate: true,
+            ..metrics
+        };
+        assert_eq!(estimated_size(&metrics), Some(0));
+    }
+}
@@ -8,8 +8,12 @@
 //! # Core Functions
 //!
 //! - [`size_estimate`]: Estimates compressed data size based on LZ matches and entropy
+//! - [`size_estimate_breakdown`]: Same as [`size_estimate`], with the intermediate terms
 //! - [`get_zstd_compressed_size`]: Calculates actual compressed size using zstandard
+//! - [`get_zstd_compressed_size_with_strategy`]: Same, with an explicit [`ZstdStrategy`]
 //! - [`calculate_file_entropy`]: Computes Shannon entropy of input data
+//! - [`calculate_file_entropy_order1`]: Computes the entropy of each byte given the previous byte
+//! - [`get_entropy_coded_size`]: Calculates the size of the data coded with an order-0 range coder
 //! - [`reverse_bits`]: Reverses bits in a u64 value
 //!
 //! # Bitstream Utilities
@@ -24,13 +28,16 @@
 //!
 //! - [`BitReaderContainer`]: Wrapper around bit readers supporting both endians
 //! - [`BitWriterContainer`]: Wrapper around bit writers supporting both endians
+//! - [`ZstdStrategy`]: The zstd match finding strategy used when compressing
 
 use crate::{analyzer::SizeEstimationParameters, schema::BitOrder};
 use bitstream_io::{BigEndian, BitRead, BitReader, BitWrite, BitWriter, LittleEndian};
+use derive_more::FromStr;
 use lossless_transform_utils::{
     entropy::code_length_of_histogram32,
     histogram::{histogram32_from_bytes, Histogram32},
 };
+use serde::{Deserialize, Serialize};
 use std::io::{self, Cursor, SeekFrom};
 
 /// Estimate size of a compressed data based on precalculated LZ matches and entropy
@@ -49,12 +56,43 @@ use std::io::{self, Cursor, SeekFrom};
 /// This is a rough estimation based on very limited testing on DXT1, only, you'll want to
 /// replace this function with something more suitable for your use case, possibly.
 pub fn size_estimate(params: SizeEstimationParameters) -> usize {
+    size_estimate_breakdown(params).entropy_bytes as usize
+}
+
+/// The intermediate terms of the default [`size_estimate`] function.
+///
+/// Useful for debugging why an estimate diverges from the actual zstd size.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeEstimateBreakdown {
+    /// Bytes removed by the LZ term (`num_lz_matches * lz_match_multiplier`).
+    pub lz_savings: u64,
+    /// Bytes remaining after the LZ term.
+    pub bytes_after_lz: u64,
+    /// Size after applying the entropy term to the bytes remaining after LZ.
+    /// This is the final estimate.
+    pub entropy_bytes: u64,
+}
+
+/// Estimates the size of compressed data exactly like [`size_estimate`],
+/// but returns the intermediate terms alongside the final estimate.
+///
+/// # Arguments
+///
+/// * `params` - [`SizeEstimationParameters`], see [`size_estimate`].
+pub fn size_estimate_breakdown(params: SizeEstimationParameters) -> SizeEstimateBreakdown {
     // Calculate expected bytes after LZ
-    let bytes_after_lz =
-        params.data_len - (params.num_lz_matches as f64 * params.lz_match_multiplier) as usize;
+    let lz_savings = (params.num_lz_matches as f64 * params.lz_match_multiplier) as usize;
+    let bytes_after_lz = params.data_len.saturating_sub(lz_savings);
 
     // Calculate expected bits and convert to bytes
-    (bytes_after_lz as f64 * params.entropy * params.entropy_multiplier).ceil() as usize / 8
+    let entropy_bytes =
+        (bytes_after_lz as f64 * params.entropy * params.entropy_multiplier).ceil() as usize / 8;
+
+    SizeEstimateBreakdown {
+        lz_savings: (params.data_len - bytes_after_lz) as u64,
+        bytes_after_lz: bytes_after_lz as u64,
+        entropy_bytes: entropy_bytes as u64,
+    }
 }
 
 /// Determines the actual size of the compressed data by compressing with a realistic compressor.
@@ -65,6 +103,71 @@ pub fn get_zstd_compressed_size(data: &[u8], level: i32) -> u64 {
         .unwrap() as u64
 }
 
+/// Determines the actual size of the compressed data, using a specific zstd strategy.
+///
+/// With [`ZstdStrategy::Default`] this is identical to [`get_zstd_compressed_size`].
+pub fn get_zstd_compressed_size_with_strategy(
+    data: &[u8],
+    level: i32,
+    strategy: ZstdStrategy,
+) -> u64 {
+    let Some(strategy) = strategy.to_zstd() else {
+        return get_zstd_compressed_size(data, level);
+    };
+
+    let mut compressor = zstd::bulk::Compressor::new(level).unwrap();
+    compressor
+        .set_parameter(zstd::zstd_safe::CParameter::Strategy(strategy))
+        .unwrap();
+    compressor
+        .compress(data)
+        .ok()
+        .map(|compressed| compressed.len())
+        .unwrap() as u64
+}
+
+/// The match finding strategy used by zstd.
+///
+/// Each compression level implies a strategy; setting one explicitly overrides
+/// the strategy of the level, while keeping its other parameters (window size, etc.).
+/// Strategies are listed from fastest (weakest) to slowest (strongest).
+///
+/// Parsed case-insensitively from the variant name, e.g. `dfast` or `btultra2`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, FromStr)]
+pub enum ZstdStrategy {
+    /// Use the strategy implied by the compression level.
+    #[default]
+    Default,
+    Fast,
+    DFast,
+    Greedy,
+    Lazy,
+    Lazy2,
+    BtLazy2,
+    BtOpt,
+    BtUltra,
+    BtUltra2,
+}
+
+impl ZstdStrategy {
+    /// The zstd parameter for this strategy; `None` for [`ZstdStrategy::Default`].
+    pub(crate) fn to_zstd(self) -> Option<zstd::zstd_safe::Strategy> {
+        use zstd::zstd_safe::Strategy;
+        match self {
+            ZstdStrategy::Default => None,
+            ZstdStrategy::Fast => Some(Strategy::ZSTD_fast),
+            ZstdStrategy::DFast => Some(Strategy::ZSTD_dfast),
+            ZstdStrategy::Greedy => Some(Strategy::ZSTD_greedy),
+            ZstdStrategy::Lazy => Some(Strategy::ZSTD_lazy),
+            ZstdStrategy::Lazy2 => Some(Strategy::ZSTD_lazy2),
+            ZstdStrategy::BtLazy2 => Some(Strategy::ZSTD_btlazy2),
+            ZstdStrategy::BtOpt => Some(Strategy::ZSTD_btopt),
+            ZstdStrategy::BtUltra => Some(Strategy::ZSTD_btultra),
+            ZstdStrategy::BtUltra2 => Some(Strategy::ZSTD_btultra2),
+        }
+    }
+}
+
 /// Calculates the entropy of a given input
 pub fn calculate_file_entropy(bytes: &[u8]) -> f64 {
     let mut histogram = Histogram32::default();
@@ -72,6 +175,145 @@ pub fn calculate_file_entropy(bytes: &[u8]) -> f64 {
     code_length_of_histogram32(&histogram, bytes.len() as u64)
 }
 
+/// Calculates the order-1 (conditional) entropy of a given input, H(X<sub>n</sub> | X<sub>n-1</sub>),
+/// in bits per byte: the entropy of each byte given the byte before it.
+///
+/// This is at most the order-0 entropy of [`calculate_file_entropy`], and lower when consecutive
+/// bytes are correlated; so it's a tighter floor for what a context modelling coder can achieve.
+/// The first byte has no previous byte, so it's not counted. Inputs of fewer than 2 bytes
+/// have an entropy of 0.
+pub fn calculate_file_entropy_order1(bytes: &[u8]) -> f64 {
+    if bytes.len() < 2 {
+        return 0.0;
+    }
+
+    // Occurrences of each (previous byte, byte) pair, and of each previous byte.
+    let mut pair_counts = vec![0u32; 256 * 256];
+    let mut context_counts = [0u32; 256];
+    for pair in bytes.windows(2) {
+        pair_counts[(pair[0] as usize) << 8 | pair[1] as usize] += 1;
+        context_counts[pair[0] as usize] += 1;
+    }
+
+    // Sum of -log2(P(byte | previous byte)) over all pairs, averaged per byte.
+    let mut total_bits = 0.0;
+    for (context, symbol_counts) in pair_counts.chunks_exact(256).enumerate() {
+        let context_count = context_counts[context] as f64;
+        for &count in symbol_counts.iter().filter(|&&count| count > 0) {
+            let count = count as f64;
+            total_bits -= count * (count / context_count).log2();
+        }
+    }
+
+    total_bits / (bytes.len() - 1) as f64
+}
+
+/// Calculates the entropy of each consecutive window of `window_bytes` bytes of the input.
+/// The last window is shorter if the input is not a multiple of `window_bytes`.
+pub fn calculate_windowed_entropy(bytes: &[u8], window_bytes: usize) -> Vec<f64> {
+    bytes
+        .chunks(window_bytes.max(1))
+        .map(calculate_file_entropy)
+        .collect()
+}
+
+/// Total of the scaled symbol frequencies used by [`get_entropy_coded_size`].
+/// Must be small enough that `range / total` keeps enough precision after renormalization.
+const RANGE_CODER_FREQUENCY_BITS: u32 = 15;
+
+/// Determines the size of the data after coding it with an order-0 (static) range coder,
+/// using the observed byte frequencies of the data.
+///
+/// Unlike zstd, this has no LZ stage, so it measures only what entropy coding alone achieves;
+/// this is close to the Shannon limit ([`calculate_file_entropy`] * length) plus the overhead of
+/// a real coder (rounded frequencies, flushing). The frequency table is not included, as it
+/// depends on how it would be stored.
+///
+/// The data is encoded as it would be, but only the number of output bytes is counted.
+pub fn get_entropy_coded_size(data: &[u8]) -> u64 {
+    if data.is_empty() {
+        return 0;
+    }
+
+    let mut histogram = Histogram32::default();
+    histogram32_from_bytes(data, &mut histogram);
+
+    // Scale the frequencies down to a fixed total; symbols which occur keep a frequency of at least 1.
+    let len = data.len() as u64;
+    let mut frequencies = [0u32; 256];
+    let mut cumulative = [0u32; 256];
+    let mut total = 0u32;
+    for (symbol, &count) in histogram.inner.counter.iter().enumerate() {
+        if count != 0 {
+            let scaled = (count as u64 * (1 << RANGE_CODER_FREQUENCY_BITS)) / len;
+            frequencies[symbol] = (scaled as u32).max(1);
+        }
+        cumulative[symbol] = total;
+        total += frequencies[symbol];
+    }
+
+    let mut encoder = RangeEncoderSize::default();
+    for &byte in data {
+        encoder.encode(cumulative[byte as usize], frequencies[byte as usize], total);
+    }
+    encoder.finish()
+}
+
+/// An LZMA style range encoder (with carry propagation), which counts its output bytes
+/// instead of writing them.
+struct RangeEncoderSize {
+    low: u64,
+    range: u32,
+    /// Number of bytes pending output, i.e. the cached byte and any `0xFF` bytes after it,
+    /// which can still be changed by a carry.
+    pending: u64,
+    written: u64,
+}
+
+impl Default for RangeEncoderSize {
+    fn default() -> Self {
+        Self {
+            low: 0,
+            range: u32::MAX,
+            pending: 1,
+            written: 0,
+        }
+    }
+}
+
+impl RangeEncoderSize {
+    const TOP: u32 = 1 << 24;
+
+    #[inline]
+    fn encode(&mut self, cumulative: u32, frequency: u32, total: u32) {
+        let step = self.range / total;
+        self.low += cumulative as u64 * step as u64;
+        self.range = frequency * step;
+        while self.range < Self::TOP {
+            self.range <<= 8;
+            self.shift_low();
+        }
+    }
+
+    #[inline]
+    fn shift_low(&mut self) {
+        // Bytes are only final once no carry can reach them.
+        if (self.low as u32) < 0xFF00_0000 || (self.low >> 32) != 0 {
+            self.written += self.pending;
+            self.pending = 0;
+        }
+        self.pending += 1;
+        self.low = (self.low & 0x00FF_FFFF) << 8;
+    }
+
+    fn finish(mut self) -> u64 {
+        for _ in 0..5 {
+            self.shift_low();
+        }
+        self.written
+    }
+}
+
 /// Reverses the bits of a u64 value
 /// # Arguments
 /// * `max_bits` - The number of bits to reverse
@@ -238,4 +480,72 @@ mod tests {
         let compressed_size = get_zstd_compressed_size(data, 16);
         assert!(compressed_size < data.len() as u64);
     }
+
+    #[test]
+    fn zstd_strategy_affects_compressed_size() {
+        // Repeats at varying distances, which the weaker match finders miss.
+        let data: Vec<u8> = (0..64 * 1024u32)
+            .map(|x| (x.wrapping_mul(2654435761) >> 24) as u8 ^ (x % 251) as u8)
+            .chain((0..64 * 1024u32).map(|x| (x.wrapping_mul(2654435761) >> 24) as u8))
+            .collect();
+
+        let fast = get_zstd_compressed_size_with_strategy(&data, 16, ZstdStrategy::Fast);
+        let btultra2 = get_zstd_compressed_size_with_strategy(&data, 16, ZstdStrategy::BtUltra2);
+        assert_ne!(fast, btultra2);
+        assert_eq!(
+            get_zstd_compressed_size_with_strategy(&data, 16, ZstdStrategy::Default),
+            get_zstd_compressed_size(&data, 16)
+        );
+    }
+
+    #[test]
+    fn order1_entropy_captures_correlation_between_bytes() {
+        // Every value is equally likely, but fully determined by the previous one.
+        let cycle: Vec<u8> = (0..=255u8).cycle().take(64 * 1024).collect();
+        assert!((calculate_file_entropy(&cycle) - 8.0).abs() < 1e-9);
+        assert_eq!(calculate_file_entropy_order1(&cycle), 0.0);
+
+        // Unrelated bytes: each previous byte is followed by both values equally often.
+        let pairs: Vec<u8> = [0u8, 0, 1, 1].iter().copied().cycle().take(4096).collect();
+        assert!((calculate_file_entropy(&pairs) - 1.0).abs() < 1e-9);
+        assert!((calculate_file_entropy_order1(&pairs) - 1.0).abs() < 1e-3);
+
+        assert_eq!(calculate_file_entropy_order1(&[7]), 0.0);
+    }
+
+    #[test]
+    fn entropy_coded_size_is_close_to_entropy() {
+        // Skewed distribution: mostly zeroes, some small values.
+        let data: Vec<u8> = (0..64 * 1024u32)
+            .map(|x| match x.wrapping_mul(2654435761) >> 28 {
+                0..=11 => 0,
+                n => n as u8,
+            })
+            .collect();
+
+        let shannon_limit = calculate_file_entropy(&data) * data.len() as f64 / 8.0;
+        let coded = get_entropy_coded_size(&data) as f64;
+        assert!(coded >= shannon_limit.floor());
+        assert!(coded <= shannon_limit * 1.01 + 8.0);
+    }
+
+    #[test]
+    fn entropy_coded_size_of_trivial_data() {
+        assert_eq!(get_entropy_coded_size(&[]), 0);
+        // A single symbol costs (next to) nothing but the flush.
+        assert!(get_entropy_coded_size(&[7; 4096]) <= 6);
+    }
+
+    #[test]
+    fn parses_zstd_strategy_case_insensitively() {
+        assert_eq!(
+            "dfast".parse::<ZstdStrategy>().ok(),
+            Some(ZstdStrategy::DFast)
+        );
+        assert_eq!(
+            "btultra2".parse::<ZstdStrategy>().ok(),
+            Some(ZstdStrategy::BtUltra2)
+        );
+        assert!("ultra".parse::<ZstdStrategy>().is_err());
+    }
 }
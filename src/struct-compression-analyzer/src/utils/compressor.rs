@@ -0,0 +1,355 @@
+//! The compressor used to measure the 'actual' compressed size of groups.
+//!
+//! Zstd sizes ([`GroupComparisonMetrics::zstd_size`]) are always measured, as the size
+//! estimator and brute forcing its coefficients are based on them. Data which ships compressed
+//! with another compressor can additionally be measured with that compressor, by setting
+//! [`CompressionOptions::compressor`]:
+//!
+//! - [`Compressor::Zstd`]: Only zstd, the default.
+//! - [`Compressor::Brotli`]: Also Brotli, stored in [`GroupComparisonMetrics::brotli_size`].
+//! - [`Compressor::Deflate`]: Also Deflate (gzip), stored in
+//!   [`GroupComparisonMetrics::deflate_size`].
+//!
+//! To check the size estimator against Brotli or Deflate sizes instead of zstd sizes when merging
+//! results, use [`GroundTruth::Brotli`] or [`GroundTruth::Deflate`].
+//!
+//! [`GroupComparisonMetrics::zstd_size`]: crate::comparison::GroupComparisonMetrics::zstd_size
+//! [`GroupComparisonMetrics::brotli_size`]: crate::comparison::GroupComparisonMetrics::brotli_size
+//! [`GroupComparisonMetrics::deflate_size`]: crate::comparison::GroupComparisonMetrics::deflate_size
+//! [`CompressionOptions::compressor`]: crate::analyzer::CompressionOptions::compressor
+//! [`GroundTruth::Brotli`]: crate::comparison::GroundTruth::Brotli
+//! [`GroundTruth::Deflate`]: crate::comparison::GroundTruth::Deflate
+
+use brotli::enc::BrotliEncoderParams;
+use flate2::{write::DeflateEncoder, Compression};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Default Brotli quality, the strongest.
+pub const DEFAULT_BROTLI_QUALITY: u32 = 11;
+
+/// Default Brotli window size (log2), the default of the reference encoder.
+pub const DEFAULT_BROTLI_WINDOW: u32 = 22;
+
+/// Default Deflate level, the strongest.
+pub const DEFAULT_DEFLATE_LEVEL: u32 = 9;
+
+/// Size of the header and trailer a gzip file wraps its Deflate stream in, without optional
+/// fields such as the file name. Add this to [`GroupComparisonMetrics::deflate_size`] for the size
+/// of a `.gz` file or a `Content-Encoding: gzip` response.
+///
+/// [`GroupComparisonMetrics::deflate_size`]: crate::comparison::GroupComparisonMetrics::deflate_size
+pub const GZIP_OVERHEAD: u64 = 18;
+
+/// The compressor measuring the actual size of groups, besides zstd.
+///
+/// Parsed from `zstd`, `brotli` (quality 11, window 22), `brotli:<quality>:<window>`,
+/// `deflate` (level 9) or `deflate:<level>`, case-insensitively. `gzip` is accepted in place
+/// of `deflate`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Compressor {
+    /// Zstd, with the configured level and strategy
+    /// ([`CompressionOptions::zstd_compression_level`], [`CompressionOptions::zstd_strategy`]).
+    ///
+    /// [`CompressionOptions::zstd_compression_level`]: crate::analyzer::CompressionOptions::zstd_compression_level
+    /// [`CompressionOptions::zstd_strategy`]: crate::analyzer::CompressionOptions::zstd_strategy
+    #[default]
+    Zstd,
+    /// Brotli, in addition to zstd.
+    Brotli {
+        /// Compression quality, 0 (fastest) to 11 (strongest).
+        quality: u32,
+        /// Log2 of the window size, 10 to 24.
+        window: u32,
+    },
+    /// Deflate, as used by gzip and zlib, in addition to zstd.
+    /// Sizes are of the raw Deflate stream, without the gzip header and trailer
+    /// (see [`GZIP_OVERHEAD`]).
+    Deflate {
+        /// Compression level, 0 (store) to 9 (strongest).
+        level: u32,
+    },
+}
+
+impl Compressor {
+    /// Human readable name of the compressor, used as a label when printing.
+    pub fn name(self) -> &'static str {
+        match self {
+            Compressor::Zstd => "Zstd",
+            Compressor::Brotli { .. } => "Brotli",
+            Compressor::Deflate { .. } => "Deflate",
+        }
+    }
+}
+
+impl fmt::Display for Compressor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compressor::Zstd => f.write_str("zstd"),
+            Compressor::Brotli { quality, window } => write!(f, "brotli:{quality}:{window}"),
+            Compressor::Deflate { level } => write!(f, "deflate:{level}"),
+        }
+    }
+}
+
+/// Error parsing a [`Compressor`].
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error(
+    "Invalid compressor '{0}', expected 'zstd', 'brotli', 'brotli:<quality 0-11>:<window 10-24>', \
+    'deflate' or 'deflate:<level 0-9>'"
+)]
+pub struct ParseCompressorError(String);
+
+impl FromStr for Compressor {
+    type Err = ParseCompressorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseCompressorError(s.to_string());
+        let mut parts = s.split(':');
+        let name = parts.next().unwrap_or_default();
+        if name.eq_ignore_ascii_case("zstd") {
+            return match parts.next() {
+                None => Ok(Compressor::Zstd),
+                Some(_) => Err(error()),
+            };
+        }
+        if name.eq_ignore_ascii_case("deflate") || name.eq_ignore_ascii_case("gzip") {
+            let level = match (parts.next(), parts.next()) {
+                (None, _) => DEFAULT_DEFLATE_LEVEL,
+                (Some(level), None) => level.parse().map_err(|_| error())?,
+                _ => return Err(error()),
+            };
+            if level > 9 {
+                return Err(error());
+            }
+            return Ok(Compressor::Deflate { level });
+        }
+        if !name.eq_ignore_ascii_case("brotli") {
+            return Err(error());
+        }
+
+        let (quality, window) = match (parts.next(), parts.next(), parts.next()) {
+            (None, _, _) => (DEFAULT_BROTLI_QUALITY, DEFAULT_BROTLI_WINDOW),
+            (Some(quality), Some(window), None) => (
+                quality.parse().map_err(|_| error())?,
+                window.parse().map_err(|_| error())?,
+            ),
+            _ => return Err(error()),
+        };
+        if quality > 11 || !(10..=24).contains(&window) {
+            return Err(error());
+        }
+        Ok(Compressor::Brotli { quality, window })
+    }
+}
+
+/// Determines the size of the data when compressed with Brotli.
+///
+/// # Arguments
+///
+/// * `data` - The data to compress.
+/// * `quality` - Compression quality, 0 (fastest) to 11 (strongest).
+/// * `window` - Log2 of the window size, 10 to 24.
+pub fn get_brotli_compressed_size(data: &[u8], quality: u32, window: u32) -> u64 {
+    let params = BrotliEncoderParams {
+        quality: quality as i32,
+        lgwin: window as i32,
+        ..Default::default()
+    };
+
+    // Only the size is needed, so the output is counted rather than stored.
+    let mut output = CountingWriter(0);
+    brotli::BrotliCompress(&mut &data[..], &mut output, &params).unwrap();
+    output.0
+}
+
+/// Determines the size of the data when compressed with Deflate.
+///
+/// The encoder is the pure Rust `miniz_oxide` backend of `flate2`, so for a given level
+/// the size is the same on every platform.
+///
+/// # Arguments
+///
+/// * `data` - The data to compress.
+/// * `level` - Compression level, 0 (store) to 9 (strongest).
+pub fn get_deflate_compressed_size(data: &[u8], level: u32) -> u64 {
+    let mut encoder = DeflateEncoder::new(CountingWriter(0), Compression::new(level));
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap().0
+}
+
+/// A writer which discards its data, counting the number of bytes written.
+struct CountingWriter(u64);
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_entries, CompressionOptions};
+    use crate::comparison::{GroundTruth, GroupComparisonMetrics};
+    use crate::schema::Schema;
+
+    #[test]
+    fn parses_compressors() {
+        assert_eq!("zstd".parse(), Ok(Compressor::Zstd));
+        assert_eq!(
+            "Brotli".parse(),
+            Ok(Compressor::Brotli {
+                quality: DEFAULT_BROTLI_QUALITY,
+                window: DEFAULT_BROTLI_WINDOW
+            })
+        );
+        let custom = Compressor::Brotli {
+            quality: 5,
+            window: 18,
+        };
+        assert_eq!("brotli:5:18".parse(), Ok(custom));
+        assert_eq!(custom.to_string().parse(), Ok(custom));
+        assert_eq!(
+            "deflate".parse(),
+            Ok(Compressor::Deflate {
+                level: DEFAULT_DEFLATE_LEVEL
+            })
+        );
+        let deflate = Compressor::Deflate { level: 6 };
+        assert_eq!("GZIP:6".parse(), Ok(deflate));
+        assert_eq!(deflate.to_string().parse(), Ok(deflate));
+        for invalid in [
+            "lzma",
+            "zstd:3",
+            "brotli:5",
+            "brotli:12:18",
+            "brotli:5:9",
+            "deflate:10",
+            "deflate:6:15",
+        ] {
+            assert!(invalid.parse::<Compressor>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn brotli_size_matches_compressed_output() {
+        let data: Vec<u8> = (0..4096u32).map(|x| (x % 251) as u8).collect();
+        let size = get_brotli_compressed_size(&data, 11, 22);
+
+        let mut compressed = Vec::new();
+        let params = BrotliEncoderParams {
+            quality: 11,
+            lgwin: 22,
+            ..Default::default()
+        };
+        brotli::BrotliCompress(&mut &data[..], &mut compressed, &params).unwrap();
+        assert!(size > 0 && size < data.len() as u64);
+        assert_eq!(size, compressed.len() as u64);
+    }
+
+    #[test]
+    fn deflate_size_matches_compressed_output() {
+        let data: Vec<u8> = (0..4096u32).map(|x| (x % 251) as u8).collect();
+        let size = get_deflate_compressed_size(&data, 9);
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&data).unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert!(size > 0 && size < data.len() as u64);
+        assert_eq!(size, compressed.len() as u64);
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), Compression::best());
+        gzip.write_all(&data).unwrap();
+        assert_eq!(gzip.finish().unwrap().len() as u64, size + GZIP_OVERHEAD);
+    }
+
+    #[test]
+    fn brotli_size_is_measured_only_when_selected() {
+        let data = [1u8, 2, 3, 4].repeat(256);
+        let zstd =
+            GroupComparisonMetrics::from_bytes(&data, "zstd", &CompressionOptions::default());
+        assert_eq!(zstd.brotli_size, None);
+
+        let compressor = "brotli:9:20".parse().unwrap();
+        let options = CompressionOptions::default().with_compressor(compressor);
+        let brotli = GroupComparisonMetrics::from_bytes(&data, "brotli", &options);
+        assert_eq!(brotli.zstd_size, zstd.zstd_size);
+        assert_eq!(
+            brotli.brotli_size,
+            Some(get_brotli_compressed_size(&data, 9, 20))
+        );
+        assert_eq!(
+            brotli.actual_size(GroundTruth::Brotli),
+            brotli.brotli_size.unwrap()
+        );
+        assert_eq!(zstd.actual_size(GroundTruth::Brotli), zstd.zstd_size);
+        assert!(brotli.is_measured_by(GroundTruth::Brotli));
+        assert!(!zstd.is_measured_by(GroundTruth::Brotli));
+    }
+
+    #[test]
+    fn deflate_size_is_measured_only_when_selected() {
+        let data = [1u8, 2, 3, 4].repeat(256);
+        let zstd =
+            GroupComparisonMetrics::from_bytes(&data, "zstd", &CompressionOptions::default());
+        assert_eq!(zstd.deflate_size, None);
+
+        let options =
+            CompressionOptions::default().with_compressor(Compressor::Deflate { level: 6 });
+        let deflate = GroupComparisonMetrics::from_bytes(&data, "deflate", &options);
+        assert_eq!(deflate.brotli_size, None);
+        assert_eq!(
+            deflate.deflate_size,
+            Some(get_deflate_compressed_size(&data, 6))
+        );
+        assert_eq!(
+            deflate.actual_size(GroundTruth::Deflate),
+            deflate.deflate_size.unwrap()
+        );
+        assert_eq!(zstd.actual_size(GroundTruth::Deflate), zstd.zstd_size);
+        assert!(deflate.is_measured_by(GroundTruth::Deflate));
+        assert!(!deflate.is_measured_by(GroundTruth::Brotli));
+    }
+
+    #[test]
+    fn results_measure_the_ground_truth_of_their_compressor() {
+        let yaml = r###"
+version: '1.0'
+root:
+  type: group
+  fields:
+    a: 8
+    b: 8
+analysis:
+  split_groups:
+    - { name: a_b, group_1: [a, b], group_2: [b, a] }
+  compare_groups:
+    - name: only_a
+      baseline:
+        - { type: array, field: a }
+        - { type: array, field: b }
+      comparisons:
+        a:
+          - { type: array, field: a }
+"###;
+        let schema = Schema::from_yaml(yaml).unwrap();
+        let entries = (0..64u8).map(|x| [x, x / 4]);
+        let zstd = analyze_entries(&schema, CompressionOptions::default(), entries.clone());
+        let options =
+            CompressionOptions::default().with_compressor(Compressor::Deflate { level: 6 });
+        let deflate = analyze_entries(&schema, options, entries);
+
+        assert!(zstd.measures_ground_truth(GroundTruth::Zstd));
+        assert!(!zstd.measures_ground_truth(GroundTruth::Deflate));
+        assert!(deflate.measures_ground_truth(GroundTruth::Deflate));
+        assert!(!deflate.measures_ground_truth(GroundTruth::Brotli));
+    }
+}
@@ -0,0 +1,433 @@
+//! On-disk cache of the metrics of the groups of analyzed files, to speed up repeated analyses.
+//!
+//! Compressing every group is the slowest part of an analysis. When re-running an analysis while
+//! tuning a schema (e.g. the multipliers of the size estimator), most groups are unchanged; so
+//! [`GroupMetricsCache`] remembers the [`GroupComparisonMetrics`] of each group of each file,
+//! and [`GroupComparisonMetrics::from_bytes_cached`] consults it before compressing.
+//!
+//! Entries are keyed by a [`MetricsKey`]: the file, the analyzed region of it, a hash of the
+//! group's definition and the zstd level. The hash covers the layout of the schema the group is
+//! read with, the definition of the group itself (e.g. its fields) and the other compressor
+//! settings, so editing the relevant part of the schema results in a different key, and the stale
+//! entry is simply never looked up again. Entries computed before the file was last modified are
+//! recomputed. Entries which weren't used for [`MAX_UNUSED_RUNS`] runs are dropped when saving.
+//!
+//! The estimated sizes are always recomputed, as they are cheap, and depend on the multipliers.
+//!
+//! [`GroupComparisonMetrics`]: crate::comparison::GroupComparisonMetrics
+//! [`GroupComparisonMetrics::from_bytes_cached`]: crate::comparison::GroupComparisonMetrics::from_bytes_cached
+
+use crate::analyzer::CompressionOptions;
+use crate::comparison::GroupComparisonMetrics;
+use crate::schema::Schema;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Debug,
+    fs::{self, File},
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::SystemTime,
+};
+
+/// Name of the cache file within the cache directory.
+pub const CACHE_FILE_NAME: &str = "group-metrics.json";
+
+/// Entries which weren't used in this many runs are dropped when saving the cache.
+pub const MAX_UNUSED_RUNS: u64 = 10;
+
+/// Version of the format of the cache file; caches of other versions are discarded.
+const CACHE_VERSION: u32 = 1;
+
+/// Metrics of the groups of previously analyzed files, shared by all analyzed files.
+#[derive(Debug, Default)]
+pub struct GroupMetricsCache {
+    /// Number of this run, i.e. of times the cache was loaded.
+    run: u64,
+    /// Metrics of each group, without their estimated sizes.
+    entries: Mutex<FxHashMap<MetricsKey, CachedMetrics>>,
+    /// Number of metrics looked up which were cached.
+    hits: AtomicU64,
+    /// Number of metrics looked up which had to be computed.
+    misses: AtomicU64,
+}
+
+/// Identifies the metrics of a group of a file in the [`GroupMetricsCache`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MetricsKey {
+    /// Path of the analyzed file.
+    pub file: PathBuf,
+    /// Offset of the analyzed region of the file.
+    pub offset: u64,
+    /// Length of the analyzed region of the file.
+    pub length: u64,
+    /// Hash of the definition of the group, see [`CachedRegion::key`].
+    pub group_hash: u64,
+    /// The zstd compression level the group was compressed with.
+    pub zstd_level: i32,
+}
+
+/// Metrics of a group in the cache.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct CachedMetrics {
+    /// The metrics, without an estimated size.
+    metrics: GroupComparisonMetrics,
+    /// Modification time of the file when the metrics were computed.
+    file_modified: Option<SystemTime>,
+    /// The last run in which these metrics were used.
+    last_used_run: u64,
+}
+
+/// The contents of the cache file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    run: u64,
+    /// JSON only has string keys, so the entries are stored as a list.
+    entries: Vec<(MetricsKey, CachedMetrics)>,
+}
+
+impl GroupMetricsCache {
+    /// Creates an empty cache, which is only kept in memory unless [saved](Self::save).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the cache from the [`CACHE_FILE_NAME`] file in a directory.
+    ///
+    /// Returns an empty cache if the directory or file doesn't exist yet,
+    /// or the file is from an incompatible version.
+    ///
+    /// # Errors
+    /// If the file exists but can't be read.
+    pub fn load(dir: &Path) -> io::Result<Self> {
+        let path = cache_file_path(dir);
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(e),
+        };
+
+        // A corrupt or outdated cache is no worse than no cache.
+        let cache: CacheFile = serde_json::from_reader(BufReader::new(file)).unwrap_or_default();
+        if cache.version != CACHE_VERSION {
+            return Ok(Self::new());
+        }
+
+        Ok(Self {
+            run: cache.run + 1,
+            entries: Mutex::new(cache.entries.into_iter().collect()),
+            ..Self::default()
+        })
+    }
+
+    /// Saves the cache to the [`CACHE_FILE_NAME`] file in a directory, creating the directory
+    /// if needed. Entries which weren't used in the last [`MAX_UNUSED_RUNS`] runs are dropped.
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        let entries = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, entry)| entry.last_used_run + MAX_UNUSED_RUNS > self.run)
+            .map(|(key, entry)| (key.clone(), *entry))
+            .collect();
+
+        fs::create_dir_all(dir)?;
+        let writer = BufWriter::new(File::create(cache_file_path(dir))?);
+        let cache = CacheFile {
+            version: CACHE_VERSION,
+            run: self.run,
+            entries,
+        };
+        serde_json::to_writer(writer, &cache).map_err(io::Error::other)
+    }
+
+    /// Number of cached metrics.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Whether no metrics are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of metrics looked up which were cached.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of metrics looked up which had to be computed.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Returns the cached metrics for the key, or computes (and caches) them with `measure`.
+    /// Metrics computed before the file was last modified (`file_modified`) are recomputed.
+    pub(crate) fn get_or_compute(
+        &self,
+        key: MetricsKey,
+        file_modified: Option<SystemTime>,
+        measure: impl FnOnce() -> GroupComparisonMetrics,
+    ) -> GroupComparisonMetrics {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(&key) {
+            if entry.file_modified == file_modified {
+                entry.last_used_run = self.run;
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return entry.metrics;
+            }
+        }
+
+        // Compressed without holding the lock, so files are still compressed in parallel.
+        let metrics = measure();
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.entries.lock().unwrap().insert(
+            key,
+            CachedMetrics {
+                metrics,
+                file_modified,
+                last_used_run: self.run,
+            },
+        );
+        metrics
+    }
+}
+
+/// The analyzed region of a file, whose group metrics are looked up in a [`GroupMetricsCache`].
+/// Set with [`CompressionOptions::with_metrics_cache`].
+#[derive(Debug, Clone)]
+pub struct CachedRegion {
+    /// The cache shared by all analyzed files.
+    pub cache: Arc<GroupMetricsCache>,
+    /// Path of the analyzed file.
+    pub file: PathBuf,
+    /// Offset of the analyzed region of the file.
+    pub offset: u64,
+    /// Length of the analyzed region of the file.
+    pub length: u64,
+    /// Modification time of the file, if known.
+    pub file_modified: Option<SystemTime>,
+    /// Hash of the layout of the schema the file is analyzed with, see [`schema_layout_hash`].
+    pub schema_hash: u64,
+}
+
+impl CachedRegion {
+    /// The region of `length` bytes at `offset` of the file at `file`, analyzed with `schema`.
+    ///
+    /// # Errors
+    /// If the metadata of the file can't be read.
+    pub fn new(
+        cache: Arc<GroupMetricsCache>,
+        schema: &Schema,
+        file: impl Into<PathBuf>,
+        offset: u64,
+        length: u64,
+    ) -> io::Result<Self> {
+        let file = file.into();
+        Ok(Self {
+            cache,
+            file_modified: fs::metadata(&file)?.modified().ok(),
+            file,
+            offset,
+            length,
+            schema_hash: schema_layout_hash(schema),
+        })
+    }
+
+    /// Key of the metrics of a group of this region. The group hash combines the
+    /// [`Self::schema_hash`], the `group_definition` (e.g. the fields of the group) and the
+    /// settings of `options` which the metrics depend on, besides the zstd level.
+    ///
+    /// [`DefaultHasher`] is deterministic, but may change between Rust versions;
+    /// a different version only results in cache misses.
+    pub fn key(
+        &self,
+        group_definition: &(impl Debug + ?Sized),
+        options: &CompressionOptions,
+    ) -> MetricsKey {
+        let mut hasher = DefaultHasher::new();
+        self.schema_hash.hash(&mut hasher);
+        // Schema types don't implement `Hash`; their debug representation is just as unique.
+        format!("{group_definition:?}").hash(&mut hasher);
+        options.zstd_strategy.hash(&mut hasher);
+        options.zstd_calibration_level.hash(&mut hasher);
+        options.compressor.hash(&mut hasher);
+        options.zstd_dictionary.hash(&mut hasher);
+        options.measure_order1_entropy.hash(&mut hasher);
+
+        MetricsKey {
+            file: self.file.clone(),
+            offset: self.offset,
+            length: self.length,
+            group_hash: hasher.finish(),
+            zstd_level: options.zstd_compression_level,
+        }
+    }
+}
+
+/// Hash of the parts of a schema which determine how its groups are read from a file,
+/// i.e. everything but the metadata and the analysis settings.
+pub fn schema_layout_hash(schema: &Schema) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!(
+        "{:?} {:?} {:?} {:?} {:?} {:?}",
+        schema.bit_order,
+        schema.byte_order,
+        schema.conditional_offsets,
+        schema.continuous,
+        schema.element_size,
+        schema.root
+    )
+    .hash(&mut hasher);
+    hasher.finish()
+}
+
+fn cache_file_path(dir: &Path) -> PathBuf {
+    dir.join(CACHE_FILE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const SCHEMA: &str = r#"
+version: '1.0'
+root:
+  type: group
+  fields:
+    a: 8
+    b: 8
+"#;
+
+    /// A cached region of a file in `dir`, with its metrics cache loaded from `dir`.
+    fn cached_region(dir: &Path) -> CachedRegion {
+        let file = dir.join("data.bin");
+        if !file.exists() {
+            let data: Vec<u8> = (0..4096u32).map(|x| (x % 7) as u8).collect();
+            File::create(&file).unwrap().write_all(&data).unwrap();
+        }
+        let schema = Schema::from_yaml(SCHEMA).unwrap();
+        let cache = Arc::new(GroupMetricsCache::load(dir).unwrap());
+        CachedRegion::new(cache, &schema, file, 0, 4096).unwrap()
+    }
+
+    fn options(region: &CachedRegion) -> CompressionOptions {
+        CompressionOptions::default().with_metrics_cache(region.clone())
+    }
+
+    #[test]
+    fn metrics_are_cached_across_runs() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let data: Vec<u8> = (0..4096u32).map(|x| (x % 7) as u8).collect();
+        let fields = vec!["a".to_string()];
+
+        let region = cached_region(dir);
+        let metrics =
+            GroupComparisonMetrics::from_bytes_cached(&data, "a", &fields, &options(&region));
+        let cached =
+            GroupComparisonMetrics::from_bytes_cached(&data, "a", &fields, &options(&region));
+        assert_eq!(cached, metrics);
+        assert_eq!(
+            metrics,
+            GroupComparisonMetrics::from_bytes(&data, "a", &CompressionOptions::default())
+        );
+        assert_eq!((region.cache.hits(), region.cache.misses()), (1, 1));
+        region.cache.save(dir).unwrap();
+
+        // Another group or level is a different key.
+        let region = cached_region(dir);
+        let options = options(&region);
+        GroupComparisonMetrics::from_bytes_cached(&data, "a", &fields, &options);
+        GroupComparisonMetrics::from_bytes_cached(&data, "b", &vec!["b".to_string()], &options);
+        let options = options.with_zstd_compression_level(1);
+        GroupComparisonMetrics::from_bytes_cached(&data, "a", &fields, &options);
+        assert_eq!((region.cache.hits(), region.cache.misses()), (1, 2));
+        assert_eq!(region.cache.len(), 3);
+    }
+
+    #[test]
+    fn estimates_are_recomputed_on_hits() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..4096u32).map(|x| (x % 7) as u8).collect();
+        let fields = vec!["a".to_string()];
+
+        let region = cached_region(temp_dir.path());
+        GroupComparisonMetrics::from_bytes_cached(&data, "a", &fields, &options(&region));
+        let options = CompressionOptions {
+            lz_match_multiplier: 0.5,
+            entropy_multiplier: 1.5,
+            ..options(&region)
+        };
+        let cached = GroupComparisonMetrics::from_bytes_cached(&data, "a", &fields, &options);
+        assert_eq!(region.cache.hits(), 1);
+
+        let uncached = GroupComparisonMetrics::from_bytes(&data, "a", &options);
+        assert_ne!(uncached.estimated_size, 0);
+        assert_eq!(cached, uncached);
+    }
+
+    #[test]
+    fn schema_and_file_changes_miss() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let data: Vec<u8> = (0..4096u32).map(|x| (x % 7) as u8).collect();
+        let fields = vec!["a".to_string()];
+
+        let region = cached_region(dir);
+        GroupComparisonMetrics::from_bytes_cached(&data, "a", &fields, &options(&region));
+
+        // Field `a` is now read differently, so its group is too.
+        let schema = Schema::from_yaml(&SCHEMA.replace("a: 8", "a: 4\n    pad: 4")).unwrap();
+        let resized = CachedRegion {
+            schema_hash: schema_layout_hash(&schema),
+            ..region.clone()
+        };
+        GroupComparisonMetrics::from_bytes_cached(&data, "a", &fields, &options(&resized));
+
+        let modified = CachedRegion {
+            file_modified: Some(SystemTime::UNIX_EPOCH),
+            ..region.clone()
+        };
+        GroupComparisonMetrics::from_bytes_cached(&data, "a", &fields, &options(&modified));
+        assert_eq!((region.cache.hits(), region.cache.misses()), (0, 3));
+    }
+
+    #[test]
+    fn timed_groups_bypass_the_cache() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data = [1u8; 64];
+
+        let region = cached_region(temp_dir.path());
+        let options = options(&region).with_zstd_timing(true);
+        let metrics = GroupComparisonMetrics::from_bytes_cached(&data, "a", &"a", &options);
+        assert_ne!(metrics.zstd_compress_ns, 0);
+        assert!(region.cache.is_empty());
+    }
+
+    #[test]
+    fn unused_entries_are_dropped_after_max_unused_runs() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let region = cached_region(dir);
+        let key = region.key(&"a", &CompressionOptions::default());
+        region
+            .cache
+            .get_or_compute(key, None, GroupComparisonMetrics::default);
+        region.cache.save(dir).unwrap();
+
+        for _ in 0..MAX_UNUSED_RUNS {
+            let cache = GroupMetricsCache::load(dir).unwrap();
+            assert_eq!(cache.len(), 1);
+            cache.save(dir).unwrap();
+        }
+        assert!(GroupMetricsCache::load(dir).unwrap().is_empty());
+    }
+}
@@ -1,3 +1,7 @@
 pub mod analyze_utils;
 pub mod bitstream_ext;
+pub mod compressor;
 pub mod constants;
+pub mod metrics_cache;
+pub mod size_estimate_expression;
+pub mod zstd_dictionary;
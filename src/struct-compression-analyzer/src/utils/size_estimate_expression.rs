@@ -0,0 +1,396 @@
+//! Minimal arithmetic expression evaluator for user provided size estimation formulas.
+//!
+//! This allows schema authors to tune the estimation formula without recompiling,
+//! e.g. `size_estimate: "entropy * len / 8 - lz * 0.3"`.
+//!
+//! Only numbers, the variables listed below, parentheses, unary minus and the
+//! `+`, `-`, `*`, `/` operators are supported; so evaluating untrusted schemas is safe.
+//! Expressions nested (or chained) deeper than [`MAX_EXPRESSION_DEPTH`] levels are rejected,
+//! so hostile input can't overflow the stack while parsing or evaluating.
+//!
+//! # Variables
+//!
+//! Each variable is sourced from [`SizeEstimationParameters`]:
+//!
+//! - `len` / `data_len`: Length of the raw bytes of the data
+//! - `lz` / `num_lz_matches`: Number of LZ matches found in the data
+//! - `entropy`: Estimated entropy of the data (bits per byte)
+//! - `lz_mult` / `lz_match_multiplier`: LZ match multiplier
+//! - `entropy_mult` / `entropy_multiplier`: Entropy multiplier
+//!
+//! [`SizeEstimationParameters`]: crate::analyzer::SizeEstimationParameters
+
+use crate::analyzer::SizeEstimationParameters;
+use std::fmt;
+use thiserror::Error;
+
+/// Maximum nesting depth of a [`SizeEstimateExpression`].
+///
+/// Each parenthesis, unary minus and binary operator counts as one level.
+pub const MAX_EXPRESSION_DEPTH: usize = 64;
+
+/// A parsed size estimation formula, evaluated against [`SizeEstimationParameters`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeEstimateExpression {
+    source: String,
+    root: Node,
+}
+
+/// Errors that can occur when parsing a [`SizeEstimateExpression`].
+#[derive(Debug, Error, PartialEq)]
+pub enum ExpressionError {
+    #[error("Unexpected character '{0}' at position {1}")]
+    UnexpectedCharacter(char, usize),
+
+    #[error("Unknown variable '{0}'. Expected one of: len, data_len, lz, num_lz_matches, entropy, lz_mult, lz_match_multiplier, entropy_mult, entropy_multiplier")]
+    UnknownVariable(String),
+
+    #[error("Invalid number '{0}'")]
+    InvalidNumber(String),
+
+    #[error("Unexpected end of expression")]
+    UnexpectedEnd,
+
+    #[error("Unexpected trailing input at position {0}")]
+    TrailingInput(usize),
+
+    #[error("Expression is nested more than {0} levels deep")]
+    TooDeep(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Variable {
+    DataLen,
+    NumLzMatches,
+    Entropy,
+    LzMatchMultiplier,
+    EntropyMultiplier,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Number(f64),
+    Variable(Variable),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+}
+
+impl SizeEstimateExpression {
+    /// Parses an expression from a string.
+    ///
+    /// # Arguments
+    /// * `source` - The formula, e.g. `entropy * len / 8 - lz * 0.3`
+    pub fn parse(source: &str) -> Result<Self, ExpressionError> {
+        let mut parser = Parser {
+            input: source.as_bytes(),
+            pos: 0,
+            depth: 0,
+        };
+        let root = parser.parse_sum()?;
+        parser.skip_whitespace();
+        if parser.pos != parser.input.len() {
+            return Err(ExpressionError::TrailingInput(parser.pos));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+
+    /// The original text the expression was parsed from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluates the raw (unrounded) value of the expression.
+    pub fn evaluate(&self, params: &SizeEstimationParameters) -> f64 {
+        self.root.evaluate(params)
+    }
+
+    /// Evaluates the expression as a size in bytes.
+    /// Negative and non-finite results are clamped to 0.
+    pub fn estimate_size(&self, params: &SizeEstimationParameters) -> usize {
+        let value = self.evaluate(params);
+        if value.is_finite() && value > 0.0 {
+            value.ceil() as usize
+        } else {
+            0
+        }
+    }
+}
+
+impl fmt::Display for SizeEstimateExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SizeEstimateExpression {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let source = String::deserialize(deserializer)?;
+        SizeEstimateExpression::parse(&source).map_err(|e| {
+            serde::de::Error::custom(format!("Invalid size estimate '{}': {}", source, e))
+        })
+    }
+}
+
+impl Node {
+    fn evaluate(&self, params: &SizeEstimationParameters) -> f64 {
+        match self {
+            Node::Number(value) => *value,
+            Node::Variable(var) => match var {
+                Variable::DataLen => params.data_len as f64,
+                Variable::NumLzMatches => params.num_lz_matches as f64,
+                Variable::Entropy => params.entropy,
+                Variable::LzMatchMultiplier => params.lz_match_multiplier,
+                Variable::EntropyMultiplier => params.entropy_multiplier,
+            },
+            Node::Negate(inner) => -inner.evaluate(params),
+            Node::Binary(op, lhs, rhs) => {
+                let lhs = lhs.evaluate(params);
+                let rhs = rhs.evaluate(params);
+                match op {
+                    Operator::Add => lhs + rhs,
+                    Operator::Sub => lhs - rhs,
+                    Operator::Mul => lhs * rhs,
+                    Operator::Div => lhs / rhs,
+                }
+            }
+        }
+    }
+}
+
+/// Recursive descent parser.
+///
+/// ```text
+/// sum     := product (('+' | '-') product)*
+/// product := unary (('*' | '/') unary)*
+/// unary   := '-' unary | primary
+/// primary := number | variable | '(' sum ')'
+/// ```
+///
+/// `depth` bounds both the recursion of the parser and the height of the
+/// resulting tree, which [`Node::evaluate`] recurses over.
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.input.len() && self.input[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.input.get(self.pos).copied()
+    }
+
+    /// Enters one more level of nesting, failing if [`MAX_EXPRESSION_DEPTH`] is exceeded.
+    fn descend(&mut self) -> Result<(), ExpressionError> {
+        self.depth += 1;
+        if self.depth > MAX_EXPRESSION_DEPTH {
+            return Err(ExpressionError::TooDeep(MAX_EXPRESSION_DEPTH));
+        }
+        Ok(())
+    }
+
+    fn parse_sum(&mut self) -> Result<Node, ExpressionError> {
+        // Chained operators build a left-deep tree, so each one is a level of nesting.
+        let depth = self.depth;
+        let mut node = self.parse_product()?;
+        while let Some(c @ (b'+' | b'-')) = self.peek() {
+            self.pos += 1;
+            self.descend()?;
+            let op = if c == b'+' {
+                Operator::Add
+            } else {
+                Operator::Sub
+            };
+            node = Node::Binary(op, Box::new(node), Box::new(self.parse_product()?));
+        }
+        self.depth = depth;
+        Ok(node)
+    }
+
+    fn parse_product(&mut self) -> Result<Node, ExpressionError> {
+        let depth = self.depth;
+        let mut node = self.parse_unary()?;
+        while let Some(c @ (b'*' | b'/')) = self.peek() {
+            self.pos += 1;
+            self.descend()?;
+            let op = if c == b'*' {
+                Operator::Mul
+            } else {
+                Operator::Div
+            };
+            node = Node::Binary(op, Box::new(node), Box::new(self.parse_unary()?));
+        }
+        self.depth = depth;
+        Ok(node)
+    }
+
+    fn parse_unary(&mut self) -> Result<Node, ExpressionError> {
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+            self.descend()?;
+            let node = Node::Negate(Box::new(self.parse_unary()?));
+            self.depth -= 1;
+            return Ok(node);
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Node, ExpressionError> {
+        match self.peek() {
+            None => Err(ExpressionError::UnexpectedEnd),
+            Some(b'(') => {
+                self.pos += 1;
+                self.descend()?;
+                let node = self.parse_sum()?;
+                self.depth -= 1;
+                match self.peek() {
+                    Some(b')') => {
+                        self.pos += 1;
+                        Ok(node)
+                    }
+                    Some(c) => Err(ExpressionError::UnexpectedCharacter(c as char, self.pos)),
+                    None => Err(ExpressionError::UnexpectedEnd),
+                }
+            }
+            Some(c) if c.is_ascii_digit() || c == b'.' => {
+                let token = self.take_while(|c| c.is_ascii_digit() || c == b'.');
+                token
+                    .parse::<f64>()
+                    .map(Node::Number)
+                    .map_err(|_| ExpressionError::InvalidNumber(token.to_string()))
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == b'_' => {
+                let token = self.take_while(|c| c.is_ascii_alphanumeric() || c == b'_');
+                let var = match token {
+                    "len" | "data_len" => Variable::DataLen,
+                    "lz" | "num_lz_matches" => Variable::NumLzMatches,
+                    "entropy" => Variable::Entropy,
+                    "lz_mult" | "lz_match_multiplier" => Variable::LzMatchMultiplier,
+                    "entropy_mult" | "entropy_multiplier" => Variable::EntropyMultiplier,
+                    _ => return Err(ExpressionError::UnknownVariable(token.to_string())),
+                };
+                Ok(Node::Variable(var))
+            }
+            Some(c) => Err(ExpressionError::UnexpectedCharacter(c as char, self.pos)),
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(u8) -> bool) -> &str {
+        let start = self.pos;
+        while self.pos < self.input.len() && predicate(self.input[self.pos]) {
+            self.pos += 1;
+        }
+
+        // Only ASCII is consumed, so this is always on a char boundary.
+        std::str::from_utf8(&self.input[start..self.pos]).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> SizeEstimationParameters<'static> {
+        SizeEstimationParameters {
+            name: "test",
+            data_len: 1000,
+            data: None,
+            num_lz_matches: 100,
+            entropy: 4.0,
+            lz_match_multiplier: 0.5,
+            entropy_multiplier: 1.5,
+        }
+    }
+
+    #[test]
+    fn evaluates_with_precedence() {
+        let expr = SizeEstimateExpression::parse("entropy*len/8 + lz*0.3").unwrap();
+        assert_eq!(expr.evaluate(&params()), 4.0 * 1000.0 / 8.0 + 100.0 * 0.3);
+    }
+
+    #[test]
+    fn supports_parentheses_unary_minus_and_aliases() {
+        let expr = SizeEstimateExpression::parse(
+            "(data_len - num_lz_matches * lz_mult) * entropy * entropy_mult / 8",
+        )
+        .unwrap();
+        assert_eq!(expr.evaluate(&params()), (1000.0 - 50.0) * 4.0 * 1.5 / 8.0);
+
+        let expr = SizeEstimateExpression::parse("-(-len)").unwrap();
+        assert_eq!(expr.evaluate(&params()), 1000.0);
+    }
+
+    #[test]
+    fn clamps_negative_estimates_to_zero() {
+        let expr = SizeEstimateExpression::parse("len - lz * 100").unwrap();
+        assert_eq!(expr.estimate_size(&params()), 0);
+
+        let expr = SizeEstimateExpression::parse("len / 3").unwrap();
+        assert_eq!(expr.estimate_size(&params()), 334);
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        assert_eq!(
+            SizeEstimateExpression::parse("len * size"),
+            Err(ExpressionError::UnknownVariable("size".into()))
+        );
+        assert_eq!(
+            SizeEstimateExpression::parse("len *"),
+            Err(ExpressionError::UnexpectedEnd)
+        );
+        assert_eq!(
+            SizeEstimateExpression::parse("(len"),
+            Err(ExpressionError::UnexpectedEnd)
+        );
+        assert_eq!(
+            SizeEstimateExpression::parse("len lz"),
+            Err(ExpressionError::TrailingInput(4))
+        );
+        assert_eq!(
+            SizeEstimateExpression::parse("len % 2"),
+            Err(ExpressionError::TrailingInput(4))
+        );
+        assert_eq!(
+            SizeEstimateExpression::parse("1.2.3"),
+            Err(ExpressionError::InvalidNumber("1.2.3".into()))
+        );
+    }
+
+    #[test]
+    fn rejects_deeply_nested_expressions() {
+        let too_deep = Err(ExpressionError::TooDeep(MAX_EXPRESSION_DEPTH));
+        let nested = |levels: usize| format!("{}len{}", "(".repeat(levels), ")".repeat(levels));
+        let chained = |terms: usize| vec!["len"; terms].join(" + ");
+
+        assert!(SizeEstimateExpression::parse(&nested(MAX_EXPRESSION_DEPTH)).is_ok());
+        assert!(SizeEstimateExpression::parse(&chained(MAX_EXPRESSION_DEPTH + 1)).is_ok());
+        assert_eq!(SizeEstimateExpression::parse(&nested(100_000)), too_deep);
+        assert_eq!(
+            SizeEstimateExpression::parse(&format!("{}len", "-".repeat(100_000))),
+            too_deep
+        );
+        assert_eq!(SizeEstimateExpression::parse(&chained(100_000)), too_deep);
+    }
+}
@@ -0,0 +1,178 @@
+//! Training of a zstd dictionary shared by all analyzed files.
+//!
+//! Each group is compressed on its own, so redundancy shared between files (e.g. common
+//! palettes or headers) doesn't show in [`GroupComparisonMetrics::zstd_size`]. A dictionary
+//! trained on samples from across the dataset captures some of that redundancy, the same way
+//! it would when shipping many small files compressed with a shared dictionary.
+//!
+//! The flow is:
+//!
+//! 1. Collect [`ZstdDictionarySamples`] from analyzers which have ingested a file each.
+//! 2. [`ZstdDictionarySamples::train`] a dictionary.
+//! 3. Analyze the files with [`CompressionOptions::with_zstd_dictionary`], which additionally
+//!    measures [`GroupComparisonMetrics::zstd_dict_size`].
+//!
+//! [`GroupComparisonMetrics::zstd_size`]: crate::comparison::GroupComparisonMetrics::zstd_size
+//! [`GroupComparisonMetrics::zstd_dict_size`]: crate::comparison::GroupComparisonMetrics::zstd_dict_size
+//! [`CompressionOptions::with_zstd_dictionary`]: crate::analyzer::CompressionOptions::with_zstd_dictionary
+
+use super::analyze_utils::{get_writer_buffer, ZstdStrategy};
+use crate::analyzer::SchemaAnalyzer;
+use std::io;
+use thiserror::Error;
+
+/// Default maximum size of a trained dictionary, the default of the zstd command line tool.
+pub const DEFAULT_ZSTD_DICTIONARY_SIZE: usize = 112_640;
+
+/// Minimum number of samples needed to train a dictionary.
+/// zstd can't find common content in fewer samples than this.
+pub const MIN_ZSTD_DICTIONARY_SAMPLES: usize = 16;
+
+/// Maximum size of a single sample, in bytes.
+/// Only the start of the bytes of each field is sampled, so every file contributes.
+const MAX_SAMPLE_SIZE: usize = 16 * 1024;
+
+/// Errors that can occur when training a zstd dictionary.
+#[derive(Debug, Error)]
+pub enum TrainZstdDictionaryError {
+    #[error(
+        "Too few samples to train a zstd dictionary: {found} (at least {required} are needed)"
+    )]
+    TooFewSamples { found: usize, required: usize },
+
+    #[error("zstd failed to train a dictionary: {0}")]
+    Zstd(#[from] io::Error),
+}
+
+/// Samples of the bytes of the fields and groups of (a subset of) the analyzed files.
+#[derive(Debug, Clone, Default)]
+pub struct ZstdDictionarySamples {
+    /// The bytes of each sample.
+    samples: Vec<Vec<u8>>,
+}
+
+impl ZstdDictionarySamples {
+    /// Creates an empty set of samples.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sample of the bytes of each field and group of an analyzer
+    /// which has ingested a whole file.
+    ///
+    /// # Arguments
+    /// * `analyzer` - The analyzer to copy the bytes out of.
+    pub fn add_analyzer(&mut self, analyzer: &mut SchemaAnalyzer) {
+        // Sorted, so the trained dictionary doesn't depend on the order of a hash map.
+        let mut states: Vec<_> = analyzer.field_states.values_mut().collect();
+        states.sort_by(|a, b| a.full_path.cmp(&b.full_path));
+        for state in states {
+            let bytes = get_writer_buffer(&mut state.writer);
+            if !bytes.is_empty() {
+                self.samples
+                    .push(bytes[..bytes.len().min(MAX_SAMPLE_SIZE)].to_vec());
+            }
+        }
+    }
+
+    /// Adds the samples of another set, e.g. collected from another file.
+    pub fn extend(&mut self, other: Self) {
+        self.samples.extend(other.samples);
+    }
+
+    /// Number of samples collected.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Whether no samples were collected.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Trains a dictionary of up to `max_size` bytes from the samples.
+    ///
+    /// # Errors
+    /// [`TrainZstdDictionaryError::TooFewSamples`] if there are fewer than
+    /// [`MIN_ZSTD_DICTIONARY_SAMPLES`] samples, or an error from zstd, e.g. if the samples
+    /// are too small in total.
+    pub fn train(&self, max_size: usize) -> Result<Vec<u8>, TrainZstdDictionaryError> {
+        if self.samples.len() < MIN_ZSTD_DICTIONARY_SAMPLES {
+            return Err(TrainZstdDictionaryError::TooFewSamples {
+                found: self.samples.len(),
+                required: MIN_ZSTD_DICTIONARY_SAMPLES,
+            });
+        }
+
+        Ok(zstd::dict::from_samples(&self.samples, max_size)?)
+    }
+}
+
+/// Determines the size of the data when compressed with the given dictionary,
+/// level and strategy.
+pub fn get_zstd_dict_compressed_size(
+    data: &[u8],
+    dictionary: &[u8],
+    level: i32,
+    strategy: ZstdStrategy,
+) -> u64 {
+    let mut compressor = zstd::bulk::Compressor::with_dictionary(level, dictionary).unwrap();
+    if let Some(strategy) = strategy.to_zstd() {
+        compressor
+            .set_parameter(zstd::zstd_safe::CParameter::Strategy(strategy))
+            .unwrap();
+    }
+    compressor
+        .compress(data)
+        .ok()
+        .map(|compressed| compressed.len())
+        .unwrap() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{analyzer::CompressionOptions, comparison::GroupComparisonMetrics, schema::Schema};
+
+    #[test]
+    fn too_few_samples_are_reported() {
+        let schema =
+            Schema::from_yaml("version: '1.0'\nroot: { type: group, fields: { a: 8, b: 8 } }\n")
+                .unwrap();
+        let mut analyzer = SchemaAnalyzer::new(&schema, CompressionOptions::default());
+        analyzer.add_entry(&[1, 2]).unwrap();
+
+        // One sample for each of 'a' and 'b'.
+        let mut samples = ZstdDictionarySamples::new();
+        samples.add_analyzer(&mut analyzer);
+        assert_eq!(samples.len(), 2);
+        assert!(matches!(
+            samples.train(DEFAULT_ZSTD_DICTIONARY_SIZE),
+            Err(TrainZstdDictionaryError::TooFewSamples {
+                found: 2,
+                required: MIN_ZSTD_DICTIONARY_SAMPLES
+            })
+        ));
+    }
+
+    #[test]
+    fn dictionary_size_is_measured_only_when_trained() {
+        // Samples sharing a common 'header', which the dictionary can capture.
+        let header: Vec<u8> = (0..512u32).map(|x| (x * 31 % 251) as u8).collect();
+        let samples = ZstdDictionarySamples {
+            samples: (0..64u8)
+                .map(|x| [header.as_slice(), &[x; 64]].concat())
+                .collect(),
+        };
+        let dictionary = samples.train(4096).unwrap();
+
+        let data = [header.as_slice(), &[200; 64]].concat();
+        let plain = GroupComparisonMetrics::from_bytes(&data, "", &CompressionOptions::default());
+        assert_eq!(plain.zstd_dict_size, None);
+
+        let options = CompressionOptions::default().with_zstd_dictionary(dictionary);
+        let with_dict = GroupComparisonMetrics::from_bytes(&data, "", &options);
+        assert_eq!(with_dict.zstd_size, plain.zstd_size);
+        assert!(with_dict.zstd_dict_size.unwrap() < plain.zstd_size);
+    }
+}